@@ -1,14 +1,17 @@
 use {
     crate::{
         decoration::{Decoration, DecorationType},
+        inlays::{BlockInlay, InlineInlay},
         layout::{BlockElement, WrappedElement},
+        search::SearchQuery,
         selection::Affinity,
         session::{SelectionMode, Session},
         settings::Settings,
         str::StrExt,
         text::Length,
-        text::Position,
+        text::{Change, Position},
         token::TokenKind,
+        widgets::{BlockWidget, InlineWidget},
         Line, Selection, Token,
     },
     makepad_widgets::*,
@@ -18,7 +21,9 @@ use {
 
 live_design! {
     import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
     import makepad_widgets::theme_desktop_dark::*;
+    import makepad_widgets::theme::*;
 
     TokenColors = {{TokenColors}} {
         unknown: #C0C0C0,
@@ -38,6 +43,9 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        search_match: #8a6d3b80,
+        widget_bg: #3a3a3a,
+        widget_selection: #264f78,
     }
 
     DrawIndentGuide = {{DrawIndentGuide}} {
@@ -146,9 +154,23 @@ live_design! {
         draw_decoration: {
           //  draw_depth: 2.0,
         }
+        draw_search_match: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 1.5);
+                return sdf.fill(self.color);
+            }
+        }
         draw_selection: {
            // draw_depth: 3.0,
         }
+        draw_widget_bg: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.box(0., 0., self.rect_size.x, self.rect_size.y, 1.);
+                return sdf.fill(self.color);
+            }
+        }
 
         draw_cursor: {
           //  draw_depth: 4.0,
@@ -170,6 +192,35 @@ live_design! {
             }
         }
 
+        find_bar: <RoundedView> {
+            width: Fit,
+            height: Fit,
+            flow: Down,
+            padding: 4,
+            spacing: 2,
+            cursor: Default,
+            draw_bg: {color: #3a3a3a, radius: 3.0}
+            <View> {
+                width: Fit,
+                height: Fit,
+                align: {y: 0.5}
+                find_input = <TextInput> {width: 220, empty_message: "Find", on_focus_select_all: true}
+                find_regex = <CheckBox> {text: ".*"}
+                find_case = <CheckBox> {text: "Aa"}
+                find_word = <CheckBox> {text: "W"}
+                find_status = <Label> {width: Fit, padding: {left: 4, right: 4}, draw_text: {color: #9}, text: ""}
+            }
+            replace_row = <View> {
+                visible: false,
+                width: Fit,
+                height: Fit,
+                align: {y: 0.5}
+                replace_input = <TextInput> {width: 220, empty_message: "Replace", on_focus_select_all: true}
+                replace = <Button> {text: "Replace"}
+                replace_all = <Button> {text: "All"}
+            }
+        }
+
         animator: {
             blink = {
                 default: off
@@ -240,6 +291,8 @@ pub struct CodeEditor {
     #[live]
     draw_decoration: DrawDecoration,
     #[live]
+    draw_search_match: DrawColor,
+    #[live]
     draw_selection: DrawSelection,
     #[live]
     draw_cursor: DrawColor,
@@ -247,6 +300,8 @@ pub struct CodeEditor {
     draw_cursor_bg: DrawColor,
     #[live]
     draw_bg: DrawColor,
+    #[live]
+    draw_widget_bg: DrawColor,
     #[rust(KeepCursorInView::Off)]
     keep_cursor_in_view: KeepCursorInView,
     #[rust]
@@ -271,11 +326,64 @@ pub struct CodeEditor {
     #[live(0.5)]
     blink_speed: f64,
 
+    #[live]
+    find_bar: WidgetRef,
+    #[rust]
+    is_find_bar_open: bool,
+    // where the selection was when the find bar opened, incremental search starts from here
+    #[rust]
+    find_origin: Position,
+    #[rust]
+    find_error: Option<String>,
+
     #[animator]
     animator: Animator,
 
     #[rust]
     blink_timer: Timer,
+
+    #[live(0.5)]
+    hover_delay: f64,
+    #[rust]
+    hover_timer: Timer,
+    // where the mouse rests while the hover timer runs
+    #[rust]
+    hover_abs: Option<DVec2>,
+    #[rust]
+    hover: Option<Vec<String>>,
+    #[rust]
+    completion: Option<Completion>,
+}
+
+// the code editor draws the widgets it puts in inlays itself, and tells them apart by these ids
+const HOVER_WIDGET_ID: usize = 1;
+const COMPLETION_WIDGET_ID: usize = 2;
+
+const MAX_VISIBLE_COMPLETION_COUNT: usize = 10;
+
+/// An entry of the list shown by [`CodeEditor::show_completions`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub detail: String,
+    /// Replaces the part of the word before the cursor when the item is accepted.
+    pub insert_text: String,
+}
+
+struct Completion {
+    // where the word being completed starts
+    start: Position,
+    items: Vec<CompletionItem>,
+    selected_index: usize,
+}
+
+// a row of text in a widget, in cells relative to the viewport
+struct WidgetRow {
+    pos: DVec2,
+    width: f64,
+    height: f64,
+    text: String,
+    is_selected: bool,
 }
 
 enum KeepCursorInView {
@@ -485,11 +593,19 @@ impl CodeEditor {
         self.draw_bg.draw_abs(cx, cx.turtle().unscrolled_rect());
 
         self.draw_gutter(cx, session);
+        self.draw_search_match_layer(cx, session);
         self.draw_selection_layer(cx, session);
+        // widget backgrounds go below the text, their text is drawn with the code
+        let widget_rows = self.widget_rows(session);
+        self.draw_widget_bg_layer(cx, &widget_rows);
         self.draw_text_layer(cx, session);
+        self.draw_widget_text_layer(cx, &widget_rows);
         self.draw_indent_guide_layer(cx, session);
         self.draw_decoration_layer(cx, session);
         self.draw_selection_layer(cx, session);
+        if self.is_find_bar_open {
+            self.draw_find_bar(cx, session);
+        }
 
         // Get the last added selection.
         // Get the normalized cursor position. To go from normalized to screen position, multiply by
@@ -552,6 +668,189 @@ impl CodeEditor {
         }
     }
 
+    /// Shows the text of a hover for the given position. Text that fits on a single line is shown
+    /// after the end of the line, longer text below the line.
+    pub fn show_hover(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        position: Position,
+        text: &str,
+    ) {
+        self.hide_hover(cx, session);
+        let lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        let line_len = match session
+            .document()
+            .as_text()
+            .as_lines()
+            .get(position.line_index)
+        {
+            Some(line) => line.len(),
+            None => return,
+        };
+        if lines.is_empty() {
+            return;
+        }
+        if lines.len() == 1 {
+            session.add_inline_inlay(
+                Position {
+                    line_index: position.line_index,
+                    byte_index: line_len,
+                },
+                InlineInlay::Widget(InlineWidget {
+                    id: HOVER_WIDGET_ID,
+                    column_count: lines[0].column_count() + 3,
+                }),
+            );
+        } else {
+            session.add_block_inlay(
+                position.line_index + 1,
+                BlockInlay::Widget(BlockWidget {
+                    id: HOVER_WIDGET_ID,
+                    height: lines.len() as f64,
+                }),
+            );
+        }
+        self.hover = Some(lines);
+        self.redraw(cx);
+    }
+
+    pub fn hide_hover(&mut self, cx: &mut Cx, session: &mut Session) {
+        if self.hover.take().is_some() {
+            session.remove_widget_inlays(HOVER_WIDGET_ID);
+            self.redraw(cx);
+        }
+    }
+
+    /// Shows a list of completions below the cursor. The user picks one with the arrow keys and
+    /// accepts it with enter or tab.
+    pub fn show_completions(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        items: Vec<CompletionItem>,
+    ) {
+        self.hide_completions(cx, session);
+        if items.is_empty() {
+            return;
+        }
+        let cursor = self.last_cursor_position(session);
+        let start = Position {
+            line_index: cursor.line_index,
+            byte_index: word_start(
+                &session.document().as_text().as_lines()[cursor.line_index],
+                cursor.byte_index,
+            ),
+        };
+        session.add_block_inlay(
+            cursor.line_index + 1,
+            BlockInlay::Widget(BlockWidget {
+                id: COMPLETION_WIDGET_ID,
+                height: items.len().min(MAX_VISIBLE_COMPLETION_COUNT) as f64,
+            }),
+        );
+        self.completion = Some(Completion {
+            start,
+            items,
+            selected_index: 0,
+        });
+        self.redraw(cx);
+    }
+
+    pub fn hide_completions(&mut self, cx: &mut Cx, session: &mut Session) {
+        if self.completion.take().is_some() {
+            session.remove_widget_inlays(COMPLETION_WIDGET_ID);
+            self.redraw(cx);
+        }
+    }
+
+    fn accept_completion(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        session.remove_widget_inlays(COMPLETION_WIDGET_ID);
+        let cursor = self.last_cursor_position(session);
+        // the cursor moved away from the word since the list was shown
+        if cursor.line_index != completion.start.line_index
+            || cursor.byte_index < completion.start.byte_index
+        {
+            self.redraw(cx);
+            return;
+        }
+        let mut changes = Vec::new();
+        if cursor != completion.start {
+            changes.push(Change::Delete(completion.start, cursor - completion.start));
+        }
+        changes.push(Change::Insert(
+            completion.start,
+            completion.items[completion.selected_index]
+                .insert_text
+                .as_str()
+                .into(),
+        ));
+        session.apply_changes(changes);
+        self.keep_cursor_in_view = KeepCursorInView::Once;
+        self.redraw(cx);
+        dispatch_action(cx, CodeEditorAction::TextDidChange);
+    }
+
+    // returns true when the key was meant for the completion list
+    fn handle_completion_key_down(
+        &mut self,
+        cx: &mut Cx,
+        hit: &Hit,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) -> bool {
+        let Some(completion) = &mut self.completion else {
+            return false;
+        };
+        match hit {
+            Hit::KeyDown(KeyEvent { key_code, .. }) => match key_code {
+                KeyCode::ArrowUp => {
+                    completion.selected_index = completion.selected_index.saturating_sub(1);
+                    self.redraw(cx);
+                    true
+                }
+                KeyCode::ArrowDown => {
+                    completion.selected_index =
+                        (completion.selected_index + 1).min(completion.items.len() - 1);
+                    self.redraw(cx);
+                    true
+                }
+                KeyCode::ReturnKey | KeyCode::Tab => {
+                    self.accept_completion(cx, session, dispatch_action);
+                    true
+                }
+                KeyCode::Escape => {
+                    self.hide_completions(cx, session);
+                    true
+                }
+                KeyCode::Shift | KeyCode::Control | KeyCode::Alt | KeyCode::Logo => false,
+                _ => {
+                    self.hide_completions(cx, session);
+                    false
+                }
+            },
+            Hit::TextInput(_) | Hit::FingerDown(_) => {
+                self.hide_completions(cx, session);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn last_cursor_position(&self, session: &Session) -> Position {
+        session.selections()[session.last_added_selection_index().unwrap()]
+            .cursor
+            .position
+    }
+
     pub fn handle_event(
         &mut self,
         cx: &mut Cx,
@@ -580,6 +879,14 @@ impl CodeEditor {
 
         session.handle_changes();
 
+        if self.is_find_bar_open {
+            if self.handle_find_bar_key_down(cx, event, session, dispatch_action) {
+                return;
+            }
+            let actions = self.find_bar.handle_widget_event(cx, event);
+            self.handle_find_bar_actions(cx, &actions, session, dispatch_action);
+        }
+
         self.scroll_bars.handle_event_with(cx, event, &mut |cx, _| {
             cx.redraw_all();
         });
@@ -591,8 +898,23 @@ impl CodeEditor {
             }
             self.blink_timer = cx.start_timeout(self.blink_speed)
         }
+        if self.hover_timer.is_event(event).is_some() {
+            if let Some(abs) = self.hover_abs.take() {
+                let ((position, _), is_in_gutter) = self.pick(session, abs);
+                if !is_in_gutter {
+                    dispatch_action(cx, CodeEditorAction::Hover(position));
+                }
+            }
+        }
         let mut keyboard_moved_cursor = false;
-        match event.hits(cx, self.scroll_bars.area()) {
+        let hit = event.hits(cx, self.scroll_bars.area());
+        if self.handle_completion_key_down(cx, &hit, session, dispatch_action) {
+            return;
+        }
+        if let Hit::KeyDown(_) | Hit::TextInput(_) | Hit::FingerDown(_) = hit {
+            self.hide_hover(cx, session);
+        }
+        match hit {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
             }
@@ -670,6 +992,46 @@ impl CodeEditor {
                     self.redraw(cx);
                 }
             }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyF,
+                modifiers: KeyModifiers { control, logo, .. },
+                ..
+            }) => {
+                if control || logo {
+                    self.open_find_bar(cx, session, false);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::KeyH,
+                modifiers: KeyModifiers { control, logo, .. },
+                ..
+            }) => {
+                if control || logo {
+                    self.open_find_bar(cx, session, true);
+                }
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::F3,
+                modifiers: KeyModifiers { shift, .. },
+                ..
+            }) => {
+                self.find(cx, session, shift);
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::F12,
+                ..
+            }) => {
+                let position = self.last_cursor_position(session);
+                dispatch_action(cx, CodeEditorAction::GoToDefinition(position));
+            }
+            Hit::KeyDown(KeyEvent {
+                key_code: KeyCode::Space,
+                modifiers: KeyModifiers { control: true, .. },
+                ..
+            }) => {
+                let position = self.last_cursor_position(session);
+                dispatch_action(cx, CodeEditorAction::Complete(position));
+            }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::ArrowLeft,
                 modifiers:
@@ -876,6 +1238,27 @@ impl CodeEditor {
                     keyboard_moved_cursor = true;
                 }
             }
+            Hit::FingerDown(FingerDownEvent {
+                abs,
+                modifiers:
+                    KeyModifiers {
+                        alt: false,
+                        shift: false,
+                        control,
+                        logo,
+                    },
+                ..
+            }) if control || logo => {
+                self.animator_play(cx, id!(focus.on));
+                cx.set_key_focus(self.scroll_bars.area());
+                let ((cursor, affinity), is_in_gutter) = self.pick(session, abs);
+                session.set_selection(cursor, affinity, SelectionMode::Simple);
+                self.reset_cursor_blinker(cx);
+                self.redraw(cx);
+                if !is_in_gutter {
+                    dispatch_action(cx, CodeEditorAction::GoToDefinition(cursor));
+                }
+            }
             Hit::FingerDown(FingerDownEvent {
                 abs,
                 tap_count,
@@ -944,8 +1327,16 @@ impl CodeEditor {
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Off;
             }
-            Hit::FingerHoverIn(_) | Hit::FingerHoverOver(_) => {
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. }) => {
                 cx.set_cursor(MouseCursor::Text);
+                self.hover_abs = Some(abs);
+                cx.stop_timer(self.hover_timer);
+                self.hover_timer = cx.start_timeout(self.hover_delay);
+            }
+            Hit::FingerHoverOut(_) => {
+                self.hover_abs = None;
+                cx.stop_timer(self.hover_timer);
             }
             Hit::FingerDown(FingerDownEvent {
                 abs,
@@ -980,9 +1371,229 @@ impl CodeEditor {
         }
     }
 
+    fn open_find_bar(&mut self, cx: &mut Cx, session: &mut Session, show_replace: bool) {
+        let selection = session.selections()[session.last_added_selection_index().unwrap_or(0)];
+        let find_input = self.find_bar.text_input(id!(find_input));
+        // A selection within a line is most likely what we are looking for.
+        if !selection.is_empty() && selection.start().line_index == selection.end().line_index {
+            let text = session
+                .document()
+                .as_text()
+                .slice(selection.start(), selection.length())
+                .to_string();
+            find_input.set_text(&text);
+        }
+        self.find_bar
+            .view(id!(replace_row))
+            .set_visible(show_replace);
+        self.is_find_bar_open = true;
+        self.find_origin = selection.start();
+        self.update_search(cx, session);
+        find_input.set_key_focus(cx);
+        self.redraw(cx);
+    }
+
+    fn close_find_bar(&mut self, cx: &mut Cx, session: &mut Session) {
+        self.is_find_bar_open = false;
+        self.find_error = None;
+        session.set_search_query(SearchQuery::default()).unwrap();
+        cx.set_key_focus(self.scroll_bars.area());
+        self.redraw(cx);
+    }
+
+    fn update_search(&mut self, cx: &mut Cx, session: &mut Session) {
+        let query = SearchQuery {
+            text: self.find_bar.text_input(id!(find_input)).text(),
+            is_regex: self.find_bar.check_box(id!(find_regex)).selected(cx),
+            case_sensitive: self.find_bar.check_box(id!(find_case)).selected(cx),
+            whole_word: self.find_bar.check_box(id!(find_word)).selected(cx),
+        };
+        let is_empty = query.is_empty();
+        self.find_error = session.set_search_query(query).err();
+        // Incremental search: the selection moves to the first match from where we started.
+        if !is_empty && self.find_error.is_none() {
+            session.set_selection(self.find_origin, Affinity::Before, SelectionMode::Simple);
+            if session.find_next() {
+                self.keep_cursor_in_view = KeepCursorInView::JumpToPosition;
+            }
+        }
+        self.redraw(cx);
+    }
+
+    fn find(&mut self, cx: &mut Cx, session: &mut Session, backwards: bool) {
+        let found = if backwards {
+            session.find_previous()
+        } else {
+            session.find_next()
+        };
+        if found {
+            self.keep_cursor_in_view = KeepCursorInView::JumpToPosition;
+            self.redraw(cx);
+        }
+    }
+
+    fn replace(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) {
+        let replacement = self.find_bar.text_input(id!(replace_input)).text();
+        // The first replace only selects a match, so we see what is replaced.
+        if session.replace_search_match(&replacement) {
+            self.keep_cursor_in_view = KeepCursorInView::JumpToPosition;
+            self.redraw(cx);
+            dispatch_action(cx, CodeEditorAction::TextDidChange);
+        } else {
+            self.find(cx, session, false);
+        }
+    }
+
+    fn replace_all(
+        &mut self,
+        cx: &mut Cx,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) {
+        let replacement = self.find_bar.text_input(id!(replace_input)).text();
+        if session.replace_all_search_matches(&replacement) > 0 {
+            self.redraw(cx);
+            dispatch_action(cx, CodeEditorAction::TextDidChange);
+        }
+    }
+
+    // Keys of the find bar are handled here, before its inputs see them, because an input can't
+    // tell us which modifiers came with a return.
+    fn handle_find_bar_key_down(
+        &mut self,
+        cx: &mut Cx,
+        event: &Event,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) -> bool {
+        let Event::KeyDown(KeyEvent {
+            key_code,
+            modifiers:
+                KeyModifiers {
+                    shift,
+                    control,
+                    alt,
+                    logo,
+                },
+            ..
+        }) = *event
+        else {
+            return false;
+        };
+        let is_in_replace = self
+            .find_bar
+            .text_input(id!(replace_input))
+            .has_key_focus(cx);
+        if !is_in_replace && !self.find_bar.text_input(id!(find_input)).has_key_focus(cx) {
+            return false;
+        }
+        match key_code {
+            KeyCode::ReturnKey if alt && (control || logo) => {
+                self.replace_all(cx, session, dispatch_action);
+            }
+            KeyCode::ReturnKey if alt => {
+                if session.select_all_search_matches() {
+                    self.close_find_bar(cx, session);
+                }
+            }
+            KeyCode::ReturnKey if is_in_replace && !shift => {
+                self.replace(cx, session, dispatch_action);
+            }
+            KeyCode::ReturnKey | KeyCode::F3 => {
+                self.find(cx, session, shift);
+            }
+            KeyCode::Escape => {
+                self.close_find_bar(cx, session);
+            }
+            KeyCode::KeyF if control || logo => {
+                self.open_find_bar(cx, session, false);
+            }
+            KeyCode::KeyH if control || logo => {
+                self.open_find_bar(cx, session, true);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_find_bar_actions(
+        &mut self,
+        cx: &mut Cx,
+        actions: &WidgetActions,
+        session: &mut Session,
+        dispatch_action: &mut dyn FnMut(&mut Cx, CodeEditorAction),
+    ) {
+        if self
+            .find_bar
+            .text_input(id!(find_input))
+            .changed(actions)
+            .is_some()
+            || self
+                .find_bar
+                .check_box(id!(find_regex))
+                .changed(actions)
+                .is_some()
+            || self
+                .find_bar
+                .check_box(id!(find_case))
+                .changed(actions)
+                .is_some()
+            || self
+                .find_bar
+                .check_box(id!(find_word))
+                .changed(actions)
+                .is_some()
+        {
+            self.update_search(cx, session);
+        }
+        if self.find_bar.button(id!(replace)).clicked(actions) {
+            self.replace(cx, session, dispatch_action);
+        }
+        if self.find_bar.button(id!(replace_all)).clicked(actions) {
+            self.replace_all(cx, session, dispatch_action);
+        }
+    }
+
+    fn draw_find_bar(&mut self, cx: &mut Cx2d, session: &Session) {
+        let status = if let Some(error) = &self.find_error {
+            error.clone()
+        } else if session.search_query().is_empty() {
+            String::new()
+        } else {
+            let count = session.search_matches().len();
+            match session.selected_search_match_index() {
+                Some(index) => format!("{} of {}", index + 1, count),
+                None if count == 0 => "No results".to_string(),
+                None => format!("{} results", count),
+            }
+        };
+        self.find_bar.label(id!(find_status)).set_text(&status);
+        // The find bar stays in the top right corner of the visible part of the editor.
+        cx.begin_turtle(
+            Walk::fixed_size(self.unscrolled_rect.size).with_abs_pos(self.unscrolled_rect.pos),
+            Layout {
+                align: Align { x: 1.0, y: 0.0 },
+                padding: Padding {
+                    left: 0.0,
+                    top: 4.0,
+                    right: 16.0,
+                    bottom: 0.0,
+                },
+                ..Layout::default()
+            },
+        );
+        self.find_bar.draw_widget_all(cx);
+        cx.end_turtle();
+    }
+
     fn draw_gutter(&mut self, cx: &mut Cx2d, session: &Session) {
         let mut line_index = self.line_start;
-        let mut origin_y = session.layout().line(self.line_start).y();
+        let mut origin_y = session.layout().block_elements_y(self.line_start);
         let mut buf = String::new();
         for element in session
             .layout()
@@ -1020,7 +1631,7 @@ impl CodeEditor {
     fn draw_text_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let highlighted_delimiter_positions = session.highlighted_delimiter_positions();
         let mut line_index = self.line_start;
-        let mut origin_y = session.layout().line(self.line_start).y();
+        let mut origin_y = session.layout().block_elements_y(self.line_start);
         for element in session
             .layout()
             .block_elements(self.line_start, self.line_end)
@@ -1139,8 +1750,153 @@ impl CodeEditor {
         }
     }
 
+    fn widget_rows(&self, session: &Session) -> Vec<WidgetRow> {
+        let layout = session.layout();
+        let mut rows = Vec::new();
+        let mut origin_y = layout.block_elements_y(self.line_start);
+        for element in layout.block_elements(self.line_start, self.line_end) {
+            match element {
+                BlockElement::Line { line, .. } => {
+                    let mut row_index = 0;
+                    let mut column_index = 0;
+                    for element in line.wrapped_elements() {
+                        match element {
+                            WrappedElement::Text { text, .. } => {
+                                column_index += text.column_count();
+                            }
+                            WrappedElement::Widget(widget) => {
+                                let (x, y) =
+                                    line.grid_to_normalized_position(row_index, column_index);
+                                if let (HOVER_WIDGET_ID, Some(lines)) = (widget.id, &self.hover) {
+                                    // leave a column between the code and the hover
+                                    rows.push(WidgetRow {
+                                        pos: DVec2 {
+                                            x: x + line.scale(),
+                                            y: origin_y + y,
+                                        },
+                                        width: (widget.column_count - 1) as f64 * line.scale(),
+                                        height: line.scale(),
+                                        text: lines[0].clone(),
+                                        is_selected: false,
+                                    });
+                                }
+                                column_index += widget.column_count;
+                            }
+                            WrappedElement::Wrap => {
+                                column_index = line.wrap_indent_column_count();
+                                row_index += 1;
+                            }
+                        }
+                    }
+                    origin_y += line.height();
+                }
+                BlockElement::Widget(widget) => {
+                    match (widget.id, &self.hover, &self.completion) {
+                        (HOVER_WIDGET_ID, Some(lines), _) => {
+                            let width = lines
+                                .iter()
+                                .map(|line| line.column_count())
+                                .max()
+                                .unwrap_or(0);
+                            for (index, line) in lines.iter().enumerate() {
+                                rows.push(WidgetRow {
+                                    pos: DVec2 {
+                                        x: 0.0,
+                                        y: origin_y + index as f64,
+                                    },
+                                    width: (width + 2) as f64,
+                                    height: 1.0,
+                                    text: line.clone(),
+                                    is_selected: false,
+                                });
+                            }
+                        }
+                        (COMPLETION_WIDGET_ID, _, Some(completion))
+                            if layout
+                                .as_text()
+                                .as_lines()
+                                .get(completion.start.line_index)
+                                .map_or(false, |line| {
+                                    completion.start.byte_index <= line.len()
+                                }) =>
+                        {
+                            let texts: Vec<String> = completion
+                                .items
+                                .iter()
+                                .map(|item| {
+                                    format!("{}  {}", item.label, item.detail)
+                                        .trim_end()
+                                        .to_string()
+                                })
+                                .collect();
+                            let width = texts.iter().map(|text| text.column_count()).max().unwrap();
+                            // the text lines up with the start of the word
+                            let (x, _) = layout
+                                .logical_to_normalized_position(completion.start, Affinity::Before);
+                            let first_index = (completion.selected_index + 1)
+                                .saturating_sub(MAX_VISIBLE_COMPLETION_COUNT);
+                            for (index, text) in texts
+                                .into_iter()
+                                .enumerate()
+                                .skip(first_index)
+                                .take(MAX_VISIBLE_COMPLETION_COUNT)
+                            {
+                                rows.push(WidgetRow {
+                                    pos: DVec2 {
+                                        x: (x - 1.0).max(0.0),
+                                        y: origin_y + (index - first_index) as f64,
+                                    },
+                                    width: (width + 2) as f64,
+                                    height: 1.0,
+                                    text,
+                                    is_selected: index == completion.selected_index,
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                    origin_y += widget.height;
+                }
+            }
+        }
+        rows
+    }
+
+    fn draw_widget_bg_layer(&mut self, cx: &mut Cx2d<'_>, rows: &[WidgetRow]) {
+        for row in rows {
+            self.draw_widget_bg.color = if row.is_selected {
+                self.token_colors.widget_selection
+            } else {
+                self.token_colors.widget_bg
+            };
+            self.draw_widget_bg.draw_abs(
+                cx,
+                Rect {
+                    pos: row.pos * self.cell_size + self.viewport_rect.pos,
+                    size: DVec2 {
+                        x: row.width,
+                        y: row.height,
+                    } * self.cell_size,
+                },
+            );
+        }
+    }
+
+    fn draw_widget_text_layer(&mut self, cx: &mut Cx2d<'_>, rows: &[WidgetRow]) {
+        self.draw_text.color = self.token_colors.unknown;
+        self.draw_text.outline = 0.0;
+        for row in rows {
+            self.draw_text.font_scale = row.height;
+            self.draw_text.draw_abs(
+                cx,
+                (row.pos + dvec2(row.height, 0.0)) * self.cell_size + self.viewport_rect.pos,
+                &row.text,
+            );
+        }
+    }
+
     fn draw_indent_guide_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
-        let mut origin_y = session.layout().line(self.line_start).y();
+        let mut origin_y = session.layout().block_elements_y(self.line_start);
         for element in session
             .layout()
             .block_elements(self.line_start, self.line_end)
@@ -1178,8 +1934,22 @@ impl CodeEditor {
     }
 
     fn draw_decoration_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
-        let mut active_decoration = None;
         let decorations = session.document().decorations();
+        self.draw_decorations(cx, session, &decorations);
+    }
+
+    fn draw_search_match_layer(&mut self, cx: &mut Cx2d<'_>, session: &Session) {
+        let search_matches = session.search_matches();
+        self.draw_decorations(cx, session, &search_matches);
+    }
+
+    fn draw_decorations(
+        &mut self,
+        cx: &mut Cx2d<'_>,
+        session: &Session,
+        decorations: &[Decoration],
+    ) {
+        let mut active_decoration = None;
         let mut decorations = decorations.iter();
         while decorations.as_slice().first().map_or(false, |decoration| {
             decoration.end().line_index < self.line_start
@@ -1256,7 +2026,7 @@ impl CodeEditor {
             );
         }
         let mut line_index = layout.find_first_line_ending_after_y(position.y);
-        let mut origin_y = layout.line(line_index).y();
+        let mut origin_y = layout.block_elements_y(line_index);
         for block in layout.block_elements(line_index, line_index + 1) {
             match block {
                 BlockElement::Line {
@@ -1430,6 +2200,35 @@ impl CodeEditor {
                     origin_y += line.height();
                 }
                 BlockElement::Widget(widget) => {
+                    let start_y = origin_y;
+                    let end_y = start_y + widget.height;
+                    if (start_y..=end_y).contains(&position.y) {
+                        // widgets hold no text, so pick the end of the line above them
+                        return if line_index == 0 {
+                            (
+                                (
+                                    Position {
+                                        line_index,
+                                        byte_index: 0,
+                                    },
+                                    Affinity::Before,
+                                ),
+                                false,
+                            )
+                        } else {
+                            (
+                                (
+                                    Position {
+                                        line_index: line_index - 1,
+                                        byte_index: layout.as_text().as_lines()[line_index - 1]
+                                            .len(),
+                                    },
+                                    Affinity::After,
+                                ),
+                                false,
+                            )
+                        };
+                    }
                     origin_y += widget.height;
                 }
             }
@@ -1441,6 +2240,13 @@ impl CodeEditor {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum CodeEditorAction {
     TextDidChange,
+    /// The mouse rested on a position, for a hover to be shown with [`CodeEditor::show_hover`].
+    Hover(Position),
+    /// The user asked where the symbol at a position is defined, with F12 or a ctrl or cmd click.
+    GoToDefinition(Position),
+    /// The user asked for completions at a position, with ctrl+space. Show them with
+    /// [`CodeEditor::show_completions`].
+    Complete(Position),
 }
 
 struct DrawDecorationLayer<'a> {
@@ -1452,7 +2258,7 @@ struct DrawDecorationLayer<'a> {
 impl<'a> DrawDecorationLayer<'a> {
     fn draw_decoration_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let mut line_index = self.code_editor.line_start;
-        let mut origin_y = session.layout().line(line_index).y();
+        let mut origin_y = session.layout().block_elements_y(line_index);
         for block in session
             .layout()
             .block_elements(self.code_editor.line_start, self.code_editor.line_end)
@@ -1609,26 +2415,32 @@ impl<'a> DrawDecorationLayer<'a> {
     ) {
         let start_x = mem::take(&mut self.active_decoration.as_mut().unwrap().start_x);
         let (x, y) = line.grid_to_normalized_position(row_index, column_index);
-        self.code_editor.draw_decoration.color =
-            match self.active_decoration.as_mut().unwrap().decoration.ty {
-                DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
-                DecorationType::Error => self.code_editor.token_colors.error_decoration,
-            };
-
-        self.code_editor.draw_decoration.draw_abs(
-            cx,
-            Rect {
-                pos: DVec2 {
-                    x: start_x,
-                    y: origin_y + y,
-                } * self.code_editor.cell_size
-                    + self.code_editor.viewport_rect.pos,
-                size: DVec2 {
-                    x: x - start_x,
-                    y: line.scale(),
-                } * self.code_editor.cell_size,
-            },
-        );
+        let rect = Rect {
+            pos: DVec2 {
+                x: start_x,
+                y: origin_y + y,
+            } * self.code_editor.cell_size
+                + self.code_editor.viewport_rect.pos,
+            size: DVec2 {
+                x: x - start_x,
+                y: line.scale(),
+            } * self.code_editor.cell_size,
+        };
+        let token_colors = &self.code_editor.token_colors;
+        match self.active_decoration.as_mut().unwrap().decoration.ty {
+            DecorationType::Warning => {
+                self.code_editor.draw_decoration.color = token_colors.warning_decoration;
+                self.code_editor.draw_decoration.draw_abs(cx, rect);
+            }
+            DecorationType::Error => {
+                self.code_editor.draw_decoration.color = token_colors.error_decoration;
+                self.code_editor.draw_decoration.draw_abs(cx, rect);
+            }
+            DecorationType::SearchMatch => {
+                self.code_editor.draw_search_match.color = token_colors.search_match;
+                self.code_editor.draw_search_match.draw_abs(cx, rect);
+            }
+        }
     }
 }
 
@@ -1646,7 +2458,7 @@ struct DrawSelectionLayer<'a> {
 impl<'a> DrawSelectionLayer<'a> {
     fn draw_selection_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let mut line_index = self.code_editor.line_start;
-        let mut origin_y = session.layout().line(line_index).y();
+        let mut origin_y = session.layout().block_elements_y(line_index);
         for block in session
             .layout()
             .block_elements(self.code_editor.line_start, self.code_editor.line_end)
@@ -1923,6 +2735,12 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    search_match: Vec4,
+    #[live]
+    widget_bg: Vec4,
+    #[live]
+    widget_selection: Vec4,
 }
 
 #[derive(Live, LiveHook)]
@@ -1998,3 +2816,13 @@ impl DrawSelection {
         }
     }
 }
+
+// the start of the word that ends at the byte index
+fn word_start(line: &str, byte_index: usize) -> usize {
+    line[..byte_index]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, char)| char.is_alphanumeric() || char == '_')
+        .last()
+        .map_or(byte_index, |(index, _)| index)
+}
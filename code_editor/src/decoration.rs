@@ -7,6 +7,7 @@ use {
 pub enum DecorationType {
     Error,
     Warning,
+    SearchMatch,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -94,6 +95,11 @@ impl DecorationSet {
         self.decorations.clear();
     }
 
+    /// Removes the decorations with the given id, keeping the ones that came from elsewhere.
+    pub fn remove_decorations(&mut self, id: usize) {
+        self.decorations.retain(|decoration| decoration.id != id);
+    }
+
     pub fn apply_edit(&mut self, edit: &Edit) {
         for decoration in &mut self.decorations {
             *decoration = decoration.apply_edit(edit);
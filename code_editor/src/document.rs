@@ -3,7 +3,6 @@ use {
         char::CharExt,
         decoration::{Decoration, DecorationSet},
         history::{EditKind, History},
-        inlays::{BlockInlay, InlineInlay},
         iter::IteratorExt,
         selection::SelectionSet,
         session::SessionId,
@@ -11,11 +10,10 @@ use {
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
         token::{Token, TokenKind},
-        tokenizer::Tokenizer,
+        tokenizer::{Tokenizer, TokenizerFactory},
     },
     std::{
         cell::{Ref, RefCell},
-        cmp::Ordering,
         collections::HashMap,
         iter,
         ops::Range,
@@ -29,6 +27,18 @@ pub struct Document(Rc<DocumentInner>);
 
 impl Document {
     pub fn new(text: Text, decorations: DecorationSet) -> Self {
+        Self::new_with_tokenizer(text, decorations, Tokenizer::new)
+    }
+
+    /// Like [`Document::new`], for languages other than Rust. Use a [`TokenizerRegistry`] to pick
+    /// the tokenizer from the file name.
+    ///
+    /// [`TokenizerRegistry`]: crate::tokenizer::TokenizerRegistry
+    pub fn new_with_tokenizer(
+        text: Text,
+        decorations: DecorationSet,
+        new_tokenizer: TokenizerFactory,
+    ) -> Self {
         let line_count = text.as_lines().len();
         let tokens: Vec<_> = (0..line_count)
             .map(|line| tokenize(&text.as_lines()[line]).collect::<Vec<_>>())
@@ -38,12 +48,11 @@ impl Document {
             layout: RefCell::new(DocumentLayout {
                 indent_state: (0..line_count).map(|_| None).collect(),
                 tokens,
-                inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
-                block_inlays: Vec::new(),
             }),
-            tokenizer: RefCell::new(Tokenizer::new(line_count)),
+            tokenizer: RefCell::new(new_tokenizer(line_count)),
             decorations: RefCell::new(decorations),
             edit_senders: RefCell::new(HashMap::new()),
+            local_changes: RefCell::new(None),
         }));
         inner.update_indent_state();
         inner.0.tokenizer.borrow_mut().update(
@@ -135,7 +144,7 @@ impl Document {
         }
         drop(history);
         self.autoindent(&line_ranges, settings.tab_column_count, &mut edits);
-        self.update_after_edit(session_id, None, &edits);
+        self.update_after_edit(Some(session_id), None, &edits);
     }
 
     pub fn edit_linewise(
@@ -171,7 +180,28 @@ impl Document {
             }
         }
         drop(history);
-        self.update_after_edit(origin_id, None, &edits);
+        self.update_after_edit(Some(origin_id), None, &edits);
+    }
+
+    pub fn edit_changes(
+        &self,
+        origin_id: SessionId,
+        selections: &SelectionSet,
+        changes: Vec<Change>,
+    ) {
+        let mut history = self.0.history.borrow_mut();
+        history.push_or_extend_group(origin_id, EditKind::Other, selections);
+        let mut edits = Vec::new();
+        for change in changes {
+            let edit = Edit {
+                change,
+                drift: Drift::Before,
+            };
+            history.apply_edit(edit.clone());
+            edits.push(edit);
+        }
+        drop(history);
+        self.update_after_edit(Some(origin_id), None, &edits);
     }
 
     pub fn add_decoration(&mut self, decoration: Decoration) {
@@ -182,6 +212,12 @@ impl Document {
         self.0.decorations.borrow_mut().clear()
     }
 
+    /// Removes the decorations with the given id, so that decorations from different sources
+    /// can be replaced independently.
+    pub fn remove_decorations(&mut self, id: usize) {
+        self.0.decorations.borrow_mut().remove_decorations(id)
+    }
+
     pub fn add_session(
         &mut self,
         session_id: SessionId,
@@ -323,7 +359,7 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().undo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
@@ -334,19 +370,52 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().redo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
         }
     }
 
+    pub fn track_local_changes(&self) {
+        self.0.local_changes.borrow_mut().get_or_insert_with(Vec::new);
+    }
+
+    pub fn take_local_changes(&self) -> Vec<Change> {
+        self.0
+            .local_changes
+            .borrow_mut()
+            .as_mut()
+            .map_or_else(Vec::new, std::mem::take)
+    }
+
+    pub fn apply_remote_changes(&self, changes: Vec<Change>) {
+        let mut history = self.0.history.borrow_mut();
+        let edits: Vec<_> = changes
+            .into_iter()
+            .map(|change| Edit {
+                change,
+                drift: Drift::Before,
+            })
+            .collect();
+        for edit in &edits {
+            history.apply_remote_edit(edit.clone());
+        }
+        drop(history);
+        self.update_after_edit(None, None, &edits);
+    }
+
     fn update_after_edit(
         &self,
-        origin_id: SessionId,
+        origin_id: Option<SessionId>,
         selections: Option<SelectionSet>,
         edits: &[Edit],
     ) {
+        if let (Some(_), Some(local_changes)) =
+            (origin_id, &mut *self.0.local_changes.borrow_mut())
+        {
+            local_changes.extend(edits.iter().map(|edit| edit.change.clone()));
+        }
         let mut layout = self.0.layout.borrow_mut();
         for edit in edits {
             match edit.change {
@@ -373,7 +442,6 @@ impl Document {
         drop(layout);
         for edit in edits {
             self.apply_change_to_tokens(&edit.change);
-            self.apply_change_to_inline_inlays(&edit.change, edit.drift);
             self.0.tokenizer.borrow_mut().apply_change(&edit.change);
         }
         self.update_indent_state();
@@ -387,7 +455,7 @@ impl Document {
         }
         drop(decorations);
         for (&session_id, edit_sender) in &*self.0.edit_senders.borrow() {
-            if session_id == origin_id {
+            if Some(session_id) == origin_id {
                 edit_sender
                     .send((selections.clone(), edits.to_vec()))
                     .unwrap();
@@ -529,81 +597,6 @@ impl Document {
         }
     }
 
-    fn apply_change_to_inline_inlays(&self, change: &Change, drift: Drift) {
-        let mut layout = self.0.layout.borrow_mut();
-        let inline_inlays = &mut layout.inline_inlays;
-        match *change {
-            Change::Insert(point, ref text) => {
-                let index = inline_inlays[point.line_index]
-                    .iter()
-                    .position(|(byte, _)| match byte.cmp(&point.byte_index) {
-                        Ordering::Less => false,
-                        Ordering::Equal => match drift {
-                            Drift::Before => true,
-                            Drift::After => false,
-                        },
-                        Ordering::Greater => true,
-                    })
-                    .unwrap_or(inline_inlays[point.line_index].len());
-                if text.length().line_count == 0 {
-                    for (byte, _) in &mut inline_inlays[point.line_index][index..] {
-                        *byte += text.length().byte_count;
-                    }
-                } else {
-                    let mut new_inline_inlays = (0..text.as_lines().len())
-                        .map(|_| Vec::new())
-                        .collect::<Vec<_>>();
-                    new_inline_inlays
-                        .first_mut()
-                        .unwrap()
-                        .splice(..0, inline_inlays[point.line_index].drain(..index));
-                    new_inline_inlays.last_mut().unwrap().splice(
-                        ..0,
-                        inline_inlays[point.line_index]
-                            .drain(..)
-                            .map(|(byte, inline_inlay)| {
-                                (byte + text.length().byte_count, inline_inlay)
-                            }),
-                    );
-                    inline_inlays.splice(point.line_index..point.line_index + 1, new_inline_inlays);
-                }
-            }
-            Change::Delete(start, length) => {
-                let end = start + length;
-                let start_inlay = inline_inlays[start.line_index]
-                    .iter()
-                    .position(|&(byte, _)| byte >= start.byte_index)
-                    .unwrap_or(inline_inlays[start.line_index].len());
-                let end_inlay = inline_inlays[end.line_index]
-                    .iter()
-                    .position(|&(byte, _)| byte >= end.byte_index)
-                    .unwrap_or(inline_inlays[end.line_index].len());
-                if length.line_count == 0 {
-                    inline_inlays[start.line_index].drain(start_inlay..end_inlay);
-                    for (byte, _) in &mut inline_inlays[start.line_index][start_inlay..] {
-                        *byte = start.byte_index + (*byte - end.byte_index.min(*byte));
-                    }
-                } else {
-                    let mut new_inline_inlays = inline_inlays[start.line_index]
-                        .drain(..start_inlay)
-                        .collect::<Vec<_>>();
-                    new_inline_inlays.extend(inline_inlays[end.line_index].drain(end_inlay..).map(
-                        |(byte, inline_inlay)| {
-                            (
-                                start.byte_index + byte - end.byte_index.min(byte),
-                                inline_inlay,
-                            )
-                        },
-                    ));
-                    inline_inlays.splice(
-                        start.line_index..end.line_index + 1,
-                        iter::once(new_inline_inlays),
-                    );
-                }
-            }
-        }
-    }
-
     fn update_indent_state(&self) {
         let mut layout = self.0.layout.borrow_mut();
         let indent_state = &mut layout.indent_state;
@@ -651,8 +644,6 @@ impl Document {
 pub struct DocumentLayout {
     pub indent_state: Vec<Option<IndentState>>,
     pub tokens: Vec<Vec<Token>>,
-    pub inline_inlays: Vec<Vec<(usize, InlineInlay)>>,
-    pub block_inlays: Vec<(usize, BlockInlay)>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -685,6 +676,9 @@ struct DocumentInner {
     tokenizer: RefCell<Tokenizer>,
     decorations: RefCell<DecorationSet>,
     edit_senders: RefCell<HashMap<SessionId, Sender<(Option<SelectionSet>, Vec<Edit>)>>>,
+    // changes made by sessions of this document, kept once tracking is turned on so they can
+    // be sent to other participants
+    local_changes: RefCell<Option<Vec<Change>>>,
 }
 
 fn tokenize(text: &str) -> impl Iterator<Item = Token> + '_ {
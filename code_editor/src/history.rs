@@ -51,6 +51,15 @@ impl History {
         self.redo_stack.clear();
     }
 
+    // Edits made elsewhere are not part of the undo history. The stacks no longer line up with
+    // the text after one, so they are dropped.
+    pub fn apply_remote_edit(&mut self, edit: Edit) {
+        self.text.apply_change(edit.change);
+        self.current_desc = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
     pub fn undo(
         &mut self,
         selections: &SelectionSet,
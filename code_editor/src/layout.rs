@@ -76,7 +76,7 @@ impl<'a> Layout<'a> {
             text: &self.text.as_lines()[index],
             indent_state: self.document_layout.indent_state[index],
             tokens: &self.document_layout.tokens[index],
-            inlays: &self.document_layout.inline_inlays[index],
+            inlays: &self.session_layout.inline_inlays[index],
             wrap_data: self.session_layout.wrap_data[index].as_ref(),
         }
     }
@@ -92,13 +92,23 @@ impl<'a> Layout<'a> {
             text: self.text.as_lines()[start..end].iter(),
             indent_state: self.document_layout.indent_state[start..end].iter(),
             tokens: self.document_layout.tokens[start..end].iter(),
-            inline_inlays: self.document_layout.inline_inlays[start..end].iter(),
+            inline_inlays: self.session_layout.inline_inlays[start..end].iter(),
             wrap_data: self.session_layout.wrap_data[start..end].iter(),
         }
     }
 
+    /// The y where [`Layout::block_elements`] starts for the given line, which is above the block
+    /// inlays that come before that line.
+    pub fn block_elements_y(&self, line_start: usize) -> f64 {
+        if line_start == 0 {
+            return 0.0;
+        }
+        let line = self.line(line_start - 1);
+        line.y() + line.height()
+    }
+
     pub fn block_elements(&self, line_start: usize, line_end: usize) -> BlockElements<'_> {
-        let mut block_inlays = self.document_layout.block_inlays.iter();
+        let mut block_inlays = self.session_layout.block_inlays.iter();
         while block_inlays
             .as_slice()
             .first()
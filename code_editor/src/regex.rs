@@ -0,0 +1,620 @@
+// A small backtracking regex engine for find in the editor and searching in files. It supports
+// the usual syntax: literals and escapes, `.`, classes like `[a-z]`, `\d\w\s` and their
+// negations, `^ $ \b \B`, capturing and `(?:)` groups, `|`, and the `* + ? {n,m}` quantifiers
+// with lazy variants. Every (instruction, position) pair is visited at most once, so matching a
+// line is linear.
+
+use std::{iter::Peekable, ops::Range, str::Chars};
+
+#[derive(Clone, Debug)]
+pub struct Regex {
+    prog: Vec<Inst>,
+    group_count: usize,
+    case_insensitive: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Captures {
+    slots: Vec<Option<usize>>,
+}
+
+impl Captures {
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        match (self.slots.get(index * 2), self.slots.get(index * 2 + 1)) {
+            (Some(Some(start)), Some(Some(end))) => Some(*start..*end),
+            _ => None,
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.get(0).unwrap()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assert),
+    Split(usize, usize),
+    Jmp(usize),
+    Save(usize),
+    Match,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Assert {
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+#[derive(Clone, Debug)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assert),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+const REPEAT_MAX: usize = 1000;
+
+impl Regex {
+    pub fn new(pattern: &str, case_insensitive: bool) -> Result<Regex, String> {
+        let mut parser = Parser {
+            chars: pattern.chars().peekable(),
+            group_count: 1,
+        };
+        let node = parser.parse_alt()?;
+        if let Some(c) = parser.chars.next() {
+            return Err(format!("unexpected `{}`", c));
+        }
+        let mut prog = Vec::new();
+        prog.push(Inst::Save(0));
+        compile(&node, &mut prog, case_insensitive);
+        prog.push(Inst::Save(1));
+        prog.push(Inst::Match);
+        Ok(Regex {
+            prog,
+            group_count: parser.group_count,
+            case_insensitive,
+        })
+    }
+
+    // escapes all syntax so the text matches literally
+    pub fn escape(text: &str) -> String {
+        let mut out = String::new();
+        for c in text.chars() {
+            if "\\.+*?()|[]{}^$".contains(c) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    pub fn find_at(&self, text: &str, start: usize) -> Option<Captures> {
+        let len = text.len() + 1;
+        let mut visited = vec![0u64; (self.prog.len() * len + 63) / 64];
+        let mut slots = vec![None; self.group_count * 2];
+        let mut pos = start;
+        loop {
+            if self.run(text, pos, &mut visited, &mut slots) {
+                return Some(Captures { slots });
+            }
+            match text[pos..].chars().next() {
+                Some(c) => pos += c.len_utf8(),
+                None => return None,
+            }
+        }
+    }
+
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Captures> + 'a {
+        let mut pos = Some(0);
+        std::iter::from_fn(move || {
+            let captures = self.find_at(text, pos?)?;
+            let range = captures.range();
+            // step over empty matches so we don't find them again
+            pos = if range.is_empty() {
+                text[range.end..]
+                    .chars()
+                    .next()
+                    .map(|c| range.end + c.len_utf8())
+            } else {
+                Some(range.end)
+            };
+            Some(captures)
+        })
+    }
+
+    fn run(
+        &self,
+        text: &str,
+        start: usize,
+        visited: &mut [u64],
+        slots: &mut [Option<usize>],
+    ) -> bool {
+        enum Job {
+            Run(usize, usize),
+            Restore(usize, Option<usize>),
+        }
+        let len = text.len() + 1;
+        let mut stack = vec![Job::Run(0, start)];
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Run(pc, pos) => (pc, pos),
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            loop {
+                let bit = pc * len + pos;
+                if visited[bit / 64] & (1 << (bit % 64)) != 0 {
+                    break;
+                }
+                visited[bit / 64] |= 1 << (bit % 64);
+                match &self.prog[pc] {
+                    Inst::Match => return true,
+                    Inst::Char(expected) => match text[pos..].chars().next() {
+                        Some(c)
+                            if c == *expected
+                                || self.case_insensitive && fold_case(c) == *expected =>
+                        {
+                            pc += 1;
+                            pos += c.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Any => match text[pos..].chars().next() {
+                        Some(c) if c != '\n' => {
+                            pc += 1;
+                            pos += c.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Class(class) => match text[pos..].chars().next() {
+                        Some(c) if class.matches(c, self.case_insensitive) => {
+                            pc += 1;
+                            pos += c.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Assert(assert) => {
+                        let before = text[..pos].chars().next_back();
+                        let after = text[pos..].chars().next();
+                        let at_boundary =
+                            before.map_or(false, is_word_char) != after.map_or(false, is_word_char);
+                        let ok = match assert {
+                            Assert::LineStart => before.map_or(true, |c| c == '\n'),
+                            Assert::LineEnd => after.map_or(true, |c| c == '\n'),
+                            Assert::WordBoundary => at_boundary,
+                            Assert::NotWordBoundary => !at_boundary,
+                        };
+                        if !ok {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Run(*second, pos));
+                        pc = *first;
+                    }
+                    Inst::Jmp(target) => pc = *target,
+                    Inst::Save(slot) => {
+                        stack.push(Job::Restore(*slot, slots[*slot]));
+                        slots[*slot] = Some(pos);
+                        pc += 1;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Class {
+    fn matches(&self, c: char, case_insensitive: bool) -> bool {
+        let matches_char = |c: char| {
+            self.items.iter().any(|item| match *item {
+                ClassItem::Range(start, end) => start <= c && c <= end,
+                ClassItem::Digit(negated) => c.is_ascii_digit() != negated,
+                ClassItem::Word(negated) => is_word_char(c) != negated,
+                ClassItem::Space(negated) => c.is_whitespace() != negated,
+            })
+        };
+        let found = matches_char(c)
+            || case_insensitive
+                && (c.to_lowercase().any(|c| matches_char(c))
+                    || c.to_uppercase().any(|c| matches_char(c)));
+        found != self.negated
+    }
+}
+
+fn compile(node: &Node, prog: &mut Vec<Inst>, case_insensitive: bool) {
+    match node {
+        Node::Empty => (),
+        Node::Char(c) => prog.push(Inst::Char(if case_insensitive {
+            fold_case(*c)
+        } else {
+            *c
+        })),
+        Node::Any => prog.push(Inst::Any),
+        Node::Class(class) => prog.push(Inst::Class(class.clone())),
+        Node::Assert(assert) => prog.push(Inst::Assert(*assert)),
+        Node::Group(node, index) => {
+            if let Some(index) = index {
+                prog.push(Inst::Save(index * 2));
+            }
+            compile(node, prog, case_insensitive);
+            if let Some(index) = index {
+                prog.push(Inst::Save(index * 2 + 1));
+            }
+        }
+        Node::Concat(nodes) => {
+            for node in nodes {
+                compile(node, prog, case_insensitive);
+            }
+        }
+        Node::Alt(nodes) => {
+            let mut jumps = Vec::new();
+            for (i, node) in nodes.iter().enumerate() {
+                if i + 1 == nodes.len() {
+                    compile(node, prog, case_insensitive);
+                    break;
+                }
+                let split = prog.len();
+                prog.push(Inst::Split(split + 1, 0));
+                compile(node, prog, case_insensitive);
+                jumps.push(prog.len());
+                prog.push(Inst::Jmp(0));
+                let next = prog.len();
+                prog[split] = Inst::Split(split + 1, next);
+            }
+            let end = prog.len();
+            for jump in jumps {
+                prog[jump] = Inst::Jmp(end);
+            }
+        }
+        Node::Repeat {
+            node,
+            min,
+            max,
+            greedy,
+        } => {
+            let split = |body, out| {
+                if *greedy {
+                    Inst::Split(body, out)
+                } else {
+                    Inst::Split(out, body)
+                }
+            };
+            for _ in 0..*min {
+                compile(node, prog, case_insensitive);
+            }
+            match max {
+                None => {
+                    let start = prog.len();
+                    prog.push(Inst::Jmp(0));
+                    compile(node, prog, case_insensitive);
+                    prog.push(Inst::Jmp(start));
+                    let end = prog.len();
+                    prog[start] = split(start + 1, end);
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(prog.len());
+                        prog.push(Inst::Jmp(0));
+                        compile(node, prog, case_insensitive);
+                    }
+                    let end = prog.len();
+                    for at in splits {
+                        prog[at] = split(at + 1, end);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    group_count: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_alt(&mut self) -> Result<Node, String> {
+        let mut nodes = vec![self.parse_concat()?];
+        while self.chars.peek() == Some(&'|') {
+            self.chars.next();
+            nodes.push(self.parse_concat()?);
+        }
+        Ok(if nodes.len() == 1 {
+            nodes.pop().unwrap()
+        } else {
+            Node::Alt(nodes)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, String> {
+        let mut nodes = Vec::new();
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_repeat(atom)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, String> {
+        match self.chars.next().unwrap() {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assert::LineStart)),
+            '$' => Ok(Node::Assert(Assert::LineEnd)),
+            '[' => self.parse_class(),
+            '(' => {
+                let index = if self.chars.peek() == Some(&'?') {
+                    self.chars.next();
+                    if self.chars.next() != Some(':') {
+                        return Err("only (?: groups are supported".to_string());
+                    }
+                    None
+                } else {
+                    self.group_count += 1;
+                    Some(self.group_count - 1)
+                };
+                let node = self.parse_alt()?;
+                if self.chars.next() != Some(')') {
+                    return Err("missing )".to_string());
+                }
+                Ok(Node::Group(Box::new(node), index))
+            }
+            '\\' => match self.chars.next() {
+                Some('b') => Ok(Node::Assert(Assert::WordBoundary)),
+                Some('B') => Ok(Node::Assert(Assert::NotWordBoundary)),
+                Some(c) => Ok(match escape_class(c) {
+                    Some(item) => Node::Class(Class {
+                        negated: false,
+                        items: vec![item],
+                    }),
+                    None => Node::Char(escape_char(c)),
+                }),
+                None => Err("trailing \\".to_string()),
+            },
+            c @ ('*' | '+' | '?') => Err(format!("nothing to repeat before `{}`", c)),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, String> {
+        let negated = if self.chars.peek() == Some(&'^') {
+            self.chars.next();
+            true
+        } else {
+            false
+        };
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = match self.chars.next() {
+                Some(']') if !first => break,
+                Some(c) => c,
+                None => return Err("missing ]".to_string()),
+            };
+            first = false;
+            let start = if c == '\\' {
+                let c = self.chars.next().ok_or("trailing \\")?;
+                if let Some(item) = escape_class(c) {
+                    items.push(item);
+                    continue;
+                }
+                escape_char(c)
+            } else {
+                c
+            };
+            let mut lookahead = self.chars.clone();
+            if lookahead.next() == Some('-') && lookahead.peek().map_or(false, |c| *c != ']') {
+                self.chars.next();
+                let end = match self.chars.next().unwrap() {
+                    '\\' => escape_char(self.chars.next().ok_or("trailing \\")?),
+                    c => c,
+                };
+                if end < start {
+                    return Err(format!("invalid range {}-{}", start, end));
+                }
+                items.push(ClassItem::Range(start, end));
+            } else {
+                items.push(ClassItem::Range(start, start));
+            }
+        }
+        Ok(Node::Class(Class { negated, items }))
+    }
+
+    fn parse_repeat(&mut self, atom: Node) -> Result<Node, String> {
+        let mut node = atom;
+        loop {
+            let (min, max) = match self.chars.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => {
+                    // a { that doesn't start a valid count is a literal
+                    let mut lookahead = self.chars.clone();
+                    lookahead.next();
+                    let mut body = String::new();
+                    let mut closed = false;
+                    for c in lookahead.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        body.push(c);
+                    }
+                    let parse = |s: &str| s.trim().parse::<usize>().ok();
+                    let range = if !closed {
+                        None
+                    } else {
+                        match body.split_once(',') {
+                            Some((min, "")) => parse(min).map(|min| (min, None)),
+                            Some((min, max)) => parse(min)
+                                .zip(parse(max))
+                                .map(|(min, max)| (min, Some(max))),
+                            None => parse(&body).map(|n| (n, Some(n))),
+                        }
+                    };
+                    match range {
+                        Some((min, max)) => {
+                            if min > REPEAT_MAX
+                                || max.map_or(false, |max| max > REPEAT_MAX || max < min)
+                            {
+                                return Err(format!("invalid repeat {{{}}}", body));
+                            }
+                            self.chars = lookahead;
+                            let greedy = !self.eat_lazy();
+                            node = Node::Repeat {
+                                node: Box::new(node),
+                                min,
+                                max,
+                                greedy,
+                            };
+                            continue;
+                        }
+                        None => return Ok(node),
+                    }
+                }
+                _ => return Ok(node),
+            };
+            self.chars.next();
+            let greedy = !self.eat_lazy();
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+    }
+
+    fn eat_lazy(&mut self) -> bool {
+        if self.chars.peek() == Some(&'?') {
+            self.chars.next();
+            return true;
+        }
+        false
+    }
+}
+
+fn escape_class(c: char) -> Option<ClassItem> {
+    match c {
+        'd' => Some(ClassItem::Digit(false)),
+        'D' => Some(ClassItem::Digit(true)),
+        'w' => Some(ClassItem::Word(false)),
+        'W' => Some(ClassItem::Word(true)),
+        's' => Some(ClassItem::Space(false)),
+        'S' => Some(ClassItem::Space(true)),
+        _ => None,
+    }
+}
+
+fn escape_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, text: &str) -> Option<String> {
+        let regex = Regex::new(pattern, false).unwrap();
+        regex
+            .find_at(text, 0)
+            .map(|captures| text[captures.range()].to_string())
+    }
+
+    #[test]
+    fn matches() {
+        assert_eq!(find("b+c", "abbbcd").as_deref(), Some("bbbc"));
+        assert_eq!(find("a.*?c", "abcabc").as_deref(), Some("abc"));
+        assert_eq!(find("a.*c", "abcabc").as_deref(), Some("abcabc"));
+        assert_eq!(find("[0-9]{2,3}", "a12345").as_deref(), Some("123"));
+        assert_eq!(find("\\bfn\\b", "often fn x").as_deref(), Some("fn"));
+        assert_eq!(find("^x|y$", "xay").as_deref(), Some("x"));
+        assert_eq!(find("(a|ab)(c|bcd)", "abcd").as_deref(), Some("abcd"));
+        assert_eq!(find("[^a-c\\d]+", "ab12xyz").as_deref(), Some("xyz"));
+        assert_eq!(find("(a*)*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaac"), None);
+        assert_eq!(find("x{1", "ax{1").as_deref(), Some("x{1"));
+        assert!(Regex::new("(a", false).is_err());
+        assert!(Regex::new("*a", false).is_err());
+    }
+
+    #[test]
+    fn captures_and_case() {
+        let regex = Regex::new("(\\w+)=(\\d+)", false).unwrap();
+        let captures = regex.find_at("let a=12, b=3", 0).unwrap();
+        assert_eq!(captures.get(1), Some(4..5));
+        assert_eq!(captures.get(2), Some(6..8));
+        assert_eq!(regex.find_iter("a=1 b=2 c").count(), 2);
+
+        let regex = Regex::new("straße[a-z]", true).unwrap();
+        assert!(regex.find_at("STRASSE STRAßEX", 0).is_some());
+        assert_eq!(
+            Regex::new(&Regex::escape("a.b(c)"), false)
+                .unwrap()
+                .find_at("axb(c) a.b(c)", 0)
+                .unwrap()
+                .range(),
+            7..13
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::regex::{Captures, Regex};
+
+/// What to look for, as typed in a find bar.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub is_regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+}
+
+impl SearchQuery {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn compile(&self) -> Result<Regex, String> {
+        let pattern = if self.is_regex {
+            self.text.clone()
+        } else {
+            Regex::escape(&self.text)
+        };
+        let pattern = if self.whole_word {
+            format!("\\b(?:{})\\b", pattern)
+        } else {
+            pattern
+        };
+        Regex::new(&pattern, !self.case_sensitive)
+    }
+
+    /// The text that replaces a match. In regex mode `$0` to `$9` insert the groups of the match
+    /// and `$$` inserts a `$`, otherwise the replacement is used as is.
+    pub fn replacement(&self, replacement: &str, line: &str, captures: &Captures) -> String {
+        if self.is_regex {
+            expand_replacement(replacement, line, captures)
+        } else {
+            replacement.to_string()
+        }
+    }
+}
+
+pub fn expand_replacement(replacement: &str, line: &str, captures: &Captures) -> String {
+    let mut string = String::new();
+    let mut chars = replacement.chars().peekable();
+    while let Some(char) = chars.next() {
+        if char == '$' {
+            match chars.peek() {
+                Some('$') => {
+                    chars.next();
+                    string.push('$');
+                    continue;
+                }
+                Some(digit) if digit.is_ascii_digit() => {
+                    let index = digit.to_digit(10).unwrap() as usize;
+                    chars.next();
+                    if let Some(range) = captures.get(index) {
+                        string.push_str(&line[range]);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        string.push(char);
+    }
+    string
+}
@@ -1,9 +1,13 @@
 use {
     crate::{
         char::CharExt,
+        decoration::{Decoration, DecorationType},
         document::Document,
         history::EditKind,
+        inlays::{BlockInlay, InlineInlay},
         layout::{BlockElement, Layout, WrappedElement},
+        regex::Regex,
+        search::SearchQuery,
         selection::{Affinity, Cursor, SelectionSet},
         str::StrExt,
         text::{Change, Drift, Edit, Length, Position, Text},
@@ -13,9 +17,11 @@ use {
     },
     std::{
         cell::{Cell, Ref, RefCell},
+        cmp::Ordering,
         collections::HashSet,
         fmt::Write,
         iter, mem,
+        ops::Range,
         rc::Rc,
         sync::{atomic, atomic::AtomicUsize, mpsc, mpsc::Receiver},
     },
@@ -30,6 +36,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<SearchState>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -49,6 +56,8 @@ impl Session {
                 fold_column: (0..line_count).map(|_| 0).collect(),
                 scale: (0..line_count).map(|_| 1.0).collect(),
                 wrap_data: (0..line_count).map(|_| None).collect(),
+                inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
+                block_inlays: Vec::new(),
             }),
             selection_state: RefCell::new(SelectionState {
                 mode: SelectionMode::Simple,
@@ -63,6 +72,7 @@ impl Session {
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
             }),
+            search_state: RefCell::new(SearchState::default()),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -113,6 +123,33 @@ impl Session {
         })
     }
 
+    pub fn search_query(&self) -> Ref<'_, SearchQuery> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            &search_state.query
+        })
+    }
+
+    pub fn search_matches(&self) -> Ref<'_, [Decoration]> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            search_state.matches.as_slice()
+        })
+    }
+
+    /// Returns the index of the search match that the last added selection covers exactly, if
+    /// any.
+    pub fn selected_search_match_index(&self) -> Option<usize> {
+        let selection = self.selections()[self.last_added_selection_index()?];
+        let search_state = self.search_state.borrow();
+        let index = search_state
+            .matches
+            .binary_search_by_key(&selection.start(), |search_match| search_match.start())
+            .ok()?;
+        if search_state.matches[index].end() != selection.end() {
+            return None;
+        }
+        Some(index)
+    }
+
     pub fn set_wrap_column(&self, wrap_column: Option<usize>) {
         if self.wrap_column.get() == wrap_column {
             return;
@@ -746,6 +783,14 @@ impl Session {
         );
     }
 
+    pub fn apply_changes(&self, changes: Vec<Change>) {
+        self.document.edit_changes(
+            self.id,
+            &self.selection_state.borrow().selections,
+            changes,
+        );
+    }
+
     pub fn copy(&self) -> String {
         let mut string = String::new();
         for selection in &self.selection_state.borrow().selections {
@@ -779,7 +824,197 @@ impl Session {
             .redo(self.id, &self.selection_state.borrow().selections)
     }
 
+    /// Highlights every match of the query, and keeps the matches up to date as the document
+    /// changes. An empty query clears the matches. If the query is not a valid regex the matches
+    /// are cleared as well, and the error is returned.
+    pub fn set_search_query(&self, query: SearchQuery) -> Result<(), String> {
+        let regex = if query.is_empty() {
+            Ok(None)
+        } else {
+            query.compile().map(Some)
+        };
+        let mut search_state = self.search_state.borrow_mut();
+        search_state.query = query;
+        search_state.regex = regex.clone().unwrap_or(None);
+        drop(search_state);
+        self.update_search_matches();
+        regex.map(|_| ())
+    }
+
+    /// Selects the first search match after the last added selection, wrapping around at the end
+    /// of the document.
+    pub fn find_next(&self) -> bool {
+        let position = self.selections()[self.last_added_selection_index().unwrap_or(0)].end();
+        let search_state = self.search_state.borrow();
+        let index = search_state
+            .matches
+            .iter()
+            .position(|search_match| search_match.start() >= position)
+            .or((!search_state.matches.is_empty()).then_some(0));
+        drop(search_state);
+        match index {
+            Some(index) => {
+                self.select_search_match(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Selects the last search match before the last added selection, wrapping around at the
+    /// start of the document.
+    pub fn find_previous(&self) -> bool {
+        let position = self.selections()[self.last_added_selection_index().unwrap_or(0)].start();
+        let search_state = self.search_state.borrow();
+        let index = search_state
+            .matches
+            .iter()
+            .rposition(|search_match| search_match.start() < position)
+            .or(search_state.matches.len().checked_sub(1));
+        drop(search_state);
+        match index {
+            Some(index) => {
+                self.select_search_match(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Replaces the selections with one selection for every search match.
+    pub fn select_all_search_matches(&self) -> bool {
+        let search_state = self.search_state.borrow();
+        let Some((first_match, other_matches)) = search_state.matches.split_first() else {
+            return false;
+        };
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        selection_state
+            .selections
+            .set_selection(search_match_selection(*first_match));
+        for &search_match in other_matches {
+            selection_state
+                .selections
+                .add_selection(search_match_selection(search_match));
+        }
+        selection_state.last_added_selection_index = Some(selection_state.selections.len() - 1);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        drop(search_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+        true
+    }
+
+    /// Replaces the selected search match and selects the next one. Does nothing if the last
+    /// added selection is not a search match.
+    pub fn replace_search_match(&self, replacement: &str) -> bool {
+        let Some(index) = self.selected_search_match_index() else {
+            return false;
+        };
+        let search_match = self.search_state.borrow().matches[index];
+        let text = Text::from(self.search_replacement(search_match, replacement));
+        let position = search_match.start() + text.length();
+        self.apply_changes(vec![
+            Change::Delete(search_match.start(), search_match.length()),
+            Change::Insert(search_match.start(), text),
+        ]);
+        self.receive_edits();
+        self.set_selection(position, Affinity::Before, SelectionMode::Simple);
+        self.find_next();
+        true
+    }
+
+    /// Replaces every search match, as a single step in the history. Returns the number of
+    /// replaced matches.
+    pub fn replace_all_search_matches(&self, replacement: &str) -> usize {
+        let matches = self.search_state.borrow().matches.clone();
+        if matches.is_empty() {
+            return 0;
+        }
+        // Last match first, so that the positions of the other matches stay valid.
+        let mut changes = Vec::new();
+        for &search_match in matches.iter().rev() {
+            let text = self.search_replacement(search_match, replacement);
+            changes.push(Change::Delete(search_match.start(), search_match.length()));
+            changes.push(Change::Insert(search_match.start(), text.into()));
+        }
+        self.document.force_new_group();
+        self.apply_changes(changes);
+        self.document.force_new_group();
+        self.receive_edits();
+        matches.len()
+    }
+
+    /// Adds an inline inlay at the given position, after the inlays that are already there.
+    /// Inlays belong to this session only, other sessions of the document don't show them.
+    pub fn add_inline_inlay(&self, position: Position, inlay: InlineInlay) {
+        self.receive_edits();
+        let mut layout = self.layout.borrow_mut();
+        let inline_inlays = &mut layout.inline_inlays[position.line_index];
+        let index = inline_inlays
+            .iter()
+            .position(|&(byte_index, _)| byte_index > position.byte_index)
+            .unwrap_or(inline_inlays.len());
+        inline_inlays.insert(index, (position.byte_index, inlay));
+        drop(layout);
+        self.update_after_inlays_changed(position.line_index..position.line_index + 1);
+    }
+
+    /// Adds a block inlay above the line with the given index, after the inlays that are already
+    /// there. Use the line count as index to add it below the last line.
+    pub fn add_block_inlay(&self, line_index: usize, inlay: BlockInlay) {
+        self.receive_edits();
+        let mut layout = self.layout.borrow_mut();
+        let index = layout
+            .block_inlays
+            .iter()
+            .position(|&(other_line_index, _)| other_line_index > line_index)
+            .unwrap_or(layout.block_inlays.len());
+        layout.block_inlays.insert(index, (line_index, inlay));
+        drop(layout);
+        self.update_after_inlays_changed(line_index..line_index);
+    }
+
+    /// Removes the inline and block inlays of the widgets with the given id.
+    pub fn remove_widget_inlays(&self, id: usize) {
+        self.receive_edits();
+        let mut layout = self.layout.borrow_mut();
+        let mut line_range: Option<Range<usize>> = None;
+        let mut extend_line_range = |start: usize, end: usize| {
+            line_range = Some(match line_range.take() {
+                Some(line_range) => line_range.start.min(start)..line_range.end.max(end),
+                None => start..end,
+            });
+        };
+        for (line_index, inline_inlays) in layout.inline_inlays.iter_mut().enumerate() {
+            let len = inline_inlays.len();
+            inline_inlays.retain(
+                |(_, inlay)| !matches!(inlay, InlineInlay::Widget(widget) if widget.id == id),
+            );
+            if inline_inlays.len() != len {
+                extend_line_range(line_index, line_index + 1);
+            }
+        }
+        layout.block_inlays.retain(|&(line_index, ref inlay)| {
+            let BlockInlay::Widget(widget) = inlay;
+            if widget.id == id {
+                extend_line_range(line_index, line_index);
+                return false;
+            }
+            true
+        });
+        drop(layout);
+        if let Some(line_range) = line_range {
+            self.update_after_inlays_changed(line_range);
+        }
+    }
+
     pub fn handle_changes(&mut self) {
+        self.receive_edits();
+    }
+
+    fn receive_edits(&self) {
         while let Ok((selections, edits)) = self.edit_receiver.try_recv() {
             self.update_after_edit(selections, &edits);
         }
@@ -865,6 +1100,8 @@ impl Session {
                     }
                 }
             }
+            self.apply_change_to_inline_inlays(&edit.change, edit.drift);
+            self.apply_change_to_block_inlays(&edit.change);
         }
         let line_count = self.document.as_text().as_lines().len();
         for line in 0..line_count {
@@ -886,6 +1123,113 @@ impl Session {
         }
         drop(selection_state);
         self.update_highlighted_delimiter_positions();
+        self.update_search_matches();
+    }
+
+    fn update_after_inlays_changed(&self, line_range: Range<usize>) {
+        let line_count = self.document.as_text().as_lines().len();
+        self.layout.borrow_mut().y.truncate(line_range.start);
+        for line in line_range.start..line_range.end.min(line_count) {
+            self.update_wrap_data(line);
+        }
+        self.update_y();
+    }
+
+    fn apply_change_to_inline_inlays(&self, change: &Change, drift: Drift) {
+        let mut layout = self.layout.borrow_mut();
+        let inline_inlays = &mut layout.inline_inlays;
+        match *change {
+            Change::Insert(point, ref text) => {
+                let index = inline_inlays[point.line_index]
+                    .iter()
+                    .position(|(byte, _)| match byte.cmp(&point.byte_index) {
+                        Ordering::Less => false,
+                        Ordering::Equal => match drift {
+                            Drift::Before => true,
+                            Drift::After => false,
+                        },
+                        Ordering::Greater => true,
+                    })
+                    .unwrap_or(inline_inlays[point.line_index].len());
+                if text.length().line_count == 0 {
+                    for (byte, _) in &mut inline_inlays[point.line_index][index..] {
+                        *byte += text.length().byte_count;
+                    }
+                } else {
+                    let mut new_inline_inlays = (0..text.as_lines().len())
+                        .map(|_| Vec::new())
+                        .collect::<Vec<_>>();
+                    new_inline_inlays
+                        .first_mut()
+                        .unwrap()
+                        .splice(..0, inline_inlays[point.line_index].drain(..index));
+                    new_inline_inlays.last_mut().unwrap().splice(
+                        ..0,
+                        inline_inlays[point.line_index]
+                            .drain(..)
+                            .map(|(byte, inline_inlay)| {
+                                (byte + text.length().byte_count, inline_inlay)
+                            }),
+                    );
+                    inline_inlays.splice(point.line_index..point.line_index + 1, new_inline_inlays);
+                }
+            }
+            Change::Delete(start, length) => {
+                let end = start + length;
+                let start_inlay = inline_inlays[start.line_index]
+                    .iter()
+                    .position(|&(byte, _)| byte >= start.byte_index)
+                    .unwrap_or(inline_inlays[start.line_index].len());
+                let end_inlay = inline_inlays[end.line_index]
+                    .iter()
+                    .position(|&(byte, _)| byte >= end.byte_index)
+                    .unwrap_or(inline_inlays[end.line_index].len());
+                if length.line_count == 0 {
+                    inline_inlays[start.line_index].drain(start_inlay..end_inlay);
+                    for (byte, _) in &mut inline_inlays[start.line_index][start_inlay..] {
+                        *byte = start.byte_index + (*byte - end.byte_index.min(*byte));
+                    }
+                } else {
+                    let mut new_inline_inlays = inline_inlays[start.line_index]
+                        .drain(..start_inlay)
+                        .collect::<Vec<_>>();
+                    new_inline_inlays.extend(inline_inlays[end.line_index].drain(end_inlay..).map(
+                        |(byte, inline_inlay)| {
+                            (
+                                start.byte_index + byte - end.byte_index.min(byte),
+                                inline_inlay,
+                            )
+                        },
+                    ));
+                    inline_inlays.splice(
+                        start.line_index..end.line_index + 1,
+                        iter::once(new_inline_inlays),
+                    );
+                }
+            }
+        }
+    }
+
+    fn apply_change_to_block_inlays(&self, change: &Change) {
+        let mut layout = self.layout.borrow_mut();
+        for (line_index, _) in &mut layout.block_inlays {
+            match *change {
+                Change::Insert(point, ref text) => {
+                    if *line_index > point.line_index {
+                        *line_index += text.length().line_count;
+                    }
+                }
+                Change::Delete(start, length) => {
+                    // inlays above deleted lines end up above the line after the deletion
+                    if *line_index > start.line_index {
+                        *line_index = start.line_index
+                            + 1
+                            + (*line_index - start.line_index - 1)
+                                .saturating_sub(length.line_count);
+                    }
+                }
+            }
+        }
     }
 
     fn update_y(&self) {
@@ -977,6 +1321,63 @@ impl Session {
         }
         selection_state.highlighted_delimiter_positions = highlighted_delimiter_positions;
     }
+
+    fn update_search_matches(&self) {
+        let mut search_state = self.search_state.borrow_mut();
+        let SearchState { regex, matches, .. } = &mut *search_state;
+        matches.clear();
+        let Some(regex) = regex else {
+            return;
+        };
+        for (line_index, line) in self.document.as_text().as_lines().iter().enumerate() {
+            for captures in regex.find_iter(line) {
+                let range = captures.range();
+                if range.is_empty() {
+                    continue;
+                }
+                matches.push(Decoration::new(
+                    matches.len(),
+                    Position {
+                        line_index,
+                        byte_index: range.start,
+                    },
+                    Position {
+                        line_index,
+                        byte_index: range.end,
+                    },
+                    DecorationType::SearchMatch,
+                ));
+            }
+        }
+    }
+
+    fn select_search_match(&self, index: usize) {
+        let search_match = self.search_state.borrow().matches[index];
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.mode = SelectionMode::Simple;
+        selection_state
+            .selections
+            .set_selection(search_match_selection(search_match));
+        selection_state.last_added_selection_index = Some(0);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+    }
+
+    fn search_replacement(&self, search_match: Decoration, replacement: &str) -> String {
+        let search_state = self.search_state.borrow();
+        let text = self.document.as_text();
+        let line = &text.as_lines()[search_match.start().line_index];
+        match search_state
+            .regex
+            .as_ref()
+            .and_then(|regex| regex.find_at(line, search_match.start().byte_index))
+        {
+            Some(captures) => search_state.query.replacement(replacement, line, &captures),
+            None => replacement.to_string(),
+        }
+    }
 }
 
 impl Drop for Session {
@@ -995,6 +1396,8 @@ pub struct SessionLayout {
     pub fold_column: Vec<usize>,
     pub scale: Vec<f64>,
     pub wrap_data: Vec<Option<WrapData>>,
+    pub inline_inlays: Vec<Vec<(usize, InlineInlay)>>,
+    pub block_inlays: Vec<(usize, BlockInlay)>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -1021,6 +1424,13 @@ struct FoldState {
     unfolding_lines: HashSet<usize>,
 }
 
+#[derive(Debug, Default)]
+struct SearchState {
+    query: SearchQuery,
+    regex: Option<Regex>,
+    matches: Vec<Decoration>,
+}
+
 pub fn reindent(string: &str, f: impl FnOnce(usize) -> usize) -> (usize, usize, String) {
     let indentation = string.indent().unwrap_or("");
     let indentation_column_count = indentation.column_count();
@@ -1155,6 +1565,17 @@ fn grow_selection(
     }
 }
 
+fn search_match_selection(search_match: Decoration) -> Selection {
+    Selection {
+        anchor: search_match.start(),
+        cursor: Cursor {
+            position: search_match.end(),
+            affinity: Affinity::Before,
+            preferred_column_index: None,
+        },
+    }
+}
+
 fn new_indentation(column_count: usize) -> String {
     iter::repeat(' ').take(column_count).collect()
 }
@@ -1297,3 +1718,159 @@ fn find_closing_delimiter(
         position.byte_index = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{decoration::DecorationSet, widgets::InlineWidget},
+    };
+
+    #[test]
+    fn inlays_are_per_session() {
+        let document = Document::new(Text::from("ab\ncd"), DecorationSet::new());
+        let mut session = Session::new(document.clone());
+        let mut other_session = Session::new(document);
+        let inlay = InlineInlay::Widget(InlineWidget {
+            id: 1,
+            column_count: 3,
+        });
+        session.add_inline_inlay(
+            Position {
+                line_index: 1,
+                byte_index: 1,
+            },
+            inlay.clone(),
+        );
+        assert_eq!(session.layout().session_layout.inline_inlays[1].len(), 1);
+        assert!(other_session.layout().session_layout.inline_inlays[1].is_empty());
+
+        // the inlay moves along with edits made in other sessions
+        other_session.insert(Text::from("x\n"));
+        other_session.handle_changes();
+        session.handle_changes();
+        assert_eq!(
+            session.layout().session_layout.inline_inlays[2],
+            [(1, inlay)]
+        );
+        session.remove_widget_inlays(1);
+        assert!(session
+            .layout()
+            .session_layout
+            .inline_inlays
+            .iter()
+            .all(|inline_inlays| inline_inlays.is_empty()));
+    }
+
+    fn search_session(text: &str, query: &str, is_regex: bool) -> Session {
+        let session = Session::new(Document::new(Text::from(text), DecorationSet::new()));
+        session
+            .set_search_query(SearchQuery {
+                text: query.to_string(),
+                is_regex,
+                ..SearchQuery::default()
+            })
+            .unwrap();
+        session
+    }
+
+    fn position(line_index: usize, byte_index: usize) -> Position {
+        Position {
+            line_index,
+            byte_index,
+        }
+    }
+
+    fn selected_range(session: &Session) -> (Position, Position) {
+        let selection = session.selections()[session.last_added_selection_index().unwrap()];
+        (selection.start(), selection.end())
+    }
+
+    #[test]
+    fn find_wraps_around() {
+        let session = search_session("ab\nxab\nab", "ab", false);
+        assert_eq!(session.search_matches().len(), 3);
+        session.set_selection(position(1, 3), Affinity::Before, SelectionMode::Simple);
+        assert!(session.find_next());
+        assert_eq!(selected_range(&session), (position(2, 0), position(2, 2)));
+        // past the last match find_next goes back to the first one
+        assert!(session.find_next());
+        assert_eq!(selected_range(&session), (position(0, 0), position(0, 2)));
+        // and before the first one find_previous goes to the last one
+        assert!(session.find_previous());
+        assert_eq!(selected_range(&session), (position(2, 0), position(2, 2)));
+        assert!(session.find_previous());
+        assert_eq!(selected_range(&session), (position(1, 1), position(1, 3)));
+
+        let session = search_session("ab", "cd", false);
+        assert!(!session.find_next());
+        assert!(!session.find_previous());
+    }
+
+    #[test]
+    fn empty_regex_matches_are_skipped() {
+        // x* matches the empty string at every position, only the runs of x are kept
+        let session = search_session("axxb\n\nx", "x*", true);
+        let matches: Vec<_> = session
+            .search_matches()
+            .iter()
+            .map(|search_match| (search_match.start(), search_match.end()))
+            .collect();
+        assert_eq!(
+            matches,
+            [
+                (position(0, 1), position(0, 3)),
+                (position(2, 0), position(2, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_expands_groups() {
+        let session = search_session("let a = b;\nlet cd = ef;", "(\\w+) = (\\w+)", true);
+        session.set_selection(position(0, 0), Affinity::Before, SelectionMode::Simple);
+        assert!(session.find_next());
+        assert!(session.replace_search_match("$2 = $1 $$0"));
+        assert_eq!(
+            session.document().as_text().to_string(),
+            "let b = a $0;\nlet cd = ef;"
+        );
+        // the next match is selected after a replace
+        assert_eq!(selected_range(&session), (position(1, 4), position(1, 11)));
+    }
+
+    #[test]
+    fn replace_all_is_undone_at_once() {
+        let mut session = search_session("a a\na", "a", false);
+        session.insert(Text::from("x"));
+        session.handle_changes();
+        assert_eq!(session.document().as_text().to_string(), "xa a\na");
+        assert_eq!(session.replace_all_search_matches("bb"), 3);
+        assert_eq!(session.document().as_text().to_string(), "xbb bb\nbb");
+        assert!(session.undo());
+        assert_eq!(session.document().as_text().to_string(), "xa a\na");
+        // the edit before it is a group of its own
+        assert!(session.undo());
+        assert_eq!(session.document().as_text().to_string(), "a a\na");
+    }
+
+    #[test]
+    fn select_all_selects_every_match() {
+        let session = search_session("a ab\nb a", "a", false);
+        assert!(session.select_all_search_matches());
+        let selections: Vec<_> = session
+            .selections()
+            .iter()
+            .map(|selection| (selection.start(), selection.end()))
+            .collect();
+        assert_eq!(
+            selections,
+            [
+                (position(0, 0), position(0, 1)),
+                (position(0, 2), position(0, 3)),
+                (position(1, 2), position(1, 3))
+            ]
+        );
+        assert_eq!(session.selected_search_match_index(), Some(2));
+    }
+}
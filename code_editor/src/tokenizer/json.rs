@@ -0,0 +1,185 @@
+use {
+    super::{Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
+};
+
+/// JSON, with the comments JSONC allows.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum JsonState {
+    #[default]
+    Initial,
+    BlockComment,
+}
+
+impl TokenizerState for JsonState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        if cursor.peek(0) == '\0' {
+            return (self, None);
+        }
+        let start = cursor.index;
+        let (next_state, kind) = match self {
+            JsonState::Initial => initial(cursor),
+            JsonState::BlockComment => block_comment(cursor),
+        };
+        (
+            next_state,
+            Some(Token {
+                len: cursor.index - start,
+                kind,
+            }),
+        )
+    }
+}
+
+fn initial(cursor: &mut Cursor) -> (JsonState, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_line();
+            (JsonState::Initial, TokenKind::Comment)
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            block_comment(cursor)
+        }
+        ('"', _) => {
+            cursor.skip(1);
+            loop {
+                match cursor.peek(0) {
+                    '"' => {
+                        cursor.skip(1);
+                        break;
+                    }
+                    '\0' => break,
+                    '\\' => cursor.skip(2),
+                    _ => cursor.skip(1),
+                }
+            }
+            // a string followed by a colon is a key
+            let kind = if cursor.rest().trim_start().starts_with(':') {
+                TokenKind::Identifier
+            } else {
+                TokenKind::String
+            };
+            (JsonState::Initial, kind)
+        }
+        ('-', char) | (char, _) if char.is_ascii_digit() => {
+            cursor.skip_if(|char| char == '-');
+            while cursor.skip_if(|char| char.is_ascii_digit() || char == '.') {}
+            if cursor.skip_if(|char| char == 'e' || char == 'E') {
+                cursor.skip_if(|char| char == '+' || char == '-');
+                while cursor.skip_if(|char| char.is_ascii_digit()) {}
+            }
+            (JsonState::Initial, TokenKind::Number)
+        }
+        ('{', _) | ('}', _) | ('[', _) | (']', _) => {
+            cursor.skip(1);
+            (JsonState::Initial, TokenKind::Delimiter)
+        }
+        (':', _) | (',', _) => {
+            cursor.skip(1);
+            (JsonState::Initial, TokenKind::Punctuator)
+        }
+        (char, _) if char.is_whitespace() => {
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (JsonState::Initial, TokenKind::Whitespace)
+        }
+        (char, _) if char.is_ascii_alphabetic() => {
+            let start = cursor.index;
+            while cursor.skip_if(|char| char.is_ascii_alphanumeric()) {}
+            let kind = match &cursor.string[start..cursor.index] {
+                "true" | "false" | "null" => TokenKind::Constant,
+                _ => TokenKind::Unknown,
+            };
+            (JsonState::Initial, kind)
+        }
+        _ => {
+            cursor.skip(1);
+            (JsonState::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn block_comment(cursor: &mut Cursor) -> (JsonState, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('*', '/') => {
+                cursor.skip(2);
+                break (JsonState::Initial, TokenKind::Comment);
+            }
+            ('\0', _) => break (JsonState::BlockComment, TokenKind::Comment),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tokenizer::tests::{delete, insert, retokenize, tokenize},
+    };
+
+    #[test]
+    fn tokens() {
+        let lines = tokenize::<JsonState>("{\"a\" : [-1.5e3, true, null], \"b\": \"c\\\"\"} // d");
+        assert_eq!(
+            lines[0].0,
+            [
+                ("{", TokenKind::Delimiter),
+                ("\"a\"", TokenKind::Identifier),
+                (":", TokenKind::Punctuator),
+                ("[", TokenKind::Delimiter),
+                ("-1.5e3", TokenKind::Number),
+                (",", TokenKind::Punctuator),
+                ("true", TokenKind::Constant),
+                (",", TokenKind::Punctuator),
+                ("null", TokenKind::Constant),
+                ("]", TokenKind::Delimiter),
+                (",", TokenKind::Punctuator),
+                ("\"b\"", TokenKind::Identifier),
+                (":", TokenKind::Punctuator),
+                ("\"c\\\"\"", TokenKind::String),
+                ("}", TokenKind::Delimiter),
+                ("// d", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(lines[0].1, JsonState::Initial);
+        assert_eq!(
+            tokenize::<JsonState>("nope")[0].0,
+            [("nope", TokenKind::Unknown)]
+        );
+    }
+
+    #[test]
+    fn block_comments() {
+        let lines = tokenize::<JsonState>("1 /* a\n\"b\"\n*/ 2");
+        assert_eq!(
+            lines[0].0,
+            [("1", TokenKind::Number), ("/* a", TokenKind::Comment)]
+        );
+        assert_eq!(lines[0].1, JsonState::BlockComment);
+        assert_eq!(lines[1].0, [("\"b\"", TokenKind::Comment)]);
+        assert_eq!(lines[1].1, JsonState::BlockComment);
+        assert_eq!(
+            lines[2].0,
+            [("*/", TokenKind::Comment), ("2", TokenKind::Number)]
+        );
+        assert_eq!(lines[2].1, JsonState::Initial);
+    }
+
+    #[test]
+    fn incremental() {
+        assert_eq!(
+            retokenize::<JsonState>("[\n1,\n2\n]", insert(1, 0, "-")),
+            [1]
+        );
+        assert_eq!(
+            retokenize::<JsonState>("/* a */\n1\n2", delete(0, 5, 0, 2)),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            retokenize::<JsonState>("/*\n1\n*/\n2", insert(0, 2, "*/")),
+            [0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use {
+    super::{Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MarkdownState {
+    #[default]
+    Text,
+    // inside a ``` or ~~~ fence of at least this many chars
+    Code {
+        fence: char,
+        len: usize,
+    },
+    HtmlComment,
+}
+
+impl TokenizerState for MarkdownState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        if cursor.peek(0) == '\0' {
+            return (self, None);
+        }
+        let start = cursor.index;
+        let (next_state, kind) = match self {
+            MarkdownState::Text => text(cursor),
+            MarkdownState::Code { fence, len } => code(cursor, fence, len),
+            MarkdownState::HtmlComment => html_comment(cursor),
+        };
+        (
+            next_state,
+            Some(Token {
+                len: cursor.index - start,
+                kind,
+            }),
+        )
+    }
+}
+
+fn fence_len(cursor: &Cursor, fence: char) -> usize {
+    cursor
+        .rest()
+        .chars()
+        .take_while(|char| *char == fence)
+        .count()
+}
+
+fn text(cursor: &mut Cursor) -> (MarkdownState, TokenKind) {
+    if cursor.is_at_line_start() {
+        match cursor.peek(0) {
+            '#' => {
+                cursor.skip_line();
+                return (MarkdownState::Text, TokenKind::Typename);
+            }
+            '>' => {
+                cursor.skip_line();
+                return (MarkdownState::Text, TokenKind::Comment);
+            }
+            fence @ ('`' | '~') if fence_len(cursor, fence) >= 3 => {
+                let len = fence_len(cursor, fence);
+                // the info string after the fence, like rust
+                cursor.skip_line();
+                return (MarkdownState::Code { fence, len }, TokenKind::Punctuator);
+            }
+            '-' | '*' | '+' if cursor.peek(1) == ' ' => {
+                cursor.skip(1);
+                return (MarkdownState::Text, TokenKind::Punctuator);
+            }
+            char if char.is_ascii_digit() => {
+                let digits = cursor
+                    .rest()
+                    .chars()
+                    .take_while(|char| char.is_ascii_digit())
+                    .count();
+                if let '.' | ')' = cursor.peek(digits) {
+                    cursor.skip(digits + 1);
+                    return (MarkdownState::Text, TokenKind::Punctuator);
+                }
+            }
+            _ => {}
+        }
+    }
+    match (cursor.peek(0), cursor.peek(1)) {
+        ('<', '!') if cursor.rest().starts_with("<!--") => {
+            cursor.skip(4);
+            html_comment(cursor)
+        }
+        ('`', _) => {
+            let len = fence_len(cursor, '`');
+            cursor.skip(len);
+            // a code span ends at the same number of backticks, without it they are just text
+            let closing = "`".repeat(len);
+            if let Some(end) = cursor.rest().find(&closing) {
+                let end_chars = cursor.rest()[..end].chars().count();
+                cursor.skip(end_chars + len);
+                (MarkdownState::Text, TokenKind::String)
+            } else {
+                (MarkdownState::Text, TokenKind::Punctuator)
+            }
+        }
+        // the target of a link or image
+        (']', '(') => {
+            cursor.skip(2);
+            while cursor.skip_if(|char| char != ')' && char != '\0') {}
+            cursor.skip_if(|char| char == ')');
+            (MarkdownState::Text, TokenKind::String)
+        }
+        ('[', _) | (']', _) => {
+            cursor.skip(1);
+            (MarkdownState::Text, TokenKind::Delimiter)
+        }
+        (char, _) if char.is_whitespace() => {
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (MarkdownState::Text, TokenKind::Whitespace)
+        }
+        (char, _) if char.is_alphanumeric() => {
+            while cursor.skip_if(|char| char.is_alphanumeric() || char == '\'') {}
+            (MarkdownState::Text, TokenKind::Identifier)
+        }
+        _ => {
+            cursor.skip(1);
+            (MarkdownState::Text, TokenKind::Punctuator)
+        }
+    }
+}
+
+fn code(cursor: &mut Cursor, fence: char, len: usize) -> (MarkdownState, TokenKind) {
+    if cursor.is_at_line_start() {
+        let indent = cursor.rest().len() - cursor.rest().trim_start().len();
+        if indent > 0 {
+            cursor.skip(cursor.rest()[..indent].chars().count());
+            return (MarkdownState::Code { fence, len }, TokenKind::Whitespace);
+        }
+        if fence_len(cursor, fence) >= len
+            && cursor.rest().trim_start_matches(fence).trim().is_empty()
+        {
+            cursor.skip_line();
+            return (MarkdownState::Text, TokenKind::Punctuator);
+        }
+    }
+    cursor.skip_line();
+    (MarkdownState::Code { fence, len }, TokenKind::String)
+}
+
+fn html_comment(cursor: &mut Cursor) -> (MarkdownState, TokenKind) {
+    match cursor.rest().find("-->") {
+        Some(end) => {
+            cursor.skip(cursor.rest()[..end].chars().count() + 3);
+            (MarkdownState::Text, TokenKind::Comment)
+        }
+        None => {
+            cursor.skip_line();
+            (MarkdownState::HtmlComment, TokenKind::Comment)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tokenizer::tests::{delete, insert, retokenize, tokenize},
+    };
+
+    #[test]
+    fn tokens() {
+        let lines = tokenize::<MarkdownState>(
+            "# Title\n- see [the docs](a.md) and ``a`b``\n> quote\n12. one `two",
+        );
+        assert_eq!(lines[0].0, [("# Title", TokenKind::Typename)]);
+        assert_eq!(
+            lines[1].0,
+            [
+                ("-", TokenKind::Punctuator),
+                ("see", TokenKind::Identifier),
+                ("[", TokenKind::Delimiter),
+                ("the", TokenKind::Identifier),
+                ("docs", TokenKind::Identifier),
+                ("](a.md)", TokenKind::String),
+                ("and", TokenKind::Identifier),
+                ("``a`b``", TokenKind::String),
+            ]
+        );
+        assert_eq!(lines[2].0, [("> quote", TokenKind::Comment)]);
+        // a backtick without a closing one is just text
+        assert_eq!(
+            lines[3].0,
+            [
+                ("12.", TokenKind::Punctuator),
+                ("one", TokenKind::Identifier),
+                ("`", TokenKind::Punctuator),
+                ("two", TokenKind::Identifier),
+            ]
+        );
+        assert!(lines.iter().all(|(_, state)| *state == MarkdownState::Text));
+    }
+
+    #[test]
+    fn code_blocks() {
+        let lines = tokenize::<MarkdownState>("````rust\n  let x = 1;\n```\n````\n# a");
+        let code = MarkdownState::Code { fence: '`', len: 4 };
+        assert_eq!(lines[0].0, [("````rust", TokenKind::Punctuator)]);
+        assert_eq!(lines[0].1, code);
+        assert_eq!(lines[1].0, [("let x = 1;", TokenKind::String)]);
+        assert_eq!(lines[1].1, code);
+        // a shorter fence doesn't end the block
+        assert_eq!(lines[2].0, [("```", TokenKind::String)]);
+        assert_eq!(lines[2].1, code);
+        assert_eq!(lines[3].0, [("````", TokenKind::Punctuator)]);
+        assert_eq!(lines[3].1, MarkdownState::Text);
+        assert_eq!(lines[4].0, [("# a", TokenKind::Typename)]);
+
+        let lines = tokenize::<MarkdownState>("~~~\n```\n~~~");
+        assert_eq!(lines[1].1, MarkdownState::Code { fence: '~', len: 3 });
+        assert_eq!(lines[2].1, MarkdownState::Text);
+    }
+
+    #[test]
+    fn html_comments() {
+        let lines = tokenize::<MarkdownState>("a <!-- b\n# c\nd --> e");
+        assert_eq!(
+            lines[0].0,
+            [("a", TokenKind::Identifier), ("<!-- b", TokenKind::Comment)]
+        );
+        assert_eq!(lines[0].1, MarkdownState::HtmlComment);
+        assert_eq!(lines[1].0, [("# c", TokenKind::Comment)]);
+        assert_eq!(lines[1].1, MarkdownState::HtmlComment);
+        assert_eq!(
+            lines[2].0,
+            [("d -->", TokenKind::Comment), ("e", TokenKind::Identifier)]
+        );
+        assert_eq!(lines[2].1, MarkdownState::Text);
+    }
+
+    #[test]
+    fn incremental() {
+        assert_eq!(
+            retokenize::<MarkdownState>("a\nb\nc", insert(1, 1, " d")),
+            [1]
+        );
+        assert_eq!(
+            retokenize::<MarkdownState>("a\nb\n```\nc", insert(0, 0, "```")),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            retokenize::<MarkdownState>("```\na\n```\nb\n", delete(0, 0, 1, 0)),
+            [0, 1, 2, 3]
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use {
+    crate::{
+        text::{Change, Text},
+        Token,
+    },
+    std::{fmt::Debug, path::Path},
+};
+
+pub mod json;
+pub mod markdown;
+pub mod plain;
+pub mod rust;
+pub mod shader;
+pub mod toml;
+
+pub use self::{
+    json::JsonState, markdown::MarkdownState, plain::PlainState, rust::RustState,
+    shader::ShaderState, toml::TomlState,
+};
+
+/// The state a tokenizer has between two tokens. Tokenizing a line starts from the state the
+/// previous line ended in, so after an edit only the lines whose start state changed are
+/// tokenized again.
+pub trait TokenizerState: Clone + Copy + Debug + Default + Eq + 'static {
+    /// Returns the next token on the line and the state after it, or `None` at the end of the
+    /// line.
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>);
+}
+
+trait LineStates: Debug {
+    fn apply_change(&mut self, change: &Change);
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]);
+}
+
+#[derive(Debug)]
+struct LineStatesOf<S> {
+    state: Vec<Option<(S, S)>>,
+}
+
+impl<S: TokenizerState> LineStates for LineStatesOf<S> {
+    fn apply_change(&mut self, change: &Change) {
+        match *change {
+            Change::Insert(point, ref text) => {
+                self.state[point.line_index] = None;
+                let line_count = text.length().line_count;
+                if line_count > 0 {
+                    let line = point.line_index + 1;
+                    self.state.splice(line..line, (0..line_count).map(|_| None));
+                }
+            }
+            Change::Delete(start, length) => {
+                self.state[start.line_index] = None;
+                let line_count = length.line_count;
+                if line_count > 0 {
+                    let start_line = start.line_index + 1;
+                    let end_line = start_line + line_count;
+                    self.state.drain(start_line..end_line);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        let mut state = S::default();
+        for line in 0..text.as_lines().len() {
+            match self.state[line] {
+                Some((start_state, end_state)) if state == start_state => {
+                    state = end_state;
+                }
+                _ => {
+                    let start_state = state;
+                    let mut new_tokens = Vec::new();
+                    let mut cursor = Cursor::new(&text.as_lines()[line]);
+                    loop {
+                        let start = cursor.index;
+                        let (next_state, token) = state.next(&mut cursor);
+                        state = next_state;
+                        match token {
+                            Some(token) => {
+                                assert!(token.len > 0 && cursor.index == start + token.len);
+                                new_tokens.push(token)
+                            }
+                            None => break,
+                        }
+                    }
+                    self.state[line] = Some((start_state, state));
+                    tokens[line] = new_tokens;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Tokenizer {
+    lines: Box<dyn LineStates>,
+}
+
+impl Tokenizer {
+    /// A tokenizer for Rust, see [`TokenizerRegistry`] for the other languages.
+    pub fn new(line_count: usize) -> Self {
+        Self::with_state::<RustState>(line_count)
+    }
+
+    pub fn with_state<S: TokenizerState>(line_count: usize) -> Self {
+        Self {
+            lines: Box::new(LineStatesOf::<S> {
+                state: (0..line_count).map(|_| None).collect(),
+            }),
+        }
+    }
+
+    pub fn apply_change(&mut self, change: &Change) {
+        self.lines.apply_change(change);
+    }
+
+    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        self.lines.update(text, tokens);
+    }
+}
+
+/// Creates a tokenizer for a document with the given number of lines.
+pub type TokenizerFactory = fn(usize) -> Tokenizer;
+
+/// Picks a tokenizer by file extension. Files with an unknown extension are split on whitespace
+/// only.
+#[derive(Clone, Debug)]
+pub struct TokenizerRegistry {
+    extensions: Vec<(String, TokenizerFactory)>,
+    fallback: TokenizerFactory,
+}
+
+impl Default for TokenizerRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            extensions: Vec::new(),
+            fallback: Tokenizer::with_state::<PlainState>,
+        };
+        registry.register("rs", Tokenizer::with_state::<RustState>);
+        registry.register("toml", Tokenizer::with_state::<TomlState>);
+        registry.register("json", Tokenizer::with_state::<JsonState>);
+        registry.register("md", Tokenizer::with_state::<MarkdownState>);
+        registry.register("markdown", Tokenizer::with_state::<MarkdownState>);
+        for extension in ["glsl", "vert", "frag", "comp"] {
+            registry.register(extension, Tokenizer::with_state::<ShaderState>);
+        }
+        registry
+    }
+}
+
+impl TokenizerRegistry {
+    /// Uses `factory` for files with the given extension, replacing what was registered for it.
+    pub fn register(&mut self, extension: &str, factory: TokenizerFactory) {
+        let extension = extension.to_lowercase();
+        self.extensions.retain(|(other, _)| *other != extension);
+        self.extensions.push((extension, factory));
+    }
+
+    pub fn set_fallback(&mut self, factory: TokenizerFactory) {
+        self.fallback = factory;
+    }
+
+    pub fn get(&self, file_name: &str) -> TokenizerFactory {
+        let extension = Path::new(file_name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        extension
+            .and_then(|extension| {
+                self.extensions
+                    .iter()
+                    .find(|(other, _)| *other == extension)
+                    .map(|(_, factory)| *factory)
+            })
+            .unwrap_or(self.fallback)
+    }
+}
+
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    string: &'a str,
+    index: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(string: &'a str) -> Self {
+        Cursor { string, index: 0 }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.string[self.index..]
+    }
+
+    pub fn is_at_line_start(&self) -> bool {
+        self.string[..self.index].trim().is_empty()
+    }
+
+    pub fn skip_line(&mut self) {
+        self.index = self.string.len();
+    }
+
+    pub fn peek(&self, index: usize) -> char {
+        self.string[self.index..].chars().nth(index).unwrap_or('\0')
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        self.index = self.string[self.index..]
+            .char_indices()
+            .nth(count)
+            .map_or(self.string.len(), |(index, _)| self.index + index);
+    }
+
+    pub fn skip_if<P>(&mut self, predicate: P) -> bool
+    where
+        P: FnOnce(char) -> bool,
+    {
+        if predicate(self.peek(0)) {
+            self.skip(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn skip_exponent(&mut self) -> bool {
+        debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
+        self.skip(1);
+        if self.peek(0) == '+' || self.peek(0) == '-' {
+            self.skip(1);
+        }
+        self.skip_digits(10)
+    }
+
+    pub fn skip_digits(&mut self, radix: u32) -> bool {
+        let mut has_skip_digits = false;
+        loop {
+            match self.peek(0) {
+                '_' => {
+                    self.skip(1);
+                }
+                char if char.is_digit(radix) => {
+                    self.skip(1);
+                    has_skip_digits = true;
+                }
+                _ => break,
+            }
+        }
+        has_skip_digits
+    }
+
+    pub fn skip_suffix(&mut self) -> bool {
+        if self.peek(0).is_identifier_start() {
+            self.skip(1);
+            while self.skip_if(|char| char.is_identifier_continue()) {}
+            return true;
+        }
+        false
+    }
+}
+
+pub trait CharExt {
+    fn is_identifier_start(self) -> bool;
+    fn is_identifier_continue(self) -> bool;
+}
+
+impl CharExt for char {
+    fn is_identifier_start(self) -> bool {
+        match self {
+            'A'..='Z' | '_' | 'a'..='z' => true,
+            _ => false,
+        }
+    }
+
+    fn is_identifier_continue(self) -> bool {
+        match self {
+            '0'..='9' | 'A'..='Z' | '_' | 'a'..='z' => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::*,
+        crate::{
+            text::{Change, Length, Position},
+            token::TokenKind,
+        },
+    };
+
+    pub(crate) fn insert(line_index: usize, byte_index: usize, text: &str) -> Change {
+        Change::Insert(
+            Position {
+                line_index,
+                byte_index,
+            },
+            text.into(),
+        )
+    }
+
+    pub(crate) fn delete(
+        line_index: usize,
+        byte_index: usize,
+        line_count: usize,
+        byte_count: usize,
+    ) -> Change {
+        Change::Delete(
+            Position {
+                line_index,
+                byte_index,
+            },
+            Length {
+                line_count,
+                byte_count,
+            },
+        )
+    }
+
+    /// Tokenizes `text` line by line, returning the tokens of each line other than whitespace,
+    /// and the state the line ends in.
+    pub(crate) fn tokenize<S: TokenizerState>(text: &str) -> Vec<(Vec<(&str, TokenKind)>, S)> {
+        let mut state = S::default();
+        text.split('\n')
+            .map(|line| {
+                let mut tokens = Vec::new();
+                let mut cursor = Cursor::new(line);
+                loop {
+                    let start = cursor.index;
+                    let (next_state, token) = state.next(&mut cursor);
+                    state = next_state;
+                    match token {
+                        Some(token) => {
+                            assert_eq!(cursor.index, start + token.len);
+                            if token.kind != TokenKind::Whitespace {
+                                tokens.push((&line[start..cursor.index], token.kind));
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                (tokens, state)
+            })
+            .collect()
+    }
+
+    /// Tokenizes `text`, applies `change` to it and updates the tokens like `Document` does after
+    /// an edit. Checks the result is the same as tokenizing the changed text from scratch, and
+    /// returns the lines that were tokenized again.
+    pub(crate) fn retokenize<S: TokenizerState>(text: &str, change: Change) -> Vec<usize> {
+        let mut text = Text::from(text);
+        let mut tokenizer = Tokenizer::with_state::<S>(text.as_lines().len());
+        let mut tokens = vec![Vec::new(); text.as_lines().len()];
+        tokenizer.update(&text, &mut tokens);
+
+        // the lines an edit touches have no tokens until they are tokenized again
+        match change {
+            Change::Insert(position, ref inserted) => {
+                tokens[position.line_index] = Vec::new();
+                let line = position.line_index + 1;
+                let line_count = inserted.length().line_count;
+                tokens.splice(line..line, (0..line_count).map(|_| Vec::new()));
+            }
+            Change::Delete(start, length) => {
+                tokens[start.line_index] = Vec::new();
+                let line = start.line_index + 1;
+                tokens.drain(line..line + length.line_count);
+            }
+        }
+        tokenizer.apply_change(&change);
+        text.apply_change(change);
+
+        // tokens no tokenizer produces, to see which lines were tokenized again
+        let stale = vec![Token {
+            len: 0,
+            kind: TokenKind::Unknown,
+        }];
+        let mut updated = vec![stale.clone(); text.as_lines().len()];
+        tokenizer.update(&text, &mut updated);
+
+        let mut expected = vec![Vec::new(); text.as_lines().len()];
+        Tokenizer::with_state::<S>(text.as_lines().len()).update(&text, &mut expected);
+        let mut lines = Vec::new();
+        for (line, updated) in updated.into_iter().enumerate() {
+            if updated == stale {
+                assert_eq!(tokens[line], expected[line], "line {} is out of date", line);
+            } else {
+                assert_eq!(updated, expected[line], "line {} is tokenized wrong", line);
+                lines.push(line);
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn registry() {
+        let registry = TokenizerRegistry::default();
+        let tokens = |file_name: &str| {
+            let text = Text::from("a = #");
+            let mut tokens = vec![Vec::new()];
+            registry.get(file_name)(1).update(&text, &mut tokens);
+            tokens[0].iter().map(|token| token.kind).collect::<Vec<_>>()
+        };
+        assert_eq!(tokens("Cargo.TOML"), tokens("a.toml"));
+        assert_eq!(tokens("a.toml").last(), Some(&TokenKind::Comment));
+        assert_eq!(tokens("a.rs").last(), Some(&TokenKind::Punctuator));
+        assert!(tokens("a.txt")
+            .iter()
+            .all(|kind| *kind == TokenKind::Unknown || *kind == TokenKind::Whitespace));
+    }
+}
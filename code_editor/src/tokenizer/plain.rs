@@ -0,0 +1,32 @@
+use {
+    super::{Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
+};
+
+/// Splits text into words and whitespace, for files without a tokenizer of their own.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PlainState;
+
+impl TokenizerState for PlainState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        let start = cursor.index;
+        let kind = match cursor.peek(0) {
+            '\0' => return (self, None),
+            char if char.is_whitespace() => {
+                while cursor.skip_if(|char| char.is_whitespace()) {}
+                TokenKind::Whitespace
+            }
+            _ => {
+                while cursor.skip_if(|char| char != '\0' && !char.is_whitespace()) {}
+                TokenKind::Unknown
+            }
+        };
+        (
+            self,
+            Some(Token {
+                len: cursor.index - start,
+                kind,
+            }),
+        )
+    }
+}
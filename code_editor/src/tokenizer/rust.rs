@@ -1,67 +1,49 @@
-use crate::{
-    text::{Change, Text},
-    token::TokenKind,
-    Token,
+use {
+    super::{shader, CharExt, Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct Tokenizer {
-    state: Vec<Option<(State, State)>>,
+/// Rust, with the contents of `live_design!{}` blocks tokenized as the live and shader DSL.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RustState {
+    state: State,
+    live_design: LiveDesign,
 }
 
-impl Tokenizer {
-    pub fn new(line_count: usize) -> Self {
-        Self {
-            state: (0..line_count).map(|_| None).collect(),
-        }
-    }
-
-    pub fn apply_change(&mut self, change: &Change) {
-        match *change {
-            Change::Insert(point, ref text) => {
-                self.state[point.line_index] = None;
-                let line_count = text.length().line_count;
-                if line_count > 0 {
-                    let line = point.line_index + 1;
-                    self.state.splice(line..line, (0..line_count).map(|_| None));
-                }
-            }
-            Change::Delete(start, length) => {
-                self.state[start.line_index] = None;
-                let line_count = length.line_count;
-                if line_count > 0 {
-                    let start_line = start.line_index + 1;
-                    let end_line = start_line + line_count;
-                    self.state.drain(start_line..end_line);
-                }
-            }
-        }
-    }
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+enum LiveDesign {
+    #[default]
+    Outside,
+    Name,
+    Bang,
+    Inside {
+        depth: usize,
+    },
+}
 
-    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
-        let mut state = State::default();
-        for line in 0..text.as_lines().len() {
-            match self.state[line] {
-                Some((start_state, end_state)) if state == start_state => {
-                    state = end_state;
-                }
-                _ => {
-                    let start_state = state;
-                    let mut new_tokens = Vec::new();
-                    let mut cursor = Cursor::new(&text.as_lines()[line]);
-                    loop {
-                        let (next_state, token) = state.next(&mut cursor);
-                        state = next_state;
-                        match token {
-                            Some(token) => new_tokens.push(token),
-                            None => break,
-                        }
-                    }
-                    self.state[line] = Some((start_state, state));
-                    tokens[line] = new_tokens;
-                }
+impl TokenizerState for RustState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        let start = cursor.index;
+        let (state, token) = match self.live_design {
+            LiveDesign::Inside { .. } => shader::next(self.state, cursor),
+            _ => self.state.next(cursor),
+        };
+        let mut live_design = self.live_design;
+        if let Some(token) = token {
+            if token.kind != TokenKind::Whitespace && token.kind != TokenKind::Comment {
+                live_design = match (live_design, &cursor.string[start..cursor.index]) {
+                    (LiveDesign::Inside { depth }, "{") => LiveDesign::Inside { depth: depth + 1 },
+                    (LiveDesign::Inside { depth: 0 }, "}") => LiveDesign::Outside,
+                    (LiveDesign::Inside { depth }, "}") => LiveDesign::Inside { depth: depth - 1 },
+                    (LiveDesign::Inside { .. }, _) => live_design,
+                    (_, "live_design") => LiveDesign::Name,
+                    (LiveDesign::Name, "!") => LiveDesign::Bang,
+                    (LiveDesign::Bang, "{") => LiveDesign::Inside { depth: 0 },
+                    _ => LiveDesign::Outside,
+                };
             }
         }
+        (RustState { state, live_design }, token)
     }
 }
 
@@ -470,93 +452,192 @@ impl RawDoubleQuotedStringTailState {
     }
 }
 
-#[derive(Debug)]
-pub struct Cursor<'a> {
-    string: &'a str,
-    index: usize,
-}
-
-impl<'a> Cursor<'a> {
-    pub fn new(string: &'a str) -> Self {
-        Cursor { string, index: 0 }
-    }
-
-    fn peek(&self, index: usize) -> char {
-        self.string[self.index..].chars().nth(index).unwrap_or('\0')
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tokenizer::tests::{delete, insert, retokenize, tokenize},
+    };
+
+    fn states(text: &str) -> Vec<RustState> {
+        tokenize::<RustState>(text)
+            .into_iter()
+            .map(|(_, state)| state)
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        let lines = tokenize::<RustState>("if x.len() != 0 { return r#\"a\"b\"#; } // c");
+        assert_eq!(
+            lines[0].0,
+            [
+                ("if", TokenKind::BranchKeyword),
+                ("x", TokenKind::Identifier),
+                (".", TokenKind::Punctuator),
+                ("len", TokenKind::Function),
+                ("(", TokenKind::Delimiter),
+                (")", TokenKind::Delimiter),
+                ("!=", TokenKind::Punctuator),
+                ("0", TokenKind::Number),
+                ("{", TokenKind::Delimiter),
+                ("return", TokenKind::BranchKeyword),
+                ("r#\"a\"b\"#", TokenKind::String),
+                (";", TokenKind::Punctuator),
+                ("}", TokenKind::Delimiter),
+                ("// c", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(
+            tokenize::<RustState>("'a: for x in 'b'..Foo::BAR {}")[0].0,
+            [
+                ("'a", TokenKind::String),
+                (":", TokenKind::Punctuator),
+                ("for", TokenKind::LoopKeyword),
+                ("x", TokenKind::Identifier),
+                ("in", TokenKind::OtherKeyword),
+                ("'b'", TokenKind::String),
+                ("..", TokenKind::Punctuator),
+                ("Foo", TokenKind::Typename),
+                ("::", TokenKind::Punctuator),
+                ("BAR", TokenKind::Constant),
+                ("{", TokenKind::Delimiter),
+                ("}", TokenKind::Delimiter),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comments() {
+        let lines = tokenize::<RustState>("a /* b /* c\nd */\ne */ f");
+        assert_eq!(
+            lines[0].0,
+            [
+                ("a", TokenKind::Identifier),
+                ("/* b /* c", TokenKind::Comment)
+            ]
+        );
+        assert_eq!(
+            lines[0].1.state,
+            State::BlockCommentTail(BlockCommentTailState { depth: 1 })
+        );
+        // block comments nest, so this only ends the inner one
+        assert_eq!(lines[1].0, [("d */", TokenKind::Comment)]);
+        assert_eq!(
+            lines[1].1.state,
+            State::BlockCommentTail(BlockCommentTailState { depth: 0 })
+        );
+        assert_eq!(
+            lines[2].0,
+            [("e */", TokenKind::Comment), ("f", TokenKind::Identifier)]
+        );
+        assert_eq!(lines[2].1, RustState::default());
+    }
+
+    #[test]
+    fn raw_strings() {
+        let lines = tokenize::<RustState>("let s = r##\"a\n\"# b\n\"##;");
+        assert_eq!(lines[0].0.last(), Some(&("r##\"a", TokenKind::String)));
+        assert_eq!(
+            lines[0].1.state,
+            State::RawDoubleQuotedStringTail(RawDoubleQuotedStringTailState {
+                start_hash_count: 2
+            })
+        );
+        // a quote with too few hashes doesn't end the string
+        assert_eq!(lines[1].0, [("\"# b", TokenKind::String)]);
+        assert_eq!(lines[1].1, lines[0].1);
+        assert_eq!(
+            lines[2].0,
+            [("\"##", TokenKind::String), (";", TokenKind::Punctuator)]
+        );
+        assert_eq!(lines[2].1, RustState::default());
+
+        let lines = tokenize::<RustState>("\"a\\\"\nb\"");
+        assert_eq!(
+            lines[0].1.state,
+            State::DoubleQuotedStringTail(DoubleQuotedStringTailState)
+        );
+        assert_eq!(lines[1].0, [("b\"", TokenKind::String)]);
+    }
+
+    #[test]
+    fn live_design() {
+        let text = "\
+#[derive(Live)]
+live_design! {
+    Foo = {{Foo}} {
+        color: #f00
+        fn pixel(self) -> vec4 { return texture(self.image, #x2e2e2e.xy); }
     }
-
-    fn skip(&mut self, count: usize) {
-        self.index = self.string[self.index..]
-            .char_indices()
-            .nth(count)
-            .map_or(self.string.len(), |(index, _)| self.index + index);
-    }
-
-    fn skip_if<P>(&mut self, predicate: P) -> bool
-    where
-        P: FnOnce(char) -> bool,
-    {
-        if predicate(self.peek(0)) {
-            self.skip(1);
-            true
-        } else {
-            false
-        }
-    }
-
-    fn skip_exponent(&mut self) -> bool {
-        debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
-        self.skip(1);
-        if self.peek(0) == '+' || self.peek(0) == '-' {
-            self.skip(1);
-        }
-        self.skip_digits(10)
-    }
-
-    fn skip_digits(&mut self, radix: u32) -> bool {
-        let mut has_skip_digits = false;
-        loop {
-            match self.peek(0) {
-                '_' => {
-                    self.skip(1);
-                }
-                char if char.is_digit(radix) => {
-                    self.skip(1);
-                    has_skip_digits = true;
-                }
-                _ => break,
-            }
-        }
-        has_skip_digits
-    }
-
-    fn skip_suffix(&mut self) -> bool {
-        if self.peek(0).is_identifier_start() {
-            self.skip(1);
-            while self.skip_if(|char| char.is_identifier_continue()) {}
-            return true;
-        }
-        false
-    }
-}
-
-pub trait CharExt {
-    fn is_identifier_start(self) -> bool;
-    fn is_identifier_continue(self) -> bool;
 }
-
-impl CharExt for char {
-    fn is_identifier_start(self) -> bool {
-        match self {
-            'A'..='Z' | '_' | 'a'..='z' => true,
-            _ => false,
-        }
-    }
-
-    fn is_identifier_continue(self) -> bool {
-        match self {
-            '0'..='9' | 'A'..='Z' | '_' | 'a'..='z' => true,
-            _ => false,
-        }
+fn texture() {}";
+        let lines = tokenize::<RustState>(text);
+        assert_eq!(lines[0].0[0], ("#", TokenKind::Punctuator));
+        assert_eq!(
+            lines[3].0,
+            [
+                ("color", TokenKind::Identifier),
+                (":", TokenKind::Punctuator),
+                ("#f00", TokenKind::Number),
+            ]
+        );
+        assert!(lines[4].0.contains(&("texture", TokenKind::OtherKeyword)));
+        assert!(lines[4].0.contains(&("#x2e2e2e", TokenKind::Number)));
+        assert!(lines[7].0.contains(&("texture", TokenKind::Function)));
+
+        let live_design: Vec<_> = states(text)
+            .into_iter()
+            .map(|state| state.live_design)
+            .collect();
+        assert_eq!(
+            live_design,
+            [
+                LiveDesign::Outside,
+                LiveDesign::Inside { depth: 0 },
+                LiveDesign::Inside { depth: 1 },
+                LiveDesign::Inside { depth: 1 },
+                LiveDesign::Inside { depth: 1 },
+                LiveDesign::Inside { depth: 0 },
+                LiveDesign::Outside,
+                LiveDesign::Outside,
+            ]
+        );
+
+        // braces in comments and strings don't count
+        let states = states("live_design!{ a: \"{\" /* { */\n}");
+        assert_eq!(states[0].live_design, LiveDesign::Inside { depth: 0 });
+        assert_eq!(states[1].live_design, LiveDesign::Outside);
+    }
+
+    #[test]
+    fn incremental() {
+        // an edit that doesn't change the state at the end of the line stays on that line
+        assert_eq!(
+            retokenize::<RustState>("fn a() {}\nfn b() {}\nfn c() {}", insert(1, 4, "x")),
+            [1]
+        );
+        // opening a comment changes the lines up to where it was already closed
+        assert_eq!(
+            retokenize::<RustState>("a\nb\nc */ d\ne", insert(0, 0, "/* ")),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            retokenize::<RustState>("r#\"a\nb\"#\nc\nd", delete(1, 1, 0, 2)),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            retokenize::<RustState>("a\nb\nc\nd", insert(1, 1, "\"\n\"")),
+            [1, 2]
+        );
+        assert_eq!(
+            retokenize::<RustState>("a\nb /*\nc\nd */", delete(0, 1, 2, 1)),
+            [0, 1]
+        );
+        // leaving a live_design block changes how the rest of the file is tokenized
+        assert_eq!(
+            retokenize::<RustState>("live_design!{\n#fff\n}\n#fff", delete(2, 0, 0, 1)),
+            [2, 3]
+        );
     }
 }
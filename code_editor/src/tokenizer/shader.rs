@@ -0,0 +1,168 @@
+use {
+    super::{rust::State, CharExt, Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
+};
+
+/// GLSL and the Makepad shader DSL. Both are close enough to Rust lexically that this reuses the
+/// Rust states, with colors, preprocessor lines and the shader keywords added.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ShaderState {
+    state: State,
+}
+
+impl TokenizerState for ShaderState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        let (state, token) = next(self.state, cursor);
+        (ShaderState { state }, token)
+    }
+}
+
+pub(super) fn next(state: State, cursor: &mut Cursor) -> (State, Option<Token>) {
+    if let State::Initial(_) = state {
+        if cursor.peek(0) == '#' {
+            if let Some(token) = hash(cursor) {
+                return (state, Some(token));
+            }
+        }
+    }
+    let start = cursor.index;
+    let (state, token) = state.next(cursor);
+    let token = token.map(|mut token| {
+        if let TokenKind::Identifier | TokenKind::Function = token.kind {
+            if let Some(kind) = keyword(&cursor.string[start..cursor.index]) {
+                token.kind = kind;
+            }
+        }
+        token
+    });
+    (state, token)
+}
+
+// colors like #f0f or #x2e2e2e, and #version or #define at the start of a line
+fn hash(cursor: &mut Cursor) -> Option<Token> {
+    let rest = &cursor.rest()[1..];
+    let word_len = rest
+        .find(|char: char| !char.is_identifier_continue())
+        .unwrap_or(rest.len());
+    if word_len == 0 {
+        return None;
+    }
+    let word = &rest[..word_len];
+    let hex = word
+        .strip_prefix('x')
+        .filter(|hex| !hex.is_empty())
+        .unwrap_or(word);
+    let kind = if hex.chars().all(|char| char.is_ascii_hexdigit()) {
+        TokenKind::Number
+    } else if cursor.is_at_line_start() {
+        TokenKind::OtherKeyword
+    } else {
+        return None;
+    };
+    cursor.skip(1 + word.chars().count());
+    Some(Token {
+        len: 1 + word_len,
+        kind,
+    })
+}
+
+fn keyword(string: &str) -> Option<TokenKind> {
+    Some(match string {
+        "discard" => TokenKind::BranchKeyword,
+        "attribute" | "const" | "flat" | "float" | "highp" | "import" | "in" | "inout"
+        | "instance" | "int" | "ivec2" | "ivec3" | "ivec4" | "layout" | "lowp" | "mat2"
+        | "mat3" | "mat4" | "mediump" | "out" | "precision" | "sampler2D" | "samplerCube"
+        | "texture" | "uint" | "uniform" | "varying" | "void" => TokenKind::OtherKeyword,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tokenizer::tests::{insert, retokenize, tokenize},
+    };
+
+    #[test]
+    fn tokens() {
+        let lines = tokenize::<ShaderState>(
+            "#version 300 es\nuniform sampler2D image;\nvoid main() { if a #x2e2e2e.x { discard; } }",
+        );
+        assert_eq!(
+            lines[0].0,
+            [
+                ("#version", TokenKind::OtherKeyword),
+                ("300", TokenKind::Number),
+                ("es", TokenKind::Identifier),
+            ]
+        );
+        assert_eq!(
+            lines[1].0,
+            [
+                ("uniform", TokenKind::OtherKeyword),
+                ("sampler2D", TokenKind::OtherKeyword),
+                ("image", TokenKind::Identifier),
+                (";", TokenKind::Punctuator),
+            ]
+        );
+        assert_eq!(
+            lines[2].0,
+            [
+                ("void", TokenKind::OtherKeyword),
+                ("main", TokenKind::Function),
+                ("(", TokenKind::Delimiter),
+                (")", TokenKind::Delimiter),
+                ("{", TokenKind::Delimiter),
+                ("if", TokenKind::BranchKeyword),
+                ("a", TokenKind::Identifier),
+                ("#x2e2e2e", TokenKind::Number),
+                (".", TokenKind::Punctuator),
+                ("x", TokenKind::Identifier),
+                ("{", TokenKind::Delimiter),
+                ("discard", TokenKind::BranchKeyword),
+                (";", TokenKind::Punctuator),
+                ("}", TokenKind::Delimiter),
+                ("}", TokenKind::Delimiter),
+            ]
+        );
+        // only a line can start with a directive
+        assert_eq!(
+            tokenize::<ShaderState>("a #define")[0].0,
+            [
+                ("a", TokenKind::Identifier),
+                ("#", TokenKind::Punctuator),
+                ("define", TokenKind::Identifier),
+            ]
+        );
+    }
+
+    #[test]
+    fn block_comments() {
+        let lines = tokenize::<ShaderState>("float a; /* #fff\nvoid */ void b;");
+        assert_eq!(lines[0].0.last(), Some(&("/* #fff", TokenKind::Comment)));
+        assert!(matches!(lines[0].1.state, State::BlockCommentTail(_)));
+        assert_eq!(
+            lines[1].0,
+            [
+                ("void */", TokenKind::Comment),
+                ("void", TokenKind::OtherKeyword),
+                ("b", TokenKind::Identifier),
+                (";", TokenKind::Punctuator),
+            ]
+        );
+        assert_eq!(lines[1].1, ShaderState::default());
+    }
+
+    #[test]
+    fn incremental() {
+        assert_eq!(
+            retokenize::<ShaderState>("float a;\nfloat b;\nfloat c;", insert(1, 7, "2")),
+            [1]
+        );
+        assert_eq!(
+            retokenize::<ShaderState>("float a;\nfloat b;\nfloat c; */", insert(0, 0, "/*")),
+            [0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use {
+    super::{Cursor, TokenizerState},
+    crate::{token::TokenKind, Token},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TomlState {
+    #[default]
+    Initial,
+    MultilineString {
+        literal: bool,
+    },
+}
+
+impl TokenizerState for TomlState {
+    fn next(self, cursor: &mut Cursor) -> (Self, Option<Token>) {
+        if cursor.peek(0) == '\0' {
+            return (self, None);
+        }
+        let start = cursor.index;
+        let (next_state, kind) = match self {
+            TomlState::Initial => initial(cursor),
+            TomlState::MultilineString { literal } => multiline_string(cursor, literal),
+        };
+        (
+            next_state,
+            Some(Token {
+                len: cursor.index - start,
+                kind,
+            }),
+        )
+    }
+}
+
+fn initial(cursor: &mut Cursor) -> (TomlState, TokenKind) {
+    match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        ('#', _, _) => {
+            cursor.skip_line();
+            (TomlState::Initial, TokenKind::Comment)
+        }
+        // a [table] or [[array.of.tables]] header
+        ('[', _, _) if cursor.is_at_line_start() => {
+            while cursor.skip_if(|char| char != ']' && char != '\0') {}
+            while cursor.skip_if(|char| char == ']') {}
+            (TomlState::Initial, TokenKind::Typename)
+        }
+        ('"', '"', '"') => {
+            cursor.skip(3);
+            multiline_string(cursor, false)
+        }
+        ('\'', '\'', '\'') => {
+            cursor.skip(3);
+            multiline_string(cursor, true)
+        }
+        ('"', _, _) | ('\'', _, _) => {
+            let quote = cursor.peek(0);
+            cursor.skip(1);
+            loop {
+                match (cursor.peek(0), cursor.peek(1)) {
+                    (char, _) if char == quote => {
+                        cursor.skip(1);
+                        break;
+                    }
+                    ('\0', _) => break,
+                    ('\\', _) if quote == '"' => cursor.skip(2),
+                    _ => cursor.skip(1),
+                }
+            }
+            (TomlState::Initial, TokenKind::String)
+        }
+        ('[', _, _) | (']', _, _) | ('{', _, _) | ('}', _, _) => {
+            cursor.skip(1);
+            (TomlState::Initial, TokenKind::Delimiter)
+        }
+        ('=', _, _) | (',', _, _) | ('.', _, _) => {
+            cursor.skip(1);
+            (TomlState::Initial, TokenKind::Punctuator)
+        }
+        // numbers, dates and times
+        (char, _, _) if char.is_ascii_digit() => {
+            while cursor.skip_if(|char| char.is_ascii_alphanumeric() || "_.:+-".contains(char)) {}
+            (TomlState::Initial, TokenKind::Number)
+        }
+        ('+', char, _) | ('-', char, _) if char.is_ascii_digit() || char == 'i' || char == 'n' => {
+            cursor.skip(1);
+            while cursor.skip_if(|char| char.is_ascii_alphanumeric() || "_.:+-".contains(char)) {}
+            (TomlState::Initial, TokenKind::Number)
+        }
+        (char, _, _) if char.is_whitespace() => {
+            while cursor.skip_if(|char| char.is_whitespace()) {}
+            (TomlState::Initial, TokenKind::Whitespace)
+        }
+        // values can't be bare words, so anything else is a key
+        (char, _, _) if is_bare_key(char) => {
+            let start = cursor.index;
+            while cursor.skip_if(is_bare_key) {}
+            let kind = match &cursor.string[start..cursor.index] {
+                "true" | "false" => TokenKind::Constant,
+                "inf" | "nan" => TokenKind::Number,
+                _ => TokenKind::Identifier,
+            };
+            (TomlState::Initial, kind)
+        }
+        _ => {
+            cursor.skip(1);
+            (TomlState::Initial, TokenKind::Unknown)
+        }
+    }
+}
+
+fn multiline_string(cursor: &mut Cursor, literal: bool) -> (TomlState, TokenKind) {
+    let quote = if literal { '\'' } else { '"' };
+    loop {
+        match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+            (a, b, c) if a == quote && b == quote && c == quote => {
+                cursor.skip(3);
+                // up to two more quotes belong to the string
+                while cursor.skip_if(|char| char == quote) {}
+                break (TomlState::Initial, TokenKind::String);
+            }
+            ('\0', _, _) => break (TomlState::MultilineString { literal }, TokenKind::String),
+            ('\\', _, _) if !literal => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn is_bare_key(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::tokenizer::tests::{delete, insert, retokenize, tokenize},
+    };
+
+    #[test]
+    fn tokens() {
+        let lines = tokenize::<TomlState>(
+            "[package]\nname = \"a\\\"b\" # c\nx.y = [true, -1_000, inf, 1979-05-27T07:32:00Z]",
+        );
+        assert_eq!(lines[0].0, [("[package]", TokenKind::Typename)]);
+        assert_eq!(
+            lines[1].0,
+            [
+                ("name", TokenKind::Identifier),
+                ("=", TokenKind::Punctuator),
+                ("\"a\\\"b\"", TokenKind::String),
+                ("# c", TokenKind::Comment),
+            ]
+        );
+        assert_eq!(
+            lines[2].0,
+            [
+                ("x", TokenKind::Identifier),
+                (".", TokenKind::Punctuator),
+                ("y", TokenKind::Identifier),
+                ("=", TokenKind::Punctuator),
+                ("[", TokenKind::Delimiter),
+                ("true", TokenKind::Constant),
+                (",", TokenKind::Punctuator),
+                ("-1_000", TokenKind::Number),
+                (",", TokenKind::Punctuator),
+                ("inf", TokenKind::Number),
+                (",", TokenKind::Punctuator),
+                ("1979-05-27T07:32:00Z", TokenKind::Number),
+                ("]", TokenKind::Delimiter),
+            ]
+        );
+        assert!(lines.iter().all(|(_, state)| *state == TomlState::Initial));
+    }
+
+    #[test]
+    fn multiline_strings() {
+        let lines = tokenize::<TomlState>("a = \"\"\"x\ny\nz\"\"\"\"\nb = '''p\\'''\n[[c]]");
+        assert_eq!(lines[0].0.last(), Some(&("\"\"\"x", TokenKind::String)));
+        assert_eq!(lines[0].1, TomlState::MultilineString { literal: false });
+        assert_eq!(lines[1].0, [("y", TokenKind::String)]);
+        assert_eq!(lines[1].1, TomlState::MultilineString { literal: false });
+        // up to two quotes before the closing ones are part of the string
+        assert_eq!(lines[2].0, [("z\"\"\"\"", TokenKind::String)]);
+        assert_eq!(lines[2].1, TomlState::Initial);
+        // literal strings have no escapes
+        assert_eq!(lines[3].0.last(), Some(&("'''p\\'''", TokenKind::String)));
+        assert_eq!(lines[3].1, TomlState::Initial);
+        assert_eq!(lines[4].0, [("[[c]]", TokenKind::Typename)]);
+
+        let lines = tokenize::<TomlState>("a = \"\"\"x\\\"\"\"\n\"\"\"");
+        assert_eq!(lines[0].1, TomlState::MultilineString { literal: false });
+        assert_eq!(lines[1].0, [("\"\"\"", TokenKind::String)]);
+        assert_eq!(lines[1].1, TomlState::Initial);
+
+        let lines = tokenize::<TomlState>("a = '''\n# b\n'''");
+        assert_eq!(lines[0].1, TomlState::MultilineString { literal: true });
+        assert_eq!(lines[1].0, [("# b", TokenKind::String)]);
+    }
+
+    #[test]
+    fn incremental() {
+        assert_eq!(
+            retokenize::<TomlState>("a = 1\nb = 2\nc = 3", insert(1, 5, "0")),
+            [1]
+        );
+        assert_eq!(
+            retokenize::<TomlState>("a = 1\nb = 2\nc = '''\nd", insert(0, 4, "'''")),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            retokenize::<TomlState>("a = \"\"\"\nb\n\"\"\"\nc = 1", delete(2, 0, 0, 3)),
+            [2, 3]
+        );
+    }
+}
@@ -1,9 +1,17 @@
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct InlineWidget {
+    /// Tells widgets apart when drawing them, and when removing them from a [`Session`].
+    ///
+    /// [`Session`]: crate::Session
+    pub id: usize,
     pub column_count: usize,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct BlockWidget {
+    /// Tells widgets apart when drawing them, and when removing them from a [`Session`].
+    ///
+    /// [`Session`]: crate::Session
+    pub id: usize,
     pub height: f64,
 }
@@ -7,7 +7,7 @@ pub use {
         collections::HashMap,
     },
     crate::{
-        shader::draw_trapezoid::DrawTrapezoidVector,
+        shader::draw_trapezoid::{DrawTrapezoidVector, DrawVectorPaint},
         makepad_platform::*,
         cx_2d::Cx2d,
         turtle::{Walk, Layout},
@@ -16,7 +16,8 @@ pub use {
         makepad_vector::trapezoidator::Trapezoidator,
         makepad_vector::geometry::{AffineTransformation, Transform, Vector, Point},
         makepad_vector::internal_iter::*,
-        makepad_vector::path::{PathIterator, PathCommand},
+        makepad_vector::path::{PathIterator, PathCommand, LinePathCommand},
+        makepad_vector::svg::{parse_path_data, stroke_line_path, StrokeStyle, SvgColor, SvgDocument, SvgPaint, SvgShape, SvgShapeKind},
     }
 };
 
@@ -24,7 +25,8 @@ pub use {
 pub struct CxIconSlot {
     pub t1: Vec2,
     pub t2: Vec2,
-    pub chan: f32
+    pub chan: f32,
+    pub multicolor: bool,
 }
 
 #[derive(Clone)]
@@ -35,24 +37,11 @@ pub struct CxIconEntry {
     args: CxIconArgs,
 }
 
-struct CxIconPathCommands {
+struct CxIconDocument {
     bounds: Rect,
-    path: Vec<PathCommand>
-}
-
-impl<'a> InternalIterator for &CxIconPathCommands {
-    type Item = PathCommand;
-    fn for_each<F>(self, f: &mut F) -> bool
-    where
-    F: FnMut(PathCommand) -> bool,
-    {
-        for item in &self.path {
-            if !f(item.clone()) {
-                return false
-            }
-        }
-        true
-    }
+    // documents with more than one color are stored as premultiplied colors instead of coverage
+    multicolor: bool,
+    shapes: Vec<SvgShape>,
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -65,7 +54,7 @@ pub struct CxIconAtlas {
     pub texture: Texture,
     pub clear_buffer: bool,
     svg_deps: HashMap<String, CxIconPathHash>,
-    paths: HashMap<CxIconPathHash, CxIconPathCommands>,
+    documents: HashMap<CxIconPathHash, CxIconDocument>,
     entries: HashMap<CxIconEntryHash, CxIconEntry>,
     alloc: CxIconAtlasAlloc
 }
@@ -109,7 +98,7 @@ impl CxIconAtlas {
             clear_buffer: false,
             entries: HashMap::new(),
             svg_deps: HashMap::new(),
-            paths: HashMap::new(),
+            documents: HashMap::new(),
             alloc: CxIconAtlasAlloc {
                 texture_size: DVec2 {x: 2048.0, y: 2048.0},
                 xpos: 0.0,
@@ -121,82 +110,49 @@ impl CxIconAtlas {
     }
     
     pub fn parse_and_cache_path(&mut self, path_hash: CxIconPathHash, path: &[u8]) -> Option<(CxIconPathHash, Rect)> {
-        match parse_svg_path(path) {
-            Ok(path) => {
-                let mut min = dvec2(f64::INFINITY, f64::INFINITY);
-                let mut max = dvec2(-f64::INFINITY, -f64::INFINITY);
-                fn bound(p: &Point, min: &mut DVec2, max: &mut DVec2) {
-                    if p.x < min.x {min.x = p.x}
-                    if p.y < min.y {min.y = p.y}
-                    if p.x > max.x {max.x = p.x}
-                    if p.y > max.y {max.y = p.y}
-                }
-                for cmd in &path {
-                    match cmd {
-                        PathCommand::MoveTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::LineTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::QuadraticTo(p1, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::CubicTo(p1, p2, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p2, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::Close => ()
-                    }
-                }
-                let bounds = Rect {pos: min, size: max - min};
-                self.paths.insert(path_hash, CxIconPathCommands {
-                    bounds,
-                    path
-                });
-                return Some((path_hash, bounds));
-            }
+        match std::str::from_utf8(path).map_err(|e| e.to_string()).and_then(parse_path_data) {
+            Ok(path) => self.cache_document(path_hash, SvgDocument::from_path(path)),
             Err(e) => {
                 log!("Error in SVG Path {}", e);
-                return None
+                None
             }
         }
     }
     
+    fn cache_document(&mut self, path_hash: CxIconPathHash, document: SvgDocument) -> Option<(CxIconPathHash, Rect)> {
+        let bounds = document.bounds()?;
+        let bounds = Rect {
+            pos: dvec2(bounds.p_min.x, bounds.p_min.y),
+            size: dvec2(bounds.p_max.x - bounds.p_min.x, bounds.p_max.y - bounds.p_min.y)
+        };
+        self.documents.insert(path_hash, CxIconDocument {
+            bounds,
+            multicolor: !document.is_monochrome(),
+            shapes: document.shapes,
+        });
+        Some((path_hash, bounds))
+    }
+    
     pub fn get_icon_bounds(&mut self, cx: &Cx, path_str: &Rc<String>, svg_dep: &Rc<String>) -> Option<(CxIconPathHash, Rect)> {
         if svg_dep.len() != 0 {
             // alright so. lets see if we have a path hash
             if let Some(path_hash) = self.svg_deps.get(svg_dep.as_str()) {
-                if let Some(path) = self.paths.get(&path_hash) {
-                    return Some((*path_hash, path.bounds))
+                if let Some(document) = self.documents.get(&path_hash) {
+                    return Some((*path_hash, document.bounds))
                 }
                 return None
             }
             let path_hash = CxIconPathHash(LiveId(self.svg_deps.len() as u64));
             self.svg_deps.insert(svg_dep.as_str().to_string(), path_hash);
-            // lets parse the path range out of the svg file
             match cx.get_dependency(svg_dep.as_str()) {
                 Ok(data)=>{
-                    fn find_path_str(data:&[u8])->Option<&[u8]>{
-                        let pat = "path d=\"".as_bytes();
-                        'outer:for i in 0..data.len(){
-                            for j in 0..pat.len(){
-                                if data[i+j] != pat[j]{
-                                    continue 'outer;
-                                }
-                            }
-                            for k in i+pat.len()..data.len(){
-                                if data[k] == '\"' as u8{
-                                    return Some(&data[i+pat.len()..k])
-                                }
-                            }
+                    match std::str::from_utf8(&data).map_err(|e| e.to_string()).and_then(SvgDocument::parse) {
+                        Ok(document) => return self.cache_document(path_hash, document),
+                        Err(e) => {
+                            log!("Error in SVG file {}: {}", svg_dep, e);
                             return None
                         }
-                        None
-                    }
-                    if let Some(data) = find_path_str(&data){
-                        return self.parse_and_cache_path(path_hash, data)
                     }
-                    return None
-                    
                 }
                 Err(_err)=>{
                     return None
@@ -207,8 +163,8 @@ impl CxIconAtlas {
             return None
         }
         let path_hash = CxIconPathHash(LiveId(Rc::as_ptr(path_str) as u64));
-        if let Some(path) = self.paths.get(&path_hash) {
-            return Some((path_hash, path.bounds))
+        if let Some(document) = self.documents.get(&path_hash) {
+            return Some((path_hash, document.bounds))
         }
         self.parse_and_cache_path(path_hash, path_str.as_str().as_bytes())
     }
@@ -220,7 +176,8 @@ impl CxIconAtlas {
             return entry.slot
         }
         
-        let (slot,pos) = self.alloc.alloc_icon_slot(args.size.x as f64, args.size.y as f64);
+        let (mut slot,pos) = self.alloc.alloc_icon_slot(args.size.x as f64, args.size.y as f64);
+        slot.multicolor = self.documents.get(&path_hash).map_or(false, |document| document.multicolor);
         self.entries.insert(
             entry_hash,
             CxIconEntry {
@@ -260,10 +217,27 @@ impl CxIconAtlasAlloc {
         
         (CxIconSlot {
             chan: 0.0,
+            multicolor: false,
             t1: dvec2(tx1, ty1).into(),
             t2: dvec2(tx1 + (w / self.texture_size.x), ty1 + (h / self.texture_size.y)).into()
         },dvec2(px, py).into())
     }
+    
+    // like alloc_icon_slot, but returns None instead of overflowing
+    fn try_alloc(&mut self, w: f64, h: f64) -> Option<DVec2> {
+        if w + self.xpos >= self.texture_size.x {
+            self.xpos = 0.0;
+            self.ypos += self.hmax + 1.0;
+            self.hmax = 0.0;
+        }
+        if w >= self.texture_size.x || h + self.ypos >= self.texture_size.y {
+            return None
+        }
+        self.hmax = self.hmax.max(h);
+        let pos = dvec2(self.xpos, self.ypos);
+        self.xpos += w + 1.0;
+        Some(pos)
+    }
 }
 
 #[derive(Clone)]
@@ -285,50 +259,131 @@ impl CxIconAtlas {
 
 
 impl DrawTrapezoidVector {
-    // atlas drawing function used by CxAfterDraw
-    fn draw_vector(&mut self, entry: &CxIconEntry, path: &CxIconPathCommands, many: &mut ManyInstances) {
-        let trapezoids = {
-            let mut trapezoids = Vec::new();
-            //log_str(&format!("Serializing char {} {} {} {}", glyphtc.tx1 , cx.fonts_atlas.texture_size.x ,todo.subpixel_x_fract ,atlas_page.dpi_factor));
-            let trapezoidate = self.trapezoidator.trapezoidate(
-                path.map({
-                    //log!("{:?} {:?}", entry.args, entry.pos);
-                    move | cmd | {
-                        let cmd = cmd.transform(
-                            &AffineTransformation::identity()
-                                .translate(Vector::new(entry.args.translate.x, entry.args.translate.y))
-                                .uniform_scale(entry.args.scale)
-                                .translate(Vector::new(entry.pos.x + entry.args.subpixel.x, entry.pos.y + entry.args.subpixel.y))
-                        );
-                        cmd
+    // renders the coverage of a shape into a channel, at origin in the target texture
+    fn draw_shape(&mut self, shape: &SvgShape, args: &CxIconArgs, origin: DVec2, chan: f32, many: &mut ManyInstances) {
+        let transform = AffineTransformation::identity()
+            .translate(Vector::new(args.translate.x, args.translate.y))
+            .uniform_scale(args.scale)
+            .translate(Vector::new(origin.x + args.subpixel.x, origin.y + args.subpixel.y));
+        let mut path = shape.path.clone();
+        for cmd in &mut path {
+            cmd.transform_mut(&transform);
+        }
+        let path = path.into_iter().linearize(args.linearize);
+        
+        let mut trapezoids = Vec::new();
+        match shape.kind {
+            SvgShapeKind::Fill(fill_rule) => {
+                if let Some(trapezoidate) = self.trapezoidator.trapezoidate_with_fill_rule(path, fill_rule) {
+                    trapezoids.extend_from_internal_iter(trapezoidate);
+                }
+            }
+            SvgShapeKind::Stroke(style) => {
+                let style = StrokeStyle {width: style.width * args.scale, ..style};
+                let outline = stroke_line_path(path, &style, args.linearize);
+                // the pieces of the outline overlap, but coverage saturates in the atlas so
+                // they can be trapezoidated one by one
+                let mut contour = Vec::new();
+                for cmd in outline.commands() {
+                    contour.push(cmd);
+                    if let LinePathCommand::Close = cmd {
+                        if let Some(trapezoidate) = self.trapezoidator.trapezoidate(contour.drain(..)) {
+                            trapezoids.extend_from_internal_iter(trapezoidate);
+                        }
                     }
-                }).linearize(entry.args.linearize)
-            );
-            if let Some(trapezoidate) = trapezoidate {
-                trapezoids.extend_from_internal_iter(
-                    trapezoidate
-                );
+                }
             }
-            trapezoids
-        };
+        }
         
+        // keep content outside of the view box out of the neighbouring slots
+        self.clip = vec4(origin.x as f32, origin.y as f32, (origin.x + args.size.x) as f32, (origin.y + args.size.y) as f32);
+        self.chan = chan;
         for trapezoid in trapezoids {
             self.a_xs = Vec2 {x: trapezoid.xs[0], y: trapezoid.xs[1]};
             self.a_ys = Vec4 {x: trapezoid.ys[0], y: trapezoid.ys[1], z: trapezoid.ys[2], w: trapezoid.ys[3]};
-            self.chan = 0.0 as f32;
             many.instances.extend_from_slice(self.draw_vars.as_slice());
         }
     }
 }
 
+impl DrawVectorPaint {
+    // paints shape coverage rendered at coverage_pos in the scratch texture into the slot of entry
+    fn draw_paint(&mut self, shape: &SvgShape, entry: &CxIconEntry, coverage_pos: DVec2, chan: f32, texture_size: DVec2, many: &mut ManyInstances) {
+        fn color(color: SvgColor, opacity: f32) -> Vec4 {
+            vec4(color.r, color.g, color.b, color.a * opacity)
+        }
+        self.a_rect = vec4(entry.pos.x as f32, entry.pos.y as f32, entry.args.size.x as f32, entry.args.size.y as f32);
+        self.coverage_offset = (coverage_pos - entry.pos).into();
+        self.coverage_size = texture_size.into();
+        self.chan = chan;
+        match &shape.paint {
+            SvgPaint::Color(c) => {
+                self.gradient = Vec4::default();
+                self.offsets = Vec4::default();
+                self.color0 = color(*c, shape.opacity);
+                self.color1 = self.color0;
+                self.color2 = self.color0;
+                self.color3 = self.color0;
+            }
+            // the initial value of color is black
+            SvgPaint::CurrentColor => {
+                self.gradient = Vec4::default();
+                self.offsets = Vec4::default();
+                self.color0 = vec4(0.0, 0.0, 0.0, shape.opacity);
+                self.color1 = self.color0;
+                self.color2 = self.color0;
+                self.color3 = self.color0;
+            }
+            SvgPaint::LinearGradient(gradient) => {
+                // map the gradient from document to atlas coordinates
+                let origin = entry.pos + entry.args.subpixel;
+                let scale = entry.args.scale;
+                let t = gradient.t;
+                self.gradient = vec4(
+                    (t[0] / scale) as f32,
+                    (t[1] / scale) as f32,
+                    (t[2] - t[0] * (origin.x / scale - entry.args.translate.x) - t[1] * (origin.y / scale - entry.args.translate.y)) as f32,
+                    0.0
+                );
+                // the shader takes 4 stops, longer gradients get resampled
+                let mut stops = [(0.0, SvgColor::default()); 4];
+                if gradient.stops.len() <= 4 {
+                    for (i, stop) in stops.iter_mut().enumerate() {
+                        let src = gradient.stops[i.min(gradient.stops.len() - 1)];
+                        *stop = (src.offset, src.color);
+                    }
+                }
+                else {
+                    let first = gradient.stops[0].offset;
+                    let last = gradient.stops[gradient.stops.len() - 1].offset;
+                    for (i, stop) in stops.iter_mut().enumerate() {
+                        let offset = first + (last - first) * i as f64 / 3.0;
+                        *stop = (offset, gradient.color_at(offset));
+                    }
+                }
+                self.offsets = vec4(stops[0].0 as f32, stops[1].0 as f32, stops[2].0 as f32, stops[3].0 as f32);
+                self.color0 = color(stops[0].1, shape.opacity);
+                self.color1 = color(stops[1].1, shape.opacity);
+                self.color2 = color(stops[2].1, shape.opacity);
+                self.color3 = color(stops[3].1, shape.opacity);
+            }
+        }
+        many.instances.extend_from_slice(self.draw_vars.as_slice());
+    }
+}
+
 #[derive(Clone)]
 pub struct CxDrawIconAtlasRc(pub Rc<RefCell<CxDrawIconAtlas >>);
 
 pub struct CxDrawIconAtlas {
     pub draw_trapezoid: DrawTrapezoidVector,
+    pub draw_paint: DrawVectorPaint,
     pub atlas_pass: Pass,
     pub atlas_draw_list: DrawList2d,
     pub atlas_texture: Texture,
+    pub scratch_pass: Pass,
+    pub scratch_draw_list: DrawList2d,
+    pub scratch_texture: Texture,
 }
 
 impl CxDrawIconAtlas {
@@ -341,12 +396,25 @@ impl CxDrawIconAtlas {
         //cx.fonts_atlas.texture_id = Some(atlas_texture.texture_id());
         
         let draw_trapezoid = DrawTrapezoidVector::new_local(cx);
+        let draw_paint = DrawVectorPaint::new_local(cx);
+        let atlas_pass = Pass::new(cx);
+        
+        // multicolor icons render their shape coverage here first. the pass is a child of
+        // the atlas pass, which has to exist before it to be painted after it
+        let scratch_texture = Texture::new(cx);
+        scratch_texture.set_format(cx, TextureFormat::RenderBGRAu8{
+            size: TextureSize::Auto
+        });
         // ok we need to initialize drawtrapezoidtext from a live pointer.
         Self {
             draw_trapezoid,
-            atlas_pass: Pass::new(cx),
+            draw_paint,
+            atlas_pass,
             atlas_draw_list: DrawList2d::new(cx),
-            atlas_texture: atlas_texture
+            atlas_texture: atlas_texture,
+            scratch_pass: Pass::new(cx),
+            scratch_draw_list: DrawList2d::new(cx),
+            scratch_texture,
         }
     }
 }
@@ -401,250 +469,88 @@ impl<'a> Cx2d<'a> {
             let mut atlas_todo = Vec::new();
             std::mem::swap(&mut atlas.alloc.todo, &mut atlas_todo);
             
+            // multicolor entries render the coverage of every shape into a channel of the
+            // scratch texture, and then get painted from there in a second step
+            let mut paints = Vec::new();
+            let mut deferred = Vec::new();
+            if atlas_todo.iter().any(|todo| atlas.entries.get(todo).unwrap().slot.multicolor) {
+                self.make_child_pass(&draw_atlas.scratch_pass);
+                self.begin_pass(&draw_atlas.scratch_pass, Some(1.0));
+                draw_atlas.scratch_pass.set_size(self.cx, texture_size);
+                draw_atlas.scratch_pass.clear_color_textures(self.cx);
+                draw_atlas.scratch_pass.add_color_texture(self.cx, &draw_atlas.scratch_texture, PassClearColor::ClearWith(Vec4::default()));
+                draw_atlas.scratch_draw_list.begin_always(self);
+                
+                let mut scratch = CxIconAtlasAlloc {texture_size, ..Default::default()};
+                if let Some(mut many) = self.begin_many_instances(&draw_atlas.draw_trapezoid.draw_vars) {
+                    atlas_todo.retain(|todo| {
+                        let entry = atlas.entries.get(todo).unwrap();
+                        if !entry.slot.multicolor {
+                            return true
+                        }
+                        let document = atlas.documents.get(&entry.path_hash).unwrap();
+                        // 3 shapes per region, one per channel
+                        let mut regions = Vec::new();
+                        for _ in 0..(document.shapes.len() + 2) / 3 {
+                            if let Some(pos) = scratch.try_alloc(entry.args.size.x, entry.args.size.y) {
+                                regions.push(pos);
+                            }
+                            else if paints.len() > 0 {
+                                deferred.push(*todo);
+                                return false
+                            }
+                            else {
+                                log!("Icon too large to render in the scratch texture");
+                                return false
+                            }
+                        }
+                        for (index, shape) in document.shapes.iter().enumerate() {
+                            let pos = regions[index / 3];
+                            let chan = (index % 3) as f32;
+                            draw_atlas.draw_trapezoid.draw_shape(shape, &entry.args, pos, chan, &mut many);
+                            paints.push((*todo, index, pos, chan));
+                        }
+                        false
+                    });
+                    self.end_many_instances(many);
+                }
+                draw_atlas.scratch_draw_list.end(self);
+                self.end_pass(&draw_atlas.scratch_pass);
+            }
+            
             if let Some(mut many) = self.begin_many_instances(&draw_atlas.draw_trapezoid.draw_vars) {
                 for todo in atlas_todo {
                     let entry = atlas.entries.get(&todo).unwrap();
-                    let path = atlas.paths.get(&entry.path_hash).unwrap();
-                    draw_atlas.draw_trapezoid.draw_vector(entry, path, &mut many);
+                    let document = atlas.documents.get(&entry.path_hash).unwrap();
+                    for shape in &document.shapes {
+                        draw_atlas.draw_trapezoid.draw_shape(shape, &entry.args, entry.pos, 0.0, &mut many);
+                    }
                 }
-                
                 self.end_many_instances(many);
             }
-            draw_atlas.atlas_draw_list.end(self);
-            self.end_pass(&draw_atlas.atlas_pass);
-        }
-    }
-    
-    
-}
-
-fn parse_svg_path(path: &[u8]) -> Result<Vec<PathCommand>, String> {
-    #[derive(Debug)]
-    enum Cmd {
-        Unknown,
-        Move(bool),
-        Hor(bool),
-        Vert(bool),
-        Line(bool),
-        Cubic(bool),
-        Quadratic(bool),
-        Close
-    }
-    impl Default for Cmd {fn default() -> Self {Self::Unknown}}
-    
-    #[derive(Default)]
-    struct ParseState {
-        cmd: Cmd,
-        expect_nums: usize,
-        chain: bool,
-        nums: [f64; 6],
-        num_count: usize,
-        last_pt: Point,
-        out: Vec<PathCommand>,
-        num_state: Option<NumState>
-    }
-    
-    struct NumState {
-        num: f64,
-        mul: f64,
-        has_dot: bool,
-    }
-    
-    impl NumState {
-        fn new_pos(v: f64) -> Self {Self {num: v, mul: 1.0, has_dot: false}}
-        fn new_min() -> Self {Self {num: 0.0, mul: -1.0, has_dot: false}}
-        fn finalize(self) -> f64 {self.num * self.mul}
-        fn add_digit(&mut self, digit: f64) {
-            self.num *= 10.0;
-            self.num += digit;
-            if self.has_dot {
-                self.mul *= 0.1;
-            }
-        }
-    }
-    
-    impl ParseState {
-        fn next_cmd(&mut self, cmd: Cmd) -> Result<(), String> {
-            self.finalize_cmd() ?;
-            self.chain = false;
-            self.expect_nums = match cmd {
-                Cmd::Unknown => panic!(),
-                Cmd::Move(_) => 2,
-                Cmd::Hor(_) => 1,
-                Cmd::Vert(_) => 1,
-                Cmd::Line(_) => 2,
-                Cmd::Cubic(_) => 6,
-                Cmd::Quadratic(_) => 4,
-                Cmd::Close => 0
-            };
-            self.cmd = cmd;
-            Ok(())
-        }
-        
-        fn add_min(&mut self) -> Result<(), String> {
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            if self.expect_nums == 0 {
-                return Err(format!("Unexpected minus"));
-            }
-            self.num_state = Some(NumState::new_min());
-            Ok(())
-        }
-        
-        fn add_digit(&mut self, digit: f64) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                num_state.add_digit(digit);
-            }
-            else {
-                if self.expect_nums == self.num_count {
-                    self.finalize_cmd() ?;
-                }
-                if self.expect_nums == 0 {
-                    return Err(format!("Unexpected digit"));
-                }
-                self.num_state = Some(NumState::new_pos(digit))
-            }
-            Ok(())
-        }
-        
-        fn add_dot(&mut self) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                if num_state.has_dot {
-                    return Err(format!("Unexpected ."));
-                }
-                num_state.has_dot = true;
-            }
-            else {
-                return Err(format!("Unexpected ."));
-            }
-            Ok(())
-        }
-        
-        fn finalize_num(&mut self) {
-            if let Some(num_state) = self.num_state.take() {
-                self.nums[self.num_count] = num_state.finalize();
-                self.num_count += 1;
-            }
-        }
-        
-        fn whitespace(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            Ok(())
-        }
-        
-        fn finalize_cmd(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.chain && self.num_count == 0 {
-                return Ok(())
-            }
-            if self.expect_nums != self.num_count {
-                return Err(format!("SVG Path command {:?} expected {} points, got {}", self.cmd, self.expect_nums, self.num_count));
-            }
-            match self.cmd {
-                Cmd::Unknown => (),
-                Cmd::Move(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.out.push(PathCommand::MoveTo(self.last_pt));
-                },
-                Cmd::Hor(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.last_pt.y};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: 0.0};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Vert(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.last_pt.x, y: self.nums[0]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: 0.0, y: self.nums[0]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Line(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                },
-                Cmd::Cubic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[4], y: self.nums[5]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[4], y: self.nums[5]};
-                    }
-                    self.out.push(PathCommand::CubicTo(
-                        Point {x: self.nums[0], y: self.nums[1]},
-                        Point {x: self.nums[2], y: self.nums[3]},
-                        
-                        self.last_pt,
-                    ))
-                },
-                Cmd::Quadratic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[2], y: self.nums[3]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[2], y: self.nums[3]};
+            
+            if paints.len() > 0 {
+                draw_atlas.draw_paint.draw_vars.texture_slots[0] = Some(draw_atlas.scratch_texture.clone());
+                if let Some(mut many) = self.begin_many_instances(&draw_atlas.draw_paint.draw_vars) {
+                    for (todo, index, pos, chan) in paints {
+                        let entry = atlas.entries.get(&todo).unwrap();
+                        let document = atlas.documents.get(&entry.path_hash).unwrap();
+                        draw_atlas.draw_paint.draw_paint(&document.shapes[index], entry, pos, chan, texture_size, &mut many);
                     }
-                    self.out.push(PathCommand::QuadraticTo(
-                        Point {x: self.nums[0], y: self.nums[1]},
-                        self.last_pt
-                    ));
-                }
-                Cmd::Close => {
-                    self.out.push(PathCommand::Close);
+                    self.end_many_instances(many);
                 }
             }
-            self.num_count = 0;
-            self.chain = true;
-            Ok(())
-        }
-    }
-    
-    let mut state = ParseState::default();
-    for i in 0..path.len() {
-        match path[i] {
-            b'M' => state.next_cmd(Cmd::Move(true)) ?,
-            b'm' => state.next_cmd(Cmd::Move(false)) ?,
-            b'Q' => state.next_cmd(Cmd::Quadratic(true)) ?,
-            b'q' => state.next_cmd(Cmd::Quadratic(false)) ?,
-            b'C' => state.next_cmd(Cmd::Cubic(true)) ?,
-            b'c' => state.next_cmd(Cmd::Cubic(false)) ?,
-            b'H' => state.next_cmd(Cmd::Hor(true)) ?,
-            b'h' => state.next_cmd(Cmd::Hor(false)) ?,
-            b'V' => state.next_cmd(Cmd::Vert(true)) ?,
-            b'v' => state.next_cmd(Cmd::Vert(false)) ?,
-            b'L' => state.next_cmd(Cmd::Line(true)) ?,
-            b'l' => state.next_cmd(Cmd::Line(false)) ?,
-            b'Z' | b'z' => state.next_cmd(Cmd::Close) ?,
-            b'-' => state.add_min() ?,
-            b'0'..=b'9' => state.add_digit((path[i] - b'0') as f64) ?,
-            b'.' => state.add_dot() ?,
-            b',' | b' ' | b'\r' | b'\n' | b'\t' => state.whitespace() ?,
-            x => {
-                return Err(format!("Unexpected character {} - {}", x, x as char))
+            draw_atlas.atlas_draw_list.end(self);
+            self.end_pass(&draw_atlas.atlas_pass);
+            
+            // entries that didn't fit the scratch texture get rendered next frame
+            if deferred.len() > 0 {
+                atlas.alloc.todo = deferred;
+                self.cx.redraw_all();
             }
         }
     }
-    state.finalize_cmd() ?;
     
-    Ok(state.out)
+    
 }
 
@@ -49,8 +49,9 @@ pub use crate::{
         //draw_shape::{DrawShape, Shape, Fill},
         draw_icon::DrawIcon,
         draw_quad::DrawQuad,
-        draw_text::DrawText,
+        draw_text::{DrawText, TextLayoutLine},
         draw_color::DrawColor,
+        draw_vector::{DrawVector, FillRule, LineCap, LineJoin, StrokeStyle},
     },
     geometry::{
         GeometryGen,
@@ -66,4 +67,5 @@ pub fn live_design(cx: &mut Cx) {
     crate::geometry::geometry_gen::live_design(cx);
     crate::shader::std::live_design(cx);
     crate::shader::draw_trapezoid::live_design(cx);
+    crate::shader::draw_vector::live_design(cx);
 }
\ No newline at end of file
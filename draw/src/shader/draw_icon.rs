@@ -59,6 +59,11 @@ live_design!{
             let dx = dFdx(vec2(self.tex_coord1.x * 2048.0, 0.)).x;
             let dp = 1.0 / 2048.0;
             
+            // multicolor icons are stored as premultiplied colors
+            if self.icon_multicolor > 0.5 {
+                return sample2d_rt(self.tex, self.tex_coord1.xy) * self.get_color().a;
+            }
+            
             // basic hardcoded mipmapping so it stops 'swimming' in VR
             // mipmaps are stored in red/green/blue channel
             let s = sample2d_rt(self.tex, self.tex_coord1.xy).x;
@@ -92,6 +97,7 @@ pub struct DrawIcon {
     #[live] pub color: Vec4,
     #[calc] pub icon_t1: Vec2,
     #[calc] pub icon_t2: Vec2,
+    #[calc] pub icon_multicolor: f32,
 }
 
 impl LiveHook for DrawIcon{
@@ -184,6 +190,7 @@ impl DrawIcon {
             
             self.icon_t1 = slot.t1;
             self.icon_t2 = slot.t2;
+            self.icon_multicolor = if slot.multicolor {1.0} else {0.0};
             
             if let Some(mi) = &mut self.many_instances {
                 mi.instances.extend_from_slice(self.draw_vars.as_slice());
@@ -186,6 +186,16 @@ pub enum Overflow {
     #[live] None
 }*/
 
+/// A line of text as laid out by `DrawText::layout_lines`.
+#[derive(Clone, Debug, Default)]
+pub struct TextLayoutLine {
+    /// Byte range of the line in the text, without the newline that ends it.
+    pub start: usize,
+    pub end: usize,
+    /// The x offset of every char boundary in the line, so one more than the number of chars.
+    pub offsets: Vec<f64>,
+}
+
 pub struct TextGeom {
     pub eval_width: f64,
     pub eval_height: f64,
@@ -787,6 +797,73 @@ impl DrawText {
         }
     }
     
+    /// Splits text into the lines it is drawn as. Newlines always start a new line, and with
+    /// `TextWrap::Word` lines are also wrapped at word boundaries to fit in `width`. Words wider
+    /// than `width` are split on chars. Advances come from the glyphs of single chars, so
+    /// kerning and ligatures are not taken into account.
+    pub fn layout_lines(&self, cx: &Cx2d, width: f64, text: &str) -> Vec<TextLayoutLine> {
+        let font_id = if let Some(font_id) = self.text_style.font.font_id {font_id} else {
+            return Vec::new()
+        };
+        let mut fonts_atlas = cx.fonts_atlas_rc.0.borrow_mut();
+        let font = if let Some(font) = fonts_atlas.fonts[font_id].as_mut() {font} else {
+            return Vec::new()
+        };
+        let font_size_total = self.text_style.font_size * 96.0 / (72.0 * font.ttf_font.units_per_em) * self.font_scale;
+        let wrap = if let TextWrap::Word = self.wrap {!width.is_nan()} else {false};
+        
+        let mut lines = Vec::new();
+        let mut paragraph_start = 0;
+        for paragraph in text.split('\n') {
+            // (byte offset, advance, is whitespace) for every char
+            let chars: Vec<(usize, f64, bool)> = paragraph.char_indices().map( | (i, c) | {
+                let advance = if let Some(glyph) = font.get_glyph(c) {
+                    glyph.horizontal_metrics.advance_width * font_size_total
+                } else {0.0};
+                (paragraph_start + i, advance, c.is_whitespace())
+            }).collect();
+            let paragraph_end = paragraph_start + paragraph.len();
+            let mut push_line = | start: usize, end: usize | {
+                let mut offsets = Vec::with_capacity(end - start + 1);
+                let mut x = 0.0;
+                offsets.push(x);
+                for (_, advance, _) in &chars[start..end] {
+                    x += advance;
+                    offsets.push(x);
+                }
+                lines.push(TextLayoutLine {
+                    start: chars.get(start).map_or(paragraph_end, | c | c.0),
+                    end: chars.get(end).map_or(paragraph_end, | c | c.0),
+                    offsets
+                });
+            };
+            
+            let mut line_start = 0;
+            let mut line_width = 0.0;
+            let mut word_start = None;
+            for (i, (_, advance, is_whitespace)) in chars.iter().enumerate() {
+                if !is_whitespace && i > line_start && chars[i - 1].2 {
+                    word_start = Some(i);
+                }
+                // trailing whitespace is allowed to hang over the edge
+                if wrap && !is_whitespace && i > line_start && line_width + advance > width {
+                    let end = match word_start {
+                        Some(word_start) if word_start > line_start => word_start,
+                        _ => i
+                    };
+                    push_line(line_start, end);
+                    line_width = chars[end..i].iter().map( | c | c.1).sum();
+                    line_start = end;
+                    word_start = None;
+                }
+                line_width += advance;
+            }
+            push_line(line_start, chars.len());
+            paragraph_start = paragraph_end + 1;
+        }
+        lines
+    }
+    
     pub fn get_line_spacing(&self) -> f64 {
         self.text_style.font_size * self.text_style.height_factor * self.font_scale * self.text_style.line_spacing
     }
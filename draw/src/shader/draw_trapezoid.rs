@@ -100,6 +100,10 @@ live_design!{
             let pos_min = vec2(self.a_xs.x, min(self.a_ys.x, self.a_ys.y));
             let pos_max = vec2(self.a_xs.y, max(self.a_ys.z, self.a_ys.w));
             let pos = mix(pos_min - 1.0, pos_max + 1.0, self.geom_pos);
+            // an empty clip rect means no clipping
+            if self.clip.z > self.clip.x {
+                pos = clamp(pos, self.clip.xy, self.clip.zw);
+            }
             
             // set the varyings
             self.v_p0 = vec2(self.a_xs.x, self.a_ys.x);
@@ -110,6 +114,47 @@ live_design!{
             return self.camera_projection * vec4(pos, 0.0, 1.0);
         }
     }
+    
+    DrawVectorPaint= {{DrawVectorPaint}} {
+        texture coverage: texture2d
+        varying v_pixel: vec2;
+        
+        fn gradient_color(self, t: float) -> vec4 {
+            if t <= self.offsets.x {
+                return self.color0;
+            }
+            if t <= self.offsets.y {
+                return mix(self.color0, self.color1, (t - self.offsets.x) / max(self.offsets.y - self.offsets.x, 0.0001));
+            }
+            if t <= self.offsets.z {
+                return mix(self.color1, self.color2, (t - self.offsets.y) / max(self.offsets.z - self.offsets.y, 0.0001));
+            }
+            if t <= self.offsets.w {
+                return mix(self.color2, self.color3, (t - self.offsets.z) / max(self.offsets.w - self.offsets.z, 0.0001));
+            }
+            return self.color3;
+        }
+        
+        fn pixel(self) -> vec4 {
+            let c = sample2d_rt(self.coverage, (self.v_pixel + self.coverage_offset) / self.coverage_size);
+            let cov = c.x;
+            if self.chan > 0.5 {
+                cov = c.y;
+            }
+            if self.chan > 1.5 {
+                cov = c.z;
+            }
+            cov = min(cov, 1.0);
+            let color = self.gradient_color(dot(self.gradient.xy, self.v_pixel) + self.gradient.z);
+            return vec4(color.rgb * color.a, color.a) * cov;
+        }
+        
+        fn vertex(self) -> vec4 {
+            let pos = mix(self.a_rect.xy, self.a_rect.xy + self.a_rect.zw, self.geom_pos);
+            self.v_pixel = pos;
+            return self.camera_projection * vec4(pos, 0.0, 1.0);
+        }
+    }
 }
 
 
@@ -122,6 +167,7 @@ pub struct DrawTrapezoidVector {
     #[calc] pub a_xs: Vec2,
     #[calc] pub a_ys: Vec4,
     #[calc] pub chan: f32,
+    #[calc] pub clip: Vec4,
 }
 
 impl LiveHook for DrawTrapezoidVector{
@@ -133,3 +179,31 @@ impl LiveHook for DrawTrapezoidVector{
     }
 }
 
+
+/// Paints the coverage of one shape, rendered into a channel of `coverage` by
+/// `DrawTrapezoidVector`, with a solid color or a linear gradient of up to 4 stops.
+#[derive(Live)]
+#[repr(C)]
+pub struct DrawVectorPaint {
+    #[live] pub geometry: GeometryQuad2D,
+    #[deref] pub draw_vars: DrawVars,
+    #[calc] pub a_rect: Vec4,
+    #[calc] pub coverage_offset: Vec2,
+    #[calc] pub coverage_size: Vec2,
+    #[calc] pub chan: f32,
+    #[calc] pub gradient: Vec4,
+    #[calc] pub offsets: Vec4,
+    #[calc] pub color0: Vec4,
+    #[calc] pub color1: Vec4,
+    #[calc] pub color2: Vec4,
+    #[calc] pub color3: Vec4,
+}
+
+impl LiveHook for DrawVectorPaint{
+    fn before_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]){
+        self.draw_vars.before_apply_init_shader(cx, apply_from, index, nodes, &self.geometry);
+    }
+    fn after_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.after_apply_update_self(cx, apply_from, index, nodes, &self.geometry);
+    }
+}
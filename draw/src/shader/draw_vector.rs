@@ -0,0 +1,734 @@
+use {
+    std::{
+        collections::HashMap,
+        f64::consts::PI,
+    },
+    crate::{
+        makepad_platform::*,
+        draw_list_2d::ManyInstances,
+        geometry::GeometryQuad2D,
+        cx_2d::Cx2d,
+        makepad_vector::geometry::Point,
+        makepad_vector::path::{LinePathCommand, Path, PathIterator},
+    },
+    bender_clipper::{Clipper, Operation},
+    bender_geometry::{
+        linear_path::Command,
+        mesh::Writer,
+        Mesh,
+        Point as MeshPoint,
+        Polygon,
+        Polyline,
+        Vector as MeshVector,
+    },
+    bender_offsetter::{CapKind, JoinKind},
+    bender_tessellator::Tessellator,
+};
+
+pub use crate::makepad_vector::{
+    svg::{LineCap, LineJoin, StrokeStyle},
+    trapezoidator::FillRule,
+};
+
+live_design!{
+    DrawVector = {{DrawVector}} {
+        varying v_pixel: vec2
+        varying v_alpha: float
+
+        fn vertex(self) -> vec4 {
+            // the quad collapses onto the triangle, its second half is degenerate
+            let p = mix(mix(self.v0, self.v1, self.geom_pos.x), self.v2, self.geom_pos.y);
+            let pos = self.rect_pos + p;
+            self.v_pixel = pos;
+            self.v_alpha = mix(mix(self.alpha.x, self.alpha.y, self.geom_pos.x), self.alpha.z, self.geom_pos.y);
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                pos.x,
+                pos.y,
+                self.draw_depth + self.draw_zbias,
+                1.
+            )))
+        }
+
+        fn get_color(self) -> vec4 {
+            return self.color
+        }
+
+        fn pixel(self) -> vec4 {
+            let alpha = self.v_alpha;
+            // an empty clip rect means no clipping
+            if self.draw_clip.z > self.draw_clip.x {
+                let inside = step(self.draw_clip.xy, self.v_pixel) * step(self.v_pixel, self.draw_clip.zw);
+                alpha = alpha * inside.x * inside.y;
+            }
+            let color = self.get_color();
+            return vec4(color.rgb * color.a, color.a) * alpha
+        }
+    }
+}
+
+/// An immediate mode canvas for anti-aliased paths. A path is built up with `move_to`, `line_to`,
+/// `cubic_to`, `arc` and friends, then `fill` and `stroke` tessellate it and append the triangles
+/// to the draw list, so consecutive paths batch into a single draw call. Coordinates are absolute,
+/// as with `DrawQuad::draw_abs`. The path is kept until `begin_path`, so it can be filled and
+/// stroked in turn.
+#[derive(Live)]
+#[repr(C)]
+pub struct DrawVector {
+    #[rust] pub many_instances: Option<ManyInstances>,
+    #[rust] path: Path,
+    #[rust] start: Option<DVec2>,
+    #[rust] current: Option<DVec2>,
+    #[rust] needs_move: bool,
+    #[rust] dashes: Vec<f64>,
+    #[rust] dash_offset: f64,
+    #[rust] tessellator: VectorTessellator,
+
+    #[live] pub geometry: GeometryQuad2D,
+    #[deref] pub draw_vars: DrawVars,
+    #[live] pub color: Vec4,
+    #[calc] pub rect_pos: Vec2,
+    #[calc] pub rect_size: Vec2,
+    #[calc] pub draw_clip: Vec4,
+    #[calc] pub v0: Vec2,
+    #[calc] pub v1: Vec2,
+    #[calc] pub v2: Vec2,
+    #[calc] pub alpha: Vec3,
+    #[live(1.0)] pub draw_depth: f32,
+}
+
+impl LiveHook for DrawVector {
+    fn before_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.before_apply_init_shader(cx, apply_from, index, nodes, &self.geometry);
+    }
+    fn after_apply(&mut self, cx: &mut Cx, apply_from: ApplyFrom, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.after_apply_update_self(cx, apply_from, index, nodes, &self.geometry);
+    }
+}
+
+impl DrawVector {
+    pub fn begin_path(&mut self) {
+        self.path.clear();
+        self.start = None;
+        self.current = None;
+        self.needs_move = false;
+    }
+
+    pub fn move_to(&mut self, p: DVec2) {
+        self.path.move_to(point(p));
+        self.start = Some(p);
+        self.current = Some(p);
+        self.needs_move = false;
+    }
+
+    pub fn line_to(&mut self, p: DVec2) {
+        if self.ensure_contour(p) {
+            self.path.line_to(point(p));
+        }
+        self.current = Some(p);
+    }
+
+    pub fn quad_to(&mut self, p1: DVec2, p: DVec2) {
+        self.ensure_contour(p1);
+        self.path.quadratic_to(point(p1), point(p));
+        self.current = Some(p);
+    }
+
+    pub fn cubic_to(&mut self, p1: DVec2, p2: DVec2, p: DVec2) {
+        self.ensure_contour(p1);
+        self.path.cubic_to(point(p1), point(p2), point(p));
+        self.current = Some(p);
+    }
+
+    /// Adds a circular arc from `start_angle` to `end_angle`, in radians and clockwise on screen.
+    /// A line connects the current point to the start of the arc.
+    pub fn arc(&mut self, center: DVec2, radius: f64, start_angle: f64, end_angle: f64) {
+        self.line_to(center + dvec2(start_angle.cos(), start_angle.sin()) * radius);
+        for [p1, p2, p] in arc_segments(center, radius, start_angle, end_angle) {
+            self.cubic_to(p1, p2, p);
+        }
+    }
+
+    pub fn close(&mut self) {
+        if self.start.is_some() && !self.needs_move {
+            self.path.close();
+            self.current = self.start;
+            self.needs_move = true;
+        }
+    }
+
+    pub fn rect(&mut self, rect: Rect) {
+        self.move_to(rect.pos);
+        self.line_to(rect.pos + dvec2(rect.size.x, 0.0));
+        self.line_to(rect.pos + rect.size);
+        self.line_to(rect.pos + dvec2(0.0, rect.size.y));
+        self.close();
+    }
+
+    pub fn circle(&mut self, center: DVec2, radius: f64) {
+        self.move_to(center + dvec2(radius, 0.0));
+        self.arc(center, radius, 0.0, 2.0 * PI);
+        self.close();
+    }
+
+    /// Sets the dash pattern for `stroke`, as alternating dash and gap lengths. An odd number of
+    /// lengths is repeated to make it even, and an empty or invalid pattern draws solid lines.
+    pub fn set_line_dash(&mut self, dashes: &[f64], offset: f64) {
+        self.dashes = dash_pattern(dashes);
+        self.dash_offset = offset;
+    }
+
+    /// Fills the current path with `color`. Open contours are closed implicitly.
+    pub fn fill(&mut self, cx: &mut Cx2d, fill_rule: FillRule) {
+        let dpi_factor = cx.current_dpi_factor();
+        let contours = self.flatten(0.25 / dpi_factor);
+        self.tessellator.polygons.clear();
+        for (points, _) in contours {
+            if points.len() >= 3 {
+                self.tessellator.polygons.push(Polygon {vertices: points.iter().map(|p| mesh_point(*p)).collect()});
+            }
+        }
+        let fill_rule = match fill_rule {
+            FillRule::NonZero => bender_clipper::FillRule::NonZero,
+            FillRule::EvenOdd => bender_clipper::FillRule::EvenOdd,
+        };
+        self.tessellator.fill(fill_rule, (1.0 / dpi_factor) as f32, 1.0);
+        self.draw_triangles(cx);
+    }
+
+    /// Strokes the current path with `color`, using the dash pattern set with `set_line_dash`.
+    pub fn stroke(&mut self, cx: &mut Cx2d, style: &StrokeStyle) {
+        let dpi_factor = cx.current_dpi_factor();
+        let pixel = 1.0 / dpi_factor;
+        // strokes thinner than a device pixel are drawn a pixel wide and faded out instead
+        let (width, alpha) = if style.width < pixel {
+            (pixel, style.width.max(0.0) / pixel)
+        } else {
+            (style.width, 1.0)
+        };
+        if alpha <= 0.0 {
+            return
+        }
+        let half_width = width * 0.5;
+        let options = bender_offsetter::Options {
+            join_kind: match style.join {
+                LineJoin::Miter => JoinKind::Miter,
+                LineJoin::Round => JoinKind::Round,
+                LineJoin::Bevel => JoinKind::Bevel,
+            },
+            cap_kind: match style.cap {
+                LineCap::Butt => CapKind::Butt,
+                LineCap::Round => CapKind::Round,
+                LineCap::Square => CapKind::Square,
+            },
+            // the offsetter measures the miter from the centerline, svg from the inner corner
+            miter_limit: (style.miter_limit * 0.5) as f32,
+            arc_tolerance: (0.25 * pixel).min(half_width * 0.5) as f32,
+        };
+        let closed_options = bender_offsetter::Options {cap_kind: CapKind::Butt, ..options};
+
+        let contours = self.flatten(0.25 * pixel);
+        let polygons = &mut self.tessellator.polygons;
+        polygons.clear();
+        let mut offset = |points: &[Point], options| {
+            let polyline = Polyline {vertices: points.iter().map(|p| mesh_point(*p)).collect()};
+            polygons.push(bender_offsetter::offset_polyline(&polyline, half_width as f32, options));
+        };
+        for (mut points, closed) in contours {
+            if points.len() < 2 {
+                continue;
+            }
+            if closed {
+                points.push(points[0]);
+            }
+            if !self.dashes.is_empty() {
+                for dash in dash_polyline(&points, &self.dashes, self.dash_offset, closed) {
+                    offset(&dash, options);
+                }
+            }
+            else if closed {
+                // run over the first segment again so the start gets a join instead of caps
+                points.push(points[1]);
+                offset(&points, closed_options);
+            }
+            else {
+                offset(&points, options);
+            }
+        }
+        self.tessellator.fill(bender_clipper::FillRule::NonZero, pixel as f32, alpha as f32);
+        self.draw_triangles(cx);
+    }
+
+    pub fn new_draw_call(&self, cx: &mut Cx2d) {
+        cx.new_draw_call(&self.draw_vars);
+    }
+
+    pub fn begin_many_instances(&mut self, cx: &mut Cx2d) {
+        let mi = cx.begin_many_aligned_instances(&self.draw_vars);
+        self.many_instances = mi;
+    }
+
+    pub fn end_many_instances(&mut self, cx: &mut Cx2d) {
+        if let Some(mi) = self.many_instances.take() {
+            let new_area = cx.end_many_instances(mi);
+            self.draw_vars.area = cx.update_area_refs(self.draw_vars.area, new_area);
+        }
+    }
+
+    // starts a contour at the current point if there is none, returns false if p starts it
+    fn ensure_contour(&mut self, p: DVec2) -> bool {
+        match self.current {
+            None => {
+                self.move_to(p);
+                false
+            }
+            Some(current) => {
+                if self.needs_move {
+                    self.move_to(current);
+                }
+                true
+            }
+        }
+    }
+
+    // the contours of the path as polylines, and whether each of them is closed
+    fn flatten(&self, tolerance: f64) -> Vec<(Vec<Point>, bool)> {
+        let mut contours: Vec<(Vec<Point>, bool)> = Vec::new();
+        // both internal iterator traits cover std iterators, so neither is imported
+        makepad_vector::internal_iter::InternalIterator::for_each(self.path.commands().linearize(tolerance), &mut |command| {
+            match command {
+                LinePathCommand::MoveTo(p) => contours.push((vec![p], false)),
+                LinePathCommand::LineTo(p) => if let Some((points, _)) = contours.last_mut() {
+                    if points.last() != Some(&p) {
+                        points.push(p);
+                    }
+                }
+                LinePathCommand::Close => if let Some((points, closed)) = contours.last_mut() {
+                    if points.len() > 1 && points.first() == points.last() {
+                        points.pop();
+                    }
+                    *closed = true;
+                }
+            }
+            true
+        });
+        contours
+    }
+
+    fn draw_triangles(&mut self, cx: &mut Cx2d) {
+        let triangles = std::mem::take(&mut self.tessellator.triangles);
+        if !triangles.is_empty() && self.draw_vars.can_instance() {
+            let mut min = triangles[0].points[0];
+            let mut max = min;
+            for triangle in &triangles {
+                for p in triangle.points {
+                    min = min.min(p);
+                    max = max.max(p);
+                }
+            }
+            self.rect_pos = vec2(min.x(), min.y());
+            self.rect_size = vec2(max.x() - min.x(), max.y() - min.y());
+
+            let began = self.many_instances.is_none();
+            if began {
+                self.begin_many_instances(cx);
+            }
+            if let Some(mi) = &mut self.many_instances {
+                for triangle in &triangles {
+                    let [p0, p1, p2] = triangle.points.map(|p| vec2(p.x() - min.x(), p.y() - min.y()));
+                    self.v0 = p0;
+                    self.v1 = p1;
+                    self.v2 = p2;
+                    self.alpha = vec3(triangle.alpha[0], triangle.alpha[1], triangle.alpha[2]);
+                    mi.instances.extend_from_slice(self.draw_vars.as_slice());
+                }
+            }
+            if began {
+                self.end_many_instances(cx);
+            }
+        }
+        self.tessellator.triangles = triangles;
+        self.tessellator.triangles.clear();
+    }
+}
+
+struct VectorTriangle {
+    points: [MeshPoint; 3],
+    alpha: [f32; 3],
+}
+
+// the clipper and tessellator with their scratch buffers, reused between paths
+struct VectorTessellator {
+    clipper: Clipper,
+    tessellator: Tessellator,
+    polygons: Vec<Polygon>,
+    boundary: Vec<Command>,
+    mesh: Mesh,
+    insets: HashMap<(u32, u32), MeshPoint>,
+    triangles: Vec<VectorTriangle>,
+    clip_pending_edges: Vec<bender_clipper::PendingEdge>,
+    clip_left_edges: Vec<usize>,
+    clip_right_edges: Vec<usize>,
+    clip_left_boundary: Vec<usize>,
+    clip_right_boundary: Vec<usize>,
+    pending_edges: Vec<bender_tessellator::PendingEdge>,
+    active_edges: Vec<bender_tessellator::ActiveEdge>,
+}
+
+impl Default for VectorTessellator {
+    fn default() -> Self {
+        Self {
+            clipper: Clipper::new(),
+            tessellator: Tessellator::new(),
+            polygons: Vec::new(),
+            boundary: Vec::new(),
+            mesh: Mesh::new(),
+            insets: HashMap::new(),
+            triangles: Vec::new(),
+            clip_pending_edges: Vec::new(),
+            clip_left_edges: Vec::new(),
+            clip_right_edges: Vec::new(),
+            clip_left_boundary: Vec::new(),
+            clip_right_boundary: Vec::new(),
+            pending_edges: Vec::new(),
+            active_edges: Vec::new(),
+        }
+    }
+}
+
+impl VectorTessellator {
+    // fills `polygons` into `triangles`. The boundary gets an anti-aliasing fringe `fringe` wide
+    // that is centered on it, so the area covered stays the same.
+    fn fill(&mut self, fill_rule: bender_clipper::FillRule, fringe: f32, alpha: f32) {
+        self.triangles.clear();
+        if self.polygons.is_empty() {
+            return
+        }
+        // resolving the fill rule gives non intersecting contours, with the filled area on the
+        // right of every edge
+        self.boundary.clear();
+        let boundary = &mut self.boundary;
+        let clip = self.clipper.clip_polygons(
+            Operation::Union,
+            &self.polygons,
+            &[],
+            bender_clipper::Options {subject_fill_rule: fill_rule, ..Default::default()},
+            &mut self.clip_pending_edges,
+            &mut self.clip_left_edges,
+            &mut self.clip_right_edges,
+            &mut self.clip_left_boundary,
+            &mut self.clip_right_boundary,
+        );
+        bender_internal_iter::InternalIterator::for_each(clip, &mut |command| {
+            boundary.push(command);
+            true
+        });
+        if self.boundary.is_empty() {
+            return
+        }
+
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+        self.tessellator.tessellate(
+            self.boundary.iter().cloned(),
+            &mut Writer::new(&mut self.mesh),
+            &mut self.pending_edges,
+            &mut self.active_edges,
+        );
+
+        // the fringe fades out from half its width inside the boundary to half its width outside
+        self.insets.clear();
+        let mut contour = Vec::new();
+        for command in &self.boundary {
+            match *command {
+                Command::MoveTo(p) => {
+                    contour.clear();
+                    contour.push(p);
+                }
+                Command::LineTo(p) => contour.push(p),
+                Command::Close => {
+                    let offsets: Vec<MeshVector> = (0..contour.len()).map(|index| {
+                        let prev = contour[(index + contour.len() - 1) % contour.len()];
+                        let next = contour[(index + 1) % contour.len()];
+                        miter(contour[index] - prev, next - contour[index]) * (fringe * 0.5)
+                    }).collect();
+                    for index in 0..contour.len() {
+                        let next = (index + 1) % contour.len();
+                        let inner = [contour[index] - offsets[index], contour[next] - offsets[next]];
+                        let outer = [contour[index] + offsets[index], contour[next] + offsets[next]];
+                        self.triangles.push(VectorTriangle {
+                            points: [inner[0], inner[1], outer[1]],
+                            alpha: [alpha, alpha, 0.0],
+                        });
+                        self.triangles.push(VectorTriangle {
+                            points: [inner[0], outer[1], outer[0]],
+                            alpha: [alpha, 0.0, 0.0],
+                        });
+                        self.insets.entry(point_key(contour[index])).or_insert(inner[0]);
+                    }
+                }
+            }
+        }
+
+        let vertex = |index: u16| {
+            let p = self.mesh.vertices[index as usize].position;
+            let p = MeshPoint::new(p[0], p[1]);
+            self.insets.get(&point_key(p)).cloned().unwrap_or(p)
+        };
+        for indices in self.mesh.indices.chunks(3) {
+            self.triangles.push(VectorTriangle {
+                points: [vertex(indices[0]), vertex(indices[1]), vertex(indices[2])],
+                alpha: [alpha; 3],
+            });
+        }
+    }
+}
+
+// the offset of a boundary vertex that moves both of its edges outward by one unit. Sharp
+// corners are limited to four units.
+fn miter(d0: MeshVector, d1: MeshVector) -> MeshVector {
+    let outward = |d: MeshVector| d.normalize().map(|d| MeshVector::new(d.y(), -d.x()));
+    let (n0, n1) = match (outward(d0), outward(d1)) {
+        (Some(n0), Some(n1)) => (n0, n1),
+        (Some(n), None) | (None, Some(n)) => (n, n),
+        (None, None) => return MeshVector::new(0.0, 0.0),
+    };
+    let d = 1.0 + n0.dot(n1);
+    if d < 0.125 {
+        return (n0 + n1).normalize().unwrap_or(n0) * 4.0;
+    }
+    (n0 + n1) / d
+}
+
+// the control points and end point of the cubic curves, of at most 90 degrees each, that make
+// up a circular arc
+fn arc_segments(center: DVec2, radius: f64, start_angle: f64, end_angle: f64) -> Vec<[DVec2; 3]> {
+    let sweep = (end_angle - start_angle).clamp(-2.0 * PI, 2.0 * PI);
+    if radius <= 0.0 || sweep == 0.0 {
+        return Vec::new()
+    }
+    let point_at = |angle: f64| center + dvec2(angle.cos(), angle.sin()) * radius;
+    let segments = (sweep.abs() / (PI / 2.0)).ceil() as usize;
+    let step = sweep / segments as f64;
+    let kappa = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+    let tangent_at = |angle: f64| dvec2(-angle.sin(), angle.cos()) * kappa;
+    (0..segments).map(|index| {
+        let a0 = start_angle + step * index as f64;
+        let a1 = a0 + step;
+        [point_at(a0) + tangent_at(a0), point_at(a1) - tangent_at(a1), point_at(a1)]
+    }).collect()
+}
+
+// an even dash pattern, or an empty one for solid lines
+fn dash_pattern(dashes: &[f64]) -> Vec<f64> {
+    if dashes.iter().any(|dash| !dash.is_finite() || *dash < 0.0) || dashes.iter().sum::<f64>() <= 0.0 {
+        return Vec::new()
+    }
+    let mut pattern = dashes.to_vec();
+    if dashes.len() % 2 == 1 {
+        pattern.extend_from_slice(dashes);
+    }
+    pattern
+}
+
+// splits a polyline into dashes, joining the first and last dash of a closed contour when they
+// meet at its start. dashes of zero length are left out
+fn dash_polyline(points: &[Point], dashes: &[f64], offset: f64, closed: bool) -> Vec<Vec<Point>> {
+    let total: f64 = dashes.iter().sum();
+    let mut index = 0;
+    let mut remaining = offset.rem_euclid(total);
+    while remaining >= dashes[index] {
+        remaining -= dashes[index];
+        index = (index + 1) % dashes.len();
+    }
+    remaining = dashes[index] - remaining;
+    let starts_on = index % 2 == 0;
+
+    let mut out = Vec::new();
+    let mut dash = if starts_on {vec![points[0]]} else {Vec::new()};
+    for segment in points.windows(2) {
+        let (p0, p1) = (segment[0], segment[1]);
+        let length = (p1 - p0).length();
+        let mut t = 0.0;
+        while t < length {
+            let step = remaining.min(length - t);
+            t += step;
+            remaining -= step;
+            let p = p0.lerp(p1, t / length);
+            if index % 2 == 0 && dash.last() != Some(&p) {
+                dash.push(p);
+            }
+            if remaining <= 0.0 {
+                if index % 2 == 0 {
+                    out.push(std::mem::take(&mut dash));
+                } else {
+                    dash.push(p);
+                }
+                index = (index + 1) % dashes.len();
+                remaining = dashes[index];
+            }
+        }
+    }
+    if dash.len() > 1 {
+        if closed && starts_on && !out.is_empty() {
+            dash.extend_from_slice(&out[0][1..]);
+            out[0] = dash;
+        } else {
+            out.push(dash);
+        }
+    }
+    out.retain(|dash| dash.len() > 1);
+    out
+}
+
+fn point(p: DVec2) -> Point {
+    Point::new(p.x, p.y)
+}
+
+fn mesh_point(p: Point) -> MeshPoint {
+    MeshPoint::new(p.x as f32, p.y as f32)
+}
+
+fn point_key(p: MeshPoint) -> (u32, u32) {
+    (p.x().to_bits(), p.y().to_bits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    // the dashes of a polyline as ranges of the distance along it
+    fn dash_ranges(points: &[Point], dashes: &[f64], offset: f64, closed: bool) -> Vec<(f64, f64)> {
+        let along = |p: Point| {
+            let mut distance = 0.0;
+            for segment in points.windows(2) {
+                let (p0, p1) = (segment[0], segment[1]);
+                let length = (p1 - p0).length();
+                if ((p - p0).length() + (p1 - p).length() - length).abs() < 1e-9 {
+                    return distance + (p - p0).length()
+                }
+                distance += length;
+            }
+            panic!("{:?} is not on the polyline", p)
+        };
+        dash_polyline(points, &dash_pattern(dashes), offset, closed).iter()
+            .map(|dash| (along(dash[0]), along(*dash.last().unwrap())))
+            .collect()
+    }
+
+    fn assert_ranges(ranges: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        assert_eq!(ranges.len(), expected.len(), "{:?}", ranges);
+        for (range, expected) in ranges.iter().zip(expected) {
+            assert!(near(range.0, expected.0) && near(range.1, expected.1), "{:?} != {:?}", ranges, expected);
+        }
+    }
+
+    #[test]
+    fn dash_offsets() {
+        let line = [Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(10.0, 0.0)];
+        assert_ranges(dash_ranges(&line, &[2.0, 2.0], 0.0, false), &[(0.0, 2.0), (4.0, 6.0), (8.0, 10.0)]);
+        assert_ranges(dash_ranges(&line, &[2.0, 2.0], 1.0, false), &[(0.0, 1.0), (3.0, 5.0), (7.0, 9.0)]);
+        // offsets wrap around the pattern, in both directions
+        assert_ranges(dash_ranges(&line, &[2.0, 2.0], -1.0, false), &[(1.0, 3.0), (5.0, 7.0), (9.0, 10.0)]);
+        assert_ranges(dash_ranges(&line, &[2.0, 2.0], 9.0, false), &[(0.0, 1.0), (3.0, 5.0), (7.0, 9.0)]);
+        // a dash crossing a vertex keeps it
+        let dashes = dash_polyline(&line, &[5.0, 1.0], 0.0, false);
+        assert_eq!(dashes[0], vec![line[0], line[1], Point::new(5.0, 0.0)]);
+    }
+
+    #[test]
+    fn dash_patterns() {
+        // odd patterns repeat, so dashes and gaps swap the second time around
+        assert_eq!(dash_pattern(&[3.0, 1.0, 2.0]), vec![3.0, 1.0, 2.0, 3.0, 1.0, 2.0]);
+        let line = [Point::new(0.0, 0.0), Point::new(12.0, 0.0)];
+        assert_ranges(dash_ranges(&line, &[3.0, 1.0, 2.0], 0.0, false), &[(0.0, 3.0), (4.0, 6.0), (9.0, 10.0)]);
+        // patterns that can't be drawn give solid lines
+        for dashes in [&[][..], &[0.0, 0.0], &[1.0, -1.0], &[f64::NAN, 1.0], &[f64::INFINITY]] {
+            assert!(dash_pattern(dashes).is_empty(), "{:?}", dashes);
+        }
+    }
+
+    #[test]
+    fn zero_length_dashes() {
+        let line = [Point::new(0.0, 0.0), Point::new(6.0, 0.0)];
+        // dashes of no length draw nothing
+        assert!(dash_ranges(&line, &[0.0, 2.0], 0.0, false).is_empty());
+        assert_ranges(dash_ranges(&line, &[0.0, 2.0, 1.0, 2.0], 0.0, false), &[(2.0, 3.0)]);
+        // gaps of no length split the line without a hole
+        assert_ranges(dash_ranges(&line, &[2.0, 0.0], 0.0, false), &[(0.0, 2.0), (2.0, 4.0), (4.0, 6.0)]);
+    }
+
+    #[test]
+    fn dash_closed_contours() {
+        // a square as stroke passes it, with the first point repeated at the end
+        let square = [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+            Point::new(0.0, 10.0),
+            Point::new(0.0, 0.0),
+        ];
+        let open = dash_polyline(&square, &[6.0, 4.0], 3.0, false);
+        assert_eq!(open.len(), 5);
+        // the dash running into the start continues into the first dash, over the corner
+        let closed = dash_polyline(&square, &[6.0, 4.0], 3.0, true);
+        assert_eq!(closed.len(), 4);
+        let joined = [Point::new(0.0, 3.0), Point::new(0.0, 0.0), Point::new(3.0, 0.0)];
+        assert_eq!(closed[0].len(), joined.len());
+        assert!(closed[0].iter().zip(joined).all(|(p, q)| (*p - q).length() < 1e-9), "{:?}", closed[0]);
+        assert_eq!(closed[1..], open[1..4]);
+        // contours that start in a gap have nothing to join
+        assert_eq!(dash_polyline(&square, &[6.0, 4.0], 8.0, true).len(), 4);
+    }
+
+    #[test]
+    fn miter_offsets() {
+        let v = |x: f64, y: f64| MeshVector::new(x as f32, y as f32);
+        let close = |a: MeshVector, b: MeshVector| (a - b).length() < 1e-5;
+        // both edges move out by one unit
+        let corner = miter(v(1.0, 0.0), v(0.0, 1.0));
+        assert!(close(corner, v(1.0, -1.0)), "{:?}", corner);
+        assert!(close(miter(v(2.0, 0.0), v(3.0, 0.0)), v(0.0, -1.0)));
+        let (d0, d1) = (v(1.0, 0.0), v(1.0, 1.0));
+        let m = miter(d0, d1);
+        for d in [d0, d1] {
+            let n = d.normalize().unwrap();
+            assert!((m.dot(MeshVector::new(n.y(), -n.x())) - 1.0).abs() < 1e-5);
+        }
+        // a corner that almost turns back is limited
+        let sharp = miter(v(1.0, 0.0), v(-1.0, 0.01));
+        assert!((sharp.length() - 4.0).abs() < 1e-4, "{:?}", sharp);
+        // edges without a direction take that of the other one
+        assert!(close(miter(v(0.0, 0.0), v(0.0, 2.0)), v(1.0, 0.0)));
+        assert!(close(miter(v(0.0, 0.0), v(0.0, 0.0)), v(0.0, 0.0)));
+    }
+
+    #[test]
+    fn arc_segment_endpoints() {
+        let center = dvec2(10.0, 20.0);
+        let on_circle = |p: DVec2, angle: f64| near(p.x, 10.0 + 5.0 * angle.cos()) && near(p.y, 20.0 + 5.0 * angle.sin());
+        let circle = arc_segments(center, 5.0, 0.0, 2.0 * PI);
+        assert_eq!(circle.len(), 4);
+        for (index, [_, _, p]) in circle.iter().enumerate() {
+            assert!(on_circle(*p, (index + 1) as f64 * PI / 2.0), "{:?}", p);
+        }
+        // the middle of each curve is close to the circle as well
+        let mut p0 = center + dvec2(5.0, 0.0);
+        for [p1, p2, p] in &circle {
+            let mid = (p0 + *p1 * 3.0 + *p2 * 3.0 + *p) / 8.0;
+            assert!(((mid - center).length() - 5.0).abs() < 5.0 * 1e-3);
+            p0 = *p;
+        }
+        // segments split the sweep evenly, and counterclockwise arcs run backwards
+        let arc = arc_segments(center, 5.0, 0.5, 0.5 - 1.0 * PI);
+        assert_eq!(arc.len(), 2);
+        assert!(on_circle(arc[0][2], 0.5 - 0.5 * PI) && on_circle(arc[1][2], 0.5 - PI));
+        // sweeps past a full circle stop at one
+        assert_eq!(arc_segments(center, 5.0, 0.0, 7.0 * PI).len(), 4);
+        assert!(arc_segments(center, 5.0, 1.0, 1.0).is_empty());
+        assert!(arc_segments(center, 0.0, 0.0, PI).is_empty());
+    }
+}
@@ -14,7 +14,8 @@ pub struct Layout {
     #[live] pub padding: Padding,
     #[live] pub align: Align,
     #[live] pub flow: Flow,
-    #[live] pub spacing: f64
+    #[live] pub spacing: f64,
+    #[live] pub grid: GridLayout,
 }
 
 impl Default for Layout{
@@ -26,7 +27,8 @@ impl Default for Layout{
             padding: Padding::default(),
             align: Align{x:0.0,y:0.0},
             flow: Flow::Right,
-            spacing: 0.0
+            spacing: 0.0,
+            grid: GridLayout::default(),
         }
     }
 }
@@ -74,7 +76,36 @@ impl Default for Axis2 {
 pub enum Flow {
     #[pick] Right,
     Down,
-    Overlay
+    Overlay,
+    RightWrap,
+    Grid
+}
+
+#[derive(Copy, Clone, Debug, Live, LiveHook)]
+#[live_ignore]
+pub enum GridTrack {
+    #[pick] Fit,
+    #[live(1.0)] Fr(f64),
+    #[live(100.0)] Fixed(f64),
+}
+
+// written as grid: {columns: [..], rows: [..], row_spacing: 5} so the names stay out of the layout splat.
+// row_spacing is also the space between the rows of a RightWrap flow
+#[derive(Copy, Clone, Debug, Default, Live, LiveHook)]
+#[live_ignore]
+pub struct GridLayout {
+    #[live] pub columns: GridTracks,
+    #[live] pub rows: GridTracks,
+    #[live] pub row_spacing: f64,
+}
+
+pub const GRID_TRACKS_MAX: usize = 8;
+
+// fixed capacity so Layout stays Copy. tracks past len are Fit
+#[derive(Copy, Clone, Debug)]
+pub struct GridTracks {
+    len: usize,
+    tracks: [GridTrack; GRID_TRACKS_MAX],
 }
 
 #[derive(Copy, Clone, Debug, Live)]
@@ -94,7 +125,12 @@ pub enum DeferWalk{
         other_axis: Size,
         pos: DVec2
     },
-    Resolved(Walk)
+    Resolved(Walk),
+    GridCell {
+        row: usize,
+        column: usize,
+        walk: Walk
+    }
 }
 
 #[derive(Debug)]
@@ -111,6 +147,8 @@ pub enum AlignEntry{
 pub struct TurtleWalk {
     align_start: usize,
     defer_index: usize,
+    // (row, column) in a wrap or grid flow, none for abs_pos walks
+    cell: Option<(usize, usize)>,
     rect: Rect,
 }
 
@@ -128,6 +166,9 @@ pub struct Turtle {
     height: f64,
     width_used: f64,
     height_used: f64,
+    row_index: usize,
+    row_start: usize,
+    cell_index: usize,
     guard_area: Area
 }
 
@@ -183,6 +224,17 @@ impl<'a> Cx2d<'a> {
                     pos: pos + spacing
                 })
             },
+            Flow::Grid if walk.width.is_fill() && size.x.is_nan() || walk.height.is_fill() && size.y.is_nan() => {
+                // the cell size depends on content, so it is resolved after the rest of the grid
+                let columns = turtle.layout.grid.columns.len().max(1);
+                let cell = turtle.cell_index;
+                turtle.cell_index += 1;
+                Some(DeferWalk::GridCell {
+                    row: cell / columns,
+                    column: cell % columns,
+                    walk
+                })
+            }
             _ => {
                 None
             }
@@ -208,6 +260,9 @@ impl<'a> Cx2d<'a> {
             shift: dvec2(0.0,0.0),
             width_used: layout.padding.left,
             height_used: layout.padding.top,
+            row_index: 0,
+            row_start: self.turtle_walks.len(),
+            cell_index: 0,
             guard_area: Area::Empty,
         };
         self.turtles.push(turtle);
@@ -293,6 +348,9 @@ impl<'a> Cx2d<'a> {
             shift: dvec2(0.0,0.0),
             width_used: layout.padding.left,
             height_used: layout.padding.top,
+            row_index: 0,
+            row_start: self.turtle_walks.len(),
+            cell_index: 0,
             guard_area,
         };
         
@@ -313,11 +371,16 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn end_turtle_with_guard(&mut self, guard_area: Area) -> Rect {
-        let turtle = self.turtles.pop().unwrap();
+        let mut turtle = self.turtles.pop().unwrap();
         if guard_area != turtle.guard_area {
             panic!("End turtle guard area misaligned!, begin/end pair not matched begin {:?} end {:?}", turtle.guard_area, guard_area)
         }
         
+        // grid tracks are sized by their content, so the grid has to be placed before we know our size
+        if let Flow::Grid = turtle.layout.flow {
+            self.place_grid_cells(&mut turtle);
+        }
+        
         // computed height
         let w = if turtle.width.is_nan() {
             Size::Fixed(turtle.width_used + turtle.layout.padding.right - turtle.layout.scroll.x)
@@ -392,6 +455,10 @@ impl<'a> Cx2d<'a> {
                     self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
                 }
             }
+            Flow::RightWrap => {
+                self.align_wrap_rows(&turtle);
+            }
+            Flow::Grid => ()
         }
 
         self.turtle_walks.truncate(turtle.turtle_walks_start);
@@ -441,6 +508,7 @@ impl<'a> Cx2d<'a> {
     
      fn walk_turtle_move(&mut self, walk: Walk, align_start: usize) -> Rect {
         
+        let wrap_right = self.wrap_right_edge();
         let turtle = self.turtles.last_mut().unwrap();
         let size = dvec2(
             turtle.eval_width(walk.width, walk.margin, turtle.layout.flow),
@@ -451,19 +519,23 @@ impl<'a> Cx2d<'a> {
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: 0,
+                cell: None,
                 rect: Rect {pos, size: size + walk.margin.size()}
             });
             
             match turtle.layout.flow {
-                Flow::Right=>turtle.update_height_max(pos.y, size.y + walk.margin.size().y),
+                Flow::Right | Flow::RightWrap=>turtle.update_height_max(pos.y, size.y + walk.margin.size().y),
                 Flow::Down=>turtle.update_width_max(pos.x, size.x + walk.margin.size().x),
                 _=>()
             }
             Rect {pos: pos + walk.margin.left_top(), size}
         }
         else {
-            let spacing = turtle.child_spacing(self.turtle_walks.len());
-            let pos = turtle.pos;
+            let walks_len = self.turtle_walks.len();
+            let mut spacing = turtle.child_spacing(walks_len);
+            let mut pos = turtle.pos;
+            let mut cell = None;
+            let mut wrap_shift = None;
         
             let margin_size = walk.margin.size();
             match turtle.layout.flow {
@@ -493,13 +565,45 @@ impl<'a> Cx2d<'a> {
                     turtle.update_width_max(turtle.pos.x, size.x);
                     turtle.update_height_max(turtle.pos.y,size.y);
                 }
+                Flow::RightWrap => {
+                    // an item that runs past the right edge starts a new row,
+                    // unless it is the first one on its row
+                    if walks_len > turtle.row_start && pos.x + spacing.x + size.x + margin_size.x > wrap_right {
+                        let row_pos = dvec2(
+                            turtle.origin.x + turtle.layout.padding.left,
+                            turtle.origin.y + turtle.height_used + turtle.layout.grid.row_spacing
+                        );
+                        // a nested turtle already drew its items at the old position
+                        wrap_shift = Some(row_pos - pos - spacing);
+                        pos = row_pos;
+                        spacing = dvec2(0.0, 0.0);
+                        turtle.row_index += 1;
+                        turtle.row_start = walks_len;
+                    }
+                    pos += spacing;
+                    spacing = dvec2(0.0, 0.0);
+                    cell = Some((turtle.row_index, walks_len - turtle.row_start));
+                    turtle.pos = dvec2(pos.x + size.x + margin_size.x, pos.y);
+                    turtle.update_width_max(turtle.pos.x, 0.0);
+                    turtle.update_height_max(pos.y, size.y + margin_size.y);
+                }
+                Flow::Grid => {
+                    // everything is walked at the content origin and moved into its cell at end_turtle
+                    let columns = turtle.layout.grid.columns.len().max(1);
+                    cell = Some((turtle.cell_index / columns, turtle.cell_index % columns));
+                    turtle.cell_index += 1;
+                }
             };
             
             self.turtle_walks.push(TurtleWalk {
                 align_start,
                 defer_index: turtle.defer_count,
+                cell,
                 rect: Rect {pos, size: size + margin_size}
             });
+            if let Some(shift) = wrap_shift {
+                self.move_align_list(shift.x, shift.y, align_start, self.align_list.len(), false, dvec2(0.0, 0.0));
+            }
             Rect {pos: pos + walk.margin.left_top() + spacing, size}
         }
     }
@@ -649,6 +753,123 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    // the right edge the rows of a RightWrap flow wrap at. a Fit width has no edge of its own,
+    // so it wraps where the nearest parent with a known width ends
+    fn wrap_right_edge(&self) -> f64 {
+        let turtle = self.turtles.last().unwrap();
+        if !matches!(turtle.layout.flow, Flow::RightWrap) {
+            return f64::NAN
+        }
+        if !turtle.width.is_nan() {
+            return turtle.origin.x + turtle.width - turtle.layout.padding.right
+        }
+        for parent in self.turtles.iter().rev().skip(1) {
+            if !parent.width.is_nan() {
+                return parent.origin.x + parent.width - parent.layout.padding.right - turtle.layout.padding.right
+            }
+        }
+        f64::NAN
+    }
+    
+    fn align_wrap_rows(&mut self, turtle: &Turtle) {
+        // every row is aligned on its own within the padded width
+        let left = turtle.origin.x + turtle.layout.padding.left;
+        let right = if turtle.width.is_nan() {
+            turtle.origin.x + turtle.width_used
+        }
+        else {
+            turtle.origin.x + turtle.width - turtle.layout.padding.right
+        };
+        let mut rows = vec![(left, 0.0f64); turtle.row_index + 1];
+        for walk in &self.turtle_walks[turtle.turtle_walks_start..] {
+            if let Some((row, _)) = walk.cell {
+                rows[row].0 = rows[row].0.max(walk.rect.pos.x + walk.rect.size.x);
+                rows[row].1 = rows[row].1.max(walk.rect.size.y);
+            }
+        }
+        for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+            let walk = &self.turtle_walks[i];
+            let (shift_x, shift_y) = if let Some((row, _)) = walk.cell {
+                let (row_right, row_height) = rows[row];
+                (
+                    turtle.layout.align.x * (right - row_right),
+                    turtle.layout.align.y * (row_height - walk.rect.size.y)
+                )
+            }
+            else {
+                (0.0, 0.0)
+            };
+            let align_start = walk.align_start;
+            let align_end = self.get_turtle_walk_align_end(i);
+            self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+        }
+    }
+    
+    fn grid_cells(&self, turtle: &Turtle) -> GridCells {
+        let layout = &turtle.layout;
+        let columns = layout.grid.columns.len().max(1);
+        let rows = layout.grid.rows.len().max((turtle.cell_index + columns - 1) / columns);
+        
+        let mut column_fit = vec![0.0f64; columns];
+        let mut row_fit = vec![0.0f64; rows];
+        for walk in &self.turtle_walks[turtle.turtle_walks_start..] {
+            if let Some((row, column)) = walk.cell {
+                column_fit[column] = column_fit[column].max(walk.rect.size.x);
+                row_fit[row] = row_fit[row].max(walk.rect.size.y);
+            }
+        }
+        let mut column_size = vec![0.0; columns];
+        layout.grid.columns.resolve(turtle.width - layout.padding.width(), layout.spacing, &column_fit, &mut column_size);
+        let mut row_size = vec![0.0; rows];
+        layout.grid.rows.resolve(turtle.height - layout.padding.height(), layout.grid.row_spacing, &row_fit, &mut row_size);
+        
+        let track_pos = | sizes: &[f64], spacing: f64 | {
+            let mut pos = Vec::with_capacity(sizes.len());
+            let mut p = 0.0;
+            for size in sizes {
+                pos.push(p);
+                p += size + spacing;
+            }
+            (pos, (p - spacing).max(0.0))
+        };
+        let (column_pos, width) = track_pos(&column_size, layout.spacing);
+        let (row_pos, height) = track_pos(&row_size, layout.grid.row_spacing);
+        GridCells {
+            origin: turtle.origin + layout.padding.left_top(),
+            column_pos,
+            column_size,
+            row_pos,
+            row_size,
+            size: dvec2(width, height)
+        }
+    }
+    
+    fn place_grid_cells(&mut self, turtle: &mut Turtle) {
+        let grid = self.grid_cells(turtle);
+        let align = turtle.layout.align;
+        for i in turtle.turtle_walks_start..self.turtle_walks.len() {
+            let walk = &self.turtle_walks[i];
+            let (shift_x, shift_y) = if let Some((row, column)) = walk.cell {
+                let size = dvec2(
+                    if walk.rect.size.x.is_nan() {0.0} else {walk.rect.size.x},
+                    if walk.rect.size.y.is_nan() {0.0} else {walk.rect.size.y}
+                );
+                (
+                    grid.origin.x + grid.column_pos[column] + align.x * (grid.column_size[column] - size.x) - walk.rect.pos.x,
+                    grid.origin.y + grid.row_pos[row] + align.y * (grid.row_size[row] - size.y) - walk.rect.pos.y
+                )
+            }
+            else {
+                (0.0, 0.0)
+            };
+            let align_start = walk.align_start;
+            let align_end = self.get_turtle_walk_align_end(i);
+            self.move_align_list(shift_x, shift_y, align_start, align_end, false, turtle.shift);
+        }
+        turtle.width_used = turtle.layout.padding.left + grid.size.x;
+        turtle.height_used = turtle.layout.padding.top + grid.size.y;
+    }
+    
     pub fn get_turtle_align_range(&self) -> TurtleAlignRange {
         TurtleAlignRange{
             start:  self.turtles.last().unwrap().align_start,
@@ -666,6 +887,15 @@ impl<'a> Cx2d<'a> {
     }
 }
 
+struct GridCells {
+    origin: DVec2,
+    column_pos: Vec<f64>,
+    column_size: Vec<f64>,
+    row_pos: Vec<f64>,
+    row_size: Vec<f64>,
+    size: DVec2,
+}
+
 pub struct TurtleAlignRange{
     start: usize,
     end: usize
@@ -727,7 +957,10 @@ impl Turtle {
                 Flow::Down => {
                     dvec2(0.0, self.layout.spacing)
                 }
-                Flow::Overlay => {
+                Flow::RightWrap if walks_len > self.row_start => {
+                    dvec2(self.layout.spacing, 0.0)
+                }
+                Flow::Overlay | Flow::RightWrap | Flow::Grid => {
                     dvec2(0.0, 0.0)
                 }
             }
@@ -770,6 +1003,16 @@ impl Turtle {
                     Flow::Right => {
                         max_zero_keep_nan(self.width_left() - margin.width())
                     },
+                    Flow::RightWrap => {
+                        let right = self.origin.x + self.width - self.layout.padding.right;
+                        max_zero_keep_nan(right - self.pos.x - margin.width())
+                    },
+                    Flow::Grid => {
+                        let columns = self.layout.grid.columns.len().max(1);
+                        let available = self.width - self.layout.padding.width();
+                        let cell = self.layout.grid.columns.cell_size(self.cell_index % columns, available, self.layout.spacing);
+                        max_zero_keep_nan(cell - margin.width())
+                    }
                     Flow::Down | Flow::Overlay => {
                         let r = max_zero_keep_nan(self.width - self.layout.padding.width() - margin.width());
                         if r.is_nan() {
@@ -789,7 +1032,7 @@ impl Turtle {
             Size::Fixed(v) => max_zero_keep_nan(v),
             Size::Fill => {
                 match flow {
-                    Flow::Right | Flow::Overlay => {
+                    Flow::Right | Flow::RightWrap | Flow::Overlay => {
                         let r = max_zero_keep_nan(self.height - self.layout.padding.height() - margin.height());
                         if r.is_nan() {
                             return self.height_used - margin.height() - self.layout.padding.bottom
@@ -799,6 +1042,12 @@ impl Turtle {
                     Flow::Down => {
                         max_zero_keep_nan(self.height_left() - margin.height())
                     }
+                    Flow::Grid => {
+                        let columns = self.layout.grid.columns.len().max(1);
+                        let available = self.height - self.layout.padding.height();
+                        let cell = self.layout.grid.rows.cell_size(self.cell_index / columns, available, self.layout.grid.row_spacing);
+                        max_zero_keep_nan(cell - margin.height())
+                    }
                 }
             }
             Size::All=>self.height
@@ -900,7 +1149,27 @@ impl DeferWalk {
                             width: *other_axis
                         }
                     }
-                    Flow::Overlay => panic!()
+                    Flow::Overlay | Flow::RightWrap | Flow::Grid => panic!()
+                };
+                *self = DeferWalk::Resolved(walk);
+                walk
+            }
+            Self::GridCell {row, column, walk} => {
+                let turtle = cx.turtles.last().unwrap();
+                let grid = cx.grid_cells(turtle);
+                let align = turtle.layout.align;
+                let fit_cell = | size: Size, cell: f64, margin: f64, align: f64 | match size {
+                    Size::Fill => (Size::Fixed(max_zero_keep_nan(cell - margin)), 0.0),
+                    Size::Fixed(v) => (size, align * (cell - v - margin)),
+                    _ => (size, 0.0)
+                };
+                let (width, dx) = fit_cell(walk.width, grid.column_size[*column], walk.margin.width(), align.x);
+                let (height, dy) = fit_cell(walk.height, grid.row_size[*row], walk.margin.height(), align.y);
+                let walk = Walk {
+                    abs_pos: Some(grid.origin + dvec2(grid.column_pos[*column] + dx, grid.row_pos[*row] + dy)),
+                    margin: walk.margin,
+                    width,
+                    height
                 };
                 *self = DeferWalk::Resolved(walk);
                 walk
@@ -931,6 +1200,25 @@ impl Layout {
             ..Self::default()
         }
     }
+    
+    pub fn flow_right_wrap() -> Self {
+        Self {
+            flow: Flow::RightWrap,
+            ..Self::default()
+        }
+    }
+    
+    pub fn flow_grid(columns: &[GridTrack], rows: &[GridTrack]) -> Self {
+        Self {
+            flow: Flow::Grid,
+            grid: GridLayout {
+                columns: GridTracks::new(columns),
+                rows: GridTracks::new(rows),
+                row_spacing: 0.0
+            },
+            ..Self::default()
+        }
+    }
 
     pub fn with_scroll(mut self, v: DVec2) -> Self {
         self.scroll = v;
@@ -1122,6 +1410,127 @@ impl LiveHook for Padding {
     }
 }
 
+impl Default for GridTracks {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            tracks: [GridTrack::Fit; GRID_TRACKS_MAX]
+        }
+    }
+}
+
+impl GridTracks {
+    pub fn new(tracks: &[GridTrack]) -> Self {
+        let mut ret = Self::default();
+        for track in tracks.iter().take(GRID_TRACKS_MAX) {
+            ret.tracks[ret.len] = *track;
+            ret.len += 1;
+        }
+        ret
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    pub fn get(&self, index: usize) -> GridTrack {
+        if index < self.len {self.tracks[index]} else {GridTrack::Fit}
+    }
+    
+    pub fn as_slice(&self) -> &[GridTrack] {
+        &self.tracks[0..self.len]
+    }
+    
+    // fit tracks take their measured size and fr tracks share what is left of available.
+    // with nothing left to share (nan available) fr tracks fit their content as well
+    pub fn resolve(&self, available: f64, spacing: f64, measured: &[f64], sizes: &mut [f64]) {
+        let mut used = spacing * sizes.len().saturating_sub(1) as f64;
+        let mut fr_total = 0.0;
+        for i in 0..sizes.len() {
+            sizes[i] = match self.get(i) {
+                GridTrack::Fixed(v) => v,
+                GridTrack::Fit => measured[i],
+                GridTrack::Fr(fr) => {
+                    fr_total += fr;
+                    0.0
+                }
+            };
+            used += sizes[i];
+        }
+        if fr_total > 0.0 {
+            let left = max_zero_keep_nan(available - used);
+            for i in 0..sizes.len() {
+                if let GridTrack::Fr(fr) = self.get(i) {
+                    sizes[i] = if left.is_nan() {measured[i]} else {left * fr / fr_total};
+                }
+            }
+        }
+    }
+    
+    // size of a track before any content is measured, nan when it depends on content
+    pub fn cell_size(&self, index: usize, available: f64, spacing: f64) -> f64 {
+        if index >= self.len {
+            return f64::NAN
+        }
+        let measured = [f64::NAN; GRID_TRACKS_MAX];
+        let mut sizes = [0.0; GRID_TRACKS_MAX];
+        self.resolve(available, spacing, &measured[0..self.len], &mut sizes[0..self.len]);
+        sizes[index]
+    }
+}
+
+impl LiveHook for GridTracks {}
+impl LiveApply for GridTracks {
+    fn apply(&mut self, cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        // tracks are given as an array, like columns: [Fixed(100), Fr(1.0), Fit]
+        if nodes[index].is_array() {
+            let mut index = index + 1;
+            self.len = 0;
+            loop {
+                if nodes[index].is_close() {
+                    index += 1;
+                    break;
+                }
+                if self.len < GRID_TRACKS_MAX {
+                    let mut track = GridTrack::new(cx);
+                    index = track.apply(cx, from, index, nodes);
+                    self.tracks[self.len] = track;
+                    self.len += 1;
+                }
+                else {
+                    cx.apply_error(live_error_origin!(), index, nodes, format!("a grid can have at most {} tracks", GRID_TRACKS_MAX));
+                    index = nodes.skip_node(index);
+                }
+            }
+            index
+        }
+        else {
+            cx.apply_error_expected_array(live_error_origin!(), index, nodes);
+            nodes.skip_node(index)
+        }
+    }
+}
+
+impl LiveNew for GridTracks {
+    fn new(_cx: &mut Cx) -> Self {
+        Self::default()
+    }
+    
+    fn new_apply(cx: &mut Cx, from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> Self {
+        let mut ret = Self::default();
+        ret.apply(cx, from, index, nodes);
+        ret
+    }
+    
+    fn live_type_info(cx: &mut Cx) -> LiveTypeInfo {
+        GridTrack::live_type_info(cx)
+    }
+}
+
 impl Default for Flow {
     fn default() -> Self {Self::Down}
 }
@@ -1215,3 +1624,107 @@ fn max_zero_keep_nan(v: f64) -> f64 {
         f64::max(v, 0.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            draw_list_2d::DrawList2d,
+            makepad_platform::{Pass, DrawEvent},
+        }
+    };
+    
+    // walks the given sizes in a root turtle of the given walk and layout,
+    // and returns where they ended up after alignment, and the size of the turtle
+    fn layout_walks(walk: Walk, layout: Layout, sizes: &[(f64, f64)]) -> (Vec<Rect>, DVec2) {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        crate::live_design(&mut cx);
+        cx.live_expand();
+        let pass = Pass::new(&mut cx);
+        let mut draw_list = DrawList2d::new(&mut cx);
+        let draw_event = DrawEvent::default();
+        let cx = &mut Cx2d::new(&mut cx, &draw_event);
+        cx.begin_pass(&pass, Some(1.0));
+        draw_list.begin_always(cx);
+        cx.begin_turtle(walk, layout);
+        let mut areas = vec![Area::Empty; sizes.len()];
+        for (area, (w, h)) in areas.iter_mut().zip(sizes) {
+            cx.walk_turtle_with_area(area, Walk::fixed(*w, *h));
+        }
+        let rect = cx.end_turtle();
+        draw_list.end(cx);
+        cx.end_pass(&pass);
+        (areas.iter().map( | area | area.get_rect(cx)).collect(), rect.size)
+    }
+    
+    fn rect(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect {pos: dvec2(x, y), size: dvec2(w, h)}
+    }
+    
+    #[test]
+    fn right_wrap() {
+        let layout = Layout {
+            spacing: 10.0,
+            grid: GridLayout {row_spacing: 5.0, ..GridLayout::default()},
+            ..Layout::flow_right_wrap()
+        };
+        let sizes = [(30.0, 10.0), (30.0, 20.0), (30.0, 10.0), (50.0, 10.0)];
+        // the third item would end at 110, so it starts a new row below the tallest of the first
+        let (rects, size) = layout_walks(Walk::fixed(100.0, 100.0), layout, &sizes);
+        assert_eq!(rects, vec![
+            rect(0.0, 0.0, 30.0, 10.0),
+            rect(40.0, 0.0, 30.0, 20.0),
+            rect(0.0, 25.0, 30.0, 10.0),
+            rect(40.0, 25.0, 50.0, 10.0),
+        ]);
+        assert_eq!(size, dvec2(100.0, 100.0));
+        
+        // every row is centered on its own, and items within the height of their row
+        let layout = Layout {align: Align {x: 0.5, y: 0.5}, ..layout};
+        let (rects, _) = layout_walks(Walk::fixed(100.0, 100.0), layout, &sizes);
+        assert_eq!(rects, vec![
+            rect(15.0, 5.0, 30.0, 10.0),
+            rect(55.0, 0.0, 30.0, 20.0),
+            rect(5.0, 25.0, 30.0, 10.0),
+            rect(45.0, 25.0, 50.0, 10.0),
+        ]);
+        
+        // an item wider than the turtle still goes on a row, alone
+        let (rects, _) = layout_walks(Walk::fixed(100.0, 100.0), layout, &[(150.0, 10.0), (10.0, 10.0)]);
+        assert_eq!(rects[0].pos.y, 0.0);
+        assert_eq!(rects[1].pos.y, 15.0);
+    }
+    
+    #[test]
+    fn grid() {
+        let mut layout = Layout::flow_grid(
+            &[GridTrack::Fixed(50.0), GridTrack::Fr(1.0), GridTrack::Fit],
+            &[GridTrack::Fit, GridTrack::Fixed(40.0)]
+        );
+        layout.spacing = 10.0;
+        layout.grid.row_spacing = 5.0;
+        let sizes = [
+            (20.0, 10.0), (30.0, 15.0), (25.0, 12.0),
+            (10.0, 10.0), (10.0, 10.0), (40.0, 8.0),
+        ];
+        // the Fit column is as wide as its widest cell, the Fr one gets the 90 that is left of 200.
+        // the Fit row is as high as its highest cell, and a Fit height grid is as high as its rows
+        let (rects, size) = layout_walks(Walk::size(Size::Fixed(200.0), Size::Fit), layout, &sizes);
+        assert_eq!(rects, vec![
+            rect(0.0, 0.0, 20.0, 10.0),
+            rect(60.0, 0.0, 30.0, 15.0),
+            rect(160.0, 0.0, 25.0, 12.0),
+            rect(0.0, 20.0, 10.0, 10.0),
+            rect(60.0, 20.0, 10.0, 10.0),
+            rect(160.0, 20.0, 40.0, 8.0),
+        ]);
+        assert_eq!(size, dvec2(200.0, 60.0));
+        
+        // cells align their content within the cell
+        let layout = Layout {align: Align {x: 1.0, y: 0.5}, ..layout};
+        let (rects, _) = layout_walks(Walk::size(Size::Fixed(200.0), Size::Fit), layout, &sizes);
+        assert_eq!(rects[1], rect(120.0, 0.0, 30.0, 15.0));
+        assert_eq!(rects[4], rect(140.0, 35.0, 10.0, 10.0));
+    }
+}
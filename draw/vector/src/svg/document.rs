@@ -0,0 +1,503 @@
+use super::path_data::{arc_to, parse_path_data, NumberParser};
+use super::style::{parse_color, parse_declarations, parse_length, parse_transform, Matrix, StyleSheet};
+use super::xml::XmlElement;
+use super::*;
+use crate::geometry::Transform;
+use std::collections::HashMap;
+
+// `use` elements can nest, and can refer to their own ancestors
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Paint {
+    None,
+    Color(SvgColor),
+    CurrentColor,
+    Url(String, Option<SvgColor>),
+}
+
+#[derive(Clone, Debug)]
+struct Style {
+    transform: Matrix,
+    opacity: f32,
+    visible: bool,
+    color: Option<SvgColor>,
+    fill: Paint,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke_paint: Paint,
+    stroke_opacity: f32,
+    stroke: StrokeStyle,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            transform: Matrix::identity(),
+            opacity: 1.0,
+            visible: true,
+            color: None,
+            fill: Paint::Color(SvgColor::from_u8(0, 0, 0, 255)),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke_paint: Paint::None,
+            stroke_opacity: 1.0,
+            stroke: StrokeStyle::default(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    ids: HashMap<&'a str, &'a XmlElement>,
+    style_sheet: StyleSheet,
+    view_box: Option<Rectangle>,
+    shapes: Vec<SvgShape>,
+}
+
+pub(crate) fn parse_document(root: &XmlElement) -> Result<SvgDocument, String> {
+    if local_name(&root.name) != "svg" {
+        return Err(format!("Expected <svg> root element, got <{}>", root.name));
+    }
+    let mut parser = Parser {
+        ids: HashMap::new(),
+        style_sheet: StyleSheet::default(),
+        view_box: None,
+        shapes: Vec::new(),
+    };
+    parser.collect(root);
+    parser.view_box = match root.attr("viewBox") {
+        Some(view_box) => {
+            let mut numbers = NumberParser::new(view_box);
+            let (x, y, w, h) = (numbers.number()?, numbers.number()?, numbers.number()?, numbers.number()?);
+            Some(Rectangle::new(Point::new(x, y), Point::new(x + w, y + h)))
+        }
+        None => match (
+            root.attr("width").and_then(|w| parse_length(w, f64::NAN)),
+            root.attr("height").and_then(|h| parse_length(h, f64::NAN)),
+        ) {
+            (Some(w), Some(h)) if w > 0.0 && h > 0.0 => {
+                Some(Rectangle::new(Point::origin(), Point::new(w, h)))
+            }
+            _ => None,
+        },
+    };
+    let style = parser.element_style(root, &Style::default());
+    parser.children(root, &style, 0)?;
+    Ok(SvgDocument {
+        view_box: parser.view_box,
+        shapes: parser.shapes,
+    })
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap()
+}
+
+impl<'a> Parser<'a> {
+    fn collect(&mut self, element: &'a XmlElement) {
+        if let Some(id) = element.attr("id") {
+            self.ids.entry(id).or_insert(element);
+        }
+        if local_name(&element.name) == "style" {
+            self.style_sheet.add(&element.text);
+        }
+        for child in &element.children {
+            self.collect(child);
+        }
+    }
+
+    fn lookup(&self, href: &str) -> Option<&'a XmlElement> {
+        self.ids.get(href.trim().strip_prefix('#')?).cloned()
+    }
+
+    fn children(&mut self, element: &'a XmlElement, style: &Style, depth: usize) -> Result<(), String> {
+        for child in &element.children {
+            self.element(child, style, depth)?;
+        }
+        Ok(())
+    }
+
+    fn element(&mut self, element: &'a XmlElement, parent: &Style, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err("Too deeply nested SVG elements".to_string());
+        }
+        let name = local_name(&element.name);
+        let is_container = matches!(name, "g" | "a" | "switch" | "svg" | "use");
+        if !is_container && !matches!(name, "path" | "rect" | "circle" | "ellipse" | "line" | "polyline" | "polygon") {
+            return Ok(());
+        }
+        if self.property(element, "display").as_deref() == Some("none") {
+            return Ok(());
+        }
+        let mut style = self.element_style(element, parent);
+        let number = |attr: &str, reference: f64| {
+            element.attr(attr).and_then(|value| parse_length(value, reference)).unwrap_or(0.0)
+        };
+        let (vw, vh) = match self.view_box {
+            Some(view_box) => (view_box.p_max.x - view_box.p_min.x, view_box.p_max.y - view_box.p_min.y),
+            None => (100.0, 100.0),
+        };
+        match name {
+            "g" | "a" | "switch" => self.children(element, &style, depth + 1),
+            "svg" => {
+                style.transform = style.transform.multiply(Matrix::translation(number("x", vw), number("y", vh)));
+                self.children(element, &style, depth + 1)
+            }
+            "use" => {
+                let target = element
+                    .attr("href")
+                    .or_else(|| element.attr("xlink:href"))
+                    .and_then(|href| self.lookup(href));
+                if let Some(target) = target {
+                    style.transform = style.transform.multiply(Matrix::translation(number("x", vw), number("y", vh)));
+                    if local_name(&target.name) == "symbol" {
+                        let style = self.element_style(target, &style);
+                        self.children(target, &style, depth + 1)?;
+                    } else {
+                        self.element(target, &style, depth + 1)?;
+                    }
+                }
+                Ok(())
+            }
+            _ => {
+                if !style.visible {
+                    return Ok(());
+                }
+                let (path, closed) = match name {
+                    "path" => match element.attr("d") {
+                        Some(d) => (parse_path_data(d)?, true),
+                        None => return Ok(()),
+                    },
+                    "rect" => {
+                        let (x, y) = (number("x", vw), number("y", vh));
+                        let (w, h) = (number("width", vw), number("height", vh));
+                        let rx = element.attr("rx").and_then(|rx| parse_length(rx, vw));
+                        let ry = element.attr("ry").and_then(|ry| parse_length(ry, vh));
+                        let (rx, ry) = match (rx, ry) {
+                            (Some(rx), Some(ry)) => (rx, ry),
+                            (Some(r), None) | (None, Some(r)) => (r, r),
+                            (None, None) => (0.0, 0.0),
+                        };
+                        if w <= 0.0 || h <= 0.0 {
+                            return Ok(());
+                        }
+                        (rect_path(x, y, w, h, rx.min(w * 0.5), ry.min(h * 0.5)), true)
+                    }
+                    "circle" => {
+                        let r = number("r", (vw * vw + vh * vh).sqrt() / 2f64.sqrt());
+                        (ellipse_path(number("cx", vw), number("cy", vh), r, r), true)
+                    }
+                    "ellipse" => {
+                        let (rx, ry) = (number("rx", vw), number("ry", vh));
+                        (ellipse_path(number("cx", vw), number("cy", vh), rx, ry), true)
+                    }
+                    "line" => (
+                        vec![
+                            PathCommand::MoveTo(Point::new(number("x1", vw), number("y1", vh))),
+                            PathCommand::LineTo(Point::new(number("x2", vw), number("y2", vh))),
+                        ],
+                        false,
+                    ),
+                    _ => {
+                        let mut numbers = NumberParser::new(element.attr("points").unwrap_or(""));
+                        let mut path = Vec::new();
+                        while numbers.has_number() {
+                            let p = numbers.point()?;
+                            path.push(if path.is_empty() {PathCommand::MoveTo(p)} else {PathCommand::LineTo(p)});
+                        }
+                        if name == "polygon" && !path.is_empty() {
+                            path.push(PathCommand::Close);
+                        }
+                        (path, name == "polygon")
+                    }
+                };
+                if path.is_empty() || (path.len() < 2 && !closed) {
+                    return Ok(());
+                }
+                self.shape(path, name != "line", &style);
+                Ok(())
+            }
+        }
+    }
+
+    // presentation attributes, then stylesheet rules, then the style attribute
+    fn declarations(&self, element: &'a XmlElement) -> Vec<(String, String)> {
+        let mut declarations: Vec<(String, String)> = element.attrs.clone();
+        declarations.extend(self.style_sheet.declarations(element).cloned());
+        if let Some(style) = element.attr("style") {
+            declarations.extend(parse_declarations(style));
+        }
+        declarations
+    }
+
+    fn property(&self, element: &'a XmlElement, name: &str) -> Option<String> {
+        self.declarations(element)
+            .into_iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    fn element_style(&self, element: &'a XmlElement, parent: &Style) -> Style {
+        let mut style = parent.clone();
+        // `color` first, so currentColor in this element resolves against it
+        if let Some(color) = self.property(element, "color").and_then(|color| parse_color(&color)) {
+            style.color = Some(color);
+        }
+        for (name, value) in self.declarations(element) {
+            let value = value.trim();
+            if value == "inherit" {
+                continue;
+            }
+            let number = || value.parse::<f32>().ok().map(|v| v.max(0.0).min(1.0));
+            match name.as_str() {
+                "transform" => {
+                    if let Ok(transform) = parse_transform(value) {
+                        style.transform = parent.transform.multiply(transform);
+                    }
+                }
+                "opacity" => style.opacity = parent.opacity * number().unwrap_or(1.0),
+                "visibility" => style.visible = value == "visible",
+                "fill" => style.fill = parse_paint(value, &style),
+                "fill-opacity" => style.fill_opacity = number().unwrap_or(1.0),
+                "fill-rule" => {
+                    style.fill_rule = if value == "evenodd" {FillRule::EvenOdd} else {FillRule::NonZero}
+                }
+                "stroke" => style.stroke_paint = parse_paint(value, &style),
+                "stroke-opacity" => style.stroke_opacity = number().unwrap_or(1.0),
+                "stroke-width" => {
+                    if let Some(width) = parse_length(value, 1.0) {
+                        style.stroke.width = width;
+                    }
+                }
+                "stroke-linecap" => {
+                    style.stroke.cap = match value {
+                        "round" => LineCap::Round,
+                        "square" => LineCap::Square,
+                        _ => LineCap::Butt,
+                    }
+                }
+                "stroke-linejoin" => {
+                    style.stroke.join = match value {
+                        "round" => LineJoin::Round,
+                        "bevel" => LineJoin::Bevel,
+                        _ => LineJoin::Miter,
+                    }
+                }
+                "stroke-miterlimit" => {
+                    if let Ok(limit) = value.parse::<f64>() {
+                        style.stroke.miter_limit = limit.max(1.0);
+                    }
+                }
+                _ => (),
+            }
+        }
+        style
+    }
+
+    fn shape(&mut self, path: Vec<PathCommand>, fill: bool, style: &Style) {
+        let bbox = point_bounds(path.iter().flat_map(|command| match *command {
+            PathCommand::MoveTo(p) | PathCommand::LineTo(p) => vec![p],
+            PathCommand::QuadraticTo(p1, p) => vec![p1, p],
+            PathCommand::CubicTo(p1, p2, p) => vec![p1, p2, p],
+            PathCommand::Close => vec![],
+        }));
+        let transform = style.transform;
+        let document_path = || -> Vec<PathCommand> {
+            path.iter().map(|command| command.transform(&transform)).collect()
+        };
+        if fill {
+            if let Some(paint) = self.resolve_paint(&style.fill, style, bbox) {
+                self.shapes.push(SvgShape {
+                    path: close_contours(document_path()),
+                    kind: SvgShapeKind::Fill(style.fill_rule),
+                    paint,
+                    opacity: style.opacity * style.fill_opacity,
+                });
+            }
+        }
+        let width = style.stroke.width * transform.scale_factor();
+        if width > 0.0 {
+            if let Some(paint) = self.resolve_paint(&style.stroke_paint, style, bbox) {
+                self.shapes.push(SvgShape {
+                    path: document_path(),
+                    kind: SvgShapeKind::Stroke(StrokeStyle {width, ..style.stroke}),
+                    paint,
+                    opacity: style.opacity * style.stroke_opacity,
+                });
+            }
+        }
+    }
+
+    fn resolve_paint(&self, paint: &Paint, style: &Style, bbox: Option<Rectangle>) -> Option<SvgPaint> {
+        match paint {
+            Paint::None => None,
+            Paint::Color(color) => Some(SvgPaint::Color(*color)),
+            Paint::CurrentColor => Some(SvgPaint::CurrentColor),
+            Paint::Url(href, fallback) => match self.lookup(href) {
+                Some(element) => self.gradient(element, style.transform, bbox?),
+                None => fallback.map(SvgPaint::Color),
+            },
+        }
+    }
+
+    fn gradient(&self, element: &'a XmlElement, transform: Matrix, bbox: Rectangle) -> Option<SvgPaint> {
+        // gradients inherit attributes and stops they don't have from the one they refer to
+        let mut chain = vec![element];
+        while chain.len() < MAX_DEPTH {
+            let href = chain.last().unwrap().attr("href").or_else(|| chain.last().unwrap().attr("xlink:href"));
+            match href.and_then(|href| self.lookup(href)) {
+                Some(next) => chain.push(next),
+                None => break,
+            }
+        }
+        let attr = |name: &str| chain.iter().find_map(|element| element.attr(name));
+        let stop_elements = chain.iter().find(|element| {
+            element.children.iter().any(|child| local_name(&child.name) == "stop")
+        })?;
+        let mut stops: Vec<SvgGradientStop> = Vec::new();
+        for stop in stop_elements.children.iter().filter(|child| local_name(&child.name) == "stop") {
+            let offset = stop.attr("offset").and_then(|offset| parse_length(offset, 1.0)).unwrap_or(0.0);
+            let offset = offset.max(0.0).min(1.0).max(stops.last().map_or(0.0, |s| s.offset));
+            let mut color = self
+                .property(stop, "stop-color")
+                .and_then(|color| parse_color(&color))
+                .unwrap_or(SvgColor::from_u8(0, 0, 0, 255));
+            color.a *= self
+                .property(stop, "stop-opacity")
+                .and_then(|opacity| opacity.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            stops.push(SvgGradientStop {offset, color});
+        }
+        let mut gradient = SvgLinearGradient {t: [0.0, 0.0, 0.0], stops};
+        if gradient.stops.len() == 1 || local_name(&element.name) != "linearGradient" {
+            // radial gradients are drawn in their average color
+            return Some(SvgPaint::Color(gradient.color_at(0.5)));
+        }
+        let user_space = attr("gradientUnits") == Some("userSpaceOnUse");
+        let (w, h) = match (user_space, self.view_box) {
+            (true, Some(view_box)) => (view_box.p_max.x - view_box.p_min.x, view_box.p_max.y - view_box.p_min.y),
+            _ => (1.0, 1.0),
+        };
+        let coord = |name: &str, reference: f64, default: f64| {
+            attr(name).and_then(|value| parse_length(value, reference)).unwrap_or(default)
+        };
+        let p1 = Point::new(coord("x1", w, 0.0), coord("y1", h, 0.0));
+        let p2 = Point::new(coord("x2", w, w), coord("y2", h, 0.0));
+        let mut to_document = transform;
+        if !user_space {
+            let size = bbox.p_max - bbox.p_min;
+            to_document = to_document.multiply(Matrix::new(size.x, 0.0, 0.0, size.y, bbox.p_min.x, bbox.p_min.y));
+        }
+        if let Some(gradient_transform) = attr("gradientTransform").and_then(|t| parse_transform(t).ok()) {
+            to_document = to_document.multiply(gradient_transform);
+        }
+        let d = p2 - p1;
+        let dd = d.dot(d);
+        let inverse = match to_document.invert() {
+            Some(inverse) if dd > 0.0 => inverse,
+            _ => return Some(SvgPaint::Color(gradient.stops.last().unwrap().color)),
+        };
+        // t = dot(inverse * p - p1, d) / dd, written out as a linear function of p
+        gradient.t = [
+            (inverse.a * d.x + inverse.b * d.y) / dd,
+            (inverse.c * d.x + inverse.d * d.y) / dd,
+            ((inverse.e - p1.x) * d.x + (inverse.f - p1.y) * d.y) / dd,
+        ];
+        Some(SvgPaint::LinearGradient(gradient))
+    }
+}
+
+fn parse_paint(value: &str, style: &Style) -> Paint {
+    if value == "none" {
+        return Paint::None;
+    }
+    if value == "currentColor" {
+        return match style.color {
+            Some(color) => Paint::Color(color),
+            None => Paint::CurrentColor,
+        };
+    }
+    if let Some(rest) = value.strip_prefix("url(") {
+        if let Some((href, fallback)) = rest.split_once(')') {
+            let href = href.trim().trim_matches(|c| c == '"' || c == '\'');
+            return Paint::Url(href.to_string(), parse_color(fallback));
+        }
+    }
+    match parse_color(value) {
+        Some(color) => Paint::Color(color),
+        None => Paint::None,
+    }
+}
+
+fn rect_path(x: f64, y: f64, w: f64, h: f64, rx: f64, ry: f64) -> Vec<PathCommand> {
+    let mut path = vec![PathCommand::MoveTo(Point::new(x + rx, y))];
+    let corner = |path: &mut Vec<PathCommand>, from: Point, to: Point| {
+        if rx > 0.0 && ry > 0.0 {
+            arc_to(path, from, rx, ry, 0.0, false, true, to);
+        }
+    };
+    path.push(PathCommand::LineTo(Point::new(x + w - rx, y)));
+    corner(&mut path, Point::new(x + w - rx, y), Point::new(x + w, y + ry));
+    path.push(PathCommand::LineTo(Point::new(x + w, y + h - ry)));
+    corner(&mut path, Point::new(x + w, y + h - ry), Point::new(x + w - rx, y + h));
+    path.push(PathCommand::LineTo(Point::new(x + rx, y + h)));
+    corner(&mut path, Point::new(x + rx, y + h), Point::new(x, y + h - ry));
+    path.push(PathCommand::LineTo(Point::new(x, y + ry)));
+    corner(&mut path, Point::new(x, y + ry), Point::new(x + rx, y));
+    path.push(PathCommand::Close);
+    path
+}
+
+fn ellipse_path(cx: f64, cy: f64, rx: f64, ry: f64) -> Vec<PathCommand> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return Vec::new();
+    }
+    let mut path = vec![PathCommand::MoveTo(Point::new(cx + rx, cy))];
+    arc_to(&mut path, Point::new(cx + rx, cy), rx, ry, 0.0, false, true, Point::new(cx - rx, cy));
+    arc_to(&mut path, Point::new(cx - rx, cy), rx, ry, 0.0, false, true, Point::new(cx + rx, cy));
+    path.push(PathCommand::Close);
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_styled_document() {
+        let document = SvgDocument::parse(r##"<?xml version="1.0"?>
+            <svg viewBox="0 0 20 10" xmlns="http://www.w3.org/2000/svg">
+                <style>.a { fill: #f00; }</style>
+                <defs>
+                    <linearGradient id="g" x1="0" x2="10" gradientUnits="userSpaceOnUse">
+                        <stop offset="0" stop-color="#000"/>
+                        <stop offset="100%" stop-color="#fff" stop-opacity="0.5"/>
+                    </linearGradient>
+                </defs>
+                <g transform="translate(10 0)" opacity="0.5">
+                    <rect class="a" width="5" height="5" fill="blue"/>
+                    <circle cx="5" cy="5" r="2" fill="url(#g)" stroke="currentColor" stroke-width="2"/>
+                </g>
+                <path d="M0 0h1" style="display:none"/>
+            </svg>
+        "##).unwrap();
+        assert_eq!(document.view_box, Some(Rectangle::new(Point::new(0.0, 0.0), Point::new(20.0, 10.0))));
+        assert_eq!(document.shapes.len(), 3);
+        // stylesheet rules win over presentation attributes
+        assert_eq!(document.shapes[0].paint, SvgPaint::Color(SvgColor::from_u8(255, 0, 0, 255)));
+        assert_eq!(document.shapes[0].opacity, 0.5);
+        assert_eq!(document.shapes[0].bounds(), Some(Rectangle::new(Point::new(10.0, 0.0), Point::new(15.0, 5.0))));
+        // the gradient is defined in user space and moves along with the group transform
+        match &document.shapes[1].paint {
+            SvgPaint::LinearGradient(gradient) => {
+                assert_eq!(gradient.t, [0.1, 0.0, -1.0]);
+                assert_eq!(gradient.stops[1].color.a, 0.5);
+            }
+            paint => panic!("{:?}", paint),
+        }
+        assert_eq!(document.shapes[2].paint, SvgPaint::CurrentColor);
+        assert!(matches!(document.shapes[2].kind, SvgShapeKind::Stroke(StrokeStyle {width, ..}) if width == 2.0));
+        assert!(!document.is_monochrome());
+    }
+}
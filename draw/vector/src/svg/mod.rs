@@ -0,0 +1,233 @@
+//! A parser for the subset of SVG that exported icons and illustrations use: paths, basic
+//! shapes, groups, `use`, transforms, solid and linear gradient paint, strokes and simple
+//! stylesheets. Filters, masks, clip paths, text and images are ignored.
+
+mod document;
+mod path_data;
+mod stroke;
+mod style;
+mod xml;
+
+pub use self::path_data::parse_path_data;
+pub use self::stroke::{stroke_line_path, LineCap, LineJoin, StrokeStyle};
+pub use self::xml::{parse_xml, XmlElement};
+
+use crate::geometry::{Point, Rectangle};
+use crate::path::{PathCommand, PathIterator};
+use crate::trapezoidator::FillRule;
+
+/// A non-premultiplied color.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SvgColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl SvgColor {
+    pub fn from_u8(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+            a: a as f32 / 255.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgGradientStop {
+    pub offset: f64,
+    pub color: SvgColor,
+}
+
+/// A linear gradient. The gradient position of a point in document coordinates is
+/// `t[0] * x + t[1] * y + t[2]`, which also covers gradient transforms and bounding box units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgLinearGradient {
+    pub t: [f64; 3],
+    pub stops: Vec<SvgGradientStop>,
+}
+
+impl SvgLinearGradient {
+    /// The color at gradient position `t`, padded beyond the first and last stop.
+    pub fn color_at(&self, t: f64) -> SvgColor {
+        let first = self.stops[0];
+        if t <= first.offset {
+            return first.color;
+        }
+        for window in self.stops.windows(2) {
+            let (s0, s1) = (window[0], window[1]);
+            if t <= s1.offset {
+                let f = if s1.offset > s0.offset {
+                    ((t - s0.offset) / (s1.offset - s0.offset)) as f32
+                } else {
+                    1.0
+                };
+                return SvgColor {
+                    r: s0.color.r + (s1.color.r - s0.color.r) * f,
+                    g: s0.color.g + (s1.color.g - s0.color.g) * f,
+                    b: s0.color.b + (s1.color.b - s0.color.b) * f,
+                    a: s0.color.a + (s1.color.a - s0.color.a) * f,
+                };
+            }
+        }
+        self.stops.last().unwrap().color
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SvgPaint {
+    Color(SvgColor),
+    /// `currentColor` where the document doesn't set `color`; the renderer picks the color.
+    CurrentColor,
+    LinearGradient(SvgLinearGradient),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvgShapeKind {
+    Fill(FillRule),
+    /// The path is the centerline of the stroke, see `stroke_line_path`.
+    Stroke(StrokeStyle),
+}
+
+/// A single painted shape. Paths are in document coordinates, with every transform applied.
+/// Filled paths only contain closed contours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgShape {
+    pub path: Vec<PathCommand>,
+    pub kind: SvgShapeKind,
+    pub paint: SvgPaint,
+    pub opacity: f32,
+}
+
+impl SvgShape {
+    /// The bounds of the painted area. Curves are bounded by their control points.
+    pub fn bounds(&self) -> Option<Rectangle> {
+        match self.kind {
+            SvgShapeKind::Fill(_) => point_bounds(self.path.iter().flat_map(|command| {
+                let points = match *command {
+                    PathCommand::MoveTo(p) | PathCommand::LineTo(p) => [Some(p), None, None],
+                    PathCommand::QuadraticTo(p1, p) => [Some(p1), Some(p), None],
+                    PathCommand::CubicTo(p1, p2, p) => [Some(p1), Some(p2), Some(p)],
+                    PathCommand::Close => [None, None, None],
+                };
+                points.into_iter().flatten()
+            })),
+            SvgShapeKind::Stroke(style) => {
+                let tolerance = style.width * 0.01;
+                let outline = stroke_line_path(
+                    self.path.iter().cloned().linearize(tolerance),
+                    &style,
+                    tolerance,
+                );
+                point_bounds(outline.points().iter().cloned())
+            }
+        }
+    }
+}
+
+/// A parsed SVG document, flattened to a list of shapes in paint order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SvgDocument {
+    /// The `viewBox`, or the `width` and `height` of the root element.
+    pub view_box: Option<Rectangle>,
+    pub shapes: Vec<SvgShape>,
+}
+
+impl SvgDocument {
+    pub fn parse(data: &str) -> Result<SvgDocument, String> {
+        document::parse_document(&parse_xml(data)?)
+    }
+
+    /// A document with a single path filled with the current color.
+    pub fn from_path(path: Vec<PathCommand>) -> SvgDocument {
+        SvgDocument {
+            view_box: None,
+            shapes: vec![SvgShape {
+                path: close_contours(path),
+                kind: SvgShapeKind::Fill(FillRule::NonZero),
+                paint: SvgPaint::CurrentColor,
+                opacity: 1.0,
+            }],
+        }
+    }
+
+    /// The area covered by the shapes, clipped to the view box.
+    pub fn bounds(&self) -> Option<Rectangle> {
+        let mut bounds = self.shapes.iter().filter_map(|shape| shape.bounds()).reduce(|a, b| {
+            Rectangle::new(
+                Point::new(a.p_min.x.min(b.p_min.x), a.p_min.y.min(b.p_min.y)),
+                Point::new(a.p_max.x.max(b.p_max.x), a.p_max.y.max(b.p_max.y)),
+            )
+        })?;
+        if let Some(view_box) = self.view_box {
+            bounds = Rectangle::new(
+                Point::new(bounds.p_min.x.max(view_box.p_min.x), bounds.p_min.y.max(view_box.p_min.y)),
+                Point::new(bounds.p_max.x.min(view_box.p_max.x), bounds.p_max.y.min(view_box.p_max.y)),
+            );
+        }
+        if bounds.p_max.x <= bounds.p_min.x || bounds.p_max.y <= bounds.p_min.y {
+            return None;
+        }
+        Some(bounds)
+    }
+
+    /// Whether every shape is painted opaquely with one and the same color, so the document
+    /// can be rendered as a single coverage mask and tinted.
+    pub fn is_monochrome(&self) -> bool {
+        let mut paints = self.shapes.iter().map(|shape| match &shape.paint {
+            SvgPaint::Color(color) if color.a == 1.0 && shape.opacity == 1.0 => Some(&shape.paint),
+            SvgPaint::CurrentColor if shape.opacity == 1.0 => Some(&shape.paint),
+            _ => None,
+        });
+        match paints.next() {
+            Some(Some(first)) => paints.all(|paint| paint == Some(first)),
+            Some(None) => false,
+            None => true,
+        }
+    }
+}
+
+/// Makes every contour of a path end with `Close`, as filling does implicitly.
+pub fn close_contours(path: Vec<PathCommand>) -> Vec<PathCommand> {
+    let mut out = Vec::with_capacity(path.len() + 1);
+    let mut open = false;
+    for command in path {
+        match command {
+            PathCommand::MoveTo(_) => {
+                if open {
+                    out.push(PathCommand::Close);
+                }
+                open = true;
+            }
+            PathCommand::Close => {
+                if !open {
+                    continue;
+                }
+                open = false;
+            }
+            _ => (),
+        }
+        out.push(command);
+    }
+    if open {
+        out.push(PathCommand::Close);
+    }
+    out
+}
+
+fn point_bounds(points: impl Iterator<Item = Point>) -> Option<Rectangle> {
+    let mut bounds: Option<Rectangle> = None;
+    for p in points {
+        bounds = Some(match bounds {
+            None => Rectangle::new(p, p),
+            Some(b) => Rectangle::new(
+                Point::new(b.p_min.x.min(p.x), b.p_min.y.min(p.y)),
+                Point::new(b.p_max.x.max(p.x), b.p_max.y.max(p.y)),
+            ),
+        });
+    }
+    bounds
+}
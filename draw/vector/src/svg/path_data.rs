@@ -0,0 +1,321 @@
+use crate::geometry::{Point, Vector};
+use crate::path::PathCommand;
+use std::f64::consts::PI;
+
+/// Parses the contents of an SVG path `d` attribute. Arcs are converted to cubic curves, and every
+/// contour starts with a `MoveTo`.
+pub fn parse_path_data(data: &str) -> Result<Vec<PathCommand>, String> {
+    let mut parser = NumberParser::new(data);
+    let mut out = Vec::new();
+    let mut cmd = None;
+    let mut current = Point::origin();
+    let mut start = current;
+    let mut needs_move = true;
+    let mut last_cubic_control = None;
+    let mut last_quadratic_control = None;
+    loop {
+        parser.skip_separators();
+        let next = match parser.peek() {
+            Some(next) => next,
+            None => break,
+        };
+        if next.is_ascii_alphabetic() {
+            parser.pos += 1;
+            cmd = Some(next);
+        } else {
+            // numbers without a command repeat the previous one, where a move becomes a line
+            cmd = match cmd {
+                Some(b'M') => Some(b'L'),
+                Some(b'm') => Some(b'l'),
+                Some(b'Z' | b'z') | None => {
+                    return Err(format!("Expected path command at {}", parser.pos))
+                }
+                cmd => cmd,
+            };
+        }
+        let cmd = cmd.unwrap();
+        let relative = cmd.is_ascii_lowercase();
+        let origin = if relative {current.to_vector()} else {Vector::zero()};
+        if needs_move && !matches!(cmd, b'M' | b'm' | b'Z' | b'z') {
+            out.push(PathCommand::MoveTo(current));
+            start = current;
+        }
+        needs_move = false;
+        let mut cubic_control = None;
+        let mut quadratic_control = None;
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                current = parser.point()? + origin;
+                start = current;
+                out.push(PathCommand::MoveTo(current));
+            }
+            b'L' => {
+                current = parser.point()? + origin;
+                out.push(PathCommand::LineTo(current));
+            }
+            b'H' => {
+                current.x = parser.number()? + origin.x;
+                out.push(PathCommand::LineTo(current));
+            }
+            b'V' => {
+                current.y = parser.number()? + origin.y;
+                out.push(PathCommand::LineTo(current));
+            }
+            b'C' => {
+                let p1 = parser.point()? + origin;
+                let p2 = parser.point()? + origin;
+                current = parser.point()? + origin;
+                out.push(PathCommand::CubicTo(p1, p2, current));
+                cubic_control = Some(p2);
+            }
+            b'S' => {
+                let p1 = reflect(last_cubic_control, current);
+                let p2 = parser.point()? + origin;
+                current = parser.point()? + origin;
+                out.push(PathCommand::CubicTo(p1, p2, current));
+                cubic_control = Some(p2);
+            }
+            b'Q' => {
+                let p1 = parser.point()? + origin;
+                current = parser.point()? + origin;
+                out.push(PathCommand::QuadraticTo(p1, current));
+                quadratic_control = Some(p1);
+            }
+            b'T' => {
+                let p1 = reflect(last_quadratic_control, current);
+                current = parser.point()? + origin;
+                out.push(PathCommand::QuadraticTo(p1, current));
+                quadratic_control = Some(p1);
+            }
+            b'A' => {
+                let rx = parser.number()?;
+                let ry = parser.number()?;
+                let angle = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let p = parser.point()? + origin;
+                arc_to(&mut out, current, rx, ry, angle, large_arc, sweep, p);
+                current = p;
+            }
+            b'Z' => {
+                out.push(PathCommand::Close);
+                current = start;
+                needs_move = true;
+            }
+            _ => return Err(format!("Unknown path command {}", cmd as char)),
+        }
+        last_cubic_control = cubic_control;
+        last_quadratic_control = quadratic_control;
+    }
+    Ok(out)
+}
+
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some(control) => current + (current - control),
+        None => current,
+    }
+}
+
+// converts an endpoint parameterized elliptical arc to cubic curves of at most 90 degrees each
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn arc_to(
+    out: &mut Vec<PathCommand>,
+    p0: Point,
+    rx: f64,
+    ry: f64,
+    angle: f64,
+    large_arc: bool,
+    sweep: bool,
+    p1: Point,
+) {
+    if p0 == p1 {
+        return;
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        out.push(PathCommand::LineTo(p1));
+        return;
+    }
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let d = (p0 - p1) * 0.5;
+    let x1 = cos * d.x + sin * d.y;
+    let y1 = -sin * d.x + cos * d.y;
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut k = (num / den).max(0.0).sqrt();
+    if large_arc == sweep {
+        k = -k;
+    }
+    let cx1 = k * rx * y1 / ry;
+    let cy1 = -k * ry * x1 / rx;
+    let center = Point::new(
+        cos * cx1 - sin * cy1 + (p0.x + p1.x) * 0.5,
+        sin * cx1 + cos * cy1 + (p0.y + p1.y) * 0.5,
+    );
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let mut sweep_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - start_angle;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    }
+    let segments = (sweep_angle.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let step = sweep_angle / segments as f64;
+    let kappa = 4.0 / 3.0 * (step / 4.0).tan();
+    let point_at = |t: f64| {
+        let (s, c) = t.sin_cos();
+        Point::new(
+            center.x + rx * c * cos - ry * s * sin,
+            center.y + rx * c * sin + ry * s * cos,
+        )
+    };
+    let tangent_at = |t: f64| {
+        let (s, c) = t.sin_cos();
+        Vector::new(-rx * s * cos - ry * c * sin, -rx * s * sin + ry * c * cos)
+    };
+    let mut t = start_angle;
+    for index in 0..segments {
+        let t_next = t + step;
+        let end = if index + 1 == segments {p1} else {point_at(t_next)};
+        out.push(PathCommand::CubicTo(
+            point_at(t) + tangent_at(t) * kappa,
+            end - tangent_at(t_next) * kappa,
+            end,
+        ));
+        t = t_next;
+    }
+}
+
+/// Parses the numbers and flags used in path data, point lists and transforms.
+pub(crate) struct NumberParser<'a> {
+    data: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> NumberParser<'a> {
+    pub fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).cloned()
+    }
+
+    pub fn skip_separators(&mut self) {
+        while let Some(b' ' | b',' | b'\t' | b'\r' | b'\n') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    pub fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.peek(), Some(b'0'..=b'9' | b'-' | b'+' | b'.'))
+    }
+
+    pub fn number(&mut self) -> Result<f64, String> {
+        self.skip_separators();
+        let start = self.pos;
+        if let Some(b'-' | b'+') = self.peek() {
+            self.pos += 1;
+        }
+        let mut has_dot = false;
+        let mut has_digits = false;
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' => has_digits = true,
+                // a second dot starts the next number, as in "1.5.5"
+                b'.' if !has_dot => has_dot = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        if !has_digits {
+            return Err(format!("Expected number at {}", start));
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if let Some(b'-' | b'+') = self.peek() {
+                self.pos += 1;
+            }
+            let exponent_start = self.pos;
+            while let Some(b'0'..=b'9') = self.peek() {
+                self.pos += 1;
+            }
+            if self.pos == exponent_start {
+                self.pos = mantissa_end;
+            }
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .unwrap()
+            .parse()
+            .map_err(|_| format!("Invalid number at {}", start))
+    }
+
+    pub fn point(&mut self) -> Result<Point, String> {
+        Ok(Point::new(self.number()?, self.number()?))
+    }
+
+    // arc flags are single characters that need not be separated from what follows
+    pub fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(format!("Expected arc flag at {}", self.pos)),
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        let path = parse_path_data("M10-5.5l.5.5 1e1 0h-2v2.5zm1,1 2,2").unwrap();
+        assert_eq!(
+            path,
+            vec![
+                PathCommand::MoveTo(Point::new(10.0, -5.5)),
+                PathCommand::LineTo(Point::new(10.5, -5.0)),
+                PathCommand::LineTo(Point::new(20.5, -5.0)),
+                PathCommand::LineTo(Point::new(18.5, -5.0)),
+                PathCommand::LineTo(Point::new(18.5, -2.5)),
+                PathCommand::Close,
+                PathCommand::MoveTo(Point::new(11.0, -4.5)),
+                PathCommand::LineTo(Point::new(13.0, -2.5)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_arcs_and_smooth_curves() {
+        let path = parse_path_data("M0 0a5 5 0 1110 0c0-2 3-2 5 0s5 2 5 0").unwrap();
+        assert_eq!(path.len(), 5);
+        match path[2] {
+            PathCommand::CubicTo(_, _, p) => {
+                assert!((p.x - 10.0).abs() < 1e-9 && p.y.abs() < 1e-9)
+            }
+            _ => panic!(),
+        }
+        // the first control point of s mirrors the second control point of c
+        match (path[3], path[4]) {
+            (PathCommand::CubicTo(_, p2, p), PathCommand::CubicTo(p1, _, _)) => {
+                assert!(((p1 - p) - (p - p2)).length() < 1e-9)
+            }
+            _ => panic!(),
+        }
+    }
+}
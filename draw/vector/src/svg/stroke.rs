@@ -0,0 +1,285 @@
+use crate::geometry::{Point, Vector};
+use crate::path::{LinePath, LinePathCommand, LinePathIterator};
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    pub miter_limit: f64,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+/// Converts a stroked line path into a set of closed contours that cover the stroke when filled
+/// with the nonzero fill rule. Every segment, join and cap becomes its own convex contour, and all
+/// of them wind the same way so they union rather than cancel. Renderers that saturate coverage
+/// can also fill each contour on its own, which avoids the many near coincident crossings
+/// between them. `tolerance` bounds the error of round joins and caps.
+pub fn stroke_line_path<P: LinePathIterator>(path: P, style: &StrokeStyle, tolerance: f64) -> LinePath {
+    let mut contours: Vec<(Vec<Point>, bool)> = Vec::new();
+    path.for_each(&mut |command| {
+        match command {
+            LinePathCommand::MoveTo(p) => contours.push((vec![p], false)),
+            LinePathCommand::LineTo(p) => {
+                if let Some((points, _)) = contours.last_mut() {
+                    if points.last() != Some(&p) {
+                        points.push(p);
+                    }
+                }
+            }
+            LinePathCommand::Close => {
+                if let Some((points, closed)) = contours.last_mut() {
+                    if points.len() > 1 && points.first() == points.last() {
+                        points.pop();
+                    }
+                    *closed = true;
+                }
+            }
+        }
+        true
+    });
+    let mut stroker = Stroker {
+        half_width: style.width * 0.5,
+        style: *style,
+        tolerance: tolerance.max(1e-6),
+        out: LinePath::new(),
+    };
+    for (points, closed) in &contours {
+        stroker.stroke_contour(points, *closed);
+    }
+    stroker.out
+}
+
+struct Stroker {
+    half_width: f64,
+    style: StrokeStyle,
+    tolerance: f64,
+    out: LinePath,
+}
+
+impl Stroker {
+    fn stroke_contour(&mut self, points: &[Point], closed: bool) {
+        if self.half_width <= 0.0 || points.is_empty() {
+            return;
+        }
+        if points.len() == 1 {
+            // zero length subpaths only show their caps
+            match self.style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => self.arc(points[0], 0.0, 2.0 * PI),
+                LineCap::Square => self.square_cap(points[0], Vector::new(1.0, 0.0), true),
+            }
+            return;
+        }
+        let segment_count = if closed {points.len()} else {points.len() - 1};
+        for index in 0..segment_count {
+            let p0 = points[index];
+            let p1 = points[(index + 1) % points.len()];
+            let n = normal(p1 - p0) * self.half_width;
+            self.polygon(&[p0 + n, p1 + n, p1 - n, p0 - n]);
+        }
+        let join_range = if closed {0..points.len()} else {1..points.len() - 1};
+        for index in join_range {
+            let p = points[index];
+            let prev = points[(index + points.len() - 1) % points.len()];
+            let next = points[(index + 1) % points.len()];
+            self.join(p, p - prev, next - p);
+        }
+        if !closed {
+            let last = points.len() - 1;
+            self.cap(points[0], points[0] - points[1]);
+            self.cap(points[last], points[last] - points[last - 1]);
+        }
+    }
+
+    fn join(&mut self, p: Point, d0: Vector, d1: Vector) {
+        let (d0, d1) = match (d0.normalize(), d1.normalize()) {
+            (Some(d0), Some(d1)) => (d0, d1),
+            _ => return,
+        };
+        let cross = d0.cross(d1);
+        let cos = d0.dot(d1);
+        if cross.abs() < 1e-9 && cos > 0.0 {
+            return;
+        }
+        // the join goes on the outside of the turn
+        let side = if cross > 0.0 {-1.0} else {1.0};
+        let n0 = normal(d0) * (self.half_width * side);
+        let n1 = normal(d1) * (self.half_width * side);
+        match self.style.join {
+            LineJoin::Round => {
+                let a0 = n0.y.atan2(n0.x);
+                let mut sweep = n1.y.atan2(n1.x) - a0;
+                if sweep > PI {
+                    sweep -= 2.0 * PI;
+                } else if sweep < -PI {
+                    sweep += 2.0 * PI;
+                }
+                self.arc(p, a0, sweep);
+            }
+            LineJoin::Miter => {
+                let ratio = (2.0 / (1.0 + cos)).sqrt();
+                match (n0 + n1).normalize() {
+                    Some(dir) if cos > -1.0 && ratio <= self.style.miter_limit => {
+                        let tip = p + dir * (self.half_width * ratio);
+                        self.polygon(&[p, p + n0, tip, p + n1]);
+                    }
+                    _ => self.polygon(&[p, p + n0, p + n1]),
+                }
+            }
+            LineJoin::Bevel => self.polygon(&[p, p + n0, p + n1]),
+        }
+    }
+
+    fn cap(&mut self, p: Point, outward: Vector) {
+        let dir = match outward.normalize() {
+            Some(dir) => dir,
+            None => return,
+        };
+        match self.style.cap {
+            LineCap::Butt => {}
+            LineCap::Round => {
+                let n = normal(dir);
+                self.arc(p, n.y.atan2(n.x), -PI);
+            }
+            LineCap::Square => self.square_cap(p, dir, false),
+        }
+    }
+
+    fn square_cap(&mut self, p: Point, dir: Vector, both_sides: bool) {
+        let n = normal(dir) * self.half_width;
+        let d = dir * self.half_width;
+        let back = if both_sides {p - d} else {p};
+        self.polygon(&[back + n, p + d + n, p + d - n, back - n]);
+    }
+
+    // a pie slice around p, starting at angle a0
+    fn arc(&mut self, p: Point, a0: f64, sweep: f64) {
+        let r = self.half_width;
+        let step = if self.tolerance < r {
+            2.0 * (1.0 - self.tolerance / r).acos()
+        } else {
+            PI / 2.0
+        };
+        let steps = (sweep.abs() / step).ceil().max(1.0) as usize;
+        let mut polygon = Vec::with_capacity(steps + 2);
+        if sweep.abs() < 2.0 * PI {
+            polygon.push(p);
+        }
+        for index in 0..=steps {
+            let (s, c) = (a0 + sweep * index as f64 / steps as f64).sin_cos();
+            polygon.push(p + Vector::new(c, s) * r);
+        }
+        self.polygon(&polygon);
+    }
+
+    fn polygon(&mut self, points: &[Point]) {
+        let mut area = 0.0;
+        for index in 0..points.len() {
+            let p0 = points[index];
+            let p1 = points[(index + 1) % points.len()];
+            area += p0.x * p1.y - p1.x * p0.y;
+        }
+        if area == 0.0 {
+            return;
+        }
+        let mut iter: Box<dyn Iterator<Item = &Point>> = if area > 0.0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        self.out.move_to(*iter.next().unwrap());
+        for p in iter {
+            self.out.line_to(*p);
+        }
+        self.out.close();
+    }
+}
+
+fn normal(d: Vector) -> Vector {
+    match d.normalize() {
+        Some(d) => Vector::new(-d.y, d.x),
+        None => Vector::zero(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Trapezoid;
+    use crate::internal_iter::ExtendFromInternalIterator;
+    use crate::trapezoidator::{FillRule, Trapezoidator};
+
+    fn area(path: &LinePath, fill_rule: FillRule) -> f64 {
+        let mut trapezoids: Vec<Trapezoid> = Vec::new();
+        trapezoids.extend_from_internal_iter(
+            Trapezoidator::new()
+                .trapezoidate_with_fill_rule(path.commands(), fill_rule)
+                .unwrap(),
+        );
+        trapezoids
+            .iter()
+            .map(|t| (t.xs[1] - t.xs[0]) as f64 * ((t.ys[2] - t.ys[0]) + (t.ys[3] - t.ys[1])) as f64 * 0.5)
+            .sum()
+    }
+
+    fn rect(path: &mut LinePath, x0: f64, y0: f64, x1: f64, y1: f64) {
+        path.move_to(Point::new(x0, y0));
+        path.line_to(Point::new(x1, y0));
+        path.line_to(Point::new(x1, y1));
+        path.line_to(Point::new(x0, y1));
+        path.close();
+    }
+
+    #[test]
+    fn overlapping_contours_follow_fill_rule() {
+        let mut path = LinePath::new();
+        rect(&mut path, 0.0, 0.0, 2.0, 2.0);
+        rect(&mut path, 1.0, 1.0, 3.0, 3.0);
+        rect(&mut path, 9.0, 0.0, 11.0, 10.0);
+        rect(&mut path, 0.0, 9.0, 10.0, 11.0);
+        assert_eq!(area(&path, FillRule::NonZero), 7.0 + 39.0);
+        assert_eq!(area(&path, FillRule::EvenOdd), 6.0 + 38.0);
+    }
+
+    #[test]
+    fn stroked_square_covers_ring() {
+        let mut path = LinePath::new();
+        rect(&mut path, 0.0, 0.0, 10.0, 10.0);
+        // segment quads and joins union into a 12x12 square minus an 8x8 hole, bevels cut the
+        // corners off
+        let style = StrokeStyle {width: 2.0, ..StrokeStyle::default()};
+        let stroke = stroke_line_path(path.commands(), &style, 0.01);
+        assert_eq!(area(&stroke, FillRule::NonZero), 80.0);
+        let style = StrokeStyle {join: LineJoin::Bevel, ..style};
+        let stroke = stroke_line_path(path.commands(), &style, 0.01);
+        assert_eq!(area(&stroke, FillRule::NonZero), 78.0);
+    }
+}
@@ -0,0 +1,314 @@
+use super::path_data::NumberParser;
+use super::xml::XmlElement;
+use super::SvgColor;
+use crate::geometry::{Point, Transformation, Vector};
+
+/// An SVG transform matrix, mapping (x, y) to (a x + c y + e, b x + d y + f).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Matrix {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Matrix {
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Self {a, b, c, d, e, f}
+    }
+
+    pub fn translation(x: f64, y: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub fn scaling(x: f64, y: f64) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Returns the matrix that applies `other` first and then `self`.
+    pub fn multiply(self, other: Matrix) -> Matrix {
+        Matrix::new(
+            self.a * other.a + self.c * other.b,
+            self.b * other.a + self.d * other.b,
+            self.a * other.c + self.c * other.d,
+            self.b * other.c + self.d * other.d,
+            self.a * other.e + self.c * other.f + self.e,
+            self.b * other.e + self.d * other.f + self.f,
+        )
+    }
+
+    pub fn invert(self) -> Option<Matrix> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some(Matrix::new(
+            self.d / det,
+            -self.b / det,
+            -self.c / det,
+            self.a / det,
+            (self.c * self.f - self.d * self.e) / det,
+            (self.b * self.e - self.a * self.f) / det,
+        ))
+    }
+
+    /// The factor by which the matrix scales lengths, on average.
+    pub fn scale_factor(self) -> f64 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+impl Transformation for Matrix {
+    fn transform_point(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    fn transform_vector(&self, v: Vector) -> Vector {
+        Vector::new(self.a * v.x + self.c * v.y, self.b * v.x + self.d * v.y)
+    }
+}
+
+/// Parses a transform list such as `translate(10 20) rotate(45)`.
+pub(crate) fn parse_transform(data: &str) -> Result<Matrix, String> {
+    let mut matrix = Matrix::identity();
+    let mut rest = data.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(|| format!("Invalid transform {}", data))?;
+        let close = rest[open..]
+            .find(')')
+            .map(|close| open + close)
+            .ok_or_else(|| format!("Invalid transform {}", data))?;
+        let name = rest[..open].trim();
+        let mut parser = NumberParser::new(&rest[open + 1..close]);
+        let mut args = Vec::new();
+        while parser.has_number() {
+            args.push(parser.number()?);
+        }
+        let arg = |index: usize, default: f64| args.get(index).cloned().unwrap_or(default);
+        let transform = match (name, args.len()) {
+            ("matrix", 6) => Matrix::new(args[0], args[1], args[2], args[3], args[4], args[5]),
+            ("translate", 1 | 2) => Matrix::translation(args[0], arg(1, 0.0)),
+            ("scale", 1 | 2) => Matrix::scaling(args[0], arg(1, args[0])),
+            ("rotate", 1 | 3) => {
+                let (sin, cos) = args[0].to_radians().sin_cos();
+                let (cx, cy) = (arg(1, 0.0), arg(2, 0.0));
+                Matrix::translation(cx, cy)
+                    .multiply(Matrix::new(cos, sin, -sin, cos, 0.0, 0.0))
+                    .multiply(Matrix::translation(-cx, -cy))
+            }
+            ("skewX", 1) => Matrix::new(1.0, 0.0, args[0].to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", 1) => Matrix::new(1.0, args[0].to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return Err(format!("Invalid transform {}", &rest[..close + 1])),
+        };
+        matrix = matrix.multiply(transform);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(matrix)
+}
+
+/// Parses a color. Returns `None` for anything that isn't one, including `none`.
+pub(crate) fn parse_color(data: &str) -> Option<SvgColor> {
+    let data = data.trim();
+    if let Some(hex) = data.strip_prefix('#') {
+        let digit = |index: usize| u32::from_str_radix(hex.get(index..index + 1)?, 16).ok();
+        let byte = |index: usize| Some(digit(index)? * 16 + digit(index + 1)?);
+        let (r, g, b, a) = match hex.len() {
+            3 => (digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, 255),
+            4 => (digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, digit(3)? * 17),
+            6 => (byte(0)?, byte(2)?, byte(4)?, 255),
+            8 => (byte(0)?, byte(2)?, byte(4)?, byte(6)?),
+            _ => return None,
+        };
+        return Some(SvgColor::from_u8(r as u8, g as u8, b as u8, a as u8));
+    }
+    if let Some(args) = data
+        .strip_prefix("rgba(")
+        .or_else(|| data.strip_prefix("rgb("))
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        let mut channels = [0.0f32, 0.0, 0.0, 1.0];
+        for (index, arg) in args.split(|c| c == ',' || c == '/').enumerate().take(4) {
+            let arg = arg.trim();
+            let value = if let Some(percent) = arg.strip_suffix('%') {
+                percent.trim().parse::<f32>().ok()? / 100.0
+            } else if index == 3 {
+                arg.parse::<f32>().ok()?
+            } else {
+                arg.parse::<f32>().ok()? / 255.0
+            };
+            channels[index] = value.max(0.0).min(1.0);
+        }
+        return Some(SvgColor {
+            r: channels[0],
+            g: channels[1],
+            b: channels[2],
+            a: channels[3],
+        });
+    }
+    let rgb = match data.to_ascii_lowercase().as_str() {
+        "black" => 0x000000,
+        "white" => 0xffffff,
+        "red" => 0xff0000,
+        "lime" => 0x00ff00,
+        "green" => 0x008000,
+        "blue" => 0x0000ff,
+        "yellow" => 0xffff00,
+        "cyan" | "aqua" => 0x00ffff,
+        "magenta" | "fuchsia" => 0xff00ff,
+        "gray" | "grey" => 0x808080,
+        "silver" => 0xc0c0c0,
+        "maroon" => 0x800000,
+        "olive" => 0x808000,
+        "navy" => 0x000080,
+        "purple" => 0x800080,
+        "teal" => 0x008080,
+        "orange" => 0xffa500,
+        "transparent" => return Some(SvgColor::from_u8(0, 0, 0, 0)),
+        _ => return None,
+    };
+    Some(SvgColor::from_u8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8, 255))
+}
+
+/// Parses a length or percentage, where percentages are relative to `reference`. Units other
+/// than percentages are treated as user units.
+pub(crate) fn parse_length(data: &str, reference: f64) -> Option<f64> {
+    let data = data.trim();
+    if let Some(percent) = data.strip_suffix('%') {
+        return Some(percent.trim().parse::<f64>().ok()? * reference / 100.0);
+    }
+    let number = data.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    number.trim().parse().ok()
+}
+
+/// The parts of a `<style>` sheet this parser understands: rules with simple `tag`, `.class`,
+/// `#id` and `tag.class` selectors. Rules with other selectors are ignored.
+#[derive(Default)]
+pub(crate) struct StyleSheet {
+    rules: Vec<(SimpleSelector, Vec<(String, String)>)>,
+}
+
+struct SimpleSelector {
+    tag: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+}
+
+impl StyleSheet {
+    pub fn add(&mut self, css: &str) {
+        let mut css = css.to_string();
+        while let Some(start) = css.find("/*") {
+            let end = css[start..].find("*/").map_or(css.len(), |end| start + end + 2);
+            css.replace_range(start..end, "");
+        }
+        for rule in css.split('}') {
+            let (selectors, body) = match rule.split_once('{') {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let declarations = parse_declarations(body);
+            for selector in selectors.split(',') {
+                if let Some(selector) = SimpleSelector::parse(selector.trim()) {
+                    self.rules.push((selector, declarations.clone()));
+                }
+            }
+        }
+    }
+
+    /// The declarations that apply to `element`, in cascade order.
+    pub fn declarations<'a>(&'a self, element: &'a XmlElement) -> impl Iterator<Item = &'a (String, String)> {
+        self.rules
+            .iter()
+            .filter(move |(selector, _)| selector.matches(element))
+            .flat_map(|(_, declarations)| declarations.iter())
+    }
+}
+
+impl SimpleSelector {
+    fn parse(selector: &str) -> Option<SimpleSelector> {
+        if selector.is_empty()
+            || selector
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '>' | '+' | '~' | '[' | ':'))
+        {
+            return None;
+        }
+        let mut result = SimpleSelector {
+            tag: None,
+            id: None,
+            classes: Vec::new(),
+        };
+        let mut rest = selector;
+        let first = rest.find(|c| c == '.' || c == '#').unwrap_or(rest.len());
+        if first > 0 && &rest[..first] != "*" {
+            result.tag = Some(rest[..first].to_string());
+        }
+        rest = &rest[first..];
+        while !rest.is_empty() {
+            let end = rest[1..].find(|c| c == '.' || c == '#').map_or(rest.len(), |end| end + 1);
+            let name = rest[1..end].to_string();
+            if rest.starts_with('#') {
+                result.id = Some(name);
+            } else {
+                result.classes.push(name);
+            }
+            rest = &rest[end..];
+        }
+        Some(result)
+    }
+
+    fn matches(&self, element: &XmlElement) -> bool {
+        if let Some(tag) = &self.tag {
+            if tag != &element.name {
+                return false;
+            }
+        }
+        if let Some(id) = &self.id {
+            if element.attr("id") != Some(id.as_str()) {
+                return false;
+            }
+        }
+        let classes = element.attr("class").unwrap_or("");
+        self.classes
+            .iter()
+            .all(|class| classes.split_whitespace().any(|c| c == class))
+    }
+}
+
+/// Parses `name: value; ...` declarations as found in `style` attributes.
+pub(crate) fn parse_declarations(data: &str) -> Vec<(String, String)> {
+    data.split(';')
+        .filter_map(|declaration| {
+            let (name, value) = declaration.split_once(':')?;
+            let value = value.trim().trim_end_matches("!important").trim();
+            Some((name.trim().to_string(), value.to_string()))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_transform_lists() {
+        let matrix = parse_transform("translate(10 20), scale(2)").unwrap();
+        let p = matrix.transform_point(Point::new(1.0, 1.0));
+        assert!((p.x - 12.0).abs() < 1e-9 && (p.y - 22.0).abs() < 1e-9);
+        let p = parse_transform("rotate(90 5 5)").unwrap().transform_point(Point::new(10.0, 5.0));
+        assert!((p.x - 5.0).abs() < 1e-9 && (p.y - 10.0).abs() < 1e-9);
+        assert!(parse_transform("").is_ok());
+        for data in ["scale 2)", ")scale(2)", "scale(2", "scale(2)) translate(1)", "skewX(1 2)", "spin(1)"] {
+            assert!(parse_transform(data).is_err(), "should not parse: {}", data);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+/// An element of an XML document. Text content, comments and processing instructions are dropped,
+/// except for the text of `style` elements which is kept in `text`.
+#[derive(Clone, Debug, Default)]
+pub struct XmlElement {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    pub text: String,
+}
+
+impl XmlElement {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(attr_name, _)| attr_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Parses an XML document into a tree and returns its root element.
+pub fn parse_xml(data: &str) -> Result<XmlElement, String> {
+    let mut parser = XmlParser { data, pos: 0 };
+    let mut stack = vec![XmlElement::default()];
+    while let Some(offset) = parser.rest().find('<') {
+        let text = &parser.rest()[..offset];
+        if stack.len() > 1 {
+            stack.last_mut().unwrap().text.push_str(&decode_entities(text));
+        }
+        parser.pos += offset;
+        if parser.skip("<!--") {
+            parser.skip_past("-->")?;
+        } else if parser.skip("<![CDATA[") {
+            let end = parser.rest().find("]]>").ok_or("Unterminated CDATA section")?;
+            stack.last_mut().unwrap().text.push_str(&parser.rest()[..end]);
+            parser.pos += end + 3;
+        } else if parser.skip("<?") {
+            parser.skip_past("?>")?;
+        } else if parser.skip("<!") {
+            parser.skip_past(">")?;
+        } else if parser.skip("</") {
+            let name = parser.name();
+            parser.skip_whitespace();
+            if !parser.skip(">") {
+                return Err(format!("Expected > after </{}", name));
+            }
+            if stack.len() < 2 || stack.last().unwrap().name != name {
+                return Err(format!("Unexpected closing tag </{}>", name));
+            }
+            let element = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(element);
+        } else {
+            parser.pos += 1;
+            let mut element = XmlElement {
+                name: parser.name().to_string(),
+                ..XmlElement::default()
+            };
+            if element.name.is_empty() {
+                return Err(format!("Expected element name at {}", parser.pos));
+            }
+            loop {
+                parser.skip_whitespace();
+                if parser.skip("/>") {
+                    stack.last_mut().unwrap().children.push(element);
+                    break;
+                }
+                if parser.skip(">") {
+                    stack.push(element);
+                    break;
+                }
+                let name = parser.name().to_string();
+                if name.is_empty() {
+                    return Err(format!("Unexpected character in <{}>", element.name));
+                }
+                parser.skip_whitespace();
+                if !parser.skip("=") {
+                    return Err(format!("Expected = after attribute {}", name));
+                }
+                parser.skip_whitespace();
+                let value = parser.quoted()?;
+                element.attrs.push((name, decode_entities(value)));
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err(format!("Unclosed element <{}>", stack.last().unwrap().name));
+    }
+    stack
+        .pop()
+        .unwrap()
+        .children
+        .pop()
+        .ok_or_else(|| "No root element".to_string())
+}
+
+struct XmlParser<'a> {
+    data: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.data[self.pos..]
+    }
+
+    fn skip(&mut self, pat: &str) -> bool {
+        if self.rest().starts_with(pat) {
+            self.pos += pat.len();
+            return true;
+        }
+        false
+    }
+
+    fn skip_past(&mut self, pat: &str) -> Result<(), String> {
+        let end = self
+            .rest()
+            .find(pat)
+            .ok_or_else(|| format!("Expected {}", pat))?;
+        self.pos += end + pat.len();
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == ':' || c == '_' || c == '-' || c == '.'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn quoted(&mut self) -> Result<&'a str, String> {
+        let rest = self.rest();
+        let quote = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(format!("Expected quoted attribute value at {}", self.pos)),
+        };
+        let end = rest[1..]
+            .find(quote)
+            .ok_or("Unterminated attribute value")?;
+        self.pos += end + 2;
+        Ok(&rest[1..end + 1])
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let decoded = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            entity if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            entity if entity.starts_with('#') => {
+                entity[1..].parse().ok().and_then(char::from_u32)
+            }
+            _ => None,
+        };
+        if let Some(c) = decoded {
+            out.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            out.push('&');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
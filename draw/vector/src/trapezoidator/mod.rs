@@ -6,12 +6,30 @@ use std::collections::BinaryHeap;
 use std::mem;
 use std::ops::Range;
 
+/// The rule that decides which regions of a set of contours are inside.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
 /// Converts a sequence of line path commands to a sequence of trapezoids. The line path commands
 /// should define a set of closed contours.
 #[derive(Clone, Debug, Default)]
 pub struct Trapezoidator {
     event_queue: BinaryHeap<Event>,
     active_segments: Vec<ActiveSegment>,
+    fill_rule: FillRule,
 }
 
 impl Trapezoidator {
@@ -23,6 +41,12 @@ impl Trapezoidator {
     /// Returns an iterator over trapezoids corresponding to the given iterator over line path
     /// commands.
     pub fn trapezoidate<P: LinePathIterator>(&mut self, path: P)->Option<Trapezoidate>{
+        self.trapezoidate_with_fill_rule(path, FillRule::NonZero)
+    }
+
+    /// Like `trapezoidate`, but with the given fill rule.
+    pub fn trapezoidate_with_fill_rule<P: LinePathIterator>(&mut self, path: P, fill_rule: FillRule)->Option<Trapezoidate>{
+        self.fill_rule = fill_rule;
         let mut initial_point = None;
         let mut current_point = None;
         if !path.for_each(&mut |command| {
@@ -123,11 +147,53 @@ impl Trapezoidator {
         {
             trapezoid_segments.push(trapezoid_segment);
         }
+        self.split_new_neighbors_at_intersections(point, incident_segment_range);
         self.generate_trapezoids(trapezoid_segments, f)
     }
 
+    // segments that became neighbors at this point may cross further on. splitting both at the
+    // crossing keeps the active segments ordered, so overlapping contours work
+    fn split_new_neighbors_at_intersections(&mut self, point: Point, range: Range<usize>) {
+        let len = self.active_segments.len();
+        if range.start > 0 && range.start < len {
+            self.split_at_intersection(point, range.start - 1, range.start);
+        }
+        if range.end > range.start && range.end < len {
+            self.split_at_intersection(point, range.end - 1, range.end);
+        }
+    }
+
+    fn split_at_intersection(&mut self, point: Point, index_0: usize, index_1: usize) {
+        let segment_0 = self.active_segments[index_0].segment;
+        let segment_1 = self.active_segments[index_1].segment;
+        let mut intersection = match intersect_segments(segment_0, segment_1) {
+            Some(intersection) => intersection,
+            None => return,
+        };
+        // rounding can move the crossing out of the part that is left to sweep, in which case
+        // the segments are split as close to it as possible, so they get reordered anyway
+        if intersection < point {
+            intersection = point;
+        }
+        let end = if segment_0.p1 < segment_1.p1 {segment_0.p1} else {segment_1.p1};
+        if intersection > end {
+            intersection = end;
+        }
+        for index in [index_0, index_1] {
+            if self.active_segments[index].segment.p0 >= intersection {
+                continue;
+            }
+            if let Some(pending_segment) = self.active_segments[index].split_back_mut(intersection) {
+                self.event_queue.push(Event {
+                    point: intersection,
+                    pending_segment: Some(pending_segment),
+                });
+            }
+        }
+    }
+
     fn find_incident_segment_range(&self, point: Point) -> Range<usize> {
-        Range {
+        let mut range = Range {
             start: self
                 .active_segments
                 .iter()
@@ -142,7 +208,19 @@ impl Trapezoidator {
                     active_segment.segment.compare_to_point(point).unwrap() != Ordering::Greater
                 })
                 .map_or(0, |index| index + 1),
+        };
+        // rounding can make a segment through the point compare as below or above it, while
+        // splitting it at the point would still land on the point exactly
+        let passes_through = |index: usize| {
+            self.active_segments[index].segment.intersect_with_vertical_line(point.x) == Some(point)
+        };
+        while range.start > 0 && passes_through(range.start - 1) {
+            range.start -= 1;
         }
+        while range.end < self.active_segments.len() && passes_through(range.end) {
+            range.end += 1;
+        }
+        range
     }
 
     fn find_lower_trapezoid_segment(
@@ -220,13 +298,14 @@ impl Trapezoidator {
         } else {
             self.active_segments[incident_segment_range.end - 1].upper_region
         };
+        let fill_rule = self.fill_rule;
         self.active_segments.splice(
             incident_segment_range.end..incident_segment_range.end,
             Iterator::map(right_segments.iter(), |right_segment| {
                 let upper_region = {
                     let winding = lower_region.winding + right_segment.winding;
                     Region {
-                        is_inside: winding != 0,
+                        is_inside: fill_rule.is_inside(winding),
                         winding,
                     }
                 };
@@ -295,6 +374,29 @@ impl Trapezoidator {
     }
 }
 
+// the point where two segments cross, excluding their endpoints
+fn intersect_segments(segment_0: LineSegment, segment_1: LineSegment) -> Option<Point> {
+    let d0 = segment_0.p1 - segment_0.p0;
+    let d1 = segment_1.p1 - segment_1.p0;
+    let denominator = d0.cross(d1);
+    if denominator == 0.0 {
+        return None;
+    }
+    let w = segment_1.p0 - segment_0.p0;
+    let t0 = w.cross(d1) / denominator;
+    let t1 = w.cross(d0) / denominator;
+    if !(t0 > 0.0 && t0 < 1.0 && t1 > 0.0 && t1 < 1.0) {
+        return None;
+    }
+    if d0.x == 0.0 {
+        return segment_1.intersect_with_vertical_line(segment_0.p0.x);
+    }
+    if d1.x == 0.0 {
+        return segment_0.intersect_with_vertical_line(segment_1.p0.x);
+    }
+    Some(segment_0.p0 + d0 * t0)
+}
+
 /// An iterator over trapezoids corresponding to the given iterator over line path commands.
 #[derive(Debug)]
 pub struct Trapezoidate<'a> {
@@ -54,6 +54,27 @@ pub enum WebSocketError<'a> {
     TextNotUTF8(&'a [u8]),
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageFormat {
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong
+}
+
+impl MessageFormat {
+    pub fn opcode(&self) -> u8 {
+        match self {
+            MessageFormat::Text => 1,
+            MessageFormat::Binary => 2,
+            MessageFormat::Close => 8,
+            MessageFormat::Ping => 9,
+            MessageFormat::Pong => 10,
+        }
+    }
+}
+
 pub const PING_MESSAGE:[u8;2] = [128 | 9,0];
 pub const PONG_MESSAGE:[u8;2] = [128 | 10,0];
 
@@ -114,19 +135,45 @@ impl WebSocket {
         }
     }
     
-    pub fn create_upgrade_response(key: &str) -> String {
+    pub fn create_accept_key(key: &str) -> String {
         let to_hash = format!("{}258EAFA5-E914-47DA-95CA-C5AB0DC85B11", key);
         let mut sha1 = Sha1::new();
         sha1.update(to_hash.as_bytes());
         let out_bytes = sha1.finalise();
-        let base64 = base64_encode(&out_bytes);
+        base64_encode(&out_bytes)
+    }
+    
+    pub fn create_upgrade_response(key: &str) -> String {
         let response_ack = format!(
             "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
-            base64
+            Self::create_accept_key(key)
         );
         response_ack
     }
     
+    // clients have to mask every frame they send, servers must not
+    pub fn create_client_message(format: MessageFormat, data: &[u8], mask: [u8; 4]) -> Vec<u8> {
+        let len = data.len();
+        let mut out = Vec::with_capacity(len + 14);
+        out.push(128 | format.opcode());
+        if len < 126 {
+            out.push(128 | len as u8);
+        }
+        else if len < 65536 {
+            out.push(128 | 126);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        else {
+            out.push(128 | 127);
+            out.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+        out.extend_from_slice(&mask);
+        for (i, byte) in data.iter().enumerate() {
+            out.push(byte ^ mask[i & 3]);
+        }
+        out
+    }
+    
     fn parse_head(&mut self, input: &[u8]) -> bool {
         while self.head_expected > 0
             && self.input_read < input.len()
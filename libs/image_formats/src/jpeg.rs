@@ -1205,7 +1205,7 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
                 }
                 for k in 0..mbwidth - 1 {
                     match itype {
-                        TYPE_Y => {draw_macroblock_y(&mut image, k * 8, mbheight * 8 - 8, 8, mbheight * 8 - height, &coeffs[mb..mb + 64]); mb += 64;},
+                        TYPE_Y => {draw_macroblock_y(&mut image, k * 8, mbheight * 8 - 8, 8, height - (mbheight - 1) * 8, &coeffs[mb..mb + 64]); mb += 64;},
                         TYPE_YUV420 => {draw_macroblock_yuv420(&mut image, k * 16, mbheight * 16 - 16, 16, height - (mbheight - 1) * 16, &coeffs[mb..mb + 384]); mb += 384;},
                         TYPE_YUV422 => {draw_macroblock_yuv422(&mut image, k * 16, mbheight * 8 - 8, 16, height - (mbheight - 1) * 8, &coeffs[mb..mb + 256]); mb += 256;},
                         TYPE_YUV440 => {draw_macroblock_yuv440(&mut image, k * 8, mbheight * 16 - 16, 8, height - (mbheight - 1) * 16, &coeffs[mb..mb + 256]); mb += 256;},
@@ -1235,6 +1235,341 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     Err("Invalid JPEG 11".to_string())
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented yet".to_string())
+// zigzag position -> natural position in the 8x8 block
+const ZIGZAG: [u8; 64] = [0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20, 13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59, 52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,];
+
+// the example tables from annex K of the standard, in natural order
+const LUMINANCE_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61,
+    12, 12, 14, 19, 26, 58, 60, 55,
+    14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62,
+    18, 22, 37, 56, 68, 109, 103, 77,
+    24, 35, 55, 64, 81, 104, 113, 92,
+    49, 64, 78, 87, 103, 121, 120, 101,
+    72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+const CHROMINANCE_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99,
+    18, 21, 26, 66, 99, 99, 99, 99,
+    24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+const DC_LUMINANCE_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_LUMINANCE_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const DC_CHROMINANCE_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_CHROMINANCE_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const AC_LUMINANCE_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7D];
+const AC_LUMINANCE_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08, 0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+    0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5,
+    0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+const AC_CHROMINANCE_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMINANCE_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0,
+    0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5,
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3,
+    0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum JpegSubsampling {
+    Yuv444,
+    Yuv422,
+    Yuv420,
+    // a single luminance component, color is dropped
+    Gray,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct JpegOptions {
+    pub quality: u8, // 1..=100
+    pub subsampling: JpegSubsampling,
+}
+
+impl Default for JpegOptions {
+    fn default() -> JpegOptions {
+        JpegOptions {
+            quality: 90,
+            subsampling: JpegSubsampling::Yuv420,
+        }
+    }
+}
+
+// the quantization table for a quality, scaled like the IJG encoder does
+fn scale_quant(base: &[u16; 64], quality: u8) -> [u16; 64] {
+    let quality = clamp(quality as u32, 1, 100);
+    let scale = if quality < 50 {5000 / quality} else {200 - quality * 2};
+    let mut table = [0u16; 64];
+    for i in 0..64 {
+        table[i] = clamp((base[i] as u32 * scale + 50) / 100, 1, 255) as u16;
+    }
+    table
+}
+
+// (code, length) for every symbol of a huffman table
+fn huffman_codes(bits: &[u8; 16], values: &[u8]) -> [(u16, u8); 256] {
+    let mut codes = [(0u16, 0u8); 256];
+    let mut code = 0u16;
+    let mut k = 0;
+    for (i, count) in bits.iter().enumerate() {
+        for _ in 0..*count {
+            codes[values[k] as usize] = (code, i as u8 + 1);
+            code += 1;
+            k += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+struct Writer {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl Writer {
+    fn push16(&mut self, d: u16) {
+        self.out.push((d >> 8) as u8);
+        self.out.push((d & 255) as u8);
+    }
+    
+    fn put(&mut self, code: u32, length: u32) {
+        self.acc = (self.acc << length) | (code & ((1 << length) - 1));
+        self.bits += length;
+        while self.bits >= 8 {
+            let b = (self.acc >> (self.bits - 8)) as u8;
+            self.out.push(b);
+            if b == 0xFF { // stuffing
+                self.out.push(0);
+            }
+            self.bits -= 8;
+        }
+        self.acc &= (1 << self.bits) - 1;
+    }
+    
+    fn flush(&mut self) {
+        if self.bits > 0 { // pad with ones
+            self.put(0x7F, 8 - self.bits);
+        }
+    }
+    
+    fn marker_segment(&mut self, marker: u16, data: &[u8]) {
+        self.push16(marker);
+        self.push16(data.len() as u16 + 2);
+        self.out.extend_from_slice(data);
+    }
+    
+    fn huffman_segment(&mut self, class_id: u8, bits: &[u8; 16], values: &[u8]) {
+        let mut data = vec![class_id];
+        data.extend_from_slice(bits);
+        data.extend_from_slice(values);
+        self.marker_segment(0xFFC4, &data);
+    }
+}
+
+// forward DCT of one block of level shifted samples, quantized and in zigzag order
+fn fdct_quantize(samples: &[f32; 64], cos: &[[f32; 8]; 8], quant: &[u16; 64]) -> [i32; 64] {
+    let mut rows = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.0;
+            for x in 0..8 {
+                sum += cos[u][x] * samples[y * 8 + x];
+            }
+            rows[y * 8 + u] = sum;
+        }
+    }
+    let mut out = [0i32; 64];
+    for (k, n) in ZIGZAG.iter().enumerate() {
+        let v = *n as usize / 8;
+        let u = *n as usize % 8;
+        let mut sum = 0.0;
+        for y in 0..8 {
+            sum += cos[v][y] * rows[y * 8 + u];
+        }
+        let limit = if k == 0 {2047} else {1023};
+        out[k] = clamp((sum / quant[*n as usize] as f32).round() as i32, -limit, limit);
+    }
+    out
+}
+
+fn category(v: i32) -> u32 {
+    32 - v.unsigned_abs().leading_zeros()
+}
+
+fn pack_block(writer: &mut Writer, coeffs: &[i32; 64], dc: &mut i32, dc_codes: &[(u16, u8); 256], ac_codes: &[(u16, u8); 256]) {
+    let diff = coeffs[0] - *dc;
+    *dc = coeffs[0];
+    let cat = category(diff);
+    let (code, length) = dc_codes[cat as usize];
+    writer.put(code as u32, length as u32);
+    if cat > 0 {
+        writer.put(if diff < 0 {(diff - 1) as u32} else {diff as u32}, cat);
+    }
+    let mut run = 0;
+    for coeff in &coeffs[1..] {
+        if *coeff == 0 {
+            run += 1;
+            continue;
+        }
+        while run >= 16 { // 16 zeroes
+            let (code, length) = ac_codes[0xF0];
+            writer.put(code as u32, length as u32);
+            run -= 16;
+        }
+        let cat = category(*coeff);
+        let (code, length) = ac_codes[(run << 4) | cat as usize];
+        writer.put(code as u32, length as u32);
+        writer.put(if *coeff < 0 {(*coeff - 1) as u32} else {*coeff as u32}, cat);
+        run = 0;
+    }
+    if run > 0 { // end of block
+        let (code, length) = ac_codes[0x00];
+        writer.put(code as u32, length as u32);
+    }
+}
+
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    encode_with_options(image, &JpegOptions::default())
+}
+
+// baseline JPEG with the standard huffman tables. alpha is dropped
+pub fn encode_with_options(image: &ImageBuffer, options: &JpegOptions) -> Result<Vec<u8>, String> {
+    let width = image.width;
+    let height = image.height;
+    if (width == 0) || (height == 0) || (width > 65535) || (height > 65535) || (image.data.len() < width * height) {
+        return Err("Invalid image size for JPEG".to_string());
+    }
+    let (hs, vs) = match options.subsampling {
+        JpegSubsampling::Yuv444 | JpegSubsampling::Gray => (1, 1),
+        JpegSubsampling::Yuv422 => (2, 1),
+        JpegSubsampling::Yuv420 => (2, 2),
+    };
+    let gray = options.subsampling == JpegSubsampling::Gray;
+    let luminance_quant = scale_quant(&LUMINANCE_QUANT, options.quality);
+    let chrominance_quant = scale_quant(&CHROMINANCE_QUANT, options.quality);
+    
+    let mut writer = Writer {out: Vec::new(), acc: 0, bits: 0};
+    writer.push16(0xFFD8); // image start
+    writer.marker_segment(0xFFE0, &[b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0]); // JFIF, 1:1 aspect ratio
+    let mut data = Vec::new();
+    for (id, table) in [&luminance_quant, &chrominance_quant].iter().enumerate().take(if gray {1} else {2}) {
+        data.push(id as u8);
+        for n in ZIGZAG.iter() {
+            data.push(table[*n as usize] as u8);
+        }
+    }
+    writer.marker_segment(0xFFDB, &data);
+    let components = if gray {1} else {3};
+    let mut data = vec![8, (height >> 8) as u8, (height & 255) as u8, (width >> 8) as u8, (width & 255) as u8, components];
+    data.extend_from_slice(&[1, ((hs << 4) | vs) as u8, 0]);
+    if !gray {
+        data.extend_from_slice(&[2, 0x11, 1, 3, 0x11, 1]);
+    }
+    writer.marker_segment(0xFFC0, &data); // baseline sequential
+    writer.huffman_segment(0x00, &DC_LUMINANCE_BITS, &DC_LUMINANCE_VALUES);
+    writer.huffman_segment(0x10, &AC_LUMINANCE_BITS, &AC_LUMINANCE_VALUES);
+    if !gray {
+        writer.huffman_segment(0x01, &DC_CHROMINANCE_BITS, &DC_CHROMINANCE_VALUES);
+        writer.huffman_segment(0x11, &AC_CHROMINANCE_BITS, &AC_CHROMINANCE_VALUES);
+    }
+    let data = if gray {
+        vec![1, 1, 0x00, 0, 63, 0]
+    }
+    else {
+        vec![3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]
+    };
+    writer.marker_segment(0xFFDA, &data); // scan start
+    
+    // convert to YCbCr planes, level shifted around 0
+    let mut planes = vec![vec![0f32; width * height]; components as usize];
+    for (i, pixel) in image.data[..width * height].iter().enumerate() {
+        let r = ((pixel >> 16) & 255) as f32;
+        let g = ((pixel >> 8) & 255) as f32;
+        let b = (pixel & 255) as f32;
+        planes[0][i] = 0.299 * r + 0.587 * g + 0.114 * b - 128.0;
+        if !gray {
+            planes[1][i] = -0.168736 * r - 0.331264 * g + 0.5 * b;
+            planes[2][i] = 0.5 * r - 0.418688 * g - 0.081312 * b;
+        }
+    }
+    
+    let mut cos = [[0f32; 8]; 8];
+    for (u, row) in cos.iter_mut().enumerate() {
+        for (x, c) in row.iter_mut().enumerate() {
+            let scale = if u == 0 {0.5 / 2f32.sqrt()} else {0.5};
+            *c = scale * (((2 * x + 1) * u) as f32 * std::f32::consts::PI / 16.0).cos();
+        }
+    }
+    let dc_luminance = huffman_codes(&DC_LUMINANCE_BITS, &DC_LUMINANCE_VALUES);
+    let ac_luminance = huffman_codes(&AC_LUMINANCE_BITS, &AC_LUMINANCE_VALUES);
+    let dc_chrominance = huffman_codes(&DC_CHROMINANCE_BITS, &DC_CHROMINANCE_VALUES);
+    let ac_chrominance = huffman_codes(&AC_CHROMINANCE_BITS, &AC_CHROMINANCE_VALUES);
+    
+    // samples past the edge repeat the last row or column
+    let sample = |plane: &[f32], x: usize, y: usize| plane[y.min(height - 1) * width + x.min(width - 1)];
+    let mut dc = [0i32; 3];
+    let mut block = [0f32; 64];
+    for my in 0..height.div_ceil(8 * vs) {
+        for mx in 0..width.div_ceil(8 * hs) {
+            let x0 = mx * 8 * hs;
+            let y0 = my * 8 * vs;
+            for by in 0..vs {
+                for bx in 0..hs {
+                    for i in 0..8 {
+                        for k in 0..8 {
+                            block[i * 8 + k] = sample(&planes[0], x0 + bx * 8 + k, y0 + by * 8 + i);
+                        }
+                    }
+                    let coeffs = fdct_quantize(&block, &cos, &luminance_quant);
+                    pack_block(&mut writer, &coeffs, &mut dc[0], &dc_luminance, &ac_luminance);
+                }
+            }
+            for c in 1..components as usize {
+                // average the pixels under each chroma sample
+                for i in 0..8 {
+                    for k in 0..8 {
+                        let mut sum = 0.0;
+                        for dy in 0..vs {
+                            for dx in 0..hs {
+                                sum += sample(&planes[c], x0 + k * hs + dx, y0 + i * vs + dy);
+                            }
+                        }
+                        block[i * 8 + k] = sum / (hs * vs) as f32;
+                    }
+                }
+                let coeffs = fdct_quantize(&block, &cos, &chrominance_quant);
+                pack_block(&mut writer, &coeffs, &mut dc[c], &dc_chrominance, &ac_chrominance);
+            }
+        }
+    }
+    writer.flush();
+    writer.push16(0xFFD9); // image end
+    Ok(writer.out)
 }
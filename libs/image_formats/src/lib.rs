@@ -4,7 +4,120 @@
 
 mod image;
 pub use image::*;
-//pub mod bmp;
-//pub mod png;
+pub mod bmp;
+pub mod png;
 pub mod jpeg;
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Bmp,
+}
+
+impl ImageFormat {
+    // sniffs the format from the first bytes of a file
+    pub fn detect(src: &[u8]) -> Option<ImageFormat> {
+        if src.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+            Some(ImageFormat::Png)
+        }
+        else if src.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(ImageFormat::Jpeg)
+        }
+        else if (src.len() >= 26) && [b"BM", b"BA", b"CI", b"CP", b"IC", b"PT"].iter().any(|tag| src.starts_with(*tag)) {
+            Some(ImageFormat::Bmp)
+        }
+        else {
+            None
+        }
+    }
+}
+
+pub fn decode_any(src: &[u8]) -> Result<ImageBuffer, String> {
+    match ImageFormat::detect(src) {
+        Some(ImageFormat::Png) => png::decode(src),
+        Some(ImageFormat::Jpeg) => jpeg::decode(src),
+        Some(ImageFormat::Bmp) => bmp::decode(src),
+        None => Err("Unknown image format".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a smooth gradient with some alpha
+    fn gradient(width: usize, height: usize, alpha: bool) -> ImageBuffer {
+        let mut image = ImageBuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let r = (x * 255 / width) as u32;
+                let g = (y * 255 / height) as u32;
+                let b = ((x + y) * 127 / (width + height)) as u32 + 64;
+                let a = if alpha {((x * 7 + y * 3) & 255) as u32} else {255};
+                image.data[y * width + x] = (a << 24) | (r << 16) | (g << 8) | b;
+            }
+        }
+        image
+    }
+
+    fn mean_error(a: &ImageBuffer, b: &ImageBuffer) -> f32 {
+        assert_eq!((a.width, a.height), (b.width, b.height));
+        let mut total = 0;
+        for (p, q) in a.data.iter().zip(b.data.iter()) {
+            for shift in [0, 8, 16] {
+                total += (((p >> shift) & 255) as i32 - ((q >> shift) & 255) as i32).abs();
+            }
+        }
+        total as f32 / (a.data.len() * 3) as f32
+    }
+
+    #[test]
+    fn png_round_trip() {
+        for alpha in [false, true] {
+            let image = gradient(37, 21, alpha);
+            for filter in [png::PngFilter::None, png::PngFilter::Sub, png::PngFilter::Up, png::PngFilter::Average, png::PngFilter::Paeth, png::PngFilter::Adaptive] {
+                let data = png::encode_with_options(&image, &png::PngOptions {filter, level: 6}).unwrap();
+                assert_eq!(ImageFormat::detect(&data), Some(ImageFormat::Png));
+                assert_eq!(png::test(&data), Some((37, 21)));
+                assert_eq!(decode_any(&data).unwrap().data, image.data, "{:?}", filter);
+            }
+        }
+        assert!(png::encode(&ImageBuffer::new(0, 4)).is_err());
+    }
+
+    #[test]
+    fn jpeg_round_trip() {
+        let image = gradient(45, 29, false);
+        for (subsampling, limit) in [(jpeg::JpegSubsampling::Yuv444, 2.0), (jpeg::JpegSubsampling::Yuv422, 2.5), (jpeg::JpegSubsampling::Yuv420, 3.0)] {
+            let data = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality: 95, subsampling}).unwrap();
+            assert_eq!(ImageFormat::detect(&data), Some(ImageFormat::Jpeg));
+            let decoded = decode_any(&data).unwrap();
+            assert!(mean_error(&image, &decoded) < limit, "{:?} {}", subsampling, mean_error(&image, &decoded));
+            assert!(decoded.data.iter().all(|pixel| pixel >> 24 == 255));
+        }
+        // lower quality gives smaller files
+        let small = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality: 20, ..Default::default()}).unwrap();
+        let large = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality: 100, ..Default::default()}).unwrap();
+        assert!(small.len() < large.len());
+        assert!(mean_error(&image, &decode_any(&small).unwrap()) < 10.0);
+
+        let data = jpeg::encode_with_options(&image, &jpeg::JpegOptions {quality: 95, subsampling: jpeg::JpegSubsampling::Gray}).unwrap();
+        let decoded = decode_any(&data).unwrap();
+        for (p, q) in image.data.iter().zip(decoded.data.iter()) {
+            let luma = (((p >> 16) & 255) * 299 + ((p >> 8) & 255) * 587 + (p & 255) * 114) / 1000;
+            assert!(((q & 255) as i32 - luma as i32).abs() <= 6);
+            assert_eq!(q & 255, (q >> 16) & 255);
+        }
+    }
+
+    #[test]
+    fn bmp_round_trip() {
+        let image = gradient(13, 7, true);
+        let data = bmp::encode(&image).unwrap();
+        assert_eq!(ImageFormat::detect(&data), Some(ImageFormat::Bmp));
+        assert_eq!(decode_any(&data).unwrap().data, image.data);
+        assert!(decode_any(b"GIF89a").is_err());
+        assert_eq!(ImageFormat::detect(b""), None);
+    }
+}
@@ -3,6 +3,11 @@
 
 use std::cmp::Ordering;
 
+use makepad_miniz::{
+    crc32::{crc32, update_crc32},
+    deflate::compress_to_vec_zlib,
+};
+
 use crate::ImageBuffer;
 
 // Inflate algorithm
@@ -822,8 +827,8 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
                     TYPE_RGBA8 => {stride = width * 4; bpp = 4;},
                     TYPE_L16 => {stride = width * 2; bpp = 2;},
                     TYPE_RGB16 => {stride = width * 6; bpp = 6;},
-                    TYPE_LA16 => {stride = width * 2; bpp = 4;},
-                    TYPE_RGBA16 => {stride = width * 4; bpp = 8;},
+                    TYPE_LA16 => {stride = width * 4; bpp = 4;},
+                    TYPE_RGBA16 => {stride = width * 8; bpp = 8;},
                     _ => {return Err("Invalid PNG".to_string());}
                 }
                 sp += chunk_length;
@@ -1039,6 +1044,191 @@ pub fn decode(src: &[u8]) -> Result<ImageBuffer, String> {
     }
 }
 
-pub fn encode(_image: &ImageBuffer) -> Result<Vec<u8>, String> {
-    Err("not implemented".to_string())
-}
\ No newline at end of file
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PngFilter {
+    None,
+    Sub,
+    Up,
+    Average,
+    Paeth,
+    // pick the filter for each row that leaves the smallest differences
+    Adaptive,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PngOptions {
+    pub filter: PngFilter,
+    pub level: u8, // deflate level, 0..=10
+}
+
+impl Default for PngOptions {
+    fn default() -> PngOptions {
+        PngOptions {
+            filter: PngFilter::Adaptive,
+            level: 6,
+        }
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if (pa <= pb) && (pa <= pc) {
+        a
+    }
+    else if pb <= pc {
+        b
+    }
+    else {
+        c
+    }
+}
+
+// filters one row into dst, prev is the unfiltered row above (zeroes for the first row)
+fn filter_row(dst: &mut Vec<u8>, filter: u8, row: &[u8], prev: &[u8], bpp: usize) {
+    dst.push(filter);
+    for i in 0..row.len() {
+        let a = if i >= bpp {row[i - bpp]} else {0};
+        let b = prev[i];
+        let c = if i >= bpp {prev[i - bpp]} else {0};
+        let predicted = match filter {
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => 0,
+        };
+        dst.push(row[i].wrapping_sub(predicted));
+    }
+}
+
+fn chunk(dst: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    dst.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    dst.extend_from_slice(chunk_type);
+    dst.extend_from_slice(data);
+    let crc = update_crc32(crc32(chunk_type), data);
+    dst.extend_from_slice(&crc.to_be_bytes());
+}
+
+pub fn encode(image: &ImageBuffer) -> Result<Vec<u8>, String> {
+    encode_with_options(image, &PngOptions::default())
+}
+
+// 8-bit RGB, or RGBA when any pixel is not opaque
+pub fn encode_with_options(image: &ImageBuffer, options: &PngOptions) -> Result<Vec<u8>, String> {
+    let width = image.width;
+    let height = image.height;
+    // IHDR allows sizes up to 2^31 - 1
+    if (width == 0) || (height == 0) || (width > 0x7FFFFFFF) || (height > 0x7FFFFFFF) || (image.data.len() < width * height) {
+        return Err("Invalid image size for PNG".to_string());
+    }
+    let pixels = &image.data[..width * height];
+    let alpha = pixels.iter().any(|pixel| (pixel >> 24) != 255);
+    let (itype, bpp) = if alpha {(TYPE_RGBA8, 4)} else {(TYPE_RGB8, 3)};
+    let stride = width * bpp;
+    
+    let mut filtered: Vec<u8> = Vec::with_capacity((stride + 1) * height);
+    let mut prev = vec![0u8; stride];
+    let mut row = Vec::with_capacity(stride);
+    let mut candidate = Vec::with_capacity(stride + 1);
+    for y in 0..height {
+        row.clear();
+        for pixel in &pixels[y * width..(y + 1) * width] {
+            row.push(((pixel >> 16) & 255) as u8);
+            row.push(((pixel >> 8) & 255) as u8);
+            row.push((pixel & 255) as u8);
+            if alpha {
+                row.push((pixel >> 24) as u8);
+            }
+        }
+        match options.filter {
+            PngFilter::None => filter_row(&mut filtered, 0, &row, &prev, bpp),
+            PngFilter::Sub => filter_row(&mut filtered, 1, &row, &prev, bpp),
+            PngFilter::Up => filter_row(&mut filtered, 2, &row, &prev, bpp),
+            PngFilter::Average => filter_row(&mut filtered, 3, &row, &prev, bpp),
+            PngFilter::Paeth => filter_row(&mut filtered, 4, &row, &prev, bpp),
+            PngFilter::Adaptive => {
+                // minimum sum of absolute differences, the usual heuristic
+                let mut best = (u64::MAX, 0);
+                for filter in 0..5 {
+                    candidate.clear();
+                    filter_row(&mut candidate, filter, &row, &prev, bpp);
+                    let cost = candidate[1..].iter().map(|d| (*d as i8).unsigned_abs() as u64).sum::<u64>();
+                    if cost < best.0 {
+                        best = (cost, filter);
+                    }
+                }
+                filter_row(&mut filtered, best.1, &row, &prev, bpp);
+            },
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    
+    let mut dst: Vec<u8> = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&itype.to_be_bytes());
+    header.extend_from_slice(&[0, 0, 0]); // deflate, adaptive filtering, no interlace
+    chunk(&mut dst, b"IHDR", &header);
+    chunk(&mut dst, b"IDAT", &compress_to_vec_zlib(&filtered, options.level));
+    chunk(&mut dst, b"IEND", &[]);
+    Ok(dst)
+}
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a 3x2 16-bit image with the given samples per pixel, the second row filtered
+    // with Up and Sub so the decoder has to get the stride and bpp right
+    fn png16(itype: u16, samples: usize, values: &[u16]) -> Vec<u8> {
+        let stride = 3 * samples * 2;
+        let raw: Vec<u8> = values.iter().flat_map(|v| v.to_be_bytes()).collect();
+        let mut filtered = vec![0];
+        filtered.extend_from_slice(&raw[..stride]);
+        filtered.push(2);
+        filtered.extend((0..stride).map(|i| raw[stride + i].wrapping_sub(raw[i])));
+        let mut dst: Vec<u8> = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+        let mut header = Vec::new();
+        header.extend_from_slice(&3u32.to_be_bytes());
+        header.extend_from_slice(&2u32.to_be_bytes());
+        header.extend_from_slice(&itype.to_be_bytes());
+        header.extend_from_slice(&[0, 0, 0]);
+        chunk(&mut dst, b"IHDR", &header);
+        chunk(&mut dst, b"IDAT", &compress_to_vec_zlib(&filtered, 6));
+        chunk(&mut dst, b"IEND", &[]);
+        dst
+    }
+
+    #[test]
+    fn decode_la16() {
+        let values = [
+            0x0011, 0xFFFF, 0x80AA, 0xFF00, 0xFF77, 0x0000,
+            0x4012, 0xFF34, 0xC056, 0x8078, 0x209A, 0x40BC,
+        ];
+        let image = decode(&png16(TYPE_LA16, 2, &values)).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        let expected: Vec<u32> = values.chunks(2).map(|la| {
+            let (l, a) = ((la[0] >> 8) as u32, (la[1] >> 8) as u32);
+            (a << 24) | (l << 16) | (l << 8) | l
+        }).collect();
+        assert_eq!(image.data, expected);
+    }
+
+    #[test]
+    fn decode_rgba16() {
+        let values = [
+            0xFF01, 0x0002, 0x0003, 0xFF04, 0x0005, 0xFF06, 0x0007, 0x8008, 0x0009, 0x000A, 0xFF0B, 0xFF0C,
+            0x40FF, 0x80EE, 0xC0DD, 0xFFCC, 0x20BB, 0x40AA, 0x6099, 0x8088, 0xFF77, 0xFF66, 0xFF55, 0x0044,
+        ];
+        let image = decode(&png16(TYPE_RGBA16, 4, &values)).unwrap();
+        assert_eq!((image.width, image.height), (3, 2));
+        let expected: Vec<u32> = values.chunks(4).map(|rgba| {
+            let [r, g, b, a] = [0, 1, 2, 3].map(|i| (rgba[i] >> 8) as u32);
+            (a << 24) | (r << 16) | (g << 8) | b
+        }).collect();
+        assert_eq!(image.data, expected);
+    }
+}
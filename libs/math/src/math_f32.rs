@@ -365,6 +365,27 @@ impl Vec4 {
     }
     
     
+    // hue, saturation and lightness all in 0..1
+    pub fn from_hsla(hsl: Vec4) -> Vec4 {
+        let v = hsl.z + hsl.y * hsl.z.min(1.0 - hsl.z);
+        let s = if v <= 0.0 {0.0} else {2.0 * (1.0 - hsl.z / v)};
+        let mut rgb = Self::from_hsva(Vec4 {x: hsl.x, y: s, z: v, w: 1.0});
+        rgb.w = hsl.w;
+        rgb
+    }
+    
+    pub fn to_hsla(&self) -> Vec4 {
+        let hsv = self.to_hsva();
+        let l = hsv.z * (1.0 - hsv.y * 0.5);
+        let s = if l <= 0.0 || l >= 1.0 {0.0} else {(hsv.z - l) / l.min(1.0 - l)};
+        Vec4 {
+            x: hsv.x,
+            y: s,
+            z: l,
+            w: self.w
+        }
+    }
+    
     pub fn from_u32(val: u32) -> Vec4 {
         Vec4 {
             x: ((val >> 24) & 0xff) as f32 / 255.0,
@@ -1,16 +1,117 @@
 use proc_macro::TokenStream;
 use makepad_micro_proc_macro::*;
+use crate::serde_attrs::*;
+
+// how generated code reaches a field, through self for structs or the binding of a match arm
+fn field_ref(in_self: bool, field: &SerdeField) -> String {
+    if in_self {
+        format!("& self . {}", field.name)
+    }
+    else {
+        field.name.clone()
+    }
+}
+
+// every field is followed by a comma, st_post takes off the last one
+fn ser_json_fields(tb: &mut TokenBuilder, fields: &[SerdeField], in_self: bool) {
+    for field in fields {
+        let field_ref = field_ref(in_self, field);
+        if field.skip {
+            continue
+        }
+        if field.flatten {
+            tb.add("s . flatten ( d ,").add(&field_ref).add(") ;");
+        }
+        else if field.is_option() {
+            tb.add("if let Some ( t ) = ").add(&field_ref).add("{");
+            tb.add("s . field ( d + 1 ,").string(&field.json_key()).add(") ;");
+            tb.add("t . ser_json ( d + 1 , s ) ; s . conl ( ) ; } ;");
+        }
+        else {
+            tb.add("s . field ( d + 1 ,").string(&field.json_key()).add(" ) ;");
+            tb.add("(").add(&field_ref).add(") . ser_json ( d + 1 , s ) ; s . conl ( ) ;");
+        }
+    }
+}
+
+// a block that reads an object into the fields and evaluates to ctor { fields }
+fn de_json_fields(tb: &mut TokenBuilder, fields: &[SerdeField], deny_unknown_fields: bool, ctor: &str) {
+    let has_flatten = fields.iter().any( | field | field.flatten);
+    tb.add("{ s . curly_open ( i ) ? ;");
+    for field in fields.iter().filter( | field | !field.skip && !field.flatten) {
+        tb.add("let mut").ident(&field.local()).add("= None ;");
+    }
+    if has_flatten {
+        tb.add("let mut flatten_fields = String :: new ( ) ;");
+    }
+    tb.add("while let Some ( _ ) = s . next_str ( ) {");
+    tb.add("match s . strbuf . as_ref ( ) {");
+    for field in fields.iter().filter( | field | !field.skip && !field.flatten) {
+        tb.string(&field.json_key()).add("=> { s . next_colon ( i ) ? ;");
+        tb.ident(&field.local()).add("= Some ( DeJson :: de_json ( s , i ) ? ) ; } ,");
+    }
+    if has_flatten {
+        tb.add("_ => s . capture_field ( i , & mut flatten_fields ) ?");
+    }
+    else if deny_unknown_fields {
+        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+    }
+    else {
+        tb.add("_ => { s . next_colon ( i ) ? ; s . skip_value ( i ) ? ; }");
+    }
+    tb.add("} ; s . eat_comma_curly ( i ) ? ;");
+    tb.add("} ; s . curly_close ( i ) ? ;");
+
+    tb.add(ctor).add("{");
+    for field in fields {
+        tb.ident(&field.name).add(":");
+        if field.skip {
+            tb.stream(Some(field.default_value())).add(",");
+        }
+        else if field.flatten {
+            tb.add("s . de_fields ( & flatten_fields , DeJson :: de_json ) ? ,");
+        }
+        else {
+            tb.add("if let Some ( t ) =").ident(&field.local()).add("{ t } else {");
+            if field.default.is_some() {
+                tb.stream(Some(field.default_value()));
+            }
+            else if field.is_option() {
+                tb.add("None");
+            }
+            else {
+                tb.add("return std :: result :: Result :: Err ( s . err_nf (").string(&field.json_key()).add(") )");
+            }
+            tb.add("} ,");
+        }
+    }
+    tb.add("} }");
+}
+
+// a block that reads a json array into a tuple variant
+fn de_json_tuple(tb: &mut TokenBuilder, len: usize, ctor: &str) {
+    tb.add("{ s . block_open ( i ) ? ;");
+    tb.add("let r =").add(ctor).add("(");
+    for _ in 0..len {
+        tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
+    }
+    tb.add(") ;");
+    tb.add("s . block_close ( i ) ? ; r }");
+}
 
 pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_attributes();
+
+    let container = match ContainerAttrs::parse(&parser.eat_attributes()) {
+        Ok(container) => container,
+        Err(err) => return err
+    };
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
-            
+
             let generic = parser.eat_generic();
             let types = parser.eat_all_types();
             let where_clause = parser.eat_where_clause(Some("SerJson"));
@@ -18,7 +119,7 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
             tb.add("impl").stream(generic.clone());
             tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut SerJsonState ) {");
-            
+
             if let Some(types) = types{
                 tb.add("s . out . push (").chr('[').add(") ;");
                 for i in 0..types.len(){
@@ -30,27 +131,13 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
                 tb.add("s . out . push (").chr(']').add(") ;");
             }
             else if let Some(fields) = parser.eat_all_struct_fields(){
-                let fields_len = fields.len();
+                let fields = match serde_fields(fields) {
+                    Ok(fields) => fields,
+                    Err(err) => return err
+                };
                 tb.add("s . st_pre ( ) ;");
                 // named struct
-                for (i, field) in fields.into_iter().enumerate() {
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) = ").add("& self .").ident(&field.name).add("{");
-                        tb.add("s . field ( d + 1 ,").string(&field.name).add(") ;");
-                        tb.add("t . ser_json ( d + 1 , s ) ;");
-                        if i != fields_len - 1 {
-                            tb.add("s . conl ( ) ;");
-                        }
-                        tb.add("} ;");
-                    }
-                    else{
-                        tb.add("s . field ( d + 1 ,").string(&field.name).add(" ) ;");
-                        tb.add("self .").ident(&field.name).add(". ser_json ( d + 1 , s ) ;");
-                        if i != fields_len - 1 {
-                            tb.add("s . conl ( ) ;");
-                        }
-                    }
-                }
+                ser_json_fields(&mut tb, &fields, true);
                 tb.add("s . st_post ( d ) ;");
             }
             else{
@@ -68,74 +155,110 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
             tb.add("impl").stream(generic.clone());
             tb.add("SerJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn ser_json ( & self , d : usize , s : & mut SerJsonState ) {");
-            tb.add("s . out . push (").chr('{').add(") ;");
             tb.add("match self {");
-            
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
 
             while !parser.eat_eot(){
                 // parse ident
-                parser.eat_attributes();
+                let attrs = parser.eat_attributes();
                 if let Some(variant) = parser.eat_any_ident(){
+                    let label = match variant_name(&variant, &attrs) {
+                        Ok(label) => label,
+                        Err(err) => return err
+                    };
                     if let Some(types) = parser.eat_all_types(){
-                        
+
                         tb.add("Self ::").ident(&variant).add("(");
                         for i in 0..types.len(){
                             tb.ident(&format!("n{}", i)).add(",");
                         }
                         tb.add(") => {");
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . out . push (").chr('[').add(") ;");
-                        
-                        for i in 0..types.len(){
-                            tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
-                            if i != types.len() - 1{
-                                tb.add("s . out . push (").chr(',').add(") ;");
+                        match &container.tagging {
+                            Tagging::Internal(tag) => {
+                                if types.len() != 1 {
+                                    return error(&format!("variant {} of an internally tagged enum can only hold one struct", variant))
+                                }
+                                tb.add("s . st_pre ( ) ;");
+                                tb.add("s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&label).add(") ; s . conl ( ) ;");
+                                tb.add("s . flatten ( d , n0 ) ;");
+                                tb.add("s . st_post ( d ) ;");
+                            }
+                            Tagging::Untagged if types.len() == 1 => {
+                                tb.add("n0 . ser_json ( d , s ) ;");
+                            }
+                            tagging => {
+                                if let Tagging::External = tagging {
+                                    tb.add("s . out . push (").chr('{').add(") ;");
+                                    tb.add("s . label (").string(&label).add(") ;");
+                                    tb.add("s . out . push (").chr(':').add(") ;");
+                                }
+                                tb.add("s . out . push (").chr('[').add(") ;");
+                                for i in 0..types.len(){
+                                    tb.ident(&format!("n{}", i)).add(". ser_json ( d , s ) ;");
+                                    if i != types.len() - 1{
+                                        tb.add("s . out . push (").chr(',').add(") ;");
+                                    }
+                                }
+                                tb.add("s . out . push (").chr(']').add(") ;");
+                                if let Tagging::External = tagging {
+                                    tb.add("s . out . push (").chr('}').add(") ;");
+                                }
                             }
                         }
-                        tb.add("s . out . push (").chr(']').add(") ;");
                         tb.add("}");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
+                        let fields = match serde_fields(fields) {
+                            Ok(fields) => fields,
+                            Err(err) => return err
+                        };
                         tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields.iter(){
+                        for field in fields.iter().filter( | field | !field.skip){
                             tb.ident(&field.name).add(",");
                         }
-                        tb.add("} => {");
-                        
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push (").chr(':').add(") ;");
-                        tb.add("s . st_pre ( ) ;");
+                        tb.add(".. } => {");
 
-                        let fields_len = fields.len();
-                        for (i, field) in fields.into_iter().enumerate() {
-                            let field_strip = if let Some(v) = field.name.strip_prefix("_"){v}else{&field.name};
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) = ").ident(&field.name).add("{");
-                                tb.add("s . field ( d + 1 ,").string(&field_strip).add(") ;");
-                                tb.add("t . ser_json ( d + 1 , s ) ;");
-                                if i != fields_len - 1 {
-                                    tb.add("s . conl ( ) ;");
-                                }
-                                tb.add("} ;");
-                            }
-                            else{
-                                tb.add("s . field ( d + 1 ,").string(&field_strip).add(" ) ;");
-                                tb.ident(&field.name).add(". ser_json ( d + 1 , s ) ;");
-                                if i != fields_len - 1 {
-                                    tb.add("s . conl ( ) ;");
-                                }
-                            }
+                        if let Tagging::External = container.tagging {
+                            tb.add("s . out . push (").chr('{').add(") ;");
+                            tb.add("s . label (").string(&label).add(") ;");
+                            tb.add("s . out . push (").chr(':').add(") ;");
                         }
-                        tb.add("s . st_post ( d ) ; }");
+                        tb.add("s . st_pre ( ) ;");
+                        if let Tagging::Internal(tag) = &container.tagging {
+                            tb.add("s . field ( d + 1 ,").string(tag).add(") ;");
+                            tb.add("s . label (").string(&label).add(") ; s . conl ( ) ;");
+                        }
+                        ser_json_fields(&mut tb, &fields, false);
+                        tb.add("s . st_post ( d ) ;");
+                        if let Tagging::External = container.tagging {
+                            tb.add("s . out . push (").chr('}').add(") ;");
+                        }
+                        tb.add("}");
                     }
                     else if parser.is_punct_alone(',') || parser.is_eot(){ // bare variant
                         tb.add("Self ::").ident(&variant).add("=> {");
-                        tb.add("s . label (").string(&variant).add(") ;");
-                        tb.add("s . out . push_str (").string(":[]").add(") ; }");
+                        match &container.tagging {
+                            Tagging::External => {
+                                tb.add("s . out . push (").chr('{').add(") ;");
+                                tb.add("s . label (").string(&label).add(") ;");
+                                tb.add("s . out . push_str (").string(":[]").add(") ;");
+                                tb.add("s . out . push (").chr('}').add(") ;");
+                            }
+                            Tagging::Internal(tag) => {
+                                tb.add("s . st_pre ( ) ;");
+                                tb.add("s . field ( d + 1 ,").string(tag).add(") ;");
+                                tb.add("s . label (").string(&label).add(") ;");
+                                tb.add("s . st_post ( d ) ;");
+                            }
+                            Tagging::Untagged => {
+                                tb.add("s . out . push_str (").string("null").add(") ;");
+                            }
+                        }
+                        tb.add("}");
                     }
                     else{
                         return parser.unexpected();
@@ -147,7 +270,6 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
                 }
             }
             tb.add("}");
-            tb.add("s . out . push (").chr('}').add(") ;");
             tb.add("} } ;");
             return tb.end();
         }
@@ -158,8 +280,11 @@ pub fn derive_ser_json_impl(input: TokenStream) -> TokenStream {
 pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
     let mut parser = TokenParser::new(input);
     let mut tb = TokenBuilder::new();
-    
-    parser.eat_attributes();
+
+    let container = match ContainerAttrs::parse(&parser.eat_attributes()) {
+        Ok(container) => container,
+        Err(err) => return err
+    };
     parser.eat_ident("pub");
     if parser.eat_ident("struct"){
         if let Some(name) = parser.eat_any_ident(){
@@ -173,56 +298,28 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
             tb.add("-> std :: result :: Result < Self ,  DeJsonErr > { ");
 
             if let Some(types) = types{
-                tb.add("s . block_open ( i ) ? ;");
-                tb.add("let r = Self");
-                tb.add("(");
-                for _ in 0..types.len(){
-                     tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
-                }
-                tb.add(") ;");
-                tb.add("s . block_close ( i ) ? ;");
-                tb.add("std :: result :: Result :: Ok ( r )");
+                tb.add("std :: result :: Result :: Ok (");
+                de_json_tuple(&mut tb, types.len(), "Self");
+                tb.add(")");
             }
-            else if let Some(fields) = parser.eat_all_struct_fields(){ 
-                tb.add("s . curly_open ( i ) ? ;");
-                for field in &fields{
-                    tb.add("let mut").ident(&format!("_{}",field.name)).add("= None ;");
-                }
-                tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                tb.add("match s . strbuf . as_ref ( ) {");
-                for field in &fields{
-                    let field_strip = if let Some(v) = field.name.strip_prefix("_"){v}else{&field.name};
-                    tb.string(&field_strip).add("=> { s . next_colon ( i ) ? ;");
-                    tb.ident(&format!("_{}",field.name)).add("= Some (DeJson :: de_json ( s , i ) ? ) ; } ,");
-                }
-                tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                tb.add("} ; s . eat_comma_curly ( i ) ? ;");
-                tb.add("} ; s . curly_close ( i ) ? ;");
-                
-                tb.add("std :: result :: Result :: Ok ( Self {");
-                for field in fields{
-                    tb.ident(&field.name).add(":");
-                    if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                        tb.add("{ t } else { None } ,");
-                    }
-                    else{
-                        tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                        tb.add("{ t } else { return Err ( s . err_nf (");
-                        tb.string(&field.name).add(") ) } ,");
-                    }
-                }
-                tb.add("} )");
+            else if let Some(fields) = parser.eat_all_struct_fields(){
+                let fields = match serde_fields(fields) {
+                    Ok(fields) => fields,
+                    Err(err) => return err
+                };
+                tb.add("std :: result :: Result :: Ok (");
+                de_json_fields(&mut tb, &fields, container.deny_unknown_fields, "Self");
+                tb.add(")");
             }
             else{
                 return parser.unexpected()
             }
-            tb.add("} } ;"); 
+            tb.add("} } ;");
             return tb.end();
         }
     }
     else if parser.eat_ident("enum"){
-        
+
         if let Some(name) = parser.eat_any_ident(){
             let generic = parser.eat_generic();
             let where_clause = parser.eat_where_clause(Some("DeJson"));
@@ -231,75 +328,119 @@ pub fn derive_de_json_impl(input: TokenStream) -> TokenStream {
             tb.add("DeJson for").ident(&name).stream(generic).stream(where_clause);
             tb.add("{ fn de_json ( s : & mut  DeJsonState , i : & mut std :: str :: Chars )");
             tb.add("-> std :: result :: Result < Self , DeJsonErr > { ");
-            tb.add("s . curly_open ( i ) ? ;");
-            tb.add("let _ = s . string ( i ) ? ;");
-            tb.add("s . colon ( i ) ? ;");
-            tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
-            
+            match &container.tagging {
+                Tagging::External => {
+                    tb.add("s . curly_open ( i ) ? ;");
+                    tb.add("let _ = s . string ( i ) ? ;");
+                    tb.add("s . colon ( i ) ? ;");
+                    tb.add("let r = std :: result :: Result :: Ok ( match s . strbuf . as_ref ( ) {");
+                }
+                Tagging::Internal(tag) => {
+                    tb.add("let ( tag , fields ) = s . tagged_fields ( i ,").string(tag).add(") ? ;");
+                    tb.add("std :: result :: Result :: Ok ( match tag . as_ref ( ) {");
+                }
+                Tagging::Untagged => {
+                    tb.add("let value = s . capture_value ( i ) ? ;");
+                }
+            }
+
             if !parser.open_brace(){
                 return parser.unexpected()
             }
             while !parser.eat_eot(){
                 // parse ident
-                parser.eat_attributes();
+                let attrs = parser.eat_attributes();
                 if let Some(variant) = parser.eat_any_ident(){
-                    tb.string(&variant).add("=> {");
+                    let label = match variant_name(&variant, &attrs) {
+                        Ok(label) => label,
+                        Err(err) => return err
+                    };
+                    let ctor = format!("Self :: {}", variant);
+                    match &container.tagging {
+                        Tagging::External | Tagging::Internal(_) => {
+                            tb.string(&label).add("=>");
+                        }
+                        Tagging::Untagged => {
+                            tb.add("if let Ok ( r ) = s . de_captured ( & value , | s , i | std :: result :: Result :: Ok (");
+                        }
+                    }
                     if let Some(types) = parser.eat_all_types(){
-                        
-                        tb.add("s . block_open ( i ) ? ;");
-                        tb.add("let r = Self ::").ident(&variant).add("(");
-                        for _ in 0..types.len(){
-                            tb.add("{ let r = DeJson :: de_json ( s , i ) ? ; s . eat_comma_block ( i ) ? ; r } ,");
+                        match &container.tagging {
+                            Tagging::Internal(_) => {
+                                if types.len() != 1 {
+                                    return error(&format!("variant {} of an internally tagged enum can only hold one struct", variant))
+                                }
+                                tb.add(&ctor).add("( s . de_fields ( & fields , DeJson :: de_json ) ? )");
+                            }
+                            Tagging::Untagged if types.len() == 1 => {
+                                tb.add(&ctor).add("( DeJson :: de_json ( s , i ) ? )");
+                            }
+                            _ => de_json_tuple(&mut tb, types.len(), &ctor)
                         }
-                        tb.add(") ;");
-                        tb.add("s . block_close ( i ) ? ; r");
                     }
                     else if let Some(fields) = parser.eat_all_struct_fields(){ // named variant
-                        tb.add("s . curly_open ( i ) ? ;");
-                        for field in &fields{
-                            tb.add("let mut").ident(&format!("_{}",field.name)).add("= None ;");
+                        let fields = match serde_fields(fields) {
+                            Ok(fields) => fields,
+                            Err(err) => return err
+                        };
+                        if let Tagging::Internal(_) = container.tagging {
+                            tb.add("s . de_fields ( & fields , | s , i | std :: result :: Result :: Ok (");
+                            de_json_fields(&mut tb, &fields, container.deny_unknown_fields, &ctor);
+                            tb.add(") ) ?");
                         }
-                        tb.add("while let Some ( _ ) = s . next_str ( ) {");
-                        tb.add("match s . strbuf . as_ref ( ) {");
-                        for field in &fields{
-                            tb.string(&field.name).add("=> { s . next_colon ( i ) ? ;");
-                            tb.ident(&format!("_{}",field.name)).add("= Some (DeJson :: de_json ( s , i ) ? ) ; } ,");
+                        else {
+                            de_json_fields(&mut tb, &fields, container.deny_unknown_fields, &ctor);
                         }
-                        tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-                        tb.add("} s . eat_comma_curly ( i ) ? ;");
-                        tb.add("} s . curly_close ( i ) ? ;");
-                        
-                        tb.add("Self ::").ident(&variant).add("{");
-                        for field in fields{
-                            tb.ident(&field.name).add(":");
-                            if field.ty.into_iter().next().unwrap().to_string() == "Option"{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                                tb.add("{ t } else { None } ,");
+                    }
+                    else if parser.is_punct_alone(',') || parser.is_eot(){ // bare variant
+                        match &container.tagging {
+                            Tagging::External => {
+                                tb.add("{ s . block_open ( i ) ? ; s . block_close ( i ) ? ;").add(&ctor).add("}");
+                            }
+                            Tagging::Internal(_) if container.deny_unknown_fields => {
+                                tb.add("{ if ! fields . is_empty ( ) { return std :: result :: Result :: Err ( s . err_exp ( & fields ) ) }");
+                                tb.add(&ctor).add("}");
                             }
-                            else{
-                                tb.add("if let Some ( t ) =").ident(&format!("_{}",field.name));
-                                tb.add("{ t } else { return Err ( s . err_nf (");
-                                tb.string(&field.name).add(") ) } ,");
+                            Tagging::Internal(_) => {
+                                tb.add(&ctor);
+                            }
+                            Tagging::Untagged => {
+                                tb.add("{ if s . tok != DeJsonTok :: Null { return std :: result :: Result :: Err ( s . err_token (").string("null").add(") ) }");
+                                tb.add("s . next_tok ( i ) ? ;").add(&ctor).add("}");
                             }
                         }
-                        tb.add("}");
-                    }
-                    else if parser.is_punct_alone(',') || parser.is_eot(){ // bare variant
-                        tb.add("s . block_open ( i ) ? ; s . block_close ( i ) ? ; Self ::").ident(&variant);
                     }
                     else{
                         return parser.unexpected();
                     }
-                    
-                    tb.add("}");
+
+                    match &container.tagging {
+                        Tagging::External | Tagging::Internal(_) => {
+                            tb.add(",");
+                        }
+                        Tagging::Untagged => {
+                            tb.add(") ) { return std :: result :: Result :: Ok ( r ) }");
+                        }
+                    }
                     parser.eat_punct_alone(',');
                 }
                 else{
                     return parser.unexpected()
                 }
             }
-            tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
-            tb.add("} ) ; s . curly_close ( i ) ? ; r } }");
+            match &container.tagging {
+                Tagging::External => {
+                    tb.add("_ => return std :: result :: Result :: Err ( s . err_exp ( & s . strbuf ) )");
+                    tb.add("} ) ; s . curly_close ( i ) ? ; r } }");
+                }
+                Tagging::Internal(_) => {
+                    tb.add("_ => return std :: result :: Result :: Err ( s . err_enum ( & tag ) )");
+                    tb.add("} ) } }");
+                }
+                Tagging::Untagged => {
+                    tb.add("std :: result :: Result :: Err ( s . err_parse (").string(&name).add(") ) } }");
+                }
+            }
             return tb.end();
         }
     }
@@ -1,6 +1,8 @@
 extern crate proc_macro;
 use proc_macro::{TokenStream};
 
+mod serde_attrs;
+
 mod derive_bin;
 use crate::derive_bin::*;
 
@@ -10,33 +12,33 @@ use crate::derive_ron::*;
 mod derive_json;
 use crate::derive_json::*;
 
-#[proc_macro_derive(SerBin)]
+#[proc_macro_derive(SerBin, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_ser_bin(input: TokenStream) -> TokenStream {
     derive_ser_bin_impl(input)
 }
 
-#[proc_macro_derive(DeBin)]
+#[proc_macro_derive(DeBin, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_de_bin(input: TokenStream) -> TokenStream {
     derive_de_bin_impl(input)
 }
 
-#[proc_macro_derive(SerJson)]
+#[proc_macro_derive(SerJson, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_ser_json(input: TokenStream) -> TokenStream {
     derive_ser_json_impl(input)
 }
 
-#[proc_macro_derive(DeJson)]
+#[proc_macro_derive(DeJson, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_de_json(input: TokenStream) -> TokenStream {
     derive_de_json_impl(input)
 }
 
 
-#[proc_macro_derive(SerRon)]
+#[proc_macro_derive(SerRon, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_ser_ron(input: TokenStream) -> TokenStream {
     derive_ser_ron_impl(input)
 }
 
-#[proc_macro_derive(DeRon)]
+#[proc_macro_derive(DeRon, attributes(rename, default, skip, flatten, tag, untagged, deny_unknown_fields))]
 pub fn derive_de_ron(input: TokenStream) -> TokenStream {
     derive_de_ron_impl(input)
 }
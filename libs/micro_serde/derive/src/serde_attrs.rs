@@ -0,0 +1,132 @@
+use proc_macro::{TokenStream, TokenTree};
+use makepad_micro_proc_macro::*;
+
+// the attributes the derives understand:
+// on a struct or enum: #[deny_unknown_fields], and on an enum #[tag("type")] or #[untagged]
+// on a field: #[rename("key")], #[default], #[default(expr)], #[skip], #[flatten]
+// on a variant: #[rename("name")]
+
+// how the variants of an enum are written
+pub enum Tagging {
+    // {"Variant":content}, the default
+    External,
+    // {"type":"Variant", ..fields}
+    Internal(String),
+    // just the content, reading tries the variants in order
+    Untagged,
+}
+
+pub struct ContainerAttrs {
+    pub tagging: Tagging,
+    pub deny_unknown_fields: bool,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self, TokenStream> {
+        let mut container = ContainerAttrs {
+            tagging: Tagging::External,
+            deny_unknown_fields: false,
+        };
+        for attr in attrs {
+            match attr.name.as_ref() {
+                "deny_unknown_fields" => container.deny_unknown_fields = true,
+                "tag" => container.tagging = Tagging::Internal(attr_string(attr) ?),
+                "untagged" => container.tagging = Tagging::Untagged,
+                _ => ()
+            }
+        }
+        Ok(container)
+    }
+}
+
+pub struct SerdeField {
+    pub name: String,
+    pub ty: TokenStream,
+    pub rename: Option<String>,
+    // #[default] is an empty expression, for Default::default()
+    pub default: Option<TokenStream>,
+    pub skip: bool,
+    pub flatten: bool,
+}
+
+impl SerdeField {
+    pub fn is_option(&self) -> bool {
+        self.ty.clone().into_iter().next().map_or(false, | tt | tt.to_string() == "Option")
+    }
+
+    // json keys leave off a leading _, so fields like _type can be read
+    pub fn json_key(&self) -> String {
+        if let Some(rename) = &self.rename {
+            return rename.clone()
+        }
+        self.name.strip_prefix('_').unwrap_or(&self.name).to_string()
+    }
+
+    pub fn ron_key(&self) -> String {
+        self.rename.clone().unwrap_or_else( || self.name.clone())
+    }
+
+    // the variable that holds the field while it is being read
+    pub fn local(&self) -> String {
+        format!("_{}", self.name)
+    }
+
+    // the expression for a missing or skipped field
+    pub fn default_value(&self) -> TokenStream {
+        if let Some(default) = self.default.as_ref().filter( | default | !default.is_empty()) {
+            return default.clone()
+        }
+        let mut tb = TokenBuilder::new();
+        tb.add("std :: default :: Default :: default ( )");
+        tb.end()
+    }
+}
+
+pub fn serde_fields(fields: Vec<StructField>) -> Result<Vec<SerdeField>, TokenStream> {
+    let mut out = Vec::new();
+    for field in fields {
+        let mut serde_field = SerdeField {
+            name: field.name,
+            ty: field.ty,
+            rename: None,
+            default: None,
+            skip: false,
+            flatten: false,
+        };
+        for attr in &field.attrs {
+            match attr.name.as_ref() {
+                "rename" => serde_field.rename = Some(attr_string(attr) ?),
+                "default" => serde_field.default = Some(attr.args.clone().unwrap_or_default()),
+                "skip" => serde_field.skip = true,
+                "flatten" => serde_field.flatten = true,
+                _ => ()
+            }
+        }
+        if serde_field.flatten && serde_field.rename.is_some() {
+            return Err(error(&format!("field {} cannot be renamed and flattened", serde_field.name)))
+        }
+        out.push(serde_field);
+    }
+    Ok(out)
+}
+
+pub fn variant_name(variant: &str, attrs: &[Attribute]) -> Result<String, TokenStream> {
+    for attr in attrs {
+        if attr.name == "rename" {
+            return attr_string(attr)
+        }
+    }
+    Ok(variant.to_string())
+}
+
+// takes both #[rename("key")] and #[rename = "key"]
+fn attr_string(attr: &Attribute) -> Result<String, TokenStream> {
+    let mut iter = attr.args.clone().unwrap_or_default().into_iter();
+    if let (Some(TokenTree::Literal(lit)), None) = (iter.next(), iter.next()) {
+        let lit = lit.to_string();
+        if let Some(value) = lit.strip_prefix('"').and_then( | lit | lit.strip_suffix('"')) {
+            return Ok(value.to_string())
+        }
+    }
+    Err(error(&format!("#[{}] needs a string, like #[{}(\"name\")]", attr.name, attr.name)))
+}
@@ -0,0 +1,434 @@
+use crate::serde_json::DeJsonErr;
+use crate::json_value::JsonValue;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonEvent {
+    ObjectStart,
+    ObjectEnd,
+    ArrayStart,
+    ArrayEnd,
+    Key(String),
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    // at the top level or after a colon
+    Value,
+    // after a [ or a comma in an array
+    ValueOrEnd,
+    // after a { or a comma in an object
+    KeyOrEnd,
+    Colon,
+    // after a value in an array or object
+    CommaOrEnd,
+}
+
+// a pull reader for json that arrives in pieces, like a streamed http response.
+// input is pushed as it comes in and next gives the events that can be read so far.
+// Ok(None) means more input is needed, or after finish that the input is done.
+// only the unread part of the input is kept, and a document can hold several values
+// one after the other, like newline delimited json
+pub struct JsonReader {
+    buf: String,
+    pos: usize,
+    // the start of a utf-8 character that was split between chunks
+    partial: Vec<u8>,
+    // pushed input was not utf-8, next returns the error once it has read up to it
+    invalid_utf8: bool,
+    finished: bool,
+    line: usize,
+    col: usize,
+    stack: Vec<Container>,
+    expect: Expect,
+    peeked: Option<JsonEvent>,
+}
+
+impl Default for JsonReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonReader {
+    pub fn new() -> Self {
+        Self {
+            buf: String::new(),
+            pos: 0,
+            partial: Vec::new(),
+            invalid_utf8: false,
+            finished: false,
+            line: 0,
+            col: 0,
+            stack: Vec::new(),
+            expect: Expect::Value,
+            peeked: None,
+        }
+    }
+
+    pub fn push(&mut self, data: &str) {
+        if !self.partial.is_empty() {
+            // a str cannot finish a split character, the error comes from next
+            let _ = self.push_bytes(data.as_bytes());
+            return
+        }
+        self.compact();
+        self.buf.push_str(data);
+    }
+
+    pub fn push_bytes(&mut self, data: &[u8]) -> Result<(), DeJsonErr> {
+        self.partial.extend_from_slice(data);
+        let valid = match std::str::from_utf8(&self.partial) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => {
+                self.invalid_utf8 = true;
+                return Err(self.err("Invalid utf-8"))
+            }
+        };
+        self.compact();
+        self.buf.push_str(std::str::from_utf8(&self.partial[..valid]).unwrap());
+        self.partial.drain(..valid);
+        Ok(())
+    }
+
+    // no more input will come, so values at the very end can be completed
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    // how many objects and arrays the last event from next is inside of, a peeked event does not count
+    pub fn depth(&self) -> usize {
+        match self.peeked {
+            Some(JsonEvent::ObjectStart | JsonEvent::ArrayStart) => self.stack.len() - 1,
+            Some(JsonEvent::ObjectEnd | JsonEvent::ArrayEnd) => self.stack.len() + 1,
+            _ => self.stack.len()
+        }
+    }
+
+    pub fn next(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        if let Some(event) = self.peeked.take() {
+            return Ok(Some(event))
+        }
+        self.read_event()
+    }
+
+    pub fn peek(&mut self) -> Result<Option<&JsonEvent>, DeJsonErr> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_event() ?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+
+    // reads the next whole value, like one element of a long array. if the value is not
+    // complete yet nothing is consumed and Ok(None) is returned. the end of an array or
+    // object, or a key, is an error and is left to be read with next
+    pub fn next_value(&mut self) -> Result<Option<JsonValue>, DeJsonErr> {
+        let (pos, line, col, expect, depth) = (self.pos, self.line, self.col, self.expect, self.stack.len());
+        let peeked = self.peeked.clone();
+        let value = match self.next() ? {
+            Some(event @ (JsonEvent::ObjectEnd | JsonEvent::ArrayEnd | JsonEvent::Key(_))) => {
+                let err = self.err(&format!("Expected a value, got {:?}", event));
+                self.peeked = Some(event);
+                return Err(err)
+            }
+            Some(event) => self.read_tree(event) ?,
+            None => None
+        };
+        if value.is_none() {
+            self.pos = pos;
+            self.line = line;
+            self.col = col;
+            self.expect = expect;
+            self.stack.truncate(depth);
+            self.peeked = peeked;
+        }
+        Ok(value)
+    }
+
+    fn read_tree(&mut self, event: JsonEvent) -> Result<Option<JsonValue>, DeJsonErr> {
+        let value = match event {
+            JsonEvent::ObjectStart => {
+                let mut fields = Vec::new();
+                loop {
+                    match self.next() ? {
+                        Some(JsonEvent::ObjectEnd) => break,
+                        Some(JsonEvent::Key(key)) => {
+                            let Some(event) = self.next() ? else {return Ok(None)};
+                            let Some(value) = self.read_tree(event) ? else {return Ok(None)};
+                            fields.push((key, value));
+                        }
+                        Some(event) => return Err(self.err(&format!("Expected a key, got {:?}", event))),
+                        None => return Ok(None)
+                    }
+                }
+                JsonValue::Object(fields)
+            }
+            JsonEvent::ArrayStart => {
+                let mut values = Vec::new();
+                loop {
+                    match self.next() ? {
+                        Some(JsonEvent::ArrayEnd) => break,
+                        Some(event) => match self.read_tree(event) ? {
+                            Some(value) => values.push(value),
+                            None => return Ok(None)
+                        },
+                        None => return Ok(None)
+                    }
+                }
+                JsonValue::Array(values)
+            }
+            JsonEvent::String(value) => JsonValue::String(value),
+            JsonEvent::Number(value) => JsonValue::Number(value),
+            JsonEvent::Bool(value) => JsonValue::Bool(value),
+            JsonEvent::Null => JsonValue::Null,
+            event => return Err(self.err(&format!("Expected a value, got {:?}", event)))
+        };
+        Ok(Some(value))
+    }
+
+    fn read_event(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        loop {
+            self.skip_whitespace();
+            let Some(c) = self.buf[self.pos..].chars().next() else {
+                return self.end_of_input()
+            };
+            match self.expect {
+                Expect::Colon => {
+                    if c != ':' {
+                        return Err(self.err(&format!("Expected : got {}", c)))
+                    }
+                    self.advance(1);
+                    self.expect = Expect::Value;
+                }
+                Expect::CommaOrEnd => match c {
+                    ',' => {
+                        self.advance(1);
+                        self.expect = if self.stack.last() == Some(&Container::Object) {
+                            Expect::KeyOrEnd
+                        }
+                        else {
+                            Expect::ValueOrEnd
+                        };
+                    }
+                    '}' | ']' => return self.close(c),
+                    _ => return Err(self.err(&format!("Expected , or end got {}", c)))
+                },
+                // a comma before the end is let through
+                Expect::KeyOrEnd => match c {
+                    '}' => return self.close(c),
+                    '"' => {
+                        let Some(key) = self.read_string() ? else {
+                            return self.end_of_input()
+                        };
+                        self.expect = Expect::Colon;
+                        return Ok(Some(JsonEvent::Key(key)))
+                    }
+                    _ => return Err(self.err(&format!("Expected a key got {}", c)))
+                },
+                Expect::ValueOrEnd if c == ']' => return self.close(c),
+                Expect::Value | Expect::ValueOrEnd => return self.read_value(c)
+            }
+        }
+    }
+
+    fn read_value(&mut self, c: char) -> Result<Option<JsonEvent>, DeJsonErr> {
+        let event = match c {
+            '{' | '[' => {
+                self.advance(1);
+                if c == '{' {
+                    self.stack.push(Container::Object);
+                    self.expect = Expect::KeyOrEnd;
+                    return Ok(Some(JsonEvent::ObjectStart))
+                }
+                self.stack.push(Container::Array);
+                self.expect = Expect::ValueOrEnd;
+                return Ok(Some(JsonEvent::ArrayStart))
+            }
+            '"' => self.read_string() ?.map(JsonEvent::String),
+            '-' | '0'..='9' => self.read_number() ?,
+            't' | 'f' | 'n' => self.read_literal() ?,
+            _ => return Err(self.err(&format!("Expected a value got {}", c)))
+        };
+        let Some(event) = event else {
+            return self.end_of_input()
+        };
+        self.value_done();
+        Ok(Some(event))
+    }
+
+    fn close(&mut self, c: char) -> Result<Option<JsonEvent>, DeJsonErr> {
+        let event = match (c, self.stack.last()) {
+            ('}', Some(Container::Object)) => JsonEvent::ObjectEnd,
+            (']', Some(Container::Array)) => JsonEvent::ArrayEnd,
+            _ => return Err(self.err(&format!("Unexpected {}", c)))
+        };
+        self.advance(1);
+        self.stack.pop();
+        self.value_done();
+        Ok(Some(event))
+    }
+
+    fn value_done(&mut self) {
+        self.expect = if self.stack.is_empty() {Expect::Value} else {Expect::CommaOrEnd};
+    }
+
+    // the invalid utf-8 comes after everything in buf, so it is reported where more input is needed
+    fn end_of_input(&self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        if self.invalid_utf8 {
+            return Err(self.err("Invalid utf-8"))
+        }
+        if !self.finished {
+            return Ok(None)
+        }
+        if !self.partial.is_empty() {
+            return Err(self.err("Input ends inside a utf-8 character"))
+        }
+        if !self.stack.is_empty() || self.expect != Expect::Value || self.pos != self.buf.len() {
+            return Err(self.err("Unexpected end of json"))
+        }
+        Ok(None)
+    }
+
+    fn read_string(&mut self) -> Result<Option<String>, DeJsonErr> {
+        let bytes = &self.buf.as_bytes()[self.pos + 1..];
+        let mut end = None;
+        let mut index = 0;
+        while index < bytes.len() {
+            match bytes[index] {
+                b'\\' => index += 1,
+                b'"' => {
+                    end = Some(index);
+                    break
+                }
+                _ => ()
+            }
+            index += 1;
+        }
+        let Some(end) = end else {return Ok(None)};
+        let value = self.unescape(&self.buf[self.pos + 1..self.pos + 1 + end]) ?;
+        self.advance(end + 2);
+        Ok(Some(value))
+    }
+
+    fn unescape(&self, raw: &str) -> Result<String, DeJsonErr> {
+        if !raw.contains('\\') {
+            return Ok(raw.to_string())
+        }
+        let mut out = String::with_capacity(raw.len());
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue
+            }
+            let c = match chars.next() {
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('b') => '\u{8}',
+                Some('f') => '\u{c}',
+                Some('0') => '\0',
+                Some(c @ ('"' | '\\' | '/')) => c,
+                Some('u') => {
+                    let mut code = hex4(&mut chars).ok_or_else( || self.err("Invalid unicode escape")) ?;
+                    // characters outside the BMP are escaped as a surrogate pair
+                    if (0xd800..0xdc00).contains(&code) {
+                        let low = match (chars.next(), chars.next()) {
+                            (Some('\\'), Some('u')) => hex4(&mut chars),
+                            _ => None
+                        };
+                        let Some(low @ 0xdc00..=0xdfff) = low else {
+                            return Err(self.err("Invalid surrogate pair"))
+                        };
+                        code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                    }
+                    char::from_u32(code).ok_or_else( || self.err("Invalid unicode escape")) ?
+                }
+                _ => return Err(self.err("Invalid escape in string"))
+            };
+            out.push(c);
+        }
+        Ok(out)
+    }
+
+    fn read_number(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        let rest = &self.buf[self.pos..];
+        let len = rest.find( | c: char | !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')).unwrap_or(rest.len());
+        // the number could go on in the next chunk
+        if len == rest.len() && !self.finished {
+            return Ok(None)
+        }
+        let text = &rest[..len];
+        let Ok(value) = text.parse::<f64>() else {
+            return Err(self.err(&format!("Cannot parse number {}", text)))
+        };
+        self.advance(len);
+        Ok(Some(JsonEvent::Number(value)))
+    }
+
+    fn read_literal(&mut self) -> Result<Option<JsonEvent>, DeJsonErr> {
+        let rest = &self.buf[self.pos..];
+        for (word, event) in [("true", JsonEvent::Bool(true)), ("false", JsonEvent::Bool(false)), ("null", JsonEvent::Null)] {
+            if let Some(after) = rest.strip_prefix(word) {
+                if after.starts_with( | c: char | c.is_alphanumeric()) {
+                    break
+                }
+                self.advance(word.len());
+                return Ok(Some(event))
+            }
+            if word.starts_with(rest) {
+                return Ok(None)
+            }
+        }
+        let word: String = rest.chars().take_while( | c | c.is_alphanumeric()).collect();
+        Err(self.err(&format!("Got {} needed true, false, null", word)))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let len = self.buf[self.pos..].find( | c: char | !matches!(c, ' ' | '\t' | '\n' | '\r')).unwrap_or(self.buf.len() - self.pos);
+        self.advance(len);
+    }
+
+    fn advance(&mut self, len: usize) {
+        for c in self.buf[self.pos..self.pos + len].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.col = 0;
+            }
+            else {
+                self.col += 1;
+            }
+        }
+        self.pos += len;
+    }
+
+    // drops the part of the input that has been read
+    fn compact(&mut self) {
+        if self.pos > 0 {
+            self.buf.drain(..self.pos);
+            self.pos = 0;
+        }
+    }
+
+    fn err(&self, msg: &str) -> DeJsonErr {
+        DeJsonErr {msg: msg.to_string(), line: self.line, col: self.col}
+    }
+}
+
+fn hex4(chars: &mut std::str::Chars) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.to_digit(16)?;
+    }
+    Some(code)
+}
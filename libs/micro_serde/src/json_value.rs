@@ -0,0 +1,240 @@
+use std::ops::Index;
+use std::str::Chars;
+use crate::serde_json::*;
+
+// a json document of unknown shape, for responses that are only partly known
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum JsonValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    // keys in the order of the document
+    Object(Vec<(String, JsonValue)>),
+}
+
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    pub fn object<const N: usize>(fields: [(&str, JsonValue); N]) -> Self {
+        JsonValue::Object(fields.into_iter().map( | (key, value) | (key.to_string(), value)).collect())
+    }
+
+    pub fn parse(data: &str) -> Result<Self, DeJsonErr> {
+        Self::deserialize_json(data)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find( | (k, _) | k == key).map( | (_, value) | value),
+            _ => None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(fields) => fields.iter_mut().find( | (k, _) | k == key).map( | (_, value) | value),
+            _ => None
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(values) => values.get(index),
+            _ => None
+        }
+    }
+
+    // replaces the value of a key, or adds the key at the end. does nothing if this is not an object
+    pub fn insert(&mut self, key: &str, value: JsonValue) {
+        if let Some(old) = self.get_mut(key) {
+            *old = value;
+        }
+        else if let JsonValue::Object(fields) = self {
+            fields.push((key.to_string(), value));
+        }
+    }
+
+    // follows object keys and array indices separated by dots, like choices.0.delta.content
+    pub fn path(&self, path: &str) -> Option<&JsonValue> {
+        path.split('.').try_fold(self, | value, key | match value {
+            JsonValue::Array(_) => value.get_index(key.parse().ok() ?),
+            _ => value.get(key)
+        })
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::Number(value) if *value >= 0.0 && value.fract() == 0.0 => Some(*value as u64),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(value) if value.fract() == 0.0 => Some(*value as i64),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(fields) => Some(fields),
+            _ => None
+        }
+    }
+}
+
+// missing keys and indices give null, so lookups can be chained like value["choices"][0]["text"]
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(values: Vec<JsonValue>) -> Self {
+        JsonValue::Array(values)
+    }
+}
+
+impl SerJson for JsonValue {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            JsonValue::Null => s.out.push_str("null"),
+            JsonValue::Bool(value) => value.ser_json(d, s),
+            // integers, like ids and positions, are written without a fraction
+            JsonValue::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+                s.out.push_str(&(*value as i64).to_string())
+            }
+            JsonValue::Number(value) if value.is_finite() => s.out.push_str(&value.to_string()),
+            // json has no infinity or nan
+            JsonValue::Number(_) => s.out.push_str("null"),
+            JsonValue::String(value) => value.ser_json(d, s),
+            JsonValue::Array(values) => values.ser_json(d, s),
+            JsonValue::Object(fields) => {
+                s.out.push('{');
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index != 0 {
+                        s.conl();
+                    }
+                    key.ser_json(d + 1, s);
+                    s.out.push(':');
+                    value.ser_json(d + 1, s);
+                }
+                s.out.push('}');
+            }
+        }
+    }
+}
+
+impl DeJson for JsonValue {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match s.tok {
+            DeJsonTok::Null => JsonValue::Null,
+            DeJsonTok::Bool(value) => JsonValue::Bool(value),
+            DeJsonTok::U64(_) | DeJsonTok::I64(_) | DeJsonTok::F64(_) => JsonValue::Number(s.as_f64() ?),
+            DeJsonTok::Str => JsonValue::String(s.as_string() ?),
+            DeJsonTok::BlockOpen => return Ok(JsonValue::Array(DeJson::de_json(s, i) ?)),
+            DeJsonTok::CurlyOpen => {
+                s.next_tok(i) ?;
+                let mut fields = Vec::new();
+                while s.tok != DeJsonTok::CurlyClose {
+                    let key = s.as_string() ?;
+                    s.next_colon(i) ?;
+                    fields.push((key, JsonValue::de_json(s, i) ?));
+                    s.eat_comma_curly(i) ?;
+                }
+                s.curly_close(i) ?;
+                return Ok(JsonValue::Object(fields))
+            }
+            _ => return Err(s.err_token("json value"))
+        };
+        s.next_tok(i) ?;
+        Ok(value)
+    }
+}
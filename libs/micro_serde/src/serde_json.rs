@@ -36,10 +36,25 @@ impl SerJsonState {
     }
     
     pub fn st_post(&mut self, d: usize) {
+        // every field is followed by a comma, the last one is not
+        if self.out.ends_with(',') {
+            self.out.pop();
+        }
         self.indent(d);
         self.out.push('}');
     }
     
+    // writes the fields of a struct into the object around it, for #[flatten]
+    pub fn flatten<T: SerJson + ?Sized>(&mut self, d: usize, value: &T) {
+        let mut inner = SerJsonState {out: String::new()};
+        value.ser_json(d, &mut inner);
+        if let Some(fields) = inner.out.strip_prefix('{').and_then( | out | out.strip_suffix('}')) {
+            if !fields.is_empty() {
+                self.out.push_str(fields);
+                self.conl();
+            }
+        }
+    }
 }
 
 pub trait SerJson {
@@ -268,6 +283,108 @@ impl DeJsonState {
         Err(self.err_token("}"))
     }
     
+    // writes the current token back as json
+    fn push_tok(&self, out: &mut String) -> Result<(), DeJsonErr> {
+        match &self.tok {
+            DeJsonTok::Str => push_json_str(&self.strbuf, out),
+            DeJsonTok::Char(c) => push_json_str(&c.to_string(), out),
+            DeJsonTok::U64(_) | DeJsonTok::I64(_) | DeJsonTok::F64(_) => out.push_str(&self.numbuf),
+            DeJsonTok::Bool(value) => out.push_str(if *value {"true"} else {"false"}),
+            DeJsonTok::BareIdent => out.push_str(&self.identbuf),
+            DeJsonTok::Null => out.push_str("null"),
+            DeJsonTok::Colon => out.push(':'),
+            DeJsonTok::CurlyOpen => out.push('{'),
+            DeJsonTok::CurlyClose => out.push('}'),
+            DeJsonTok::BlockOpen => out.push('['),
+            DeJsonTok::BlockClose => out.push(']'),
+            DeJsonTok::Comma => out.push(','),
+            DeJsonTok::Bof | DeJsonTok::Eof => return Err(self.err_token("json value"))
+        }
+        Ok(())
+    }
+    
+    fn capture_value_into(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeJsonErr> {
+        let mut depth = 0;
+        loop {
+            match self.tok {
+                DeJsonTok::CurlyOpen | DeJsonTok::BlockOpen => depth += 1,
+                DeJsonTok::CurlyClose | DeJsonTok::BlockClose if depth > 0 => depth -= 1,
+                DeJsonTok::CurlyClose | DeJsonTok::BlockClose | DeJsonTok::Colon | DeJsonTok::Comma if depth == 0 => {
+                    return Err(self.err_token("json value"))
+                }
+                _ => ()
+            }
+            self.push_tok(out) ?;
+            self.next_tok(i) ?;
+            if depth == 0 {
+                return Ok(())
+            }
+        }
+    }
+    
+    // reads the value at the current token, and returns it as json to be parsed later
+    pub fn capture_value(&mut self, i: &mut Chars) -> Result<String, DeJsonErr> {
+        let mut out = String::new();
+        self.capture_value_into(i, &mut out) ?;
+        Ok(out)
+    }
+    
+    pub fn skip_value(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
+        self.capture_value(i).map( | _ | ())
+    }
+    
+    // reads the key at the current token and its value, and adds them to fields as json
+    pub fn capture_field(&mut self, i: &mut Chars, fields: &mut String) -> Result<(), DeJsonErr> {
+        if !fields.is_empty() {
+            fields.push(',');
+        }
+        self.push_tok(fields) ?;
+        self.next_colon(i) ?;
+        fields.push(':');
+        self.capture_value_into(i, fields)
+    }
+    
+    // reads an object for an internally tagged enum, which can have the tag anywhere.
+    // returns the tag and the other fields
+    pub fn tagged_fields(&mut self, i: &mut Chars, tag: &str) -> Result<(String, String), DeJsonErr> {
+        let mut value = None;
+        let mut fields = String::new();
+        self.curly_open(i) ?;
+        while let Some(_) = self.next_str() {
+            if self.strbuf == tag {
+                self.next_colon(i) ?;
+                value = Some(self.as_string() ?);
+                self.next_tok(i) ?;
+            }
+            else {
+                self.capture_field(i, &mut fields) ?;
+            }
+            self.eat_comma_curly(i) ?;
+        }
+        self.curly_close(i) ?;
+        match value {
+            Some(value) => Ok((value, fields)),
+            None => Err(self.err_nf(tag))
+        }
+    }
+    
+    // parses captured json, errors point at the current position
+    pub fn de_captured<T>(&self, text: &str, f: impl FnOnce(&mut DeJsonState, &mut Chars) -> Result<T, DeJsonErr>) -> Result<T, DeJsonErr> {
+        let mut s = DeJsonState::default();
+        let mut i = text.chars();
+        s.next(&mut i);
+        s.next_tok(&mut i).and_then( | _ | f(&mut s, &mut i)).map_err( | err | DeJsonErr {
+            msg: err.msg,
+            line: self.line,
+            col: self.col
+        })
+    }
+    
+    // parses captured fields as an object
+    pub fn de_fields<T>(&self, fields: &str, f: impl FnOnce(&mut DeJsonState, &mut Chars) -> Result<T, DeJsonErr>) -> Result<T, DeJsonErr> {
+        self.de_captured(&format!("{{{}}}", fields), f)
+    }
+    
     pub fn u64_range(&mut self, max: u64) -> Result<u64, DeJsonErr> {
         if let DeJsonTok::U64(value) = self.tok {
             if value > max {
@@ -323,6 +440,19 @@ impl DeJsonState {
         Err(self.err_token("string"))
     }
     
+    // the 4 hex digits of a \u escape, leaves cur on the last one
+    fn hex4(&mut self, i: &mut Chars) -> Result<u32, DeJsonErr> {
+        let mut code = 0;
+        for _ in 0..4 {
+            self.next(i);
+            match self.cur.to_digit(16) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.err_parse("string"))
+            }
+        }
+        Ok(code)
+    }
+    
     pub fn next_tok(&mut self, i: &mut Chars) -> Result<(), DeJsonErr> {
         while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' {
             self.next(i);
@@ -376,13 +506,30 @@ impl DeJsonState {
                     self.numbuf.push(self.cur);
                     self.next(i);
                 }
+                let mut is_float = false;
                 if self.cur == '.' {
+                    is_float = true;
+                    self.numbuf.push(self.cur);
+                    self.next(i);
+                    while self.cur >= '0' && self.cur <= '9' {
+                        self.numbuf.push(self.cur);
+                        self.next(i);
+                    }
+                }
+                if self.cur == 'e' || self.cur == 'E' {
+                    is_float = true;
                     self.numbuf.push(self.cur);
                     self.next(i);
+                    if self.cur == '+' || self.cur == '-' {
+                        self.numbuf.push(self.cur);
+                        self.next(i);
+                    }
                     while self.cur >= '0' && self.cur <= '9' {
                         self.numbuf.push(self.cur);
                         self.next(i);
                     }
+                }
+                if is_float {
                     if let Ok(num) = self.numbuf.parse() {
                         self.tok = DeJsonTok::F64(num);
                         Ok(())
@@ -444,6 +591,28 @@ impl DeJsonState {
                             'r'=>self.strbuf.push('\r'),
                             't'=>self.strbuf.push('\t'),
                             '0'=>self.strbuf.push('\0'),
+                            'b'=>self.strbuf.push('\u{8}'),
+                            'f'=>self.strbuf.push('\u{c}'),
+                            'u'=>{
+                                let mut code = self.hex4(i) ?;
+                                // characters outside the BMP are escaped as a surrogate pair
+                                if (0xd800..0xdc00).contains(&code) {
+                                    self.next(i);
+                                    if self.cur != '\\' {
+                                        return Err(self.err_parse("string"));
+                                    }
+                                    self.next(i);
+                                    if self.cur != 'u' {
+                                        return Err(self.err_parse("string"));
+                                    }
+                                    let low = self.hex4(i) ?;
+                                    code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                                }
+                                match char::from_u32(code) {
+                                    Some(c) => self.strbuf.push(c),
+                                    None => return Err(self.err_parse("string"))
+                                }
+                            }
                             '\0'=>{
                                 return Err(self.err_parse("string"));
                             },
@@ -582,21 +751,26 @@ impl DeJson for bool {
     }
 }
 
+fn push_json_str(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c{
+            '\n'=>{out.push('\\');out.push('n');},
+            '\r'=>{out.push('\\');out.push('r');},
+            '\t'=>{out.push('\\');out.push('t');},
+            '\\'=>{out.push('\\');out.push('\\');},
+            '"'=>{out.push('\\');out.push('"');},
+            // other control characters have no short escape in json
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            _=>out.push(c)
+        }
+    }
+    out.push('"');
+}
+
 impl SerJson for String {
     fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
-        s.out.push('"');
-        for c in self.chars() {
-            match c{
-                '\n'=>{s.out.push('\\');s.out.push('n');},
-                '\r'=>{s.out.push('\\');s.out.push('r');},
-                '\t'=>{s.out.push('\\');s.out.push('t');},
-                '\0'=>{s.out.push('\\');s.out.push('0');},
-                '\\'=>{s.out.push('\\');s.out.push('\\');},
-                '"'=>{s.out.push('\\');s.out.push('"');},
-                _=>s.out.push(c)
-            }
-        }
-        s.out.push('"');
+        push_json_str(self, &mut s.out);
     }
 }
 
@@ -760,15 +934,14 @@ impl<K, V> SerJson for HashMap<K, V> where K: SerJson,
 V: SerJson {
     fn ser_json(&self, d: usize, s: &mut SerJsonState) {
         s.out.push('{');
-        let last = self.len() - 1;
         for (index, (k, v)) in self.iter().enumerate() {
+            if index != 0{
+                s.conl();
+            }
             s.indent(d + 1);
             k.ser_json(d + 1, s);
             s.out.push(':');
             v.ser_json(d + 1, s);
-            if index != last{
-                s.conl();
-            }
         }
         s.indent(d);
         s.out.push('}');
@@ -32,6 +32,14 @@ impl SerRonState {
         self.out.push(')');
     }
     
+    // writes the fields of a struct into the struct around it, for #[flatten]
+    pub fn flatten<T: SerRon + ?Sized>(&mut self, d: usize, value: &T) {
+        let mut inner = SerRonState {out: String::new()};
+        value.ser_ron(d, &mut inner);
+        if let Some(fields) = inner.out.strip_prefix("(\n").and_then( | out | out.strip_suffix(')')) {
+            self.out.push_str(fields.trim_end_matches(' '));
+        }
+    }
 }
 
 pub trait SerRon {
@@ -291,6 +299,124 @@ impl DeRonState {
     }
     
     
+    // writes the current token back as ron
+    fn push_tok(&self, out: &mut String) -> Result<(), DeRonErr> {
+        let is_word = matches!(self.tok, DeRonTok::Ident | DeRonTok::U64(_) | DeRonTok::I64(_) | DeRonTok::F64(_) | DeRonTok::Bool(_));
+        if is_word && out.ends_with( | c: char | c.is_alphanumeric() || c == '_') {
+            out.push(' ');
+        }
+        match &self.tok {
+            DeRonTok::Ident => out.push_str(&self.identbuf),
+            DeRonTok::Str => push_ron_str(&self.strbuf, out),
+            DeRonTok::U64(_) | DeRonTok::I64(_) | DeRonTok::F64(_) => out.push_str(&self.numbuf),
+            DeRonTok::Bool(value) => out.push_str(if *value {"true"} else {"false"}),
+            DeRonTok::Char(c) => {
+                out.push('\'');
+                if *c == '\'' || *c == '\\' {
+                    out.push('\\');
+                }
+                out.push(*c);
+                out.push('\'');
+            }
+            DeRonTok::Colon => out.push(':'),
+            DeRonTok::CurlyOpen => out.push('{'),
+            DeRonTok::CurlyClose => out.push('}'),
+            DeRonTok::ParenOpen => out.push('('),
+            DeRonTok::ParenClose => out.push(')'),
+            DeRonTok::BlockOpen => out.push('['),
+            DeRonTok::BlockClose => out.push(']'),
+            DeRonTok::Comma => out.push(','),
+            DeRonTok::Bof | DeRonTok::Eof => return Err(self.err_token("ron value"))
+        }
+        Ok(())
+    }
+    
+    fn capture_value_into(&mut self, i: &mut Chars, out: &mut String) -> Result<(), DeRonErr> {
+        let mut depth = 0;
+        loop {
+            match self.tok {
+                DeRonTok::CurlyOpen | DeRonTok::ParenOpen | DeRonTok::BlockOpen => depth += 1,
+                DeRonTok::CurlyClose | DeRonTok::ParenClose | DeRonTok::BlockClose if depth > 0 => depth -= 1,
+                DeRonTok::CurlyClose | DeRonTok::ParenClose | DeRonTok::BlockClose | DeRonTok::Colon | DeRonTok::Comma if depth == 0 => {
+                    return Err(self.err_token("ron value"))
+                }
+                _ => ()
+            }
+            self.push_tok(out) ?;
+            self.next_tok(i) ?;
+            // a name can be followed by the content of a variant or struct, like Some(1)
+            if depth == 0 && self.tok != DeRonTok::ParenOpen {
+                return Ok(())
+            }
+        }
+    }
+    
+    // reads the value at the current token, and returns it as ron to be parsed later
+    pub fn capture_value(&mut self, i: &mut Chars) -> Result<String, DeRonErr> {
+        let mut out = String::new();
+        self.capture_value_into(i, &mut out) ?;
+        Ok(out)
+    }
+    
+    pub fn skip_value(&mut self, i: &mut Chars) -> Result<(), DeRonErr> {
+        self.capture_value(i).map( | _ | ())
+    }
+    
+    // reads the field name at the current token and its value, and adds them to fields as ron
+    pub fn capture_field(&mut self, i: &mut Chars, fields: &mut String) -> Result<(), DeRonErr> {
+        if !fields.is_empty() {
+            fields.push_str(", ");
+        }
+        self.push_tok(fields) ?;
+        self.next_colon(i) ?;
+        fields.push_str(": ");
+        self.capture_value_into(i, fields)
+    }
+    
+    // reads a struct for an internally tagged enum, which can have the tag anywhere.
+    // returns the tag and the other fields
+    pub fn tagged_fields(&mut self, i: &mut Chars, tag: &str) -> Result<(String, String), DeRonErr> {
+        let mut value = None;
+        let mut fields = String::new();
+        self.paren_open(i) ?;
+        while let Some(_) = self.next_ident() {
+            if self.identbuf == tag {
+                self.next_colon(i) ?;
+                value = Some(match self.tok {
+                    DeRonTok::Ident => self.identbuf.clone(),
+                    _ => self.as_string() ?
+                });
+                self.next_tok(i) ?;
+            }
+            else {
+                self.capture_field(i, &mut fields) ?;
+            }
+            self.eat_comma_paren(i) ?;
+        }
+        self.paren_close(i) ?;
+        match value {
+            Some(value) => Ok((value, fields)),
+            None => Err(self.err_nf(tag))
+        }
+    }
+    
+    // parses captured ron, errors point at the current position
+    pub fn de_captured<T>(&self, text: &str, f: impl FnOnce(&mut DeRonState, &mut Chars) -> Result<T, DeRonErr>) -> Result<T, DeRonErr> {
+        let mut s = DeRonState::default();
+        let mut i = text.chars();
+        s.next(&mut i);
+        s.next_tok(&mut i).and_then( | _ | f(&mut s, &mut i)).map_err( | err | DeRonErr {
+            msg: err.msg,
+            line: self.line,
+            col: self.col
+        })
+    }
+    
+    // parses captured fields as a struct
+    pub fn de_fields<T>(&self, fields: &str, f: impl FnOnce(&mut DeRonState, &mut Chars) -> Result<T, DeRonErr>) -> Result<T, DeRonErr> {
+        self.de_captured(&format!("({})", fields), f)
+    }
+    
     pub fn u64_range(&mut self, max: u64) -> Result<u64, DeRonErr> {
         if let DeRonTok::U64(value) = self.tok {
             if value > max {
@@ -477,6 +603,7 @@ impl DeRonState {
                     self.identbuf.clear();
                     while self.cur >= 'a' && self.cur <= 'z'
                         || self.cur >= 'A' && self.cur <= 'Z'
+                        || self.cur >= '0' && self.cur <= '9'
                         || self.cur == '_' {
                         self.identbuf.push(self.cur);
                         self.next(i);
@@ -658,21 +785,25 @@ impl DeRon for bool {
     }
 }
 
+fn push_ron_str(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\n' => {out.push('\\'); out.push('n');},
+            '\r' => {out.push('\\'); out.push('r');},
+            '\t' => {out.push('\\'); out.push('t');},
+            '\0' => {out.push('\\'); out.push('0');},
+            '\\' => {out.push('\\'); out.push('\\');},
+            '"' => {out.push('\\'); out.push('"');},
+            _ => out.push(c)
+        }
+    }
+    out.push('"');
+}
+
 impl SerRon for String {
     fn ser_ron(&self, _d: usize, s: &mut SerRonState) {
-        s.out.push('"');
-        for c in self.chars() {
-            match c {
-                '\n' => {s.out.push('\\'); s.out.push('n');},
-                '\r' => {s.out.push('\\'); s.out.push('r');},
-                '\t' => {s.out.push('\\'); s.out.push('t');},
-                '\0' => {s.out.push('\\'); s.out.push('0');},
-                '\\' => {s.out.push('\\'); s.out.push('\\');},
-                '"' => {s.out.push('\\'); s.out.push('"');},
-                _ => s.out.push(c)
-            }
-        }
-        s.out.push('"');
+        push_ron_str(self, &mut s.out);
     }
 }
 
@@ -0,0 +1,144 @@
+use makepad_micro_serde::*;
+
+#[derive(SerJson, DeJson, SerRon, DeRon, SerBin, DeBin, Debug, PartialEq)]
+struct Settings {
+    #[rename("fontSize")]
+    font_size: u32,
+    #[default]
+    tab_width: u32,
+    #[default(String::from("dark"))]
+    theme: String,
+    #[skip]
+    #[default(7)]
+    cache_id: u32,
+    _type: String,
+    word_wrap: Option<bool>,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, Debug, PartialEq)]
+#[deny_unknown_fields]
+struct Strict {
+    a: u32,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, Debug, Default, PartialEq)]
+struct Position {
+    line: u32,
+    column: u32,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, Debug, PartialEq)]
+struct Marker {
+    name: String,
+    #[flatten]
+    position: Position,
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, Debug, Default, PartialEq)]
+#[tag("type")]
+enum Shape {
+    #[default]
+    Empty,
+    Circle {radius: f64},
+    #[rename("rect")]
+    Rect {width: f64, height: f64},
+    At(Position),
+}
+
+#[derive(SerJson, DeJson, SerRon, DeRon, Debug, PartialEq)]
+#[untagged]
+enum Value {
+    Nothing,
+    Number(u64),
+    Text(String),
+    Pair(u64, u64),
+    Point {x: u64, y: u64},
+}
+
+fn settings() -> Settings {
+    Settings {
+        font_size: 12,
+        tab_width: 4,
+        theme: "light".to_string(),
+        cache_id: 3,
+        _type: "editor".to_string(),
+        word_wrap: None,
+    }
+}
+
+#[test]
+fn rename_default_skip() {
+    let json = settings().serialize_json();
+    assert_eq!(json, r#"{"fontSize":12,"tab_width":4,"theme":"light","type":"editor"}"#);
+    assert_eq!(Settings::deserialize_json(&json).unwrap(), Settings {cache_id: 7, ..settings()});
+
+    let ron = settings().serialize_ron();
+    assert_eq!(Settings::deserialize_ron(&ron).unwrap(), Settings {cache_id: 7, ..settings()});
+    assert!(ron.contains("fontSize:12"));
+    assert!(!ron.contains("cache_id"));
+
+    let missing = Settings::deserialize_json(r#"{"fontSize":10,"type":"x"}"#).unwrap();
+    assert_eq!((missing.tab_width, missing.theme.as_str(), missing.cache_id), (0, "dark", 7));
+    assert!(Settings::deserialize_json(r#"{"type":"x"}"#).is_err());
+
+    let bin = settings().serialize_bin();
+    assert_eq!(Settings::deserialize_bin(&bin).unwrap(), Settings {cache_id: 7, ..settings()});
+}
+
+#[test]
+fn unknown_fields() {
+    let json = r#"{"fontSize":1,"type":"x","extra":{"deep":[1,-2.5e3,"a",null,true]},"more":"b"}"#;
+    assert_eq!(Settings::deserialize_json(json).unwrap().font_size, 1);
+    let ron = r#"(fontSize: 1, _type: "x", extra: Some((deep: [1, -2.5, "a"])), more: None)"#;
+    assert_eq!(Settings::deserialize_ron(ron).unwrap().font_size, 1);
+
+    assert!(Strict::deserialize_json(r#"{"a":1,"b":2}"#).is_err());
+    assert!(Strict::deserialize_ron("(a: 1, b: 2)").is_err());
+    assert_eq!(Strict::deserialize_json(r#"{"a":1}"#).unwrap(), Strict {a: 1});
+}
+
+#[test]
+fn flatten() {
+    let marker = Marker {name: "todo".to_string(), position: Position {line: 3, column: 9}};
+    let json = marker.serialize_json();
+    assert_eq!(json, r#"{"name":"todo","line":3,"column":9}"#);
+    assert_eq!(Marker::deserialize_json(&json).unwrap(), marker);
+    assert_eq!(Marker::deserialize_json(r#"{"column":9,"name":"todo","line":3}"#).unwrap(), marker);
+    assert_eq!(Marker::deserialize_ron(&marker.serialize_ron()).unwrap(), marker);
+}
+
+#[test]
+fn internal_tag() {
+    let shapes = vec![
+        Shape::Empty,
+        Shape::Circle {radius: 2.5},
+        Shape::Rect {width: 1.0, height: 2.0},
+        Shape::At(Position {line: 1, column: 2}),
+    ];
+    let json = shapes.serialize_json();
+    assert_eq!(json, r#"[{"type":"Empty"},{"type":"Circle","radius":2.5},{"type":"rect","width":1,"height":2},{"type":"At","line":1,"column":2}]"#);
+    assert_eq!(Vec::<Shape>::deserialize_json(&json).unwrap(), shapes);
+    assert_eq!(Vec::<Shape>::deserialize_ron(&shapes.serialize_ron()).unwrap(), shapes);
+
+    // the tag does not have to come first
+    let shape = Shape::deserialize_json(r#"{"radius":1.5,"type":"Circle"}"#).unwrap();
+    assert_eq!(shape, Shape::Circle {radius: 1.5});
+    assert!(Shape::deserialize_json(r#"{"radius":1.5}"#).is_err());
+    assert!(Shape::deserialize_json(r#"{"type":"Square"}"#).is_err());
+}
+
+#[test]
+fn untagged() {
+    let values = vec![
+        Value::Nothing,
+        Value::Number(4),
+        Value::Text("four".to_string()),
+        Value::Pair(1, 2),
+        Value::Point {x: 5, y: 6},
+    ];
+    let json = values.serialize_json();
+    assert_eq!(json, r#"[null,4,"four",[1,2],{"x":5,"y":6}]"#);
+    assert_eq!(Vec::<Value>::deserialize_json(&json).unwrap(), values);
+    assert_eq!(Vec::<Value>::deserialize_ron(&values.serialize_ron()).unwrap(), values);
+    assert!(Value::deserialize_json("true").is_err());
+}
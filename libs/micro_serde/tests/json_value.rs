@@ -0,0 +1,190 @@
+use makepad_micro_serde::*;
+
+const RESPONSE: &str = r#"{
+    "id": "chatcmpl-1",
+    "created": 1700000000,
+    "choices": [
+        {"index": 0, "message": {"role": "assistant", "content": "Hi \"there\"\né😀"}, "logprobs": null}
+    ],
+    "usage": {"prompt_tokens": 9, "total_tokens": 12.5, "cached": false}
+}"#;
+
+#[test]
+fn value_lookup() {
+    let value = JsonValue::parse(RESPONSE).unwrap();
+    assert_eq!(value["id"].as_str(), Some("chatcmpl-1"));
+    assert_eq!(value["created"].as_u64(), Some(1700000000));
+    assert_eq!(value["choices"][0]["message"]["content"].as_str(), Some("Hi \"there\"\né😀"));
+    assert_eq!(value.path("choices.0.message.role").and_then(JsonValue::as_str), Some("assistant"));
+    assert_eq!(value.path("usage.total_tokens").and_then(JsonValue::as_f64), Some(12.5));
+    assert_eq!(value["usage"]["cached"].as_bool(), Some(false));
+    assert!(value["choices"][0]["logprobs"].is_null());
+
+    // missing keys and indices are null rather than a panic
+    assert!(value["nope"][3]["deeper"].is_null());
+    assert!(value.path("choices.1.message").is_none());
+    assert!(value.path("choices.x").is_none());
+    assert_eq!(value["usage"].as_object().map(<[_]>::len), Some(3));
+    assert!(JsonValue::parse("{\"a\":").is_err());
+}
+
+#[test]
+fn value_round_trip() {
+    let mut value = JsonValue::object([
+        ("name", "tab\tand\u{1}control".into()),
+        ("count", 3u64.into()),
+        ("ratio", 0.25.into()),
+        ("neg", (-7i64).into()),
+        ("list", vec![true.into(), JsonValue::Null, JsonValue::object([])].into()),
+    ]);
+    value.insert("count", 4u64.into());
+    value.insert("extra", "x".into());
+    let json = value.serialize_json();
+    assert_eq!(json, r#"{"name":"tab\tand\u0001control","count":4,"ratio":0.25,"neg":-7,"list":[true,null,{}],"extra":"x"}"#);
+    assert_eq!(JsonValue::parse(&json).unwrap(), value);
+    assert_eq!(JsonValue::parse(RESPONSE).unwrap(), JsonValue::parse(&JsonValue::parse(RESPONSE).unwrap().serialize_json()).unwrap());
+}
+
+fn events(chunks: &[&[u8]]) -> Vec<JsonEvent> {
+    let mut reader = JsonReader::new();
+    let mut out = Vec::new();
+    for chunk in chunks {
+        reader.push_bytes(chunk).unwrap();
+        while let Some(event) = reader.next().unwrap() {
+            out.push(event);
+        }
+    }
+    reader.finish();
+    while let Some(event) = reader.next().unwrap() {
+        out.push(event);
+    }
+    out
+}
+
+#[test]
+fn reader_chunks() {
+    let doc = r#"{"a": [1, -2.5e3, "x\"é", true, null], "b": {"c": false}} 42"#;
+    let expected = vec![
+        JsonEvent::ObjectStart,
+        JsonEvent::Key("a".to_string()),
+        JsonEvent::ArrayStart,
+        JsonEvent::Number(1.0),
+        JsonEvent::Number(-2500.0),
+        JsonEvent::String("x\"é".to_string()),
+        JsonEvent::Bool(true),
+        JsonEvent::Null,
+        JsonEvent::ArrayEnd,
+        JsonEvent::Key("b".to_string()),
+        JsonEvent::ObjectStart,
+        JsonEvent::Key("c".to_string()),
+        JsonEvent::Bool(false),
+        JsonEvent::ObjectEnd,
+        JsonEvent::ObjectEnd,
+        JsonEvent::Number(42.0),
+    ];
+    assert_eq!(events(&[doc.as_bytes()]), expected);
+    // every split point, including inside strings, numbers, literals and escapes
+    for split in 1..doc.len() {
+        let (a, b) = doc.as_bytes().split_at(split);
+        assert_eq!(events(&[a, b]), expected, "split at {}", split);
+    }
+    let bytes: Vec<&[u8]> = doc.as_bytes().chunks(1).collect();
+    assert_eq!(events(&bytes), expected);
+
+    // a multi byte character split between chunks
+    let text = "[\"héllo😀\"]".as_bytes();
+    let bytes: Vec<&[u8]> = text.chunks(1).collect();
+    assert_eq!(events(&bytes)[1], JsonEvent::String("héllo😀".to_string()));
+}
+
+#[test]
+fn reader_values() {
+    let doc = r#"{"data": [{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}, 3,], "done": true}"#;
+    let mut reader = JsonReader::new();
+    let mut items = Vec::new();
+    let mut done = None;
+    for chunk in doc.as_bytes().chunks(5) {
+        reader.push(std::str::from_utf8(chunk).unwrap());
+        loop {
+            let depth = reader.depth();
+            match reader.peek().unwrap() {
+                None => break,
+                Some(JsonEvent::ObjectStart | JsonEvent::ArrayStart) if depth < 2 => {
+                    reader.next().unwrap();
+                }
+                Some(JsonEvent::Key(_) | JsonEvent::ArrayEnd | JsonEvent::ObjectEnd) => {
+                    reader.next().unwrap();
+                }
+                // the items of data arrive one at a time, whole
+                Some(_) if depth == 2 => match reader.next_value().unwrap() {
+                    Some(value) => items.push(value),
+                    None => break
+                },
+                Some(_) => match reader.next_value().unwrap() {
+                    Some(value) => done = value.as_bool(),
+                    None => break
+                }
+            }
+        }
+    }
+    reader.finish();
+    assert_eq!(reader.next().unwrap(), None);
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1]["id"].as_u64(), Some(2));
+    assert_eq!(items[0]["tags"][0].as_str(), Some("a"));
+    assert_eq!(items[2].as_u64(), Some(3));
+    assert_eq!(done, Some(true));
+    assert_eq!(reader.depth(), 0);
+}
+
+#[test]
+fn reader_errors() {
+    let fail = | doc: &str | {
+        let mut reader = JsonReader::new();
+        reader.push(doc);
+        reader.finish();
+        loop {
+            match reader.next() {
+                Ok(Some(_)) => (),
+                Ok(None) => return false,
+                Err(_) => return true
+            }
+        }
+    };
+    assert!(!fail(r#"{"a": [1, 2]} [] "x""#));
+    assert!(fail(r#"{"a": [1, 2}"#));
+    assert!(fail(r#"{"a" 1}"#));
+    assert!(fail(r#"{"a": tru}"#));
+    assert!(fail(r#"{"a": 1"#));
+    assert!(fail(r#"{"a": "open"#));
+    assert!(fail(r#"["\q"]"#));
+    assert!(fail(r#"[1 2]"#));
+    assert!(fail(r#"[1.2.3]"#));
+
+    // an unfinished document only waits for more input
+    let mut reader = JsonReader::new();
+    reader.push(r#"[{"a": 1"#);
+    assert_eq!(reader.next().unwrap(), Some(JsonEvent::ArrayStart));
+    assert_eq!(reader.next_value().unwrap(), None);
+    reader.push("}]");
+    assert_eq!(reader.next_value().unwrap(), Some(JsonValue::object([("a", 1u64.into())])));
+    assert!(reader.next_value().is_err());
+    assert_eq!(reader.next().unwrap(), Some(JsonEvent::ArrayEnd));
+
+    let mut reader = JsonReader::new();
+    assert!(reader.push_bytes(&[b'"', 0xff]).is_err());
+    let mut reader = JsonReader::new();
+    reader.push_bytes(&[b'"', 0xc3]).unwrap();
+    reader.finish();
+    assert!(reader.next().is_err());
+    // a str cannot finish a split character, what came before is read and then the error, which stays
+    let mut reader = JsonReader::new();
+    reader.push("[1, ");
+    reader.push_bytes(&[b'"', 0xc3]).unwrap();
+    reader.push("\"]");
+    assert_eq!(reader.next().unwrap(), Some(JsonEvent::ArrayStart));
+    assert_eq!(reader.next().unwrap(), Some(JsonEvent::Number(1.0)));
+    assert!(reader.next().is_err());
+    reader.push("2]");
+    assert!(reader.next().is_err());
+}
@@ -0,0 +1,47 @@
+//! CRC-32 checksum (the IEEE polynomial), as used by zip, gzip and png.
+
+const fn make_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = make_crc32_table();
+
+/// Continue a CRC-32 checksum with more data.
+///
+/// Start with `0`, the checksum of no data.
+pub fn update_crc32(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc = CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// The CRC-32 checksum of `data`.
+pub fn crc32(data: &[u8]) -> u32 {
+    update_crc32(0, data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crc32, update_crc32};
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(update_crc32(crc32(b"1234"), b"56789"), 0xcbf43926);
+    }
+}
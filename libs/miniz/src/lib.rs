@@ -22,9 +22,11 @@
 //! ```
 
 pub mod adler32;
+pub mod crc32;
 pub mod deflate;
 pub mod inflate;
 pub mod zip_file;
+pub mod zip_writer;
 mod shared;
 
 pub use crate::deflate::compress_to_vec;
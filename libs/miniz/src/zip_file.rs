@@ -1,4 +1,4 @@
-// zip fileformat reading
+// zip fileformat reading, writing is in zip_writer.rs
 
 use std::io::{Read, Seek, SeekFrom};
 pub use crate::inflate::decompress_to_vec;
@@ -6,6 +6,9 @@ pub use crate::inflate::decompress_to_vec;
 pub const COMPRESS_METHOD_UNCOMPRESSED:u16 = 0;
 pub const COMPRESS_METHOD_DEFLATED:u16 = 8;
 
+pub const DATA_DESCRIPTOR_SIGNATURE:u32 = 0x08074b50;
+pub const ZIP64_EXTRA_FIELD_ID:u16 = 0x0001;
+
 pub const LOCAL_FILE_HEADER_SIGNATURE:u32 = 0x04034b50;
 pub const LOCAL_FILE_HEADER_SIZE:usize = 30;
 #[derive(Clone, Debug)]
@@ -85,6 +88,7 @@ pub struct CentralDirectoryFileHeader {
 
     pub file_name: String,
     pub file_comment: String,
+    pub zip64: Option<Zip64ExtendedInformation>,
 }
 
 impl CentralDirectoryFileHeader{
@@ -110,8 +114,14 @@ impl CentralDirectoryFileHeader{
         let external_file_attributes = read_u32(zip_data)?;
         let relative_offset_of_local_header = read_u32(zip_data)?;
         let file_name = read_string(zip_data, file_name_length as usize)?;
-        zip_data.seek(SeekFrom::Current(extra_field_length as i64)).map_err(|_| ZipError::CantSeekSkip)?;
+        let extra_field = read_binary(zip_data, extra_field_length as usize)?;
         let file_comment = read_string(zip_data, file_comment_length as usize)?;
+        let zip64 = Zip64ExtendedInformation::from_extra_field(
+            &extra_field,
+            uncompressed_size,
+            compressed_size,
+            relative_offset_of_local_header
+        )?;
         
         Ok(Self{
             signature,
@@ -132,9 +142,59 @@ impl CentralDirectoryFileHeader{
             external_file_attributes,
             relative_offset_of_local_header,
             file_name,
-            file_comment
+            file_comment,
+            zip64,
         })
     }
+    
+    // the sizes and offset, from the zip64 extra field when the 32 bit ones overflowed
+    pub fn uncompressed_size64(&self)->u64{
+        self.zip64.as_ref().map_or(self.uncompressed_size as u64, |zip64| zip64.uncompressed_size)
+    }
+    
+    pub fn compressed_size64(&self)->u64{
+        self.zip64.as_ref().map_or(self.compressed_size as u64, |zip64| zip64.compressed_size)
+    }
+    
+    pub fn relative_offset_of_local_header64(&self)->u64{
+        self.zip64.as_ref().map_or(self.relative_offset_of_local_header as u64, |zip64| zip64.relative_offset_of_local_header)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Zip64ExtendedInformation {
+    pub uncompressed_size: u64,
+    pub compressed_size: u64,
+    pub relative_offset_of_local_header: u64,
+}
+
+impl Zip64ExtendedInformation{
+    // the extra field only holds the values that are 0xffffffff in the header, in this order
+    pub fn from_extra_field(extra_field:&[u8], uncompressed_size:u32, compressed_size:u32, relative_offset_of_local_header:u32)->Result<Option<Self>, ZipError>{
+        let mut extra_field = extra_field;
+        while extra_field.len() >= 4{
+            let id = u16::from_le_bytes([extra_field[0], extra_field[1]]);
+            let size = u16::from_le_bytes([extra_field[2], extra_field[3]]) as usize;
+            let data = extra_field.get(4..4 + size).ok_or(ZipError::ParseError)?;
+            extra_field = &extra_field[4 + size..];
+            if id != ZIP64_EXTRA_FIELD_ID{
+                continue
+            }
+            let mut data = data;
+            let mut value = |value32:u32|->Result<u64, ZipError>{
+                if value32 != u32::MAX{
+                    return Ok(value32 as u64)
+                }
+                read_u64(&mut data)
+            };
+            return Ok(Some(Self{
+                uncompressed_size: value(uncompressed_size)?,
+                compressed_size: value(compressed_size)?,
+                relative_offset_of_local_header: value(relative_offset_of_local_header)?,
+            }))
+        }
+        Ok(None)
+    }
 }
 
 pub const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
@@ -170,6 +230,68 @@ impl EndOfCentralDirectory{
     }
 }
 
+pub const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06064b50;
+pub const ZIP64_END_OF_CENTRAL_DIRECTORY_SIZE:usize = 56;
+#[derive(Clone, Debug)]
+pub struct Zip64EndOfCentralDirectory {
+    pub signature: u32,
+    pub size_of_zip64_end_of_central_directory: u64,
+    pub version_made_by: u16,
+    pub version_needed_to_extract: u16,
+    pub number_of_disk: u32,
+    pub number_of_start_central_directory_disk: u32,
+    pub total_entries_this_disk: u64,
+    pub total_entries_all_disk: u64,
+    pub size_of_the_central_directory: u64,
+    pub central_directory_offset: u64,
+}
+
+impl Zip64EndOfCentralDirectory{
+    pub fn from_stream(zip_data:&mut impl Read)->Result<Self, ZipError>{
+        let signature =  read_u32(zip_data)?;
+        if signature != ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE{
+            return Err(ZipError::EndOfCentralDirectoryInvalid)
+        }
+        Ok(Self{
+            signature,
+            size_of_zip64_end_of_central_directory: read_u64(zip_data)?,
+            version_made_by: read_u16(zip_data)?,
+            version_needed_to_extract: read_u16(zip_data)?,
+            number_of_disk: read_u32(zip_data)?,
+            number_of_start_central_directory_disk: read_u32(zip_data)?,
+            total_entries_this_disk: read_u64(zip_data)?,
+            total_entries_all_disk: read_u64(zip_data)?,
+            size_of_the_central_directory: read_u64(zip_data)?,
+            central_directory_offset: read_u64(zip_data)?,
+        })
+    }
+}
+
+pub const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+pub const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIZE:usize = 20;
+#[derive(Clone, Debug)]
+pub struct Zip64EndOfCentralDirectoryLocator {
+    pub signature: u32,
+    pub number_of_zip64_end_of_central_directory_disk: u32,
+    pub zip64_end_of_central_directory_offset: u64,
+    pub total_number_of_disks: u32,
+}
+
+impl Zip64EndOfCentralDirectoryLocator{
+    pub fn from_stream(zip_data:&mut impl Read)->Result<Self, ZipError>{
+        let signature =  read_u32(zip_data)?;
+        if signature != ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE{
+            return Err(ZipError::EndOfCentralDirectoryInvalid)
+        }
+        Ok(Self{
+            signature,
+            number_of_zip64_end_of_central_directory_disk: read_u32(zip_data)?,
+            zip64_end_of_central_directory_offset: read_u64(zip_data)?,
+            total_number_of_disks: read_u32(zip_data)?,
+        })
+    }
+}
+
 fn read_u16(zip_data:&mut impl Read)->Result<u16, ZipError>{
     let mut bytes = [0u8;2];
     if let Ok(size) = zip_data.read(&mut bytes){
@@ -192,6 +314,17 @@ fn read_u32(zip_data:&mut impl Read)->Result<u32, ZipError>{
     Err(ZipError::DataReadError)
 }
 
+fn read_u64(zip_data:&mut impl Read)->Result<u64, ZipError>{
+    let mut bytes = [0u8;8];
+    if let Ok(size) = zip_data.read(&mut bytes){
+        if size != 8{
+            return Err(ZipError::DataReadError)
+        }
+        return Ok(u64::from_le_bytes(bytes))
+    }
+    Err(ZipError::DataReadError)
+}
+
 fn read_string(zip_data:&mut impl Read, len:usize)->Result<String, ZipError>{
     let mut data = Vec::new();
     data.resize(len,0u8);
@@ -221,20 +354,21 @@ fn read_binary(zip_data:&mut impl Read, len:usize)->Result<Vec<u8>, ZipError>{
 
 pub struct ZipCentralDirectory{
     pub eocd: EndOfCentralDirectory,
+    pub zip64_eocd: Option<Zip64EndOfCentralDirectory>,
     pub file_headers: Vec<CentralDirectoryFileHeader>,
 }
 
 impl CentralDirectoryFileHeader{
     // lets read and unzip specific files.
     pub fn extract(&self, zip_data: &mut (impl Seek+Read))->Result<Vec<u8>, ZipError>{
-        zip_data.seek(SeekFrom::Start(self.relative_offset_of_local_header as u64)).map_err(|_| ZipError::CantSeekToFileHeader)?;
+        zip_data.seek(SeekFrom::Start(self.relative_offset_of_local_header64())).map_err(|_| ZipError::CantSeekToFileHeader)?;
         let header = LocalFileHeader::from_stream(zip_data)?;
         if header.compression_method == COMPRESS_METHOD_UNCOMPRESSED{
-            let decompressed = read_binary(zip_data, self.uncompressed_size as usize)?;
+            let decompressed = read_binary(zip_data, self.uncompressed_size64() as usize)?;
             return Ok(decompressed)
         }
         else if header.compression_method == COMPRESS_METHOD_DEFLATED{
-            let compressed = read_binary(zip_data, self.compressed_size as usize)?;
+            let compressed = read_binary(zip_data, self.compressed_size64() as usize)?;
             if let Ok(decompressed) = decompress_to_vec(&compressed){
                 return Ok(decompressed);
             }
@@ -259,20 +393,42 @@ pub enum ZipError{
     CantSeekToDirStart,
     UnsupportedCompressionMethod,
     DecompressionError,
-    DataReadError
+    DataReadError,
+    DataWriteError,
+    CompressionError,
+    NoFileStarted,
+    FileTooLarge,
 }
 
 pub fn zip_read_central_directory(zip_data:&mut (impl Seek+Read))->Result<ZipCentralDirectory, ZipError>{
     // lets read the the dirend
     zip_data.seek(SeekFrom::End(-(END_OF_CENTRAL_DIRECTORY_SIZE as i64))).map_err(|_| ZipError::CantSeekToDirEnd)?;
     let eocd = EndOfCentralDirectory::from_stream(zip_data)?;
-    zip_data.seek(SeekFrom::Start(eocd.central_directory_offset as u64)).map_err(|_| ZipError::CantSeekToDirStart)?;
+    // values that do not fit are 0xffff.. and the real ones are in the zip64 end of central directory
+    let zip64_eocd = if eocd.total_entries_all_disk == u16::MAX || eocd.central_directory_offset == u32::MAX || eocd.size_of_the_central_directory == u32::MAX{
+        let locator_offset = END_OF_CENTRAL_DIRECTORY_SIZE + ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIZE;
+        zip_data.seek(SeekFrom::End(-(locator_offset as i64))).map_err(|_| ZipError::CantSeekToDirEnd)?;
+        let locator = Zip64EndOfCentralDirectoryLocator::from_stream(zip_data)?;
+        zip_data.seek(SeekFrom::Start(locator.zip64_end_of_central_directory_offset)).map_err(|_| ZipError::CantSeekToDirEnd)?;
+        Some(Zip64EndOfCentralDirectory::from_stream(zip_data)?)
+    }
+    else{
+        None
+    };
+    let (central_directory_offset, total_entries) = if let Some(zip64_eocd) = &zip64_eocd{
+        (zip64_eocd.central_directory_offset, zip64_eocd.total_entries_all_disk)
+    }
+    else{
+        (eocd.central_directory_offset as u64, eocd.total_entries_all_disk as u64)
+    };
+    zip_data.seek(SeekFrom::Start(central_directory_offset)).map_err(|_| ZipError::CantSeekToDirStart)?;
     let mut file_headers = Vec::new();
-    for _ in 0..eocd.total_entries_all_disk as usize{
+    for _ in 0..total_entries{
         file_headers.push(CentralDirectoryFileHeader::from_stream(zip_data)?);
     }
     Ok(ZipCentralDirectory{
         eocd,
+        zip64_eocd,
         file_headers
     })
 }
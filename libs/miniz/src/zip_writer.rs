@@ -0,0 +1,508 @@
+// zip fileformat writing
+
+use std::io::{self, Write};
+use crate::crc32::{crc32, update_crc32};
+use crate::deflate::compress_to_vec;
+use crate::deflate::core::{compress_to_output, create_comp_flags_from_zip_params, CompressorOxide, TDEFLFlush, TDEFLStatus};
+use crate::zip_file::*;
+
+const FLAG_DATA_DESCRIPTOR:u16 = 1 << 3;
+const FLAG_UTF8_NAME:u16 = 1 << 11;
+
+const VERSION_MADE_BY_UNIX:u16 = 3 << 8;
+const VERSION_STORED:u16 = 10;
+const VERSION_DEFLATED:u16 = 20;
+const VERSION_ZIP64:u16 = 45;
+
+// 1980-01-01 00:00, the first dos date. every entry gets it so archives are reproducible
+const DOS_TIME:u16 = 0;
+const DOS_DATE:u16 = (1 << 5) | 1;
+
+const UNIX_FILE:u32 = 0o100000;
+const UNIX_DIRECTORY:u32 = 0o040000;
+const DOS_DIRECTORY:u32 = 0x10;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ZipEntryOptions {
+    // COMPRESS_METHOD_UNCOMPRESSED or COMPRESS_METHOD_DEFLATED
+    pub compression_method: u16,
+    // the deflate level, 0 to 10
+    pub level: u8,
+    // unix permissions, like 0o755 for an executable
+    pub unix_mode: u32,
+    // always write zip64 sizes. files written with start_file need this to grow past 4GB
+    pub large_file: bool,
+}
+
+impl Default for ZipEntryOptions {
+    fn default() -> Self {
+        Self {
+            compression_method: COMPRESS_METHOD_DEFLATED,
+            level: 6,
+            unix_mode: 0o644,
+            large_file: false,
+        }
+    }
+}
+
+impl ZipEntryOptions {
+    pub fn stored() -> Self {
+        Self {
+            compression_method: COMPRESS_METHOD_UNCOMPRESSED,
+            ..Self::default()
+        }
+    }
+}
+
+struct ZipWriterEntry {
+    file_name: String,
+    general_purpose_bit_flag: u16,
+    compression_method: u16,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    relative_offset_of_local_header: u64,
+    external_file_attributes: u32,
+    zip64: bool,
+}
+
+impl ZipWriterEntry {
+    fn version_needed_to_extract(&self, zip64: bool) -> u16 {
+        if zip64 {
+            VERSION_ZIP64
+        }
+        else if self.compression_method == COMPRESS_METHOD_DEFLATED || self.file_name.ends_with('/') {
+            VERSION_DEFLATED
+        }
+        else {
+            VERSION_STORED
+        }
+    }
+}
+
+struct OpenFile {
+    entry: ZipWriterEntry,
+    compressor: Option<Box<CompressorOxide>>,
+}
+
+// writes a zip archive front to back, so the output only needs to be Write.
+// files are added whole with add_file, or in pieces between start_file and finish_file,
+// and finish writes the central directory.
+pub struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    entries: Vec<ZipWriterEntry>,
+    open_file: Option<OpenFile>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            offset: 0,
+            entries: Vec::new(),
+            open_file: None,
+        }
+    }
+
+    pub fn add_file(&mut self, file_name: &str, data: &[u8], options: ZipEntryOptions) -> Result<(), ZipError> {
+        self.finish_file()?;
+        let compressed;
+        let body = match options.compression_method {
+            COMPRESS_METHOD_UNCOMPRESSED => data,
+            COMPRESS_METHOD_DEFLATED => {
+                compressed = compress_to_vec(data, options.level);
+                &compressed
+            }
+            _ => return Err(ZipError::UnsupportedCompressionMethod)
+        };
+        let mut entry = self.new_entry(file_name, options);
+        entry.crc32 = crc32(data);
+        entry.compressed_size = body.len() as u64;
+        entry.uncompressed_size = data.len() as u64;
+        entry.zip64 = options.large_file || entry.compressed_size >= u32::MAX as u64 || entry.uncompressed_size >= u32::MAX as u64;
+        self.write_local_file_header(&entry)?;
+        self.write_bytes(body)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn add_directory(&mut self, directory_name: &str) -> Result<(), ZipError> {
+        self.finish_file()?;
+        let file_name = if directory_name.ends_with('/') {
+            directory_name.to_string()
+        }
+        else {
+            format!("{}/", directory_name)
+        };
+        let mut entry = self.new_entry(&file_name, ZipEntryOptions::stored());
+        entry.external_file_attributes = ((UNIX_DIRECTORY | 0o755) << 16) | DOS_DIRECTORY;
+        self.write_local_file_header(&entry)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    // starts a file whose data follows with write_file_data, or through io::Write.
+    // the crc and sizes are not known yet, so they are written after the data in a data descriptor
+    pub fn start_file(&mut self, file_name: &str, options: ZipEntryOptions) -> Result<(), ZipError> {
+        self.finish_file()?;
+        let compressor = match options.compression_method {
+            COMPRESS_METHOD_UNCOMPRESSED => None,
+            COMPRESS_METHOD_DEFLATED => {
+                let flags = create_comp_flags_from_zip_params(options.level.into(), 0, 0);
+                Some(Box::new(CompressorOxide::new(flags)))
+            }
+            _ => return Err(ZipError::UnsupportedCompressionMethod)
+        };
+        let mut entry = self.new_entry(file_name, options);
+        entry.general_purpose_bit_flag |= FLAG_DATA_DESCRIPTOR;
+        entry.zip64 = options.large_file;
+        self.write_local_file_header(&entry)?;
+        self.open_file = Some(OpenFile {entry, compressor});
+        Ok(())
+    }
+
+    pub fn write_file_data(&mut self, data: &[u8]) -> Result<(), ZipError> {
+        let Some(open_file) = &mut self.open_file else {
+            return Err(ZipError::NoFileStarted)
+        };
+        let entry = &mut open_file.entry;
+        entry.crc32 = update_crc32(entry.crc32, data);
+        entry.uncompressed_size += data.len() as u64;
+        let written = if let Some(compressor) = &mut open_file.compressor {
+            deflate_to(&mut self.out, compressor, data, TDEFLFlush::None)?
+        }
+        else {
+            self.out.write_all(data).map_err(|_| ZipError::DataWriteError)?;
+            data.len() as u64
+        };
+        entry.compressed_size += written;
+        self.offset += written;
+        Ok(())
+    }
+
+    // ends the file from start_file. adding another file or finishing the archive also does this
+    pub fn finish_file(&mut self) -> Result<(), ZipError> {
+        let Some(mut open_file) = self.open_file.take() else {
+            return Ok(())
+        };
+        if let Some(compressor) = &mut open_file.compressor {
+            let written = deflate_to(&mut self.out, compressor, &[], TDEFLFlush::Finish)?;
+            open_file.entry.compressed_size += written;
+            self.offset += written;
+        }
+        let entry = open_file.entry;
+        if !entry.zip64 && (entry.compressed_size >= u32::MAX as u64 || entry.uncompressed_size >= u32::MAX as u64) {
+            return Err(ZipError::FileTooLarge)
+        }
+        let mut descriptor = Vec::with_capacity(24);
+        put_u32(&mut descriptor, DATA_DESCRIPTOR_SIGNATURE);
+        put_u32(&mut descriptor, entry.crc32);
+        if entry.zip64 {
+            put_u64(&mut descriptor, entry.compressed_size);
+            put_u64(&mut descriptor, entry.uncompressed_size);
+        }
+        else {
+            put_u32(&mut descriptor, entry.compressed_size as u32);
+            put_u32(&mut descriptor, entry.uncompressed_size as u32);
+        }
+        self.write_bytes(&descriptor)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    // writes the central directory and gives back the output
+    pub fn finish(mut self) -> Result<W, ZipError> {
+        self.finish_file()?;
+        let entries = std::mem::take(&mut self.entries);
+        let central_directory_offset = self.offset;
+        for entry in &entries {
+            self.write_central_directory_file_header(entry)?;
+        }
+        let central_directory_size = self.offset - central_directory_offset;
+        let zip64 = entries.len() >= u16::MAX as usize
+            || central_directory_offset >= u32::MAX as u64
+            || central_directory_size >= u32::MAX as u64
+            || entries.iter().any(|entry| entry.zip64);
+
+        let mut end = Vec::with_capacity(ZIP64_END_OF_CENTRAL_DIRECTORY_SIZE + ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIZE + END_OF_CENTRAL_DIRECTORY_SIZE);
+        if zip64 {
+            let zip64_end_of_central_directory_offset = self.offset;
+            put_u32(&mut end, ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+            // the size of the record after this field
+            put_u64(&mut end, ZIP64_END_OF_CENTRAL_DIRECTORY_SIZE as u64 - 12);
+            put_u16(&mut end, VERSION_MADE_BY_UNIX | VERSION_ZIP64);
+            put_u16(&mut end, VERSION_ZIP64);
+            put_u32(&mut end, 0);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, entries.len() as u64);
+            put_u64(&mut end, entries.len() as u64);
+            put_u64(&mut end, central_directory_size);
+            put_u64(&mut end, central_directory_offset);
+
+            put_u32(&mut end, ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE);
+            put_u32(&mut end, 0);
+            put_u64(&mut end, zip64_end_of_central_directory_offset);
+            put_u32(&mut end, 1);
+        }
+        let total_entries = if zip64 {u16::MAX} else {entries.len() as u16};
+        put_u32(&mut end, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, 0);
+        put_u16(&mut end, total_entries);
+        put_u16(&mut end, total_entries);
+        put_u32(&mut end, if zip64 {u32::MAX} else {central_directory_size as u32});
+        put_u32(&mut end, if zip64 {u32::MAX} else {central_directory_offset as u32});
+        put_u16(&mut end, 0);
+        self.write_bytes(&end)?;
+
+        self.out.flush().map_err(|_| ZipError::DataWriteError)?;
+        Ok(self.out)
+    }
+
+    fn new_entry(&self, file_name: &str, options: ZipEntryOptions) -> ZipWriterEntry {
+        ZipWriterEntry {
+            file_name: file_name.to_string(),
+            general_purpose_bit_flag: if file_name.is_ascii() {0} else {FLAG_UTF8_NAME},
+            compression_method: options.compression_method,
+            crc32: 0,
+            compressed_size: 0,
+            uncompressed_size: 0,
+            relative_offset_of_local_header: self.offset,
+            external_file_attributes: (UNIX_FILE | options.unix_mode) << 16,
+            zip64: false,
+        }
+    }
+
+    fn write_local_file_header(&mut self, entry: &ZipWriterEntry) -> Result<(), ZipError> {
+        let mut header = Vec::with_capacity(LOCAL_FILE_HEADER_SIZE + entry.file_name.len() + 20);
+        put_u32(&mut header, LOCAL_FILE_HEADER_SIGNATURE);
+        put_u16(&mut header, entry.version_needed_to_extract(entry.zip64));
+        put_u16(&mut header, entry.general_purpose_bit_flag);
+        put_u16(&mut header, entry.compression_method);
+        put_u16(&mut header, DOS_TIME);
+        put_u16(&mut header, DOS_DATE);
+        put_u32(&mut header, entry.crc32);
+        if entry.zip64 {
+            put_u32(&mut header, u32::MAX);
+            put_u32(&mut header, u32::MAX);
+        }
+        else {
+            put_u32(&mut header, entry.compressed_size as u32);
+            put_u32(&mut header, entry.uncompressed_size as u32);
+        }
+        put_u16(&mut header, entry.file_name.len() as u16);
+        put_u16(&mut header, if entry.zip64 {20} else {0});
+        header.extend_from_slice(entry.file_name.as_bytes());
+        if entry.zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_FIELD_ID);
+            put_u16(&mut header, 16);
+            put_u64(&mut header, entry.uncompressed_size);
+            put_u64(&mut header, entry.compressed_size);
+        }
+        self.write_bytes(&header)
+    }
+
+    fn write_central_directory_file_header(&mut self, entry: &ZipWriterEntry) -> Result<(), ZipError> {
+        // a small file can still start past 4GB
+        let zip64 = entry.zip64 || entry.relative_offset_of_local_header >= u32::MAX as u64;
+        let mut header = Vec::with_capacity(CENTRAL_DIR_FILE_HEADER_SIZE + entry.file_name.len() + 28);
+        put_u32(&mut header, CENTRAL_DIR_FILE_HEADER_SIGNATURE);
+        put_u16(&mut header, VERSION_MADE_BY_UNIX | VERSION_ZIP64);
+        put_u16(&mut header, entry.version_needed_to_extract(zip64));
+        put_u16(&mut header, entry.general_purpose_bit_flag);
+        put_u16(&mut header, entry.compression_method);
+        put_u16(&mut header, DOS_TIME);
+        put_u16(&mut header, DOS_DATE);
+        put_u32(&mut header, entry.crc32);
+        if zip64 {
+            put_u32(&mut header, u32::MAX);
+            put_u32(&mut header, u32::MAX);
+        }
+        else {
+            put_u32(&mut header, entry.compressed_size as u32);
+            put_u32(&mut header, entry.uncompressed_size as u32);
+        }
+        put_u16(&mut header, entry.file_name.len() as u16);
+        put_u16(&mut header, if zip64 {28} else {0});
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 0);
+        put_u16(&mut header, 0);
+        put_u32(&mut header, entry.external_file_attributes);
+        put_u32(&mut header, if zip64 {u32::MAX} else {entry.relative_offset_of_local_header as u32});
+        header.extend_from_slice(entry.file_name.as_bytes());
+        if zip64 {
+            put_u16(&mut header, ZIP64_EXTRA_FIELD_ID);
+            put_u16(&mut header, 24);
+            put_u64(&mut header, entry.uncompressed_size);
+            put_u64(&mut header, entry.compressed_size);
+            put_u64(&mut header, entry.relative_offset_of_local_header);
+        }
+        self.write_bytes(&header)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ZipError> {
+        self.out.write_all(bytes).map_err(|_| ZipError::DataWriteError)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for ZipWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_file_data(buf).map_err(|e| io::Error::new(io::ErrorKind::Other, format!("{:?}", e)))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// compresses into the output, and returns how many bytes were written
+fn deflate_to(out: &mut impl Write, compressor: &mut CompressorOxide, data: &[u8], flush: TDEFLFlush) -> Result<u64, ZipError> {
+    let mut data = data;
+    let mut written = 0;
+    let mut write_failed = false;
+    loop {
+        let (status, bytes_in) = compress_to_output(compressor, data, flush, |chunk| {
+            if out.write_all(chunk).is_err() {
+                write_failed = true;
+                return false
+            }
+            written += chunk.len() as u64;
+            true
+        });
+        if write_failed {
+            return Err(ZipError::DataWriteError)
+        }
+        data = &data[bytes_in..];
+        match status {
+            TDEFLStatus::Done => return Ok(written),
+            TDEFLStatus::Okay if data.is_empty() && flush != TDEFLFlush::Finish => return Ok(written),
+            TDEFLStatus::Okay => (),
+            _ => return Err(ZipError::CompressionError)
+        }
+    }
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ZipEntryOptions, ZipWriter};
+    use crate::crc32::crc32;
+    use crate::zip_file::*;
+    use std::io::{Cursor, Write};
+
+    fn test_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| ((i * 7) % 251) as u8 ^ (i / 1000) as u8).collect()
+    }
+
+    fn read_back(zip: Vec<u8>) -> Vec<(CentralDirectoryFileHeader, Vec<u8>)> {
+        let mut zip = Cursor::new(zip);
+        let directory = zip_read_central_directory(&mut zip).unwrap();
+        directory
+            .file_headers
+            .into_iter()
+            .map(|header| {
+                let data = header.extract(&mut zip).unwrap();
+                assert_eq!(crc32(&data), header.crc32, "{}", header.file_name);
+                (header, data)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = b"hello hello hello zip".repeat(100);
+        let binary = test_data(300_000);
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_directory("assets").unwrap();
+        zip.add_file("assets/text.txt", &text, ZipEntryOptions::default()).unwrap();
+        zip.add_file("assets/stored.bin", &binary, ZipEntryOptions::stored()).unwrap();
+        zip.add_file("empty", &[], ZipEntryOptions::default()).unwrap();
+        zip.add_file(
+            "bin/tool",
+            b"#!/bin/sh",
+            ZipEntryOptions { unix_mode: 0o755, ..ZipEntryOptions::default() },
+        )
+        .unwrap();
+        zip.add_file("naïve.txt", b"utf8", ZipEntryOptions::default()).unwrap();
+        let files = read_back(zip.finish().unwrap());
+
+        let names: Vec<&str> = files.iter().map(|(header, _)| header.file_name.as_str()).collect();
+        assert_eq!(names, ["assets/", "assets/text.txt", "assets/stored.bin", "empty", "bin/tool", "naïve.txt"]);
+        assert!(files[0].1.is_empty());
+        assert_eq!(files[0].0.external_file_attributes & 0x10, 0x10);
+        assert_eq!(files[1].1, text);
+        assert_eq!(files[1].0.compression_method, COMPRESS_METHOD_DEFLATED);
+        assert!(files[1].0.compressed_size < 100);
+        assert_eq!(files[2].1, binary);
+        assert_eq!(files[2].0.compression_method, COMPRESS_METHOD_UNCOMPRESSED);
+        assert!(files[3].1.is_empty());
+        assert_eq!(files[4].0.external_file_attributes >> 16, 0o100755);
+        assert_eq!(files[5].0.general_purpose_bit_flag & (1 << 11), 1 << 11);
+    }
+
+    #[test]
+    fn streamed_files() {
+        let data = test_data(200_000);
+        let mut zip = ZipWriter::new(Vec::new());
+        assert!(matches!(zip.write_file_data(b"x"), Err(ZipError::NoFileStarted)));
+        zip.start_file("deflated.bin", ZipEntryOptions::default()).unwrap();
+        for chunk in data.chunks(777) {
+            zip.write_all(chunk).unwrap();
+        }
+        zip.start_file("stored.bin", ZipEntryOptions::stored()).unwrap();
+        for chunk in data.chunks(4096) {
+            zip.write_file_data(chunk).unwrap();
+        }
+        zip.finish_file().unwrap();
+        zip.add_file("after.txt", b"after", ZipEntryOptions::default()).unwrap();
+        let files = read_back(zip.finish().unwrap());
+
+        assert_eq!(files.len(), 3);
+        for (header, contents) in &files[..2] {
+            assert_eq!(header.general_purpose_bit_flag & (1 << 3), 1 << 3);
+            assert_eq!(contents, &data);
+        }
+        assert_eq!(files[2].1, b"after");
+    }
+
+    #[test]
+    fn zip64() {
+        let data = test_data(10_000);
+        let large = ZipEntryOptions { large_file: true, ..ZipEntryOptions::default() };
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("small.txt", b"small", ZipEntryOptions::default()).unwrap();
+        zip.add_file("large.bin", &data, large).unwrap();
+        zip.start_file("streamed.bin", large).unwrap();
+        zip.write_file_data(&data).unwrap();
+        let zip = zip.finish().unwrap();
+
+        let mut cursor = Cursor::new(zip.clone());
+        let directory = zip_read_central_directory(&mut cursor).unwrap();
+        assert_eq!(directory.eocd.total_entries_all_disk, u16::MAX);
+        assert_eq!(directory.zip64_eocd.as_ref().unwrap().total_entries_all_disk, 3);
+
+        let files = read_back(zip);
+        assert!(files[0].0.zip64.is_none());
+        assert_eq!(files[0].1, b"small");
+        for (header, contents) in &files[1..] {
+            assert_eq!(header.uncompressed_size, u32::MAX);
+            assert_eq!(header.uncompressed_size64(), data.len() as u64);
+            assert_eq!(contents, &data);
+        }
+    }
+}
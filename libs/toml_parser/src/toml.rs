@@ -1,397 +1,918 @@
-use std::collections::{HashMap};
-use std::str::Chars;
+// a toml 1.0 parser. the document becomes a tree of tables that keeps the order of the file,
+// and every value has the span of its text so the file can be edited in place
 
-#[derive(Default)]
-pub struct TomlParser {
-    pub cur: char,
-    pub pos: usize,
-}
-
-#[derive(PartialEq, Debug, Clone)]
+// byte offsets into the parsed text
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
 pub struct TomlSpan {
     pub start: usize,
     pub len: usize
 }
 
-#[derive(PartialEq, Debug)]
-pub struct TomlTokWithSpan {
-    pub span: TomlSpan,
-    pub tok: TomlTok
-}
-
-#[derive(PartialEq, Debug)]
-pub enum TomlTok {
-    Ident(String),
-    Str(String),
-    U64(u64),
-    I64(i64),
-    F64(f64),
-    Bool(bool),
-    Nan(bool),
-    Inf(bool),
-    Date(String),
-    Equals,
-    BlockOpen,
-    BlockClose,
-    ObjectOpen,
-    ObjectClose,
-    Comma,
-    Bof,
-    Eof
+impl TomlSpan {
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
 }
 
-
 #[derive(PartialEq, Debug, Clone)]
 pub enum Toml {
     Str(String, TomlSpan),
     Bool(bool, TomlSpan),
+    Int(i64, TomlSpan),
+    // floats, including inf and nan
     Num(f64, TomlSpan),
+    // offset and local date-times, dates and times, as written
     Date(String, TomlSpan),
-    Array(Vec<Toml>),
+    Array(Vec<Toml>, TomlSpan),
+    Table(TomlTable),
+}
+
+// how a table came to be, which decides what can be added to it later
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TomlTableKind {
+    Root,
+    // [table]
+    Header,
+    // one [[table]] of an array of tables
+    ArrayHeader,
+    // a in [a.b], it can still get a [a] header of its own
+    Implicit,
+    // a in a.b = 1
+    Dotted,
+    // {a = 1}, it cannot be added to at all
+    Inline,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct TomlEntry {
+    pub key: String,
+    pub key_span: TomlSpan,
+    // the whole key = value, or the [header] of a table
+    pub span: TomlSpan,
+    pub value: Toml,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct TomlTable {
+    pub kind: TomlTableKind,
+    // the header, the inline table, or the key that made the table
+    pub span: TomlSpan,
+    // where the next key of the table goes: after its last line, or at the } of an inline table
+    pub end: usize,
+    // in the order of the file
+    pub entries: Vec<TomlEntry>,
+}
+
+pub struct TomlErr {
+    pub msg: String,
+    pub span: TomlSpan,
+}
+
+impl std::fmt::Debug for TomlErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Toml error: {}, start:{} len:{}", self.msg, self.span.start, self.span.len)
+    }
+}
+
+impl TomlTable {
+    pub fn new(kind: TomlTableKind) -> Self {
+        Self {
+            kind,
+            span: TomlSpan::default(),
+            end: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Toml> {
+        self.entry(key).map( | entry | &entry.value)
+    }
+
+    pub fn entry(&self, key: &str) -> Option<&TomlEntry> {
+        self.entries.iter().find( | entry | entry.key == key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Toml)> {
+        self.entries.iter().map( | entry | (entry.key.as_str(), &entry.value))
+    }
+
+    pub fn insert(&mut self, key: &str, value: Toml) {
+        if let Some(entry) = self.entries.iter_mut().find( | entry | entry.key == key) {
+            entry.value = value;
+        }
+        else {
+            self.entries.push(TomlEntry {
+                key: key.to_string(),
+                key_span: TomlSpan::default(),
+                span: TomlSpan::default(),
+                value
+            });
+        }
+    }
 }
 
 impl Toml {
+    // looks up a dotted key like package.metadata.name or target.'cfg(windows)'.dependencies,
+    // a number picks an element of an array, like bin.0.name
+    pub fn get(&self, path: &str) -> Option<&Toml> {
+        self.lookup(&path_keys(path).ok() ?)
+    }
+
+    pub fn lookup(&self, keys: &[String]) -> Option<&Toml> {
+        keys.iter().try_fold(self, | value, key | match value {
+            Toml::Table(table) => table.get(key),
+            Toml::Array(values, _) => values.get(key.parse::<usize>().ok() ?),
+            _ => None
+        })
+    }
+
+    pub fn span(&self) -> TomlSpan {
+        match self {
+            Toml::Str(_, span) | Toml::Bool(_, span) | Toml::Int(_, span) | Toml::Num(_, span) |
+            Toml::Date(_, span) | Toml::Array(_, span) => *span,
+            Toml::Table(table) => table.span
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Toml::Str(value, _) => Some(value),
+            _ => None
+        }
+    }
+
     pub fn into_str(self) -> Option<String> {
         match self {
             Self::Str(v, _) => Some(v),
             _ => None
         }
     }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Toml::Bool(value, _) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Toml::Int(value, _) => Some(*value),
+            _ => None
+        }
+    }
+
+    // integers are converted
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Toml::Num(value, _) => Some(*value),
+            Toml::Int(value, _) => Some(*value as f64),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Toml]> {
+        match self {
+            Toml::Array(values, _) => Some(values),
+            _ => None
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&TomlTable> {
+        match self {
+            Toml::Table(table) => Some(table),
+            _ => None
+        }
+    }
+
+    // an array made with [[headers]], rather than a value
+    pub fn is_array_of_tables(&self) -> bool {
+        match self {
+            Toml::Array(values, _) => is_array_of_tables(values),
+            _ => false
+        }
+    }
+
+    // a table or array of tables that is written as [sections] of the file, rather than as a value
+    pub fn is_section(&self) -> bool {
+        match self {
+            Toml::Table(table) => table.kind != TomlTableKind::Inline,
+            value => value.is_array_of_tables()
+        }
+    }
 }
 
-pub struct TomlErr {
-    pub msg: String,
-    pub span: TomlSpan,
+impl From<&str> for Toml {
+    fn from(value: &str) -> Self {
+        Toml::Str(value.to_string(), TomlSpan::default())
+    }
 }
 
-impl std::fmt::Debug for TomlErr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Toml error: {}, start:{} len:{}", self.msg, self.span.start, self.span.len)
+impl From<String> for Toml {
+    fn from(value: String) -> Self {
+        Toml::Str(value, TomlSpan::default())
+    }
+}
+
+impl From<bool> for Toml {
+    fn from(value: bool) -> Self {
+        Toml::Bool(value, TomlSpan::default())
+    }
+}
+
+impl From<i64> for Toml {
+    fn from(value: i64) -> Self {
+        Toml::Int(value, TomlSpan::default())
+    }
+}
+
+impl From<f64> for Toml {
+    fn from(value: f64) -> Self {
+        Toml::Num(value, TomlSpan::default())
+    }
+}
+
+impl From<Vec<Toml>> for Toml {
+    fn from(values: Vec<Toml>) -> Self {
+        Toml::Array(values, TomlSpan::default())
+    }
+}
+
+impl From<TomlTable> for Toml {
+    fn from(table: TomlTable) -> Self {
+        Toml::Table(table)
+    }
+}
+
+fn is_array_of_tables(values: &[Toml]) -> bool {
+    matches!(values.first(), Some(Toml::Table(table)) if table.kind == TomlTableKind::ArrayHeader)
+}
+
+// the keys of a dotted key
+pub fn path_keys(path: &str) -> Result<Vec<String>, TomlErr> {
+    let mut t = TomlParser {data: path, pos: 0};
+    let keys = t.key() ?;
+    if t.pos != path.len() {
+        return Err(t.err("Unexpected character in key"))
     }
+    Ok(keys.into_iter().map( | (key, _) | key).collect())
 }
 
-pub fn parse_toml(data: &str) -> Result<HashMap<String, Toml>, TomlErr> {
-    let i = &mut data.chars();
-    let mut t = TomlParser::default();
-    t.next(i);
-    let mut out = HashMap::new();
-    let mut local_scope = String::new();
+// parses a whole document into its root table
+pub fn parse_toml(data: &str) -> Result<Toml, TomlErr> {
+    let mut t = TomlParser {data, pos: 0};
+    if data.starts_with('\u{feff}') {
+        t.pos = 3;
+    }
+    let mut root = TomlTable::new(TomlTableKind::Root);
+    root.span = TomlSpan {start: 0, len: data.len()};
+    // the keys of the last [header], key = value lines go in that table
+    let mut scope = Vec::new();
     loop {
-        let tok = t.next_tok(i) ?;
-        match tok.tok {
-            TomlTok::Eof => { // at eof.
-                return Ok(out);
-            },
-            TomlTok::BlockOpen => { // its a scope
-                // we should expect an ident or a string
-                let tok = t.next_tok(i) ?;
-                let (tok, double_block) = if let TomlTok::BlockOpen = tok.tok {
-                    (t.next_tok(i) ?, true)
-                }
-                else {(tok, false)};
-                
-                match tok.tok {
-                    TomlTok::Str(key) => { // a key
-                        local_scope = key;
-                    },
-                    TomlTok::Ident(key) => { // also a key
-                        local_scope = key;
-                    },
-                    _ => return Err(t.err_token(tok))
+        t.skip_ws();
+        match t.peek() {
+            None => return Ok(Toml::Table(root)),
+            Some(b'#' | b'\r' | b'\n') => t.end_of_line() ?,
+            Some(b'[') => {
+                let start = t.pos;
+                let is_array = t.rest().starts_with("[[");
+                t.pos += if is_array {2} else {1};
+                let keys = t.key() ?;
+                if !t.eat(b']') || is_array && !t.eat(b']') {
+                    return Err(t.err(if is_array {"Expected ]] after the table name"} else {"Expected ] after the table name"}))
                 }
-                let tok = t.next_tok(i) ?;
-                
-                if tok.tok != TomlTok::BlockClose {
-                    return Err(t.err_token(tok))
-                }
-                if double_block {
-                    let tok = t.next_tok(i) ?;
-                    if tok.tok != TomlTok::BlockClose {
-                        return Err(t.err_token(tok))
-                    }
-                }
-            },
-            TomlTok::Str(key) => { // a key
-                t.parse_key_value(&local_scope, key, i, &mut out) ?;
-            },
-            TomlTok::Ident(key) => { // also a key
-                t.parse_key_value(&local_scope, key, i, &mut out) ?;
-            },
-            _ => return Err(t.err_token(tok))
-        }
-    }
-}
-
-impl TomlParser {
-    pub fn to_val(&mut self, tok: TomlTokWithSpan, i: &mut Chars) -> Result<Toml, TomlErr> {
-        match tok.tok {
-            TomlTok::BlockOpen => {
-                let mut vals = Vec::new();
-                loop {
-                    let tok = self.next_tok(i) ?;
-                    match tok.tok {
-                        TomlTok::BlockClose | TomlTok::Eof => {
-                            break;
-                        }
-                        TomlTok::Comma => {}
-                        _ => {
-                            vals.push(self.to_val(tok, i) ?);
-                        }
-                    }
-                }
-                Ok(Toml::Array(vals))
-            },
-            TomlTok::Str(v) => Ok(Toml::Str(v, tok.span)),
-            TomlTok::U64(v) => Ok(Toml::Num(v as f64, tok.span)),
-            TomlTok::I64(v) => Ok(Toml::Num(v as f64, tok.span)),
-            TomlTok::F64(v) => Ok(Toml::Num(v, tok.span)),
-            TomlTok::Bool(v) => Ok(Toml::Bool(v, tok.span)),
-            TomlTok::Nan(v) => Ok(Toml::Num(if v {-std::f64::NAN}else {std::f64::NAN}, tok.span)),
-            TomlTok::Inf(v) => Ok(Toml::Num(if v {-std::f64::INFINITY}else {std::f64::INFINITY}, tok.span)),
-            TomlTok::Date(v) => Ok(Toml::Date(v, tok.span)),
-            _ => Err(self.err_token(tok))
-        }
-    }
-    
-    pub fn parse_key_value(&mut self, local_scope: &str, key: String, i: &mut Chars, out: &mut HashMap<String, Toml>) -> Result<(), TomlErr> {
-        let tok = self.next_tok(i) ?;
-        if tok.tok != TomlTok::Equals {
-            return Err(self.err_token(tok));
-        }
-        let tok = self.next_tok(i) ?;
-        // if we are an ObjectOpen we do a subscope
-        if let TomlTok::ObjectOpen = tok.tok {
-            let local_scope = if !local_scope.is_empty() {
-                format!("{}.{}", local_scope, key)
+                scope = keys.iter().map( | (key, _) | key.clone()).collect();
+                open_table(&mut root, keys, is_array, t.span_from(start)) ?;
+                t.end_of_line() ?;
+                scope_table(&mut root, &scope).end = t.pos;
             }
-            else {
-                key
-            };
-            loop {
-                let tok = self.next_tok(i) ?;
-                match tok.tok {
-                    TomlTok::ObjectClose | TomlTok::Eof => {
-                        break;
-                    }
-                    TomlTok::Comma => {}
-                    TomlTok::Str(key) => { // a key
-                        self.parse_key_value(&local_scope, key, i, out) ?;
-                    },
-                    TomlTok::Ident(key) => { // also a key
-                        self.parse_key_value(&local_scope, key, i, out) ?;
-                    },
-                    _ => return Err(self.err_token(tok))
+            Some(_) => {
+                let start = t.pos;
+                let keys = t.key() ?;
+                if !t.eat(b'=') {
+                    return Err(t.err("Expected = after the key"))
                 }
+                t.skip_ws();
+                let value = t.value() ?;
+                let table = scope_table(&mut root, &scope);
+                insert_dotted(table, keys, value, t.span_from(start)) ?;
+                t.end_of_line() ?;
+                table.end = t.pos;
             }
         }
-        else {
-            let val = self.to_val(tok, i) ?;
-            let key = if !local_scope.is_empty() {
-                format!("{}.{}", local_scope, key)
+    }
+}
+
+// finds or makes the table of a [header] or [[header]]
+fn open_table(root: &mut TomlTable, mut keys: Vec<(String, TomlSpan)>, is_array: bool, span: TomlSpan) -> Result<(), TomlErr> {
+    let (last_key, last_span) = keys.pop().unwrap();
+    let mut table = root;
+    for (key, key_span) in keys {
+        let index = match table.entries.iter().position( | entry | entry.key == key) {
+            Some(index) => index,
+            None => {
+                let mut implicit = TomlTable::new(TomlTableKind::Implicit);
+                implicit.span = key_span;
+                table.entries.push(TomlEntry {key, key_span, span, value: Toml::Table(implicit)});
+                table.entries.len() - 1
             }
-            else {
-                key
-            };
-            out.insert(key, val);
+        };
+        let entry = &mut table.entries[index];
+        table = match &mut entry.value {
+            Toml::Table(table) if table.kind != TomlTableKind::Inline => table,
+            // a header inside an array of tables goes into its last table
+            Toml::Array(values, _) if is_array_of_tables(values) => match values.last_mut() {
+                Some(Toml::Table(table)) => table,
+                _ => unreachable!()
+            },
+            _ => return Err(TomlErr {msg: format!("Key {} is already defined as a value", entry.key), span: key_span})
+        };
+    }
+    let Some(index) = table.entries.iter().position( | entry | entry.key == last_key) else {
+        let kind = if is_array {TomlTableKind::ArrayHeader} else {TomlTableKind::Header};
+        let mut new_table = TomlTable::new(kind);
+        new_table.span = span;
+        let value = if is_array {Toml::Array(vec![Toml::Table(new_table)], span)} else {Toml::Table(new_table)};
+        table.entries.push(TomlEntry {key: last_key, key_span: last_span, span, value});
+        return Ok(())
+    };
+    let entry = &mut table.entries[index];
+    match &mut entry.value {
+        Toml::Array(values, _) if is_array && is_array_of_tables(values) => {
+            let mut new_table = TomlTable::new(TomlTableKind::ArrayHeader);
+            new_table.span = span;
+            values.push(Toml::Table(new_table));
         }
-        Ok(())
+        Toml::Table(table) if !is_array && table.kind == TomlTableKind::Implicit => {
+            table.kind = TomlTableKind::Header;
+            table.span = span;
+            entry.span = span;
+        }
+        _ => return Err(TomlErr {msg: format!("Table {} is defined twice", last_key), span: last_span})
     }
-    
-    pub fn next(&mut self, i: &mut Chars) {
-        if let Some(c) = i.next() {
-            self.cur = c;
+    Ok(())
+}
+
+// the table of the current [header]
+fn scope_table<'a>(root: &'a mut TomlTable, scope: &[String]) -> &'a mut TomlTable {
+    let mut table = root;
+    for key in scope {
+        let entry = table.entries.iter_mut().find( | entry | &entry.key == key).unwrap();
+        table = match &mut entry.value {
+            Toml::Table(table) => table,
+            Toml::Array(values, _) => match values.last_mut() {
+                Some(Toml::Table(table)) => table,
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        };
+    }
+    table
+}
+
+// adds a.b.c = value, making a and b when they are not there
+fn insert_dotted(table: &mut TomlTable, mut keys: Vec<(String, TomlSpan)>, value: Toml, span: TomlSpan) -> Result<(), TomlErr> {
+    let (last_key, last_span) = keys.pop().unwrap();
+    let mut table = table;
+    for (key, key_span) in keys {
+        let index = match table.entries.iter().position( | entry | entry.key == key) {
+            Some(index) => index,
+            None => {
+                let mut dotted = TomlTable::new(TomlTableKind::Dotted);
+                dotted.span = key_span;
+                table.entries.push(TomlEntry {key, key_span, span, value: Toml::Table(dotted)});
+                table.entries.len() - 1
+            }
+        };
+        let entry = &mut table.entries[index];
+        table = match &mut entry.value {
+            Toml::Table(table) if table.kind == TomlTableKind::Dotted => table,
+            _ => return Err(TomlErr {msg: format!("Key {} is already defined", entry.key), span: key_span})
+        };
+    }
+    if table.entry(&last_key).is_some() {
+        return Err(TomlErr {msg: format!("Key {} is defined twice", last_key), span: last_span})
+    }
+    table.entries.push(TomlEntry {key: last_key, key_span: last_span, span, value});
+    Ok(())
+}
+
+struct TomlParser<'a> {
+    data: &'a str,
+    pos: usize,
+}
+
+impl<'a> TomlParser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.data.as_bytes().get(self.pos).copied()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.data[self.pos..]
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        if self.peek() == Some(c) {
             self.pos += 1;
+            return true
         }
-        else {
-            self.cur = '\0';
+        false
+    }
+
+    fn span_from(&self, start: usize) -> TomlSpan {
+        TomlSpan {start, len: self.pos - start}
+    }
+
+    fn err(&self, msg: &str) -> TomlErr {
+        TomlErr {msg: msg.to_string(), span: TomlSpan {start: self.pos, len: 0}}
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn newline(&mut self) -> bool {
+        if self.rest().starts_with("\r\n") {
+            self.pos += 2;
+            return true
+        }
+        self.eat(b'\n')
+    }
+
+    fn comment(&mut self) -> Result<(), TomlErr> {
+        if self.peek() != Some(b'#') {
+            return Ok(())
         }
+        while let Some(c) = self.peek() {
+            match c {
+                b'\n' => break,
+                b'\r' if self.rest().starts_with("\r\n") => break,
+                b'\t' => (),
+                c if c < 0x20 || c == 0x7f => return Err(self.err("Control characters are not allowed in comments")),
+                _ => ()
+            }
+            self.pos += 1;
+        }
+        Ok(())
     }
-    
-    pub fn err_token(&self, tok: TomlTokWithSpan) -> TomlErr {
-        TomlErr {msg: format!("Unexpected token {:?} ", tok), span: tok.span}
+
+    fn end_of_line(&mut self) -> Result<(), TomlErr> {
+        self.skip_ws();
+        self.comment() ?;
+        if self.peek().is_none() || self.newline() {
+            return Ok(())
+        }
+        Err(self.err("Expected the end of the line"))
     }
-    
-    pub fn err_parse(&self, what: &str) -> TomlErr {
-        TomlErr {msg: format!("Cannot parse toml {} ", what), span: TomlSpan {start: self.pos, len: 0}}
+
+    // whitespace, comments and newlines, as allowed inside arrays
+    fn skip_ws_lines(&mut self) -> Result<(), TomlErr> {
+        loop {
+            self.skip_ws();
+            self.comment() ?;
+            if !self.newline() {
+                return Ok(())
+            }
+        }
     }
-    
-    pub fn next_tok(&mut self, i: &mut Chars) -> Result<TomlTokWithSpan, TomlErr> {
-        
-        while self.cur == '\n' || self.cur == '\r' || self.cur == '\t' || self.cur == ' ' || self.cur == '#' {
-            if self.cur == '#' {
-                while self.cur != '\n' && self.cur != '\0' {
-                    self.next(i);
+
+    fn key(&mut self) -> Result<Vec<(String, TomlSpan)>, TomlErr> {
+        let mut keys = Vec::new();
+        loop {
+            self.skip_ws();
+            let start = self.pos;
+            let key = match self.peek() {
+                Some(b'"') => self.basic_string() ?,
+                Some(b'\'') => self.literal_string() ?,
+                _ => {
+                    while matches!(self.peek(), Some(b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-')) {
+                        self.pos += 1;
+                    }
+                    if self.pos == start {
+                        return Err(self.err("Expected a key"))
+                    }
+                    self.data[start..self.pos].to_string()
                 }
-                continue
+            };
+            keys.push((key, self.span_from(start)));
+            self.skip_ws();
+            if !self.eat(b'.') {
+                return Ok(keys)
             }
-            self.next(i);
         }
+    }
+
+    fn value(&mut self) -> Result<Toml, TomlErr> {
         let start = self.pos;
-        match self.cur {
-            '\0' => {
-                Ok(TomlTokWithSpan {tok: TomlTok::Eof, span: TomlSpan {start, len: 0}})
-            }
-            ',' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Comma, span: TomlSpan {start, len: 1}})
-            }
-            '[' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::BlockOpen, span: TomlSpan {start, len: 1}})
+        match self.peek() {
+            Some(b'"') => {
+                let value = if self.rest().starts_with("\"\"\"") {
+                    self.multiline_basic_string() ?
+                }
+                else {
+                    self.basic_string() ?
+                };
+                Ok(Toml::Str(value, self.span_from(start)))
             }
-            ']' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::BlockClose, span: TomlSpan {start, len: 1}})
+            Some(b'\'') => {
+                let value = if self.rest().starts_with("'''") {
+                    self.multiline_literal_string() ?
+                }
+                else {
+                    self.literal_string() ?
+                };
+                Ok(Toml::Str(value, self.span_from(start)))
             }
-            '{' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::ObjectOpen, span: TomlSpan {start, len: 1}})
+            Some(b'[') => self.array(),
+            Some(b'{') => Ok(Toml::Table(self.inline_table() ?)),
+            _ => self.scalar()
+        }
+    }
+
+    fn array(&mut self) -> Result<Toml, TomlErr> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut values = Vec::new();
+        loop {
+            self.skip_ws_lines() ?;
+            if self.eat(b']') {
+                break
             }
-            '}' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::ObjectClose, span: TomlSpan {start, len: 1}})
+            values.push(self.value() ?);
+            self.skip_ws_lines() ?;
+            if self.eat(b']') {
+                break
             }
-            '=' => {
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Equals, span: TomlSpan {start, len: 1}})
+            if !self.eat(b',') {
+                return Err(self.err("Expected , or ] in the array"))
             }
-            '+' | '-' | '0'..='9' => {
-                let mut num = String::new();
-                let is_neg = if self.cur == '-' {
-                    num.push(self.cur);
-                    self.next(i);
-                    true
-                }
-                else {
-                    if self.cur == '+' {
-                        self.next(i);
-                    }
-                    false
-                };
-                if self.cur == 'n' {
-                    self.next(i);
-                    if self.cur == 'a' {
-                        self.next(i);
-                        if self.cur == 'n' {
-                            self.next(i);
-                            return Ok(TomlTokWithSpan {tok: TomlTok::Nan(is_neg), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("nan"))
-                        }
-                    }
-                    else {
-                        return Err(self.err_parse("nan"))
-                    }
+        }
+        Ok(Toml::Array(values, self.span_from(start)))
+    }
+
+    fn inline_table(&mut self) -> Result<TomlTable, TomlErr> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut table = TomlTable::new(TomlTableKind::Inline);
+        self.skip_ws();
+        if self.peek() != Some(b'}') {
+            loop {
+                self.skip_ws();
+                let entry_start = self.pos;
+                let keys = self.key() ?;
+                if !self.eat(b'=') {
+                    return Err(self.err("Expected = after the key"))
                 }
-                if self.cur == 'i' {
-                    self.next(i);
-                    if self.cur == 'n' {
-                        self.next(i);
-                        if self.cur == 'f' {
-                            self.next(i);
-                            return Ok(TomlTokWithSpan {tok: TomlTok::Inf(is_neg), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("inf"))
-                        }
-                    }
-                    else {
-                        return Err(self.err_parse("nan"))
+                self.skip_ws();
+                let value = self.value() ?;
+                insert_dotted(&mut table, keys, value, self.span_from(entry_start)) ?;
+                self.skip_ws();
+                if self.eat(b',') {
+                    self.skip_ws();
+                    if self.peek() == Some(b'}') {
+                        return Err(self.err("Inline tables cannot have a trailing comma"))
                     }
+                    continue
                 }
-                while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                    if self.cur != '_' {
-                        num.push(self.cur);
-                    }
-                    self.next(i);
+                if self.peek() == Some(b'}') {
+                    break
                 }
-                if self.cur == '.' {
-                    num.push(self.cur);
-                    self.next(i);
-                    while self.cur >= '0' && self.cur <= '9' || self.cur == '_' {
-                        if self.cur != '_' {
-                            num.push(self.cur);
-                        }
-                        self.next(i);
-                    }
-                    if let Ok(num) = num.parse() {
-                        Ok(TomlTokWithSpan {tok: TomlTok::F64(num), span: TomlSpan {start, len: self.pos - start}})
-                    }
-                    else {
-                        Err(self.err_parse("number"))
-                    }
+                return Err(self.err("Expected , or } in the inline table, which has to stay on one line"))
+            }
+        }
+        table.end = self.pos;
+        self.pos += 1;
+        table.span = self.span_from(start);
+        Ok(table)
+    }
+
+    // booleans, numbers and dates
+    fn scalar(&mut self) -> Result<Toml, TomlErr> {
+        let start = self.pos;
+        let token_end = | from: usize | from + self.data[from..].find([' ', '\t', '\r', '\n', ',', ']', '}', '#']).unwrap_or(self.data.len() - from);
+        let mut end = token_end(start);
+        // a date and a time can be separated by a space
+        let after = &self.data.as_bytes()[end..];
+        if is_date(&self.data[start..end]) && after.len() >= 4 && after[0] == b' ' && after[1..3].iter().all(u8::is_ascii_digit) && after[3] == b':' {
+            end = token_end(end + 1);
+        }
+        let text = &self.data[start..end];
+        self.pos = end;
+        let span = self.span_from(start);
+        if text.is_empty() {
+            return Err(self.err("Expected a value"))
+        }
+        match text {
+            "true" => return Ok(Toml::Bool(true, span)),
+            "false" => return Ok(Toml::Bool(false, span)),
+            _ => ()
+        }
+        let bytes = text.as_bytes();
+        if text.contains(':') || bytes.len() >= 10 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-' {
+            if is_date_time(text) {
+                return Ok(Toml::Date(text.to_string(), span))
+            }
+            return Err(TomlErr {msg: format!("Invalid date or time {}", text), span})
+        }
+        number(text, span).ok_or_else( || TomlErr {msg: format!("Cannot parse value {}", text), span})
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlErr> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.rest().chars().next() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(out)
                 }
-                else if self.cur == '-' { // lets assume its a date. whatever. i don't feel like more parsing today
-                    num.push(self.cur);
-                    self.next(i);
-                    while self.cur >= '0' && self.cur <= '9' || self.cur == ':' || self.cur == '-' || self.cur == 'T' {
-                        num.push(self.cur);
-                        self.next(i);
-                    }
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Date(num), span: TomlSpan {start, len: self.pos - start}})
+                Some('\\') => self.escape(&mut out) ?,
+                None | Some('\n' | '\r') => return Err(TomlErr {msg: "Unterminated string".to_string(), span: self.span_from(start)}),
+                Some(c) if is_control(c) => return Err(self.err("Control characters in strings have to be escaped")),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
                 }
-                else {
-                    if is_neg {
-                        if let Ok(num) = num.parse() {
-                            return Ok(TomlTokWithSpan {tok: TomlTok::I64(num), span: TomlSpan {start, len: self.pos - start}})
-                        }
-                        else {
-                            return Err(self.err_parse("number"));
-                        }
-                    }
-                    if let Ok(num) = num.parse() {
-                        return Ok(TomlTokWithSpan {tok: TomlTok::U64(num), span: TomlSpan {start, len: self.pos - start}})
+            }
+        }
+    }
+
+    fn multiline_basic_string(&mut self) -> Result<String, TomlErr> {
+        let start = self.pos;
+        self.pos += 3;
+        // a newline right after the quotes is not part of the string
+        self.newline();
+        let mut out = String::new();
+        loop {
+            if self.rest().starts_with("\"\"\"") {
+                return self.end_multiline(out, b'"')
+            }
+            match self.rest().chars().next() {
+                Some('\\') => {
+                    // a backslash at the end of a line removes the newline and the whitespace after it
+                    let after = self.rest()[1..].trim_start_matches([' ', '\t']);
+                    if after.starts_with('\n') || after.starts_with("\r\n") {
+                        self.pos = self.data.len() - after.len();
+                        while self.newline() || self.eat(b' ') || self.eat(b'\t') {}
                     }
                     else {
-                        return Err(self.err_parse("number"));
+                        self.escape(&mut out) ?;
                     }
                 }
-            },
-            'a'..='z' | 'A'..='Z' | '_' => {
-                let mut ident = String::new();
-                while self.cur >= 'a' && self.cur <= 'z'
-                    || self.cur >= 'A' && self.cur <= 'Z'
-                    || self.cur >= '0' && self.cur <= '9'
-                    || self.cur == '_' || self.cur == '-' || self.cur == '.'
-                    || self.cur == '\'' || self.cur == '(' || self.cur == ')' {
-                    ident.push(self.cur);
-                    self.next(i);
-                }
-                if ident == "true" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Bool(true), span: TomlSpan {start, len: self.pos - start}})
+                Some('\n') => {
+                    out.push('\n');
+                    self.pos += 1;
                 }
-                if ident == "false" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Bool(false), span: TomlSpan {start, len: self.pos - start}})
+                Some('\r') if self.newline() => out.push('\n'),
+                None => return Err(TomlErr {msg: "Unterminated string".to_string(), span: self.span_from(start)}),
+                Some(c) if is_control(c) => return Err(self.err("Control characters in strings have to be escaped")),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
                 }
-                if ident == "inf" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Inf(false), span: TomlSpan {start, len: self.pos - start}})
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlErr> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.rest().chars().next() {
+                Some('\'') => {
+                    self.pos += 1;
+                    return Ok(self.data[start + 1..self.pos - 1].to_string())
                 }
-                if ident == "nan" {
-                    return Ok(TomlTokWithSpan {tok: TomlTok::Nan(false), span: TomlSpan {start, len: self.pos - start}})
+                None | Some('\n' | '\r') => return Err(TomlErr {msg: "Unterminated string".to_string(), span: self.span_from(start)}),
+                Some(c) if is_control(c) => return Err(self.err("Control characters are not allowed in literal strings")),
+                Some(c) => self.pos += c.len_utf8()
+            }
+        }
+    }
+
+    fn multiline_literal_string(&mut self) -> Result<String, TomlErr> {
+        let start = self.pos;
+        self.pos += 3;
+        self.newline();
+        let mut out = String::new();
+        loop {
+            if self.rest().starts_with("'''") {
+                return self.end_multiline(out, b'\'')
+            }
+            match self.rest().chars().next() {
+                Some('\n') => {
+                    out.push('\n');
+                    self.pos += 1;
                 }
-                Ok(TomlTokWithSpan {tok: TomlTok::Ident(ident), span: TomlSpan {start, len: self.pos - start}})
-            },
-            
-            '"' => {
-                let mut val = String::new();
-                self.next(i);
-                while self.cur != '"' {
-                    if self.cur == '\\' {
-                        self.next(i);
-                    }
-                    if self.cur == '\0' {
-                        return Err(self.err_parse("string"));
-                    }
-                    val.push(self.cur);
-                    self.next(i);
+                Some('\r') if self.newline() => out.push('\n'),
+                None => return Err(TomlErr {msg: "Unterminated string".to_string(), span: self.span_from(start)}),
+                Some(c) if is_control(c) => return Err(self.err("Control characters are not allowed in literal strings")),
+                Some(c) => {
+                    out.push(c);
+                    self.pos += c.len_utf8();
                 }
-                self.next(i);
-                Ok(TomlTokWithSpan {tok: TomlTok::Str(val), span: TomlSpan {start, len: self.pos - start}})
-            },
-            _ => {
-                Err(self.err_parse("tokenizer"))
             }
         }
     }
-}
\ No newline at end of file
+
+    // up to two quotes can come right before the closing three
+    fn end_multiline(&mut self, mut out: String, quote: u8) -> Result<String, TomlErr> {
+        let quotes = self.rest().bytes().take_while( | c | *c == quote).count();
+        if quotes > 5 {
+            return Err(self.err("Too many quotes at the end of the string"))
+        }
+        for _ in 3..quotes {
+            out.push(quote as char);
+        }
+        self.pos += quotes;
+        Ok(out)
+    }
+
+    fn escape(&mut self, out: &mut String) -> Result<(), TomlErr> {
+        let start = self.pos;
+        self.pos += 2;
+        let c = match self.data.as_bytes().get(start + 1) {
+            Some(b'b') => '\u{8}',
+            Some(b't') => '\t',
+            Some(b'n') => '\n',
+            Some(b'f') => '\u{c}',
+            Some(b'r') => '\r',
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'u') => self.unicode_escape(start, 4) ?,
+            Some(b'U') => self.unicode_escape(start, 8) ?,
+            _ => return Err(TomlErr {msg: "Invalid escape".to_string(), span: TomlSpan {start, len: 2}})
+        };
+        out.push(c);
+        Ok(())
+    }
+
+    fn unicode_escape(&mut self, start: usize, len: usize) -> Result<char, TomlErr> {
+        let c = self.data.get(self.pos..self.pos + len)
+            .filter( | hex | hex.bytes().all( | c | c.is_ascii_hexdigit()))
+            .and_then( | hex | u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32);
+        self.pos += len;
+        c.ok_or_else( || TomlErr {msg: "Invalid unicode escape".to_string(), span: self.span_from(start)})
+    }
+}
+
+fn is_control(c: char) -> bool {
+    c < ' ' && c != '\t' || c == '\u{7f}'
+}
+
+fn number(text: &str, span: TomlSpan) -> Option<Toml> {
+    let (negative, rest) = match text.as_bytes()[0] {
+        b'+' => (false, &text[1..]),
+        b'-' => (true, &text[1..]),
+        _ => (false, text)
+    };
+    match rest {
+        "inf" => return Some(Toml::Num(if negative {f64::NEG_INFINITY} else {f64::INFINITY}, span)),
+        "nan" => return Some(Toml::Num(if negative {-f64::NAN} else {f64::NAN}, span)),
+        _ => ()
+    }
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = rest.strip_prefix(prefix) {
+            // these have no sign
+            if rest.len() != text.len() {
+                return None
+            }
+            let (digits, tail) = digits_with_underscores(digits, radix) ?;
+            if !tail.is_empty() {
+                return None
+            }
+            return i64::from_str_radix(&digits, radix).ok().map( | value | Toml::Int(value, span))
+        }
+    }
+    let (int, mut tail) = digits_with_underscores(rest, 10) ?;
+    if int.len() > 1 && int.starts_with('0') {
+        return None
+    }
+    let mut clean = format!("{}{}", if negative {"-"} else {""}, int);
+    let mut is_float = false;
+    if let Some(fraction) = tail.strip_prefix('.') {
+        let (fraction, rest) = digits_with_underscores(fraction, 10) ?;
+        clean.push('.');
+        clean.push_str(&fraction);
+        tail = rest;
+        is_float = true;
+    }
+    if let Some(exponent) = tail.strip_prefix(['e', 'E']) {
+        let (sign, exponent) = match exponent.strip_prefix(['+', '-']) {
+            Some(rest) => (&exponent[..1], rest),
+            None => ("", exponent)
+        };
+        let (exponent, rest) = digits_with_underscores(exponent, 10) ?;
+        clean.push('e');
+        clean.push_str(sign);
+        clean.push_str(&exponent);
+        tail = rest;
+        is_float = true;
+    }
+    if !tail.is_empty() {
+        return None
+    }
+    if is_float {
+        clean.parse().ok().map( | value | Toml::Num(value, span))
+    }
+    else {
+        clean.parse().ok().map( | value | Toml::Int(value, span))
+    }
+}
+
+// digits where every _ sits between two digits, and what comes after them
+fn digits_with_underscores(text: &str, radix: u32) -> Option<(String, &str)> {
+    let mut digits = String::new();
+    let mut rest = text;
+    loop {
+        let len = rest.find( | c: char | !c.is_digit(radix)).unwrap_or(rest.len());
+        if len == 0 {
+            return None
+        }
+        digits.push_str(&rest[..len]);
+        rest = &rest[len..];
+        match rest.strip_prefix('_') {
+            Some(after) => rest = after,
+            None => return Some((digits, rest))
+        }
+    }
+}
+
+fn digits(text: &[u8]) -> Option<u32> {
+    if text.is_empty() || !text.iter().all(u8::is_ascii_digit) {
+        return None
+    }
+    Some(text.iter().fold(0, | value, c | value * 10 + (c - b'0') as u32))
+}
+
+// YYYY-MM-DD
+fn is_date(text: &str) -> bool {
+    let b = text.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false
+    }
+    let (Some(year), Some(month), Some(day)) = (digits(&b[0..4]), digits(&b[5..7]), digits(&b[8..10])) else {
+        return false
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false
+    };
+    (1..=days).contains(&day)
+}
+
+// HH:MM:SS with optional fractional seconds
+fn is_time(text: &str) -> bool {
+    let b = text.as_bytes();
+    if b.len() < 8 || b[2] != b':' || b[5] != b':' {
+        return false
+    }
+    let (Some(hour), Some(minute), Some(second)) = (digits(&b[0..2]), digits(&b[3..5]), digits(&b[6..8])) else {
+        return false
+    };
+    let fraction_ok = match &b[8..] {
+        [] => true,
+        [b'.', fraction @ ..] => !fraction.is_empty() && fraction.iter().all(u8::is_ascii_digit),
+        _ => false
+    };
+    hour < 24 && minute < 60 && second <= 60 && fraction_ok
+}
+
+fn is_date_time(text: &str) -> bool {
+    if is_time(text) {
+        return true
+    }
+    let Some(date) = text.get(..10) else {
+        return false
+    };
+    if !is_date(date) {
+        return false
+    }
+    let rest = &text[10..];
+    if rest.is_empty() {
+        return true
+    }
+    let Some(rest) = rest.strip_prefix(['T', 't', ' ']) else {
+        return false
+    };
+    let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+        Some(index) => rest.split_at(index),
+        None => (rest, "")
+    };
+    let offset_ok = match offset.as_bytes() {
+        [] | [b'Z'] | [b'z'] => true,
+        [b'+' | b'-', h1, h2, b':', m1, m2] => {
+            matches!((digits(&[*h1, *h2]), digits(&[*m1, *m2])), (Some(hour), Some(minute)) if hour < 24 && minute < 60)
+        }
+        _ => false
+    };
+    is_time(time) && offset_ok
+}
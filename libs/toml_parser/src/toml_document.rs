@@ -0,0 +1,253 @@
+use crate::toml::*;
+
+// writes a key bare when it can be, and quoted otherwise
+pub fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.bytes().all( | c | c.is_ascii_alphanumeric() || c == b'_' || c == b'-') {
+        return key.to_string()
+    }
+    toml_string(key)
+}
+
+pub fn toml_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' || c == '\u{7f}' => out.push_str(&format!("\\u{:04X}", c as u32)),
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Toml {
+    // the value as it would be written after key =, tables become inline tables
+    pub fn to_toml_value(&self) -> String {
+        match self {
+            Toml::Str(value, _) => toml_string(value),
+            Toml::Bool(value, _) => format!("{}", value),
+            Toml::Int(value, _) => format!("{}", value),
+            Toml::Num(value, _) if value.is_nan() => "nan".to_string(),
+            Toml::Num(value, _) if value.is_infinite() => if *value > 0.0 {"inf"} else {"-inf"}.to_string(),
+            Toml::Num(value, _) => format!("{:?}", value),
+            Toml::Date(value, _) => value.clone(),
+            Toml::Array(values, _) => {
+                let values: Vec<String> = values.iter().map( | value | value.to_toml_value()).collect();
+                format!("[{}]", values.join(", "))
+            }
+            Toml::Table(table) => {
+                if table.entries.is_empty() {
+                    return "{}".to_string()
+                }
+                let entries: Vec<String> = table.entries.iter().map( | entry | {
+                    format!("{} = {}", toml_key(&entry.key), entry.value.to_toml_value())
+                }).collect();
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+}
+
+impl TomlTable {
+    // writes the table as a document, its values first and then its [sections]
+    pub fn to_toml(&self) -> String {
+        let mut out = String::new();
+        self.write_section(&mut out, "");
+        out
+    }
+
+    fn write_section(&self, out: &mut String, path: &str) {
+        for entry in &self.entries {
+            if !entry.value.is_section() {
+                out.push_str(&format!("{} = {}\n", toml_key(&entry.key), entry.value.to_toml_value()));
+            }
+        }
+        for entry in &self.entries {
+            let path = if path.is_empty() {toml_key(&entry.key)} else {format!("{}.{}", path, toml_key(&entry.key))};
+            match &entry.value {
+                Toml::Table(table) if table.kind != TomlTableKind::Inline => {
+                    // a table that only holds other sections does not need a header
+                    if table.entries.is_empty() || table.entries.iter().any( | entry | !entry.value.is_section()) {
+                        start_section(out, &format!("[{}]", path));
+                    }
+                    table.write_section(out, &path);
+                }
+                Toml::Array(values, _) if entry.value.is_array_of_tables() => for value in values {
+                    if let Toml::Table(table) = value {
+                        start_section(out, &format!("[[{}]]", path));
+                        table.write_section(out, &path);
+                    }
+                }
+                _ => ()
+            }
+        }
+    }
+}
+
+fn start_section(out: &mut String, header: &str) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(header);
+    out.push('\n');
+}
+
+fn dotted_key(keys: &[String]) -> String {
+    keys.iter().map( | key | toml_key(key)).collect::<Vec<_>>().join(".")
+}
+
+// a toml file that can be edited while keeping its comments, order and formatting.
+// only the text of the values that change is rewritten
+pub struct TomlDocument {
+    source: String,
+    root: Toml,
+}
+
+impl TomlDocument {
+    pub fn parse(source: &str) -> Result<Self, TomlErr> {
+        Ok(Self {
+            root: parse_toml(source) ?,
+            source: source.to_string()
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn into_string(self) -> String {
+        self.source
+    }
+
+    pub fn root(&self) -> &TomlTable {
+        self.root.as_table().unwrap()
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Toml> {
+        self.root.get(path)
+    }
+
+    // sets the value at a dotted path. an existing value is replaced where it is,
+    // a new key goes at the end of the table that should hold it
+    pub fn set(&mut self, path: &str, value: impl Into<Toml>) -> Result<(), TomlErr> {
+        let keys = path_keys(path) ?;
+        let text = value.into().to_toml_value();
+        if let Some(old) = self.root.lookup(&keys) {
+            if old.is_section() || matches!(old, Toml::Table(table) if table.kind == TomlTableKind::Dotted) {
+                return Err(TomlErr {msg: format!("Cannot replace the table {}", path), span: old.span()})
+            }
+            let span = old.span();
+            return self.splice(span.start, span.end(), &text)
+        }
+        // the deepest table that is already there
+        let mut depth = keys.len() - 1;
+        while depth > 0 && self.root.lookup(&keys[..depth]).is_none() {
+            depth -= 1;
+        }
+        // keys under a.b = 1 are added as dotted keys of the table that holds a
+        while depth > 0 && matches!(self.root.lookup(&keys[..depth]), Some(Toml::Table(table)) if table.kind == TomlTableKind::Dotted) {
+            depth -= 1;
+        }
+        let parent = self.root.lookup(&keys[..depth]).unwrap();
+        let Toml::Table(table) = parent else {
+            return Err(TomlErr {msg: format!("{} is not a table", dotted_key(&keys[..depth])), span: parent.span()})
+        };
+        let rest = &keys[depth..];
+        match table.kind {
+            TomlTableKind::Inline => {
+                let at = self.source[..table.end].trim_end().len();
+                let text = if table.entries.is_empty() {
+                    format!(" {} = {} ", dotted_key(rest), text)
+                }
+                else {
+                    format!(", {} = {}", dotted_key(rest), text)
+                };
+                self.splice(at, at, &text)
+            }
+            TomlTableKind::Root if rest.len() > 1 => {
+                self.append_section(&keys[..keys.len() - 1], keys.last().unwrap(), &text)
+            }
+            TomlTableKind::Implicit => {
+                self.append_section(&keys[..keys.len() - 1], keys.last().unwrap(), &text)
+            }
+            _ => {
+                let at = table.end;
+                let newline = if at > 0 && !self.source[..at].ends_with('\n') {"\n"} else {""};
+                let text = format!("{}{} = {}\n", newline, dotted_key(rest), text);
+                self.splice(at, at, &text)
+            }
+        }
+    }
+
+    // removes a value, along with its line, or its comma in an inline table or array.
+    // returns false when it was not there
+    pub fn remove(&mut self, path: &str) -> Result<bool, TomlErr> {
+        let keys = path_keys(path) ?;
+        let Some(old) = self.root.lookup(&keys) else {
+            return Ok(false)
+        };
+        if old.is_section() || matches!(old, Toml::Table(table) if table.kind == TomlTableKind::Dotted) {
+            return Err(TomlErr {msg: format!("Cannot remove the table {}", path), span: old.span()})
+        }
+        let (span, in_list) = match self.root.lookup(&keys[..keys.len() - 1]).unwrap() {
+            Toml::Array(_, _) => (old.span(), true),
+            Toml::Table(table) => (table.entry(keys.last().unwrap()).unwrap().span, table.kind == TomlTableKind::Inline),
+            _ => unreachable!()
+        };
+        let bytes = self.source.as_bytes();
+        let (mut start, mut end) = (span.start, span.end());
+        if in_list {
+            let after = end + self.source[end..].len() - self.source[end..].trim_start_matches([' ', '\t']).len();
+            if bytes.get(after) == Some(&b',') {
+                end = after + 1 + self.source[after + 1..].len() - self.source[after + 1..].trim_start_matches([' ', '\t']).len();
+            }
+            else {
+                let before = self.source[..start].trim_end();
+                if before.ends_with(',') {
+                    start = before.len() - 1;
+                }
+            }
+        }
+        else {
+            start = self.source[..start].rfind('\n').map( | i | i + 1).unwrap_or(0);
+            end = self.source[end..].find('\n').map( | i | end + i + 1).unwrap_or(self.source.len());
+        }
+        self.splice(start, end, "") ?;
+        Ok(true)
+    }
+
+    // adds a [section] at the end of the file holding key = value
+    fn append_section(&mut self, section: &[String], key: &str, text: &str) -> Result<(), TomlErr> {
+        // a header cannot pick an element of an array of tables other than the last
+        for i in 1..=section.len() {
+            if let Some(Toml::Array(_, span)) = self.root.lookup(&section[..i]) {
+                return Err(TomlErr {msg: format!("Cannot add a section inside the array {}", dotted_key(&section[..i])), span: *span})
+            }
+        }
+        let mut out = String::new();
+        if !self.source.is_empty() {
+            if !self.source.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        out.push_str(&format!("[{}]\n{} = {}\n", dotted_key(section), toml_key(key), text));
+        let at = self.source.len();
+        self.splice(at, at, &out)
+    }
+
+    // replaces a piece of the file, leaving the document as it was if the result does not parse
+    fn splice(&mut self, start: usize, end: usize, text: &str) -> Result<(), TomlErr> {
+        let source = format!("{}{}{}", &self.source[..start], text, &self.source[end..]);
+        self.root = parse_toml(&source) ?;
+        self.source = source;
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use makepad_toml_parser::*;
+
+const CARGO: &str = r#"# a workspace
+[package]
+name = "app"   # the name
+version = "0.1.0"
+metadata.makepad-auto-version = "abc="
+
+[dependencies]
+makepad-widgets = { path = "../widgets", version = "0.6.0" }
+serde = "1.0"
+
+[target.'cfg(windows)'.dependencies]
+windows = { version = "0.48", features = ["Win32_Foundation"] }
+
+[[bin]]
+name = "one"
+
+[[bin]]
+name = "two"
+path = "src/two.rs"
+"#;
+
+#[test]
+fn values() {
+    let toml = parse_toml(r#"
+str = "a\tb\u00e9\U0001F600 \"q\""
+lit = 'C:\path'
+multi = """
+one \
+   two
+three"""""
+multi_lit = '''
+raw \n '' '''
+ints = [1_000, -17, +3, 0xff_ff, 0o17, 0b101]
+floats = [1.5, -0.01, 5e+22, 6.626e-34, 1_0.0_1, inf, -inf]
+nan = nan
+bools = [ true,
+  false, # comment
+]
+dates = [1979-05-27T07:32:00Z, 1979-05-27 00:32:00.999-07:00, 1979-05-27T07:32:00, 1979-05-27, 07:32:00.5, 2000-02-29]
+nested = [[1, 2], ["a"], []]
+inline = { a = 1, b.c = "x", d = {} }
+"quoted key" = 1
+'lit.key' = 2
+3.14 = "pi"
+"#).unwrap();
+    assert_eq!(toml.get("str").and_then(Toml::as_str), Some("a\tbé😀 \"q\""));
+    assert_eq!(toml.get("lit").and_then(Toml::as_str), Some("C:\\path"));
+    assert_eq!(toml.get("multi").and_then(Toml::as_str), Some("one two\nthree\"\""));
+    assert_eq!(toml.get("multi_lit").and_then(Toml::as_str), Some("raw \\n '' "));
+    let ints: Vec<i64> = toml.get("ints").and_then(Toml::as_array).unwrap().iter().filter_map(Toml::as_i64).collect();
+    assert_eq!(ints, vec![1000, -17, 3, 0xffff, 0o17, 0b101]);
+    let floats: Vec<f64> = toml.get("floats").and_then(Toml::as_array).unwrap().iter().filter_map(Toml::as_f64).collect();
+    assert_eq!(floats, vec![1.5, -0.01, 5e22, 6.626e-34, 10.01, f64::INFINITY, f64::NEG_INFINITY]);
+    assert!(toml.get("nan").and_then(Toml::as_f64).unwrap().is_nan());
+    assert_eq!(toml.get("bools.1").and_then(Toml::as_bool), Some(false));
+    assert!(matches!(toml.get("dates.1"), Some(Toml::Date(date, span)) if date == "1979-05-27 00:32:00.999-07:00" && span.len == date.len()));
+    assert_eq!(toml.get("dates").and_then(Toml::as_array).map(<[_]>::len), Some(6));
+    assert_eq!(toml.get("nested.0.1").and_then(Toml::as_i64), Some(2));
+    assert_eq!(toml.get("nested.2").and_then(Toml::as_array).map(<[_]>::len), Some(0));
+    assert_eq!(toml.get("inline.b.c").and_then(Toml::as_str), Some("x"));
+    assert_eq!(toml.get("inline").and_then(Toml::as_table).map( | t | t.kind), Some(TomlTableKind::Inline));
+    assert_eq!(toml.get("\"quoted key\"").and_then(Toml::as_i64), Some(1));
+    assert_eq!(toml.get("'lit.key'").and_then(Toml::as_i64), Some(2));
+    assert_eq!(toml.get("3.14").and_then(Toml::as_str), Some("pi"));
+    assert!(toml.get("3").and_then(Toml::as_table).is_some());
+}
+
+#[test]
+fn tables() {
+    let toml = parse_toml(CARGO).unwrap();
+    assert_eq!(toml.get("package.name").and_then(Toml::as_str), Some("app"));
+    assert_eq!(toml.get("package.metadata.makepad-auto-version").and_then(Toml::as_str), Some("abc="));
+    assert_eq!(toml.get("dependencies.makepad-widgets.version").and_then(Toml::as_str), Some("0.6.0"));
+    assert_eq!(toml.get("target.'cfg(windows)'.dependencies.windows.features.0").and_then(Toml::as_str), Some("Win32_Foundation"));
+    assert!(toml.get("bin").unwrap().is_array_of_tables());
+    assert_eq!(toml.get("bin.1.path").and_then(Toml::as_str), Some("src/two.rs"));
+    assert_eq!(toml.get("bin.2"), None);
+    assert_eq!(toml.get("package.nope"), None);
+
+    // tables keep the order of the file
+    let root = toml.as_table().unwrap();
+    let keys: Vec<&str> = root.iter().map( | (key, _) | key).collect();
+    assert_eq!(keys, vec!["package", "dependencies", "target", "bin"]);
+    let deps: Vec<&str> = toml.get("dependencies").and_then(Toml::as_table).unwrap().iter().map( | (key, _) | key).collect();
+    assert_eq!(deps, vec!["makepad-widgets", "serde"]);
+    assert_eq!(toml.get("target").and_then(Toml::as_table).map( | t | t.kind), Some(TomlTableKind::Implicit));
+
+    // headers can add to tables made by other headers, in any order
+    let toml = parse_toml("[a.b.c]\nx = 1\n[a]\ny = 2\n[a.b.d]\n[[p.q]]\n[p.q.r]\nz = 3\n[[p.q]]\n").unwrap();
+    assert_eq!(toml.get("a.y").and_then(Toml::as_i64), Some(2));
+    assert_eq!(toml.get("a.b.c.x").and_then(Toml::as_i64), Some(1));
+    assert!(toml.get("a.b.d").is_some());
+    assert_eq!(toml.get("p.q.0.r.z").and_then(Toml::as_i64), Some(3));
+    assert_eq!(toml.get("p.q").and_then(Toml::as_array).map(<[_]>::len), Some(2));
+}
+
+#[test]
+fn spans() {
+    let toml = parse_toml(CARGO).unwrap();
+    let span = toml.get("package.name").unwrap().span();
+    assert_eq!(&CARGO[span.start..span.end()], "\"app\"");
+    let span = toml.get("dependencies.makepad-widgets").unwrap().span();
+    assert_eq!(&CARGO[span.start..span.end()], "{ path = \"../widgets\", version = \"0.6.0\" }");
+    let entry = toml.get("package").and_then(Toml::as_table).unwrap().entry("version").unwrap();
+    assert_eq!(&CARGO[entry.span.start..entry.span.end()], "version = \"0.1.0\"");
+    assert_eq!(&CARGO[entry.key_span.start..entry.key_span.end()], "version");
+    let span = toml.get("bin.1").unwrap().span();
+    assert_eq!(&CARGO[span.start..span.end()], "[[bin]]");
+
+    let err = parse_toml("a = 1\nb = \"open\n").unwrap_err();
+    assert_eq!(err.span.start, 10);
+}
+
+#[test]
+fn invalid() {
+    for doc in [
+        "a = 1\na = 2",
+        "a.b = 1\na = 2",
+        "a = 1\na.b = 2",
+        "[a]\n[a]",
+        "[a]\nb = 1\n[a.b]",
+        "a = [1]\n[[a]]",
+        "[[a]]\n[a]",
+        "a = {b = 1}\na.c = 2",
+        "a = {b = 1}\n[a.c]",
+        "a = {b = 1,}",
+        "a = {b = 1\n}",
+        "[x]\na.b = 1\n[x.a]",
+        "a = ",
+        "a = 1 b = 2",
+        "= 1",
+        "a.=1",
+        "[a",
+        "[[a]",
+        "a = \"\\q\"",
+        "a = \"x",
+        "a = 'x\ny'",
+        "a = \"\"\"x",
+        "a = \"\"\"x\"\"\"\"\"\"",
+        "a = \"\\uD800\"",
+        "a = 01",
+        "a = 1__0",
+        "a = _1",
+        "a = 1_",
+        "a = 1.",
+        "a = .5",
+        "a = 1e",
+        "a = +0x10",
+        "a = 0xg",
+        "a = 9223372036854775808",
+        "a = tru",
+        "a = 2023-02-29",
+        "a = 2023-13-01",
+        "a = 25:00:00",
+        "a = 2023-01-01T00:00:00+25:00",
+        "a = [1 2]",
+        "a = [1,,2]",
+        "a = 1 # \u{1}",
+        "a = 1\rb = 2",
+    ] {
+        assert!(parse_toml(doc).is_err(), "should not parse: {:?}", doc);
+    }
+    assert!(parse_toml("\u{feff}a = 1\r\n# fine\r\n").is_ok());
+    assert!(parse_toml("a = 9223372036854775807\nb = -9223372036854775808").is_ok());
+}
+
+#[test]
+fn document_edits() {
+    let mut doc = TomlDocument::parse(CARGO).unwrap();
+    // replacing a value leaves everything else as it was
+    doc.set("package.version", "0.2.0").unwrap();
+    doc.set("dependencies.makepad-widgets.version", "0.7.0").unwrap();
+    doc.set("bin.0.name", "uno").unwrap();
+    assert_eq!(doc.source(), CARGO.replace("0.1.0", "0.2.0").replace("0.6.0", "0.7.0").replace("\"one\"", "\"uno\""));
+
+    // new keys go at the end of their table
+    doc.set("package.edition", "2021").unwrap();
+    doc.set("package.metadata.flag", true).unwrap();
+    doc.set("dependencies.makepad-widgets.optional", true).unwrap();
+    doc.set("dependencies.log", "0.4").unwrap();
+    doc.set("target.'cfg(unix)'.dependencies.libc", "0.2").unwrap();
+    doc.set("workspace.members", vec!["a".into(), "b".into()]).unwrap();
+    doc.set("top", 1i64).unwrap();
+    assert!(doc.set("package.name.x", 1i64).is_err());
+    assert!(doc.set("bin", 1i64).is_err());
+    assert!(doc.set("a = b", 1i64).is_err());
+
+    assert!(doc.remove("dependencies.serde").unwrap());
+    assert!(doc.remove("target.'cfg(windows)'.dependencies.windows.features.0").unwrap());
+    assert!(!doc.remove("nope").unwrap());
+    assert!(doc.remove("package").is_err());
+
+    assert_eq!(doc.source(), r#"top = 1
+# a workspace
+[package]
+name = "app"   # the name
+version = "0.2.0"
+metadata.makepad-auto-version = "abc="
+edition = "2021"
+metadata.flag = true
+
+[dependencies]
+makepad-widgets = { path = "../widgets", version = "0.7.0", optional = true }
+log = "0.4"
+
+[target.'cfg(windows)'.dependencies]
+windows = { version = "0.48", features = [] }
+
+[[bin]]
+name = "uno"
+
+[[bin]]
+name = "two"
+path = "src/two.rs"
+
+[target."cfg(unix)".dependencies]
+libc = "0.2"
+
+[workspace]
+members = ["a", "b"]
+"#);
+    assert_eq!(doc.get("package.metadata.flag").and_then(Toml::as_bool), Some(true));
+}
+
+#[test]
+fn serialize() {
+    let toml = parse_toml(CARGO).unwrap();
+    let text = toml.as_table().unwrap().to_toml();
+    assert_eq!(text, r#"[package]
+name = "app"
+version = "0.1.0"
+
+[package.metadata]
+makepad-auto-version = "abc="
+
+[dependencies]
+makepad-widgets = { path = "../widgets", version = "0.6.0" }
+serde = "1.0"
+
+[target."cfg(windows)".dependencies]
+windows = { version = "0.48", features = ["Win32_Foundation"] }
+
+[[bin]]
+name = "one"
+
+[[bin]]
+name = "two"
+path = "src/two.rs"
+"#);
+    // what is written reads back as the same tree
+    let again = parse_toml(&text).unwrap();
+    for path in ["package.name", "package.metadata.makepad-auto-version", "dependencies.makepad-widgets.version", "bin.1.path"] {
+        assert_eq!(again.get(path).and_then(Toml::as_str), toml.get(path).and_then(Toml::as_str));
+    }
+
+    let mut table = TomlTable::new(TomlTableKind::Root);
+    table.insert("text", "a \"b\"\n\u{1}".into());
+    table.insert("f", 1.0.into());
+    table.insert("weird key", (-2i64).into());
+    table.insert("list", vec![1.5.into(), f64::NEG_INFINITY.into()].into());
+    assert_eq!(table.to_toml(), "text = \"a \\\"b\\\"\\n\\u0001\"\nf = 1.0\n\"weird key\" = -2\nlist = [1.5, -inf]\n");
+    assert_eq!(parse_toml(&table.to_toml()).unwrap().get("text").and_then(Toml::as_str), Some("a \"b\"\n\u{1}"));
+}
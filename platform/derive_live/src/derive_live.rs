@@ -186,7 +186,8 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                       field.name == "padding" ||
                       field.name == "align" ||
                       field.name == "flow" ||
-                      field.name == "spacing"{
+                      field.name == "spacing" ||
+                      field.name == "grid"{
                           return error_result(&format!("Name collision between layout splat and {}", field.name));
                       }
                 }
@@ -197,6 +198,7 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
                 tb.add("        live_id!(align)=>self.").ident(&field.name).add(".align.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(flow)=>self.").ident(&field.name).add(".flow.apply(cx, apply_from, index, nodes),");
                 tb.add("        live_id!(spacing)=>self.").ident(&field.name).add(".spacing.apply(cx, apply_from, index, nodes),");
+                tb.add("        live_id!(grid)=>self.").ident(&field.name).add(".grid.apply(cx, apply_from, index, nodes),");
             }
         }
         // Unknown value handling
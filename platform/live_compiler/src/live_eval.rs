@@ -26,6 +26,16 @@ pub enum LiveEval {
     String(Rc<String>),
 }
 
+impl LiveEval {
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float64(v) => Some(*v),
+            Self::Int64(v) => Some(*v as f64),
+            _ => None
+        }
+    }
+}
+
 impl LiveError {
     fn eval_error_wrong_value_in_expression(origin: LiveErrorOrigin, index: usize, nodes: &[LiveNode], ty: &str) ->Self{
         Self::eval_error(origin, index, nodes, format!("wrong value in expression of type {} value: {:?}", ty, nodes[index].value))
@@ -43,6 +53,10 @@ impl LiveError {
         Self::eval_error(origin, index, nodes, format!("Expression call not implemented ident:{} with number of args: {}", ident, args))
     }
     
+    fn eval_error_call_wrong_args(origin: LiveErrorOrigin, index: usize, nodes: &[LiveNode], ident: LiveId, args: &[LiveEval])->Self {
+        Self::eval_error(origin, index, nodes, format!("Expression call {} undefined for arguments {:?}", ident, args))
+    }
+    
     fn eval_error_cant_find_target(origin: LiveErrorOrigin, index: usize, nodes: &[LiveNode], id: LiveId)->Self {
         Self::eval_error(origin, index, nodes, format!("cant find target: {}", id))
     }
@@ -60,10 +74,13 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
     Ok(match &nodes[*index].value {
         LiveValue::Str(_) |
         LiveValue::InlineString(_) => {
-            LiveEval::String(Rc::new(live_registry.live_node_as_string(&nodes[*index]).unwrap()))
+            *index += 1;
+            LiveEval::String(Rc::new(live_registry.live_node_as_string(&nodes[*index - 1]).unwrap()))
+        }
+        LiveValue::Dependency(v) | LiveValue::String(v) => {
+            *index += 1;
+            LiveEval::String(v.clone())
         }
-        LiveValue::Dependency(v)=>LiveEval::String(v.clone()),
-        LiveValue::String(v)=>LiveEval::String(v.clone()),
         LiveValue::Float32(v) => {
             *index += 1;
             LiveEval::Float64(*v as f64)
@@ -190,26 +207,67 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
         }
         LiveValue::ExprCall {ident, args} => {
             *index += 1;
-            match ident {
-                live_id!(blend) if *args == 2 => {
-                    let a = live_eval(live_registry, start, index, nodes)?;
-                    let b = live_eval(live_registry, start, index, nodes)?;
-                    if let LiveEval::Vec4(va) = a {
-                        if let LiveEval::Vec4(vb) = b {
-                            // ok so how do we blend this eh.
-                            return Ok(LiveEval::Vec4(vec4(
-                                va.x + (vb.x - va.x) * vb.w,
-                                va.y + (vb.y - va.y) * vb.w,
-                                va.z + (vb.z - va.z) * vb.w,
-                                va.w
-                            )))
-                        }
-                    }
+            // conditionals only evaluate the branch they pick
+            if let (live_id!(cond), 3) = (ident, args) {
+                let c = live_eval(live_registry, start, index, nodes)?;
+                if let LiveEval::Bool(c) = c {
+                    if c {
+                        let v = live_eval(live_registry, start, index, nodes)?;
+                        *index = skip_expr(*index, nodes);
+                        return Ok(v)
+                    }
+                    *index = skip_expr(*index, nodes);
+                    return live_eval(live_registry, start, index, nodes)
                 }
-                _ => {}
+                return Err(LiveError::eval_error_call_wrong_args(live_error_origin!(), *index, nodes, *ident, &[c]))
             }
-            
-            return Err(LiveError::eval_error_expression_call_not_implemented(live_error_origin!(), *index, nodes, *ident, *args))
+            let mut a = Vec::with_capacity(*args);
+            for _ in 0..*args {
+                a.push(live_eval(live_registry, start, index, nodes)?);
+            }
+            let ret = match (ident, a.as_slice()) {
+                (live_id!(blend), [LiveEval::Vec4(va), LiveEval::Vec4(vb)]) => {
+                    Some(LiveEval::Vec4(vec4(
+                        va.x + (vb.x - va.x) * vb.w,
+                        va.y + (vb.y - va.y) * vb.w,
+                        va.z + (vb.z - va.z) * vb.w,
+                        va.w
+                    )))
+                }
+                (live_id!(min), [va, vb]) => eval_map2(va, vb, true, | a, b | a.min(b)),
+                (live_id!(max), [va, vb]) => eval_map2(va, vb, true, | a, b | a.max(b)),
+                (live_id!(clamp), [v, lo, hi]) => eval_map2(v, lo, true, | a, b | a.max(b))
+                    .and_then( | v | eval_map2(&v, hi, true, | a, b | a.min(b))),
+                (live_id!(mix), [va, vb, t]) => t.as_f64().and_then( | t | {
+                    eval_map2(va, vb, false, | a, b | a + (b - a) * t)
+                }),
+                (live_id!(abs), [v]) => eval_map1(v, true, | a | a.abs()),
+                (live_id!(floor), [v]) => eval_map1(v, true, | a | a.floor()),
+                (live_id!(round), [v]) => eval_map1(v, true, | a | a.round()),
+                // hue, saturation, lightness/value and alpha all range 0..1
+                (live_id!(hsl), [h, s, l]) => eval_color(&[h, s, l], Vec4::from_hsla),
+                (live_id!(hsla), [h, s, l, a]) => eval_color(&[h, s, l, a], Vec4::from_hsla),
+                (live_id!(hsv), [h, s, v]) => eval_color(&[h, s, v], Vec4::from_hsva),
+                (live_id!(hsva), [h, s, v, a]) => eval_color(&[h, s, v, a], Vec4::from_hsva),
+                (live_id!(lighten), [LiveEval::Vec4(c), amount]) => amount.as_f64().map( | amount | {
+                    LiveEval::Vec4(adjust_lightness(*c, amount as f32))
+                }),
+                (live_id!(darken), [LiveEval::Vec4(c), amount]) => amount.as_f64().map( | amount | {
+                    LiveEval::Vec4(adjust_lightness(*c, -amount as f32))
+                }),
+                (live_id!(blend) | live_id!(min) | live_id!(max) | live_id!(clamp) | live_id!(mix) |
+                live_id!(abs) | live_id!(floor) | live_id!(round) | live_id!(hsl) | live_id!(hsla) |
+                live_id!(hsv) | live_id!(hsva) | live_id!(lighten) | live_id!(darken), _) => {
+                    return Err(LiveError::eval_error_call_wrong_args(live_error_origin!(), *index, nodes, *ident, &a))
+                }
+                _ => {
+                    return Err(LiveError::eval_error_expression_call_not_implemented(live_error_origin!(), *index, nodes, *ident, *args))
+                }
+            };
+            if let Some(ret) = ret {
+                return Ok(ret)
+            }
+            return Err(LiveError::eval_error_call_wrong_args(live_error_origin!(), *index, nodes, *ident, &a))
         }
         LiveValue::ExprBinOp(op) => {
             *index += 1;
@@ -494,3 +552,128 @@ pub fn live_eval(live_registry: &LiveRegistry, start: usize, index: &mut usize,
         }
     })
 }
+
+// applies f per component, ints stay ints when keep_int is set
+fn eval_map1(a: &LiveEval, keep_int: bool, f: impl Fn(f64) -> f64) -> Option<LiveEval> {
+    let f32 = | v: f32 | f(v as f64) as f32;
+    Some(match a {
+        LiveEval::Int64(va) if keep_int => LiveEval::Int64(f(*va as f64) as i64),
+        LiveEval::Int64(va) => LiveEval::Float64(f(*va as f64)),
+        LiveEval::Float64(va) => LiveEval::Float64(f(*va)),
+        LiveEval::Vec2(va) => LiveEval::Vec2(vec2(f32(va.x), f32(va.y))),
+        LiveEval::Vec3(va) => LiveEval::Vec3(vec3(f32(va.x), f32(va.y), f32(va.z))),
+        LiveEval::Vec4(va) => LiveEval::Vec4(vec4(f32(va.x), f32(va.y), f32(va.z), f32(va.w))),
+        _ => return None
+    })
+}
+
+// applies f per component, a scalar on either side is used for every component
+fn eval_map2(a: &LiveEval, b: &LiveEval, keep_int: bool, f: impl Fn(f64, f64) -> f64) -> Option<LiveEval> {
+    let f32 = | a: f32, b: f32 | f(a as f64, b as f64) as f32;
+    Some(match (a, b) {
+        (LiveEval::Int64(va), LiveEval::Int64(vb)) if keep_int => LiveEval::Int64(f(*va as f64, *vb as f64) as i64),
+        (LiveEval::Vec2(va), LiveEval::Vec2(vb)) => LiveEval::Vec2(vec2(f32(va.x, vb.x), f32(va.y, vb.y))),
+        (LiveEval::Vec3(va), LiveEval::Vec3(vb)) => LiveEval::Vec3(vec3(f32(va.x, vb.x), f32(va.y, vb.y), f32(va.z, vb.z))),
+        (LiveEval::Vec4(va), LiveEval::Vec4(vb)) => LiveEval::Vec4(vec4(f32(va.x, vb.x), f32(va.y, vb.y), f32(va.z, vb.z), f32(va.w, vb.w))),
+        (va, vb) => if let (Some(va), Some(vb)) = (va.as_f64(), vb.as_f64()) {
+            LiveEval::Float64(f(va, vb))
+        }
+        else if let Some(vb) = vb.as_f64() {
+            return eval_map1(va, false, | va | f(va, vb))
+        }
+        else if let Some(va) = va.as_f64() {
+            return eval_map1(vb, false, | vb | f(va, vb))
+        }
+        else {
+            return None
+        }
+    })
+}
+
+fn eval_color(args: &[&LiveEval], to_rgba: fn(Vec4) -> Vec4) -> Option<LiveEval> {
+    let mut v = [0.0, 0.0, 0.0, 1.0];
+    for (i, arg) in args.iter().enumerate() {
+        v[i] = arg.as_f64()? as f32;
+    }
+    Some(LiveEval::Vec4(to_rgba(vec4(v[0], v[1], v[2], v[3]))))
+}
+
+fn adjust_lightness(color: Vec4, amount: f32) -> Vec4 {
+    let mut hsl = color.to_hsla();
+    hsl.z = (hsl.z + amount).clamp(0.0, 1.0);
+    Vec4::from_hsla(hsl)
+}
+
+// steps over an expression without evaluating it
+fn skip_expr(mut index: usize, nodes: &[LiveNode]) -> usize {
+    let mut pending = 1;
+    while pending > 0 && index < nodes.len() {
+        pending -= 1;
+        pending += match &nodes[index].value {
+            LiveValue::ExprBinOp(_) => 2,
+            LiveValue::ExprUnOp(_) | LiveValue::ExprMember(_) => 1,
+            LiveValue::ExprCall {args, ..} => *args,
+            _ => 0
+        };
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn eval(values: Vec<LiveValue>) -> LiveEval {
+        let nodes: Vec<LiveNode> = values.into_iter().map( | v | LiveNode::from_id_value(LiveId(0), v)).collect();
+        let mut index = 0;
+        let ret = live_eval(&LiveRegistry::default(), 0, &mut index, &nodes).unwrap();
+        assert_eq!(index, nodes.len());
+        ret
+    }
+    
+    fn call(ident: LiveId, args: usize) -> LiveValue {
+        LiveValue::ExprCall {ident, args}
+    }
+    
+    #[test]
+    fn math_calls() {
+        use LiveValue::*;
+        assert!(matches!(eval(vec![call(live_id!(clamp), 3), Int64(5), Int64(0), Int64(3)]), LiveEval::Int64(3)));
+        assert!(matches!(eval(vec![call(live_id!(max), 2), Float64(1.5), Int64(1)]), LiveEval::Float64(v) if v == 1.5));
+        assert!(matches!(eval(vec![call(live_id!(mix), 3), Float64(2.0), Float64(4.0), Float64(0.25)]), LiveEval::Float64(v) if v == 2.5));
+        assert!(matches!(eval(vec![call(live_id!(round), 1), ExprUnOp(LiveUnOp::Neg), Float64(1.6)]), LiveEval::Float64(v) if v == -2.0));
+    }
+    
+    #[test]
+    fn cond_skips_other_branch() {
+        use LiveValue::*;
+        // the untaken branch would fail to evaluate
+        let bad = [call(live_id!(lighten), 2), Int64(1), Int64(1)];
+        let mut values = vec![call(live_id!(cond), 3), Bool(false)];
+        values.extend(bad.iter().cloned());
+        values.push(call(live_id!(abs), 1));
+        values.push(Int64(-7));
+        assert!(matches!(eval(values), LiveEval::Int64(7)));
+    }
+    
+    #[test]
+    fn cond_picks_string() {
+        use LiveValue::*;
+        let on = || String(Rc::new("on".to_string()));
+        let off = || String(Rc::new("off".to_string()));
+        assert!(matches!(eval(vec![call(live_id!(cond), 3), Bool(true), on(), off()]), LiveEval::String(v) if *v == "on"));
+        assert!(matches!(eval(vec![call(live_id!(cond), 3), Bool(false), on(), off()]), LiveEval::String(v) if *v == "off"));
+    }
+    
+    #[test]
+    fn colors() {
+        use LiveValue::*;
+        let red = eval(vec![call(live_id!(hsl), 3), Float64(0.0), Float64(1.0), Float64(0.5)]);
+        assert!(matches!(red, LiveEval::Vec4(c) if c.is_equal_enough(&vec4(1.0, 0.0, 0.0, 1.0), 0.001)));
+        let light = eval(vec![call(live_id!(lighten), 2), Color(0xff0000ff), Float64(0.25)]);
+        assert!(matches!(light, LiveEval::Vec4(c) if c.is_equal_enough(&vec4(1.0, 0.5, 0.5, 1.0), 0.001)));
+        let dark = eval(vec![call(live_id!(darken), 2), Color(0x808080ff), Float64(0.6)]);
+        assert!(matches!(dark, LiveEval::Vec4(c) if c.is_equal_enough(&vec4(0.0, 0.0, 0.0, 1.0), 0.001)));
+    }
+}
@@ -80,28 +80,21 @@ impl<'a> LiveExpander<'a> {
                     // lets verify it points anywhere
                     let mut found = false;
                     let is_glob = in_node.id == LiveId::empty();
-                    if let Some(nodes) = self.live_registry.module_id_to_expanded_nodes(live_import.module_id) {
-                        let file_id = self.live_registry.module_id_to_file_id(live_import.module_id).unwrap();
-                        let mut node_iter = Some(1);
-                        while let Some(index) = node_iter {
+                    if let Some(exports) = self.live_registry.module_id_to_exports(live_import.module_id) {
+                        for (id, live_ptr) in exports {
                             if is_glob{
                                 if let LiveValue::Root {id_resolve} = &mut out_doc.nodes[0].value {
-                                    id_resolve.insert(nodes[index].id, LiveScopeTarget::LivePtr(
-                                        self.live_registry.file_id_index_to_live_ptr(file_id, index)
-                                    ));
+                                    id_resolve.insert(id, LiveScopeTarget::LivePtr(live_ptr));
                                 }
                                 found = true;
                             }
-                            else if nodes[index].id == live_import.import_id { // its *
+                            else if id == live_import.import_id { // its *
                                 // ok so what do we store...
                                 if let LiveValue::Root {id_resolve} = &mut out_doc.nodes[0].value {
-                                    id_resolve.insert(in_node.id , LiveScopeTarget::LivePtr(
-                                        self.live_registry.file_id_index_to_live_ptr(file_id, index)
-                                    ));
+                                    id_resolve.insert(in_node.id , LiveScopeTarget::LivePtr(live_ptr));
                                 }
                                 found = true;
                             }
-                            node_iter = nodes.next_child(index);
                         }
                     }
                     if !found {
@@ -439,3 +432,60 @@ impl<'a> LiveExpander<'a> {
     
 }
 
+
+#[cfg(test)]
+mod tests {
+    use {
+        crate::{
+            makepad_live_id::*,
+            span::TextPos,
+            live_ptr::LiveModuleId,
+            live_node::{LiveValue, LiveIdAsProp},
+            live_node_vec::LiveNodeSliceApi,
+            live_registry::LiveRegistry,
+        }
+    };
+    
+    // registers the modules as a::<name> and expands them all
+    fn expand(modules: &[(LiveId, &str)]) -> LiveRegistry {
+        let mut registry = LiveRegistry::default();
+        for (name, source) in modules {
+            let module_id = LiveModuleId(live_id!(a), *name);
+            if let Err(err) = registry.register_live_file(&format!("{}.rs", name), "", module_id, source.to_string(), vec![], TextPos::default()) {
+                panic!("{}", err);
+            }
+        }
+        let mut errors = Vec::new();
+        registry.expand_all_documents(&mut errors);
+        assert!(errors.is_empty(), "{:?}", errors);
+        registry
+    }
+    
+    // the value of field `field` of the top level definition `id` of a::<module>
+    fn value(registry: &LiveRegistry, module: LiveId, id: LiveId, field: LiveId) -> Option<LiveValue> {
+        let nodes = registry.module_id_to_expanded_nodes(LiveModuleId(live_id!(a), module)).unwrap();
+        nodes.child_value_by_path(0, &[id.as_instance(), field.as_field()]).cloned()
+    }
+    
+    #[test]
+    fn reexports_resolve_through_two_levels() {
+        let registry = expand(&[
+            (live_id!(tokens), "Color = {v: 1} Size = {v: 2}"),
+            (live_id!(theme), "pub import a::tokens::*; pub import a::tokens::Size as Width; Button = {v: 3}"),
+            (live_id!(widgets), "pub import a::theme::*;"),
+            (live_id!(app), "import a::widgets::*; X = <Color> {} Y = <Width> {} Z = <Button> {}"),
+        ]);
+        // the glob goes through widgets and theme to tokens, the rename is kept along the way
+        assert_eq!(value(&registry, live_id!(app), live_id!(X), live_id!(v)), Some(LiveValue::Int64(1)));
+        assert_eq!(value(&registry, live_id!(app), live_id!(Y), live_id!(v)), Some(LiveValue::Int64(2)));
+        assert_eq!(value(&registry, live_id!(app), live_id!(Z), live_id!(v)), Some(LiveValue::Int64(3)));
+        // a plain import is not passed on
+        let registry = expand(&[
+            (live_id!(tokens), "Color = {v: 1}"),
+            (live_id!(theme), "import a::tokens::*;"),
+            (live_id!(app), "import a::theme::*;"),
+        ]);
+        let exports = registry.module_id_to_exports(LiveModuleId(live_id!(a), live_id!(theme))).unwrap();
+        assert!(!exports.iter().any( | (id, _) | *id == live_id!(Color)));
+    }
+}
@@ -44,6 +44,7 @@ pub enum LiveValue {
 
     Id(LiveId),
     IdPath(Rc<Vec<LiveId>>),
+    Binding(Rc<LiveBinding>),
 
     ExprBinOp(LiveBinOp),
     ExprUnOp(LiveUnOp),
@@ -75,12 +76,19 @@ pub enum LiveValue {
 pub struct LiveImport{
     pub module_id: LiveModuleId,
     pub import_id: LiveId,
+    /// `pub import`, the imported definitions are exported again by the importing module
+    pub reexport: bool,
 }
 
+/// A data binding from the DSL. `from` is a path into the data, `to` a widget path that can end
+/// in a property path. Two way bindings sync both directions, one way bindings go from the data to
+/// the widget, optionally through a mapping expression in which `value` is the bound value.
 #[derive(Debug, Clone, PartialEq)]
 pub struct LiveBinding{
     pub from: LiveIdPath,
-    pub to: LiveIdPath
+    pub to: LiveIdPath,
+    pub two_way: bool,
+    pub map: Option<Vec<LiveNode>>
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -548,6 +556,7 @@ impl LiveValue {
         self.is_value_type() || match self {
             Self::Id(_) |
             Self::IdPath(_) |
+            Self::Binding(_) |
             Self::BareEnum(_) => true,
             _ => false
         }
@@ -613,6 +622,15 @@ impl LiveValue {
         }
     }
     
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(v) => Some(v),
+            Self::String(v) => Some(v.as_str()),
+            Self::InlineString(v) => Some(v.as_str()),
+            _ => None
+        }
+    }
+    
     pub fn enum_eq(&self, id_eq:&[LiveId])->LiveValue{
         match self{
             Self::BareEnum(id) if *id == id_eq[0]=>{
@@ -686,6 +704,7 @@ impl LiveValue {
             
             Self::DSL {..} => 29,
             Self::Import {..} => 30,
+            Self::Binding(_) => 31,
             //Self::Registry {..} => 30,
         }
     }
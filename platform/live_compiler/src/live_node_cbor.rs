@@ -384,6 +384,9 @@ impl<T> LiveNodeSliceToCbor for T where T: AsRef<[LiveNode]> {
                 }
                 LiveValue::IdPath(..) => {
                     return Err("Cannot serialise LiveValue::IdPath".into())
+                }
+                LiveValue::Binding(..) => {
+                    return Err("Cannot serialise LiveValue::Binding".into())
                 }                
             }
             index += 1;
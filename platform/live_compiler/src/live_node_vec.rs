@@ -697,6 +697,9 @@ impl<T> LiveNodeSliceApi for T where T: AsRef<[LiveNode]> {
                 LiveValue::IdPath(p) => {
                     writeln!(f, "<IdPath> {:?}", p).unwrap();
                 }
+                LiveValue::Binding(b) => {
+                    writeln!(f, "{}{} <Binding> {:?} {} {:?}", node.id, pt, b.from.0, if b.two_way {"<=>"} else {"=>"}, b.to.0).unwrap();
+                }
                 LiveValue::ExprBinOp(id) => {
                     writeln!(f, "{}{} <ExprBinOp> {:?}", node.id, pt, id).unwrap();
                 },
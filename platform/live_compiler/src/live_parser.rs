@@ -18,7 +18,7 @@ use {
         span::{TextSpan, TextPos},
         live_error::{LiveError},
         live_document::LiveOriginal,
-        live_node::{LiveImport, LivePropType, LiveNode, LiveValue, LiveTypeInfo, LiveBinOp, LiveUnOp, LiveNodeOrigin, LiveEditInfo},
+        live_node::{LiveImport, LiveBinding, LiveIdPath, LivePropType, LiveNode, LiveValue, LiveTypeInfo, LiveBinOp, LiveUnOp, LiveNodeOrigin, LiveEditInfo},
     }
 };
 
@@ -58,6 +58,13 @@ impl<'a> LiveParser<'a> {
         self.token_with_span.token.clone()
     }
     
+    fn peek_token2(&self) -> LiveToken {
+        match self.tokens_with_span.clone().next() {
+            Some(next) => next.token,
+            None => LiveToken::Eof
+        }
+    }
+    
     fn eat_token(&mut self) -> LiveToken {
         let token = self.peek_token();
         self.skip_token();
@@ -170,7 +177,7 @@ impl<'a> LiveParser<'a> {
         Ok(())
     }
     
-    fn expect_import(&mut self, ld: &mut LiveOriginal) -> Result<(), LiveError> {
+    fn expect_import(&mut self, reexport: bool, ld: &mut LiveOriginal) -> Result<(), LiveError> {
         let token_id = self.get_token_id();
         let crate_id = self.expect_ident() ?;
         // if crate_id is capitalized, its a component.
@@ -216,6 +223,7 @@ impl<'a> LiveParser<'a> {
             value: LiveValue::Import(Box::new(LiveImport{
                 module_id: LiveModuleId(crate_id, LiveId::from_str_with_lut(&module).unwrap()),
                 import_id,
+                reexport,
             }))
         });
         
@@ -816,14 +824,23 @@ impl<'a> LiveParser<'a> {
                     //let span = self.begin_span();
                     // next
                     // there is another token coming
-                    if let LiveToken::Ident(_) = self.peek_token() {
+                    if self.is_live_binding() {
+                        self.expect_live_binding(prop_id, token_id, ld) ?;
+                        self.accept_optional_delim();
+                    }
+                    else if let LiveToken::Ident(_) = self.peek_token() {
                         match prop_id {
                             live_id!(fn) => {
                                 self.expect_fn(ld) ?;
                                 self.accept_optional_delim();
                             }
                             live_id!(import) => {
-                                self.expect_import(ld) ?;
+                                self.expect_import(false, ld) ?;
+                                self.accept_optional_delim();
+                            }
+                            live_id!(pub) if self.peek_token() == LiveToken::Ident(live_id!(import)) => {
+                                self.skip_token();
+                                self.expect_import(true, ld) ?;
                                 self.accept_optional_delim();
                             }
                             _ => {
@@ -864,6 +881,60 @@ impl<'a> LiveParser<'a> {
         Err(self.error("Eof in class body".to_string(), live_error_origin!()))
     }
     
+    fn is_live_binding(&self) -> bool {
+        // a.b => c.d or a.b <=> c.d, whereas a.{..} is edit info
+        match self.peek_token() {
+            LiveToken::Punct(live_id!(.)) => matches!(self.peek_token2(), LiveToken::Ident(_)),
+            LiveToken::Punct(live_id!(=>)) | LiveToken::Punct(live_id!( <=)) => true,
+            _ => false
+        }
+    }
+    
+    fn expect_id_path(&mut self, first: LiveId) -> Result<LiveIdPath, LiveError> {
+        let mut id_path = vec![first];
+        while self.accept_token(LiveToken::Punct(live_id!(.))) {
+            id_path.push(self.expect_ident() ?);
+        }
+        Ok(LiveIdPath(id_path))
+    }
+    
+    fn expect_live_binding(&mut self, first: LiveId, token_id: LiveTokenId, ld: &mut LiveOriginal) -> Result<(), LiveError> {
+        // data.path => widget.path.prop (mapping) binds one way, data.path <=> widget.path both ways
+        let from = self.expect_id_path(first) ?;
+        let two_way = if self.accept_token(LiveToken::Punct(live_id!(=>))) {
+            false
+        }
+        else if self.accept_token(LiveToken::Punct(live_id!( <=))) {
+            // the tokenizer splits <=> into <= and >
+            self.expect_token(LiveToken::Punct(live_id!(>))) ?;
+            true
+        }
+        else {
+            return Err(self.error(format!("Unexpected token {} in binding, expected => or <=>", self.peek_token()), live_error_origin!()))
+        };
+        let first = self.expect_ident() ?;
+        let to = self.expect_id_path(first) ?;
+        let map = if self.peek_token() == LiveToken::Open(Delim::Paren) {
+            if two_way {
+                return Err(self.error("Two way bindings cannot have a mapping expression".to_string(), live_error_origin!()))
+            }
+            let mut map = LiveOriginal::new();
+            self.expect_expression(LiveId::empty(), LiveNodeOrigin::empty(), &mut map) ?;
+            Some(map.nodes)
+        }
+        else {
+            None
+        };
+        // bindings are named after their target so a derived class can override them
+        let id = to.0[1..].iter().fold(to.0[0], | id, part | id.id_append(*part));
+        ld.nodes.push(LiveNode {
+            origin: LiveNodeOrigin::from_token_id(token_id).with_prop_type(LivePropType::Field),
+            id,
+            value: LiveValue::Binding(Rc::new(LiveBinding {from, to, two_way, map}))
+        });
+        Ok(())
+    }
+    
     pub fn expect_prop_type(&mut self)->Result<LivePropType, LiveError>{
         Ok(if self.accept_token(LiveToken::Punct(live_id!(:))){
             LivePropType::Field
@@ -995,6 +1066,13 @@ impl<'a> LiveParser<'a> {
                         value: LiveValue::Color(v)
                     });
                 }
+                Expr::String {token_id, v} => {
+                    ld.nodes.push(LiveNode {
+                        origin: LiveNodeOrigin::from_token_id(token_id).with_prop_type(LivePropType::Nameless),
+                        id: LiveId::empty(),
+                        value: LiveValue::String(v)
+                    });
+                }
             }
         }
         
@@ -1185,6 +1263,11 @@ impl<'a> LiveParser<'a> {
                 let token_id = self.get_token_id();
                 Ok(Expr::Color {token_id, v})
             }
+            LiveToken::String(v) => {
+                let token_id = self.get_token_id();
+                self.skip_token();
+                Ok(Expr::String {token_id, v})
+            }
             LiveToken::Open(Delim::Paren) => {
                 self.skip_token();
                 let expr = self.expect_expr() ?;
@@ -1255,6 +1338,10 @@ enum Expr {
     Color {
         token_id: LiveTokenId,
         v: u32
+    },
+    String {
+        token_id: LiveTokenId,
+        v: Rc<String>
     }
 }
 
@@ -1319,3 +1406,101 @@ impl LiveUnOp {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::live_registry::LiveRegistry,
+    };
+    
+    fn parse(source: &str) -> Result<LiveOriginal, LiveError> {
+        let tokens = LiveRegistry::tokenize_from_str(source, TextPos::default(), LiveFileId::new(0)) ?;
+        LiveParser::new(&tokens, &[], LiveFileId::new(0)).parse_live_document()
+    }
+    
+    fn bindings(source: &str) -> Vec<(LiveId, Rc<LiveBinding>)> {
+        parse(source).unwrap().nodes.iter().filter_map( | node | match &node.value {
+            LiveValue::Binding(binding) => Some((node.id, binding.clone())),
+            _ => None
+        }).collect()
+    }
+    
+    fn path(ids: &[LiveId]) -> LiveIdPath {
+        LiveIdPath(ids.to_vec())
+    }
+    
+    #[test]
+    fn one_way_binding() {
+        let found = bindings("Ui = {a.b => c.d, x => label}");
+        assert_eq!(found.len(), 2);
+        let (id, binding) = &found[0];
+        assert_eq!(binding.from, path(&[live_id!(a), live_id!(b)]));
+        assert_eq!(binding.to, path(&[live_id!(c), live_id!(d)]));
+        assert!(!binding.two_way && binding.map.is_none());
+        // named after the target
+        assert_eq!(*id, live_id!(c).id_append(live_id!(d)));
+        assert_eq!(found[1].1.from, path(&[live_id!(x)]));
+        assert_eq!(found[1].1.to, path(&[live_id!(label)]));
+    }
+    
+    #[test]
+    fn two_way_binding() {
+        let found = bindings("Ui = {a <=> b.c}");
+        assert_eq!(found.len(), 1);
+        let binding = &found[0].1;
+        assert_eq!(binding.from, path(&[live_id!(a)]));
+        assert_eq!(binding.to, path(&[live_id!(b), live_id!(c)]));
+        assert!(binding.two_way && binding.map.is_none());
+    }
+    
+    #[test]
+    fn binding_mapping() {
+        let found = bindings("Ui = {level => meter.draw_bg.fill (value * 0.5) label: \"x\"}");
+        assert_eq!(found.len(), 1);
+        let binding = &found[0].1;
+        assert_eq!(binding.to, path(&[live_id!(meter), live_id!(draw_bg), live_id!(fill)]));
+        let map: Vec<&LiveValue> = binding.map.as_ref().unwrap().iter().map( | node | &node.value).collect();
+        assert!(matches!(map[..], [
+            LiveValue::Expr {..},
+            LiveValue::ExprBinOp(LiveBinOp::Mul),
+            LiveValue::Id(live_id!(value)),
+            LiveValue::Float64(v),
+            LiveValue::Close
+        ] if *v == 0.5), "{:?}", map);
+        // the property after the mapping is parsed as usual
+        let ld = parse("Ui = {level => meter (value) label: \"x\"}").unwrap();
+        assert!(ld.nodes.iter().any( | node | node.id == live_id!(label) && matches!(node.value, LiveValue::Str(_) | LiveValue::String(_))));
+    }
+    
+    #[test]
+    fn binding_errors() {
+        let err = parse("Ui = {a <=> b (value)}").err().unwrap();
+        assert!(err.message.contains("Two way bindings cannot have a mapping"), "{}", err.message);
+        assert!(parse("Ui = {a.b => }").is_err());
+        assert!(parse("Ui = {a.b <= c}").is_err());
+    }
+    
+    #[test]
+    fn reexports() {
+        let ld = parse("import a::b::*; pub import a::c::*; pub import a::d::E as F; pub: 1").unwrap();
+        let imports: Vec<(LiveId, &LiveImport)> = ld.nodes.iter().filter_map( | node | match &node.value {
+            LiveValue::Import(live_import) => Some((node.id, &**live_import)),
+            _ => None
+        }).collect();
+        assert_eq!(imports.len(), 3);
+        assert!(!imports[0].1.reexport && imports[1].1.reexport && imports[2].1.reexport);
+        assert_eq!((imports[2].0, imports[2].1.import_id), (live_id!(F), live_id!(E)));
+        // pub is still a name of its own
+        assert!(ld.nodes.iter().any( | node | node.id == live_id!(pub) && node.value == LiveValue::Int64(1)));
+    }
+    
+    #[test]
+    fn edit_info_is_not_a_binding() {
+        let ld = parse("Ui = {a.{x: 1}: 5}").unwrap();
+        assert!(!ld.nodes.iter().any( | node | matches!(node.value, LiveValue::Binding(_))));
+        let node = ld.nodes.iter().find( | node | node.id == live_id!(a)).unwrap();
+        assert!(matches!(node.value, LiveValue::Int64(5)));
+        assert!(node.origin.edit_info().is_some());
+    }
+}
@@ -253,6 +253,53 @@ impl LiveRegistry {
         None
     }
     
+    /// The top level definitions a module exports, its own and those of the modules it imports
+    /// with `pub import`. Its own definitions come last so they win over the imported ones.
+    pub fn module_id_to_exports(&self, module_id: LiveModuleId) -> Option<Vec<(LiveId, LivePtr)>> {
+        let mut exports = Vec::new();
+        if self.collect_exports(module_id, &mut Vec::new(), &mut exports) {
+            Some(exports)
+        }
+        else {
+            None
+        }
+    }
+    
+    fn collect_exports(&self, module_id: LiveModuleId, stack: &mut Vec<LiveModuleId>, exports: &mut Vec<(LiveId, LivePtr)>) -> bool {
+        let file_id = match self.module_id_to_file_id.get(&module_id) {
+            Some(file_id) => *file_id,
+            None => return false
+        };
+        if stack.contains(&module_id) {
+            return true
+        }
+        stack.push(module_id);
+        let live_file = &self.live_files[file_id.to_index()];
+        for node in &live_file.original.nodes {
+            if let LiveValue::Import(live_import) = &node.value {
+                if !live_import.reexport {
+                    continue;
+                }
+                let mut imported = Vec::new();
+                self.collect_exports(live_import.module_id, stack, &mut imported);
+                if node.id == LiveId::empty() {
+                    exports.extend(imported);
+                }
+                else {
+                    exports.extend(imported.into_iter().filter( | (id, _) | *id == live_import.import_id).map( | (_, ptr) | (node.id, ptr)));
+                }
+            }
+        }
+        stack.pop();
+        let nodes = &live_file.expanded.nodes;
+        let mut node_iter = Some(1);
+        while let Some(index) = node_iter {
+            exports.push((nodes[index].id, self.file_id_index_to_live_ptr(file_id, index)));
+            node_iter = nodes.next_child(index);
+        }
+        true
+    }
+    
     pub fn module_id_and_name_to_ptr(&self, module_id: LiveModuleId, name: LiveId) -> Option<LivePtr> {
         if let Some(file_id) = self.module_id_to_file_id.get(&module_id) {
             let live = &self.live_files[file_id.to_index()];
@@ -570,6 +617,55 @@ impl LiveRegistry {
         }
     }
 
+    /// Swaps the document of `module_id` for the one parsed from the source of `source_module_id`,
+    /// so everything importing `module_id` picks up the other definitions on the next expand.
+    /// Passing the module itself restores its own source.
+    pub fn replace_module_document(&mut self, module_id: LiveModuleId, source_module_id: LiveModuleId, errors: &mut Vec<LiveError>) {
+        let (file_id, source_file_id) = match (self.module_id_to_file_id(module_id), self.module_id_to_file_id(source_module_id)) {
+            (Some(file_id), Some(source_file_id)) => (file_id, source_file_id),
+            _ => return
+        };
+        let source_file = self.file_id_to_file(source_file_id);
+        let live_type_infos = source_file.live_type_infos.clone();
+        let tokens = match Self::tokenize_from_str(&source_file.source, source_file.start_pos, file_id) {
+            Err(msg) => return errors.push(msg),
+            Ok(tokens) => tokens
+        };
+        let mut parser = LiveParser::new(&tokens, &live_type_infos, file_id);
+        let mut ld = match parser.parse_live_document() {
+            Err(msg) => return errors.push(msg),
+            Ok(ld) => ld
+        };
+        let mut deps = BTreeSet::new();
+        for node in &mut ld.nodes {
+            match &mut node.value {
+                LiveValue::Import(live_import) => {
+                    if live_import.module_id.0 == live_id!(crate) { // crate refs stay relative to the source
+                        live_import.module_id.0 = source_module_id.0
+                    };
+                    deps.insert(live_import.module_id);
+                }
+                LiveValue::Class {live_type, ..} => {
+                    if let Some(infos) = self.live_type_infos.get(live_type) {
+                        for sub_type in &infos.fields {
+                            let sub_module_id = sub_type.live_type_info.module_id;
+                            if sub_module_id != module_id {
+                                deps.insert(sub_module_id);
+                            }
+                        }
+                    }
+                }
+                _ => ()
+            }
+        }
+        ld.tokens = tokens;
+        let live_file = self.file_id_to_file_mut(file_id);
+        live_file.original = ld;
+        live_file.deps = deps;
+        live_file.reexpand = true;
+        live_file.generation.next_gen();
+    }
+
     pub fn register_live_file(
         &mut self,
         file_name: &str,
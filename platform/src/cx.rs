@@ -13,7 +13,8 @@ use {
     crate::{
         makepad_live_compiler::{
             LiveRegistry,
-            LiveFileChange
+            LiveFileChange,
+            LiveModuleId,
         },
         makepad_shader_compiler::ShaderRegistry,
         draw_shader::CxDrawShaders,
@@ -92,6 +93,7 @@ pub struct Cx {
 
     pub (crate) live_file_change_receiver: std::sync::mpsc::Receiver<Vec<LiveFileChange>>,
     pub (crate) live_file_change_sender: std::sync::mpsc::Sender<Vec<LiveFileChange >>,
+    pub (crate) live_design_replacements: Vec<(LiveModuleId, LiveModuleId)>,
 
     pub shader_registry: ShaderRegistry,
     
@@ -242,6 +244,7 @@ impl Cx {
             
             live_file_change_receiver: recv,
             live_file_change_sender: send,
+            live_design_replacements: Vec::new(),
             
             shader_registry: ShaderRegistry::new(),
             
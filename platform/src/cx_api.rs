@@ -70,6 +70,7 @@ pub enum CxOsOp {
     StartDragging(Vec<DragItem>),
     UpdateMacosMenu(MacosMenu),
     ShowClipboardActions(String),
+    CopyToClipboard(String),
 
     HttpRequest{request_id: LiveId, request:HttpRequest},
 
@@ -147,6 +148,12 @@ impl Cx {
     pub fn show_clipboard_actions(&mut self, selected: String) {
         self.platform_ops.push(CxOsOp::ShowClipboardActions(selected));
     }
+    
+    // puts text on the clipboard outside of a TextCopy/TextCut event, when the text is not known
+    // until later, like when it comes from an app running in studio
+    pub fn copy_to_clipboard(&mut self, content: &str) {
+        self.platform_ops.push(CxOsOp::CopyToClipboard(content.to_string()));
+    }
 
     pub fn start_dragging(&mut self, items: Vec<DragItem>) {
         self.platform_ops.iter().for_each( | p | {
@@ -460,6 +467,13 @@ impl Cx {
             data,
         });
     }
+    
+    pub fn web_socket_send_string(&mut self, request_id: LiveId, data: String) {
+        self.platform_ops.push(CxOsOp::WebSocketSendString{
+            request_id,
+            data,
+        });
+    }
 
     pub fn initialize_video_decoding(&mut self, video_id: LiveId, video: Rc<Vec<u8>>) {
         self.platform_ops.push(CxOsOp::InitializeVideoDecoding(video_id, video));
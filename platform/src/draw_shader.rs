@@ -65,6 +65,8 @@ pub struct CxDrawShaders {
     pub os_shaders: Vec<CxOsDrawShader>,
     pub generation: u64,
     pub ptr_to_item: HashMap<DrawShaderPtr, CxDrawShaderItem>,
+    // the ptr each draw shader was compiled from, shaders shared by fingerprint only have a def there
+    pub id_to_ptr: HashMap<usize, DrawShaderPtr>,
     pub compile_set: BTreeSet<DrawShaderPtr>,
     pub fingerprints: Vec<DrawShaderFingerprint>,
     pub error_set: HashSet<DrawShaderPtr>,
@@ -86,6 +88,7 @@ impl Cx {
         self.shader_registry.flush_registry();
         self.draw_shaders.shaders.clear();
         self.draw_shaders.ptr_to_item.clear();
+        self.draw_shaders.id_to_ptr.clear();
         self.draw_shaders.fingerprints.clear();
         self.draw_shaders.error_set.clear();
         self.draw_shaders.error_fingerprints.clear();
@@ -334,6 +334,7 @@ impl DrawVars {
                         draw_shader_id,
                         options: self.options.clone()
                     });
+                    cx.draw_shaders.id_to_ptr.insert(draw_shader_id, draw_shader_ptr);
                     cx.draw_shaders.compile_set.insert(draw_shader_ptr);
                     // now we simply queue it somewhere somehow to compile.
                     self.draw_shader = Some(DrawShader {
@@ -1,10 +1,13 @@
 use {
     std::{
+        rc::Rc,
+        cell::RefCell,
         collections::{HashSet, HashMap}
     },
     crate::{
         //makepad_live_compiler::LiveEditEvent,
         makepad_live_id::LiveId,
+        makepad_micro_serde::*,
         cx::Cx,
         area::Area,
         //midi::{Midi1InputData, MidiInputInfo},
@@ -80,6 +83,8 @@ pub enum Event {
     VideoDecodingInitialized(VideoDecodingInitializedEvent),
     VideoChunkDecoded(LiveId),
     VideoDecodingError(VideoDecodingErrorEvent),
+    
+    WidgetTree(WidgetTreeEvent),
  
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
@@ -196,6 +201,30 @@ pub struct NextFrameEvent {
     pub set: HashSet<NextFrame>
 }
 
+// asks the app to describe its widgets, used by the studio widget inspector
+#[derive(Clone, Debug)]
+pub struct WidgetTreeEvent {
+    pub response: Rc<RefCell<Vec<WidgetTreeNode>>>
+}
+
+// one widget in depth first order, the rect is in window coordinates and the file position
+// points at the live_design that created it
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct WidgetTreeNode {
+    pub depth: usize,
+    pub id: String,
+    pub type_name: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub visible: bool,
+    pub text: String,
+    pub file_name: String,
+    pub line: u32,
+    pub column: u32,
+}
+
 #[derive(Clone, Debug)]
 pub struct TimerEvent {
     pub time: Option<f64>,
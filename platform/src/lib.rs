@@ -54,6 +54,12 @@ pub use makepad_objc_sys;
 #[cfg(target_os = "windows")]
 pub use ::makepad_windows as windows;
 
+#[cfg(target_os = "linux")]
+pub use makepad_http;
+
+#[cfg(any(target_os = "linux", target_os="macos", target_os="windows"))]
+pub use makepad_miniz;
+
 pub use makepad_futures;
  
 pub use {
@@ -176,6 +182,8 @@ pub use {
             KeyFocusEvent,
             TextInputEvent,
             TextClipboardEvent,
+            WidgetTreeEvent,
+            WidgetTreeNode,
             WindowCloseRequestedEvent,
             WindowClosedEvent,
             WindowDragQueryResponse,
@@ -224,9 +224,16 @@ impl Cx {
         while let Ok(changes) = self.live_file_change_receiver.try_recv(){
             all_changes.extend(changes);
         }
-        if all_changes.len()>0{
+        let replacements = std::mem::take(&mut self.live_design_replacements);
+        if all_changes.len()>0 || replacements.len()>0{
             let mut live_registry = self.live_registry.borrow_mut();
             let mut errs = Vec::new();
+            if replacements.len()>0{
+                for (module_id, source_module_id) in replacements{
+                    live_registry.replace_module_document(module_id, source_module_id, &mut errs);
+                }
+                live_registry.expand_all_documents(&mut errs);
+            }
             live_registry.process_file_changes(all_changes, &mut errs);
             for err in errs {
                 // alright we need to output the correct error
@@ -253,6 +260,13 @@ impl Cx {
         }
     }
     
+    /// Makes `module_id` use the live design of `source_module_id` from the next event loop
+    /// iteration on, re-applying the application like a live edit does. Passing the module
+    /// itself switches it back to its own design.
+    pub fn replace_live_design(&mut self, module_id: LiveModuleId, source_module_id: LiveModuleId){
+        self.live_design_replacements.push((module_id, source_module_id));
+    }
+    
     // ok so now what. now we should run the expansion
     pub fn live_expand(&mut self) {
         let mut errs = Vec::new();
@@ -316,6 +316,9 @@ impl Cx {
                 CxOsOp::HttpRequest{request_id, request} => {
                     make_http_request(request_id, request, self.os.network_response.sender.clone());
                 },
+                CxOsOp::CopyToClipboard(_content) => {
+                    crate::log!("Copy to clipboard not supported yet");
+                }
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 }
@@ -29,6 +29,7 @@ use {
                 ns_url_session::{make_http_request, web_socket_open},
             },
             metal_xpc::start_xpc_service,
+            headless::HeadlessOptions,
             apple_media::CxAppleMedia,
             apple_decoding::CxAppleDecoding,
             metal::{MetalCx, DrawPassMode},
@@ -142,6 +143,11 @@ impl Cx {
         init_apple_classes_global();
         cx.borrow_mut().self_ref = Some(cx.clone());
         cx.borrow_mut().os_type = OsType::Macos;
+        
+        if let Some(options) = HeadlessOptions::from_args() {
+            return Cx::headless_event_loop(cx, options);
+        }
+        
         let metal_cx: Rc<RefCell<MetalCx >> = Rc::new(RefCell::new(MetalCx::new()));
         
         
@@ -519,6 +525,9 @@ impl Cx {
                 CxOsOp::ShowClipboardActions(_request) => {
                     crate::log!("Show clipboard actions not supported yet");
                 }
+                CxOsOp::CopyToClipboard(content) => {
+                    get_macos_app_global().copy_to_clipboard(&content);
+                }
                 CxOsOp::WebSocketOpen {request_id, request} => {
                     web_socket_open(request_id, request, self.os.network_response.sender.clone());
                 }
@@ -628,6 +628,15 @@ impl MacosApp {
         }
     }
     
+    pub fn copy_to_clipboard(&mut self, content: &str) {
+        unsafe {
+            let nsstring = str_to_nsstring(content);
+            let array: ObjcId = msg_send![class!(NSArray), arrayWithObject: NSStringPboardType];
+            let () = msg_send![self.pasteboard, declareTypes: array owner: nil];
+            let () = msg_send![self.pasteboard, setString: nsstring forType: NSStringPboardType];
+        }
+    }
+    
     pub fn start_timer(&mut self, timer_id: u64, interval: f64, repeats: bool) {
         unsafe {
             let pool: ObjcId = msg_send![class!(NSAutoreleasePool), new];
@@ -1,7 +1,8 @@
 #![allow(dead_code)]
 use {
-    std::cell::Cell,
+    std::cell::{Cell, RefCell},
     std::collections::HashMap,
+    std::rc::Rc,
     crate::{
         cx::Cx,
         cursor::MouseCursor,
@@ -13,6 +14,10 @@ use {
             Event,
             TimerEvent,
             KeyEvent,
+            TextInputEvent,
+            TextClipboardEvent,
+            WidgetTreeEvent,
+            WidgetTreeNode,
             ScrollEvent,
             MouseDownEvent,
             MouseUpEvent,
@@ -389,6 +394,23 @@ impl From<StdinScroll> for ScrollEvent {
     }
 }
 
+#[derive(Clone, Debug, Default, SerBin, DeBin, SerJson, DeJson, PartialEq)]
+pub struct StdinTextInput{
+   pub input: String,
+   pub replace_last: bool,
+   pub was_paste: bool,
+}
+
+impl From<StdinTextInput> for TextInputEvent {
+    fn from(v: StdinTextInput) -> Self {
+        Self{
+            input: v.input,
+            replace_last: v.replace_last,
+            was_paste: v.was_paste,
+        }
+    }
+}
+
 #[derive(Clone, Debug, SerBin, DeBin, SerJson, DeJson)]
 pub enum HostToStdin{
     Swapchain(SharedSwapchain),
@@ -409,6 +431,12 @@ pub enum HostToStdin{
     MouseMove(StdinMouseMove),
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
+    TextInput(StdinTextInput),
+    // the client answers these with SetClipboard if it has a selection
+    TextCopy,
+    TextCut,
+    // the client answers with WidgetTree
+    RequestWidgetTree,
     Scroll(StdinScroll),
     ReloadFile{
         file:String,
@@ -432,7 +460,15 @@ pub enum StdinToHost {
     ReadyToStart,
     SetCursor(MouseCursor),
     // the client is done drawing, and the texture is completely updated
-    DrawCompleteAndFlip(PresentableDraw)
+    DrawCompleteAndFlip(PresentableDraw),
+    SetClipboard(String),
+    // position of the text cursor in the client window, for placing the IME
+    ShowTextIME{
+        x: f64,
+        y: f64,
+    },
+    HideTextIME,
+    WidgetTree(Vec<WidgetTreeNode>),
 }
 
 impl StdinToHost{
@@ -452,7 +488,26 @@ impl HostToStdin{
 }
 
 impl Cx {
+    // runs a copy or cut through the app and returns the selected text, if any
+    pub(crate) fn stdin_text_clipboard(&mut self, cut: bool) -> Option<String> {
+        let response = Rc::new(RefCell::new(None));
+        let e = TextClipboardEvent{response: response.clone()};
+        if cut {
+            self.call_event_handler(&Event::TextCut(e));
+        }
+        else {
+            self.call_event_handler(&Event::TextCopy(e));
+        }
+        let response = response.borrow_mut().take();
+        response
+    }
     
+    pub(crate) fn stdin_widget_tree(&mut self) -> Vec<WidgetTreeNode> {
+        let response = Rc::new(RefCell::new(Vec::new()));
+        self.call_event_handler(&Event::WidgetTree(WidgetTreeEvent{response: response.clone()}));
+        let nodes = response.replace(Vec::new());
+        nodes
+    }
 }
 
 
@@ -0,0 +1,362 @@
+use {
+    std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    },
+    super::{
+        headless_png::HeadlessFrame,
+        sw_renderer::CxSwRenderer,
+    },
+    crate::{
+        makepad_error_log::*,
+        makepad_live_id::*,
+        makepad_math::{DVec2, dvec2},
+        cx::Cx,
+        cx_api::CxOsOp,
+        area::Area,
+        event::*,
+        thread::Signal,
+        window::{CxWindowPool, WindowId},
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct HeadlessOptions {
+    // overrides the size windows ask for
+    pub size: Option<DVec2>,
+    pub dpi_factor: f64,
+    pub frames: usize,
+    pub frame_time: f64,
+    pub out: Option<String>,
+    pub golden: Option<String>,
+    pub tolerance: u8,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            size: None,
+            dpi_factor: 1.0,
+            frames: 3,
+            frame_time: 1.0 / 60.0,
+            out: None,
+            golden: None,
+            tolerance: 0,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    pub fn from_args() -> Option<Self> {
+        Self::parse_args(std::env::args())
+    }
+
+    // --headless [--headless-size=WxH] [--headless-dpi=F] [--headless-frames=N]
+    // [--headless-out=file.png] [--headless-golden=file.png] [--headless-tolerance=N]
+    pub fn parse_args(args: impl Iterator<Item = String>) -> Option<Self> {
+        let mut options = None;
+        let mut parsed = Self::default();
+        for arg in args {
+            if arg == "--headless" {
+                options = Some(());
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-size=") {
+                if let Some((w, h)) = value.split_once('x') {
+                    if let (Ok(w), Ok(h)) = (w.parse(), h.parse()) {
+                        parsed.size = Some(dvec2(w, h));
+                    }
+                }
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-dpi=") {
+                parsed.dpi_factor = value.parse().unwrap_or(parsed.dpi_factor);
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-frames=") {
+                parsed.frames = value.parse().unwrap_or(parsed.frames);
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-out=") {
+                parsed.out = Some(value.to_string());
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-golden=") {
+                parsed.golden = Some(value.to_string());
+            }
+            else if let Some(value) = arg.strip_prefix("--headless-tolerance=") {
+                parsed.tolerance = value.parse().unwrap_or(parsed.tolerance);
+            }
+        }
+        options.map( | _ | parsed)
+    }
+}
+
+struct HeadlessTimer {
+    timer_id: u64,
+    interval: f64,
+    next_due: f64,
+    repeats: bool,
+}
+
+// Drives a Cx without a display. Time is synthetic and advances by frame_time per step,
+// so animations and timers produce the same frames on every run.
+pub struct HeadlessRunner {
+    pub cx: Rc<RefCell<Cx>>,
+    pub options: HeadlessOptions,
+    pub renderer: CxSwRenderer,
+    time: f64,
+    timers: Vec<HeadlessTimer>,
+    quit: bool,
+}
+
+impl HeadlessRunner {
+    pub fn new(cx: Rc<RefCell<Cx>>, options: HeadlessOptions) -> Self {
+        cx.borrow_mut().self_ref = Some(cx.clone());
+        let mut runner = Self {
+            cx,
+            options,
+            renderer: CxSwRenderer::default(),
+            time: 0.0,
+            timers: Vec::new(),
+            quit: false,
+        };
+        runner.cx.borrow_mut().call_event_handler(&Event::Construct);
+        runner.cx.borrow_mut().redraw_all();
+        runner.handle_platform_ops();
+        runner
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn is_quit(&self) -> bool {
+        self.quit
+    }
+
+    // runs one frame: timers, signals, next frame, draw and repaint
+    pub fn step(&mut self) {
+        self.time += self.options.frame_time;
+        self.handle_platform_ops();
+
+        let time = self.time;
+        let mut due = Vec::new();
+        self.timers.retain_mut( | timer | {
+            if timer.next_due > time {
+                return true
+            }
+            due.push(timer.timer_id);
+            timer.next_due += timer.interval.max(1e-6);
+            timer.repeats
+        });
+        for timer_id in due {
+            self.send_event(&Event::Timer(TimerEvent {time: Some(time), timer_id}));
+        }
+
+        let mut cx = self.cx.borrow_mut();
+        if Signal::check_and_clear_ui_signal() {
+            cx.handle_media_signals();
+            cx.call_event_handler(&Event::Signal);
+        }
+        if cx.handle_live_edit() {
+            cx.call_event_handler(&Event::LiveEdit);
+            cx.redraw_all();
+        }
+        if cx.new_next_frames.len() != 0 {
+            cx.call_next_frame_event(time);
+        }
+        if cx.need_redrawing() {
+            cx.call_draw_event();
+            // shaders are interpreted from the AST, nothing to compile
+            cx.draw_shaders.compile_set.clear();
+        }
+        drop(cx);
+        self.handle_platform_ops();
+        self.cx.borrow_mut().headless_repaint(&mut self.renderer, time);
+    }
+
+    pub fn run_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            if self.quit {
+                break;
+            }
+            self.step();
+        }
+    }
+
+    pub fn send_event(&mut self, event: &Event) {
+        self.cx.borrow_mut().call_event_handler(event);
+        self.handle_platform_ops();
+    }
+
+    pub fn mouse_down(&mut self, abs: DVec2, button: usize) {
+        let time = self.time;
+        {
+            let mut cx = self.cx.borrow_mut();
+            cx.fingers.process_tap_count(abs, time);
+            cx.fingers.mouse_down(button);
+        }
+        self.send_event(&Event::MouseDown(MouseDownEvent {
+            abs,
+            button,
+            window_id: CxWindowPool::id_zero(),
+            modifiers: Default::default(),
+            handled: Cell::new(Area::Empty),
+            time
+        }));
+    }
+
+    pub fn mouse_move(&mut self, abs: DVec2) {
+        self.send_event(&Event::MouseMove(MouseMoveEvent {
+            abs,
+            window_id: CxWindowPool::id_zero(),
+            modifiers: Default::default(),
+            time: self.time,
+            handled: Cell::new(Area::Empty),
+        }));
+        let mut cx = self.cx.borrow_mut();
+        cx.fingers.cycle_hover_area(live_id!(mouse).into());
+        cx.fingers.switch_captures();
+    }
+
+    pub fn mouse_up(&mut self, abs: DVec2, button: usize) {
+        self.send_event(&Event::MouseUp(MouseUpEvent {
+            abs,
+            button,
+            window_id: CxWindowPool::id_zero(),
+            modifiers: Default::default(),
+            time: self.time
+        }));
+        let mut cx = self.cx.borrow_mut();
+        cx.fingers.mouse_up(button);
+        cx.fingers.cycle_hover_area(live_id!(mouse).into());
+    }
+
+    pub fn click(&mut self, abs: DVec2) {
+        self.mouse_move(abs);
+        self.mouse_down(abs, 0);
+        self.mouse_up(abs, 0);
+    }
+
+    pub fn scroll(&mut self, abs: DVec2, scroll: DVec2) {
+        self.send_event(&Event::Scroll(ScrollEvent {
+            window_id: CxWindowPool::id_zero(),
+            scroll,
+            abs,
+            modifiers: Default::default(),
+            handled_x: Cell::new(false),
+            handled_y: Cell::new(false),
+            is_mouse: true,
+            time: self.time
+        }));
+    }
+
+    pub fn key_down(&mut self, key_code: KeyCode, modifiers: KeyModifiers) {
+        let event = KeyEvent {key_code, is_repeat: false, modifiers, time: self.time};
+        self.cx.borrow_mut().keyboard.process_key_down(event.clone());
+        self.send_event(&Event::KeyDown(event));
+    }
+
+    pub fn key_up(&mut self, key_code: KeyCode, modifiers: KeyModifiers) {
+        let event = KeyEvent {key_code, is_repeat: false, modifiers, time: self.time};
+        self.cx.borrow_mut().keyboard.process_key_up(event.clone());
+        self.send_event(&Event::KeyUp(event));
+    }
+
+    pub fn text_input(&mut self, input: &str) {
+        self.send_event(&Event::TextInput(TextInputEvent {
+            input: input.to_string(),
+            replace_last: false,
+            was_paste: false
+        }));
+    }
+
+    pub fn frame(&self, window_id: WindowId) -> Option<HeadlessFrame> {
+        self.renderer.window_frame(window_id)
+    }
+
+    pub fn main_window_frame(&self) -> Option<HeadlessFrame> {
+        self.frame(CxWindowPool::id_zero())
+    }
+
+    fn handle_platform_ops(&mut self) {
+        let mut cx = self.cx.borrow_mut();
+        while let Some(op) = cx.platform_ops.pop() {
+            match op {
+                CxOsOp::CreateWindow(window_id) => {
+                    let window = &mut cx.windows[window_id];
+                    let size = self.options.size.or(window.create_inner_size).unwrap_or(dvec2(800., 600.));
+                    window.window_geom = WindowGeom {
+                        dpi_factor: window.dpi_override.unwrap_or(self.options.dpi_factor),
+                        inner_size: size,
+                        outer_size: size,
+                        position: window.create_position.unwrap_or_default(),
+                        ..Default::default()
+                    };
+                    window.is_created = true;
+                }
+                CxOsOp::CloseWindow(window_id) => {
+                    cx.windows[window_id].is_created = false;
+                    self.renderer.remove_window(window_id);
+                }
+                CxOsOp::Quit => {
+                    self.quit = true;
+                }
+                CxOsOp::StartTimer {timer_id, interval, repeats} => {
+                    self.timers.retain( | timer | timer.timer_id != timer_id);
+                    self.timers.push(HeadlessTimer {
+                        timer_id,
+                        interval,
+                        next_due: self.time + interval,
+                        repeats
+                    });
+                }
+                CxOsOp::StopTimer(timer_id) => {
+                    self.timers.retain( | timer | timer.timer_id != timer_id);
+                }
+                // there is no os to talk to, windows stay as they are and
+                // network requests are dropped
+                _ => ()
+            }
+        }
+    }
+}
+
+impl Cx {
+    pub fn headless_event_loop(cx: Rc<RefCell<Cx>>, options: HeadlessOptions) {
+        let mut runner = HeadlessRunner::new(cx, options.clone());
+        runner.run_frames(options.frames);
+
+        let frame = runner.main_window_frame();
+        runner.cx.borrow_mut().call_event_handler(&Event::Destruct);
+
+        let frame = if let Some(frame) = frame {
+            frame
+        }
+        else {
+            error!("headless: no window was rendered");
+            std::process::exit(1);
+        };
+        if let Some(out) = &options.out {
+            if let Err(e) = std::fs::write(out, frame.to_png()) {
+                error!("headless: cannot write {}: {:?}", out, e);
+                std::process::exit(1);
+            }
+        }
+        if let Some(golden) = &options.golden {
+            let golden_frame = match std::fs::read(golden) {
+                Ok(data) => HeadlessFrame::from_png(&data),
+                Err(e) => Err(format!("{:?}", e))
+            };
+            match golden_frame {
+                Ok(golden_frame) => {
+                    let diff = frame.diff(&golden_frame, options.tolerance);
+                    if !diff.is_match() {
+                        error!("headless: frame differs from {}: {:?}", golden, diff);
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    error!("headless: cannot read golden image {}: {}", golden, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
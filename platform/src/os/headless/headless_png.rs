@@ -0,0 +1,197 @@
+// Minimal PNG reading and writing for headless screenshots and golden images.
+// Only 8 bit RGB(A) without interlacing is supported, which is all we produce.
+
+use crate::makepad_miniz::{compress_to_vec_zlib, decompress_to_vec_zlib};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessFrame {
+    pub width: usize,
+    pub height: usize,
+    // RGBA8, rows top to bottom
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeadlessDiff {
+    pub size_mismatch: bool,
+    pub differing_pixels: usize,
+    pub max_channel_delta: u8,
+}
+
+impl HeadlessDiff {
+    pub fn is_match(&self) -> bool {
+        !self.size_mismatch && self.differing_pixels == 0
+    }
+}
+
+impl HeadlessFrame {
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let o = (y * self.width + x) * 4;
+        [self.pixels[o], self.pixels[o + 1], self.pixels[o + 2], self.pixels[o + 3]]
+    }
+
+    // pixels count as different when any channel is off by more than tolerance
+    pub fn diff(&self, other: &HeadlessFrame, tolerance: u8) -> HeadlessDiff {
+        if self.width != other.width || self.height != other.height {
+            return HeadlessDiff {size_mismatch: true, ..Default::default()}
+        }
+        let mut diff = HeadlessDiff::default();
+        for (a, b) in self.pixels.chunks_exact(4).zip(other.pixels.chunks_exact(4)) {
+            let delta = (0..4).map( | c | a[c].abs_diff(b[c])).max().unwrap_or(0);
+            diff.max_channel_delta = diff.max_channel_delta.max(delta);
+            if delta > tolerance {
+                diff.differing_pixels += 1;
+            }
+        }
+        diff
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut ihdr = Vec::with_capacity(13);
+        ihdr.extend_from_slice(&(self.width as u32).to_be_bytes());
+        ihdr.extend_from_slice(&(self.height as u32).to_be_bytes());
+        ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+        // every scanline uses the Sub filter, UI screenshots compress well with it
+        let stride = self.width * 4;
+        let mut raw = Vec::with_capacity((stride + 1) * self.height);
+        for row in self.pixels.chunks_exact(stride.max(1)).take(self.height) {
+            raw.push(1);
+            for i in 0..stride {
+                let left = if i >= 4 {row[i - 4]} else {0};
+                raw.push(row[i].wrapping_sub(left));
+            }
+        }
+
+        let mut out = PNG_SIGNATURE.to_vec();
+        write_chunk(&mut out, b"IHDR", &ihdr);
+        write_chunk(&mut out, b"IDAT", &compress_to_vec_zlib(&raw, 6));
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    pub fn from_png(data: &[u8]) -> Result<HeadlessFrame, String> {
+        if data.len() < 8 || data[0..8] != PNG_SIGNATURE {
+            return Err("not a png file".to_string())
+        }
+        let mut pos = 8;
+        let mut header = None;
+        let mut idat = Vec::new();
+        while pos + 8 <= data.len() {
+            let len = u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
+            let kind = &data[pos + 4..pos + 8];
+            let body = data.get(pos + 8..pos + 8 + len).ok_or("truncated png chunk")?;
+            match kind {
+                b"IHDR" if len >= 13 => {
+                    let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+                    let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]) as usize;
+                    let (depth, color_type, interlace) = (body[8], body[9], body[12]);
+                    if depth != 8 || interlace != 0 {
+                        return Err(format!("unsupported png: bit depth {} interlace {}", depth, interlace))
+                    }
+                    let channels = match color_type {
+                        2 => 3,
+                        6 => 4,
+                        _ => return Err(format!("unsupported png color type {}", color_type))
+                    };
+                    header = Some((width, height, channels));
+                }
+                b"IDAT" => idat.extend_from_slice(body),
+                b"IEND" => break,
+                _ => ()
+            }
+            pos += 12 + len;
+        }
+        let (width, height, channels) = header.ok_or("png has no header")?;
+        let raw = decompress_to_vec_zlib(&idat).map_err( | e | format!("png inflate failed {:?}", e))?;
+        let stride = width * channels;
+        if raw.len() < (stride + 1) * height {
+            return Err("png image data too short".to_string())
+        }
+
+        let mut prev = vec![0u8; stride];
+        let mut line = vec![0u8; stride];
+        let mut pixels = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            let row = &raw[y * (stride + 1)..(y + 1) * (stride + 1)];
+            let filter = row[0];
+            for i in 0..stride {
+                let a = if i >= channels {line[i - channels]} else {0};
+                let b = prev[i];
+                let c = if i >= channels {prev[i - channels]} else {0};
+                let predict = match filter {
+                    0 => 0,
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    4 => paeth(a, b, c),
+                    _ => return Err(format!("invalid png filter {}", filter))
+                };
+                line[i] = row[i + 1].wrapping_add(predict);
+            }
+            for px in line.chunks_exact(channels) {
+                pixels.extend_from_slice(&px[0..3]);
+                pixels.push(if channels == 4 {px[3]} else {255});
+            }
+            std::mem::swap(&mut prev, &mut line);
+        }
+        Ok(HeadlessFrame {width, height, pixels})
+    }
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {a} else if pb <= pc {b} else {c}
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(body);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {(crc >> 1) ^ 0xedb8_8320} else {crc >> 1};
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_round_trip() {
+        let mut pixels = Vec::new();
+        for y in 0..7u8 {
+            for x in 0..5u8 {
+                pixels.extend_from_slice(&[x * 40, y * 30, x ^ y, 255 - x]);
+            }
+        }
+        let frame = HeadlessFrame {width: 5, height: 7, pixels};
+        let decoded = HeadlessFrame::from_png(&frame.to_png()).unwrap();
+        assert_eq!(decoded, frame);
+        assert!(decoded.diff(&frame, 0).is_match());
+    }
+
+    #[test]
+    fn diff_counts_pixels_over_tolerance() {
+        let a = HeadlessFrame {width: 2, height: 1, pixels: vec![10, 10, 10, 255, 0, 0, 0, 255]};
+        let b = HeadlessFrame {width: 2, height: 1, pixels: vec![12, 10, 10, 255, 0, 9, 0, 255]};
+        let diff = a.diff(&b, 2);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_channel_delta, 9);
+        assert!(a.diff(&HeadlessFrame {width: 1, height: 1, pixels: vec![0; 4]}, 0).size_mismatch);
+    }
+}
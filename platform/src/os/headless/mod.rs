@@ -0,0 +1,8 @@
+pub mod headless;
+pub mod headless_png;
+pub mod sw_renderer;
+pub mod sw_shader;
+
+pub use self::headless::{HeadlessOptions, HeadlessRunner};
+pub use self::headless_png::{HeadlessFrame, HeadlessDiff};
+pub use self::sw_renderer::CxSwRenderer;
@@ -0,0 +1,611 @@
+use {
+    std::collections::HashMap,
+    super::{
+        headless_png::HeadlessFrame,
+        sw_shader::{SwShader, SwSampler, SwUniforms, SwInvocation, SwMask, SW_LANES, SW_ALL_LANES},
+    },
+    crate::{
+        cx::Cx,
+        makepad_math::{Mat4, Vec4},
+        texture::TextureFormat,
+        pass::{CxPassParent, PassClearColor, PassClearDepth, PassId},
+        draw_list::DrawListId,
+        draw_vars::DRAW_CALL_TEXTURE_SLOTS,
+        window::WindowId,
+    },
+};
+
+// a cpu side colour buffer, rows are stored top-down like on Metal and D3D
+pub struct SwTarget {
+    pub width: usize,
+    pub height: usize,
+    pub color: Vec<[f32; 4]>,
+    pub depth: Option<Vec<f32 >>,
+    // unorm targets clamp after blending, float targets don't
+    pub is_unorm: bool,
+}
+
+impl SwTarget {
+    fn new(width: usize, height: usize, is_unorm: bool) -> Self {
+        Self {
+            width,
+            height,
+            color: vec![[0.0; 4]; width * height],
+            depth: None,
+            is_unorm,
+        }
+    }
+
+    // reuses the buffers when the size didn't change, returns true if they were reallocated
+    fn resize(&mut self, width: usize, height: usize) -> bool {
+        if self.width == width && self.height == height {
+            return false
+        }
+        *self = Self::new(width, height, self.is_unorm);
+        true
+    }
+
+    fn clear_color(&mut self, color: Vec4) {
+        self.color.fill([color.x, color.y, color.z, color.w]);
+    }
+
+    fn clear_depth(&mut self, depth: f32) {
+        let len = self.width * self.height;
+        let buf = self.depth.get_or_insert_with(Vec::new);
+        buf.clear();
+        buf.resize(len, depth);
+    }
+
+    pub fn to_frame(&self, opaque: bool) -> HeadlessFrame {
+        let mut pixels = Vec::with_capacity(self.color.len() * 4);
+        for c in &self.color {
+            pixels.push(unorm8(c[0]));
+            pixels.push(unorm8(c[1]));
+            pixels.push(unorm8(c[2]));
+            pixels.push(if opaque {255} else {unorm8(c[3])});
+        }
+        HeadlessFrame {
+            width: self.width,
+            height: self.height,
+            pixels
+        }
+    }
+}
+
+fn unorm8(v: f32) -> u8 {
+    (v.max(0.0).min(1.0) * 255.0 + 0.5) as u8
+}
+
+#[derive(Default)]
+pub struct CxSwRenderer {
+    // render target textures by texture index
+    targets: HashMap<usize, SwTarget>,
+    depth_buffers: HashMap<usize, Vec<f32 >>,
+    windows: Vec<(WindowId, SwTarget)>,
+}
+
+impl CxSwRenderer {
+    pub fn window_frame(&self, window_id: WindowId) -> Option<HeadlessFrame> {
+        self.windows.iter().find( | (id, _) | *id == window_id).map( | (_, target) | target.to_frame(true))
+    }
+
+    pub fn remove_window(&mut self, window_id: WindowId) {
+        self.windows.retain( | (id, _) | *id != window_id);
+    }
+
+    fn take_window(&mut self, window_id: WindowId) -> SwTarget {
+        if let Some(index) = self.windows.iter().position( | (id, _) | *id == window_id) {
+            self.windows.remove(index).1
+        }
+        else {
+            SwTarget::new(0, 0, true)
+        }
+    }
+}
+
+enum SwTextureRef<'a> {
+    None,
+    Format(&'a TextureFormat),
+    Target(&'a SwTarget),
+}
+
+struct SwTextureSlots<'a> {
+    slots: Vec<SwTextureRef<'a >>,
+}
+
+impl<'a> SwSampler for SwTextureSlots<'a> {
+    fn sample(&self, slot: usize, x: f32, y: f32) -> [f32; 4] {
+        // nearest filtering with clamp to edge. this matches the gl backend for every format
+        // except VecMipBGRAu8_32, which gl minifies with LINEAR_MIPMAP_LINEAR (opengl.rs) while we
+        // always read mip level 0 unfiltered. so a downscaled image looks sharper/aliased here than
+        // on a gpu, and golden images have to come from the headless renderer, not gpu screenshots
+        fn texel(width: usize, height: usize, x: f32, y: f32) -> Option<(usize, usize)> {
+            if width == 0 || height == 0 || !x.is_finite() || !y.is_finite() {
+                return None
+            }
+            let tx = ((x * width as f32).floor().max(0.0) as usize).min(width - 1);
+            let ty = ((y * height as f32).floor().max(0.0) as usize).min(height - 1);
+            Some((tx, ty))
+        }
+        let unorm = | v: u8 | v as f32 / 255.0;
+        match self.slots.get(slot) {
+            Some(SwTextureRef::Target(target)) => {
+                if let Some((tx, ty)) = texel(target.width, target.height, x, y) {
+                    return target.color[ty * target.width + tx]
+                }
+            }
+            Some(SwTextureRef::Format(format)) => match format {
+                TextureFormat::VecBGRAu8_32 {width, height, data} |
+                TextureFormat::VecMipBGRAu8_32 {width, height, data, ..} => {
+                    if let Some((tx, ty)) = texel(*width, *height, x, y) {
+                        if let Some(v) = data.get(ty * width + tx) {
+                            return [
+                                unorm(((v >> 16) & 0xff) as u8),
+                                unorm(((v >> 8) & 0xff) as u8),
+                                unorm((v & 0xff) as u8),
+                                unorm((v >> 24) as u8)
+                            ]
+                        }
+                    }
+                }
+                TextureFormat::VecRGBAf32 {width, height, data} => {
+                    if let Some((tx, ty)) = texel(*width, *height, x, y) {
+                        let o = (ty * width + tx) * 4;
+                        if let Some(v) = data.get(o..o + 4) {
+                            return [v[0], v[1], v[2], v[3]]
+                        }
+                    }
+                }
+                TextureFormat::VecRu8 {width, height, data, unpack_row_length} => {
+                    if let Some((tx, ty)) = texel(*width, *height, x, y) {
+                        let stride = unpack_row_length.unwrap_or(*width);
+                        if let Some(v) = data.get(ty * stride + tx) {
+                            return [unorm(*v), 0.0, 0.0, 1.0]
+                        }
+                    }
+                }
+                TextureFormat::VecRGu8 {width, height, data, unpack_row_length} => {
+                    if let Some((tx, ty)) = texel(*width, *height, x, y) {
+                        let o = (ty * unpack_row_length.unwrap_or(*width) + tx) * 2;
+                        if let Some(v) = data.get(o..o + 2) {
+                            return [unorm(v[0]), unorm(v[1]), 0.0, 1.0]
+                        }
+                    }
+                }
+                TextureFormat::VecRf32 {width, height, data} => {
+                    if let Some((tx, ty)) = texel(*width, *height, x, y) {
+                        if let Some(v) = data.get(ty * width + tx) {
+                            return [*v, 0.0, 0.0, 1.0]
+                        }
+                    }
+                }
+                _ => ()
+            }
+            _ => ()
+        }
+        [0.0, 0.0, 0.0, 0.0]
+    }
+}
+
+struct SwVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    // 1/w, for perspective correct interpolation
+    inv_w: f32,
+    clipped: bool,
+    varyings: Vec<f32>,
+}
+
+// edge function, positive when p is left of a->b in y-down screen space
+fn sw_edge(ax: f32, ay: f32, bx: f32, by: f32, px: f32, py: f32) -> f32 {
+    (bx - ax) * (py - ay) - (by - ay) * (px - ax)
+}
+
+// of two triangles sharing an edge exactly one sees it as an including edge,
+// so pixels on the diagonal of a quad are only blended once
+fn sw_edge_includes(ax: f32, ay: f32, bx: f32, by: f32) -> bool {
+    let (dx, dy) = (bx - ax, by - ay);
+    dy > 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+fn sw_draw_triangle(
+    shader: &SwShader,
+    target: &mut SwTarget,
+    depth_test: bool,
+    verts: [&SwVertex; 3],
+) {
+    if verts.iter().any( | v | v.clipped) {
+        return
+    }
+    let [v0, mut v1, mut v2] = verts;
+    let mut area = sw_edge(v0.x, v0.y, v1.x, v1.y, v2.x, v2.y);
+    if area == 0.0 || !area.is_finite() {
+        return
+    }
+    // there is no face culling, flip to a consistent winding
+    if area < 0.0 {
+        std::mem::swap(&mut v1, &mut v2);
+        area = -area;
+    }
+    let (w, h) = (target.width as i64, target.height as i64);
+    let min_x = (v0.x.min(v1.x).min(v2.x).floor() as i64).max(0) & !1;
+    let min_y = (v0.y.min(v1.y).min(v2.y).floor() as i64).max(0) & !1;
+    let max_x = (v0.x.max(v1.x).max(v2.x).ceil() as i64).min(w);
+    let max_y = (v0.y.max(v1.y).max(v2.y).ceil() as i64).min(h);
+    if min_x >= max_x || min_y >= max_y {
+        return
+    }
+    let include = [
+        sw_edge_includes(v1.x, v1.y, v2.x, v2.y),
+        sw_edge_includes(v2.x, v2.y, v0.x, v0.y),
+        sw_edge_includes(v0.x, v0.y, v1.x, v1.y),
+    ];
+    let varying_len = v0.varyings.len();
+    let mut inv = shader.new_invocation(true);
+    let mut lane_varyings = vec![0.0f32; varying_len];
+
+    let mut qy = min_y;
+    while qy < max_y {
+        let mut qx = min_x;
+        while qx < max_x {
+            let mut coverage: SwMask = 0;
+            let mut bary = [[0.0f32; 3]; SW_LANES];
+            let mut depth = [0.0f32; SW_LANES];
+            for lane in 0..SW_LANES {
+                let px = qx + (lane & 1) as i64;
+                let py = qy + (lane >> 1) as i64;
+                let (fx, fy) = (px as f32 + 0.5, py as f32 + 0.5);
+                let e = [
+                    sw_edge(v1.x, v1.y, v2.x, v2.y, fx, fy),
+                    sw_edge(v2.x, v2.y, v0.x, v0.y, fx, fy),
+                    sw_edge(v0.x, v0.y, v1.x, v1.y, fx, fy),
+                ];
+                let l = [e[0] / area, e[1] / area, e[2] / area];
+                // perspective correct weights
+                let p = [l[0] * v0.inv_w, l[1] * v1.inv_w, l[2] * v2.inv_w];
+                let sum = p[0] + p[1] + p[2];
+                bary[lane] = if sum != 0.0 {[p[0] / sum, p[1] / sum, p[2] / sum]} else {l};
+                // relative to v0 so triangles at a constant depth keep it exactly, overlapping
+                // instances of one draw call would fight over the depth test otherwise
+                depth[lane] = v0.z + l[1] * (v1.z - v0.z) + l[2] * (v2.z - v0.z);
+
+                if px >= w || py >= h {
+                    continue;
+                }
+                let inside = (0..3).all( | i | e[i] > 0.0 || (e[i] == 0.0 && include[i]));
+                if !inside || !(0.0..=1.0).contains(&depth[lane]) {
+                    continue;
+                }
+                if depth_test {
+                    if let Some(buf) = &target.depth {
+                        if depth[lane] > buf[py as usize * target.width + px as usize] {
+                            continue;
+                        }
+                    }
+                }
+                coverage |= 1 << lane;
+            }
+            if coverage != 0 {
+                // helper lanes run as well so derivatives stay valid
+                for (lane, b) in bary.iter().enumerate() {
+                    for i in 0..varying_len {
+                        lane_varyings[i] = b[0] * v0.varyings[i] + b[1] * v1.varyings[i] + b[2] * v2.varyings[i];
+                    }
+                    load_varyings(shader, &mut inv, lane, &lane_varyings);
+                }
+                let color = shader.run_pixel(&mut inv, SW_ALL_LANES);
+                for lane in 0..SW_LANES {
+                    if coverage & (1 << lane) == 0 {
+                        continue;
+                    }
+                    let px = (qx + (lane & 1) as i64) as usize;
+                    let py = (qy + (lane >> 1) as i64) as usize;
+                    let index = py * target.width + px;
+                    if depth_test {
+                        if let Some(buf) = &mut target.depth {
+                            buf[index] = depth[lane];
+                        }
+                    }
+                    // premultiplied alpha: ONE, ONE_MINUS_SRC_ALPHA
+                    let src = [color.v[0][lane], color.v[1][lane], color.v[2][lane], color.v[3][lane]];
+                    let dst = &mut target.color[index];
+                    for c in 0..4 {
+                        let v = src[c] + dst[c] * (1.0 - src[3]);
+                        dst[c] = if target.is_unorm {v.max(0.0).min(1.0)} else {v};
+                    }
+                }
+            }
+            qx += 2;
+        }
+        qy += 2;
+    }
+}
+
+fn load_varyings(shader: &SwShader, inv: &mut SwInvocation, lane: usize, data: &[f32]) {
+    let mut offset = 0;
+    for field in shader.varyings() {
+        offset += inv.fields[*field].read_lane(lane, data.get(offset..).unwrap_or(&[]));
+    }
+}
+
+fn sw_draw_instance(
+    shader: &SwShader,
+    target: &mut SwTarget,
+    depth_test: bool,
+    instance: &[f32],
+    vertices: &[f32],
+    geometry_slots: usize,
+    indices: &[u32],
+) {
+    let vertex_count = if geometry_slots == 0 {0} else {vertices.len() / geometry_slots};
+    let (w, h) = (target.width as f32, target.height as f32);
+    let mut shaded = Vec::with_capacity(vertex_count);
+    for start in (0..vertex_count).step_by(SW_LANES) {
+        let lanes = (vertex_count - start).min(SW_LANES);
+        let mask = ((1u32 << lanes) - 1) as SwMask;
+        let mut inv = shader.new_invocation(false);
+        shader.load_instance(&mut inv, instance);
+        for lane in 0..lanes {
+            let offset = (start + lane) * geometry_slots;
+            shader.load_geometry_lane(&mut inv, lane, &vertices[offset..offset + geometry_slots]);
+        }
+        let pos = shader.run_vertex(&mut inv, mask);
+        for lane in 0..lanes {
+            let cw = pos.v[3][lane];
+            let mut varyings = Vec::new();
+            for field in shader.varyings() {
+                inv.fields[*field].write_lane(lane, &mut varyings);
+            }
+            // no near plane clipping, primitives that cross w=0 are dropped
+            let clipped = !(cw > 0.0) || pos.len < 4;
+            let inv_w = if clipped {0.0} else {1.0 / cw};
+            shaded.push(SwVertex {
+                x: (pos.v[0][lane] * inv_w * 0.5 + 0.5) * w,
+                y: (0.5 - pos.v[1][lane] * inv_w * 0.5) * h,
+                z: pos.v[2][lane] * inv_w * 0.5 + 0.5,
+                inv_w,
+                clipped,
+                varyings
+            });
+        }
+    }
+    for tri in indices.chunks_exact(3) {
+        let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+        if a < shaded.len() && b < shaded.len() && c < shaded.len() {
+            sw_draw_triangle(shader, target, depth_test, [&shaded[a], &shaded[b], &shaded[c]]);
+        }
+    }
+}
+
+impl Cx {
+    pub (crate) fn headless_repaint(&mut self, renderer: &mut CxSwRenderer, time: f64) {
+        let mut passes_todo = Vec::new();
+        self.compute_pass_repaint_order(&mut passes_todo);
+        self.repaint_id += 1;
+        for pass_id in &passes_todo {
+            self.passes[*pass_id].set_time(time as f32);
+            match self.passes[*pass_id].parent.clone() {
+                CxPassParent::Window(window_id) => {
+                    self.headless_draw_pass_to_window(renderer, *pass_id, window_id);
+                }
+                CxPassParent::Pass(_) | CxPassParent::None => {
+                    self.headless_draw_pass_to_texture(renderer, *pass_id);
+                }
+            }
+        }
+    }
+
+    fn headless_setup_render_pass(&mut self, pass_id: PassId) -> Option<(usize, usize)> {
+        let dpi_factor = self.passes[pass_id].dpi_factor?;
+        let pass_rect = self.get_pass_rect2(pass_id, dpi_factor)?;
+        self.passes[pass_id].paint_dirty = false;
+        if pass_rect.size.x < 0.5 || pass_rect.size.y < 0.5 {
+            return None
+        }
+        self.passes[pass_id].set_matrix(pass_rect.pos, pass_rect.size);
+        self.passes[pass_id].set_dpi_factor(dpi_factor);
+        let size = dpi_factor * pass_rect.size;
+        Some((size.x as usize, size.y as usize))
+    }
+
+    fn headless_draw_pass_to_window(&mut self, renderer: &mut CxSwRenderer, pass_id: PassId, window_id: WindowId) {
+        let draw_list_id = if let Some(id) = self.passes[pass_id].main_draw_list_id {id} else {return};
+        self.headless_setup_render_pass(pass_id);
+        self.passes[pass_id].paint_dirty = false;
+
+        let geom = &self.windows[window_id].window_geom;
+        let width = (geom.inner_size.x * geom.dpi_factor).floor() as usize;
+        let height = (geom.inner_size.y * geom.dpi_factor).floor() as usize;
+        let mut target = renderer.take_window(window_id);
+        target.resize(width, height);
+
+        let pass = &self.passes[pass_id];
+        let clear_color = if pass.color_textures.is_empty() {
+            pass.clear_color
+        }
+        else {
+            match pass.color_textures[0].clear_color {
+                PassClearColor::InitWith(color) => color,
+                PassClearColor::ClearWith(color) => color
+            }
+        };
+        let clear_depth = match pass.clear_depth {
+            PassClearDepth::InitWith(depth) => depth,
+            PassClearDepth::ClearWith(depth) => depth
+        };
+        if !pass.dont_clear || target.depth.is_none() {
+            target.clear_color(clear_color);
+            target.clear_depth(clear_depth);
+        }
+
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.headless_render_view(renderer, &mut target, true, pass_id, draw_list_id, &mut zbias, zbias_step);
+        renderer.windows.push((window_id, target));
+    }
+
+    fn headless_draw_pass_to_texture(&mut self, renderer: &mut CxSwRenderer, pass_id: PassId) {
+        let draw_list_id = if let Some(id) = self.passes[pass_id].main_draw_list_id {id} else {return};
+        let (width, height) = if let Some(size) = self.headless_setup_render_pass(pass_id) {size} else {return};
+
+        // like the GPU backends we only render into the first colour attachment,
+        // but all of them get allocated and cleared
+        let mut first_target = None;
+        for (index, color_texture) in self.passes[pass_id].color_textures.iter().enumerate() {
+            let texture_index = color_texture.texture.texture_id().0;
+            let cxtexture = &mut self.textures[color_texture.texture.texture_id()];
+            cxtexture.alloc_render(width, height);
+            let is_initial = cxtexture.check_initial();
+            let is_unorm = matches!(cxtexture.format, TextureFormat::RenderBGRAu8 {..});
+            let mut target = renderer.targets.remove(&texture_index).unwrap_or_else( || SwTarget::new(0, 0, is_unorm));
+            target.is_unorm = is_unorm;
+            let resized = target.resize(width, height);
+            match color_texture.clear_color {
+                PassClearColor::InitWith(color) => if is_initial || resized {
+                    target.clear_color(color);
+                }
+                PassClearColor::ClearWith(color) => {
+                    target.clear_color(color);
+                }
+            }
+            if index == 0 {
+                first_target = Some((texture_index, target));
+            }
+            else {
+                renderer.targets.insert(texture_index, target);
+            }
+        }
+        let (texture_index, mut target) = if let Some(first) = first_target {first} else {return};
+
+        // depth testing only happens when the pass has a depth texture
+        let mut depth_index = None;
+        if let Some(depth_texture) = &self.passes[pass_id].depth_texture {
+            let index = depth_texture.texture_id().0;
+            let cxtexture = &mut self.textures[depth_texture.texture_id()];
+            cxtexture.alloc_depth(width, height);
+            let is_initial = cxtexture.check_initial();
+            let depth = renderer.depth_buffers.remove(&index).unwrap_or_default();
+            let resized = depth.len() != width * height;
+            target.depth = Some(depth);
+            match self.passes[pass_id].clear_depth {
+                PassClearDepth::InitWith(clear) => if is_initial || resized {
+                    target.clear_depth(clear);
+                }
+                PassClearDepth::ClearWith(clear) => {
+                    target.clear_depth(clear);
+                }
+            }
+            depth_index = Some(index);
+        }
+        let depth_test = depth_index.is_some();
+
+        let mut zbias = 0.0;
+        let zbias_step = self.passes[pass_id].zbias_step;
+        self.headless_render_view(renderer, &mut target, depth_test, pass_id, draw_list_id, &mut zbias, zbias_step);
+
+        if let (Some(index), Some(depth)) = (depth_index, target.depth.take()) {
+            renderer.depth_buffers.insert(index, depth);
+        }
+        renderer.targets.insert(texture_index, target);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn headless_render_view(
+        &mut self,
+        renderer: &CxSwRenderer,
+        target: &mut SwTarget,
+        depth_test: bool,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        zbias: &mut f32,
+        zbias_step: f32,
+    ) {
+        let draw_items_len = self.draw_lists[draw_list_id].draw_items.len();
+        self.draw_lists[draw_list_id].uniform_view_transform(&Mat4::identity());
+
+        for draw_item_id in 0..draw_items_len {
+            if let Some(sub_list_id) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.sub_list() {
+                self.headless_render_view(renderer, target, depth_test, pass_id, sub_list_id, zbias, zbias_step);
+                continue;
+            }
+            if let Some(draw_call) = self.draw_lists[draw_list_id].draw_items[draw_item_id].kind.draw_call_mut() {
+                draw_call.draw_uniforms.set_zbias(*zbias);
+                *zbias += zbias_step;
+            }
+            else {
+                continue;
+            }
+            self.headless_render_draw_call(renderer, target, depth_test, pass_id, draw_list_id, draw_item_id);
+        }
+    }
+
+    fn headless_render_draw_call(
+        &self,
+        renderer: &CxSwRenderer,
+        target: &mut SwTarget,
+        depth_test: bool,
+        pass_id: PassId,
+        draw_list_id: DrawListId,
+        draw_item_id: usize,
+    ) {
+        let draw_list = &self.draw_lists[draw_list_id];
+        let draw_item = &draw_list.draw_items[draw_item_id];
+        let draw_call = if let Some(draw_call) = draw_item.kind.draw_call() {draw_call} else {return};
+        let sh = &self.draw_shaders.shaders[draw_call.draw_shader.draw_shader_id];
+
+        let instance_slots = sh.mapping.instances.total_slots;
+        let instances = if let Some(instances) = &draw_item.instances {instances} else {return};
+        if instance_slots == 0 || instances.len() < instance_slots {
+            return
+        }
+        let geometry = if let Some(geometry_id) = draw_call.geometry_id {&self.geometries[geometry_id]} else {return};
+        // shaders with the same fingerprint share one draw shader, only the first ptr has a def
+        let def = self.shader_registry.draw_shader_defs.get(&draw_call.draw_shader.draw_shader_ptr).or_else( || {
+            let ptr = self.draw_shaders.id_to_ptr.get(&draw_call.draw_shader.draw_shader_id) ?;
+            self.shader_registry.draw_shader_defs.get(ptr)
+        });
+        let def = if let Some(def) = def {def} else {return};
+
+        let mut sampler = SwTextureSlots {slots: Vec::with_capacity(DRAW_CALL_TEXTURE_SLOTS)};
+        for texture in &draw_call.texture_slots {
+            sampler.slots.push(match texture {
+                Some(texture) => {
+                    let texture_id = texture.texture_id();
+                    if let Some(target) = renderer.targets.get(&texture_id.0) {
+                        SwTextureRef::Target(target)
+                    }
+                    else {
+                        SwTextureRef::Format(&self.textures[texture_id].format)
+                    }
+                }
+                None => SwTextureRef::None
+            });
+        }
+        let du = &draw_call.draw_uniforms;
+        let draw_uniforms = [du.draw_zbias, du.pad1, du.pad2, du.pad3];
+        let uniforms = SwUniforms {
+            pass: self.passes[pass_id].pass_uniforms.as_slice(),
+            view: &draw_list.draw_list_uniforms.view_transform,
+            draw: &draw_uniforms,
+            user: &draw_call.user_uniforms,
+        };
+        let shader = if let Some(shader) = SwShader::new(&self.shader_registry, def, &sh.mapping, uniforms, &sampler) {
+            shader
+        }
+        else {
+            return
+        };
+        for instance in instances.chunks_exact(instance_slots) {
+            sw_draw_instance(
+                &shader,
+                target,
+                depth_test,
+                instance,
+                &geometry.vertices,
+                sh.mapping.geometries.total_slots,
+                &geometry.indices
+            );
+        }
+    }
+}
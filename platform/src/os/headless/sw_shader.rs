@@ -0,0 +1,1495 @@
+// A small interpreter that executes draw shaders straight from the shader compiler AST.
+// Every invocation runs 4 lanes at once, a 2x2 pixel quad in the pixel stage, so that
+// dFdx/dFdy can be computed the way a GPU does it. Control flow is handled with lane masks.
+
+use {
+    std::{
+        cell::RefCell,
+        collections::HashMap,
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_math::Vec4,
+        makepad_live_compiler::LivePtr,
+        makepad_shader_compiler::{
+            shader_ast::*,
+            swizzle::Swizzle,
+            ShaderRegistry,
+        },
+        draw_shader::CxDrawShaderMapping,
+    }
+};
+
+pub (crate) const SW_LANES: usize = 4;
+pub (crate) type SwMask = u8;
+pub (crate) const SW_ALL_LANES: SwMask = 0b1111;
+
+type SwLanes = [f32; SW_LANES];
+type SwLocal = (LiveId, usize, SwVal);
+
+// shadow id used for for-loop counters, the analyser doesn't hand those out
+const SW_LOOP_SHADOW: usize = usize::MAX;
+
+pub (crate) trait SwSampler {
+    fn sample(&self, slot: usize, x: f32, y: f32) -> [f32; 4];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub (crate) enum SwKind {
+    Bool,
+    Int,
+    Float
+}
+
+#[derive(Clone, Copy, Debug)]
+pub (crate) struct SwNum {
+    pub kind: SwKind,
+    pub len: usize,
+    // component major: v[component][lane]
+    pub v: [SwLanes; 4],
+}
+
+#[derive(Clone, Debug)]
+pub (crate) struct SwMat {
+    pub n: usize,
+    // column major: v[col * n + row][lane]
+    pub v: [SwLanes; 16],
+}
+
+#[derive(Clone, Debug)]
+pub (crate) enum SwVal {
+    Void,
+    Num(SwNum),
+    Mat(Box<SwMat>),
+    Struct(Vec<SwVal>),
+    Array(Vec<SwVal>),
+    Texture(usize),
+}
+
+impl SwNum {
+    pub fn splat(kind: SwKind, len: usize, value: f32) -> Self {
+        Self {kind, len, v: [[value; SW_LANES]; 4]}
+    }
+
+    pub fn float(value: f32) -> Self {
+        Self::splat(SwKind::Float, 1, value)
+    }
+
+    // lanes where the first component is non-zero
+    pub fn mask(&self) -> SwMask {
+        let mut mask = 0;
+        for l in 0..SW_LANES {
+            if self.v[0][l] != 0.0 {
+                mask |= 1 << l;
+            }
+        }
+        mask
+    }
+
+    // scalars broadcast over all components
+    fn get(&self, c: usize, l: usize) -> f32 {
+        if self.len == 1 {self.v[0][l]} else {self.v[c.min(3)][l]}
+    }
+
+    fn convert(mut self, kind: SwKind) -> Self {
+        if self.kind != kind {
+            for c in 0..self.len {
+                for l in 0..SW_LANES {
+                    self.v[c][l] = convert_scalar(self.v[c][l], kind);
+                }
+            }
+            self.kind = kind;
+        }
+        self
+    }
+
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        let mut out = *self;
+        for c in 0..self.len {
+            for l in 0..SW_LANES {
+                out.v[c][l] = f(self.v[c][l]);
+            }
+        }
+        out
+    }
+
+    fn zip(&self, other: &SwNum, kind: SwKind, f: impl Fn(f32, f32) -> f32) -> Self {
+        let len = self.len.max(other.len);
+        let mut out = Self::splat(kind, len, 0.0);
+        for c in 0..len {
+            for l in 0..SW_LANES {
+                out.v[c][l] = f(self.get(c, l), other.get(c, l));
+            }
+        }
+        out
+    }
+
+    fn zip3(&self, b: &SwNum, c: &SwNum, f: impl Fn(f32, f32, f32) -> f32) -> Self {
+        let len = self.len.max(b.len).max(c.len);
+        let mut out = Self::splat(self.kind, len, 0.0);
+        for i in 0..len {
+            for l in 0..SW_LANES {
+                out.v[i][l] = f(self.get(i, l), b.get(i, l), c.get(i, l));
+            }
+        }
+        out
+    }
+
+    fn dot(&self, other: &SwNum) -> Self {
+        let mut out = Self::float(0.0);
+        for c in 0..self.len {
+            for l in 0..SW_LANES {
+                out.v[0][l] += self.v[c][l] * other.v[c][l];
+            }
+        }
+        out
+    }
+
+    fn length(&self) -> Self {
+        self.dot(self).map( | v | v.sqrt())
+    }
+
+    fn scale(&self, s: &SwNum) -> Self {
+        self.zip(s, SwKind::Float, | a, b | a * b)
+    }
+}
+
+fn convert_scalar(v: f32, kind: SwKind) -> f32 {
+    match kind {
+        SwKind::Bool => if v != 0.0 {1.0} else {0.0},
+        SwKind::Int => v.trunc(),
+        SwKind::Float => v
+    }
+}
+
+impl SwMat {
+    fn zero(n: usize) -> Self {
+        Self {n, v: [[0.0; SW_LANES]; 16]}
+    }
+
+    fn identity(n: usize, value: &SwLanes) -> Self {
+        let mut out = Self::zero(n);
+        for i in 0..n {
+            out.v[i * n + i] = *value;
+        }
+        out
+    }
+
+    fn map(&self, f: impl Fn(f32) -> f32) -> Self {
+        let mut out = self.clone();
+        for i in 0..self.n * self.n {
+            for l in 0..SW_LANES {
+                out.v[i][l] = f(self.v[i][l]);
+            }
+        }
+        out
+    }
+
+    fn zip(&self, other: &SwMat, f: impl Fn(f32, f32) -> f32) -> Self {
+        let mut out = self.clone();
+        for i in 0..self.n * self.n {
+            for l in 0..SW_LANES {
+                out.v[i][l] = f(self.v[i][l], other.v[i][l]);
+            }
+        }
+        out
+    }
+
+    fn mul_mat(&self, other: &SwMat) -> Self {
+        let n = self.n;
+        let mut out = Self::zero(n);
+        for c in 0..n {
+            for r in 0..n {
+                for k in 0..n {
+                    for l in 0..SW_LANES {
+                        out.v[c * n + r][l] += self.v[k * n + r][l] * other.v[c * n + k][l];
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn mul_vec(&self, v: &SwNum) -> SwNum {
+        let n = self.n;
+        let mut out = SwNum::splat(SwKind::Float, n, 0.0);
+        for r in 0..n {
+            for c in 0..n {
+                for l in 0..SW_LANES {
+                    out.v[r][l] += self.v[c * n + r][l] * v.v[c][l];
+                }
+            }
+        }
+        out
+    }
+
+    fn vec_mul(&self, v: &SwNum) -> SwNum {
+        let n = self.n;
+        let mut out = SwNum::splat(SwKind::Float, n, 0.0);
+        for c in 0..n {
+            for r in 0..n {
+                for l in 0..SW_LANES {
+                    out.v[c][l] += v.v[r][l] * self.v[c * n + r][l];
+                }
+            }
+        }
+        out
+    }
+
+    fn transpose(&self) -> Self {
+        let n = self.n;
+        let mut out = Self::zero(n);
+        for c in 0..n {
+            for r in 0..n {
+                out.v[r * n + c] = self.v[c * n + r];
+            }
+        }
+        out
+    }
+
+    fn inverse(&self) -> Self {
+        let n = self.n;
+        let mut out = Self::zero(n);
+        for l in 0..SW_LANES {
+            // gauss-jordan on a row major copy
+            let mut a = [[0.0f64; 8]; 4];
+            for r in 0..n {
+                for c in 0..n {
+                    a[r][c] = self.v[c * n + r][l] as f64;
+                }
+                a[r][n + r] = 1.0;
+            }
+            for col in 0..n {
+                let mut pivot = col;
+                for r in col + 1..n {
+                    if a[r][col].abs() > a[pivot][col].abs() {
+                        pivot = r;
+                    }
+                }
+                a.swap(col, pivot);
+                let p = a[col][col];
+                if p == 0.0 {
+                    continue;
+                }
+                for c in 0..2 * n {
+                    a[col][c] /= p;
+                }
+                for r in 0..n {
+                    if r != col {
+                        let f = a[r][col];
+                        for c in 0..2 * n {
+                            a[r][c] -= f * a[col][c];
+                        }
+                    }
+                }
+            }
+            for r in 0..n {
+                for c in 0..n {
+                    out.v[c * n + r][l] = a[r][n + c] as f32;
+                }
+            }
+        }
+        out
+    }
+}
+
+impl SwVal {
+    pub fn num(&self) -> SwNum {
+        match self {
+            SwVal::Num(num) => *num,
+            _ => SwNum::float(0.0)
+        }
+    }
+
+    // takes the lanes in mask from a, the others from b
+    pub fn select(mask: SwMask, a: SwVal, b: SwVal) -> SwVal {
+        if mask == SW_ALL_LANES {
+            return a
+        }
+        if mask == 0 {
+            return b
+        }
+        match (a, b) {
+            (SwVal::Num(mut a), SwVal::Num(b)) => {
+                for c in 0..4 {
+                    for l in 0..SW_LANES {
+                        if mask & (1 << l) == 0 {
+                            a.v[c][l] = b.v[c][l];
+                        }
+                    }
+                }
+                SwVal::Num(a)
+            }
+            (SwVal::Mat(mut a), SwVal::Mat(b)) => {
+                for i in 0..16 {
+                    for l in 0..SW_LANES {
+                        if mask & (1 << l) == 0 {
+                            a.v[i][l] = b.v[i][l];
+                        }
+                    }
+                }
+                SwVal::Mat(a)
+            }
+            (SwVal::Struct(a), SwVal::Struct(b)) => {
+                SwVal::Struct(a.into_iter().zip(b).map( | (a, b) | SwVal::select(mask, a, b)).collect())
+            }
+            (SwVal::Array(a), SwVal::Array(b)) => {
+                SwVal::Array(a.into_iter().zip(b).map( | (a, b) | SwVal::select(mask, a, b)).collect())
+            }
+            (a, _) => a
+        }
+    }
+
+    // appends the components of one lane, used to pass varyings to the rasterizer
+    pub fn write_lane(&self, lane: usize, out: &mut Vec<f32>) {
+        match self {
+            SwVal::Num(num) => for c in 0..num.len {
+                out.push(num.v[c][lane]);
+            }
+            SwVal::Mat(mat) => for i in 0..mat.n * mat.n {
+                out.push(mat.v[i][lane]);
+            }
+            SwVal::Array(items) => for item in items {
+                item.write_lane(lane, out);
+            }
+            _ => ()
+        }
+    }
+
+    // the inverse of write_lane, returns the number of floats consumed
+    pub fn read_lane(&mut self, lane: usize, input: &[f32]) -> usize {
+        match self {
+            SwVal::Num(num) => {
+                for c in 0..num.len {
+                    num.v[c][lane] = input.get(c).cloned().unwrap_or(0.0);
+                }
+                num.len
+            }
+            SwVal::Mat(mat) => {
+                for i in 0..mat.n * mat.n {
+                    mat.v[i][lane] = input.get(i).cloned().unwrap_or(0.0);
+                }
+                mat.n * mat.n
+            }
+            SwVal::Array(items) => {
+                let mut used = 0;
+                for item in items {
+                    used += item.read_lane(lane, input.get(used..).unwrap_or(&[]));
+                }
+                used
+            }
+            _ => 0
+        }
+    }
+
+    fn read_all_lanes(&mut self, input: &[f32]) {
+        for lane in 0..SW_LANES {
+            self.read_lane(lane, input);
+        }
+    }
+
+    fn from_val(val: &Val) -> SwVal {
+        match val {
+            Val::Bool(v) => SwVal::Num(SwNum::splat(SwKind::Bool, 1, if *v {1.0} else {0.0})),
+            Val::Int(v) => SwVal::Num(SwNum::splat(SwKind::Int, 1, *v as f32)),
+            Val::Float(v) => SwVal::Num(SwNum::float(*v)),
+            Val::Vec4(v) => SwVal::vec4(*v)
+        }
+    }
+
+    fn vec4(v: Vec4) -> SwVal {
+        let mut out = SwNum::splat(SwKind::Float, 4, 0.0);
+        out.v = [[v.x; SW_LANES], [v.y; SW_LANES], [v.z; SW_LANES], [v.w; SW_LANES]];
+        SwVal::Num(out)
+    }
+}
+
+struct SwClosure<'a> {
+    owner: &'a FnDef,
+    def: &'a ClosureDef,
+    captured: Vec<SwLocal>,
+}
+
+struct SwFrame<'a> {
+    // the fn being executed, its params hold the closure decls
+    fn_def: &'a FnDef,
+    // the fn whose closure defs and const table slice apply to the code being executed
+    owner: &'a FnDef,
+    const_offset: Option<usize>,
+    locals: Vec<SwLocal>,
+    closures: Vec<(usize, SwClosure<'a>)>,
+    ret: SwVal,
+    returned: SwMask,
+    brk: SwMask,
+    cont: SwMask,
+}
+
+impl<'a> SwFrame<'a> {
+    fn active(&self, mask: SwMask) -> SwMask {
+        mask & !(self.returned | self.brk | self.cont)
+    }
+
+    fn find_local(&self, ident: LiveId, shadow: usize) -> Option<usize> {
+        self.locals.iter().rposition( | (i, s, _) | *i == ident && *s == shadow)
+            .or_else( || self.locals.iter().rposition( | (i, s, _) | *i == ident && *s == SW_LOOP_SHADOW))
+    }
+}
+
+pub (crate) struct SwInvocation {
+    pub fields: Vec<SwVal>,
+    pub is_pixel: bool,
+}
+
+pub (crate) struct SwUniforms<'a> {
+    pub pass: &'a [f32],
+    pub view: &'a [f32],
+    pub draw: &'a [f32],
+    pub user: &'a [f32],
+}
+
+pub (crate) struct SwShader<'a> {
+    registry: &'a ShaderRegistry,
+    def: &'a DrawShaderDef,
+    mapping: &'a CxDrawShaderMapping,
+    sampler: &'a dyn SwSampler,
+    vertex_fn: &'a FnDef,
+    pixel_fn: &'a FnDef,
+    field_index: HashMap<LiveId, usize>,
+    // uniform and texture values per field, Void for per-invocation fields
+    globals: Vec<SwVal>,
+    live_values: HashMap<LivePtr, SwVal>,
+    varyings: Vec<usize>,
+    swizzles: RefCell<HashMap<LiveId, Vec<usize >> >,
+}
+
+impl<'a> SwShader<'a> {
+    pub fn new(
+        registry: &'a ShaderRegistry,
+        def: &'a DrawShaderDef,
+        mapping: &'a CxDrawShaderMapping,
+        uniforms: SwUniforms<'a>,
+        sampler: &'a dyn SwSampler
+    ) -> Option<Self> {
+        let vertex_fn = registry.draw_shader_method_decl_from_ident(def, Ident(live_id!(vertex))) ?;
+        let pixel_fn = registry.draw_shader_method_decl_from_ident(def, Ident(live_id!(pixel))) ?;
+
+        let mut shader = Self {
+            registry,
+            def,
+            mapping,
+            sampler,
+            vertex_fn,
+            pixel_fn,
+            field_index: HashMap::new(),
+            globals: Vec::new(),
+            live_values: HashMap::new(),
+            varyings: Vec::new(),
+            swizzles: RefCell::new(HashMap::new()),
+        };
+
+        for (index, field) in def.fields.iter().enumerate() {
+            shader.field_index.insert(field.ident.0, index);
+            let ty = field.ty_expr.ty.borrow().clone().unwrap_or(Ty::Void);
+            let global = match &field.kind {
+                DrawShaderFieldKind::Uniform {block_ident, ..} => {
+                    let (inputs, buf) = match block_ident.0 {
+                        live_id!(draw) => (&mapping.draw_uniforms, uniforms.draw),
+                        live_id!(view) => (&mapping.view_uniforms, uniforms.view),
+                        live_id!(pass) => (&mapping.pass_uniforms, uniforms.pass),
+                        _ => (&mapping.user_uniforms, uniforms.user),
+                    };
+                    let mut value = shader.zero(&ty);
+                    if let Some(input) = inputs.inputs.iter().find( | input | input.id == field.ident.0) {
+                        value.read_all_lanes(buf.get(input.offset..).unwrap_or(&[]));
+                    }
+                    value
+                }
+                DrawShaderFieldKind::Texture {..} => {
+                    let slot = mapping.textures.iter().position( | t | t.id == field.ident.0).unwrap_or(0);
+                    SwVal::Texture(slot)
+                }
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} => {
+                    if is_used_in_pixel_shader.get() {
+                        shader.varyings.push(index);
+                    }
+                    SwVal::Void
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    shader.varyings.push(index);
+                    SwVal::Void
+                }
+            };
+            shader.globals.push(global);
+        }
+
+        for input in &mapping.live_uniforms.inputs {
+            let mut value = shader.zero(&input.ty);
+            value.read_all_lanes(mapping.live_uniforms_buf.get(input.offset..).unwrap_or(&[]));
+            if let Some(live_ptr) = input.live_ptr {
+                shader.live_values.insert(live_ptr, value);
+            }
+        }
+        Some(shader)
+    }
+
+    // the draw shader fields that travel from the vertex to the pixel stage
+    pub fn varyings(&self) -> &[usize] {
+        &self.varyings
+    }
+
+    pub fn new_invocation(&self, is_pixel: bool) -> SwInvocation {
+        SwInvocation {
+            is_pixel,
+            fields: self.def.fields.iter().enumerate().map( | (index, field) | {
+                if let SwVal::Void = self.globals[index] {
+                    self.zero(field.ty_expr.ty.borrow().as_ref().unwrap_or(&Ty::Void))
+                }
+                else {
+                    SwVal::Void
+                }
+            }).collect()
+        }
+    }
+
+    pub fn load_instance(&self, inv: &mut SwInvocation, instance: &[f32]) {
+        for input in &self.mapping.instances.inputs {
+            if let Some(index) = self.field_index.get(&input.id) {
+                inv.fields[*index].read_all_lanes(instance.get(input.offset..).unwrap_or(&[]));
+            }
+        }
+    }
+
+    pub fn load_geometry_lane(&self, inv: &mut SwInvocation, lane: usize, vertex: &[f32]) {
+        for input in &self.mapping.geometries.inputs {
+            if let Some(index) = self.field_index.get(&input.id) {
+                inv.fields[*index].read_lane(lane, vertex.get(input.offset..).unwrap_or(&[]));
+            }
+        }
+    }
+
+    pub fn run_vertex(&self, inv: &mut SwInvocation, mask: SwMask) -> SwNum {
+        self.call_fn(inv, self.vertex_fn, Vec::new(), Vec::new(), mask).0.num()
+    }
+
+    pub fn run_pixel(&self, inv: &mut SwInvocation, mask: SwMask) -> SwNum {
+        self.call_fn(inv, self.pixel_fn, Vec::new(), Vec::new(), mask).0.num()
+    }
+
+    fn zero(&self, ty: &Ty) -> SwVal {
+        let num = | kind, len | SwVal::Num(SwNum::splat(kind, len, 0.0));
+        match ty {
+            Ty::Bool => num(SwKind::Bool, 1),
+            Ty::Bvec2 => num(SwKind::Bool, 2),
+            Ty::Bvec3 => num(SwKind::Bool, 3),
+            Ty::Bvec4 => num(SwKind::Bool, 4),
+            Ty::Int => num(SwKind::Int, 1),
+            Ty::Ivec2 => num(SwKind::Int, 2),
+            Ty::Ivec3 => num(SwKind::Int, 3),
+            Ty::Ivec4 => num(SwKind::Int, 4),
+            Ty::Float | Ty::Enum(_) => num(SwKind::Float, 1),
+            Ty::Vec2 => num(SwKind::Float, 2),
+            Ty::Vec3 => num(SwKind::Float, 3),
+            Ty::Vec4 => num(SwKind::Float, 4),
+            Ty::Mat2 => SwVal::Mat(Box::new(SwMat::zero(2))),
+            Ty::Mat3 => SwVal::Mat(Box::new(SwMat::zero(3))),
+            Ty::Mat4 => SwVal::Mat(Box::new(SwMat::zero(4))),
+            Ty::Array {elem_ty, len} => SwVal::Array((0..*len).map( | _ | self.zero(elem_ty)).collect()),
+            Ty::Struct(struct_ptr) => match self.registry.structs.get(struct_ptr) {
+                Some(struct_def) => SwVal::Struct(struct_def.fields.iter().map( | field | {
+                    self.zero(field.ty_expr.ty.borrow().as_ref().unwrap_or(&Ty::Void))
+                }).collect()),
+                None => SwVal::Void
+            },
+            Ty::Texture2D => SwVal::Texture(0),
+            _ => SwVal::Void
+        }
+    }
+
+    fn call_fn(
+        &self,
+        inv: &mut SwInvocation,
+        fn_def: &'a FnDef,
+        mut args: Vec<SwVal>,
+        closures: Vec<(usize, SwClosure<'a>)>,
+        mask: SwMask
+    ) -> (SwVal, Vec<(usize, SwVal)>) {
+        let mut frame = SwFrame {
+            fn_def,
+            owner: fn_def,
+            const_offset: self.mapping.const_table.offsets.get(&fn_def.fn_ptr).cloned(),
+            locals: Vec::new(),
+            closures,
+            ret: SwVal::Void,
+            returned: 0,
+            brk: 0,
+            cont: 0
+        };
+        for (index, param) in fn_def.params.iter().enumerate() {
+            if let Some(shadow) = param.shadow.get() {
+                let arg = if index < args.len() {std::mem::replace(&mut args[index], SwVal::Void)} else {SwVal::Void};
+                frame.locals.push((param.ident.0, shadow.0, arg));
+            }
+        }
+        self.exec_block(inv, &mut frame, &fn_def.block, mask);
+
+        let mut outs = Vec::new();
+        for (index, param) in fn_def.params.iter().enumerate() {
+            if let (true, Some(shadow)) = (param.is_inout, param.shadow.get()) {
+                if let Some(local) = frame.find_local(param.ident.0, shadow.0) {
+                    outs.push((index, std::mem::replace(&mut frame.locals[local].2, SwVal::Void)));
+                }
+            }
+        }
+        (frame.ret, outs)
+    }
+
+    fn call_closure(
+        &self,
+        inv: &mut SwInvocation,
+        frame: &mut SwFrame<'a>,
+        param_index: usize,
+        arg_exprs: &'a [Expr],
+        mask: SwMask
+    ) -> SwVal {
+        let closure = if let Some((_, closure)) = frame.closures.iter().find( | (p, _) | *p == param_index) {
+            closure
+        }
+        else {
+            return SwVal::Void
+        };
+        let (owner, def) = (closure.owner, closure.def);
+        let mut locals = closure.captured.clone();
+
+        let decl_params = match frame.fn_def.params.get(param_index).map( | p | &p.ty_expr.kind) {
+            Some(TyExprKind::ClosureDecl {params, ..}) => Some(params),
+            _ => None
+        };
+
+        let args: Vec<SwVal> = arg_exprs.iter().map( | arg | self.eval(inv, frame, arg, mask)).collect();
+        for (param, arg) in def.params.iter().zip(args) {
+            if let Some(shadow) = param.shadow.get() {
+                locals.push((param.ident.0, shadow.0, arg));
+            }
+        }
+        let mut closure_frame = SwFrame {
+            fn_def: owner,
+            owner,
+            const_offset: self.mapping.const_table.offsets.get(&owner.fn_ptr).cloned(),
+            locals,
+            closures: Vec::new(),
+            ret: SwVal::Void,
+            returned: 0,
+            brk: 0,
+            cont: 0
+        };
+        match &def.kind {
+            ClosureDefKind::Expr(expr) => {
+                closure_frame.ret = self.eval(inv, &mut closure_frame, expr, mask);
+            }
+            ClosureDefKind::Block(block) => {
+                self.exec_block(inv, &mut closure_frame, block, mask);
+            }
+        }
+        // write back inout closure params
+        if let Some(decl_params) = decl_params {
+            for (index, (decl, param)) in decl_params.iter().zip(def.params.iter()).enumerate() {
+                if let (true, Some(shadow), Some(arg_expr)) = (decl.is_inout, param.shadow.get(), arg_exprs.get(index)) {
+                    if let Some(local) = closure_frame.find_local(param.ident.0, shadow.0) {
+                        let value = closure_frame.locals[local].2.clone();
+                        self.assign(inv, frame, arg_expr, value, mask);
+                    }
+                }
+            }
+        }
+        closure_frame.ret
+    }
+
+    fn make_closure(&self, frame: &SwFrame<'a>, index: ClosureDefIndex) -> Option<SwClosure<'a >> {
+        let def = frame.owner.closure_defs.get(index.0) ?;
+        let mut captured = Vec::new();
+        if let Some(syms) = def.closed_over_syms.borrow().as_ref() {
+            for sym in syms {
+                if let Ty::DrawShader(_) = sym.ty {
+                    continue;
+                }
+                if let Some(local) = frame.find_local(sym.ident.0, sym.shadow.0) {
+                    captured.push((sym.ident.0, sym.shadow.0, frame.locals[local].2.clone()));
+                }
+            }
+        }
+        Some(SwClosure {owner: frame.owner, def, captured})
+    }
+
+    fn eval_call(
+        &self,
+        inv: &mut SwInvocation,
+        frame: &mut SwFrame<'a>,
+        fn_def: &'a FnDef,
+        arg_exprs: &'a [Expr],
+        mask: SwMask
+    ) -> SwVal {
+        let mut args = Vec::new();
+        let mut closures = Vec::new();
+        for (index, arg_expr) in arg_exprs.iter().enumerate() {
+            if let ExprKind::ClosureDef(closure_index) = arg_expr.kind {
+                if let Some(closure) = self.make_closure(frame, closure_index) {
+                    closures.push((index, closure));
+                }
+                args.push(SwVal::Void);
+            }
+            else {
+                args.push(self.eval(inv, frame, arg_expr, mask));
+            }
+        }
+        let (ret, outs) = self.call_fn(inv, fn_def, args, closures, mask);
+        for (index, value) in outs {
+            self.assign(inv, frame, &arg_exprs[index], value, mask);
+        }
+        ret
+    }
+
+    fn exec_block(&self, inv: &mut SwInvocation, frame: &mut SwFrame<'a>, block: &'a Block, mask: SwMask) {
+        let base = frame.locals.len();
+        for stmt in &block.stmts {
+            if frame.active(mask) == 0 {
+                break;
+            }
+            self.exec_stmt(inv, frame, stmt, mask);
+        }
+        frame.locals.truncate(base);
+    }
+
+    fn exec_stmt(&self, inv: &mut SwInvocation, frame: &mut SwFrame<'a>, stmt: &'a Stmt, mask: SwMask) {
+        let mask = frame.active(mask);
+        match stmt {
+            Stmt::Break {..} => {
+                frame.brk |= mask;
+            }
+            Stmt::Continue {..} => {
+                frame.cont |= mask;
+            }
+            Stmt::For {ident, from_expr, to_expr, step_expr, block, ..} => {
+                let const_int = | expr: &Expr | {
+                    expr.const_val.borrow().as_ref().and_then( | v | v.as_ref()).and_then( | v | v.to_int()).unwrap_or(0)
+                };
+                let from = const_int(from_expr);
+                let to = const_int(to_expr);
+                let step = if let Some(step_expr) = step_expr {const_int(step_expr)} else if from < to {1} else {-1};
+                let step = if from <= to {step.abs()} else {-step.abs()};
+                if step == 0 {
+                    return
+                }
+                let (saved_brk, saved_cont) = (frame.brk, frame.cont);
+                frame.brk = 0;
+                let mut i = if from <= to {from} else {from - 1};
+                while if from <= to {i < to} else {i >= to} {
+                    frame.cont = 0;
+                    if frame.active(mask) == 0 {
+                        break;
+                    }
+                    frame.locals.push((ident.0, SW_LOOP_SHADOW, SwVal::Num(SwNum::splat(SwKind::Int, 1, i as f32))));
+                    self.exec_block(inv, frame, block, mask);
+                    frame.locals.pop();
+                    i += step;
+                }
+                frame.brk = saved_brk;
+                frame.cont = saved_cont;
+            }
+            Stmt::If {expr, block_if_true, block_if_false, ..} => {
+                let cond = self.eval(inv, frame, expr, mask).num().mask();
+                if mask & cond != 0 {
+                    self.exec_block(inv, frame, block_if_true, mask & cond);
+                }
+                if let Some(block_if_false) = block_if_false {
+                    if mask & !cond != 0 {
+                        self.exec_block(inv, frame, block_if_false, mask & !cond);
+                    }
+                }
+            }
+            Stmt::Match {expr, matches, ..} => {
+                let value = self.eval(inv, frame, expr, mask).num();
+                let mut remaining = mask;
+                for item in matches {
+                    let enum_value = item.enum_value.get().unwrap_or(0) as f32;
+                    let mut hit = 0;
+                    for l in 0..SW_LANES {
+                        if (value.v[0][l] - enum_value).abs() < 0.5 {
+                            hit |= 1 << l;
+                        }
+                    }
+                    if remaining & hit != 0 {
+                        self.exec_block(inv, frame, &item.block, remaining & hit);
+                    }
+                    remaining &= !hit;
+                }
+            }
+            Stmt::Let {ty, shadow, ident, expr, ..} => {
+                let value = if let Some(expr) = expr {
+                    self.eval(inv, frame, expr, mask)
+                }
+                else {
+                    self.zero(ty.borrow().as_ref().unwrap_or(&Ty::Void))
+                };
+                let shadow = shadow.get().map( | s | s.0).unwrap_or(0);
+                frame.locals.push((ident.0, shadow, value));
+            }
+            Stmt::Return {expr, ..} => {
+                if let Some(expr) = expr {
+                    let value = self.eval(inv, frame, expr, mask);
+                    let ret = std::mem::replace(&mut frame.ret, SwVal::Void);
+                    frame.ret = if let SwVal::Void = ret {value} else {SwVal::select(mask, value, ret)};
+                }
+                frame.returned |= mask;
+            }
+            Stmt::Block {block, ..} => {
+                self.exec_block(inv, frame, block, mask);
+            }
+            Stmt::Expr {expr, ..} => {
+                self.eval(inv, frame, expr, mask);
+            }
+        }
+    }
+
+    fn const_value(&self, frame: &SwFrame<'a>, expr: &Expr) -> Option<SwVal> {
+        let const_val = expr.const_val.borrow();
+        let val = const_val.as_ref()?.as_ref()?;
+        // prefer the const table, live edits patch it in place
+        if let (Some(index), Some(offset)) = (expr.const_index.get(), frame.const_offset) {
+            let table = &self.mapping.const_table.table;
+            match val {
+                Val::Float(_) => if let Some(v) = table.get(offset + index) {
+                    return Some(SwVal::Num(SwNum::float(*v)))
+                }
+                Val::Vec4(_) => if let Some(v) = table.get(offset + index..offset + index + 4) {
+                    return Some(SwVal::vec4(Vec4 {x: v[0], y: v[1], z: v[2], w: v[3]}))
+                }
+                _ => ()
+            }
+        }
+        Some(SwVal::from_val(val))
+    }
+
+    fn swizzle(&self, ident: Ident) -> Vec<usize> {
+        if let Some(indices) = self.swizzles.borrow().get(&ident.0) {
+            return indices.clone()
+        }
+        let indices: Vec<usize> = Swizzle::parse(ident).map( | s | s.iter().cloned().collect()).unwrap_or_default();
+        self.swizzles.borrow_mut().insert(ident.0, indices.clone());
+        indices
+    }
+
+    fn struct_field_index(&self, struct_ptr: StructPtr, ident: Ident) -> Option<usize> {
+        self.registry.structs.get(&struct_ptr) ?.fields.iter().position( | field | field.ident == ident)
+    }
+
+    fn read_draw_shader_field(&self, inv: &SwInvocation, ident: Ident) -> SwVal {
+        if let Some(index) = self.field_index.get(&ident.0) {
+            if let SwVal::Void = self.globals[*index] {
+                return inv.fields[*index].clone()
+            }
+            return self.globals[*index].clone()
+        }
+        SwVal::Void
+    }
+
+    fn eval(&self, inv: &mut SwInvocation, frame: &mut SwFrame<'a>, expr: &'a Expr, mask: SwMask) -> SwVal {
+        if let Some(value) = self.const_value(frame, expr) {
+            return value
+        }
+        match &expr.kind {
+            ExprKind::Cond {expr, expr_if_true, expr_if_false, ..} => {
+                let cond = self.eval(inv, frame, expr, mask).num().mask();
+                let a = self.eval(inv, frame, expr_if_true, mask & cond);
+                let b = self.eval(inv, frame, expr_if_false, mask & !cond);
+                SwVal::select(cond, a, b)
+            }
+            ExprKind::Bin {op, left_expr, right_expr, ..} => {
+                match op {
+                    BinOp::Assign => {
+                        let value = self.eval(inv, frame, right_expr, mask);
+                        self.assign(inv, frame, left_expr, value.clone(), mask);
+                        value
+                    }
+                    BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign => {
+                        let left = self.eval(inv, frame, left_expr, mask);
+                        let right = self.eval(inv, frame, right_expr, mask);
+                        let value = bin_op(*op, left, right);
+                        self.assign(inv, frame, left_expr, value.clone(), mask);
+                        value
+                    }
+                    _ => {
+                        let left = self.eval(inv, frame, left_expr, mask);
+                        let right = self.eval(inv, frame, right_expr, mask);
+                        bin_op(*op, left, right)
+                    }
+                }
+            }
+            ExprKind::Un {op, expr, ..} => {
+                match (op, self.eval(inv, frame, expr, mask)) {
+                    (UnOp::Not, SwVal::Num(num)) => SwVal::Num(num.map( | v | if v != 0.0 {0.0} else {1.0})),
+                    (UnOp::Neg, SwVal::Num(num)) => SwVal::Num(num.map( | v | -v)),
+                    (UnOp::Neg, SwVal::Mat(mat)) => SwVal::Mat(Box::new(mat.map( | v | -v))),
+                    (_, value) => value
+                }
+            }
+            ExprKind::Field {expr: base, field_ident, ..} => {
+                let struct_ptr = match base.ty.borrow().as_ref() {
+                    Some(Ty::DrawShader(_)) => return self.read_draw_shader_field(inv, *field_ident),
+                    Some(Ty::Struct(struct_ptr)) => Some(*struct_ptr),
+                    _ => None
+                };
+                let value = self.eval(inv, frame, base, mask);
+                if let Some(struct_ptr) = struct_ptr {
+                    if let (SwVal::Struct(mut fields), Some(index)) = (value, self.struct_field_index(struct_ptr, *field_ident)) {
+                        if index < fields.len() {
+                            return fields.swap_remove(index)
+                        }
+                    }
+                    return SwVal::Void
+                }
+                let num = value.num();
+                let indices = self.swizzle(*field_ident);
+                let mut out = SwNum::splat(num.kind, indices.len().max(1), 0.0);
+                for (c, index) in indices.iter().enumerate() {
+                    out.v[c] = num.v[*index];
+                }
+                SwVal::Num(out)
+            }
+            ExprKind::Index {expr: base, index_expr, ..} => {
+                let value = self.eval(inv, frame, base, mask);
+                let index = self.eval(inv, frame, index_expr, mask).num();
+                read_index(value, &index)
+            }
+            ExprKind::MethodCall {ident, arg_exprs, ..} => {
+                let fn_def = match arg_exprs.first().and_then( | arg | arg.ty.borrow().clone()) {
+                    Some(Ty::Struct(struct_ptr)) => self.registry.structs.get(&struct_ptr)
+                        .and_then( | struct_def | self.registry.struct_method_decl_from_ident(struct_def, *ident)),
+                    Some(Ty::DrawShader(shader_ptr)) => self.registry.draw_shader_defs.get(&shader_ptr)
+                        .and_then( | def | self.registry.draw_shader_method_decl_from_ident(def, *ident)),
+                    _ => None
+                };
+                if let Some(fn_def) = fn_def {
+                    self.eval_call(inv, frame, fn_def, arg_exprs, mask)
+                }
+                else {
+                    SwVal::Void
+                }
+            }
+            ExprKind::PlainCall {fn_ptr, param_index, arg_exprs, ..} => {
+                if let Some(param_index) = param_index.get() {
+                    return self.call_closure(inv, frame, param_index, arg_exprs, mask)
+                }
+                if let Some(fn_def) = fn_ptr.and_then( | fn_ptr | self.registry.all_fns.get(&fn_ptr)) {
+                    self.eval_call(inv, frame, fn_def, arg_exprs, mask)
+                }
+                else {
+                    SwVal::Void
+                }
+            }
+            ExprKind::BuiltinCall {ident, arg_exprs, ..} => {
+                let args: Vec<SwVal> = arg_exprs.iter().map( | arg | self.eval(inv, frame, arg, mask)).collect();
+                self.builtin(inv, *ident, args)
+            }
+            ExprKind::ClosureDef(_) => SwVal::Void,
+            ExprKind::ConsCall {ty_lit, arg_exprs, ..} => {
+                let args: Vec<SwVal> = arg_exprs.iter().map( | arg | self.eval(inv, frame, arg, mask)).collect();
+                cons(*ty_lit, args)
+            }
+            ExprKind::StructCons {struct_ptr, args, ..} => {
+                if let Some(struct_def) = self.registry.structs.get(struct_ptr) {
+                    let mut fields = Vec::new();
+                    for field in &struct_def.fields {
+                        if let Some((_, arg)) = args.iter().find( | (ident, _) | *ident == field.ident) {
+                            fields.push(self.eval(inv, frame, arg, mask));
+                        }
+                        else {
+                            fields.push(self.zero(field.ty_expr.ty.borrow().as_ref().unwrap_or(&Ty::Void)));
+                        }
+                    }
+                    SwVal::Struct(fields)
+                }
+                else {
+                    SwVal::Void
+                }
+            }
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    if let Some(local) = frame.find_local(ident.0, shadow.0) {
+                        frame.locals[local].2.clone()
+                    }
+                    else {
+                        SwVal::Void
+                    }
+                }
+                Some(VarKind::LiveValue(value_ptr)) => {
+                    self.live_values.get(&value_ptr.0).cloned().unwrap_or(SwVal::Void)
+                }
+                None => SwVal::Void
+            },
+            ExprKind::Lit {lit, ..} => SwVal::from_val(&lit.to_val()),
+        }
+    }
+
+    fn assign(&self, inv: &mut SwInvocation, frame: &mut SwFrame<'a>, expr: &'a Expr, value: SwVal, mask: SwMask) {
+        match &expr.kind {
+            ExprKind::Var {kind, ..} => match kind.get() {
+                Some(VarKind::Local {ident, shadow}) | Some(VarKind::MutLocal {ident, shadow}) => {
+                    if let Some(local) = frame.find_local(ident.0, shadow.0) {
+                        let old = std::mem::replace(&mut frame.locals[local].2, SwVal::Void);
+                        frame.locals[local].2 = SwVal::select(mask, value, old);
+                    }
+                }
+                _ => ()
+            }
+            ExprKind::Field {expr: base, field_ident, ..} => {
+                let struct_ptr = match base.ty.borrow().as_ref() {
+                    Some(Ty::DrawShader(_)) => {
+                        if let Some(index) = self.field_index.get(&field_ident.0) {
+                            let old = std::mem::replace(&mut inv.fields[*index], SwVal::Void);
+                            inv.fields[*index] = SwVal::select(mask, value, old);
+                        }
+                        return
+                    }
+                    Some(Ty::Struct(struct_ptr)) => Some(*struct_ptr),
+                    _ => None
+                };
+                let base_value = self.eval(inv, frame, base, mask);
+                let new_base = if let Some(struct_ptr) = struct_ptr {
+                    match (base_value, self.struct_field_index(struct_ptr, *field_ident)) {
+                        (SwVal::Struct(mut fields), Some(index)) if index < fields.len() => {
+                            fields[index] = value;
+                            SwVal::Struct(fields)
+                        }
+                        _ => return
+                    }
+                }
+                else {
+                    let mut num = base_value.num();
+                    let value = value.num();
+                    for (c, index) in self.swizzle(*field_ident).iter().enumerate() {
+                        for l in 0..SW_LANES {
+                            num.v[*index][l] = value.get(c, l);
+                        }
+                    }
+                    SwVal::Num(num)
+                };
+                self.assign(inv, frame, base, new_base, mask);
+            }
+            ExprKind::Index {expr: base, index_expr, ..} => {
+                let base_value = self.eval(inv, frame, base, mask);
+                let index = self.eval(inv, frame, index_expr, mask).num();
+                let new_base = write_index(base_value, &index, value, mask);
+                self.assign(inv, frame, base, new_base, mask);
+            }
+            _ => ()
+        }
+    }
+
+    fn builtin(&self, inv: &SwInvocation, ident: Ident, args: Vec<SwVal>) -> SwVal {
+        let num = | i: usize | args.get(i).map( | a | a.num()).unwrap_or(SwNum::float(0.0));
+        let mat = | i: usize | match args.get(i) {
+            Some(SwVal::Mat(m)) => Some(m.clone()),
+            _ => None
+        };
+        let map1 = | f: fn(f32) -> f32 | SwVal::Num(num(0).map(f));
+        let map2 = | f: fn(f32, f32) -> f32 | {
+            let a = num(0);
+            SwVal::Num(a.zip(&num(1), a.kind, f))
+        };
+        let compare = | f: fn(f32, f32) -> bool | {
+            SwVal::Num(num(0).zip(&num(1), SwKind::Bool, | a, b | if f(a, b) {1.0} else {0.0}))
+        };
+        let out = match ident.0 {
+            live_id!(abs) => map1( | v | v.abs()),
+            live_id!(acos) => map1( | v | v.acos()),
+            live_id!(asin) => map1( | v | v.asin()),
+            live_id!(atan) => if args.len() > 1 {
+                map2( | y, x | y.atan2(x))
+            } else {
+                map1( | v | v.atan())
+            },
+            live_id!(ceil) => map1( | v | v.ceil()),
+            live_id!(cos) => map1( | v | v.cos()),
+            live_id!(degrees) => map1( | v | v.to_degrees()),
+            live_id!(exp) => map1( | v | v.exp()),
+            live_id!(exp2) => map1( | v | v.exp2()),
+            live_id!(floor) => map1( | v | v.floor()),
+            live_id!(fract) => map1( | v | v - v.floor()),
+            live_id!(inversesqrt) => map1( | v | 1.0 / v.sqrt()),
+            live_id!(log) => map1( | v | v.ln()),
+            live_id!(log2) => map1( | v | v.log2()),
+            live_id!(radians) => map1( | v | v.to_radians()),
+            live_id!(sign) => map1( | v | if v > 0.0 {1.0} else if v < 0.0 {-1.0} else {0.0}),
+            live_id!(sin) => map1( | v | v.sin()),
+            live_id!(sqrt) => map1( | v | v.sqrt()),
+            live_id!(tan) => map1( | v | v.tan()),
+            live_id!(max) => map2( | a, b | a.max(b)),
+            live_id!(min) => map2( | a, b | a.min(b)),
+            live_id!(mod) => map2( | a, b | a - b * (a / b).floor()),
+            live_id!(pow) => map2( | a, b | a.powf(b)),
+            live_id!(step) => {
+                let (edge, x) = (num(0), num(1));
+                SwVal::Num(x.zip(&edge, SwKind::Float, | x, e | if x < e {0.0} else {1.0}))
+            }
+            live_id!(clamp) => SwVal::Num(num(0).zip3(&num(1), &num(2), | x, lo, hi | x.max(lo).min(hi))),
+            live_id!(mix) => SwVal::Num(num(0).zip3(&num(1), &num(2), | x, y, a | x * (1.0 - a) + y * a)),
+            live_id!(smoothstep) => {
+                let (e0, e1, x) = (num(0), num(1), num(2));
+                SwVal::Num(x.zip3(&e0, &e1, | x, e0, e1 | {
+                    let t = ((x - e0) / (e1 - e0)).max(0.0).min(1.0);
+                    t * t * (3.0 - 2.0 * t)
+                }))
+            }
+            live_id!(length) => SwVal::Num(num(0).length()),
+            live_id!(distance) => {
+                let a = num(0);
+                SwVal::Num(a.zip(&num(1), SwKind::Float, | a, b | a - b).length())
+            }
+            live_id!(dot) => SwVal::Num(num(0).dot(&num(1))),
+            live_id!(normalize) => {
+                let a = num(0);
+                let len = a.length();
+                SwVal::Num(a.zip(&len, SwKind::Float, | a, l | a / l))
+            }
+            live_id!(cross) => {
+                let (a, b) = (num(0), num(1));
+                let mut out = SwNum::splat(SwKind::Float, 3, 0.0);
+                for l in 0..SW_LANES {
+                    out.v[0][l] = a.v[1][l] * b.v[2][l] - a.v[2][l] * b.v[1][l];
+                    out.v[1][l] = a.v[2][l] * b.v[0][l] - a.v[0][l] * b.v[2][l];
+                    out.v[2][l] = a.v[0][l] * b.v[1][l] - a.v[1][l] * b.v[0][l];
+                }
+                SwVal::Num(out)
+            }
+            live_id!(reflect) => {
+                let (i, n) = (num(0), num(1));
+                let d = n.dot(&i).map( | v | 2.0 * v);
+                SwVal::Num(i.zip(&n.scale(&d), SwKind::Float, | a, b | a - b))
+            }
+            live_id!(refract) => {
+                let (i, n, eta) = (num(0), num(1), num(2));
+                let d = n.dot(&i);
+                let mut out = SwNum::splat(SwKind::Float, i.len, 0.0);
+                for l in 0..SW_LANES {
+                    let (d, eta) = (d.v[0][l], eta.v[0][l]);
+                    let k = 1.0 - eta * eta * (1.0 - d * d);
+                    if k >= 0.0 {
+                        for c in 0..i.len {
+                            out.v[c][l] = eta * i.v[c][l] - (eta * d + k.sqrt()) * n.v[c][l];
+                        }
+                    }
+                }
+                SwVal::Num(out)
+            }
+            live_id!(faceforward) => {
+                let (n, i, nref) = (num(0), num(1), num(2));
+                let d = nref.dot(&i);
+                SwVal::Num(n.zip(&d, SwKind::Float, | n, d | if d < 0.0 {n} else {-n}))
+            }
+            live_id!(equal) => compare( | a, b | a == b),
+            live_id!(notEqual) => compare( | a, b | a != b),
+            live_id!(lessThan) => compare( | a, b | a < b),
+            live_id!(lessThanEqual) => compare( | a, b | a <= b),
+            live_id!(greaterThan) => compare( | a, b | a > b),
+            live_id!(greaterThanEqual) => compare( | a, b | a >= b),
+            live_id!(not) => map1( | v | if v != 0.0 {0.0} else {1.0}),
+            live_id!(all) | live_id!(any) => {
+                let a = num(0);
+                let is_all = ident.0 == live_id!(all);
+                let mut out = SwNum::splat(SwKind::Bool, 1, 0.0);
+                for l in 0..SW_LANES {
+                    let mut acc = is_all;
+                    for c in 0..a.len {
+                        if is_all {acc &= a.v[c][l] != 0.0} else {acc |= a.v[c][l] != 0.0}
+                    }
+                    out.v[0][l] = if acc {1.0} else {0.0};
+                }
+                SwVal::Num(out)
+            }
+            live_id!(matrixCompMult) => match (mat(0), mat(1)) {
+                (Some(a), Some(b)) => SwVal::Mat(Box::new(a.zip(&b, | a, b | a * b))),
+                _ => SwVal::Void
+            },
+            live_id!(transpose) => match mat(0) {
+                Some(a) => SwVal::Mat(Box::new(a.transpose())),
+                None => SwVal::Void
+            },
+            live_id!(inverse) => match mat(0) {
+                Some(a) => SwVal::Mat(Box::new(a.inverse())),
+                None => SwVal::Void
+            },
+            live_id!(sample2d) | live_id!(sample2d_rt) => {
+                // every backend stores textures top-down, so both variants sample the same way
+                let slot = if let Some(SwVal::Texture(slot)) = args.first() {*slot} else {0};
+                let pos = num(1);
+                let mut out = SwNum::splat(SwKind::Float, 4, 0.0);
+                for l in 0..SW_LANES {
+                    let texel = self.sampler.sample(slot, pos.v[0][l], pos.v[1][l]);
+                    for c in 0..4 {
+                        out.v[c][l] = texel[c];
+                    }
+                }
+                SwVal::Num(out)
+            }
+            live_id!(dFdx) | live_id!(dFdy) => {
+                let a = num(0);
+                let mut out = SwNum::splat(SwKind::Float, a.len, 0.0);
+                if inv.is_pixel {
+                    for c in 0..a.len {
+                        let v = a.v[c];
+                        out.v[c] = if ident.0 == live_id!(dFdx) {
+                            [v[1] - v[0], v[1] - v[0], v[3] - v[2], v[3] - v[2]]
+                        }
+                        else { // window y points up, the second row of the quad is below
+                            [v[0] - v[2], v[1] - v[3], v[0] - v[2], v[1] - v[3]]
+                        };
+                    }
+                }
+                SwVal::Num(out)
+            }
+            _ => SwVal::Void
+        };
+        out
+    }
+}
+
+fn bin_op(op: BinOp, a: SwVal, b: SwVal) -> SwVal {
+    match (a, b) {
+        (SwVal::Num(a), SwVal::Num(b)) => SwVal::Num(num_bin_op(op, &a, &b)),
+        (SwVal::Mat(a), SwVal::Mat(b)) => SwVal::Mat(Box::new(match op {
+            BinOp::Mul | BinOp::MulAssign => a.mul_mat(&b),
+            BinOp::Add | BinOp::AddAssign => a.zip(&b, | a, b | a + b),
+            BinOp::Sub | BinOp::SubAssign => a.zip(&b, | a, b | a - b),
+            BinOp::Div | BinOp::DivAssign => a.zip(&b, | a, b | a / b),
+            _ => *a
+        })),
+        (SwVal::Mat(a), SwVal::Num(b)) => {
+            if b.len == 1 {
+                let s = b.v[0];
+                let mut out = (*a).clone();
+                for i in 0..a.n * a.n {
+                    for l in 0..SW_LANES {
+                        out.v[i][l] = num_scalar_op(op, a.v[i][l], s[l]);
+                    }
+                }
+                SwVal::Mat(Box::new(out))
+            }
+            else {
+                SwVal::Num(a.mul_vec(&b))
+            }
+        }
+        (SwVal::Num(a), SwVal::Mat(b)) => {
+            if a.len == 1 {
+                let s = a.v[0];
+                let mut out = (*b).clone();
+                for i in 0..b.n * b.n {
+                    for l in 0..SW_LANES {
+                        out.v[i][l] = num_scalar_op(op, s[l], b.v[i][l]);
+                    }
+                }
+                SwVal::Mat(Box::new(out))
+            }
+            else {
+                SwVal::Num(b.vec_mul(&a))
+            }
+        }
+        (a, _) => a
+    }
+}
+
+fn num_scalar_op(op: BinOp, a: f32, b: f32) -> f32 {
+    match op {
+        BinOp::Add | BinOp::AddAssign => a + b,
+        BinOp::Sub | BinOp::SubAssign => a - b,
+        BinOp::Mul | BinOp::MulAssign => a * b,
+        BinOp::Div | BinOp::DivAssign => a / b,
+        _ => b
+    }
+}
+
+fn num_bin_op(op: BinOp, a: &SwNum, b: &SwNum) -> SwNum {
+    let kind = if a.kind == SwKind::Float || b.kind == SwKind::Float {SwKind::Float} else {a.kind};
+    let bool_of = | v: bool | if v {1.0} else {0.0};
+    match op {
+        BinOp::Assign => *b,
+        BinOp::Add | BinOp::AddAssign => a.zip(b, kind, | a, b | a + b),
+        BinOp::Sub | BinOp::SubAssign => a.zip(b, kind, | a, b | a - b),
+        BinOp::Mul | BinOp::MulAssign => a.zip(b, kind, | a, b | a * b),
+        BinOp::Div | BinOp::DivAssign => if kind == SwKind::Int {
+            a.zip(b, kind, | a, b | if b == 0.0 {0.0} else {(a / b).trunc()})
+        }
+        else {
+            a.zip(b, kind, | a, b | a / b)
+        },
+        BinOp::Or => a.zip(b, SwKind::Bool, | a, b | bool_of(a != 0.0 || b != 0.0)),
+        BinOp::And => a.zip(b, SwKind::Bool, | a, b | bool_of(a != 0.0 && b != 0.0)),
+        BinOp::Lt => a.zip(b, SwKind::Bool, | a, b | bool_of(a < b)),
+        BinOp::Le => a.zip(b, SwKind::Bool, | a, b | bool_of(a <= b)),
+        BinOp::Gt => a.zip(b, SwKind::Bool, | a, b | bool_of(a > b)),
+        BinOp::Ge => a.zip(b, SwKind::Bool, | a, b | bool_of(a >= b)),
+        BinOp::Eq | BinOp::Ne => {
+            // vector equality compares all components into a single bool
+            let len = a.len.max(b.len);
+            let mut out = SwNum::splat(SwKind::Bool, 1, 0.0);
+            for l in 0..SW_LANES {
+                let eq = (0..len).all( | c | a.get(c, l) == b.get(c, l));
+                out.v[0][l] = bool_of(if let BinOp::Eq = op {eq} else {!eq});
+            }
+            out
+        }
+    }
+}
+
+fn lane_index(index: &SwNum, lane: usize, len: usize) -> usize {
+    (index.v[0][lane].max(0.0) as usize).min(len.max(1) - 1)
+}
+
+fn read_index(value: SwVal, index: &SwNum) -> SwVal {
+    match value {
+        SwVal::Num(num) => {
+            let mut out = SwNum::splat(num.kind, 1, 0.0);
+            for l in 0..SW_LANES {
+                out.v[0][l] = num.v[lane_index(index, l, num.len)][l];
+            }
+            SwVal::Num(out)
+        }
+        SwVal::Mat(mat) => {
+            let mut out = SwNum::splat(SwKind::Float, mat.n, 0.0);
+            for l in 0..SW_LANES {
+                let col = lane_index(index, l, mat.n);
+                for r in 0..mat.n {
+                    out.v[r][l] = mat.v[col * mat.n + r][l];
+                }
+            }
+            SwVal::Num(out)
+        }
+        SwVal::Array(items) if !items.is_empty() => {
+            let mut out = items[lane_index(index, 0, items.len())].clone();
+            for l in 1..SW_LANES {
+                let item = lane_index(index, l, items.len());
+                out = SwVal::select(1 << l, items[item].clone(), out);
+            }
+            out
+        }
+        _ => SwVal::Void
+    }
+}
+
+fn write_index(base: SwVal, index: &SwNum, value: SwVal, mask: SwMask) -> SwVal {
+    match base {
+        SwVal::Num(mut num) => {
+            let value = value.num();
+            for l in 0..SW_LANES {
+                if mask & (1 << l) != 0 {
+                    num.v[lane_index(index, l, num.len)][l] = value.v[0][l];
+                }
+            }
+            SwVal::Num(num)
+        }
+        SwVal::Mat(mut mat) => {
+            let value = value.num();
+            for l in 0..SW_LANES {
+                if mask & (1 << l) != 0 {
+                    let col = lane_index(index, l, mat.n);
+                    for r in 0..mat.n {
+                        mat.v[col * mat.n + r][l] = value.v[r][l];
+                    }
+                }
+            }
+            SwVal::Mat(mat)
+        }
+        SwVal::Array(mut items) if !items.is_empty() => {
+            for l in 0..SW_LANES {
+                if mask & (1 << l) != 0 {
+                    let item = lane_index(index, l, items.len());
+                    let old = std::mem::replace(&mut items[item], SwVal::Void);
+                    items[item] = SwVal::select(1 << l, value.clone(), old);
+                }
+            }
+            SwVal::Array(items)
+        }
+        base => base
+    }
+}
+
+fn cons(ty_lit: TyLit, args: Vec<SwVal>) -> SwVal {
+    let (kind, len, mat_n) = match ty_lit {
+        TyLit::Bool => (SwKind::Bool, 1, 0),
+        TyLit::Int => (SwKind::Int, 1, 0),
+        TyLit::Float => (SwKind::Float, 1, 0),
+        TyLit::Bvec2 => (SwKind::Bool, 2, 0),
+        TyLit::Bvec3 => (SwKind::Bool, 3, 0),
+        TyLit::Bvec4 => (SwKind::Bool, 4, 0),
+        TyLit::Ivec2 => (SwKind::Int, 2, 0),
+        TyLit::Ivec3 => (SwKind::Int, 3, 0),
+        TyLit::Ivec4 => (SwKind::Int, 4, 0),
+        TyLit::Vec2 => (SwKind::Float, 2, 0),
+        TyLit::Vec3 => (SwKind::Float, 3, 0),
+        TyLit::Vec4 => (SwKind::Float, 4, 0),
+        TyLit::Mat2 => (SwKind::Float, 0, 2),
+        TyLit::Mat3 => (SwKind::Float, 0, 3),
+        TyLit::Mat4 => (SwKind::Float, 0, 4),
+        TyLit::Texture2D => return SwVal::Void,
+    };
+    if mat_n > 0 && args.len() == 1 {
+        match &args[0] {
+            SwVal::Num(s) if s.len == 1 => return SwVal::Mat(Box::new(SwMat::identity(mat_n, &s.v[0]))),
+            SwVal::Mat(m) => {
+                let mut out = SwMat::identity(mat_n, &[1.0; SW_LANES]);
+                for c in 0..mat_n.min(m.n) {
+                    for r in 0..mat_n.min(m.n) {
+                        out.v[c * mat_n + r] = m.v[c * m.n + r];
+                    }
+                }
+                return SwVal::Mat(Box::new(out))
+            }
+            _ => ()
+        }
+    }
+    if mat_n == 0 && args.len() == 1 {
+        if let SwVal::Num(s) = &args[0] {
+            if s.len == 1 {
+                let mut out = SwNum::splat(kind, len, 0.0);
+                for c in 0..len {
+                    out.v[c] = s.v[0];
+                }
+                return SwVal::Num(out.convert_from(s.kind, kind))
+            }
+        }
+    }
+    // flatten all components in order
+    let mut flat: Vec<(SwKind, SwLanes)> = Vec::new();
+    for arg in &args {
+        match arg {
+            SwVal::Num(num) => for c in 0..num.len {
+                flat.push((num.kind, num.v[c]));
+            }
+            SwVal::Mat(mat) => for i in 0..mat.n * mat.n {
+                flat.push((SwKind::Float, mat.v[i]));
+            }
+            _ => ()
+        }
+    }
+    if mat_n > 0 {
+        let mut out = SwMat::zero(mat_n);
+        for (i, (_, lanes)) in flat.iter().take(mat_n * mat_n).enumerate() {
+            out.v[i] = *lanes;
+        }
+        return SwVal::Mat(Box::new(out))
+    }
+    let mut out = SwNum::splat(kind, len, 0.0);
+    for (c, (from_kind, lanes)) in flat.iter().take(len).enumerate() {
+        for l in 0..SW_LANES {
+            out.v[c][l] = if *from_kind == kind {lanes[l]} else {convert_scalar(lanes[l], kind)};
+        }
+    }
+    SwVal::Num(out)
+}
+
+impl SwNum {
+    fn convert_from(mut self, from: SwKind, to: SwKind) -> Self {
+        self.kind = from;
+        self.convert(to)
+    }
+}
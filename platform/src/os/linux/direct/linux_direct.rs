@@ -9,11 +9,13 @@ use {
     self::super::super::{
         gl_sys,
         select_timer::SelectTimers,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        linux_network::CxLinuxNetwork,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi},
         makepad_live_id::*,
+        makepad_http::websocket::MessageFormat,
         makepad_math::*,
         thread::Signal,
         event::{
@@ -26,6 +28,7 @@ use {
         cx::{Cx, OsType,},
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
+        os::headless::HeadlessOptions,
         pass::{PassClearColor, PassClearDepth, PassId},
     }
 };
@@ -70,10 +73,14 @@ impl DirectApp {
 impl Cx {
     pub fn event_loop(cx: Rc<RefCell<Cx >>) {
         
-        let mut cx = cx.borrow_mut();
+        cx.borrow_mut().os_type = OsType::LinuxDirect;
+        cx.borrow_mut().gpu_info.performance = GpuPerformance::Tier1;
         
-        cx.os_type = OsType::LinuxDirect;
-        cx.gpu_info.performance = GpuPerformance::Tier1;
+        if let Some(options) = HeadlessOptions::from_args() {
+            return Cx::headless_event_loop(cx, options);
+        }
+        
+        let mut cx = cx.borrow_mut();
         
         cx.call_event_handler(&Event::Construct);
         cx.redraw_all();
@@ -180,6 +187,11 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    if self.handle_live_edit() {
+                        self.call_event_handler(&Event::LiveEdit);
+                        self.redraw_all();
+                    }
+                    self.handle_networking_events();
                 }
                 else {
                     self.call_event_handler(&Event::Timer(e))
@@ -297,6 +309,18 @@ impl Cx {
                 CxOsOp::StopTimer(timer_id) => {
                     direct_app.timers.stop_timer(timer_id);
                 },
+                CxOsOp::HttpRequest {request_id, request} => {
+                    self.os.network.http_request(request_id, request);
+                },
+                CxOsOp::WebSocketOpen {request_id, request} => {
+                    self.os.network.web_socket_open(request_id, request);
+                }
+                CxOsOp::WebSocketSendBinary {request_id, data} => {
+                    self.os.network.web_socket_send(request_id, MessageFormat::Binary, data);
+                }
+                CxOsOp::WebSocketSendString {request_id, data} => {
+                    self.os.network.web_socket_send(request_id, MessageFormat::Text, data.into_bytes());
+                },
                 _ => ()
             }
         }
@@ -319,5 +343,6 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub (crate) media: CxLinuxMedia,
+    pub (crate) network: CxLinuxNetwork,
 }
 
@@ -0,0 +1,679 @@
+use {
+    std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read, Write},
+        net::{TcpStream, Shutdown},
+        sync::mpsc::{channel, Sender},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    crate::{
+        makepad_live_id::*,
+        makepad_http::{
+            digest::base64_encode,
+            websocket::{WebSocket, WebSocketMessage, MessageFormat},
+        },
+        cx::Cx,
+        event::{
+            Event,
+            HttpMethod,
+            HttpRequest,
+            HttpResponse,
+            NetworkResponse,
+            NetworkResponseChannel,
+            NetworkResponseEvent,
+        },
+    }
+};
+
+const MAX_REDIRECTS: usize = 10;
+const PROGRESS_INTERVAL: usize = 65536;
+
+#[derive(Default)]
+pub struct CxLinuxNetwork {
+    pub (crate) network_response: NetworkResponseChannel,
+    web_sockets: HashMap<LiveId, LinuxWebSocket>,
+}
+
+impl Cx {
+    pub (crate) fn handle_networking_events(&mut self) {
+        let mut out = Vec::new();
+        while let Ok(event) = self.os.network.network_response.receiver.try_recv() {
+            if let NetworkResponse::WebSocketClose = &event.response {
+                self.os.network.web_sockets.remove(&event.request_id);
+            }
+            out.push(event);
+        }
+        if !out.is_empty() {
+            self.call_event_handler(&Event::NetworkResponses(out))
+        }
+    }
+}
+
+impl CxLinuxNetwork {
+    pub fn http_request(&mut self, request_id: LiveId, request: HttpRequest) {
+        make_http_request(request_id, request, self.network_response.sender.clone());
+    }
+
+    pub fn web_socket_open(&mut self, request_id: LiveId, request: HttpRequest) {
+        let web_socket = LinuxWebSocket::open(request_id, request, self.network_response.sender.clone());
+        self.web_sockets.insert(request_id, web_socket);
+    }
+
+    pub fn web_socket_send(&mut self, request_id: LiveId, format: MessageFormat, data: Vec<u8>) {
+        if let Some(web_socket) = self.web_sockets.get(&request_id) {
+            web_socket.send(format, data);
+        }
+        else {
+            let _ = self.network_response.sender.send(NetworkResponseEvent {
+                request_id,
+                response: NetworkResponse::WebSocketError("Websocket not open".to_string())
+            });
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedUrl {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl ParsedUrl {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let (scheme, rest) = if let Some(pos) = url.find("://") {
+            (url[0..pos].to_ascii_lowercase(), &url[pos + 3..])
+        }
+        else {
+            return Err(format!("Url has no scheme: {}", url))
+        };
+        let default_port = match scheme.as_str() {
+            "http" | "ws" => 80,
+            "https" | "wss" => 443,
+            _ => return Err(format!("Unsupported url scheme: {}", scheme))
+        };
+        let (authority, path) = if let Some(pos) = rest.find(['/', '?', '#']) {
+            (&rest[0..pos], &rest[pos..])
+        }
+        else {
+            (rest, "/")
+        };
+        // strip the fragment, it never goes over the wire
+        let path = path.split('#').next().unwrap();
+        let path = if path.starts_with('?') {format!("/{}", path)} else {path.to_string()};
+        // strip userinfo
+        let authority = authority.rsplit('@').next().unwrap();
+        let (host, port) = if authority.starts_with('[') {
+            if let Some(end) = authority.find(']') {
+                let port = authority[end + 1..].strip_prefix(':');
+                (&authority[0..end + 1], port)
+            }
+            else {
+                return Err(format!("Invalid ipv6 host in url: {}", url))
+            }
+        }
+        else if let Some(pos) = authority.rfind(':') {
+            (&authority[0..pos], Some(&authority[pos + 1..]))
+        }
+        else {
+            (authority, None)
+        };
+        let port = if let Some(port) = port {
+            port.parse().map_err( | _ | format!("Invalid port in url: {}", url)) ?
+        }
+        else {
+            default_port
+        };
+        if host.is_empty() {
+            return Err(format!("Url has no host: {}", url))
+        }
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            port,
+            path: if path.is_empty() {"/".to_string()} else {path},
+        })
+    }
+
+    pub fn is_secure(&self) -> bool {
+        self.scheme == "https" || self.scheme == "wss"
+    }
+
+    pub fn host_header(&self) -> String {
+        let default_port = if self.is_secure() {443} else {80};
+        if self.port == default_port {
+            self.host.clone()
+        }
+        else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    pub fn resolve(&self, location: &str) -> Result<Self, String> {
+        if location.contains("://") {
+            Self::parse(location)
+        }
+        else if location.starts_with("//") {
+            Self::parse(&format!("{}:{}", self.scheme, location))
+        }
+        else if location.starts_with('/') {
+            Ok(Self {path: location.to_string(), ..self.clone()})
+        }
+        else {
+            let base = self.path.split('?').next().unwrap();
+            let dir = &base[0..base.rfind('/').map( | v | v + 1).unwrap_or(0)];
+            Ok(Self {path: format!("/{}{}", dir.trim_start_matches('/'), location), ..self.clone()})
+        }
+    }
+
+    fn connect(&self) -> Result<TcpStream, String> {
+        if self.is_secure() {
+            return Err(format!("TLS ({}) is not supported by the Linux network backend", self.scheme))
+        }
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        TcpStream::connect((host, self.port)).map_err( | e | format!("Cannot connect to {}:{} - {}", self.host, self.port, e))
+    }
+}
+
+fn write_request_head(out: &mut Vec<u8>, method: &str, url: &ParsedUrl, headers: &[(String, String)]) {
+    out.extend_from_slice(format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, url.path, url.host_header()).as_bytes());
+    for (key, value) in headers {
+        out.extend_from_slice(format!("{}: {}\r\n", key, value).as_bytes());
+    }
+    out.extend_from_slice(b"\r\n");
+}
+
+struct ResponseHead {
+    status_code: u16,
+    headers: Vec<(String, String)>,
+}
+
+impl ResponseHead {
+    fn read(reader: &mut impl BufRead) -> Result<Self, String> {
+        let mut line = String::new();
+        read_line(reader, &mut line) ?;
+        // HTTP/1.1 200 OK
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some(v) if v.starts_with("HTTP/") => (),
+            _ => return Err(format!("Invalid http status line: {}", line.trim_end()))
+        }
+        let status_code = parts.next().and_then( | v | v.parse().ok()).ok_or_else( || format!("Invalid http status line: {}", line.trim_end())) ?;
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            read_line(reader, &mut line) ?;
+            let trimmed = line.trim_end_matches(['\r', '\n']);
+            if trimmed.is_empty() {
+                break;
+            }
+            if let Some(pos) = trimmed.find(':') {
+                headers.push((trimmed[0..pos].trim().to_string(), trimmed[pos + 1..].trim().to_string()));
+            }
+            if headers.len() > 4096 { // some overflow protection
+                return Err("Too many http headers".to_string())
+            }
+        }
+        Ok(Self {status_code, headers})
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find( | (key, _) | key.eq_ignore_ascii_case(name)).map( | (_, value) | value.as_str())
+    }
+}
+
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> Result<(), String> {
+    match reader.read_line(line) {
+        Ok(0) => Err("Connection closed unexpectedly".to_string()),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("Error reading from connection: {}", e))
+    }
+}
+
+fn read_body(
+    reader: &mut impl BufRead,
+    head: &ResponseHead,
+    mut progress: impl FnMut(usize, usize)
+) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    let is_chunked = head.header("Transfer-Encoding").is_some_and( | v | v.to_ascii_lowercase().contains("chunked"));
+    if is_chunked {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            read_line(reader, &mut line) ?;
+            // chunk extensions come after a ';'
+            let size = line.trim().split(';').next().unwrap();
+            let size = usize::from_str_radix(size.trim(), 16).map_err( | _ | format!("Invalid chunk size: {}", line.trim())) ?;
+            if size == 0 {
+                // skip trailers up to the final empty line
+                loop {
+                    line.clear();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                        break;
+                    }
+                }
+                break;
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            reader.read_exact(&mut body[start..]).map_err( | e | format!("Error reading chunk: {}", e)) ?;
+            let mut crlf = [0u8; 2];
+            reader.read_exact(&mut crlf).map_err( | e | format!("Error reading chunk: {}", e)) ?;
+            progress(body.len(), 0);
+        }
+    }
+    else if let Some(len) = head.header("Content-Length") {
+        let total: usize = len.parse().map_err( | _ | format!("Invalid content length: {}", len)) ?;
+        body.resize(total, 0);
+        let mut loaded = 0;
+        let mut last_progress = 0;
+        while loaded < total {
+            match reader.read(&mut body[loaded..]) {
+                Ok(0) => return Err("Connection closed before end of body".to_string()),
+                Ok(n) => loaded += n,
+                Err(e) => return Err(format!("Error reading body: {}", e))
+            }
+            if loaded - last_progress >= PROGRESS_INTERVAL || loaded == total {
+                last_progress = loaded;
+                progress(loaded, total);
+            }
+        }
+    }
+    else {
+        // no length given, the body runs until the connection closes
+        let mut buf = [0u8; 65536];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    body.extend_from_slice(&buf[0..n]);
+                    progress(body.len(), 0);
+                }
+                Err(e) => return Err(format!("Error reading body: {}", e))
+            }
+        }
+    }
+    Ok(body)
+}
+
+fn has_no_body(method: &HttpMethod, status_code: u16) -> bool {
+    *method == HttpMethod::HEAD || status_code == 204 || status_code == 304 || (100..200).contains(&status_code)
+}
+
+pub fn make_http_request(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseEvent>) {
+    std::thread::spawn(move || {
+        let response = match run_http_request(request_id, &request, &networking_sender) {
+            Ok(response) => NetworkResponse::HttpResponse(response),
+            Err(err) => NetworkResponse::HttpRequestError(err)
+        };
+        let _ = networking_sender.send(NetworkResponseEvent {
+            request_id,
+            response
+        });
+    });
+}
+
+fn run_http_request(request_id: LiveId, request: &HttpRequest, networking_sender: &Sender<NetworkResponseEvent>) -> Result<HttpResponse, String> {
+    let mut url = ParsedUrl::parse(&request.url) ?;
+    let mut method = request.method.to_string().to_string();
+    let mut body = request.body.clone();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut tcp_stream = url.connect() ?;
+
+        let mut headers = Vec::new();
+        for (key, values) in request.headers.iter() {
+            // we compute these ourselves
+            if key.eq_ignore_ascii_case("host") || key.eq_ignore_ascii_case("content-length") || key.eq_ignore_ascii_case("connection") {
+                continue;
+            }
+            headers.push((key.clone(), values.join(",")));
+        }
+        if let Some(body) = &body {
+            headers.push(("Content-Length".to_string(), body.len().to_string()));
+        }
+        headers.push(("Connection".to_string(), "close".to_string()));
+
+        let mut out = Vec::new();
+        write_request_head(&mut out, &method, &url, &headers);
+        if let Some(body) = &body {
+            out.extend_from_slice(body);
+        }
+        tcp_stream.write_all(&out).map_err( | e | format!("Error writing request: {}", e)) ?;
+
+        let mut reader = BufReader::new(tcp_stream);
+        let head = ResponseHead::read(&mut reader) ?;
+
+        if let (301 | 302 | 303 | 307 | 308, Some(location)) = (head.status_code, head.header("Location")) {
+            url = url.resolve(location) ?;
+            // 303 always turns into a GET, 301/302 do so for anything but GET/HEAD by convention
+            if head.status_code == 303 || ((head.status_code == 301 || head.status_code == 302) && method != "GET" && method != "HEAD") {
+                method = "GET".to_string();
+                body = None;
+            }
+            continue;
+        }
+
+        let body = if has_no_body(&request.method, head.status_code) {
+            Vec::new()
+        }
+        else {
+            read_body(&mut reader, &head, | loaded, total | {
+                let _ = networking_sender.send(NetworkResponseEvent {
+                    request_id,
+                    response: NetworkResponse::HttpProgress {loaded: loaded as u32, total: total as u32}
+                });
+            }) ?
+        };
+        let _ = reader.get_ref().shutdown(Shutdown::Both);
+
+        let mut response = HttpResponse::new(
+            request.metadata_id,
+            head.status_code,
+            "".to_string(),
+            Some(body),
+        );
+        for (key, value) in head.headers {
+            response.set_header(key, value);
+        }
+        return Ok(response)
+    }
+    Err(format!("Too many redirects for {}", request.url))
+}
+
+enum WebSocketThreadMsg {
+    Send(MessageFormat, Vec<u8>),
+    Close,
+}
+
+pub struct LinuxWebSocket {
+    sender: Sender<WebSocketThreadMsg>,
+}
+
+impl Drop for LinuxWebSocket {
+    fn drop(&mut self) {
+        let _ = self.sender.send(WebSocketThreadMsg::Close);
+    }
+}
+
+// websocket keys and masks only need to be unpredictable, not cryptographically strong
+struct MaskRng(u64);
+
+impl MaskRng {
+    fn new(seed: LiveId) -> Self {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map( | d | d.as_nanos() as u64).unwrap_or(0);
+        Self((seed.0 ^ time) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn mask(&mut self) -> [u8; 4] {
+        (self.next() as u32).to_be_bytes()
+    }
+}
+
+impl LinuxWebSocket {
+    pub fn open(request_id: LiveId, request: HttpRequest, networking_sender: Sender<NetworkResponseEvent>) -> Self {
+        let (sender, receiver) = channel();
+        let pong_sender = sender.clone();
+        std::thread::spawn(move || {
+            let send_error = | err: String | {
+                let _ = networking_sender.send(NetworkResponseEvent {
+                    request_id,
+                    response: NetworkResponse::WebSocketError(err)
+                });
+            };
+            let mut rng = MaskRng::new(request_id);
+            let (mut write_stream, reader) = match Self::handshake(&request, &mut rng) {
+                Ok(v) => v,
+                Err(err) => return send_error(err)
+            };
+            let _ = networking_sender.send(NetworkResponseEvent {
+                request_id,
+                response: NetworkResponse::WebSocketOpen
+            });
+
+            let networking_sender_read = networking_sender.clone();
+            let _read_thread = std::thread::spawn(move || {
+                Self::read_loop(request_id, reader, pong_sender, networking_sender_read);
+            });
+
+            while let Ok(msg) = receiver.recv() {
+                match msg {
+                    WebSocketThreadMsg::Send(format, data) => {
+                        let frame = WebSocket::create_client_message(format, &data, rng.mask());
+                        if let Err(e) = write_stream.write_all(&frame) {
+                            send_error(format!("Error writing to websocket: {}", e));
+                            break;
+                        }
+                        if format == MessageFormat::Close {
+                            break;
+                        }
+                    }
+                    WebSocketThreadMsg::Close => {
+                        let frame = WebSocket::create_client_message(MessageFormat::Close, &[], rng.mask());
+                        let _ = write_stream.write_all(&frame);
+                        break;
+                    }
+                }
+            }
+            let _ = write_stream.shutdown(Shutdown::Both);
+        });
+        Self {sender}
+    }
+
+    pub fn send(&self, format: MessageFormat, data: Vec<u8>) {
+        let _ = self.sender.send(WebSocketThreadMsg::Send(format, data));
+    }
+
+    fn handshake(request: &HttpRequest, rng: &mut MaskRng) -> Result<(TcpStream, BufReader<TcpStream>), String> {
+        let url = ParsedUrl::parse(&request.url) ?;
+        let mut tcp_stream = url.connect() ?;
+
+        let mut key_bytes = [0u8; 16];
+        key_bytes[0..8].copy_from_slice(&rng.next().to_be_bytes());
+        key_bytes[8..16].copy_from_slice(&rng.next().to_be_bytes());
+        let key = base64_encode(&key_bytes);
+
+        let mut headers = vec![
+            ("Upgrade".to_string(), "websocket".to_string()),
+            ("Connection".to_string(), "Upgrade".to_string()),
+            ("Sec-WebSocket-Key".to_string(), key.clone()),
+            ("Sec-WebSocket-Version".to_string(), "13".to_string()),
+        ];
+        for (name, values) in request.headers.iter() {
+            if headers.iter().any( | (key, _) | key.eq_ignore_ascii_case(name)) || name.eq_ignore_ascii_case("host") {
+                continue;
+            }
+            headers.push((name.clone(), values.join(",")));
+        }
+        let mut out = Vec::new();
+        write_request_head(&mut out, "GET", &url, &headers);
+        tcp_stream.write_all(&out).map_err( | e | format!("Error writing websocket handshake: {}", e)) ?;
+
+        let mut reader = BufReader::new(tcp_stream.try_clone().map_err( | e | e.to_string()) ?);
+        let head = ResponseHead::read(&mut reader) ?;
+        if head.status_code != 101 {
+            return Err(format!("Websocket upgrade failed with status {}", head.status_code))
+        }
+        if head.header("Sec-WebSocket-Accept") != Some(&WebSocket::create_accept_key(&key)) {
+            return Err("Websocket upgrade returned an invalid accept key".to_string())
+        }
+        Ok((tcp_stream, reader))
+    }
+
+    fn read_loop(
+        request_id: LiveId,
+        mut reader: BufReader<TcpStream>,
+        pong_sender: Sender<WebSocketThreadMsg>,
+        networking_sender: Sender<NetworkResponseEvent>
+    ) {
+        let send = | response: NetworkResponse | {
+            let _ = networking_sender.send(NetworkResponseEvent {
+                request_id,
+                response
+            });
+        };
+        let mut web_socket = WebSocket::new();
+        let mut data = [0u8; 65535];
+        let mut is_closed = false;
+        // the handshake reader may already hold the first frames
+        let buffered = reader.buffer().to_vec();
+        reader.consume(buffered.len());
+        let mut input = buffered;
+        loop {
+            web_socket.parse(&input, | result | {
+                match result {
+                    Ok(WebSocketMessage::Ping(data)) => {
+                        let _ = pong_sender.send(WebSocketThreadMsg::Send(MessageFormat::Pong, data.to_vec()));
+                    },
+                    Ok(WebSocketMessage::Pong(_)) => {
+                    },
+                    Ok(WebSocketMessage::Text(text)) => {
+                        send(NetworkResponse::WebSocketString(text.to_string()));
+                    }
+                    Ok(WebSocketMessage::Binary(data)) => {
+                        send(NetworkResponse::WebSocketBinary(data.to_vec()));
+                    },
+                    Ok(WebSocketMessage::Close) => {
+                        is_closed = true;
+                    }
+                    Err(e) => {
+                        send(NetworkResponse::WebSocketError(format!("Websocket error {:?}", e)));
+                        is_closed = true;
+                    }
+                }
+            });
+            if is_closed {
+                break;
+            }
+            match reader.get_mut().read(&mut data) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    input.clear();
+                    input.extend_from_slice(&data[0..n]);
+                }
+            }
+        }
+        let _ = pong_sender.send(WebSocketThreadMsg::Close);
+        send(NetworkResponse::WebSocketClose);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        std::{
+            net::{SocketAddr, TcpListener},
+            sync::mpsc::{self, channel},
+            time::Duration,
+        },
+        crate::makepad_http::server::*,
+        super::*,
+    };
+
+    fn start_test_server() -> (SocketAddr, mpsc::Receiver<HttpServerRequest>) {
+        // grab a free port from the os, then hand it to the server
+        let listen_address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let (request, receiver) = channel();
+        start_http_server(HttpServer {
+            listen_address,
+            request,
+            post_max_size: 1024 * 1024
+        }).unwrap();
+        (listen_address, receiver)
+    }
+
+    fn recv_final(receiver: &mpsc::Receiver<NetworkResponseEvent>) -> NetworkResponse {
+        loop {
+            let event = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            if let NetworkResponse::HttpProgress {..} = event.response {
+                continue;
+            }
+            return event.response
+        }
+    }
+
+    #[test]
+    fn parse_url() {
+        let url = ParsedUrl::parse("http://example.com:8080/a/b?c=d#frag").unwrap();
+        assert_eq!(url.host, "example.com");
+        assert_eq!(url.port, 8080);
+        assert_eq!(url.path, "/a/b?c=d");
+        assert_eq!(url.host_header(), "example.com:8080");
+        assert_eq!(ParsedUrl::parse("ws://example.com").unwrap().path, "/");
+        assert_eq!(url.resolve("/x").unwrap().path, "/x");
+        assert_eq!(url.resolve("x").unwrap().path, "/a/x");
+        assert_eq!(url.resolve("http://other/y").unwrap().host, "other");
+    }
+
+    #[test]
+    fn http_get_and_redirect() {
+        let (addr, server) = start_test_server();
+        std::thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                if let HttpServerRequest::Get {headers, response_sender} = request {
+                    let response = if headers.path == "/redirect" {
+                        HttpServerResponse {
+                            header: "HTTP/1.1 302 Found\r\nLocation: /chunked\r\nContent-Length: 0\r\n\r\n".to_string(),
+                            body: Vec::new()
+                        }
+                    }
+                    else {
+                        HttpServerResponse {
+                            header: "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nX-Test: a:b\r\n\r\n".to_string(),
+                            body: b"5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n".to_vec()
+                        }
+                    };
+                    let _ = response_sender.send(response);
+                }
+            }
+        });
+        let (sender, receiver) = channel();
+        let request = HttpRequest::new(format!("http://{}/redirect", addr), HttpMethod::GET);
+        make_http_request(LiveId(1), request, sender);
+        match recv_final(&receiver) {
+            NetworkResponse::HttpResponse(response) => {
+                assert_eq!(response.status_code, 200);
+                assert_eq!(response.get_string_body().unwrap(), "hello world");
+                assert_eq!(response.headers.get("X-Test").unwrap(), &vec!["a:b".to_string()]);
+            }
+            other => panic!("unexpected response {:?}", other)
+        }
+    }
+
+    #[test]
+    fn web_socket_echo() {
+        let (addr, server) = start_test_server();
+        std::thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                if let HttpServerRequest::BinaryMessage {response_sender, data, ..} = request {
+                    let _ = response_sender.send(data);
+                }
+            }
+        });
+        let (sender, receiver) = channel();
+        let request = HttpRequest::new(format!("ws://{}/", addr), HttpMethod::GET);
+        let web_socket = LinuxWebSocket::open(LiveId(2), request, sender);
+        web_socket.send(MessageFormat::Binary, vec![1, 2, 3]);
+        let mut got_open = false;
+        loop {
+            match receiver.recv_timeout(Duration::from_secs(5)).unwrap().response {
+                NetworkResponse::WebSocketOpen => got_open = true,
+                NetworkResponse::WebSocketBinary(data) => {
+                    assert_eq!(data, vec![1, 2, 3]);
+                    break;
+                }
+                other => panic!("unexpected response {:?}", other)
+            }
+        }
+        assert!(got_open);
+    }
+}
@@ -22,6 +22,8 @@ pub mod linux_media;
 #[cfg(not(target_os="android"))]
 pub mod linux_decoding;
 #[cfg(not(target_os="android"))]
+pub mod linux_network;
+#[cfg(not(target_os="android"))]
 pub mod alsa_audio;
 #[cfg(not(target_os="android"))]
 pub mod alsa_midi;
@@ -9,17 +9,20 @@ use {
         egl_sys,
         x11::xlib_event::*,
         x11::xlib_app::*,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        linux_network::CxLinuxNetwork,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
         makepad_math::dvec2,
         makepad_live_id::*,
+        makepad_http::websocket::MessageFormat,
         thread::Signal,
         event::Event,
         pass::CxPassParent,
         cx::{Cx, OsType,LinuxWindowParams}, 
         os::cx_stdin::{PollTimers},
+        os::headless::HeadlessOptions,
         gpu_info::GpuPerformance,
         os::cx_native::EventFlow,
     }
@@ -32,6 +35,10 @@ impl Cx {
             custom_window_chrome: false
         });
         cx.borrow_mut().gpu_info.performance = GpuPerformance::Tier1;
+        
+        if let Some(options) = HeadlessOptions::from_args() {
+            return Cx::headless_event_loop(cx, options);
+        }
 
         let opengl_windows = Rc::new(RefCell::new(Vec::new()));
         let is_stdin_loop = std::env::args().find(|v| v=="--stdin-loop").is_some();
@@ -193,6 +200,11 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    if self.handle_live_edit(){
+                        self.call_event_handler(&Event::LiveEdit);
+                        self.redraw_all();
+                    }
+                    self.handle_networking_events();
                 }
                 else{
                     self.call_event_handler(&Event::Timer(e))
@@ -208,9 +220,6 @@ impl Cx {
         
     }
 
-    pub(crate) fn handle_networking_events(&mut self) {
-    }
-    
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
         self.os.opengl_cx.as_ref().unwrap().make_current();
         let mut passes_todo = Vec::new();
@@ -284,6 +293,11 @@ impl Cx {
                 },
                 CxOsOp::ShowClipboardActions(_) =>{
                 }
+                CxOsOp::CopyToClipboard(content) => {
+                    if let Some(window) = opengl_windows.first() {
+                        xlib_app.set_clipboard(&content, &window.xlib_window);
+                    }
+                }
                 CxOsOp::FullscreenWindow(_window_id) => {
                     todo!()
                 },
@@ -318,17 +332,17 @@ impl Cx {
                 },
                 CxOsOp::UpdateMacosMenu(_menu) => {
                 },
-                CxOsOp::HttpRequest{request_id:_, request:_} => {
-                    todo!()
+                CxOsOp::HttpRequest{request_id, request} => {
+                    self.os.network.http_request(request_id, request);
                 },
-                CxOsOp::WebSocketOpen{request_id:_, request:_}=>{
-                    todo!()
+                CxOsOp::WebSocketOpen{request_id, request}=>{
+                    self.os.network.web_socket_open(request_id, request);
                 }
-                CxOsOp::WebSocketSendBinary{request_id:_, data:_}=>{
-                    todo!()
+                CxOsOp::WebSocketSendBinary{request_id, data}=>{
+                    self.os.network.web_socket_send(request_id, MessageFormat::Binary, data);
                 }
-                CxOsOp::WebSocketSendString{request_id:_, data:_}=>{
-                    todo!()
+                CxOsOp::WebSocketSendString{request_id, data}=>{
+                    self.os.network.web_socket_send(request_id, MessageFormat::Text, data.into_bytes());
                 },
                 CxOsOp::InitializeVideoDecoding(_, _,) => todo!(),
                 CxOsOp::DecodeNextVideoChunk(_, _) => todo!(),
@@ -355,6 +369,7 @@ impl CxOsApi for Cx {
 #[derive(Default)]
 pub struct CxOs {
     pub(crate) media: CxLinuxMedia,
+    pub(crate) network: CxLinuxNetwork,
     pub (crate) stdin_timers: PollTimers,
 
     // HACK(eddyb) generalize this to EGL, properly.
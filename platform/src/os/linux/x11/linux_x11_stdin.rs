@@ -10,6 +10,7 @@ use {
         makepad_error_log::*,
         makepad_micro_serde::*,
         makepad_live_compiler::LiveFileChange,
+        makepad_http::websocket::MessageFormat,
         event::Event,
         window::CxWindowPool,
         event::WindowGeom,
@@ -127,6 +128,19 @@ impl Cx {
                 HostToStdin::KeyUp(e) => {
                     self.call_event_handler(&Event::KeyUp(e));
                 }
+                HostToStdin::TextInput(e) => {
+                    self.call_event_handler(&Event::TextInput(e.into()));
+                }
+                HostToStdin::TextCopy | HostToStdin::TextCut => {
+                    let cut = matches!(msg, HostToStdin::TextCut);
+                    if let Some(text) = self.stdin_text_clipboard(cut) {
+                        let _ = io::stdout().write_all(StdinToHost::SetClipboard(text).to_json().as_bytes());
+                    }
+                }
+                HostToStdin::RequestWidgetTree => {
+                    let nodes = self.stdin_widget_tree();
+                    let _ = io::stdout().write_all(StdinToHost::WidgetTree(nodes).to_json().as_bytes());
+                }
                 HostToStdin::MouseDown(e) => {
                     self.fingers.process_tap_count(
                         dvec2(e.x,e.y),
@@ -255,12 +269,34 @@ impl Cx {
                 CxOsOp::SetCursor(cursor) => {
                     let _ = io::stdout().write_all(StdinToHost::SetCursor(cursor).to_json().as_bytes());
                 },
+                CxOsOp::ShowTextIME(area, pos) => {
+                    let pos = area.get_clipped_rect(self).pos + pos;
+                    let _ = io::stdout().write_all(StdinToHost::ShowTextIME{x: pos.x, y: pos.y}.to_json().as_bytes());
+                },
+                CxOsOp::HideTextIME => {
+                    let _ = io::stdout().write_all(StdinToHost::HideTextIME.to_json().as_bytes());
+                },
+                CxOsOp::CopyToClipboard(content) => {
+                    let _ = io::stdout().write_all(StdinToHost::SetClipboard(content).to_json().as_bytes());
+                },
                 CxOsOp::StartTimer {timer_id, interval, repeats} => {
                     self.os.stdin_timers.timers.insert(timer_id, PollTimer::new(interval, repeats));
                 },
                 CxOsOp::StopTimer(timer_id) => {
                     self.os.stdin_timers.timers.remove(&timer_id);
                 },
+                CxOsOp::HttpRequest{request_id, request} => {
+                    self.os.network.http_request(request_id, request);
+                },
+                CxOsOp::WebSocketOpen{request_id, request} => {
+                    self.os.network.web_socket_open(request_id, request);
+                }
+                CxOsOp::WebSocketSendBinary{request_id, data} => {
+                    self.os.network.web_socket_send(request_id, MessageFormat::Binary, data);
+                }
+                CxOsOp::WebSocketSendString{request_id, data} => {
+                    self.os.network.web_socket_send(request_id, MessageFormat::Text, data.into_bytes());
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
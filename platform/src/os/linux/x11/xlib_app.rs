@@ -834,6 +834,20 @@ impl XlibApp {
         }
     }
 
+    // same as copy_to_clipboard but without a key event to take the time from
+    pub fn set_clipboard(&mut self, text: &str, window: &XlibWindow) {
+        unsafe {
+            self.clipboard = text.to_string();
+            x11_sys::XSetSelectionOwner(
+                self.display,
+                self.atoms.clipboard,
+                window.window.unwrap(),
+                x11_sys::CurrentTime as x11_sys::Time
+            );
+            x11_sys::XFlush(self.display);
+        }
+    }
+    
     unsafe fn copy_to_clipboard(&mut self, text: &String, window: &XlibWindow, event: &XEvent) {
         // store the text on the clipboard
         self.clipboard = text.clone();
@@ -7,6 +7,12 @@ pub mod cx_shared;
 
 pub mod cx_stdin;
 
+#[cfg(any(target_os = "linux", target_os="macos", target_os="windows"))]
+pub mod headless;
+
+#[cfg(any(target_os = "linux", target_os="macos", target_os="windows"))]
+pub use crate::os::headless::*;
+
 #[cfg(any(target_os = "macos", target_os="ios"))]
 pub mod apple;
 
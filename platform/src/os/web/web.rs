@@ -437,6 +437,8 @@ impl Cx {
                 CxOsOp::HideTextIME => {
                     self.os.from_wasm(FromWasmHideTextIME {});
                 },
+                CxOsOp::CopyToClipboard(_) =>{
+                }
                 CxOsOp::ShowClipboardActions(_) =>{
                 }
                 CxOsOp::SetCursor(cursor) => {
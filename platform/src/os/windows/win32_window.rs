@@ -735,7 +735,7 @@ impl Win32Window {
         //run_catch_panic(-1, || callback_inner(window, msg, wparam, lparam))
     }
 
-    unsafe fn copy_to_clipboard(text: &String) {
+    pub unsafe fn copy_to_clipboard(text: &String) {
         // plug it into the windows clipboard
         // make utf16 dta
         if let Ok(()) = OpenClipboard(None) {
@@ -15,8 +15,10 @@ use {
                 win32_event::*,
                 d3d11::{D3d11Window, D3d11Cx},
                 win32_app::*,
+                win32_window::Win32Window,
             },
             cx_native::EventFlow,
+            headless::HeadlessOptions,
         },
         pass::CxPassParent,
         cx_api::{CxOsApi, CxOsOp},
@@ -32,6 +34,10 @@ impl Cx {
         cx.borrow_mut().self_ref = Some(cx.clone());
         cx.borrow_mut().os_type = OsType::Windows;
         
+        if let Some(options) = HeadlessOptions::from_args() {
+            return Cx::headless_event_loop(cx, options);
+        }
+        
         let d3d11_cx = Rc::new(RefCell::new(D3d11Cx::new()));
 
         // hack: store ID3D11Device in CxOs, so texture-related operations become possible on the makepad/studio side, yet don't completely destroy the code there
@@ -217,6 +223,10 @@ impl Cx {
                     self.handle_media_signals();
                     self.call_event_handler(&Event::Signal);
                 }
+                if self.handle_live_edit() {
+                    self.call_event_handler(&Event::LiveEdit);
+                    self.redraw_all();
+                }
             }
         }
         
@@ -311,6 +321,9 @@ impl Cx {
                 CxOsOp::SetTopmost(_window_id, _is_topmost) => {
                     todo!()
                 }
+                CxOsOp::CopyToClipboard(content) => {
+                    unsafe {Win32Window::copy_to_clipboard(&content)}
+                }
                 CxOsOp::ShowClipboardActions(_) => {
                 }
                 CxOsOp::XrStartPresenting => {
@@ -134,6 +134,19 @@ impl Cx {
                 HostToStdin::KeyUp(e) => {
                     self.call_event_handler(&Event::KeyUp(e));
                 }
+                HostToStdin::TextInput(e) => {
+                    self.call_event_handler(&Event::TextInput(e.into()));
+                }
+                HostToStdin::TextCopy | HostToStdin::TextCut => {
+                    let cut = matches!(msg, HostToStdin::TextCut);
+                    if let Some(text) = self.stdin_text_clipboard(cut) {
+                        let _ = io::stdout().write_all(StdinToHost::SetClipboard(text).to_json().as_bytes());
+                    }
+                }
+                HostToStdin::RequestWidgetTree => {
+                    let nodes = self.stdin_widget_tree();
+                    let _ = io::stdout().write_all(StdinToHost::WidgetTree(nodes).to_json().as_bytes());
+                }
                 HostToStdin::MouseDown(e) => {
                     self.fingers.process_tap_count(
                         dvec2(e.x, e.y),
@@ -279,6 +292,16 @@ impl Cx {
                 CxOsOp::SetCursor(cursor) => {
                     let _ = io::stdout().write_all(StdinToHost::SetCursor(cursor).to_json().as_bytes());
                 },
+                CxOsOp::ShowTextIME(area, pos) => {
+                    let pos = area.get_clipped_rect(self).pos + pos;
+                    let _ = io::stdout().write_all(StdinToHost::ShowTextIME{x: pos.x, y: pos.y}.to_json().as_bytes());
+                },
+                CxOsOp::HideTextIME => {
+                    let _ = io::stdout().write_all(StdinToHost::HideTextIME.to_json().as_bytes());
+                },
+                CxOsOp::CopyToClipboard(content) => {
+                    let _ = io::stdout().write_all(StdinToHost::SetClipboard(content).to_json().as_bytes());
+                },
                 _ => ()
                 /*
                 CxOsOp::CloseWindow(_window_id) => {},
@@ -0,0 +1,228 @@
+// a tiny language server for testing the lsp client. it keeps the documents it is sent, and
+// answers from their words:
+// - every todo is a warning
+// - hover tells the word under the cursor
+// - go to definition leads to the first use of that word
+// - completion offers the words of the document
+// with --utf8 it counts positions in bytes, otherwise in utf-16 code units
+use {
+    std::{
+        collections::HashMap,
+        io::{self, BufReader},
+    },
+    makepad_studio_core::{
+        makepad_code_editor::text::{Change, Position, Text},
+        makepad_micro_serde::JsonValue,
+        lsp::lsp_protocol::*,
+    },
+};
+
+fn word_at(line: &str, byte_index: usize) -> Option<(usize, usize)> {
+    let is_word = | c: char | c.is_alphanumeric() || c == '_';
+    let start = line[..byte_index].rfind( | c | !is_word(c)).map_or(0, | i | i + line[i..].chars().next().unwrap().len_utf8());
+    let end = line[byte_index..].find( | c | !is_word(c)).map_or(line.len(), | i | byte_index + i);
+    if start == end {
+        return None
+    }
+    Some((start, end))
+}
+
+fn words(text: &Text) -> Vec<(Position, Position, String)> {
+    let mut words = Vec::new();
+    for (line_index, line) in text.as_lines().iter().enumerate() {
+        let mut byte_index = 0;
+        while byte_index < line.len() {
+            if let Some((start, end)) = word_at(line, byte_index).filter( | (start, _) | *start == byte_index) {
+                words.push((
+                    Position {line_index, byte_index: start},
+                    Position {line_index, byte_index: end},
+                    line[start..end].to_string()
+                ));
+                byte_index = end;
+            }
+            else {
+                byte_index += line[byte_index..].chars().next().unwrap().len_utf8();
+            }
+        }
+    }
+    words
+}
+
+struct Server {
+    encoding: PositionEncoding,
+    documents: HashMap<String, Text>,
+    stdout: io::Stdout,
+}
+
+impl Server {
+    fn send(&mut self, message: JsonValue) {
+        write_message(&mut self.stdout, &message).unwrap();
+    }
+
+    fn respond(&mut self, id: JsonValue, result: JsonValue) {
+        self.send(JsonValue::object([
+            ("jsonrpc", "2.0".into()),
+            ("id", id),
+            ("result", result),
+        ]));
+    }
+
+    // a closed document has no diagnostics left
+    fn publish_diagnostics(&mut self, uri: &str) {
+        let diagnostics: Vec<JsonValue> = match self.documents.get(uri) {
+            Some(text) => words(text).into_iter().filter( | (_, _, word) | word == "todo").map( | (start, end, _) | {
+                JsonValue::object([
+                    ("range", to_lsp_range(text.as_lines(), start, end, self.encoding)),
+                    ("severity", 2usize.into()),
+                    ("message", "todo left in the code".into()),
+                ])
+            }).collect(),
+            None => Vec::new()
+        };
+        self.send(JsonValue::object([
+            ("jsonrpc", "2.0".into()),
+            ("method", "textDocument/publishDiagnostics".into()),
+            ("params", JsonValue::object([
+                ("uri", uri.into()),
+                ("diagnostics", diagnostics.into()),
+            ])),
+        ]));
+    }
+
+    // the document and the word at the position of a request
+    fn word_at_request(&self, params: &JsonValue) -> Option<(String, Position, Position, String)> {
+        let uri = params.path("textDocument.uri") ?.as_str() ?;
+        let text = self.documents.get(uri) ?;
+        let position = from_lsp_position(text.as_lines(), params.get("position") ?, self.encoding) ?;
+        let line = &text.as_lines()[position.line_index];
+        let (start, end) = word_at(line, position.byte_index) ?;
+        Some((
+            uri.to_string(),
+            Position {line_index: position.line_index, byte_index: start},
+            Position {line_index: position.line_index, byte_index: end},
+            line[start..end].to_string()
+        ))
+    }
+
+    fn handle(&mut self, message: JsonValue) -> bool {
+        let method = message.get("method").and_then( | m | m.as_str()).unwrap_or("").to_string();
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or_default();
+        match method.as_str() {
+            "initialize" => {
+                let encoding = match self.encoding {
+                    PositionEncoding::Utf8 => "utf-8",
+                    PositionEncoding::Utf16 => "utf-16",
+                };
+                self.respond(id.unwrap(), JsonValue::object([
+                    ("capabilities", JsonValue::object([
+                        ("positionEncoding", encoding.into()),
+                        ("textDocumentSync", JsonValue::object([
+                            ("openClose", true.into()),
+                            ("change", 2usize.into()),
+                        ])),
+                        ("hoverProvider", true.into()),
+                        ("definitionProvider", true.into()),
+                        ("completionProvider", JsonValue::object([])),
+                    ])),
+                ]));
+            }
+            "textDocument/didOpen" => {
+                let uri = params.path("textDocument.uri").and_then( | u | u.as_str()).unwrap().to_string();
+                let text = params.path("textDocument.text").and_then( | t | t.as_str()).unwrap();
+                self.documents.insert(uri.clone(), text.into());
+                self.publish_diagnostics(&uri);
+            }
+            "textDocument/didClose" => {
+                let uri = params.path("textDocument.uri").and_then( | u | u.as_str()).unwrap().to_string();
+                self.documents.remove(&uri);
+                self.publish_diagnostics(&uri);
+            }
+            "textDocument/didChange" => {
+                let uri = params.path("textDocument.uri").and_then( | u | u.as_str()).unwrap().to_string();
+                let text = self.documents.get_mut(&uri).unwrap();
+                for change in params.get("contentChanges").and_then( | c | c.as_array()).unwrap() {
+                    let new_text = change.get("text").and_then( | t | t.as_str()).unwrap();
+                    let Some(range) = change.get("range") else {
+                        *text = new_text.into();
+                        continue
+                    };
+                    let (start, end) = from_lsp_range(text.as_lines(), range, self.encoding).unwrap();
+                    if start != end {
+                        text.apply_change(Change::Delete(start, end - start));
+                    }
+                    if !new_text.is_empty() {
+                        text.apply_change(Change::Insert(start, new_text.into()));
+                    }
+                }
+                self.publish_diagnostics(&uri);
+            }
+            "textDocument/hover" => {
+                let result = match self.word_at_request(&params) {
+                    Some((_, _, _, word)) => JsonValue::object([
+                        ("contents", JsonValue::object([
+                            ("kind", "markdown".into()),
+                            ("value", format!("```rust\n{}\n```\n\nhover for {}", word, word).into()),
+                        ])),
+                    ]),
+                    None => JsonValue::Null
+                };
+                self.respond(id.unwrap(), result);
+            }
+            "textDocument/definition" => {
+                let result = match self.word_at_request(&params) {
+                    Some((uri, _, _, word)) => {
+                        let text = &self.documents[&uri];
+                        let (start, end, _) = words(text).into_iter().find( | (_, _, w) | *w == word).unwrap();
+                        JsonValue::object([
+                            ("uri", uri.as_str().into()),
+                            ("range", to_lsp_range(text.as_lines(), start, end, self.encoding)),
+                        ])
+                    }
+                    None => JsonValue::Null
+                };
+                self.respond(id.unwrap(), result);
+            }
+            "textDocument/completion" => {
+                let uri = params.path("textDocument.uri").and_then( | u | u.as_str()).unwrap();
+                let mut labels: Vec<String> = words(&self.documents[uri]).into_iter().map( | (_, _, word) | word).collect();
+                labels.sort();
+                labels.dedup();
+                let items: Vec<JsonValue> = labels.into_iter().map( | label | JsonValue::object([
+                    ("label", label.as_str().into()),
+                    ("detail", "word".into()),
+                ])).collect();
+                self.respond(id.unwrap(), JsonValue::object([
+                    ("isIncomplete", false.into()),
+                    ("items", items.into()),
+                ]));
+            }
+            "shutdown" => self.respond(id.unwrap(), JsonValue::Null),
+            "exit" => return false,
+            // requests we do not know get an empty answer, notifications are ignored
+            _ => if let Some(id) = id {
+                self.respond(id, JsonValue::Null);
+            }
+        }
+        true
+    }
+}
+
+fn main() {
+    let mut server = Server {
+        encoding: if std::env::args().any( | arg | arg == "--utf8") {
+            PositionEncoding::Utf8
+        }
+        else {
+            PositionEncoding::Utf16
+        },
+        documents: HashMap::new(),
+        stdout: io::stdout(),
+    };
+    let mut reader = BufReader::new(io::stdin());
+    while let Ok(Some(message)) = read_message(&mut reader) {
+        if !server.handle(message) {
+            break
+        }
+    }
+}
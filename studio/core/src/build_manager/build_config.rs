@@ -0,0 +1,382 @@
+use {
+    std::path::Path,
+    crate::{
+        makepad_toml_parser::*,
+        build_manager::build_protocol::*,
+    },
+};
+
+// studio reads the build configuration from this file in the root of the project. without it
+// the builtin targets run on rustup nightly, as they always did. an example:
+//
+// [build]
+// toolchain = "stable"        # rustup toolchain, "" runs the cargo found on the path
+// features = ["gpu"]
+// env = {RUST_LOG = "info"}
+// args = ["--locked"]         # added to every cargo command line
+// app_args = []               # passed to the app after --
+// rustc_bootstrap = true      # RUSTC_BOOTSTRAP=1, the test target needs it off nightly
+// builtin_targets = false     # only show the targets below in the run list
+//
+// [target.bench]
+// name = "Bench"
+// command = "run --profile bench"
+// in_studio = true            # run it inside a studio tab
+// binaries = ["my-app"]       # only for these binaries, all of them if left out
+// toolchain, features, args, app_args and rustc_bootstrap replace the [build] ones, env is added to them
+//
+// [lsp]
+// command = ["rust-analyzer"] # a language server that speaks over stdio, none is started without it
+// extensions = ["rs"]         # the files it is told about, rs if left out
+pub const BUILD_CONFIG_FILE: &str = "makepad_studio.toml";
+
+#[derive(Clone, Debug)]
+pub struct BuildConfigTarget {
+    pub name: String,
+    pub command: Vec<String>,
+    pub in_studio: bool,
+    pub binaries: Vec<String>,
+    pub profile: BuildProfile,
+}
+
+#[derive(Clone, Debug)]
+pub struct LspConfig {
+    pub command: Vec<String>,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BuildConfig {
+    // used for the builtin targets, and the defaults for the configured ones
+    pub profile: BuildProfile,
+    pub builtin_targets: bool,
+    pub targets: Vec<BuildConfigTarget>,
+    pub lsp: Option<LspConfig>,
+}
+
+#[derive(Debug)]
+pub struct BuildConfigErr {
+    pub msg: String,
+    // byte offset into the file
+    pub offset: usize,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            profile: BuildProfile {
+                toolchain: "nightly".to_string(),
+                ..Default::default()
+            },
+            builtin_targets: true,
+            targets: Vec::new(),
+            lsp: None,
+        }
+    }
+}
+
+impl BuildConfig {
+    pub fn load(root_path: &Path) -> Result<Self, BuildConfigErr> {
+        match std::fs::read_to_string(root_path.join(BUILD_CONFIG_FILE)) {
+            Ok(data) => Self::parse(&data),
+            Err(_) => Ok(Self::default())
+        }
+    }
+
+    pub fn parse(data: &str) -> Result<Self, BuildConfigErr> {
+        let toml = parse_toml(data).map_err( | e | BuildConfigErr {
+            msg: e.msg,
+            offset: e.span.start
+        }) ?;
+
+        let mut config = Self::default();
+        if let Some(build) = toml.get("build") {
+            config.profile = read_profile(build, &config.profile) ?;
+        }
+        if let Some(value) = toml.get("build.builtin_targets") {
+            config.builtin_targets = read_bool(value) ?;
+        }
+
+        // targets are [target.<id>] tables, in the order of the file
+        let targets = match toml.get("target") {
+            Some(Toml::Table(table)) => table.iter().collect(),
+            Some(value) => return Err(value_err(value, "expected [target.<id>] tables")),
+            None => Vec::new()
+        };
+        for (id, target) in targets {
+            let Toml::Table(table) = target else {
+                return Err(value_err(target, "expected a [target.<id>] table"))
+            };
+            let get = | key: &str | table.get(key);
+            let name = match get("name") {
+                Some(value) => read_str(value) ?,
+                None => id.to_string()
+            };
+            let command = match get("command") {
+                Some(value) => read_list(value) ?,
+                None => vec!["run".to_string()]
+            };
+            if command.is_empty() {
+                return Err(value_err(get("command").unwrap(), "command cannot be empty"))
+            }
+            let in_studio = match get("in_studio") {
+                Some(value) => read_bool(value) ?,
+                None => false
+            };
+            let binaries = match get("binaries") {
+                Some(value) => read_list(value) ?,
+                None => Vec::new()
+            };
+            let profile = read_profile(target, &config.profile) ?;
+            config.targets.push(BuildConfigTarget {
+                name,
+                command,
+                in_studio,
+                binaries,
+                profile
+            });
+        }
+
+        if let Some(value) = toml.get("lsp.command") {
+            let command = read_list(value) ?;
+            if command.is_empty() {
+                return Err(value_err(value, "command cannot be empty"))
+            }
+            let extensions = match toml.get("lsp.extensions") {
+                Some(value) => read_list(value) ?,
+                None => vec!["rs".to_string()]
+            };
+            config.lsp = Some(LspConfig {command, extensions});
+        }
+        Ok(config)
+    }
+
+    // builtin targets come first, the configured ones are numbered after them
+    pub fn target_count(&self) -> u64 {
+        BuildTarget::len() + self.targets.len() as u64
+    }
+
+    fn config_target(&self, tgt: u64) -> Option<&BuildConfigTarget> {
+        tgt.checked_sub(BuildTarget::len()).and_then( | index | self.targets.get(index as usize))
+    }
+
+    pub fn target_name(&self, tgt: u64) -> &str {
+        if let Some(target) = self.config_target(tgt) {
+            return &target.name
+        }
+        BuildTarget::name(tgt)
+    }
+
+    pub fn target_applies(&self, tgt: u64, binary: &str) -> bool {
+        if let Some(target) = self.config_target(tgt) {
+            return target.binaries.is_empty() || target.binaries.iter().any( | b | b == binary)
+        }
+        self.builtin_targets && tgt < BuildTarget::len()
+    }
+
+    pub fn target(&self, tgt: u64) -> Option<BuildTarget> {
+        if let Some(target) = self.config_target(tgt) {
+            return Some(BuildTarget::Custom {
+                id: tgt,
+                command: target.command.clone(),
+                in_studio: target.in_studio
+            })
+        }
+        Some(match tgt {
+            BuildTarget::RELEASE => BuildTarget::Release,
+            BuildTarget::DEBUG => BuildTarget::Debug,
+            BuildTarget::RELEASE_STUDIO => BuildTarget::ReleaseStudio,
+            BuildTarget::DEBUG_STUDIO => BuildTarget::DebugStudio,
+            BuildTarget::PROFILER => BuildTarget::Profiler,
+            BuildTarget::IOS_SIM => BuildTarget::IosSim {
+                org: "makepad".to_string(),
+                app: "example1".to_string()
+            },
+            BuildTarget::IOS_DEVICE => BuildTarget::IosDevice {
+                org: "makepad".to_string(),
+                app: "example1".to_string()
+            },
+            BuildTarget::ANDROID => BuildTarget::Android,
+            BuildTarget::WEBASSEMBLY => BuildTarget::WebAssembly,
+            BuildTarget::CHECK_MACOS => BuildTarget::CheckMacos,
+            BuildTarget::CHECK_WINDOWS => BuildTarget::CheckWindows,
+            BuildTarget::CHECK_LINUX => BuildTarget::CheckLinux,
+            BuildTarget::CHECK_ALL => BuildTarget::CheckAll,
+            BuildTarget::TEST => BuildTarget::Test,
+            _ => return None
+        })
+    }
+
+    pub fn profile(&self, tgt: u64) -> &BuildProfile {
+        if let Some(target) = self.config_target(tgt) {
+            return &target.profile
+        }
+        &self.profile
+    }
+}
+
+fn read_profile(scope: &Toml, base: &BuildProfile) -> Result<BuildProfile, BuildConfigErr> {
+    let Toml::Table(table) = scope else {
+        return Err(value_err(scope, "expected a table"))
+    };
+    let get = | key: &str | table.get(key);
+    let mut profile = base.clone();
+    if let Some(value) = get("toolchain") {
+        profile.toolchain = read_str(value) ?;
+    }
+    if let Some(value) = get("features") {
+        profile.features = read_list(value) ?;
+    }
+    if let Some(value) = get("args") {
+        profile.args = read_list(value) ?;
+    }
+    if let Some(value) = get("app_args") {
+        profile.app_args = read_list(value) ?;
+    }
+    if let Some(value) = get("rustc_bootstrap") {
+        profile.rustc_bootstrap = read_bool(value) ?;
+    }
+    // env can be an inline table, env.KEY = "value" lines or an [<scope>.env] table
+    let env = match get("env") {
+        Some(Toml::Table(env)) => env.iter().collect(),
+        Some(value) => return Err(value_err(value, "expected a table of strings")),
+        None => Vec::new()
+    };
+    for (key, value) in env {
+        let value = read_str(value) ?;
+        if let Some(existing) = profile.env.iter_mut().find( | (k, _) | k == key) {
+            existing.1 = value;
+        }
+        else {
+            profile.env.push((key.to_string(), value));
+        }
+    }
+    Ok(profile)
+}
+
+fn value_err(value: &Toml, msg: &str) -> BuildConfigErr {
+    BuildConfigErr {msg: msg.to_string(), offset: value.span().start}
+}
+
+fn read_str(value: &Toml) -> Result<String, BuildConfigErr> {
+    match value {
+        Toml::Str(s, _) => Ok(s.clone()),
+        _ => Err(value_err(value, "expected a string"))
+    }
+}
+
+fn read_bool(value: &Toml) -> Result<bool, BuildConfigErr> {
+    match value {
+        Toml::Bool(b, _) => Ok(*b),
+        _ => Err(value_err(value, "expected true or false"))
+    }
+}
+
+// a list is an array of strings, or a single string split on whitespace
+fn read_list(value: &Toml) -> Result<Vec<String>, BuildConfigErr> {
+    match value {
+        Toml::Str(s, _) => Ok(s.split_whitespace().map( | s | s.to_string()).collect()),
+        Toml::Array(values, _) => values.iter().map(read_str).collect(),
+        _ => Err(value_err(value, "expected a string or an array of strings"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map( | s | s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_defaults() {
+        let config = BuildConfig::parse("# nothing here\n").unwrap();
+        assert_eq!(config.profile.toolchain, "nightly");
+        assert!(!config.profile.rustc_bootstrap);
+        assert!(config.builtin_targets && config.targets.is_empty() && config.lsp.is_none());
+        assert_eq!(config.target_count(), BuildTarget::len());
+        assert_eq!(config.target(BuildTarget::TEST), Some(BuildTarget::Test));
+        assert_eq!(config.target(BuildTarget::len()), None);
+    }
+
+    #[test]
+    fn parse_targets() {
+        let config = BuildConfig::parse(r#"
+[build]
+toolchain = ""
+features = ["gpu"]
+env = {RUST_LOG = "info", A = "1"}
+args = "--locked --offline"
+rustc_bootstrap = true
+builtin_targets = false
+
+[target.bench]
+name = "Bench"
+command = "run --profile bench"
+in_studio = true
+binaries = ["my-app"]
+toolchain = "stable"
+env.A = "2"
+env.B = "3"
+
+[target.doc]
+command = ["doc", "--no-deps"]
+rustc_bootstrap = false
+
+[lsp]
+command = "rust-analyzer --log-file x"
+"#).unwrap();
+        assert_eq!(config.profile.toolchain, "");
+        assert_eq!(config.profile.features, strings(&["gpu"]));
+        assert_eq!(config.profile.args, strings(&["--locked", "--offline"]));
+        assert!(config.profile.rustc_bootstrap);
+        assert!(!config.builtin_targets);
+
+        // targets keep the order of the file and are numbered after the builtin ones
+        let bench = BuildTarget::len();
+        let doc = bench + 1;
+        assert_eq!(config.target_count(), doc + 1);
+        assert_eq!(config.target_name(bench), "Bench");
+        assert_eq!(config.target_name(doc), "doc");
+        assert_eq!(config.target(bench), Some(BuildTarget::Custom {id: bench, command: strings(&["run", "--profile", "bench"]), in_studio: true}));
+        assert_eq!(config.target(doc), Some(BuildTarget::Custom {id: doc, command: strings(&["doc", "--no-deps"]), in_studio: false}));
+        assert!(config.target_applies(bench, "my-app") && !config.target_applies(bench, "other"));
+        assert!(config.target_applies(doc, "other"));
+        assert!(!config.target_applies(BuildTarget::TEST, "my-app"));
+
+        // targets start from the [build] profile, env adds to it
+        let profile = config.profile(bench);
+        assert_eq!(profile.toolchain, "stable");
+        assert_eq!(profile.features, strings(&["gpu"]));
+        assert!(profile.rustc_bootstrap);
+        let env: Vec<(&str, &str)> = profile.env.iter().map( | (k, v) | (k.as_str(), v.as_str())).collect();
+        assert_eq!(env, vec![("RUST_LOG", "info"), ("A", "2"), ("B", "3")]);
+        assert!(!config.profile(doc).rustc_bootstrap);
+        assert_eq!(config.profile(BuildTarget::TEST), &config.profile);
+
+        let lsp = config.lsp.unwrap();
+        assert_eq!(lsp.command, strings(&["rust-analyzer", "--log-file", "x"]));
+        assert_eq!(lsp.extensions, strings(&["rs"]));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = | data: &str | BuildConfig::parse(data).unwrap_err();
+        // errors point at the value that is wrong
+        let data = "[build]\ntoolchain = 1\n";
+        assert_eq!(err(data).offset, data.find('1').unwrap());
+        let data = "[build]\nrustc_bootstrap = \"yes\"\n";
+        assert_eq!(err(data).msg, "expected true or false");
+        assert_eq!(err(data).offset, data.find('"').unwrap());
+        assert_eq!(err("[build]\nfeatures = [1]\n").msg, "expected a string");
+        assert_eq!(err("[build]\nenv = \"A=1\"\n").msg, "expected a table of strings");
+        assert_eq!(err("target = 1\n").msg, "expected [target.<id>] tables");
+        assert_eq!(err("[target]\nx = 1\n").msg, "expected a [target.<id>] table");
+        assert_eq!(err("[target.x]\ncommand = []\n").msg, "command cannot be empty");
+        assert_eq!(err("[lsp]\ncommand = \"\"\n").msg, "command cannot be empty");
+        // and so do those of the toml itself
+        let data = "[build]\ntoolchain = \"nightly\n";
+        assert_eq!(err(data).offset, data.find('"').unwrap());
+    }
+}
@@ -1,7 +1,7 @@
 
 use {
     crate::{
-        file_system::file_system::FileSystem,
+        file_system::file_system::{FileSystem, BUILD_DECORATION_ID},
         makepad_micro_serde::*,
         makepad_platform::*,
         makepad_widgets::*,
@@ -13,12 +13,14 @@ use {
         },
         build_manager::{
             run_view::*,
+            widget_inspector::WidgetInspector,
+            build_config::*,
             build_protocol::*,
             build_client::BuildClient
         },
         makepad_shell::*,
     },
-    makepad_code_editor::{text::Position, decoration::{Decoration, DecorationType}},
+    makepad_code_editor::{text::{Change, Length, Position}, decoration::{Decoration, DecorationType}},
     makepad_http::server::*,
     std::{
         collections::HashMap,
@@ -45,10 +47,55 @@ live_design!{
     }
 }
 pub const MAX_SWAPCHAIN_HISTORY: usize = 4;
+
+// turns the edits of a fix into editor changes, last edit first so earlier positions stay valid.
+// returns None if the file no longer holds the code rustc saw under each edit
+fn fix_changes(text: &str, edits: &[&LogItemFixEdit]) -> Option<Vec<Change>> {
+    // rustc counts columns in chars, the editor in bytes
+    let byte_position = | position: Position | -> Option<Position> {
+        let line = text.split('\n').nth(position.line_index)?;
+        let byte_index = line.char_indices().map( | (index, _) | index).chain(Some(line.len())).nth(position.byte_index)?;
+        Some(Position {line_index: position.line_index, byte_index})
+    };
+    let mut edits = edits.to_vec();
+    edits.sort_by( | a, b | b.start.cmp(&a.start));
+    let mut changes = Vec::new();
+    for edit in edits {
+        let start = byte_position(edit.start)?;
+        let end = byte_position(edit.start + edit.length)?;
+        if end < start || text_between(text, start, end) != edit.original {
+            return None
+        }
+        if end > start {
+            changes.push(Change::Delete(start, end - start));
+        }
+        if !edit.replacement.is_empty() {
+            changes.push(Change::Insert(start, edit.replacement.as_str().into()));
+        }
+    }
+    Some(changes)
+}
+
+fn text_between(text: &str, start: Position, end: Position) -> String {
+    let lines: Vec<&str> = text.split('\n').skip(start.line_index).take(end.line_index - start.line_index + 1).collect();
+    if lines.len() == 1 {
+        return lines[0][start.byte_index..end.byte_index].to_string()
+    }
+    let mut out = lines[0][start.byte_index..].to_string();
+    for line in &lines[1..lines.len() - 1] {
+        out.push('\n');
+        out.push_str(line);
+    }
+    out.push('\n');
+    out.push_str(&lines[lines.len() - 1][..end.byte_index]);
+    out
+}
+
 pub struct ActiveBuild {
     pub log_index: String,
     pub item_id: LiveId,
     pub process: BuildProcess,
+    pub profile: BuildProfile,
     pub run_view_id: LiveId,
     pub cmd_id: Option<BuildCmdId>,
     
@@ -137,9 +184,13 @@ pub struct BuildManager {
     #[live(8001usize)] http_port: usize,
     #[rust] pub clients: Vec<BuildClient>,
     #[rust] pub log: Vec<(ActiveBuildId, LogItem)>,
+    #[rust] pub tests: Vec<LogItemTest>,
+    #[rust] tests_cmd_id: Option<BuildCmdId>,
+    #[rust] pub inspector: WidgetInspector,
     #[live] recompile_timeout: f64,
     #[rust] recompile_timer: Timer,
     #[rust] pub binaries: Vec<BuildBinary>,
+    #[rust] pub config: BuildConfig,
     #[rust] pub active: ActiveBuilds,
     #[rust] pub studio_http: String,
     #[rust] pub recv_external_ip: ToUIReceiver<SocketAddr>,
@@ -155,6 +206,8 @@ pub enum BuildManagerAction {
     RedrawDoc, // {doc_id: DocumentId},
     StdinToHost {run_view_id: LiveId, msg: StdinToHost},
     RedrawLog,
+    RedrawTests,
+    RedrawWidgetTree,
     RedrawFile(FileNodeId),
     RecompileStarted,
     ClearLog,
@@ -177,7 +230,29 @@ impl BuildManager {
         }
     }
     
+    pub fn load_build_config(&mut self) {
+        match BuildConfig::load(&self.root_path) {
+            Ok(config) => self.config = config,
+            Err(err) => {
+                // keep the previous config and point at the problem in the log
+                let data = std::fs::read_to_string(self.root_path.join(BUILD_CONFIG_FILE)).unwrap_or_default();
+                let before = data.get(..err.offset).unwrap_or(&data);
+                let line_index = before.matches('\n').count();
+                let byte_index = before.len() - before.rfind('\n').map( | i | i + 1).unwrap_or(0);
+                self.log.push((ActiveBuildId::default(), LogItem::Location(LogItemLocation {
+                    level: LogItemLevel::Error,
+                    file_name: BUILD_CONFIG_FILE.to_string(),
+                    start: Position {line_index, byte_index},
+                    length: Length {line_count: 0, byte_count: 0},
+                    msg: err.msg,
+                    fix: None
+                })));
+            }
+        }
+    }
+    
     pub fn update_run_list(&mut self, _cx: &mut Cx) {
+        self.load_build_config();
         self.binaries.clear();
         match shell_env_cap(&[], &self.root_path, "cargo", &["run", "--bin"]) {
             Ok(_) => {}
@@ -224,11 +299,18 @@ impl BuildManager {
     
     pub fn start_recompile(&mut self, _cx: &mut Cx) {
         // alright so. a file was changed. now what.
+        // it might have been the build config, running builds pick up its changes
+        self.load_build_config();
         for active_build in self.active.builds.values_mut() {
             if let Some(cmd_id) = active_build.cmd_id {
                 self.clients[0].send_cmd_with_id(cmd_id, BuildCmd::Stop);
             }
-            let cmd_id = self.clients[0].send_cmd(BuildCmd::Run(active_build.process.clone(), self.studio_http.clone()));
+            let tgt = active_build.process.target.id();
+            if let Some(target) = self.config.target(tgt) {
+                active_build.process.target = target;
+                active_build.profile = self.config.profile(tgt).clone();
+            }
+            let cmd_id = self.clients[0].send_cmd(BuildCmd::Run(active_build.process.clone(), active_build.profile.clone(), self.studio_http.clone()));
             active_build.cmd_id = Some(cmd_id);
             active_build.swapchain = None;
             //active_build.last_swapchain_with_completed_draws = None;
@@ -248,11 +330,36 @@ impl BuildManager {
     
     pub fn clear_log(&mut self, cx: &mut Cx, dock: &DockRef, file_system: &mut FileSystem) {
         // lets clear all log related decorations
-        file_system.clear_all_decorations();
+        file_system.clear_all_decorations(BUILD_DECORATION_ID);
         file_system.redraw_all_views(cx, dock);
         self.log.clear();
     }
     
+    // applies a compiler suggestion, to the open document of a file when there is one so the whole
+    // fix is a single undoable change. files that changed since the build are left alone
+    pub fn apply_fix(&self, file_system: &mut FileSystem, fix: &LogItemFix) {
+        let mut file_names: Vec<&str> = fix.edits.iter().map( | edit | edit.file_name.as_str()).collect();
+        file_names.sort();
+        file_names.dedup();
+        for file_name in file_names {
+            let Some(file_id) = file_system.path_to_file_node_id(file_name) else {
+                continue
+            };
+            let edits: Vec<&LogItemFixEdit> = fix.edits.iter().filter( | edit | edit.file_name == file_name).collect();
+            let text = match file_system.open_document(file_id) {
+                Some(doc) => doc.as_text().to_string(),
+                None => match std::fs::read_to_string(self.root_path.join(file_name)) {
+                    Ok(text) => text,
+                    Err(_) => continue
+                }
+            };
+            match fix_changes(&text, &edits) {
+                Some(changes) => file_system.edit_file(file_id, &text, changes),
+                None => log!("Not applying the fix to {}, the code changed since the build", file_name)
+            }
+        }
+    }
+    
     pub fn start_recompile_timer(&mut self, cx: &mut Cx, ui: &WidgetRef) {
         cx.stop_timer(self.recompile_timer);
         self.recompile_timer = cx.start_timeout(self.recompile_timeout);
@@ -297,6 +404,9 @@ impl BuildManager {
         
         let log = &mut self.log;
         let active = &mut self.active;
+        let tests = &mut self.tests;
+        let tests_cmd_id = &mut self.tests_cmd_id;
+        let inspector = &mut self.inspector;
         //let editor_state = &mut state.editor_state;
         self.clients[0].handle_event_with(cx, event, &mut | cx, wrap | {
             /*match &wrap.item{
@@ -320,7 +430,7 @@ impl BuildManager {
                         match loc.level{
                             LogItemLevel::Warning=>{
                                 file_system.add_decoration(file_id, Decoration::new(
-                                    0,
+                                    BUILD_DECORATION_ID,
                                     pos,
                                     pos + loc.length,
                                     DecorationType::Warning
@@ -329,7 +439,7 @@ impl BuildManager {
                             }
                             LogItemLevel::Error=>{
                                 file_system.add_decoration(file_id, Decoration::new(
-                                    0,
+                                    BUILD_DECORATION_ID,
                                     pos,
                                     pos + loc.length,
                                     DecorationType::Error
@@ -357,6 +467,50 @@ impl BuildManager {
                     }*/
                     //editor_state.messages.push(BuildMsg::Location(loc));
                 }
+                LogItem::Test(test) => {
+                    // a new test run replaces the results of the previous one
+                    if *tests_cmd_id != Some(wrap.cmd_id) {
+                        *tests_cmd_id = Some(wrap.cmd_id);
+                        tests.clear();
+                    }
+                    if test.status == TestStatus::Failed {
+                        if let Some(id) = active.build_id_from_cmd_id(wrap.cmd_id) {
+                            if let Some(failure) = &test.failure {
+                                if let Some(file_id) = file_system.path_to_file_node_id(&failure.file_name) {
+                                    file_system.add_decoration(file_id, Decoration::new(
+                                        BUILD_DECORATION_ID,
+                                        failure.start,
+                                        failure.start + failure.length,
+                                        DecorationType::Error
+                                    ));
+                                    dispatch_action(cx, BuildManagerAction::RedrawFile(file_id))
+                                }
+                                log.push((id, LogItem::Location(failure.clone())));
+                            }
+                            else {
+                                log.push((id, LogItem::Bare(LogItemBare {
+                                    level: LogItemLevel::Error,
+                                    line: format!("test {} failed", test.name)
+                                })));
+                            }
+                            // what the test printed before it panicked
+                            for line in test.stdout.lines().take_while( | line | !line.starts_with("thread '")).filter( | line | !line.is_empty()) {
+                                log.push((id, LogItem::Bare(LogItemBare {
+                                    level: LogItemLevel::Log,
+                                    line: line.to_string()
+                                })));
+                            }
+                            dispatch_action(cx, BuildManagerAction::RedrawLog)
+                        }
+                    }
+                    if let Some(old) = tests.iter_mut().find( | old | old.suite == test.suite && old.name == test.name) {
+                        *old = test;
+                    }
+                    else {
+                        tests.push(test);
+                    }
+                    dispatch_action(cx, BuildManagerAction::RedrawTests)
+                }
                 LogItem::Bare(bare) => {
                     //log!("{:?}", bare);
                     if let Some(id) = active.build_id_from_cmd_id(wrap.cmd_id) {
@@ -368,6 +522,12 @@ impl BuildManager {
                 LogItem::StdinToHost(line) => {
                     let msg: Result<StdinToHost, DeJsonErr> = DeJson::deserialize_json(&line);
                     match msg {
+                        Ok(StdinToHost::WidgetTree(nodes)) => {
+                            let run_view_id = active.run_view_id_from_cmd_id(wrap.cmd_id).unwrap_or(LiveId(0));
+                            if inspector.set_nodes(run_view_id, nodes) {
+                                dispatch_action(cx, BuildManagerAction::RedrawWidgetTree)
+                            }
+                        }
                         Ok(msg) => {
                             dispatch_action(cx, BuildManagerAction::StdinToHost {
                                 run_view_id: active.run_view_id_from_cmd_id(wrap.cmd_id).unwrap_or(LiveId(0)),
@@ -593,4 +753,49 @@ impl BuildManager {
             }
         });
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::makepad_code_editor::text::Text;
+
+    fn edit(line: usize, column: usize, original: &str, replacement: &str) -> LogItemFixEdit {
+        let lines: Vec<&str> = original.split('\n').collect();
+        let length = if lines.len() == 1 {
+            Length {line_count: 0, byte_count: original.chars().count()}
+        }
+        else {
+            Length {line_count: lines.len() - 1, byte_count: lines.last().unwrap().chars().count()}
+        };
+        LogItemFixEdit {
+            file_name: "src/lib.rs".to_string(),
+            start: Position {line_index: line, byte_index: column},
+            length,
+            original: original.to_string(),
+            replacement: replacement.to_string()
+        }
+    }
+
+    fn apply_fix(text: &str, edits: &[LogItemFixEdit]) -> Option<String> {
+        let edits: Vec<&LogItemFixEdit> = edits.iter().collect();
+        let mut text = Text::from(text.to_string());
+        for change in fix_changes(&text.to_string(), &edits) ? {
+            text.apply_change(change);
+        }
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn fix_changes_check_the_original_code() {
+        let text = "let é = foo(x);\nlet y = bar(\n    z);\n";
+        // columns are in chars, and edits are applied back to front
+        let fixed = apply_fix(text, &[edit(0, 8, "foo", "self.foo"), edit(0, 12, "x", "&x")]);
+        assert_eq!(fixed.as_deref(), Some("let é = self.foo(&x);\nlet y = bar(\n    z);\n"));
+        let fixed = apply_fix(text, &[edit(1, 11, "(\n    z)", "(z)")]);
+        assert_eq!(fixed.as_deref(), Some("let é = foo(x);\nlet y = bar(z);\n"));
+        // a file that moved on since the build is left alone
+        assert_eq!(apply_fix(text, &[edit(0, 8, "baz", "self.baz")]), None);
+        assert_eq!(apply_fix(text, &[edit(0, 8, "foo", "self.foo"), edit(1, 8, "baz", "qux")]), None);
+        assert_eq!(apply_fix(text, &[edit(5, 0, "", "x")]), None);
+    }
+}
@@ -23,6 +23,22 @@ pub enum BuildTarget {
     CheckWindows,
     CheckLinux,
     CheckAll,
+    Test,
+    // a target from the build config, command is the cargo subcommand with its flags
+    Custom{id:u64, command:Vec<String>, in_studio:bool},
+}
+
+// how cargo is started for a build, see build_config.rs
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BuildProfile {
+    // runs cargo through rustup with this toolchain, or the cargo on the path when empty
+    pub toolchain: String,
+    pub features: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub args: Vec<String>,
+    pub app_args: Vec<String>,
+    // sets RUSTC_BOOTSTRAP=1 so unstable options, like the json test output, work off nightly
+    pub rustc_bootstrap: bool,
 }
 
 impl BuildTarget {
@@ -30,6 +46,7 @@ impl BuildTarget {
         match self{
             Self::ReleaseStudio=>true,
             Self::DebugStudio=>true,
+            Self::Custom{in_studio,..}=>*in_studio,
             _=>false
         }
     }
@@ -47,7 +64,8 @@ impl BuildTarget {
     pub const CHECK_WINDOWS:u64 = 10;
     pub const CHECK_LINUX:u64 = 11;
     pub const CHECK_ALL:u64 = 12;
-    pub fn len() -> u64 {13}
+    pub const TEST:u64 = 13;
+    pub fn len() -> u64 {14}
     pub fn name(idx: u64) -> &'static str {
         match idx {
             Self::RELEASE_STUDIO=> "Release Studio",
@@ -63,6 +81,7 @@ impl BuildTarget {
             Self::CHECK_WINDOWS=> "Check Windows",
             Self::CHECK_LINUX=> "Check Linux",
             Self::CHECK_ALL=> "Check All",
+            Self::TEST=> "Test",
             _=>"Unknown"
         }
     }
@@ -80,7 +99,9 @@ impl BuildTarget {
             Self::CheckMacos=>Self::CHECK_MACOS,
             Self::CheckWindows=>Self::CHECK_WINDOWS,
             Self::CheckLinux=>Self::CHECK_LINUX,
-            Self::CheckAll=>Self::CHECK_ALL
+            Self::CheckAll=>Self::CHECK_ALL,
+            Self::Test=>Self::TEST,
+            Self::Custom{id,..}=>*id,
         }
     }
 }
@@ -117,7 +138,7 @@ impl BuildCmdId{
 #[derive(Clone, Debug)]
 pub enum BuildCmd {
     Stop,
-    Run(BuildProcess, String),
+    Run(BuildProcess, BuildProfile, String),
     HostToStdin(String)
 }
 
@@ -142,7 +163,26 @@ pub struct LogItemLocation{
     pub file_name: String,
     pub start: Position,
     pub length: Length,
-    pub msg: String
+    pub msg: String,
+    pub fix: Option<LogItemFix>
+}
+
+// a replacement suggested by the compiler. positions are in chars, as rustc reports them.
+// original is the code rustc saw under the span
+#[derive(Clone, Debug)]
+pub struct LogItemFixEdit{
+    pub file_name: String,
+    pub start: Position,
+    pub length: Length,
+    pub original: String,
+    pub replacement: String
+}
+
+#[derive(Clone, Debug)]
+pub struct LogItemFix{
+    pub msg: String,
+    pub applicability: Option<String>,
+    pub edits: Vec<LogItemFixEdit>
 }
 
 #[derive(Clone, Debug)]
@@ -151,10 +191,29 @@ pub struct LogItemBare{
     pub line: String,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TestStatus{
+    Running,
+    Ok,
+    Failed,
+    Ignored,
+}
+
+// a test from a libtest run. suite is what cargo said it was running, like "unittests src/lib.rs"
+#[derive(Clone, Debug)]
+pub struct LogItemTest{
+    pub suite: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub stdout: String,
+    pub failure: Option<LogItemLocation>
+}
+
 #[derive(Clone)]
 pub enum LogItem {
     Bare(LogItemBare),
     Location(LogItemLocation),
+    Test(LogItemTest),
     StdinToHost(String),
     AuxChanHostEndpointCreated(crate::makepad_platform::cx_stdin::aux_chan::HostEndpoint),
 }
@@ -4,12 +4,14 @@ use {
         makepad_micro_serde::*,
         build_manager::{
             build_protocol::*,
+            build_config::BUILD_CONFIG_FILE,
             child_process::{
                 ChildStdIn,
                 ChildProcess,
                 ChildStdIO
             },
             rustc_json::*,
+            libtest_json::*,
         },
     },
     std::{
@@ -81,181 +83,124 @@ impl BuildConnection {
         }
     }
     
-    pub fn run(&self, what: BuildProcess, cmd_id: BuildCmdId, http:String) {
+    pub fn run(&self, what: BuildProcess, profile: BuildProfile, cmd_id: BuildCmdId, http:String) {
         let shared = self.shared.clone();
         let msg_sender = self.msg_sender.clone();
         // alright lets run a cargo check and parse its output
         let path = shared.read().unwrap().path.clone();
         
-        let args: Vec<String> = match &what.target {
-            BuildTarget::ReleaseStudio => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "run".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--message-format=json".into(),
-                "--release".into(),
-                "--".into(),
-                "--message-format=json".into(),
-                "--stdin-loop".into(),
-            ],
-            BuildTarget::DebugStudio => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "run".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--message-format=json".into(),
-                "--".into(),
-                "--message-format=json".into(),
-                "--stdin-loop".into(),
-            ],
-            BuildTarget::Release => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "run".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--message-format=json".into(),
-                "--release".into(),
-                "--".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::Debug => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "run".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--message-format=json".into(),
-                "--".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::Profiler => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "instruments".into(),
-                "-t".into(),
-                "time".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-                "--".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::IosSim {org, app} => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "makepad".into(),
-                "ios".into(),
-                format!("--org={org}"),
-                format!("--app={app}"),
-                "run-sim".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::IosDevice {org, app} => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "makepad".into(),
-                "ios".into(),
-                format!("--org={org}"),
-                format!("--app={app}"),
-                "run-device".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::Android => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "makepad".into(),
-                "android".into(),
-                "run".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::WebAssembly => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "makepad".into(),
-                "wasm".into(),
-                "build".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::CheckMacos => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "check".into(),
-                "--target=aarch64-apple-darwin".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::CheckWindows => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "check".into(),
-                "--target=x86_64-pc-windows-msvc".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],
-            BuildTarget::CheckLinux => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "check".into(),
-                "--target=x86_64-unknown-linux-gnu".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ],            
-            BuildTarget::CheckAll => vec![
-                "run".into(),
-                "nightly".into(),
-                "cargo".into(),
-                "makepad".into(),
-                "check".into(),
-                "all".into(),
-                "-p".into(),
-                what.binary.clone(),
-                "--release".into(),
-                "--message-format=json".into(),
-            ]
+        let binary = what.binary.clone();
+        let strs = | args: &[&str] | -> Vec<String> {args.iter().map( | s | s.to_string()).collect()};
+        // the cargo arguments and the arguments for the app after --
+        let (mut args, mut app_args): (Vec<String>, Vec<String>) = match &what.target {
+            BuildTarget::ReleaseStudio => (
+                strs(&["run", "-p", &binary, "--message-format=json", "--release"]),
+                strs(&["--message-format=json", "--stdin-loop"])
+            ),
+            BuildTarget::DebugStudio => (
+                strs(&["run", "-p", &binary, "--message-format=json"]),
+                strs(&["--message-format=json", "--stdin-loop"])
+            ),
+            BuildTarget::Release => (
+                strs(&["run", "-p", &binary, "--message-format=json", "--release"]),
+                strs(&["--message-format=json"])
+            ),
+            BuildTarget::Debug => (
+                strs(&["run", "-p", &binary, "--message-format=json"]),
+                strs(&["--message-format=json"])
+            ),
+            BuildTarget::Profiler => (
+                strs(&["instruments", "-t", "time", "-p", &binary, "--release", "--message-format=json"]),
+                strs(&["--message-format=json"])
+            ),
+            BuildTarget::IosSim {org, app} => (
+                strs(&["makepad", "ios", &format!("--org={org}"), &format!("--app={app}"), "run-sim", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::IosDevice {org, app} => (
+                strs(&["makepad", "ios", &format!("--org={org}"), &format!("--app={app}"), "run-device", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::Android => (
+                strs(&["makepad", "android", "run", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::WebAssembly => (
+                strs(&["makepad", "wasm", "build", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::CheckMacos => (
+                strs(&["check", "--target=aarch64-apple-darwin", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::CheckWindows => (
+                strs(&["check", "--target=x86_64-pc-windows-msvc", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::CheckLinux => (
+                strs(&["check", "--target=x86_64-unknown-linux-gnu", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::CheckAll => (
+                strs(&["makepad", "check", "all", "-p", &binary, "--release", "--message-format=json"]),
+                vec![]
+            ),
+            BuildTarget::Test => (
+                strs(&["test", "-p", &binary, "--message-format=json"]),
+                strs(&["-Z", "unstable-options", "--format=json"])
+            ),
+            BuildTarget::Custom {command, in_studio, ..} => {
+                let mut args = command.clone();
+                args.extend(strs(&["-p", &binary, "--message-format=json"]));
+                let app_args = if *in_studio {
+                    strs(&["--message-format=json", "--stdin-loop"])
+                }
+                else if command[0] == "run" {
+                    strs(&["--message-format=json"])
+                }
+                else {
+                    vec![]
+                };
+                (args, app_args)
+            }
+        };
+        if !profile.features.is_empty() {
+            args.push(format!("--features={}", profile.features.join(",")));
+        }
+        args.extend(profile.args.iter().cloned());
+        app_args.extend(profile.app_args.iter().cloned());
+        if !app_args.is_empty() {
+            args.push("--".into());
+            args.extend(app_args);
+        }
+        let (cmd, args) = if profile.toolchain.is_empty() {
+            ("cargo", args)
+        }
+        else {
+            ("rustup", strs(&["run", &profile.toolchain, "cargo"]).into_iter().chain(args).collect())
         };
         
-        let env = [
+        let mut env = vec![
             ("MAKEPAD_STUDIO_HTTP", http.as_str()),
             ("MAKEPAD", "lines")
         ];
+        if profile.rustc_bootstrap {
+            env.push(("RUSTC_BOOTSTRAP", "1"));
+        }
+        else if what.target == BuildTarget::Test && profile.toolchain != "nightly" {
+            msg_sender.send_bare_msg(cmd_id, LogItemLevel::Warning, format!(
+                "The json test output needs nightly, set rustc_bootstrap = true in {} to use it on other toolchains", BUILD_CONFIG_FILE
+            ));
+        }
+        env.extend(profile.env.iter().map( | (k, v) | (k.as_str(), v.as_str())));
 
-
-        let process = ChildProcess::start("rustup", &args, path, &env).expect("Cannot start process");
+        let process = match ChildProcess::start(cmd, &args, path, &env) {
+            Ok(process) => process,
+            Err(err) => {
+                msg_sender.send_bare_msg(cmd_id, LogItemLevel::Error, format!("Cannot start {} {}: {}", cmd, args.join(" "), err));
+                return
+            }
+        };
         
         shared.write().unwrap().processes.insert(
             what,
@@ -277,6 +222,8 @@ impl BuildConnection {
        // let mut stderr_state = StdErrState::First;
         //let stdin_sender = process.stdin_sender.clone();
         std::thread::spawn(move || {
+            // cargo test runs one test binary after the other, stderr tells us which one
+            let mut test_suite = String::new();
             // lets create a BuildProcess and run it
             while let Ok(line) = process.line_receiver.recv() {
                 
@@ -301,7 +248,10 @@ impl BuildConnection {
                                     _ => ()
                                 }
                             }
-                            Err(_) => { // we should output a log string
+                            Err(_) => if let Ok(event) = LibtestEvent::deserialize_json(&line) {
+                                msg_sender.process_libtest_event(cmd_id, &test_suite, event);
+                            }
+                            else { // we should output a log string
                                 //eprintln!("GOT ERROR {:?}", err);
                                 msg_sender.send_stdin_to_host_msg(cmd_id, line);
                             }
@@ -309,6 +259,12 @@ impl BuildConnection {
                     }
                     ChildStdIO::StdErr(line) => {
                         if line.trim().starts_with("Running ") {
+                           let suite = line.trim().trim_start_matches("Running ");
+                           test_suite = suite.split(" (").next().unwrap_or(suite).to_string();
+                           msg_sender.send_bare_msg(cmd_id, LogItemLevel::Wait, line);
+                        }
+                        else if line.trim().starts_with("Doc-tests ") {
+                           test_suite = line.trim().to_string();
                            msg_sender.send_bare_msg(cmd_id, LogItemLevel::Wait, line);
                         }
                         else if line.trim().starts_with("Compiling ") {
@@ -341,9 +297,9 @@ impl BuildConnection {
     
     pub fn handle_cmd(&self, cmd_wrap: BuildCmdWrap) {
         match cmd_wrap.cmd {
-            BuildCmd::Run(process, http) => {
+            BuildCmd::Run(process, profile, http) => {
                 // lets kill all other 'whats'
-                self.run(process, cmd_wrap.cmd_id, http);
+                self.run(process, profile, cmd_wrap.cmd_id, http);
             }
             BuildCmd::Stop => {
                 // lets kill all other 'whats'
@@ -390,18 +346,60 @@ pub trait MsgSender: Send {
     }
     
 
-    fn send_location_msg(&self, cmd_id: BuildCmdId, level: LogItemLevel, file_name: String, start: Position, length: Length, msg: String) {
+    fn send_location_msg(&self, cmd_id: BuildCmdId, level: LogItemLevel, file_name: String, start: Position, length: Length, msg: String, fix: Option<LogItemFix>) {
         self.send_message(
             cmd_id.wrap_msg(LogItem::Location(LogItemLocation {
                 level,
                 file_name,
                 start,
                 length,
-                msg
+                msg,
+                fix
             }))
         );
     }
     
+    fn process_libtest_event(&self, cmd_id: BuildCmdId, suite: &str, event: LibtestEvent) {
+        match (event._type.as_str(), event.event.as_deref(), event.name) {
+            ("test", Some(status), Some(name)) => {
+                let status = match status {
+                    "started" => TestStatus::Running,
+                    "ok" => TestStatus::Ok,
+                    "ignored" => TestStatus::Ignored,
+                    "failed" | "timeout" => TestStatus::Failed,
+                    _ => return
+                };
+                let stdout = event.stdout.unwrap_or_default();
+                let failure = if status == TestStatus::Failed {
+                    test_failure(&name, &stdout)
+                }
+                else {
+                    None
+                };
+                self.send_message(
+                    cmd_id.wrap_msg(LogItem::Test(LogItemTest {
+                        suite: suite.to_string(),
+                        name,
+                        status,
+                        stdout,
+                        failure
+                    }))
+                );
+            }
+            ("suite", Some(result @ ("ok" | "failed")), _) => {
+                let level = if result == "ok" {LogItemLevel::Log} else {LogItemLevel::Error};
+                self.send_bare_msg(cmd_id, level, format!(
+                    "{}: {} passed, {} failed, {} ignored",
+                    suite,
+                    event.passed.unwrap_or(0),
+                    event.failed.unwrap_or(0),
+                    event.ignored.unwrap_or(0)
+                ));
+            }
+            _ => ()
+        }
+    }
+    
     fn process_compiler_message(&self, cmd_id: BuildCmdId, msg: RustcCompilerMessage) {
         if let Some(msg) = msg.message {
             
@@ -417,7 +415,7 @@ pub trait MsgSender: Send {
                 }
             };
             if let Some(span) = msg.spans.iter().find( | span | span.is_primary) {
-                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.length(), msg.message.clone());
+                self.send_location_msg(cmd_id, level, span.file_name.clone(),span.start(), span.length(), msg.message.clone(), compiler_fix(&msg));
                 /*
                 if let Some(label) = &span.label {
                     self.send_location_msg(cmd_id, level, span.file_name.clone(), range, label.clone());
@@ -443,6 +441,94 @@ pub trait MsgSender: Send {
     }
 }
 
+// finds where a failed test panicked in its captured output, which looks like
+// "thread 'name' panicked at src/lib.rs:10:9:\nmessage" or on older compilers
+// "thread 'name' panicked at 'message', src/lib.rs:10:9", where the message can span lines
+fn test_failure(name: &str, stdout: &str) -> Option<LogItemLocation> {
+    let at = stdout.find("panicked at ")? + "panicked at ".len();
+    let panic = &stdout[at..];
+    let (first, rest) = panic.split_once('\n').unwrap_or((panic, ""));
+    let ((file_name, line, column), msg) = if let Some(quoted) = panic.strip_prefix('\'') {
+        let mut msg = Vec::new();
+        let mut location = None;
+        for line in quoted.lines() {
+            if let Some((text, found)) = line.rsplit_once("', ").and_then( | (text, at) | Some((text, parse_location(at) ?))) {
+                msg.push(text);
+                location = Some(found);
+                break;
+            }
+            msg.push(line);
+        }
+        (location?, msg.join("\n"))
+    }
+    else {
+        let msg: Vec<&str> = rest.lines().take_while( | line | !line.starts_with("note: ") && !line.starts_with("stack backtrace:")).collect();
+        (parse_location(first.strip_suffix(':').unwrap_or(first)) ?, msg.join("\n"))
+    };
+    Some(LogItemLocation {
+        level: LogItemLevel::Error,
+        file_name,
+        start: Position {
+            line_index: line.max(1) - 1,
+            byte_index: column.max(1) - 1
+        },
+        length: Length::zero(),
+        msg: format!("test {} failed: {}", name, msg.trim()),
+        fix: None
+    })
+}
+
+// file:line:column
+fn parse_location(location: &str) -> Option<(String, usize, usize)> {
+    let mut parts = location.rsplitn(3, ':');
+    let column: usize = parts.next()?.parse().ok()?;
+    let line: usize = parts.next()?.parse().ok()?;
+    let file_name = parts.next()?;
+    if file_name.is_empty() {
+        return None
+    }
+    Some((file_name.to_string(), line, column))
+}
+
+// the first child of a compiler message that suggests replacements becomes its quick fix.
+// spans that overlap an earlier one are alternatives for the same code, only the first is kept
+fn compiler_fix(msg: &RustcMessage) -> Option<LogItemFix> {
+    for child in &msg.children {
+        let mut fix = LogItemFix {
+            msg: child.message.clone(),
+            applicability: None,
+            edits: Vec::new()
+        };
+        for span in &child.spans {
+            let Some(replacement) = &span.suggested_replacement else {
+                continue
+            };
+            // these contain things like /* value */ that would not compile
+            if span.suggestion_applicability.as_deref() == Some("HasPlaceholders") {
+                continue
+            }
+            let (start, end) = (span.start(), span.end());
+            if fix.edits.iter().any( | edit | edit.file_name == span.file_name && start <= edit.start + edit.length && edit.start <= end) {
+                continue
+            }
+            if fix.applicability.is_none() {
+                fix.applicability = span.suggestion_applicability.clone();
+            }
+            fix.edits.push(LogItemFixEdit {
+                file_name: span.file_name.clone(),
+                start,
+                length: span.length(),
+                original: span.original_text(),
+                replacement: replacement.clone()
+            });
+        }
+        if !fix.edits.is_empty() {
+            return Some(fix)
+        }
+    }
+    None
+}
+
 impl<F: Clone + Fn(LogItemWrap) + Send + 'static> MsgSender for F {
     fn box_clone(&self) -> Box<dyn MsgSender> {
         Box::new(self.clone())
@@ -466,4 +552,44 @@ impl fmt::Debug for dyn MsgSender {
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-struct ConnectionId(usize);
\ No newline at end of file
+struct ConnectionId(usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn failure(stdout: &str) -> Option<(String, usize, usize, String)> {
+        test_failure("tests::it", stdout).map( | item | (item.file_name, item.start.line_index, item.start.byte_index, item.msg))
+    }
+    
+    #[test]
+    fn test_failures() {
+        // assert_eq keeps its left and right in the message
+        let stdout = "\nthread 'tests::it' panicked at src/lib.rs:12:9:\nassertion `left == right` failed\n  left: 1\n right: 2\nnote: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+        assert_eq!(failure(stdout), Some((
+            "src/lib.rs".to_string(), 11, 8,
+            "test tests::it failed: assertion `left == right` failed\n  left: 1\n right: 2".to_string()
+        )));
+        // a plain panic, with output printed by the test before it
+        let stdout = "some output\nthread 'tests::it' panicked at C:\\code\\src\\lib.rs:3:5:\nnot yet\nstack backtrace:\n   0: rust_begin_unwind\n";
+        assert_eq!(failure(stdout), Some(("C:\\code\\src\\lib.rs".to_string(), 2, 4, "test tests::it failed: not yet".to_string())));
+        // older compilers quote the message before the location, across lines for assert_eq
+        let stdout = "thread 'tests::it' panicked at 'oops', src/lib.rs:7:1\nnote: run with `RUST_BACKTRACE=1`\n";
+        assert_eq!(failure(stdout), Some(("src/lib.rs".to_string(), 6, 0, "test tests::it failed: oops".to_string())));
+        let stdout = "thread 'tests::it' panicked at 'assertion failed: `(left == right)`\n  left: `\"a, b\"`,\n right: `\"c\"`', src/lib.rs:20:5\n";
+        assert_eq!(failure(stdout), Some((
+            "src/lib.rs".to_string(), 19, 4,
+            "test tests::it failed: assertion failed: `(left == right)`\n  left: `\"a, b\"`,\n right: `\"c\"`".to_string()
+        )));
+    }
+    
+    #[test]
+    fn test_failures_without_location() {
+        // tests returning an error, timing out or panicking somewhere unknown have nothing to point at
+        assert_eq!(failure("Error: \"bad input\"\n"), None);
+        assert_eq!(failure(""), None);
+        assert_eq!(failure("thread 'tests::it' panicked at 'oops'\n"), None);
+        assert_eq!(failure("thread 'tests::it' panicked at <unknown>:\noops\n"), None);
+        assert_eq!(failure("thread 'tests::it' panicked at src/lib.rs:x:1:\noops\n"), None);
+    }
+}
@@ -0,0 +1,26 @@
+#![allow(dead_code)]
+use crate::makepad_micro_serde::*;
+
+// libtest output with -Z unstable-options --format=json. every line is one event, with type
+// "suite", "test" or "bench" and fields depending on the event
+#[derive(Clone, DeJson, Debug, Default)]
+pub struct LibtestEvent {
+    pub _type: String,
+    pub event: Option<String>,
+    pub name: Option<String>,
+    pub test_count: Option<u64>,
+    pub shuffle_seed: Option<u64>,
+    pub passed: Option<u64>,
+    pub failed: Option<u64>,
+    pub ignored: Option<u64>,
+    pub measured: Option<u64>,
+    pub filtered_out: Option<u64>,
+    pub allowed_fail: Option<u64>,
+    pub exec_time: Option<f64>,
+    pub stdout: Option<String>,
+    pub message: Option<String>,
+    pub reason: Option<String>,
+    pub median: Option<f64>,
+    pub deviation: Option<f64>,
+    pub mib_per_second: Option<f64>
+}
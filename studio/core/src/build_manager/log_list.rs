@@ -19,6 +19,7 @@ live_design!{
     import makepad_draw::shader::std::*;
     import makepad_widgets::base::*;
     import makepad_widgets::theme_desktop_dark::*;
+    import makepad_widgets::theme::*;
     
     Icon = <View> {
         show_bg: true,
@@ -192,6 +193,13 @@ live_design!{
             location = <LinkLabel> {margin: 0, text: ""}
             body = <Label> {width: Fill, margin: {left: 5}, padding: 0, draw_text: {wrap: Word}}
         }
+        LocationFix = <LogItem> {
+            icon = <LogIcon> {},
+            binary = <Label> {draw_text: {color: #5}, width: Fit, margin: {right: 4}, padding: 0, draw_text: {wrap: Word}}
+            location = <LinkLabel> {margin: 0, text: ""}
+            fix = <LinkLabel> {margin: {left: 5}, text: ""}
+            body = <Label> {width: Fill, margin: {left: 5}, padding: 0, draw_text: {wrap: Word}}
+        }
         Bare = <LogItem> {
             icon = <LogIcon> {},
             binary = <Label> {draw_text: {color: #5}, width: Fit, margin: {right: 4}, padding: 0, draw_text: {wrap: Word}}
@@ -207,6 +215,7 @@ live_design!{
 }
 pub enum LogListAction {
     JumpToError{file_name:String, start:Position, length:Length},
+    ApplyFix(LogItemFix),
     None
 }
 
@@ -247,7 +256,8 @@ impl BuildManager {
                         
                     }
                     LogItem::Location(msg) => {
-                        let item = list.item(cx, item_id, live_id!(Location)).unwrap().as_view();
+                        let template = if msg.fix.is_some() {live_id!(LocationFix)} else {live_id!(Location)};
+                        let item = list.item(cx, item_id, template).unwrap().as_view();
                         item.apply_over(cx, live!{
                             binary = {text: (&binary)}
                             icon = {active_page: (map_level_to_icon(msg.level))},
@@ -255,6 +265,11 @@ impl BuildManager {
                             location = {text: (format!("{}: {}:{}", msg.file_name, msg.start.line_index + 1, msg.start.byte_index + 1))}
                             draw_bg: {is_even: (if is_even {1.0} else {0.0})}
                         });
+                        if let Some(fix) = &msg.fix {
+                            item.apply_over(cx, live!{
+                                fix = {text: (format!("[fix: {}]", fix.msg))}
+                            });
+                        }
                         item.draw_widget_all(cx);
                         
                     }
@@ -291,6 +306,14 @@ impl BuildManager {
                 }
             }
         }
+        // a fix is applied once, after that the log item goes back to a plain location
+        if item.link_label(id!(fix)).pressed(actions) {
+            if let Some((_build_id, LogItem::Location(msg))) = self.log.get_mut(item_id as usize) {
+                if let Some(fix) = msg.fix.take() {
+                    ret.push(LogListAction::ApplyFix(fix))
+                }
+            }
+        }
         ret    
     }
 }
\ No newline at end of file
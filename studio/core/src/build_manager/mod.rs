@@ -1,9 +1,13 @@
 pub mod build_client;
+pub mod build_config;
 pub mod build_protocol;
 pub mod build_server;
 pub mod build_manager;
 pub mod child_process;
 pub mod rustc_json;
+pub mod libtest_json;
 pub mod log_list;
 pub mod run_list;
+pub mod test_tree;
+pub mod widget_inspector;
 pub mod run_view;
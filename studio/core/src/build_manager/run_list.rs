@@ -3,6 +3,7 @@ use {
         makepad_platform::*,
         build_manager::{
             build_manager::*,
+            build_config::BuildConfig,
             build_protocol::*,
             build_client::BuildClient
         },
@@ -15,6 +16,7 @@ live_design!{
     import makepad_draw::shader::std::*;
     import makepad_widgets::base::*;
     import makepad_widgets::theme_desktop_dark::*;
+    import makepad_widgets::theme::*;
     
     BuildItem = <RectView> {
         height: Fit,
@@ -156,7 +158,10 @@ impl BuildManager {
             counter += 1;
             
             if binary.open>0.001 {
-                for i in 0..BuildTarget::len() {
+                for i in 0..self.config.target_count() {
+                    if !self.config.target_applies(i, &binary.name) {
+                        continue
+                    }
                     let is_even = counter & 1 == 0;
                     let item_id = LiveId::from_str(&binary.name).bytes_append(&i.to_be_bytes());
                     let item = list.item(cx, item_id, live_id!(Target)).unwrap().as_view();
@@ -164,7 +169,7 @@ impl BuildManager {
                     item.apply_over(cx, live!{
                         height: (height)
                         draw_bg: {is_even: (if is_even {1.0} else {0.0})}
-                        check = {text: (BuildTarget::name(i))}
+                        check = {text: (self.config.target_name(i))}
                     });
                     item.check_box(id!(check)).set_selected(cx, self.active.item_id_active(item_id));
                     item.draw_widget_all(cx);
@@ -199,26 +204,31 @@ impl BuildManager {
                 }
                 if let Some(change) = item.check_box(id!(check)).changed(actions) {
                     run_list.redraw(cx);
-                    for i in 0..if change{1}else{BuildTarget::len()} {
+                    // checking a binary starts its first target, unchecking it stops all of them
+                    for i in 0..self.config.target_count() {
+                        if !self.config.target_applies(i, &binary_name) {
+                            continue
+                        }
                         let item_id = LiveId::from_str(&binary.name).bytes_append(&i.to_be_bytes());
                         if change{
-                            Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], item_id, &binary_name, i, LiveId::unique(), &mut out);
+                            Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], &self.config, item_id, &binary_name, i, LiveId::unique(), &mut out);
+                            break
                         }
                         else{
                             Self::stop_active_build(&mut self.active, &self.clients[0], &binary_name, i,  &mut out);
                         } 
-                        self.log.clear();
                     }
+                    self.log.clear();
                 };
             }
             else{
-                for i in 0..BuildTarget::len() {
+                for i in 0..self.config.target_count() {
                     let id = LiveId::from_str(&binary.name).bytes_append(&i.to_be_bytes());
                     if item_id == id{
                         if let Some(change) = item.check_box(id!(check)).changed(actions) {
                             run_list.redraw(cx);
                             if change{
-                                Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], item_id, &binary_name, i, LiveId::unique(), &mut out);
+                                Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], &self.config, item_id, &binary_name, i, LiveId::unique(), &mut out);
                             }
                             else{
                                 Self::stop_active_build( &mut self.active, &self.clients[0], &binary_name, i,  &mut out);
@@ -234,36 +244,14 @@ impl BuildManager {
     
     pub fn run_app(&mut self, run_view_id:LiveId, binary_name:&str){
         let mut out = Vec::new();
-        Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], LiveId(0), &binary_name, 0, run_view_id, &mut out);
-    }
-    
-    pub fn target_id_to_target(tgt:u64)->BuildTarget{
-        match tgt {
-            BuildTarget::RELEASE => BuildTarget::Release,
-            BuildTarget::DEBUG => BuildTarget::Debug,
-            BuildTarget::RELEASE_STUDIO => BuildTarget::ReleaseStudio,
-            BuildTarget::DEBUG_STUDIO => BuildTarget::DebugStudio,
-            BuildTarget::PROFILER => BuildTarget::Profiler,
-            BuildTarget::IOS_SIM => BuildTarget::IosSim {
-                org: "makepad".to_string(),
-                app: "example1".to_string()
-            },
-            BuildTarget::IOS_DEVICE => BuildTarget::IosDevice {
-                org: "makepad".to_string(),
-                app: "example1".to_string()
-            },
-            BuildTarget::ANDROID => BuildTarget::Android,
-            BuildTarget::WEBASSEMBLY => BuildTarget::WebAssembly,
-            BuildTarget::CHECK_MACOS => BuildTarget::CheckMacos,
-            BuildTarget::CHECK_WINDOWS => BuildTarget::CheckWindows,
-            BuildTarget::CHECK_LINUX => BuildTarget::CheckLinux,
-            BuildTarget::CHECK_ALL => BuildTarget::CheckAll,
-            _ => panic!()
-        }
+        Self::start_active_build(self.studio_http.clone(), &mut self.active, &self.clients[0], &self.config, LiveId(0), &binary_name, BuildTarget::RELEASE_STUDIO, run_view_id, &mut out);
     }
     
-    pub fn start_active_build(studio_http:String, active:&mut ActiveBuilds, client:&BuildClient, item_id: LiveId, binary: &str, tgt: u64, run_view_id: LiveId, actions:&mut Vec<RunListAction>) {
-        let target = Self::target_id_to_target(tgt);
+    pub fn start_active_build(studio_http:String, active:&mut ActiveBuilds, client:&BuildClient, config:&BuildConfig, item_id: LiveId, binary: &str, tgt: u64, run_view_id: LiveId, actions:&mut Vec<RunListAction>) {
+        let Some(target) = config.target(tgt) else {
+            return
+        };
+        let profile = config.profile(tgt).clone();
         let process = BuildProcess {
             binary: binary.to_string(),
             target
@@ -277,8 +265,9 @@ impl BuildManager {
                 item_id,
                 log_index: format!("[{}]", index),
                 process: process.clone(),
+                profile: profile.clone(),
                 run_view_id,
-                cmd_id: Some(client.send_cmd(BuildCmd::Run(process.clone(), studio_http))),
+                cmd_id: Some(client.send_cmd(BuildCmd::Run(process.clone(), profile, studio_http))),
                 swapchain: None,
                 last_swapchain_with_completed_draws: None,
                 aux_chan_host_endpoint: None,
@@ -292,17 +281,13 @@ impl BuildManager {
     
     
     pub fn stop_active_build(active:&mut ActiveBuilds, client:&BuildClient, binary: &str, tgt: u64, actions:&mut Vec<RunListAction>) {
-        let target = Self::target_id_to_target(tgt);
-        let process = BuildProcess {
-            binary: binary.to_string(),
-            target
-        };
-        let build_id = process.as_id().into();
+        // same id as BuildProcess::as_id, the target might be gone from the config by now
+        let build_id = LiveId::from_str(binary).bytes_append(&tgt.to_be_bytes()).into();
        if let Some(build) = active.builds.remove(&build_id) {
             if let Some(cmd_id) = build.cmd_id {
                 client.send_cmd_with_id(cmd_id, BuildCmd::Stop);
             }
-            if process.target.runs_in_studio(){
+            if build.process.target.runs_in_studio(){
                 actions.push(RunListAction::Destroy(build.run_view_id))
             }
         }
@@ -30,6 +30,15 @@ live_design!{
                 return mix(fb, #4, self.recompiling * 0.4);
             }
         }
+        draw_highlight: {
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                sdf.rect(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0);
+                sdf.fill_keep(#f0f2);
+                sdf.stroke(#f0f, 1.0);
+                return sdf.result;
+            }
+        }
         animator: {
             started = {
                 default: off,
@@ -64,6 +73,7 @@ pub struct RunView {
     #[rust] draw_state: DrawStateWrap<Walk>,
     #[animator] animator: Animator,
     #[live] draw_app: DrawQuad,
+    #[live] draw_highlight: DrawQuad,
     #[live] frame_delta: f64,
     #[rust] last_size: DVec2,
     #[rust] tick: NextFrame,
@@ -72,6 +82,8 @@ pub struct RunView {
     #[rust] time: f64,
     #[rust] frame: u64,
     #[rust] started: bool,
+    #[rust] widget_tree_dirty: bool,
+    #[rust] widget_tree_frame: u64,
 }
 
 
@@ -105,6 +117,13 @@ impl RunView {
             frame: 0,
             time: time
         });
+        // the widget inspector follows what the app draws, but at most a few times a second and
+        // only while it shows
+        if manager.inspector.visible && self.widget_tree_dirty && self.frame >= self.widget_tree_frame + 30 {
+            self.widget_tree_dirty = false;
+            self.widget_tree_frame = self.frame;
+            manager.send_host_to_stdin(run_view_id, HostToStdin::RequestWidgetTree);
+        }
         if self.redraw_countdown>0 {
             self.redraw_countdown -= 1;
             self.redraw(cx);
@@ -137,6 +156,20 @@ impl RunView {
             Hit::KeyUp(e) => {
                 manager.send_host_to_stdin(run_view_id, HostToStdin::KeyUp(e));
             }
+            Hit::TextInput(e) => {
+                manager.send_host_to_stdin(run_view_id, HostToStdin::TextInput(StdinTextInput {
+                    input: e.input,
+                    replace_last: e.replace_last,
+                    was_paste: e.was_paste,
+                }));
+            }
+            // the app answers with a SetClipboard message once it has handled these
+            Hit::TextCopy(_) => {
+                manager.send_host_to_stdin(run_view_id, HostToStdin::TextCopy);
+            }
+            Hit::TextCut(_) => {
+                manager.send_host_to_stdin(run_view_id, HostToStdin::TextCut);
+            }
             _ => ()
         }
         let rect = self.draw_app.area().get_rect(cx);
@@ -188,6 +221,17 @@ impl RunView {
             StdinToHost::SetCursor(cursor) => {
                 cx.set_cursor(*cursor)
             }
+            StdinToHost::SetClipboard(text) => {
+                cx.copy_to_clipboard(text)
+            }
+            StdinToHost::ShowTextIME{x, y} => {
+                cx.show_text_ime(self.draw_app.area(), dvec2(*x, *y))
+            }
+            StdinToHost::HideTextIME => {
+                cx.hide_text_ime()
+            }
+            // the build manager keeps these for the widget inspector
+            StdinToHost::WidgetTree(_) => {}
             StdinToHost::ReadyToStart => {
                 self.animator_play(cx, id!(recompiling.off));
                 // cause a resize event to fire
@@ -219,6 +263,7 @@ impl RunView {
                             self.animator_play(cx, id!(started.on));
                         }
                         self.redraw_countdown = 20;
+                        self.widget_tree_dirty = true;
                        
                         Some(())
                     };
@@ -354,6 +399,12 @@ impl RunView {
             }
         }
         self.draw_app.draw_abs(cx, rect);
+        if let Some(node) = manager.inspector.highlight(run_view_id) {
+            self.draw_highlight.draw_abs(cx, Rect {
+                pos: rect.pos + dvec2(node.x, node.y),
+                size: dvec2(node.width, node.height)
+            });
+        }
     }
 }
 
@@ -60,6 +60,16 @@ impl RustcSpan {
     pub fn length(&self) -> Length {
         self.end() - self.start()
     }
+    
+    // the code under the span, taken from the lines rustc quotes with it
+    pub fn original_text(&self) -> String {
+        let lines: Vec<String> = self.text.iter().map( | text | {
+            let start = text.highlight_start.max(1) - 1;
+            let end = text.highlight_end.max(1) - 1;
+            text.text.chars().skip(start).take(end.saturating_sub(start)).collect()
+        }).collect();
+        lines.join("\n")
+    }
 }
 
 #[derive(Clone, DeJson, Debug, Default)]
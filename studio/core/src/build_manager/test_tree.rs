@@ -0,0 +1,101 @@
+use {
+    crate::{
+        makepad_platform::*,
+        build_manager::{
+            build_manager::*,
+            build_protocol::*,
+        },
+        makepad_widgets::*,
+        makepad_widgets::file_tree::*,
+        makepad_code_editor::text::{Position, Length},
+    },
+};
+
+pub enum TestTreeAction {
+    JumpToFailure{file_name:String, start:Position, length:Length},
+    None
+}
+
+// tests are shown per suite, with a folder for every module in their path
+fn test_node_id(suite: &str, path: &str) -> FileNodeId {
+    FileNodeId(LiveId::from_str(suite).str_append("::").str_append(path))
+}
+
+fn status_name(status: TestStatus) -> &'static str {
+    match status {
+        TestStatus::Running => "running",
+        TestStatus::Ok => "ok",
+        TestStatus::Failed => "FAILED",
+        TestStatus::Ignored => "ignored",
+    }
+}
+
+fn folder_name(name: &str, tests: &[&LogItemTest]) -> String {
+    let count = | status | tests.iter().filter( | test | test.status == status).count();
+    let failed = count(TestStatus::Failed);
+    if failed > 0 {
+        format!("{} [{} FAILED]", name, failed)
+    }
+    else {
+        format!("{} [{}/{}]", name, count(TestStatus::Ok), tests.len())
+    }
+}
+
+fn draw_test_nodes(cx: &mut Cx2d, file_tree: &mut FileTree, tests: &[&LogItemTest], depth: usize) {
+    let mut rest = tests;
+    while let Some(first) = rest.first() {
+        let segments: Vec<&str> = first.name.split("::").collect();
+        if segments.len() == depth + 1 {
+            let name = format!("{} [{}]", segments[depth], status_name(first.status));
+            file_tree.file(cx, test_node_id(&first.suite, &first.name), &name);
+            rest = &rest[1..];
+            continue
+        }
+        let len = rest.iter().take_while( | test | {
+            let mut test_segments = test.name.split("::");
+            test_segments.nth(depth) == Some(segments[depth]) && test_segments.next().is_some()
+        }).count();
+        let (group, next) = rest.split_at(len);
+        let node_id = test_node_id(&first.suite, &segments[..=depth].join("::"));
+        if file_tree.begin_folder(cx, node_id, &folder_name(segments[depth], group)).is_ok() {
+            draw_test_nodes(cx, file_tree, group, depth + 1);
+            file_tree.end_folder();
+        }
+        rest = next;
+    }
+}
+
+impl BuildManager {
+
+    pub fn draw_test_tree(&self, cx: &mut Cx2d, file_tree: &mut FileTree) {
+        let mut tests: Vec<&LogItemTest> = self.tests.iter().collect();
+        tests.sort_by( | a, b | a.suite.cmp(&b.suite).then_with( || a.name.split("::").cmp(b.name.split("::"))));
+        let mut rest = &tests[..];
+        while let Some(first) = rest.first() {
+            let len = rest.iter().take_while( | test | test.suite == first.suite).count();
+            let (suite, next) = rest.split_at(len);
+            let node_id = test_node_id(&first.suite, "");
+            file_tree.set_folder_is_open(cx, node_id, true, Animate::No);
+            if file_tree.begin_folder(cx, node_id, &folder_name(&first.suite, suite)).is_ok() {
+                draw_test_nodes(cx, file_tree, suite, 0);
+                file_tree.end_folder();
+            }
+            rest = next;
+        }
+    }
+
+    pub fn handle_test_tree(&self, test_tree: &FileTreeRef, actions: &WidgetActions) -> Vec<TestTreeAction> {
+        let mut ret = Vec::new();
+        if let Some(node_id) = test_tree.file_clicked(actions) {
+            let test = self.tests.iter().find( | test | test_node_id(&test.suite, &test.name) == node_id);
+            if let Some(failure) = test.and_then( | test | test.failure.as_ref()) {
+                ret.push(TestTreeAction::JumpToFailure {
+                    file_name: failure.file_name.clone(),
+                    start: failure.start,
+                    length: failure.length
+                })
+            }
+        }
+        ret
+    }
+}
@@ -0,0 +1,139 @@
+use {
+    std::collections::HashSet,
+    crate::{
+        makepad_platform::*,
+        build_manager::build_manager::*,
+        makepad_widgets::*,
+        makepad_widgets::file_tree::*,
+        makepad_code_editor::text::{Position, Length},
+    },
+};
+
+pub enum WidgetInspectorAction {
+    JumpToSource{file_name:String, start:Position, length:Length},
+    // the hovered widget changed, the run view has to redraw its highlight
+    Highlight(LiveId),
+    None
+}
+
+// the widget tree last sent by an app running in studio, in depth first order
+#[derive(Default)]
+pub struct WidgetInspector {
+    pub run_view_id: LiveId,
+    pub nodes: Vec<WidgetTreeNode>,
+    pub hovered: Option<usize>,
+    // whether the inspector tab is showing, the apps are only asked for their tree then
+    pub visible: bool,
+    node_ids: Vec<FileNodeId>,
+    opened: HashSet<FileNodeId>,
+}
+
+impl WidgetInspector {
+    pub fn set_nodes(&mut self, run_view_id: LiveId, nodes: Vec<WidgetTreeNode>) -> bool {
+        if self.run_view_id == run_view_id && self.nodes == nodes {
+            return false
+        }
+        if self.run_view_id != run_view_id {
+            self.opened.clear();
+        }
+        self.run_view_id = run_view_id;
+        self.hovered = None;
+        // node ids follow the path through the tree so open folders stay open when it changes
+        let mut stack: Vec<(LiveId, u64)> = Vec::new();
+        self.node_ids = nodes.iter().map( | node | {
+            stack.truncate(node.depth);
+            let parent = stack.last().map( | (id, _) | *id).unwrap_or(run_view_id);
+            let index = if let Some((_, children)) = stack.last_mut() {
+                *children += 1;
+                *children
+            }
+            else {
+                0
+            };
+            let id = LiveId::from_num(parent.0, index).str_append(&node.id);
+            stack.push((id, 0));
+            FileNodeId(id)
+        }).collect();
+        self.nodes = nodes;
+        true
+    }
+
+    pub fn highlight(&self, run_view_id: LiveId) -> Option<&WidgetTreeNode> {
+        if self.run_view_id != run_view_id {
+            return None
+        }
+        self.hovered.and_then( | index | self.nodes.get(index))
+    }
+
+    fn node_index(&self, node_id: FileNodeId) -> Option<usize> {
+        self.node_ids.iter().position( | id | *id == node_id)
+    }
+
+    fn node_name(node: &WidgetTreeNode) -> String {
+        let mut name = format!("{} <{}>", node.id, node.type_name);
+        if let Some(line) = node.text.lines().next().filter( | line | !line.is_empty()) {
+            let text: String = line.chars().take(24).collect();
+            name.push_str(&format!(" \"{}\"", text));
+        }
+        if !node.visible {
+            name.push_str(" (hidden)");
+        }
+        name
+    }
+
+    fn draw_nodes(&mut self, cx: &mut Cx2d, file_tree: &mut FileTree, range: std::ops::Range<usize>) {
+        let mut index = range.start;
+        while index < range.end {
+            let depth = self.nodes[index].depth;
+            let end = (index + 1..range.end).find( | i | self.nodes[*i].depth <= depth).unwrap_or(range.end);
+            let node_id = self.node_ids[index];
+            let name = Self::node_name(&self.nodes[index]);
+            if end == index + 1 {
+                file_tree.file(cx, node_id, &name);
+            }
+            else {
+                // only the first levels start out open, deep trees get very long
+                if depth < 2 && self.opened.insert(node_id) {
+                    file_tree.set_folder_is_open(cx, node_id, true, Animate::No);
+                }
+                if file_tree.begin_folder(cx, node_id, &name).is_ok() {
+                    self.draw_nodes(cx, file_tree, index + 1..end);
+                    file_tree.end_folder();
+                }
+            }
+            index = end;
+        }
+    }
+}
+
+impl BuildManager {
+
+    pub fn draw_widget_inspector(&mut self, cx: &mut Cx2d, file_tree: &mut FileTree) {
+        let len = self.inspector.nodes.len();
+        self.inspector.draw_nodes(cx, file_tree, 0..len);
+    }
+
+    pub fn handle_widget_inspector(&mut self, inspector: &FileTreeRef, actions: &WidgetActions) -> Vec<WidgetInspectorAction> {
+        let mut ret = Vec::new();
+        let inspector_state = &mut self.inspector;
+        if let Some(node_id) = inspector.node_hover_in(actions) {
+            inspector_state.hovered = inspector_state.node_index(node_id);
+            ret.push(WidgetInspectorAction::Highlight(inspector_state.run_view_id));
+        }
+        if inspector.node_hover_out(actions).is_some() {
+            inspector_state.hovered = None;
+            ret.push(WidgetInspectorAction::Highlight(inspector_state.run_view_id));
+        }
+        let clicked = inspector.file_clicked(actions).or_else( || inspector.folder_clicked(actions));
+        if let Some(node) = clicked.and_then( | node_id | inspector_state.node_index(node_id)).map( | index | &inspector_state.nodes[index]) {
+            if !node.file_name.is_empty() {
+                ret.push(WidgetInspectorAction::JumpToSource {
+                    file_name: node.file_name.clone(),
+                    start: Position {line_index: node.line as usize, byte_index: node.column as usize},
+                    length: Length {line_count: 0, byte_count: node.id.len()}
+                })
+            }
+        }
+        ret
+    }
+}
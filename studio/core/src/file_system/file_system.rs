@@ -2,20 +2,35 @@ use {
     std::collections::{HashMap, hash_map},
     std::path::Path,
     crate::{
-        makepad_code_editor::{Document, decoration::{Decoration, DecorationSet}, Session},
+        makepad_code_editor::{
+            Document,
+            decoration::{Decoration, DecorationSet, DecorationType},
+            Session,
+            TokenizerRegistry,
+            text::{Change, Length, Position, Text},
+        },
         makepad_platform::*,
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_draw::*,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
         file_system::FileClient,
+        lsp::{
+            lsp_client::{LspClient, LspEvent},
+            lsp_protocol::{LspDiagnostic, LspSeverity},
+        },
         makepad_file_protocol::{
             FileRequest,
             FileError,
             FileResponse,
             FileClientAction,
             FileNodeData,
+            FileNotification,
             FileTreeData,
+            TextChange,
+            TextDelta,
+            TextLength,
+            TextPosition,
         },
     },
 };
@@ -28,7 +43,36 @@ pub struct FileSystem {
     pub path_to_file_node_id: HashMap<String, FileNodeId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, FileNodeId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<FileNodeId, OpenDoc>
+    pub open_documents: HashMap<FileNodeId, OpenDoc>,
+    pub collab_files: HashMap<FileNodeId, CollabFile>,
+    // edits for files that are still opening, with the text they were made against
+    pub opening_edits: HashMap<FileNodeId, Vec<(String, Vec<Change>)>>,
+    pub tokenizers: TokenizerRegistry,
+    pub lsp: Option<LspClient>,
+}
+
+// decorations from a build and from the language server are replaced separately
+pub const BUILD_DECORATION_ID: usize = 0;
+pub const LSP_DECORATION_ID: usize = 1;
+
+// The client side of the collaboration session for an open file. Only one delta is in flight at a
+// time, local changes made while waiting for it to be acknowledged are collected in pending.
+#[derive(Default)]
+pub struct CollabFile {
+    pub revision: u64,
+    sent: Option<TextDelta>,
+    pending: TextDelta,
+    // notifications for revisions after our in flight delta, handled once it is acknowledged
+    queued: Vec<(u64, TextDelta)>,
+    save_requested: bool,
+    // the server rejected a delta and the file is being opened again to get its text
+    resync: bool,
+}
+
+impl CollabFile {
+    fn is_idle(&self) -> bool {
+        self.sent.is_none() && self.pending.is_empty() && !self.resync
+    }
 }
 
 pub enum OpenDoc {
@@ -59,7 +103,10 @@ pub struct FileEdge {
 pub enum FileSystemAction {
     TreeLoaded,
     RecompileNeeded,
-    LiveReloadNeeded(LiveFileChange)
+    LiveReloadNeeded(LiveFileChange),
+    // answers to hover, definition and completion requests. diagnostics are turned into
+    // decorations by the file system itself
+    Lsp(LspEvent),
 }
 
 impl FileSystem {
@@ -68,13 +115,49 @@ impl FileSystem {
         self.reload_file_tree();
     }
     
+    pub fn start_lsp(&mut self, root_path: &Path, command: &[String], extensions: &[String]) {
+        match LspClient::start(command, root_path, extensions) {
+            Ok(lsp) => self.lsp = Some(lsp),
+            Err(err) => log!("Cannot start language server {}: {}", command.join(" "), err)
+        }
+    }
+    
     pub fn reload_file_tree(&mut self) {
         self.file_client.send_request(FileRequest::LoadFileTree {with_data: false});
     }
     
     pub fn remove_tab(&mut self, tab_id: LiveId) {
-        self.tab_id_to_file_node_id.remove(&tab_id);
+        let file_id = self.tab_id_to_file_node_id.remove(&tab_id);
         self.tab_id_to_session.remove(&tab_id);
+        if let Some(file_id) = file_id {
+            self.send_local_changes(file_id);
+            self.close_file_if_unused(file_id);
+        }
+    }
+    
+    // leaves the collaboration session of a file once no tab shows it and our changes are in
+    fn close_file_if_unused(&mut self, file_id: FileNodeId) {
+        if self.tab_id_to_file_node_id.values().any( | id | *id == file_id) {
+            return
+        }
+        match self.collab_files.get(&file_id) {
+            Some(collab) if collab.is_idle() => (),
+            _ => return
+        }
+        self.collab_files.remove(&file_id);
+        // the decorations stay around for when the file is opened again
+        if let Some(OpenDoc::Document(doc)) = self.open_documents.remove(&file_id) {
+            let mut dec = DecorationSet::new();
+            for decoration in doc.decorations().iter() {
+                dec.add_decoration(*decoration);
+            }
+            self.open_documents.insert(file_id, OpenDoc::Decorations(dec));
+        }
+        let path = self.file_node_path(file_id);
+        if let Some(lsp) = &mut self.lsp {
+            lsp.did_close(&path);
+        }
+        self.file_client.send_request(FileRequest::CloseFile(path));
     }
     
     pub fn path_to_file_node_id(&self, path: &str) -> Option<FileNodeId> {
@@ -111,6 +194,24 @@ impl FileSystem {
     
     pub fn handle_event_with(&mut self, cx: &mut Cx, event: &Event, ui: &WidgetRef, dispatch_action: &mut dyn FnMut(&mut Cx, FileSystemAction)) {
         
+        let lsp_events = match &mut self.lsp {
+            Some(lsp) => lsp.handle_event(cx, event),
+            None => Vec::new()
+        };
+        for lsp_event in lsp_events {
+            match lsp_event {
+                LspEvent::Diagnostics {path, diagnostics} => {
+                    if let Some(file_id) = self.path_to_file_node_id(&path) {
+                        self.set_lsp_diagnostics(file_id, &diagnostics);
+                        self.redraw_view_by_file_id(cx, file_id, &ui.dock(id!(dock)));
+                    }
+                }
+                LspEvent::Stopped => {
+                    log!("Language server stopped");
+                }
+                lsp_event => dispatch_action(cx, FileSystemAction::Lsp(lsp_event))
+            }
+        }
         
         for action in self.file_client.handle_event(cx, event) {
             match action {
@@ -123,7 +224,7 @@ impl FileSystem {
                     }
                     FileResponse::OpenFile(result) => {
                         match result {
-                            Ok((_unix_path, data, id)) => {
+                            Ok((_unix_path, data, id, revision)) => {
                                 let file_id = FileNodeId(LiveId(id));
                                 let dock = ui.dock(id!(dock));
                                 for (tab_id, file_id) in &self.tab_id_to_file_node_id {
@@ -131,23 +232,74 @@ impl FileSystem {
                                         dock.redraw_tab(cx, *tab_id);
                                     }
                                 }
-                                if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
+                                if let (Some(OpenDoc::Document(doc)), Some(collab)) = (self.open_documents.get(&file_id), self.collab_files.get_mut(&file_id)) {
+                                    // opened again after a rejected delta, the server has the text to continue from
+                                    let save_requested = collab.save_requested;
+                                    *collab = CollabFile {revision, save_requested, ..Default::default()};
+                                    let length = doc.as_text().length();
+                                    let changes = vec![Change::Delete(Position::zero(), length), Change::Insert(Position::zero(), data.into())];
+                                    doc.take_local_changes();
+                                    doc.apply_remote_changes(changes.clone());
+                                    let path = self.file_node_path(file_id);
+                                    if let Some(lsp) = &mut self.lsp {
+                                        lsp.did_change(&path, &changes);
+                                    }
+                                    for session in self.tab_id_to_session.values_mut() {
+                                        session.handle_changes();
+                                    }
+                                    if save_requested {
+                                        self.collab_files.get_mut(&file_id).unwrap().save_requested = false;
+                                        self.send_save_file(file_id);
+                                    }
+                                }
+                                else if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                     let dec = dec.clone();
-                                    self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
+                                    let tokenizer = self.tokenizers.get(&self.file_node_name(file_id));
+                                    let document = Document::new_with_tokenizer(data.into(), dec, tokenizer);
+                                    document.track_local_changes();
+                                    let path = self.file_node_path(file_id);
+                                    if let Some(lsp) = &mut self.lsp {
+                                        lsp.did_open(&path, &document.as_text());
+                                    }
+                                    self.open_documents.insert(file_id, OpenDoc::Document(document));
+                                    self.collab_files.insert(file_id, CollabFile {revision, ..Default::default()});
+                                    for (text, changes) in self.opening_edits.remove(&file_id).unwrap_or_default() {
+                                        self.edit_file(file_id, &text, changes);
+                                    }
+                                    // the tab may have been closed while the file was opening
+                                    self.close_file_if_unused(file_id);
                                 }else {panic!()}
                                 
                                 ui.redraw(cx);
                             }
                             Err(FileError::CannotOpen(_unix_path)) => {
                             }
-                            Err(FileError::Unknown(err)) => {
+                            Err(FileError::Unknown(err)) | Err(FileError::InvalidDelta(err)) => {
                                 log!("File error unknown {}", err);
                                 // ignore
                             }
                         }
                     }
+                    FileResponse::ApplyDelta(result) => match result {
+                        Ok((path, revision)) => {
+                            if let Some(file_id) = self.path_to_file_node_id(&path) {
+                                self.delta_was_acknowledged(file_id, revision);
+                                ui.redraw(cx);
+                            }
+                        }
+                        Err((path, err)) => {
+                            log!("Cannot apply delta to {} {:?}, reopening it", path, err);
+                            if let Some(file_id) = self.path_to_file_node_id(&path) {
+                                self.resync_file(file_id);
+                            }
+                        }
+                    }
+                    FileResponse::CloseFile(_) => {}
                     FileResponse::SaveFile(result) => match result {
                         Ok((path, old, new, _id)) => {
+                            if let Some(lsp) = &mut self.lsp {
+                                lsp.did_save(&path);
+                            }
                             // alright file has been saved
                             // now we need to check if a live_design!{} changed or something outside it
                             if old != new {
@@ -178,14 +330,18 @@ impl FileSystem {
                                 }
                             }
                         }
-                        Err(_) => {}
-                        // ok we saved a file, we should check however what changed
-                        // to see if we need a recompile
-                        
+                        Err(err) => {
+                            log!("Cannot save file {:?}", err);
+                        }
                     }
                 },
-                FileClientAction::Notification(_notification) => {
-                    //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                FileClientAction::Notification(FileNotification::DeltaWasApplied(path, revision, delta)) => {
+                    if let Some(file_id) = self.path_to_file_node_id(&path) {
+                        self.delta_was_applied(file_id, revision, delta);
+                        ui.redraw(cx);
+                    }
+                }
+                FileClientAction::Notification(FileNotification::FileChangedOnDisk) => {
                 }
             }
         }
@@ -195,6 +351,100 @@ impl FileSystem {
         for session in self.tab_id_to_session.values_mut() {
             session.handle_changes();
         }
+        let file_ids: Vec<FileNodeId> = self.collab_files.keys().cloned().collect();
+        for file_id in file_ids {
+            self.send_local_changes(file_id);
+        }
+    }
+    
+    // Moves the changes made in the editor into pending, and sends them if nothing is in flight.
+    fn send_local_changes(&mut self, file_id: FileNodeId) {
+        let (Some(OpenDoc::Document(doc)), true) = (self.open_documents.get(&file_id), self.collab_files.contains_key(&file_id)) else {
+            return
+        };
+        let changes = doc.take_local_changes();
+        if !changes.is_empty() && self.lsp.is_some() {
+            let path = self.file_node_path(file_id);
+            self.lsp.as_mut().unwrap().did_change(&path, &changes);
+        }
+        let collab = self.collab_files.get_mut(&file_id).unwrap();
+        collab.pending.changes.extend(changes.into_iter().map(to_text_change));
+        if collab.sent.is_none() && !collab.pending.is_empty() && !collab.resync {
+            let delta = std::mem::take(&mut collab.pending);
+            collab.sent = Some(delta.clone());
+            let revision = collab.revision;
+            let path = self.file_node_path(file_id);
+            self.file_client.send_request(FileRequest::ApplyDelta(path, revision, delta));
+        }
+    }
+    
+    // our changes since the last acknowledged revision are dropped, and the file is opened
+    // again to continue from the text the server has
+    fn resync_file(&mut self, file_id: FileNodeId) {
+        let Some(collab) = self.collab_files.get_mut(&file_id) else {
+            return
+        };
+        collab.sent = None;
+        collab.pending = TextDelta::default();
+        collab.queued.clear();
+        collab.resync = true;
+        let path = self.file_node_path(file_id);
+        self.file_client.send_request(FileRequest::OpenFile(path, file_id.0.0));
+    }
+    
+    fn delta_was_acknowledged(&mut self, file_id: FileNodeId, revision: u64) {
+        let Some(collab) = self.collab_files.get_mut(&file_id) else {
+            return
+        };
+        collab.sent = None;
+        collab.revision = revision;
+        for (revision, delta) in std::mem::take(&mut collab.queued) {
+            self.delta_was_applied(file_id, revision, delta);
+        }
+        self.send_local_changes(file_id);
+        if let Some(collab) = self.collab_files.get_mut(&file_id) {
+            if collab.save_requested && collab.is_idle() {
+                collab.save_requested = false;
+                self.send_save_file(file_id);
+            }
+        }
+        self.close_file_if_unused(file_id);
+    }
+    
+    // Another participant applied a delta on the server. It was made without our in flight and
+    // pending changes, so it is transformed against those before it is applied to the document.
+    fn delta_was_applied(&mut self, file_id: FileNodeId, revision: u64, delta: TextDelta) {
+        self.send_local_changes(file_id);
+        let (Some(OpenDoc::Document(doc)), Some(collab)) = (self.open_documents.get(&file_id), self.collab_files.get_mut(&file_id)) else {
+            return
+        };
+        if collab.resync {
+            // the text we get when the file is opened again has this delta in it
+            return
+        }
+        if revision != collab.revision + 1 {
+            // this delta came after our in flight one on the server, wait for the acknowledgement
+            collab.queued.push((revision, delta));
+            return
+        }
+        let mut delta = delta;
+        if let Some(sent) = collab.sent.take() {
+            let (sent, transformed) = sent.transform(delta);
+            collab.sent = Some(sent);
+            delta = transformed;
+        }
+        let (pending, delta) = std::mem::take(&mut collab.pending).transform(delta);
+        collab.pending = pending;
+        collab.revision = revision;
+        let changes: Vec<Change> = delta.changes.into_iter().map(from_text_change).collect();
+        doc.apply_remote_changes(changes.clone());
+        let path = self.file_node_path(file_id);
+        if let Some(lsp) = &mut self.lsp {
+            lsp.did_change(&path, &changes);
+        }
+        for session in self.tab_id_to_session.values_mut() {
+            session.handle_changes();
+        }
     }
     
     pub fn request_open_file(&mut self, tab_id: LiveId, file_id: FileNodeId) {
@@ -221,38 +471,127 @@ impl FileSystem {
     
     
     pub fn request_save_file(&mut self, tab_id: LiveId) {
-        // ok lets see if we have a document
-        // ifnot, we create a new one
-        if let Some(file_id) = self.tab_id_to_file_node_id.get(&tab_id) {
-            if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
-                let text = doc.as_text().to_string();
-                let path = self.file_node_path(*file_id);
-                self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0.0));
-            }
+        if let Some(file_id) = self.tab_id_to_file_node_id.get(&tab_id).cloned() {
+            self.request_save_file_node(file_id);
         };
     }
     
-    pub fn clear_decorations(&mut self, file_node_id: &FileNodeId) {
+    pub fn request_save_file_node(&mut self, file_id: FileNodeId) {
+        // the server saves what it has, so our changes have to get there first
+        self.send_local_changes(file_id);
+        match self.collab_files.get_mut(&file_id) {
+            Some(collab) if !collab.is_idle() => collab.save_requested = true,
+            _ => self.send_save_file(file_id)
+        }
+    }
+    
+    fn send_save_file(&mut self, file_id: FileNodeId) {
+        if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
+            let text = doc.as_text().to_string();
+            let path = self.file_node_path(file_id);
+            self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0.0));
+        }
+    }
+    
+    // applies changes that were made against text, which should be what the file holds now. an open
+    // file is edited through the session of a tab so the changes can be undone, or else through its
+    // document, and saved once the server has them. a file that is still opening gets the changes
+    // when its text is in, and any other file is written to disk
+    pub fn edit_file(&mut self, file_id: FileNodeId, text: &str, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return
+        }
+        if let Some(doc) = self.open_document(file_id).cloned() {
+            if doc.as_text().to_string() != text {
+                log!("Not editing {}, it changed since the edit was made", self.file_node_path(file_id));
+                return
+            }
+            let session = self.file_node_id_to_tab_id(file_id).and_then( | tab_id | self.get_session_mut(tab_id));
+            if let Some(session) = session {
+                session.apply_changes(changes);
+            }
+            else if self.collab_files.contains_key(&file_id) {
+                // without a session the changes go to the server the way remote ones come in
+                self.send_local_changes(file_id);
+                doc.apply_remote_changes(changes.clone());
+                let path = self.file_node_path(file_id);
+                if let Some(lsp) = &mut self.lsp {
+                    lsp.did_change(&path, &changes);
+                }
+                let collab = self.collab_files.get_mut(&file_id).unwrap();
+                collab.pending.changes.extend(changes.into_iter().map(to_text_change));
+            }
+            self.handle_sessions();
+            self.request_save_file_node(file_id);
+        }
+        else if self.tab_id_to_file_node_id.values().any( | id | *id == file_id) {
+            self.opening_edits.entry(file_id).or_default().push((text.to_string(), changes));
+        }
+        else {
+            let mut text = Text::from(text.to_string());
+            for change in changes {
+                text.apply_change(change);
+            }
+            self.request_write_file(file_id, text.to_string());
+        }
+    }
+    
+    // writes new contents for a file that is not open in an editor
+    pub fn request_write_file(&mut self, file_id: FileNodeId, text: String) {
+        let path = self.file_node_path(file_id);
+        self.file_client.send_request(FileRequest::SaveFile(path, text, file_id.0.0));
+    }
+    
+    pub fn file_paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.path_to_file_node_id.iter()
+            .filter( | (_, file_id) | self.file_nodes.get(file_id).map_or(false, | node | node.is_file()))
+            .map( | (path, _) | path.clone())
+            .collect();
+        paths.sort();
+        paths
+    }
+    
+    pub fn open_document(&self, file_id: FileNodeId) -> Option<&Document> {
+        match self.open_documents.get(&file_id) {
+            Some(OpenDoc::Document(doc)) => Some(doc),
+            _ => None
+        }
+    }
+    
+    pub fn clear_decorations(&mut self, file_node_id: &FileNodeId, id: usize) {
         // ok lets see if we have a document
         // ifnot, we create a new one
         match self.open_documents.get_mut(file_node_id) {
-            Some(OpenDoc::Decorations(dec)) => dec.clear(),
-            Some(OpenDoc::Document(doc)) => doc.clear_decorations(),
+            Some(OpenDoc::Decorations(dec)) => dec.remove_decorations(id),
+            Some(OpenDoc::Document(doc)) => doc.remove_decorations(id),
             None => ()
         };
     }
     
-    pub fn clear_all_decorations(&mut self) {
+    pub fn clear_all_decorations(&mut self, id: usize) {
         // ok lets see if we have a document
         // ifnot, we create a new one
         for document in self.open_documents.values_mut() {
             match document {
-                OpenDoc::Decorations(dec) => dec.clear(),
-                OpenDoc::Document(doc) => doc.clear_decorations(),
+                OpenDoc::Decorations(dec) => dec.remove_decorations(id),
+                OpenDoc::Document(doc) => doc.remove_decorations(id),
             }
         }
     }
     
+    // the server always sends all diagnostics of a file, so they replace the previous ones
+    fn set_lsp_diagnostics(&mut self, file_id: FileNodeId, diagnostics: &[LspDiagnostic]) {
+        self.clear_decorations(&file_id, LSP_DECORATION_ID);
+        for diagnostic in diagnostics {
+            let ty = match diagnostic.severity {
+                LspSeverity::Error => DecorationType::Error,
+                LspSeverity::Warning => DecorationType::Warning,
+                LspSeverity::Information | LspSeverity::Hint => continue
+            };
+            self.add_decoration(file_id, Decoration::new(LSP_DECORATION_ID, diagnostic.start, diagnostic.end, ty));
+        }
+    }
+    
     pub fn redraw_view_by_file_id(&mut self, cx: &mut Cx, id: FileNodeId, dock: &DockRef) {
         for (tab_id, file_id) in &self.tab_id_to_file_node_id {
             if id == *file_id {
@@ -449,4 +788,30 @@ impl FileSystem {
             tree_data.root,
         );
     }
-}
\ No newline at end of file
+}
+
+fn to_text_change(change: Change) -> TextChange {
+    match change {
+        Change::Insert(position, text) => TextChange::Insert(
+            TextPosition {line_index: position.line_index, byte_index: position.byte_index},
+            text.to_string()
+        ),
+        Change::Delete(start, length) => TextChange::Delete(
+            TextPosition {line_index: start.line_index, byte_index: start.byte_index},
+            TextLength {line_count: length.line_count, byte_count: length.byte_count}
+        ),
+    }
+}
+
+fn from_text_change(change: TextChange) -> Change {
+    match change {
+        TextChange::Insert(position, text) => Change::Insert(
+            Position {line_index: position.line_index, byte_index: position.byte_index},
+            text.into()
+        ),
+        TextChange::Delete(start, length) => Change::Delete(
+            Position {line_index: start.line_index, byte_index: start.byte_index},
+            Length {line_count: length.line_count, byte_count: length.byte_count}
+        ),
+    }
+}
@@ -1,5 +1,7 @@
 pub mod build_manager;
 pub mod file_system;
+pub mod lsp;
+pub mod search;
 
 //pub use makepad_code_editor;
 pub use makepad_file_protocol;
@@ -12,6 +14,7 @@ pub use makepad_platform::makepad_live_id;
 pub use makepad_platform::makepad_error_log;
 pub use makepad_code_editor;
 pub use makepad_shell;
+pub use makepad_toml_parser;
 
 #[cfg(target_arch = "wasm32")]
 pub use makepad_platform::makepad_wasm_bridge;
@@ -0,0 +1,499 @@
+use {
+    std::{
+        collections::HashMap,
+        io::{self, BufReader},
+        path::{Path, PathBuf},
+        process::{Child, Command, Stdio},
+        sync::mpsc::{self, Sender},
+        thread,
+    },
+    crate::{
+        makepad_code_editor::text::{Change, Position, Text},
+        makepad_platform::*,
+        makepad_micro_serde::JsonValue,
+        lsp::lsp_protocol::*,
+    },
+};
+
+pub type LspRequestId = u64;
+
+pub enum LspEvent {
+    // replaces all earlier diagnostics for the file
+    Diagnostics {path: String, diagnostics: Vec<LspDiagnostic>},
+    Hover {request_id: LspRequestId, text: Option<String>},
+    Definition {request_id: LspRequestId, locations: Vec<LspLocation>},
+    Completion {request_id: LspRequestId, items: Vec<LspCompletionItem>},
+    // the server exited, or closed its output
+    Stopped,
+}
+
+enum PendingRequest {
+    Initialize,
+    Hover,
+    Definition,
+    Completion,
+    Shutdown,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum SyncKind {
+    None,
+    Full,
+    Incremental,
+}
+
+// our copy of an open document, as the server has it. positions are converted with it, so it
+// has to follow every change in the order the editor made them
+struct LspDocument {
+    version: u64,
+    text: Text,
+}
+
+// talks to one language server over its stdin and stdout. paths are relative to the root the
+// server was started in, like everywhere else in studio
+pub struct LspClient {
+    root_path: PathBuf,
+    extensions: Vec<String>,
+    child: Option<Child>,
+    sender: Option<Sender<JsonValue>>,
+    receiver: ToUIReceiver<Option<JsonValue>>,
+    next_request_id: LspRequestId,
+    pending: HashMap<LspRequestId, PendingRequest>,
+    documents: HashMap<String, LspDocument>,
+    is_initialized: bool,
+    encoding: PositionEncoding,
+    sync_kind: SyncKind,
+}
+
+impl LspClient {
+    // starts the server and sends initialize. documents opened before the server answered are
+    // sent to it after
+    pub fn start(command: &[String], root_path: &Path, extensions: &[String]) -> io::Result<Self> {
+        let Some((program, args)) = command.split_first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "no language server command"))
+        };
+        let mut child = Command::new(program)
+            .args(args)
+            .current_dir(root_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn() ?;
+        let mut stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (sender, write_receiver) = mpsc::channel::<JsonValue>();
+        thread::spawn(move || {
+            while let Ok(message) = write_receiver.recv() {
+                if write_message(&mut stdin, &message).is_err() {
+                    break
+                }
+            }
+        });
+
+        let receiver = ToUIReceiver::default();
+        let read_sender = receiver.sender();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(message)) = read_message(&mut reader) {
+                if read_sender.send(Some(message)).is_err() {
+                    return
+                }
+            }
+            let _ = read_sender.send(None);
+        });
+
+        let mut client = Self {
+            root_path: root_path.to_path_buf(),
+            extensions: extensions.to_vec(),
+            child: Some(child),
+            sender: Some(sender),
+            receiver,
+            next_request_id: 1,
+            pending: HashMap::new(),
+            documents: HashMap::new(),
+            is_initialized: false,
+            encoding: PositionEncoding::Utf16,
+            sync_kind: SyncKind::Full,
+        };
+        let root_uri = path_to_uri(root_path);
+        let params = JsonValue::object([
+            ("processId", (std::process::id() as u64).into()),
+            ("rootUri", root_uri.clone().into()),
+            ("workspaceFolders", vec![JsonValue::object([
+                ("uri", root_uri.into()),
+                ("name", root_path.file_name().map_or(String::new(), | name | name.to_string_lossy().to_string()).into()),
+            ])].into()),
+            ("capabilities", JsonValue::object([
+                ("general", JsonValue::object([
+                    ("positionEncodings", vec!["utf-8".into(), "utf-16".into()].into()),
+                ])),
+                ("textDocument", JsonValue::object([
+                    ("synchronization", JsonValue::object([("didSave", true.into())])),
+                    ("publishDiagnostics", JsonValue::object([])),
+                    ("hover", JsonValue::object([
+                        ("contentFormat", vec!["plaintext".into(), "markdown".into()].into()),
+                    ])),
+                    ("definition", JsonValue::object([("linkSupport", true.into())])),
+                    ("completion", JsonValue::object([
+                        ("completionItem", JsonValue::object([("snippetSupport", false.into())])),
+                    ])),
+                ])),
+            ])),
+        ]);
+        client.send_request("initialize", params, PendingRequest::Initialize);
+        Ok(client)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    // whether files like this one go to this server
+    pub fn handles(&self, path: &str) -> bool {
+        Path::new(path).extension().map_or(false, | ext | {
+            self.extensions.iter().any( | e | e.as_str() == ext)
+        })
+    }
+
+    pub fn did_open(&mut self, path: &str, text: &Text) {
+        if !self.handles(path) {
+            return
+        }
+        self.documents.insert(path.to_string(), LspDocument {
+            version: 0,
+            text: text.clone()
+        });
+        if self.is_initialized {
+            self.send_did_open(path);
+        }
+    }
+
+    pub fn did_change(&mut self, path: &str, changes: &[Change]) {
+        if changes.is_empty() {
+            return
+        }
+        let uri = self.uri(path);
+        let Some(document) = self.documents.get_mut(path) else {
+            return
+        };
+        let mut content_changes = Vec::new();
+        for change in changes {
+            if self.sync_kind == SyncKind::Incremental {
+                let lines = document.text.as_lines();
+                let range = match change {
+                    Change::Insert(position, _) => to_lsp_range(lines, *position, *position, self.encoding),
+                    Change::Delete(start, length) => to_lsp_range(lines, *start, *start + *length, self.encoding),
+                };
+                let text = match change {
+                    Change::Insert(_, text) => text.to_string(),
+                    Change::Delete(_, _) => String::new(),
+                };
+                content_changes.push(JsonValue::object([
+                    ("range", range),
+                    ("text", text.into()),
+                ]));
+            }
+            document.text.apply_change(change.clone());
+        }
+        document.version += 1;
+        if !self.is_initialized || self.sync_kind == SyncKind::None {
+            return
+        }
+        if self.sync_kind == SyncKind::Full {
+            content_changes.push(JsonValue::object([("text", document.text.to_string().into())]));
+        }
+        let params = JsonValue::object([
+            ("textDocument", JsonValue::object([
+                ("uri", uri.into()),
+                ("version", document.version.into()),
+            ])),
+            ("contentChanges", content_changes.into()),
+        ]);
+        self.send_notification("textDocument/didChange", params);
+    }
+
+    pub fn did_save(&mut self, path: &str) {
+        if self.is_initialized && self.documents.contains_key(path) {
+            let params = JsonValue::object([("textDocument", self.text_document(path))]);
+            self.send_notification("textDocument/didSave", params);
+        }
+    }
+
+    pub fn did_close(&mut self, path: &str) {
+        if self.documents.remove(path).is_some() && self.is_initialized {
+            let params = JsonValue::object([("textDocument", self.text_document(path))]);
+            self.send_notification("textDocument/didClose", params);
+        }
+    }
+
+    pub fn hover(&mut self, path: &str, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        self.send_request("textDocument/hover", params, PendingRequest::Hover)
+    }
+
+    pub fn definition(&mut self, path: &str, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        self.send_request("textDocument/definition", params, PendingRequest::Definition)
+    }
+
+    pub fn completion(&mut self, path: &str, position: Position) -> Option<LspRequestId> {
+        let params = self.text_document_position(path, position) ?;
+        self.send_request("textDocument/completion", params, PendingRequest::Completion)
+    }
+
+    // asks the server to exit. it is killed when the client is dropped before it did
+    pub fn stop(&mut self) {
+        if self.is_initialized {
+            self.send_request("shutdown", JsonValue::Null, PendingRequest::Shutdown);
+            self.send_notification("exit", JsonValue::Null);
+        }
+        self.is_initialized = false;
+        // closing stdin ends the writer thread, and tells the server we are gone
+        self.sender = None;
+    }
+
+    pub fn handle_event(&mut self, _cx: &mut Cx, event: &Event) -> Vec<LspEvent> {
+        if let Event::Signal = event {
+            return self.poll()
+        }
+        Vec::new()
+    }
+
+    // handles what the server sent since the last call, without waiting for more
+    pub fn poll(&mut self) -> Vec<LspEvent> {
+        let mut events = Vec::new();
+        while let Ok(message) = self.receiver.try_recv() {
+            match message {
+                Some(message) => self.handle_message(message, &mut events),
+                None => {
+                    self.sender = None;
+                    self.is_initialized = false;
+                    events.push(LspEvent::Stopped);
+                }
+            }
+        }
+        events
+    }
+
+    fn handle_message(&mut self, message: JsonValue, events: &mut Vec<LspEvent>) {
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then( | m | m.as_str()), id) {
+            // a request from the server. we have nothing to offer, but it waits for an answer
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let count = message.path("params.items").and_then( | items | items.as_array()).map_or(0, | items | items.len());
+                        JsonValue::Array(vec![JsonValue::Null; count])
+                    }
+                    _ => JsonValue::Null
+                };
+                self.send(JsonValue::object([
+                    ("jsonrpc", "2.0".into()),
+                    ("id", id),
+                    ("result", result),
+                ]));
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let Some(params) = message.get("params") else {
+                    return
+                };
+                let Some(path) = params.get("uri").and_then( | uri | uri.as_str()).map( | uri | self.uri_to_path(uri)) else {
+                    return
+                };
+                let diagnostics = params.get("diagnostics").and_then( | d | d.as_array()).unwrap_or(&[]);
+                let diagnostics = self.with_lines(&path, | lines | {
+                    diagnostics.iter().filter_map( | d | LspDiagnostic::from_json(lines, d, self.encoding)).collect()
+                });
+                events.push(LspEvent::Diagnostics {path, diagnostics});
+            }
+            (Some(_), None) => {}
+            (None, Some(id)) => {
+                let Some(request_id) = id.as_u64() else {
+                    return
+                };
+                let Some(request) = self.pending.remove(&request_id) else {
+                    return
+                };
+                // an error answers the request with nothing
+                let result = message.get("result").cloned().unwrap_or_default();
+                match request {
+                    PendingRequest::Initialize => self.initialized(&result),
+                    PendingRequest::Hover => events.push(LspEvent::Hover {
+                        request_id,
+                        text: hover_text(&result)
+                    }),
+                    PendingRequest::Definition => events.push(LspEvent::Definition {
+                        request_id,
+                        locations: self.locations(&result)
+                    }),
+                    PendingRequest::Completion => {
+                        let items = match &result {
+                            JsonValue::Array(items) => &items[..],
+                            _ => result.get("items").and_then( | items | items.as_array()).unwrap_or(&[])
+                        };
+                        events.push(LspEvent::Completion {
+                            request_id,
+                            items: items.iter().filter_map(LspCompletionItem::from_json).collect()
+                        });
+                    }
+                    PendingRequest::Shutdown => {}
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    fn initialized(&mut self, result: &JsonValue) {
+        let capabilities = result.get("capabilities").cloned().unwrap_or_default();
+        if capabilities.get("positionEncoding").and_then( | e | e.as_str()) == Some("utf-8") {
+            self.encoding = PositionEncoding::Utf8;
+        }
+        // either the kind, or an object with the kind in change
+        let sync = capabilities.get("textDocumentSync");
+        let sync = sync.and_then( | s | s.get("change")).or(sync).and_then( | s | s.as_u64());
+        self.sync_kind = match sync {
+            Some(0) => SyncKind::None,
+            Some(2) => SyncKind::Incremental,
+            _ => SyncKind::Full,
+        };
+        self.is_initialized = true;
+        self.send_notification("initialized", JsonValue::object([]));
+        let mut paths: Vec<String> = self.documents.keys().cloned().collect();
+        paths.sort();
+        for path in paths {
+            self.send_did_open(&path);
+        }
+    }
+
+    fn locations(&self, result: &JsonValue) -> Vec<LspLocation> {
+        let values = match result {
+            JsonValue::Array(values) => &values[..],
+            JsonValue::Null => &[],
+            _ => std::slice::from_ref(result),
+        };
+        values.iter().filter_map( | value | {
+            // a Location, or a LocationLink when the server knows we support those
+            let (uri, range) = match value.get("targetUri") {
+                Some(uri) => (uri, value.get("targetSelectionRange") ?),
+                None => (value.get("uri") ?, value.get("range") ?),
+            };
+            let path = self.uri_to_path(uri.as_str() ?);
+            let (start, end) = self.with_lines(&path, | lines | from_lsp_range(lines, range, self.encoding)) ?;
+            Some(LspLocation {path, start, end})
+        }).collect()
+    }
+
+    fn send_did_open(&mut self, path: &str) {
+        let Some(document) = self.documents.get(path) else {
+            return
+        };
+        let language_id = match Path::new(path).extension().and_then( | ext | ext.to_str()) {
+            Some("rs") => "rust",
+            Some("toml") => "toml",
+            Some("md") => "markdown",
+            Some("json") => "json",
+            Some(ext) => ext,
+            None => "plaintext",
+        };
+        let params = JsonValue::object([
+            ("textDocument", JsonValue::object([
+                ("uri", self.uri(path).into()),
+                ("languageId", language_id.into()),
+                ("version", document.version.into()),
+                ("text", document.text.to_string().into()),
+            ])),
+        ]);
+        self.send_notification("textDocument/didOpen", params);
+    }
+
+    fn text_document(&self, path: &str) -> JsonValue {
+        JsonValue::object([("uri", self.uri(path).into())])
+    }
+
+    fn text_document_position(&self, path: &str, position: Position) -> Option<JsonValue> {
+        if !self.is_initialized {
+            return None
+        }
+        let document = self.documents.get(path) ?;
+        Some(JsonValue::object([
+            ("textDocument", self.text_document(path)),
+            ("position", to_lsp_position(document.text.as_lines(), position, self.encoding)),
+        ]))
+    }
+
+    // the lines of a file to convert positions in it with, from disk when it is not open
+    fn with_lines<R>(&self, path: &str, f: impl FnOnce(&[String]) -> R) -> R {
+        if let Some(document) = self.documents.get(path) {
+            return f(document.text.as_lines())
+        }
+        let data = std::fs::read_to_string(self.root_path.join(path)).unwrap_or_default();
+        let lines: Vec<String> = data.split('\n').map( | line | line.strip_suffix('\r').unwrap_or(line).to_string()).collect();
+        f(&lines)
+    }
+
+    fn uri(&self, path: &str) -> String {
+        path_to_uri(&self.root_path.join(path))
+    }
+
+    fn uri_to_path(&self, uri: &str) -> String {
+        let path = uri_to_path(uri).unwrap_or_else( || PathBuf::from(uri));
+        let path = path.strip_prefix(&self.root_path).unwrap_or(&path);
+        path.to_string_lossy().replace('\\', "/")
+    }
+
+    fn send_request(&mut self, method: &str, params: JsonValue, request: PendingRequest) -> Option<LspRequestId> {
+        if self.sender.is_none() {
+            return None
+        }
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        self.pending.insert(request_id, request);
+        self.send(message(Some(request_id), method, params));
+        Some(request_id)
+    }
+
+    fn send_notification(&mut self, method: &str, params: JsonValue) {
+        self.send(message(None, method, params));
+    }
+
+    fn send(&mut self, message: JsonValue) {
+        if let Some(sender) = &self.sender {
+            if sender.send(message).is_err() {
+                self.sender = None;
+            }
+        }
+    }
+}
+
+// a request when it has an id, a notification when not. null params are left out
+fn message(request_id: Option<LspRequestId>, method: &str, params: JsonValue) -> JsonValue {
+    let mut fields = vec![("jsonrpc".to_string(), "2.0".into())];
+    if let Some(request_id) = request_id {
+        fields.push(("id".to_string(), request_id.into()));
+    }
+    fields.push(("method".to_string(), method.into()));
+    if !params.is_null() {
+        fields.push(("params".to_string(), params));
+    }
+    JsonValue::Object(fields)
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(mut child) = self.child.take() {
+            // give it a moment to exit by itself, and reap it
+            thread::spawn(move || {
+                for _ in 0..20 {
+                    if let Ok(Some(_)) = child.try_wait() {
+                        return
+                    }
+                    thread::sleep(std::time::Duration::from_millis(50));
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+            });
+        }
+    }
+}
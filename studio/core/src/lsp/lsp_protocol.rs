@@ -0,0 +1,280 @@
+use {
+    std::{
+        io::{self, BufRead, Write},
+        path::{Path, PathBuf},
+    },
+    crate::{
+        makepad_code_editor::text::Position,
+        makepad_micro_serde::*,
+    },
+};
+
+// every message is a json body behind a header: Content-Length: <bytes>\r\n\r\n<body>
+pub fn write_message(writer: &mut impl Write, message: &JsonValue) -> io::Result<()> {
+    let body = message.serialize_json();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body) ?;
+    writer.flush()
+}
+
+// returns None once the other side closed the stream
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<JsonValue>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line) ? == 0 {
+            return Ok(None)
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break
+            }
+            continue
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0; content_length.unwrap()];
+    reader.read_exact(&mut body) ?;
+    let body = String::from_utf8(body).map_err( | e | io::Error::new(io::ErrorKind::InvalidData, e)) ?;
+    JsonValue::parse(&body)
+        .map(Some)
+        .map_err( | e | io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))
+}
+
+// what the character of an lsp position counts. servers that can count bytes say so in the
+// initialize response, the others count utf-16 code units
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+}
+
+impl PositionEncoding {
+    pub fn to_character(self, line: &str, byte_index: usize) -> usize {
+        let byte_index = byte_index.min(line.len());
+        match self {
+            PositionEncoding::Utf8 => byte_index,
+            PositionEncoding::Utf16 => line[..byte_index].encode_utf16().count(),
+        }
+    }
+
+    // clamped to the line, and to the start of the char the character falls in
+    pub fn to_byte_index(self, line: &str, character: usize) -> usize {
+        match self {
+            PositionEncoding::Utf8 => {
+                let mut byte_index = character.min(line.len());
+                while !line.is_char_boundary(byte_index) {
+                    byte_index -= 1;
+                }
+                byte_index
+            }
+            PositionEncoding::Utf16 => {
+                let mut units = 0;
+                for (byte_index, c) in line.char_indices() {
+                    units += c.len_utf16();
+                    if units > character {
+                        return byte_index
+                    }
+                }
+                line.len()
+            }
+        }
+    }
+}
+
+pub fn to_lsp_position(lines: &[String], position: Position, encoding: PositionEncoding) -> JsonValue {
+    let line = lines.get(position.line_index).map_or("", | line | line.as_str());
+    JsonValue::object([
+        ("line", position.line_index.into()),
+        ("character", encoding.to_character(line, position.byte_index).into()),
+    ])
+}
+
+pub fn to_lsp_range(lines: &[String], start: Position, end: Position, encoding: PositionEncoding) -> JsonValue {
+    JsonValue::object([
+        ("start", to_lsp_position(lines, start, encoding)),
+        ("end", to_lsp_position(lines, end, encoding)),
+    ])
+}
+
+pub fn from_lsp_position(lines: &[String], position: &JsonValue, encoding: PositionEncoding) -> Option<Position> {
+    let line_index = position.get("line") ?.as_u64() ? as usize;
+    let character = position.get("character") ?.as_u64() ? as usize;
+    let Some(line) = lines.get(line_index) else {
+        // past the end, as servers do for the end of a range that covers the last line
+        let line_index = lines.len().saturating_sub(1);
+        return Some(Position {
+            line_index,
+            byte_index: lines.get(line_index).map_or(0, | line | line.len())
+        })
+    };
+    Some(Position {
+        line_index,
+        byte_index: encoding.to_byte_index(line, character)
+    })
+}
+
+pub fn from_lsp_range(lines: &[String], range: &JsonValue, encoding: PositionEncoding) -> Option<(Position, Position)> {
+    Some((
+        from_lsp_position(lines, range.get("start") ?, encoding) ?,
+        from_lsp_position(lines, range.get("end") ?, encoding) ?,
+    ))
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'/')
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // windows paths start with a drive letter
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if is_unreserved(byte) || byte == b':' {
+            uri.push(byte as char);
+        }
+        else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://") ?;
+    let mut bytes = Vec::new();
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next() ?, iter.next() ?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok() ?, 16).ok() ?);
+        }
+        else {
+            bytes.push(byte);
+        }
+    }
+    let path = String::from_utf8(bytes).ok() ?;
+    // file:///C:/dir on windows
+    let is_drive_path = path.len() > 2 && path.as_bytes()[2] == b':';
+    if cfg!(windows) && is_drive_path {
+        return Some(PathBuf::from(&path[1..]))
+    }
+    Some(PathBuf::from(path))
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LspSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspDiagnostic {
+    pub start: Position,
+    pub end: Position,
+    pub severity: LspSeverity,
+    pub message: String,
+}
+
+impl LspDiagnostic {
+    pub fn from_json(lines: &[String], value: &JsonValue, encoding: PositionEncoding) -> Option<Self> {
+        let (start, end) = from_lsp_range(lines, value.get("range") ?, encoding) ?;
+        let severity = match value.get("severity").and_then( | v | v.as_u64()) {
+            Some(2) => LspSeverity::Warning,
+            Some(3) => LspSeverity::Information,
+            Some(4) => LspSeverity::Hint,
+            // servers that leave it out mean an error most of the time
+            _ => LspSeverity::Error,
+        };
+        Some(Self {
+            start,
+            end,
+            severity,
+            message: value.get("message") ?.as_str() ?.to_string()
+        })
+    }
+}
+
+// where a go to definition leads. the path is relative to the root of the workspace, unless it
+// is outside of it
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspLocation {
+    pub path: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LspCompletionItem {
+    pub label: String,
+    pub detail: String,
+    pub insert_text: String,
+}
+
+impl LspCompletionItem {
+    pub fn from_json(value: &JsonValue) -> Option<Self> {
+        let label = value.get("label") ?.as_str() ?.to_string();
+        let insert_text = value.path("textEdit.newText")
+            .or_else( || value.get("insertText"))
+            .and_then( | text | text.as_str())
+            .unwrap_or(&label)
+            .to_string();
+        Some(Self {
+            detail: value.get("detail").and_then( | v | v.as_str()).unwrap_or("").to_string(),
+            label,
+            insert_text,
+        })
+    }
+}
+
+// the contents of a hover can be markup, a marked string or a list of marked strings. the editor
+// shows plain text, so code fences are dropped
+pub fn hover_text(result: &JsonValue) -> Option<String> {
+    fn push_contents(contents: &JsonValue, out: &mut Vec<String>) {
+        match contents {
+            JsonValue::String(value) => out.push(value.clone()),
+            JsonValue::Array(values) => for value in values {
+                push_contents(value, out);
+            }
+            JsonValue::Object(_) => if let Some(value) = contents.get("value").and_then( | v | v.as_str()) {
+                out.push(value.to_string());
+            }
+            _ => ()
+        }
+    }
+    let mut parts = Vec::new();
+    push_contents(result.get("contents") ?, &mut parts);
+    let mut lines: Vec<&str> = Vec::new();
+    for part in &parts {
+        for line in part.lines() {
+            let line = line.trim_end();
+            if line.starts_with("```") {
+                continue
+            }
+            if line.is_empty() && lines.last().map_or(true, | last | last.is_empty()) {
+                continue
+            }
+            lines.push(line);
+        }
+        if lines.last().map_or(false, | last | !last.is_empty()) {
+            lines.push("");
+        }
+    }
+    while lines.last().map_or(false, | last | last.is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return None
+    }
+    Some(lines.join("\n"))
+}
@@ -0,0 +1,2 @@
+pub mod lsp_client;
+pub mod lsp_protocol;
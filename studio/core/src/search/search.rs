@@ -0,0 +1,442 @@
+use {
+    crate::{
+        makepad_platform::*,
+        makepad_widgets::*,
+        makepad_widgets::portal_list::PortalList,
+        makepad_code_editor::{
+            regex::Regex,
+            search::{expand_replacement, SearchQuery},
+            text::{Change, Length, Position},
+        },
+        file_system::file_system::FileSystem,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        ops::Range,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+    import makepad_widgets::theme::*;
+
+    SearchItem = <RectView> {
+        height: Fit,
+        width: Fill
+        padding: {top: 4, bottom: 4, left: 5}
+        draw_bg: {
+            instance is_even: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(THEME_COLOR_BG_EDITOR, THEME_COLOR_BG_ODD, self.is_even);
+            }
+        }
+    }
+
+    SearchResults = <PortalList> {
+        grab_key_focus: true
+        allow_empty: true
+        drag_scrolling: false
+        height: Fill,
+        width: Fill
+        flow: Down
+        File = <SearchItem> {
+            padding: {top: 6, bottom: 4, left: 5}
+            path = <Label> {width: Fit, margin: {right: 6}, padding: 0}
+            count = <Label> {draw_text: {color: #7}, width: Fit, padding: 0}
+        }
+        Match = <SearchItem> {
+            line = <Label> {draw_text: {color: #7}, width: 40, margin: {left: 10}, padding: 0}
+            preview = <LinkLabel> {margin: 0, text: ""}
+        }
+        Empty = <SearchItem> {
+            cursor: Default
+            height: 24,
+            width: Fill
+        }
+    }
+
+    SearchView = <View> {
+        width: Fill,
+        height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {color: (THEME_COLOR_BG_EDITOR)}
+        <View> {
+            width: Fill,
+            height: Fit
+            flow: Down
+            padding: 5
+            spacing: 3
+            search_input = <TextInput> {width: Fill, empty_message: "Search"}
+            search_replace_input = <TextInput> {width: Fill, empty_message: "Replace"}
+            <View> {
+                width: Fill,
+                height: Fit
+                align: {y: 0.5}
+                search_regex = <CheckBox> {text: ".*"}
+                search_case = <CheckBox> {text: "Aa"}
+                search_word = <CheckBox> {text: "Word"}
+                search_replace_all = <Button> {text: "Replace All"}
+            }
+            search_status = <Label> {draw_text: {color: #7}, padding: 0, text: ""}
+        }
+        search_results = <SearchResults> {}
+    }
+}
+
+// stop after this many matches, the list is not meant for browsing the entire project
+const MAX_MATCHES: usize = 10_000;
+// files bigger than this are not text files anyone wants to search
+const MAX_FILE_SIZE: u64 = 4 << 20;
+const PREVIEW_LEN: usize = 200;
+
+#[derive(Clone, Debug)]
+pub struct SearchMatch {
+    pub line_index: usize,
+    // byte range of the match in its line
+    pub range: Range<usize>,
+    pub preview: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchFileResult {
+    pub path: String,
+    pub matches: Vec<SearchMatch>,
+}
+
+pub enum SearchMessage {
+    File(u64, SearchFileResult),
+    Done(u64),
+}
+
+pub enum SearchAction {
+    JumpTo {file_name: String, start: Position, length: Length},
+    None
+}
+
+#[derive(Clone, Copy)]
+enum SearchListItem {
+    File(usize),
+    Match(usize, usize),
+}
+
+#[derive(Default)]
+pub struct Search {
+    root_path: PathBuf,
+    pub query: SearchQuery,
+    pub results: Vec<SearchFileResult>,
+    pub error: Option<String>,
+    pub is_searching: bool,
+    match_count: usize,
+    items: Vec<SearchListItem>,
+    generation: u64,
+    cancel: Arc<AtomicBool>,
+    receiver: ToUIReceiver<SearchMessage>,
+}
+
+// all matches in a text, line by line. matches never span lines
+pub fn search_text(regex: &Regex, text: &str, max_matches: usize) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for (line_index, line) in text.split('\n').enumerate() {
+        for captures in regex.find_iter(line) {
+            let range = captures.range();
+            if range.is_empty() {
+                continue
+            }
+            if matches.len() >= max_matches {
+                return matches
+            }
+            matches.push(SearchMatch {
+                line_index,
+                preview: preview_line(line, &range),
+                range,
+            });
+        }
+    }
+    matches
+}
+
+// the changes that replace every match in a text, last match first so earlier positions stay valid.
+// in regex mode $0 to $9 in the replacement insert the groups of the match and $$ inserts a $
+pub fn replace_changes(regex: &Regex, text: &str, replacement: &str, expand_groups: bool) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (line_index, line) in text.split('\n').enumerate() {
+        for captures in regex.find_iter(line) {
+            let range = captures.range();
+            if range.is_empty() {
+                continue
+            }
+            let new_text = if expand_groups {
+                expand_replacement(replacement, line, &captures)
+            } else {
+                replacement.to_string()
+            };
+            let start = Position {line_index, byte_index: range.start};
+            changes.push(Change::Insert(start, new_text.into()));
+            changes.push(Change::Delete(start, Length {line_count: 0, byte_count: range.len()}));
+        }
+    }
+    changes.reverse();
+    changes
+}
+
+fn preview_line(line: &str, range: &Range<usize>) -> String {
+    let line = line.trim_end();
+    let mut start = line.len() - line.trim_start().len();
+    // keep the match visible in long lines
+    if range.start > start + PREVIEW_LEN / 2 {
+        start = range.start - PREVIEW_LEN / 2;
+        while !line.is_char_boundary(start) {
+            start -= 1;
+        }
+    }
+    let mut end = (start + PREVIEW_LEN).min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[start..end].to_string()
+}
+
+fn read_text_file(path: &Path) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > MAX_FILE_SIZE {
+        return None
+    }
+    let text = String::from_utf8(fs::read(path).ok()?).ok()?;
+    if text.contains('\0') {
+        return None
+    }
+    Some(text)
+}
+
+impl Search {
+    pub fn init(&mut self, root_path: &Path) {
+        self.root_path = root_path.to_path_buf();
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.match_count
+    }
+
+    pub fn status(&self) -> String {
+        if let Some(error) = &self.error {
+            return error.clone()
+        }
+        if self.query.text.is_empty() {
+            return String::new()
+        }
+        let more = if self.match_count >= MAX_MATCHES {"+"} else {""};
+        let searching = if self.is_searching {", searching..."} else {""};
+        format!("{}{} results in {} files{}", self.match_count, more, self.results.len(), searching)
+    }
+
+    // starts searching every file in the tree in a thread. results stream in with handle_event,
+    // open documents are searched as they are in the editor
+    pub fn start(&mut self, file_system: &FileSystem, query: SearchQuery) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.generation += 1;
+        self.results.clear();
+        self.items.clear();
+        self.match_count = 0;
+        self.error = None;
+        self.is_searching = false;
+        self.query = query;
+        if self.query.text.is_empty() {
+            return
+        }
+        let regex = match self.query.compile() {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.error = Some(err);
+                return
+            }
+        };
+
+        let paths = file_system.file_paths();
+        let mut open_texts = HashMap::new();
+        for path in &paths {
+            if let Some(file_id) = file_system.path_to_file_node_id(path) {
+                if let Some(doc) = file_system.open_document(file_id) {
+                    open_texts.insert(path.clone(), doc.as_text().to_string());
+                }
+            }
+        }
+
+        self.is_searching = true;
+        let generation = self.generation;
+        let cancel = self.cancel.clone();
+        let sender = self.receiver.sender();
+        let root_path = self.root_path.clone();
+        std::thread::spawn(move || {
+            let mut match_count = 0;
+            for path in paths {
+                if cancel.load(Ordering::Relaxed) || match_count >= MAX_MATCHES {
+                    break
+                }
+                let text = match open_texts.remove(&path) {
+                    Some(text) => text,
+                    None => match read_text_file(&root_path.join(&path)) {
+                        Some(text) => text,
+                        None => continue
+                    }
+                };
+                let matches = search_text(&regex, &text, MAX_MATCHES - match_count);
+                if matches.is_empty() {
+                    continue
+                }
+                match_count += matches.len();
+                if sender.send(SearchMessage::File(generation, SearchFileResult {path, matches})).is_err() {
+                    return
+                }
+            }
+            let _ = sender.send(SearchMessage::Done(generation));
+        });
+    }
+
+    // returns true when new results arrived
+    pub fn handle_event(&mut self, _cx: &mut Cx, event: &Event) -> bool {
+        let mut changed = false;
+        if let Event::Signal = event {
+            while let Ok(message) = self.receiver.try_recv() {
+                match message {
+                    SearchMessage::File(generation, result) if generation == self.generation => {
+                        let file_index = self.results.len();
+                        self.items.push(SearchListItem::File(file_index));
+                        for match_index in 0..result.matches.len() {
+                            self.items.push(SearchListItem::Match(file_index, match_index));
+                        }
+                        self.match_count += result.matches.len();
+                        self.results.push(result);
+                        changed = true;
+                    }
+                    SearchMessage::Done(generation) if generation == self.generation => {
+                        self.is_searching = false;
+                        changed = true;
+                    }
+                    // from a search that was replaced by a newer one
+                    _ => ()
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn draw_results(&self, cx: &mut Cx2d, list: &mut PortalList) {
+        list.set_item_range(cx, 0, self.items.len() as u64);
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = if item_id & 1 == 0 {1.0} else {0.0};
+            match self.items.get(item_id as usize) {
+                Some(SearchListItem::File(file_index)) => {
+                    let result = &self.results[*file_index];
+                    let item = list.item(cx, item_id, live_id!(File)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        path = {text: (&result.path)}
+                        count = {text: (format!("{}", result.matches.len()))}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_widget_all(cx);
+                }
+                Some(SearchListItem::Match(file_index, match_index)) => {
+                    let search_match = &self.results[*file_index].matches[*match_index];
+                    let item = list.item(cx, item_id, live_id!(Match)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        line = {text: (format!("{}", search_match.line_index + 1))}
+                        preview = {text: (&search_match.preview)}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_widget_all(cx);
+                }
+                None => {
+                    let item = list.item(cx, item_id, live_id!(Empty)).unwrap().as_view();
+                    item.apply_over(cx, live!{draw_bg: {is_even: (is_even)}});
+                    item.draw_widget_all(cx);
+                }
+            }
+        }
+    }
+
+    pub fn handle_result_list(&mut self, _cx: &mut Cx, item_id: u64, item: WidgetRef, actions: &WidgetActions) -> Vec<SearchAction> {
+        let mut ret = Vec::new();
+        if item.link_label(id!(preview)).pressed(actions) {
+            if let Some(SearchListItem::Match(file_index, match_index)) = self.items.get(item_id as usize) {
+                let result = &self.results[*file_index];
+                let search_match = &result.matches[*match_index];
+                ret.push(SearchAction::JumpTo {
+                    file_name: result.path.clone(),
+                    start: Position {
+                        line_index: search_match.line_index,
+                        byte_index: search_match.range.start
+                    },
+                    length: Length {line_count: 0, byte_count: search_match.range.len()}
+                });
+            }
+        }
+        ret
+    }
+
+    // replaces every match in the files of the current results. files are matched again as they
+    // are now, open ones as they are in the editor. returns the number of replaced matches
+    pub fn replace_all(&mut self, file_system: &mut FileSystem, replacement: &str) -> usize {
+        let regex = match self.query.compile() {
+            Ok(regex) => regex,
+            Err(_) => return 0
+        };
+        let mut count = 0;
+        let paths: Vec<String> = self.results.iter().map( | result | result.path.clone()).collect();
+        for path in paths {
+            let Some(file_id) = file_system.path_to_file_node_id(&path) else {
+                continue
+            };
+            let text = match file_system.open_document(file_id) {
+                Some(doc) => doc.as_text().to_string(),
+                None => match read_text_file(&self.root_path.join(&path)) {
+                    Some(text) => text,
+                    None => continue
+                }
+            };
+            let changes = replace_changes(&regex, &text, replacement, self.query.is_regex);
+            count += changes.len() / 2;
+            file_system.edit_file(file_id, &text, changes);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::makepad_code_editor::text::Text;
+
+    fn apply_changes(text: &str, changes: Vec<Change>) -> String {
+        let mut text = Text::from(text.to_string());
+        for change in changes {
+            text.apply_change(change);
+        }
+        text.to_string()
+    }
+
+    #[test]
+    fn search_and_replace() {
+        let query = SearchQuery {text: "foo".into(), whole_word: true, ..Default::default()};
+        let regex = query.compile().unwrap();
+        let text = "let foo = 1;\nfoobar(FOO);\n    foo.x(foo)";
+        let matches = search_text(&regex, text, 100);
+        assert_eq!(matches.iter().map( | m | (m.line_index, m.range.clone())).collect::<Vec<_>>(), vec![(0, 4..7), (1, 7..10), (2, 4..7), (2, 10..13)]);
+        assert_eq!(matches[2].preview, "foo.x(foo)");
+        let changes = replace_changes(&regex, text, "bar", false);
+        assert_eq!(apply_changes(text, changes), "let bar = 1;\nfoobar(bar);\n    bar.x(bar)");
+
+        let query = SearchQuery {text: "(\\w+)\\((\\w+)\\)".into(), is_regex: true, case_sensitive: true, ..Default::default()};
+        let regex = query.compile().unwrap();
+        let changes = replace_changes(&regex, text, "$2.$1()$$", true);
+        assert_eq!(apply_changes(text, changes), "let foo = 1;\nFOO.foobar()$;\n    foo.foo.x()$");
+    }
+}
@@ -0,0 +1,146 @@
+use {
+    std::{
+        path::PathBuf,
+        time::{Duration, Instant},
+    },
+    makepad_studio_core::{
+        makepad_code_editor::text::{Change, Length, Position, Text},
+        lsp::{
+            lsp_client::{LspClient, LspEvent},
+            lsp_protocol::LspSeverity,
+        },
+    },
+};
+
+fn start(args: &[&str]) -> LspClient {
+    let mut command = vec![env!("CARGO_BIN_EXE_lsp_stub_server").to_string()];
+    command.extend(args.iter().map( | arg | arg.to_string()));
+    LspClient::start(&command, &root_path(), &["rs".to_string()]).unwrap()
+}
+
+fn root_path() -> PathBuf {
+    std::env::temp_dir()
+}
+
+// polls until an event matches, failing the test when the server takes too long
+fn wait_for<T>(client: &mut LspClient, mut f: impl FnMut(LspEvent) -> Option<T>) -> T {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        for event in client.poll() {
+            if let Some(result) = f(event) {
+                return result
+            }
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    panic!("no answer from the language server")
+}
+
+fn wait_for_diagnostics(client: &mut LspClient) -> Vec<(Position, Position)> {
+    wait_for(client, | event | match event {
+        LspEvent::Diagnostics {path, diagnostics} => {
+            assert_eq!(path, "main.rs");
+            assert!(diagnostics.iter().all( | d | d.severity == LspSeverity::Warning));
+            Some(diagnostics.iter().map( | d | (d.start, d.end)).collect())
+        }
+        _ => None
+    })
+}
+
+fn pos(line_index: usize, byte_index: usize) -> Position {
+    Position {line_index, byte_index}
+}
+
+fn sync_changes(args: &[&str]) {
+    let mut client = start(args);
+    // é and 🦀 take more bytes than utf-16 code units, so a wrong conversion moves the todos
+    client.did_open("main.rs", &Text::from("let é = 1; // todo\n🦀 todo"));
+    assert_eq!(wait_for_diagnostics(&mut client), vec![(pos(0, 15), pos(0, 19)), (pos(1, 5), pos(1, 9))]);
+
+    client.did_change("main.rs", &[
+        Change::Insert(pos(1, 0), Text::from("ü")),
+        Change::Delete(pos(0, 0), Length {line_count: 0, byte_count: 4}),
+    ]);
+    assert_eq!(wait_for_diagnostics(&mut client), vec![(pos(0, 11), pos(0, 15)), (pos(1, 7), pos(1, 11))]);
+
+    // joining the lines makes the first todo part of a longer word
+    client.did_change("main.rs", &[
+        Change::Delete(pos(0, 15), Length {line_count: 1, byte_count: 0}),
+    ]);
+    assert_eq!(wait_for_diagnostics(&mut client), vec![(pos(0, 22), pos(0, 26))]);
+}
+
+#[test]
+fn sync_changes_utf16() {
+    sync_changes(&[]);
+}
+
+#[test]
+fn sync_changes_utf8() {
+    sync_changes(&["--utf8"]);
+}
+
+#[test]
+fn requests() {
+    let mut client = start(&[]);
+    client.did_open("main.rs", &Text::from("fn über() {}\nfn main() {\n    über();\n}"));
+    wait_for_diagnostics(&mut client);
+
+    let request = client.hover("main.rs", pos(2, 6)).unwrap();
+    let text = wait_for(&mut client, | event | match event {
+        LspEvent::Hover {request_id, text} if request_id == request => Some(text),
+        _ => None
+    });
+    assert_eq!(text.as_deref(), Some("über\n\nhover for über"));
+
+    let request = client.hover("main.rs", pos(2, 2)).unwrap();
+    let text = wait_for(&mut client, | event | match event {
+        LspEvent::Hover {request_id, text} if request_id == request => Some(text),
+        _ => None
+    });
+    assert_eq!(text, None);
+
+    let request = client.definition("main.rs", pos(2, 6)).unwrap();
+    let locations = wait_for(&mut client, | event | match event {
+        LspEvent::Definition {request_id, locations} if request_id == request => Some(locations),
+        _ => None
+    });
+    assert_eq!(locations.len(), 1);
+    assert_eq!(locations[0].path, "main.rs");
+    assert_eq!((locations[0].start, locations[0].end), (pos(0, 3), pos(0, 8)));
+
+    let request = client.completion("main.rs", pos(3, 0)).unwrap();
+    let items = wait_for(&mut client, | event | match event {
+        LspEvent::Completion {request_id, items} if request_id == request => Some(items),
+        _ => None
+    });
+    let labels: Vec<&str> = items.iter().map( | item | item.label.as_str()).collect();
+    assert_eq!(labels, vec!["fn", "main", "über"]);
+    assert_eq!(items[0].insert_text, "fn");
+
+    client.stop();
+    wait_for(&mut client, | event | match event {
+        LspEvent::Stopped => Some(()),
+        _ => None
+    });
+    assert!(!client.is_running());
+}
+
+#[test]
+fn ignores_other_files() {
+    let mut client = start(&[]);
+    client.did_open("notes.md", &Text::from("todo"));
+    client.did_open("main.rs", &Text::from("todo"));
+    assert_eq!(wait_for_diagnostics(&mut client), vec![(pos(0, 0), pos(0, 4))]);
+    assert_eq!(client.hover("notes.md", pos(0, 0)), None);
+}
+
+#[test]
+fn close() {
+    let mut client = start(&[]);
+    client.did_open("main.rs", &Text::from("todo"));
+    assert_eq!(wait_for_diagnostics(&mut client), vec![(pos(0, 0), pos(0, 4))]);
+    client.did_close("main.rs");
+    assert_eq!(wait_for_diagnostics(&mut client), vec![]);
+    assert_eq!(client.hover("main.rs", pos(0, 0)), None);
+}
@@ -0,0 +1,350 @@
+use {
+    crate::makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+    std::cmp::Ordering,
+};
+
+/// A position in a text, mirroring `code_editor::text::Position`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, SerBin, DeBin)]
+pub struct TextPosition {
+    pub line_index: usize,
+    pub byte_index: usize,
+}
+
+/// The length of a span of text, mirroring `code_editor::text::Length`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, SerBin, DeBin)]
+pub struct TextLength {
+    pub line_count: usize,
+    pub byte_count: usize,
+}
+
+/// A single change to a text, mirroring `code_editor::text::Change`. Inserted text uses `\n` as
+/// line separator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, SerBin, DeBin)]
+pub enum TextChange {
+    Insert(TextPosition, String),
+    Delete(TextPosition, TextLength),
+}
+
+/// A sequence of changes, each of which applies to the text as left by the previous one.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq, SerBin, DeBin)]
+pub struct TextDelta {
+    pub changes: Vec<TextChange>,
+}
+
+impl TextPosition {
+    fn add(self, length: TextLength) -> Self {
+        if length.line_count == 0 {
+            Self {
+                line_index: self.line_index,
+                byte_index: self.byte_index + length.byte_count,
+            }
+        }
+        else {
+            Self {
+                line_index: self.line_index + length.line_count,
+                byte_index: length.byte_count,
+            }
+        }
+    }
+
+    fn sub(self, other: Self) -> TextLength {
+        if self.line_index == other.line_index {
+            TextLength {
+                line_count: 0,
+                byte_count: self.byte_index - other.byte_index,
+            }
+        }
+        else {
+            TextLength {
+                line_count: self.line_index - other.line_index,
+                byte_count: self.byte_index,
+            }
+        }
+    }
+
+    // where this position ends up after the given change. positions at the point of an insert
+    // stay in front of the inserted text unless after_insert is set
+    fn apply_change(self, change: &TextChange, after_insert: bool) -> Self {
+        match change {
+            TextChange::Insert(point, text) => match self.cmp(point) {
+                Ordering::Less => self,
+                Ordering::Equal if !after_insert => self,
+                _ => point.add(text_length(text)).add(self.sub(*point)),
+            },
+            TextChange::Delete(start, length) => {
+                let end = start.add(*length);
+                if self <= *start {
+                    self
+                }
+                else if self >= end {
+                    start.add(self.sub(end))
+                }
+                else {
+                    *start
+                }
+            }
+        }
+    }
+}
+
+impl TextLength {
+    pub fn is_empty(&self) -> bool {
+        self.line_count == 0 && self.byte_count == 0
+    }
+}
+
+pub fn text_length(text: &str) -> TextLength {
+    match text.rfind('\n') {
+        Some(last) => TextLength {
+            line_count: text.matches('\n').count(),
+            byte_count: text.len() - last - 1,
+        },
+        None => TextLength {
+            line_count: 0,
+            byte_count: text.len(),
+        }
+    }
+}
+
+impl TextChange {
+    /// Applies this change to a text stored as lines. Changes that don't fit the text are
+    /// rejected and leave it untouched.
+    pub fn apply(&self, lines: &mut Vec<String>) -> Result<(), String> {
+        match self {
+            TextChange::Insert(point, text) => {
+                check_position(lines, *point) ?;
+                let line = &mut lines[point.line_index];
+                let tail = line.split_off(point.byte_index);
+                let mut new_lines = text.split('\n');
+                line.push_str(new_lines.next().unwrap());
+                let mut index = point.line_index;
+                for new_line in new_lines {
+                    index += 1;
+                    lines.insert(index, new_line.to_string());
+                }
+                lines[index].push_str(&tail);
+            }
+            TextChange::Delete(start, length) => {
+                let end = start.add(*length);
+                check_position(lines, *start) ?;
+                check_position(lines, end) ?;
+                if end < *start {
+                    return Err(format!("invalid delete range {:?} {:?}", start, length))
+                }
+                let tail = lines[end.line_index][end.byte_index..].to_string();
+                lines[start.line_index].truncate(start.byte_index);
+                lines[start.line_index].push_str(&tail);
+                lines.drain(start.line_index + 1..end.line_index + 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            TextChange::Insert(_, text) => text.is_empty(),
+            TextChange::Delete(_, length) => length.is_empty(),
+        }
+    }
+}
+
+fn check_position(lines: &[String], position: TextPosition) -> Result<(), String> {
+    match lines.get(position.line_index) {
+        Some(line) if line.is_char_boundary(position.byte_index) => Ok(()),
+        _ => Err(format!("position {:?} is outside of the text", position))
+    }
+}
+
+impl TextDelta {
+    pub fn new(changes: Vec<TextChange>) -> Self {
+        Self {changes}
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// A delta that turns `old` into `new` by replacing what lies between their common prefix and
+    /// common suffix.
+    pub fn replace(old: &str, new: &str) -> Self {
+        let mut prefix = old.bytes().zip(new.bytes()).take_while( | (a, b) | a == b).count();
+        while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let mut suffix = old.bytes().rev().zip(new.bytes().rev()).take(max_suffix).take_while( | (a, b) | a == b).count();
+        while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+            suffix -= 1;
+        }
+        let start = TextPosition {
+            line_index: old[..prefix].matches('\n').count(),
+            byte_index: prefix - old[..prefix].rfind('\n').map_or(0, | index | index + 1),
+        };
+        let deleted = &old[prefix..old.len() - suffix];
+        let inserted = &new[prefix..new.len() - suffix];
+        let mut changes = Vec::new();
+        if !deleted.is_empty() {
+            changes.push(TextChange::Delete(start, text_length(deleted)));
+        }
+        if !inserted.is_empty() {
+            changes.push(TextChange::Insert(start, inserted.to_string()));
+        }
+        Self {changes}
+    }
+
+    /// Applies all changes in order. On error the text may be partially changed.
+    pub fn apply(&self, lines: &mut Vec<String>) -> Result<(), String> {
+        for change in &self.changes {
+            change.apply(lines) ?;
+        }
+        Ok(())
+    }
+
+    /// Transforms two deltas that were made against the same text. `other` is the one that was
+    /// applied first, so its inserts win ties. Returns `self` rewritten to apply after `other`,
+    /// and `other` rewritten to apply after `self`. Applying either pair gives the same text.
+    pub fn transform(self, other: Self) -> (Self, Self) {
+        let (a, b) = transform_changes(self.changes, other.changes);
+        (Self {changes: a}, Self {changes: b})
+    }
+}
+
+fn transform_changes(a: Vec<TextChange>, b: Vec<TextChange>) -> (Vec<TextChange>, Vec<TextChange>) {
+    if a.is_empty() || b.is_empty() {
+        return (a, b)
+    }
+    if a.len() > 1 {
+        // every change in a applies after the previous one, so b moves along as we go
+        let mut a = a;
+        let rest = a.split_off(1);
+        let (mut a, b) = transform_changes(a, b);
+        let (rest, b) = transform_changes(rest, b);
+        a.extend(rest);
+        return (a, b)
+    }
+    if b.len() > 1 {
+        let mut b = b;
+        let rest = b.split_off(1);
+        let (a, mut b) = transform_changes(a, b);
+        let (a, rest) = transform_changes(a, rest);
+        b.extend(rest);
+        return (a, b)
+    }
+    let (a, b) = (&a[0], &b[0]);
+    let mut a_out = transform_change(a, b, true);
+    let mut b_out = transform_change(b, a, false);
+    a_out.retain( | change | !change.is_empty());
+    b_out.retain( | change | !change.is_empty());
+    (a_out, b_out)
+}
+
+// rewrites change so it applies after other. a delete that spans an insert is split in two so
+// the inserted text survives
+fn transform_change(change: &TextChange, other: &TextChange, after_insert: bool) -> Vec<TextChange> {
+    match (change, other) {
+        (TextChange::Insert(point, text), _) => {
+            vec![TextChange::Insert(point.apply_change(other, after_insert), text.clone())]
+        }
+        (TextChange::Delete(start, length), TextChange::Insert(point, text)) => {
+            let end = start.add(*length);
+            if start < point && point < &end {
+                let inserted_end = point.add(text_length(text));
+                vec![
+                    TextChange::Delete(inserted_end, end.sub(*point)),
+                    TextChange::Delete(*start, point.sub(*start)),
+                ]
+            }
+            else {
+                vec![TextChange::Delete(start.apply_change(other, true), *length)]
+            }
+        }
+        (TextChange::Delete(start, length), TextChange::Delete(..)) => {
+            let end = start.add(*length);
+            let new_start = start.apply_change(other, true);
+            let new_end = end.apply_change(other, true);
+            vec![TextChange::Delete(new_start, new_end.sub(new_start))]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line_index: usize, byte_index: usize) -> TextPosition {
+        TextPosition {line_index, byte_index}
+    }
+
+    fn len(line_count: usize, byte_count: usize) -> TextLength {
+        TextLength {line_count, byte_count}
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map( | line | line.to_string()).collect()
+    }
+
+    // applies a then b' and b then a' and checks both end up at expected
+    fn check(text: &str, a: Vec<TextChange>, b: Vec<TextChange>, expected: &str) {
+        let (a, b) = (TextDelta::new(a), TextDelta::new(b));
+        let (a_after_b, b_after_a) = a.clone().transform(b.clone());
+        let mut left = lines(text);
+        a.apply(&mut left).unwrap();
+        b_after_a.apply(&mut left).unwrap();
+        let mut right = lines(text);
+        b.apply(&mut right).unwrap();
+        a_after_b.apply(&mut right).unwrap();
+        assert_eq!(left.join("\n"), expected);
+        assert_eq!(right.join("\n"), expected);
+    }
+
+    #[test]
+    fn apply_changes() {
+        let mut text = lines("abc\ndef");
+        TextChange::Insert(pos(0, 1), "X\nY".into()).apply(&mut text).unwrap();
+        assert_eq!(text.join("\n"), "aX\nYbc\ndef");
+        TextChange::Delete(pos(0, 1), len(2, 1)).apply(&mut text).unwrap();
+        assert_eq!(text.join("\n"), "aef");
+        assert!(TextChange::Delete(pos(0, 2), len(1, 0)).apply(&mut text).is_err());
+        assert!(TextChange::Insert(pos(3, 0), "x".into()).apply(&mut text).is_err());
+    }
+
+    #[test]
+    fn transform_converges() {
+        // inserts at the same point, the change applied first goes first
+        check("ab", vec![TextChange::Insert(pos(0, 1), "1".into())], vec![TextChange::Insert(pos(0, 1), "2".into())], "a21b");
+        // an insert inside a concurrently deleted range survives
+        check("abcdef", vec![TextChange::Delete(pos(0, 1), len(0, 4))], vec![TextChange::Insert(pos(0, 3), "X\nY".into())], "aX\nYf");
+        // overlapping deletes
+        check("abcdef", vec![TextChange::Delete(pos(0, 1), len(0, 3))], vec![TextChange::Delete(pos(0, 2), len(0, 3))], "af");
+        // deltas with several changes on several lines
+        check(
+            "one\ntwo\nthree",
+            vec![TextChange::Insert(pos(0, 3), "!".into()), TextChange::Delete(pos(1, 0), len(1, 0))],
+            vec![TextChange::Insert(pos(2, 0), ">".into()), TextChange::Delete(pos(0, 0), len(0, 1))],
+            "ne!\n>three"
+        );
+    }
+
+    #[test]
+    fn replace_text() {
+        for (old, new) in [
+            ("abc\ndef", "abc\ndef"),
+            ("abc\ndef", "abX\nYef"),
+            ("fn a() {}\n", "fn a() {\n    b();\n}\n"),
+            ("aaa", "aa"),
+            ("", "new\ntext"),
+            ("old\ntext", ""),
+            ("é\nà", "è\nà"),
+        ] {
+            let delta = TextDelta::replace(old, new);
+            let mut text = lines(old);
+            delta.apply(&mut text).unwrap();
+            assert_eq!(text.join("\n"), new);
+        }
+        assert!(TextDelta::replace("same", "same").is_empty());
+        assert_eq!(TextDelta::replace("one\ntwo", "one\nthree").changes, vec![
+            TextChange::Delete(pos(1, 1), len(0, 2)),
+            TextChange::Insert(pos(1, 1), "hree".into()),
+        ]);
+    }
+}
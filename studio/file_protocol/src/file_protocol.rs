@@ -2,6 +2,7 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+        delta::TextDelta,
     },
 };
 
@@ -36,10 +37,17 @@ pub enum FileRequest {
     /// If the client is the first participant for the file, this also causes the file to be opened
     /// on the server.
     OpenFile(String, u64),
-    /// Requests the collab server to apply the given delta to the given revision of the file with
-    /// the given id.
+    /// Requests the collab server to write the given text to the file with the given path. If the
+    /// file is open and the client is not a participant, the text is applied to the collaboration
+    /// session as a delta first. A participant can only save the text of the newest revision.
     SaveFile(String, String, u64),
-
+    /// Requests the collab server to apply the given delta to the given revision of the file with
+    /// the given path.
+    ApplyDelta(String, u64, TextDelta),
+    /// Requests the collab server to remove the client as a participant from the file with the
+    /// given path. If the client is the last participant for the file, this also causes the file
+    /// to be closed on the server.
+    CloseFile(String),
 }
 
 /// A type for representing either a response or a notification from the collab server.
@@ -57,11 +65,18 @@ pub enum FileResponse {
     /// The result of requesting the collab server to return its file tree.
     LoadFileTree(Result<FileTreeData, FileError>),
     /// The result of requesting the collab server to add the client as a participant to the file
-    /// with the given id.
-    OpenFile(Result<(String, String, u64), FileError>),
-    /// The result of requesting the collab server to apply a delta to a revision of the file with
-    /// the given id.
+    /// with the given id. Contains the path, text, id and current revision of the file.
+    OpenFile(Result<(String, String, u64, u64), FileError>),
+    /// The result of requesting the collab server to save the file with the given id. Contains
+    /// the path, the old and the new contents on disk, and the id.
     SaveFile(Result<(String,String,String, u64), FileError>),
+    /// The result of requesting the collab server to apply a delta to a revision of the file with
+    /// the given path. Contains the path and the revision the delta produced, or the path and the
+    /// reason the delta was rejected.
+    ApplyDelta(Result<(String, u64), (String, FileError)>),
+    /// The result of requesting the collab server to remove the client as a participant from the
+    /// file with the given path.
+    CloseFile(Result<String, FileError>),
 }
 
 /// A type for representing data about a file tree.
@@ -97,16 +112,18 @@ pub struct DirectoryEntry {
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileNotification {
     FileChangedOnDisk,
-    // Notifies the client that another client applied the given delta to the file with the given
-    // id. This is only sent for files for which the client is a participant.
-   // DeltaWasApplied(TextFileId),
+    /// Notifies the client that another client applied the given delta to the file with the given
+    /// path, producing the given revision. The delta applies to the revision before it. This is
+    /// only sent for files for which the client is a participant.
+    DeltaWasApplied(String, u64, TextDelta),
 }
 
 /// A type for representing errors from the collab server.
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileError {
     Unknown(String),
-    CannotOpen(String)
+    CannotOpen(String),
+    InvalidDelta(String),
 }
 
 /// An identifier for files on the collab server.
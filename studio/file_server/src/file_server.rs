@@ -8,14 +8,16 @@ use {
             FileNotification,
             FileRequest,
             FileResponse,
+            TextDelta,
         },
     },
     std::{
         cmp::Ordering,
+        collections::HashMap,
         fmt,
         fs,
         path::{Path, PathBuf},
-        sync::{Arc, RwLock},
+        sync::{Arc, Mutex, RwLock},
     },
 };
 
@@ -33,6 +35,7 @@ impl FileServer {
             next_connection_id: 0,
             shared: Arc::new(RwLock::new(Shared {
                 root_path: root_path.into(),
+                open_files: HashMap::new(),
             })),
         }
     }
@@ -45,9 +48,9 @@ impl FileServer {
         let connection_id = ConnectionId(self.next_connection_id);
         self.next_connection_id += 1;
         FileServerConnection {
-            _connection_id:connection_id,
+            connection_id,
             shared: self.shared.clone(),
-            _notification_sender: notification_sender
+            notification_sender
         }
     }
 }
@@ -55,11 +58,11 @@ impl FileServer {
 /// A connection to a collab server.
 pub struct FileServerConnection {
     // The id for this connection.
-    _connection_id: ConnectionId,
+    connection_id: ConnectionId,
     // State is shared between every connection.
     shared: Arc<RwLock<Shared >>,
     // Used to send notifications for this connection.
-    _notification_sender: Box<dyn NotificationSender>,
+    notification_sender: Box<dyn NotificationSender>,
 }
 
 impl FileServerConnection {
@@ -74,6 +77,8 @@ impl FileServerConnection {
             FileRequest::LoadFileTree {with_data} => FileResponse::LoadFileTree(self.load_file_tree(with_data)),
             FileRequest::OpenFile(path,id) => FileResponse::OpenFile(self.open_file(path, id)),
             FileRequest::SaveFile(path, delta, id) => FileResponse::SaveFile(self.save_file(path, delta, id)),
+            FileRequest::ApplyDelta(path, revision, delta) => FileResponse::ApplyDelta(self.apply_delta(path, revision, delta)),
+            FileRequest::CloseFile(path) => FileResponse::CloseFile(self.close_file(path)),
         }
     }
     
@@ -165,25 +170,84 @@ impl FileServerConnection {
     }
     
     // Handles an `OpenFile` request.
-    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64), FileError> {
+    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64, u64), FileError> {
         let path = self.make_full_path(&child_path);
+        let mut shared = self.shared.write().unwrap();
         
-        let bytes = fs::read(&path).map_err(
-            | error | FileError::Unknown(error.to_string())
-        ) ?;
-        // Converts the file contents to a `Text`. This is necessarily a lossy conversion
-        // because `Text` assumes everything is UTF-8 encoded, and this isn't always the
-        // case for files on disk (is this a problem?)
-        /*let text: Text = Text::from_lines(String::from_utf8_lossy(&bytes)
-            .lines()
-            .map( | line | line.chars().collect::<Vec<_ >> ())
-            .collect::<Vec<_ >>());*/
+        if !shared.open_files.contains_key(&child_path) {
+            let bytes = fs::read(&path).map_err(
+                | error | FileError::Unknown(error.to_string())
+            ) ?;
+            // Converts the file contents to lines. This is necessarily a lossy conversion
+            // because the editor assumes everything is UTF-8 encoded, and this isn't always the
+            // case for files on disk (is this a problem?)
+            let lines = String::from_utf8_lossy(&bytes).split('\n').map( | line | line.to_string()).collect();
+            shared.open_files.insert(child_path.clone(), OpenFile {
+                lines,
+                base_revision: 0,
+                history: Vec::new(),
+                participants: HashMap::new(),
+            });
+        }
         
-        let text = String::from_utf8_lossy(&bytes);
-        Ok((child_path, text.to_string(), id))
+        let file = shared.open_files.get_mut(&child_path).unwrap();
+        let revision = file.revision();
+        file.participants.insert(self.connection_id, Participant {
+            revision,
+            notification_sender: Mutex::new(self.notification_sender.clone()),
+        });
+        Ok((child_path, file.lines.join("\n"), id, revision))
     }
     
     // Handles an `ApplyDelta` request.
+    fn apply_delta(&self, child_path: String, revision: u64, delta: TextDelta) -> Result<(String, u64), (String, FileError)> {
+        match self.apply_delta_to_file(&child_path, revision, delta) {
+            Ok(new_revision) => Ok((child_path, new_revision)),
+            Err(error) => Err((child_path, error))
+        }
+    }
+    
+    fn apply_delta_to_file(&self, child_path: &str, revision: u64, delta: TextDelta) -> Result<u64, FileError> {
+        let mut shared = self.shared.write().unwrap();
+        let file = match shared.open_files.get_mut(child_path) {
+            Some(file) if file.participants.contains_key(&self.connection_id) => file,
+            _ => return Err(FileError::Unknown(format!("{} is not open", child_path)))
+        };
+        if revision < file.base_revision || revision > file.revision() {
+            return Err(FileError::InvalidDelta(format!(
+                "{} revision {} is not in {}..={}",
+                child_path,
+                revision,
+                file.base_revision,
+                file.revision()
+            )))
+        }
+        
+        // The delta was made against an older revision. Transform it against every delta that
+        // was applied since, so it can be applied to the newest revision.
+        let mut delta = delta;
+        let unseen = (revision - file.base_revision) as usize;
+        for (_, applied_delta) in &file.history[unseen..] {
+            delta = delta.transform(applied_delta.clone()).0;
+        }
+        let new_revision = file.push_delta(child_path, self.connection_id, delta) ?;
+        
+        // Sending a delta based on a revision confirms the client has seen it.
+        file.participants.get_mut(&self.connection_id).unwrap().revision = revision;
+        file.prune_history();
+        Ok(new_revision)
+    }
+    
+    // Handles a `CloseFile` request.
+    fn close_file(&self, child_path: String) -> Result<String, FileError> {
+        let mut shared = self.shared.write().unwrap();
+        if !shared.remove_participant(&child_path, self.connection_id) {
+            return Err(FileError::Unknown(format!("{} is not open", child_path)))
+        }
+        Ok(child_path)
+    }
+    
+    // Handles a `SaveFile` request.
     fn save_file(
         &self,
         child_path: String,
@@ -195,6 +259,21 @@ impl FileServerConnection {
         let old_content = String::from_utf8_lossy(&fs::read(&path).map_err(
             | error | FileError::Unknown(error.to_string())
         ) ?).to_string();
+        
+        if let Some(file) = self.shared.write().unwrap().open_files.get_mut(&child_path) {
+            let text = file.lines.join("\n");
+            if text != new_content {
+                // A participant saving other text than the newest revision is behind, or has
+                // changes it did not send yet. Writing either would lose someone's changes.
+                if file.participants.contains_key(&self.connection_id) {
+                    return Err(FileError::Unknown(format!("{} does not match the newest revision", child_path)))
+                }
+                // Otherwise the new text goes into the collaboration session, so the participants
+                // are told about it.
+                file.push_delta(&child_path, self.connection_id, TextDelta::replace(&text, &new_content)) ?;
+                file.prune_history();
+            }
+        }
 
         fs::write(&path, &new_content).map_err(
             | error | FileError::Unknown(error.to_string())
@@ -204,6 +283,16 @@ impl FileServerConnection {
     }
 }
 
+impl Drop for FileServerConnection {
+    fn drop(&mut self) {
+        let mut shared = self.shared.write().unwrap();
+        let paths: Vec<String> = shared.open_files.keys().cloned().collect();
+        for path in paths {
+            shared.remove_participant(&path, self.connection_id);
+        }
+    }
+}
+
 /// A trait for sending notifications over a connection.
 pub trait NotificationSender: Send {
     /// This method is necessary to create clones of boxed trait objects.
@@ -239,6 +328,81 @@ impl fmt::Debug for dyn NotificationSender {
 #[derive(Debug)]
 struct Shared {
     root_path: PathBuf,
+    // The collaboration sessions for all files that have at least one participant.
+    open_files: HashMap<String, OpenFile>,
+}
+
+impl Shared {
+    // Removes a participant from a file, closing the file if it was the last one. Returns false if
+    // the connection was not a participant.
+    fn remove_participant(&mut self, path: &str, connection_id: ConnectionId) -> bool {
+        let file = match self.open_files.get_mut(path) {
+            Some(file) => file,
+            None => return false
+        };
+        if file.participants.remove(&connection_id).is_none() {
+            return false
+        }
+        if file.participants.is_empty() {
+            self.open_files.remove(path);
+        }
+        else {
+            file.prune_history();
+        }
+        true
+    }
+}
+
+// The collaboration session for an open file.
+#[derive(Debug)]
+struct OpenFile {
+    lines: Vec<String>,
+    // The revision before the oldest delta in the history.
+    base_revision: u64,
+    // The deltas from the base revision to the newest revision, and who applied them.
+    history: Vec<(ConnectionId, TextDelta)>,
+    participants: HashMap<ConnectionId, Participant>,
+}
+
+impl OpenFile {
+    fn revision(&self) -> u64 {
+        self.base_revision + self.history.len() as u64
+    }
+    
+    // Applies a delta to the newest revision, and notifies every participant except the one it
+    // came from. Returns the revision the delta produced.
+    fn push_delta(&mut self, path: &str, connection_id: ConnectionId, delta: TextDelta) -> Result<u64, FileError> {
+        let mut lines = self.lines.clone();
+        delta.apply(&mut lines).map_err(FileError::InvalidDelta) ?;
+        self.lines = lines;
+        self.history.push((connection_id, delta.clone()));
+        let new_revision = self.revision();
+        for (participant_id, participant) in &self.participants {
+            if *participant_id != connection_id {
+                participant.notification_sender.lock().unwrap().send_notification(
+                    FileNotification::DeltaWasApplied(path.to_string(), new_revision, delta.clone())
+                );
+            }
+        }
+        Ok(new_revision)
+    }
+    
+    // Drops the deltas every participant has seen, nothing has to be transformed against them
+    // anymore.
+    fn prune_history(&mut self) {
+        let seen = self.participants.values().map( | participant | participant.revision).min().unwrap_or(self.revision());
+        let count = (seen - self.base_revision) as usize;
+        self.history.drain(..count);
+        self.base_revision = seen;
+    }
+}
+
+#[derive(Debug)]
+struct Participant {
+    // The newest revision this participant confirmed to have seen.
+    revision: u64,
+    // Senders are only Send, the mutex lets the shared state be Sync.
+    notification_sender: Mutex<Box<dyn NotificationSender>>,
 }
 
 /// An identifier for a connection.
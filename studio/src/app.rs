@@ -1,11 +1,15 @@
 use crate::{
     makepad_code_editor::code_editor::*,
+    makepad_code_editor::text::{Length, Position},
+    makepad_code_editor::search::SearchQuery,
     makepad_platform::*,
     makepad_draw::*,
     makepad_widgets::*,
     makepad_micro_serde::*,
     makepad_widgets::file_tree::*,
     file_system::file_system::*,
+    search::search::{Search, SearchAction},
+    lsp::lsp_client::{LspEvent, LspRequestId},
     build_manager::{
         run_view::*,
         log_list::{
@@ -14,6 +18,12 @@ use crate::{
         run_list::{
             RunListAction
         },
+        test_tree::{
+            TestTreeAction
+        },
+        widget_inspector::{
+            WidgetInspectorAction
+        },
         build_manager::{
             BuildManager,
             BuildManagerAction
@@ -23,6 +33,7 @@ use crate::{
 use std::fs::File;
 use std::io::Write;
 use std::env;
+use std::collections::HashMap;
 
 live_design!{
     import makepad_draw::shader::std::*;
@@ -33,6 +44,7 @@ live_design!{
     import makepad_studio_core::build_manager::run_view::RunView;
     import makepad_studio_core::build_manager::log_list::LogList;
     import makepad_studio_core::build_manager::run_list::RunList;
+    import makepad_studio_core::search::search::SearchView;
     
     Logo = <Button> {
         draw_icon: {
@@ -142,7 +154,7 @@ live_design!{
                 }
                 
                 log_tabs = Tabs {
-                    tabs: [log_list],
+                    tabs: [log_list, test_tree, widget_tree],
                     selected: 0
                 }
                 
@@ -192,6 +204,18 @@ live_design!{
                     kind: LogList
                 }
                 
+                test_tree = Tab {
+                    name: "Tests",
+                    closable: false,
+                    kind: FileTree
+                }
+                
+                widget_tree = Tab {
+                    name: "Widgets",
+                    closable: false,
+                    kind: FileTree
+                }
+                
                 CodeEditor = <CodeEditor> {}
                 EditFirst = <RectView> {
                     draw_bg: {color: #052329}
@@ -243,9 +267,7 @@ live_design!{
                 }
                 RunList = <RunList> {
                 }
-                Search = <RectView> {
-                    draw_bg: {color: #2}
-                }
+                Search = <SearchView> {}
                 RunView = <RunView> {}
                 FileTree = <FileTree> {}
                 LogList = <LogList> {}
@@ -259,6 +281,13 @@ pub struct App {
     #[live] ui: WidgetRef,
     #[live] build_manager: BuildManager,
     #[rust] file_system: FileSystem,
+    #[rust] search: Search,
+    // a location to show once the document for the tab has loaded
+    #[rust] pending_jump: Option<(LiveId, Position, Length)>,
+    // language server requests waiting for an answer, with the tab and position they were made for
+    #[rust] lsp_requests: HashMap<LspRequestId, (LiveId, Position)>,
+    // the files of the restored code editor tabs, until the file tree is there to open them
+    #[rust] restored_tab_files: HashMap<LiveId, String>,
 }
 
 impl LiveHook for App {
@@ -269,6 +298,7 @@ impl LiveHook for App {
         crate::build_manager::run_list::live_design(cx);
         crate::build_manager::log_list::live_design(cx);
         crate::build_manager::run_view::live_design(cx);
+        crate::search::search::live_design(cx);
         // for macos
         cx.start_stdin_service();
     }
@@ -285,9 +315,14 @@ impl LiveHook for App {
         let root_path = env::current_dir().unwrap().join(root);
         
         self.file_system.init(cx, &root_path);
+        self.search.init(&root_path);
         self.build_manager.init(cx, &root_path);
+        if let Some(lsp) = self.build_manager.config.lsp.clone() {
+            self.file_system.start_lsp(&root_path, &lsp.command, &lsp.extensions);
+        }
         self.build_manager.discover_external_ip(cx);
         self.build_manager.start_http_server();
+        self.load_state(cx);
         //self.file_system.request_open_file(live_id!(file1), "examples/news_feed/src/app.rs".into());
     }
 }
@@ -295,6 +330,76 @@ impl LiveHook for App {
 app_main!(App);
 
 impl App {
+    fn load_state(&mut self, cx: &mut Cx) {
+        let contents = if let Ok(contents) = std::fs::read_to_string("makepad_state.ron") {
+            contents
+        }
+        else {
+            return
+        };
+        let mut state = match PersistentState::deserialize_ron(&contents) {
+            Ok(state) => state,
+            Err(err) => {
+                log!("Cannot parse makepad_state.ron {:?}", err);
+                return
+            }
+        };
+        let dock = self.ui.dock(id!(dock));
+        // layouts saved before the test and widget panels existed get them where the default layout has them
+        let defaults = match dock.borrow() {
+            Some(dock) => dock.to_store_items(),
+            None => return
+        };
+        for tab_id in [live_id!(test_tree), live_id!(widget_tree)] {
+            if !add_missing_tab(&mut state.dock_items, &defaults, tab_id) {
+                log!("Cannot add {} to the saved layout, using the default one", tab_id);
+                return
+            }
+        }
+        // the rest of studio relies on these existing
+        let has_item = | id: LiveId | state.dock_items.iter().any( | item | match item {
+            DockItemStore::Splitter {id: item_id, ..} |
+            DockItemStore::Tabs {id: item_id, ..} |
+            DockItemStore::Tab {id: item_id, ..} => item_id.0 == id
+        });
+        if !has_item(live_id!(edit_tabs)) || !has_item(live_id!(run_first)) {
+            return
+        }
+        if dock.from_store_item(cx, &state.dock_items) {
+            self.restored_tab_files = state.tab_files.into_iter().map( | tab | (tab.tab_id.0, tab.path)).collect();
+        }
+    }
+    
+    // layouts saved before the tab files were stored derive the file node id from the tab id,
+    // which only holds for the first tab of a file
+    fn open_restored_tabs(&mut self, cx: &mut Cx) {
+        let dock = self.ui.dock(id!(dock));
+        let mut tab_ids = Vec::new();
+        if let Some(mut dock) = dock.borrow_mut() {
+            for (tab_id, (kind, _)) in dock.items().iter() {
+                if *kind == live_id!(CodeEditor) {
+                    tab_ids.push(*tab_id);
+                }
+            }
+        }
+        for tab_id in tab_ids {
+            if self.file_system.tab_id_to_file_node_id.contains_key(&tab_id) {
+                continue;
+            }
+            let file_id = match self.restored_tab_files.remove(&tab_id) {
+                Some(path) => self.file_system.path_to_file_node_id(&path).unwrap_or(FileNodeId(LiveId(0))),
+                None => FileNodeId(tab_id)
+            };
+            if self.file_system.file_nodes.get(&file_id).map_or(false, | node | node.is_file()) {
+                self.file_system.request_open_file(tab_id, file_id);
+            }
+            else {
+                dock.close_tab(cx, tab_id);
+            }
+        }
+        self.file_system.ensure_unique_tab_names(cx, &dock);
+    }
+    
     pub fn open_code_file_by_path(&mut self, cx: &mut Cx, path: &str) {
         if let Some(file_id) = self.file_system.path_to_file_node_id(&path) {
             let dock = self.ui.dock(id!(dock));            
@@ -304,6 +409,111 @@ impl App {
             self.file_system.ensure_unique_tab_names(cx, &dock)
         }
     }
+    
+    // selects the tab for a file, or opens one, and moves the cursor to the location
+    fn jump_to_location(&mut self, cx: &mut Cx, file_name: &str, start: Position, length: Length) {
+        let dock = self.ui.dock(id!(dock));
+        if let Some(file_id) = self.file_system.path_to_file_node_id(file_name) {
+            if let Some(tab_id) = self.file_system.file_node_id_to_tab_id(file_id){
+                dock.select_tab(cx, tab_id);
+                // ok lets scroll into view
+                if let Some(mut editor) = dock.item(tab_id).as_code_editor().borrow_mut() {
+                    if let Some(session) = self.file_system.get_session_mut(tab_id) {
+                        editor.set_cursor_and_scroll(cx, start, length, session);
+                        editor.set_key_focus(cx);
+                        return
+                    }
+                }
+                self.pending_jump = Some((tab_id, start, length));
+            }
+            else{
+                // lets open the editor
+                let tab_id = dock.unique_tab_id(file_id.0.0);
+                self.file_system.request_open_file(tab_id, file_id);
+                // lets add a file tab 'somewhere'
+                dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(CodeEditor), "".to_string(), TabClosable::Yes);
+                // lets scan the entire doc for duplicates
+                self.file_system.ensure_unique_tab_names(cx, &dock);
+                self.pending_jump = Some((tab_id, start, length));
+            }
+        }
+    }
+    
+    fn send_lsp_request(&mut self, tab_id: LiveId, action: CodeEditorAction) {
+        let Some(file_id) = self.file_system.tab_id_to_file_node_id.get(&tab_id).cloned() else {
+            return
+        };
+        // the server has to know about the latest edits before it can answer about a position
+        self.file_system.handle_sessions();
+        let path = self.file_system.file_node_path(file_id);
+        let Some(lsp) = &mut self.file_system.lsp else {
+            return
+        };
+        let (request_id, position) = match action {
+            CodeEditorAction::Hover(position) => (lsp.hover(&path, position), position),
+            CodeEditorAction::GoToDefinition(position) => (lsp.definition(&path, position), position),
+            CodeEditorAction::Complete(position) => (lsp.completion(&path, position), position),
+            CodeEditorAction::TextDidChange => return
+        };
+        if let Some(request_id) = request_id {
+            self.lsp_requests.insert(request_id, (tab_id, position));
+        }
+    }
+    
+    fn handle_lsp_event(&mut self, cx: &mut Cx, event: LspEvent) {
+        let request_id = match &event {
+            LspEvent::Hover {request_id, ..} |
+            LspEvent::Definition {request_id, ..} |
+            LspEvent::Completion {request_id, ..} => *request_id,
+            LspEvent::Diagnostics {..} | LspEvent::Stopped => return
+        };
+        let Some((tab_id, position)) = self.lsp_requests.remove(&request_id) else {
+            return
+        };
+        if let LspEvent::Definition {locations, ..} = event {
+            if let Some(location) = locations.first() {
+                self.jump_to_location(cx, &location.path, location.start, location.end - location.start);
+            }
+            return
+        }
+        let editor = self.ui.dock(id!(dock)).item(tab_id).as_code_editor();
+        let Some(mut editor) = editor.borrow_mut() else {
+            return
+        };
+        let Some(session) = self.file_system.get_session_mut(tab_id) else {
+            return
+        };
+        match event {
+            LspEvent::Hover {text: Some(text), ..} => editor.show_hover(cx, session, position, &text),
+            LspEvent::Hover {text: None, ..} => editor.hide_hover(cx, session),
+            LspEvent::Completion {items, ..} => editor.show_completions(cx, session, items.into_iter().map( | item | CompletionItem {
+                label: item.label,
+                detail: item.detail,
+                insert_text: item.insert_text,
+            }).collect()),
+            _ => ()
+        }
+    }
+    
+    fn search_query(&self, cx: &Cx) -> SearchQuery {
+        SearchQuery {
+            text: self.ui.text_input(id!(search_input)).text(),
+            is_regex: self.ui.check_box(id!(search_regex)).selected(cx),
+            case_sensitive: self.ui.check_box(id!(search_case)).selected(cx),
+            whole_word: self.ui.check_box(id!(search_word)).selected(cx),
+        }
+    }
+    
+    fn start_search(&mut self, cx: &mut Cx) {
+        let query = self.search_query(cx);
+        self.search.start(&self.file_system, query);
+        self.redraw_search(cx);
+    }
+    
+    fn redraw_search(&mut self, cx: &mut Cx) {
+        self.ui.label(id!(search_status)).set_text_and_redraw(cx, &self.search.status());
+        self.ui.portal_list(id!(search_results)).redraw(cx);
+    }
 }
 
 impl AppMain for App {
@@ -314,6 +524,9 @@ impl AppMain for App {
         let file_tree = self.ui.file_tree(id!(file_tree));
         let log_list = self.ui.portal_list(id!(log_list));
         let run_list = self.ui.flat_list(id!(run_list));
+        let search_results = self.ui.portal_list(id!(search_results));
+        let test_tree = self.ui.file_tree(id!(test_tree));
+        let widget_tree = self.ui.file_tree(id!(widget_tree));
         
         if let Event::Draw(event) = event {
             
@@ -339,10 +552,23 @@ impl AppMain for App {
                 else if let Some(mut run_list) = run_list.has_widget(&next).borrow_mut() {
                     self.build_manager.draw_run_list(cx, &mut *run_list);
                 }
+                else if let Some(mut test_tree) = test_tree.has_widget(&next).borrow_mut() {
+                    self.build_manager.draw_test_tree(cx, &mut *test_tree);
+                }
+                else if let Some(mut widget_tree) = widget_tree.has_widget(&next).borrow_mut() {
+                    self.build_manager.draw_widget_inspector(cx, &mut *widget_tree);
+                }
+                else if let Some(mut search_results) = search_results.has_widget(&next).borrow_mut() {
+                    self.search.draw_results(cx, &mut *search_results);
+                }
                 else if let Some(mut code_editor) = next.as_code_editor().borrow_mut() {
                     // lets fetch a session
                     let current_id = dock.drawing_item_id().unwrap();
                     if let Some(session) = self.file_system.get_session_mut(current_id) {
+                        if let Some((_, start, length)) = self.pending_jump.filter( | (tab_id, _, _) | *tab_id == current_id) {
+                            self.pending_jump = None;
+                            code_editor.set_cursor_and_scroll(cx, start, length, session);
+                        }
                         code_editor.draw(cx, session);
                     }
                 }
@@ -380,6 +606,7 @@ impl AppMain for App {
             match action {
                 FileSystemAction::TreeLoaded => {
                     file_tree.redraw(cx);
+                    self.open_restored_tabs(cx);
                     //self.open_code_file_by_path(cx, "examples/slides/src/app.rs");
                 }
                 FileSystemAction::RecompileNeeded => {
@@ -390,9 +617,18 @@ impl AppMain for App {
                     self.build_manager.clear_log(cx, &dock, &mut self.file_system);
                     log_list.redraw(cx);
                 }
+                FileSystemAction::Lsp(lsp_event) => {
+                    self.handle_lsp_event(cx, lsp_event);
+                }
             }
         }
         
+        if self.search.handle_event(cx, event) {
+            self.redraw_search(cx);
+        }
+        
+        self.build_manager.inspector.visible = dock.borrow_mut().unwrap().visible_items().any( | (item_id, _) | item_id == live_id!(widget_tree));
+        
         // lets iterate over the editors and handle events
         for (item_id, item) in dock.borrow_mut().unwrap().visible_items() {
             if let Some(mut run_view) = item.as_run_view().borrow_mut() {
@@ -406,6 +642,7 @@ impl AppMain for App {
                                 // lets write the file
                                 self.file_system.request_save_file(item_id)
                             }
+                            action => self.send_lsp_request(item_id, action)
                         }
                     }
 
@@ -435,6 +672,12 @@ impl AppMain for App {
                         run_view.handle_stdin_to_host(cx, &msg, run_view_id, &mut self.build_manager);
                     }
                 }
+                BuildManagerAction::RedrawTests => {
+                    test_tree.redraw(cx);
+                }
+                BuildManagerAction::RedrawWidgetTree => {
+                    widget_tree.redraw(cx);
+                }
                 BuildManagerAction::RedrawFile(file_id)=>{
                     self.file_system.redraw_view_by_file_id(cx, file_id, &dock);
                 }
@@ -485,28 +728,11 @@ impl AppMain for App {
             for action in self.build_manager.handle_log_list(cx, &log_list, item_id, item, &actions) {
                 match action {
                     LogListAction::JumpToError{file_name, start, length} => {
-                        // lets find a tab if we have it otherwise open it
-                        if let Some(file_id) = self.file_system.path_to_file_node_id(&file_name) {
-                            if let Some(tab_id) = self.file_system.file_node_id_to_tab_id(file_id){
-                                dock.select_tab(cx, tab_id);
-                                // ok lets scroll into view
-                                if let Some(mut editor) = dock.item(tab_id).as_code_editor().borrow_mut() {
-                                    if let Some(session) = self.file_system.get_session_mut(tab_id) {
-                                        editor.set_cursor_and_scroll(cx, start, length, session);
-                                        editor.set_key_focus(cx);
-                                    }
-                                }
-                            }
-                            else{
-                                // lets open the editor
-                                let tab_id = dock.unique_tab_id(file_id.0.0);
-                                self.file_system.request_open_file(tab_id, file_id);
-                                // lets add a file tab 'somewhere'
-                                dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(CodeEditor), "".to_string(), TabClosable::Yes);
-                                // lets scan the entire doc for duplicates
-                                self.file_system.ensure_unique_tab_names(cx, &dock)
-                            }
-                        }
+                        self.jump_to_location(cx, &file_name, start, length);
+                    }
+                    LogListAction::ApplyFix(fix) => {
+                        self.build_manager.apply_fix(&mut self.file_system, &fix);
+                        self.file_system.redraw_all_views(cx, &dock);
                     }
                     _ => ()
                 }
@@ -514,6 +740,51 @@ impl AppMain for App {
             }
         }
         
+        for action in self.build_manager.handle_test_tree(&test_tree, &actions) {
+            match action {
+                TestTreeAction::JumpToFailure{file_name, start, length} => {
+                    self.jump_to_location(cx, &file_name, start, length);
+                }
+                _ => ()
+            }
+        }
+        
+        for action in self.build_manager.handle_widget_inspector(&widget_tree, &actions) {
+            match action {
+                WidgetInspectorAction::JumpToSource{file_name, start, length} => {
+                    self.jump_to_location(cx, &file_name, start, length);
+                }
+                WidgetInspectorAction::Highlight(run_view_id) => {
+                    dock.item(run_view_id).redraw(cx);
+                }
+                _ => ()
+            }
+        }
+        
+        for (item_id, item) in search_results.items_with_actions(&actions) {
+            for action in self.search.handle_result_list(cx, item_id, item, &actions) {
+                match action {
+                    SearchAction::JumpTo{file_name, start, length} => {
+                        self.jump_to_location(cx, &file_name, start, length);
+                    }
+                    _ => ()
+                }
+            }
+        }
+        
+        if self.ui.text_input(id!(search_input)).changed(&actions).is_some()
+            || self.ui.check_box(id!(search_regex)).changed(&actions).is_some()
+            || self.ui.check_box(id!(search_case)).changed(&actions).is_some()
+            || self.ui.check_box(id!(search_word)).changed(&actions).is_some() {
+            self.start_search(cx);
+        }
+        
+        if self.ui.button(id!(search_replace_all)).clicked(&actions) {
+            let replacement = self.ui.text_input(id!(search_replace_input)).text();
+            self.search.replace_all(&mut self.file_system, &replacement);
+            self.start_search(cx);
+        }
+        
         if let Some(tab_id) = dock.clicked_tab_close(&actions) {
             dock.close_tab(cx, tab_id);
             if self.build_manager.handle_tab_close(tab_id) {
@@ -595,8 +866,25 @@ impl AppMain for App {
                 }
                 true 
             });
+            let mut tab_files = Vec::new();
+            for item in &dock_items {
+                if let DockItemStore::Tab{id, kind, ..} = item {
+                    if kind.0 != live_id!(CodeEditor) {
+                        continue
+                    }
+                    let path = match self.file_system.tab_id_to_file_node_id.get(&id.0) {
+                        Some(file_id) => self.file_system.path_to_file_node_id.iter()
+                            .find( | (_, path_id) | *path_id == file_id).map( | (path, _) | path.clone()),
+                        None => self.restored_tab_files.get(&id.0).cloned()
+                    };
+                    if let Some(path) = path {
+                        tab_files.push(TabFileStore{tab_id: LiveIdStore(id.0), path});
+                    }
+                }
+            }
             let state = PersistentState{
-                dock_items
+                dock_items,
+                tab_files
             };
             // alright lets save it to disk
             let saved = state.serialize_ron();
@@ -609,5 +897,108 @@ impl AppMain for App {
 
 #[derive(Clone, Debug, SerRon, DeRon)]
 struct PersistentState{
-    dock_items: Vec<DockItemStore>
+    dock_items: Vec<DockItemStore>,
+    #[default] tab_files: Vec<TabFileStore>
+}
+
+#[derive(Clone, Debug, SerRon, DeRon)]
+struct TabFileStore{
+    tab_id: LiveIdStore,
+    path: String
+}
+
+// adds a tab of defaults that a stored layout lacks, to the tabs that hold it in defaults
+// or else to the tabs holding one of its neighbours there. returns false when neither exists
+fn add_missing_tab(store: &mut Vec<DockItemStore>, defaults: &[DockItemStore], tab_id: LiveId) -> bool {
+    let item_id = | item: &DockItemStore | match item {
+        DockItemStore::Splitter {id, ..} |
+        DockItemStore::Tabs {id, ..} |
+        DockItemStore::Tab {id, ..} => id.0
+    };
+    if store.iter().any( | item | item_id(item) == tab_id) {
+        return true
+    }
+    let Some(tab) = defaults.iter().find( | item | matches!(item, DockItemStore::Tab {..}) && item_id(item) == tab_id)
+    else {
+        return false
+    };
+    let Some((parent_id, neighbours)) = defaults.iter().find_map( | item | match item {
+        DockItemStore::Tabs {id, tabs, ..} if tabs.iter().any( | tab | tab.0 == tab_id) => Some((id.0, tabs.clone())),
+        _ => None
+    })
+    else {
+        return false
+    };
+    let parent = store.iter().position( | item | matches!(item, DockItemStore::Tabs {id, ..} if id.0 == parent_id)).or_else( || {
+        store.iter().position( | item | matches!(item, DockItemStore::Tabs {tabs, ..} if tabs.iter().any( | tab | neighbours.iter().any( | n | n.0 == tab.0))))
+    });
+    let Some(parent) = parent
+    else {
+        return false
+    };
+    if let DockItemStore::Tabs {tabs, ..} = &mut store[parent] {
+        tabs.push(LiveIdStore(tab_id));
+    }
+    store.push(tab.clone());
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::makepad_widgets::splitter::{SplitterAxis, SplitterAlign};
+    
+    #[test]
+    fn add_missing_tabs() {
+        let tab = | id: LiveId | DockItemStore::Tab {id: LiveIdStore(id), name: String::new(), closable: false, kind: LiveIdStore(live_id!(FileTree))};
+        let tabs = | id: LiveId, ids: &[LiveId] | DockItemStore::Tabs {id: LiveIdStore(id), tabs: ids.iter().map( | id | LiveIdStore(*id)).collect(), selected: 0, closable: false};
+        let root = | a: LiveId, b: LiveId | DockItemStore::Splitter {id: LiveIdStore(live_id!(root)), axis: SplitterAxis::Vertical, align: SplitterAlign::Weighted(0.5), a: LiveIdStore(a), b: LiveIdStore(b)};
+        let defaults = vec![
+            root(live_id!(edit_tabs), live_id!(log_tabs)),
+            tabs(live_id!(edit_tabs), &[live_id!(edit_first)]),
+            tabs(live_id!(log_tabs), &[live_id!(log_list), live_id!(test_tree)]),
+            tab(live_id!(edit_first)),
+            tab(live_id!(log_list)),
+            tab(live_id!(test_tree)),
+        ];
+        let tabs_of = | store: &[DockItemStore], id: LiveId | -> Vec<LiveId> {
+            store.iter().find_map( | item | match item {
+                DockItemStore::Tabs {id: tabs_id, tabs, ..} if tabs_id.0 == id => Some(tabs.iter().map( | tab | tab.0).collect()),
+                _ => None
+            }).unwrap()
+        };
+        
+        // an old layout gets the tab where the defaults have it
+        let mut store = defaults.clone();
+        store.retain( | item | !matches!(item, DockItemStore::Tab {id, ..} if id.0 == live_id!(test_tree)));
+        store[2] = tabs(live_id!(log_tabs), &[live_id!(log_list)]);
+        assert!(add_missing_tab(&mut store, &defaults, live_id!(test_tree)));
+        assert_eq!(tabs_of(&store, live_id!(log_tabs)), vec![live_id!(log_list), live_id!(test_tree)]);
+        assert!(store.iter().any( | item | matches!(item, DockItemStore::Tab {id, ..} if id.0 == live_id!(test_tree))));
+        // tabs it has already are left alone
+        let len = store.len();
+        assert!(add_missing_tab(&mut store, &defaults, live_id!(test_tree)));
+        assert_eq!(store.len(), len);
+        
+        // when its tabs were merged away it goes next to its old neighbours
+        let mut store = vec![
+            root(live_id!(edit_tabs), live_id!(other_tabs)),
+            tabs(live_id!(edit_tabs), &[live_id!(edit_first), live_id!(log_list)]),
+            tabs(live_id!(other_tabs), &[]),
+            tab(live_id!(edit_first)),
+            tab(live_id!(log_list)),
+        ];
+        assert!(add_missing_tab(&mut store, &defaults, live_id!(test_tree)));
+        assert_eq!(tabs_of(&store, live_id!(edit_tabs)), vec![live_id!(edit_first), live_id!(log_list), live_id!(test_tree)]);
+        
+        // and without those there is no place for it
+        let mut store = vec![
+            root(live_id!(edit_tabs), live_id!(other_tabs)),
+            tabs(live_id!(edit_tabs), &[live_id!(edit_first)]),
+            tabs(live_id!(other_tabs), &[]),
+            tab(live_id!(edit_first))
+        ];
+        assert!(!add_missing_tab(&mut store, &defaults, live_id!(test_tree)));
+        assert!(!add_missing_tab(&mut store, &defaults, live_id!(nope)));
+    }
 }
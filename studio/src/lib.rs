@@ -4,6 +4,8 @@ pub mod app;
 pub use makepad_studio_core;
 pub use makepad_studio_core::build_manager;
 pub use makepad_studio_core::file_system;
+pub use makepad_studio_core::search;
+pub use makepad_studio_core::lsp;
 
 //pub use makepad_code_editor;
 pub use makepad_studio_core::makepad_code_editor;
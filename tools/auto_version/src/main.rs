@@ -1,7 +1,7 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use makepad_toml_parser::Toml;
+use makepad_toml_parser::{Toml, TomlDocument, TomlTable, toml_key};
 use makepad_digest::sha1;
 use makepad_base64::base64;
 use std::io::prelude::*;
@@ -68,20 +68,6 @@ fn main() {
         new_sha1: String,
     }
     
-    let target_deps = [
-        "dependencies.",
-        "target.wasm32-unknown-unknown.dependencies.",
-        "target.aarch64-apple-darwin.dependencies.",
-        "target.x86_64-apple-darwin.dependencies.",
-        "target.x86_64-apple-ios.dependencies.",
-        "target.aarch64-apple-ios-sim.dependencies.",
-        "target.aarch64-apple-ios.dependencies.",
-        "target.aarch64-unknown-linux-gnu.dependencies.",
-        "target.'cfg(windows)'.dependencies.makepad-futures-legacy.",
-        "target.'cfg(windows)'.dependencies.makepad-windows.",
-        "target.'cfg(windows)'.dependencies.windows-targets."
-    ];
-    
     let mut ver_crates = Vec::new();
     
     // iterate all found crates and build up version info/dep info
@@ -109,13 +95,11 @@ fn main() {
         let new_sha1 = String::from_utf8(base64::base64_encode(&data, &base64::BASE64_URL_SAFE)).unwrap();
         let mut deps = Vec::new();
         // scan our toml file for all dependencies
-        for key in toml.keys() {
-            for pref in target_deps {
-                if let Some(pref) = key.strip_prefix(pref) {
-                    if let Some(dep) = pref.strip_suffix(".version") {
-                        println!("GOT DEP {}", dep.to_string());
-                        deps.push(dep.to_string());
-                    }
+        for (_, table) in dependency_tables(&toml) {
+            for (dep, value) in table.iter() {
+                if value.get("version").is_some() {
+                    println!("GOT DEP {}", dep);
+                    deps.push(dep.to_string());
                 }
             }
         }
@@ -164,10 +148,14 @@ fn main() {
             patch_cargo(&c.cargo, "package.version", &next_version, write);
             patch_cargo(&c.cargo, "package.metadata.makepad-auto-version", &c.new_sha1, write);
             // now lets version-up everyone elses dependency on this crate
-            for pref in target_deps {
-                let dep_version = format!("{}{}.version", pref, c.package_name);
-                for o in &ver_crates {
-                    patch_cargo(&o.cargo, &dep_version, &next_version, write);
+            for o in &ver_crates {
+                let cargo_str = fs::read_to_string(&o.cargo).unwrap();
+                let toml = makepad_toml_parser::parse_toml(&cargo_str).unwrap();
+                for (path, table) in dependency_tables(&toml) {
+                    if table.get(&c.package_name).and_then( | dep | dep.get("version")).is_some() {
+                        let dep_version = format!("{}.{}.version", path, toml_key(&c.package_name));
+                        patch_cargo(&o.cargo, &dep_version, &next_version, write);
+                    }
                 }
             }
         }
@@ -176,25 +164,31 @@ fn main() {
     println!("Done");
 }
 
+// [dependencies] and every [target.<cfg>.dependencies], with the path to them
+fn dependency_tables(toml: &Toml) -> Vec<(String, &TomlTable)> {
+    let mut tables = Vec::new();
+    if let Some(Toml::Table(deps)) = toml.get("dependencies") {
+        tables.push(("dependencies".to_string(), deps));
+    }
+    if let Some(Toml::Table(targets)) = toml.get("target") {
+        for (target, value) in targets.iter() {
+            if let Some(Toml::Table(deps)) = value.get("dependencies") {
+                tables.push((format!("target.{}.dependencies", toml_key(target)), deps));
+            }
+        }
+    }
+    tables
+}
+
 fn patch_cargo(cargo: &Path, toml_path: &str, with: &str, write: bool) {
     let old_cargo = fs::read_to_string(cargo).unwrap();
-    let toml = makepad_toml_parser::parse_toml(&old_cargo).unwrap();
+    let mut doc = TomlDocument::parse(&old_cargo).unwrap();
     
-    if let Some(Toml::Str(_, span)) = toml.get(toml_path) {
-        let mut new_cargo = String::new();
-        for (i, c) in old_cargo.chars().enumerate() {
-            if i == span.start {
-                for c in with.chars() {
-                    new_cargo.push(c);
-                }
-            }
-            if i < span.start || i >= span.start + span.len - 2 {
-                new_cargo.push(c);
-            }
-        }
+    if let Some(Toml::Str(_, _)) = doc.get(toml_path) {
+        doc.set(toml_path, with).unwrap();
         // lets write it back to disk
         if write {
-            fs::File::create(cargo).unwrap().write_all(new_cargo.as_bytes()).unwrap();
+            fs::File::create(cargo).unwrap().write_all(doc.source().as_bytes()).unwrap();
             println!("Updating {:?} with {}", cargo, with);
         }
         else {
@@ -202,4 +196,3 @@ fn patch_cargo(cargo: &Path, toml_path: &str, with: &str, write: bool) {
         }
     }
 }
-
@@ -3,6 +3,7 @@ use crate::makepad_platform::*;
 live_design!{
     import crate::button::ButtonBase;
     import crate::check_box::CheckBoxBase;
+    import crate::data_binding::DataBindingsBase;
     import crate::dock::DockBase;
     import crate::splitter::SplitterBase;
     import crate::desktop_button::DesktopButtonBase;
@@ -46,6 +47,8 @@ live_design!{
     import makepad_draw::shader::std::*;
     import makepad_draw::shader::draw_color::DrawColor;
     
+    DataBindings = <DataBindingsBase> {}
+    
     SlidePanel = <SlidePanelBase>{
         animator: {
             closed = {
@@ -589,6 +592,7 @@ live_design!{
     ViewBase = <ViewBase>{}
     ButtonBase = <ButtonBase>{}
     CheckBoxBase = <CheckBoxBase>{}
+    DataBindingsBase = <DataBindingsBase> {}
     DockBase = <DockBase>{}
     MultiWindowBase = <MultiWindowBase>{}
     WindowBase = <WindowBase> {}
@@ -58,6 +58,8 @@ impl Widget for Button{
         self.walk
     }
     
+    fn area(&self) -> Area {self.draw_bg.area()}
+    
     fn redraw(&mut self, cx:&mut Cx){
         self.draw_bg.redraw(cx)
     }
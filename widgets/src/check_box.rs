@@ -144,6 +144,8 @@ impl Widget for CheckBox {
     
     fn walk(&mut self, _cx: &mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.draw_check.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.draw_walk(cx, walk);
         WidgetDraw::done()
@@ -1,8 +1,15 @@
-use crate::{
-    makepad_platform::*,
-    widget::*,
+use {
+    std::rc::Rc,
+    crate::{
+        makepad_platform::*,
+        widget::*,
+    }
 };
 
+live_design!{
+    DataBindingsBase = {{DataBindings}} {}
+}
+
 pub struct DataBindingStore {
     pub nodes: Vec<LiveNode>,
     mutated_by: Vec<WidgetUid>,
@@ -26,6 +33,44 @@ impl DataBindingStore {
     }
 }
 
+/// Bindings declared in the DSL. `data.path <=> widget.path` syncs a widget with the data both
+/// ways, `data.path => widget.path.prop (expr)` writes the data into a widget or one of its
+/// properties, through an optional mapping expression that sees the data as `value`.
+#[derive(Live)]
+pub struct DataBindings {
+    #[rust] bindings: Vec<Rc<LiveBinding>>,
+}
+
+impl LiveHook for DataBindings {
+    fn before_apply(&mut self, _cx: &mut Cx, from: ApplyFrom, _index: usize, _nodes: &[LiveNode]) {
+        if from.is_from_doc() {
+            self.bindings.clear();
+        }
+    }
+    
+    fn apply_value_unknown(&mut self, cx: &mut Cx, _from: ApplyFrom, index: usize, nodes: &[LiveNode]) -> usize {
+        if let LiveValue::Binding(binding) = &nodes[index].value {
+            self.bindings.push(binding.clone());
+        }
+        else if !nodes[index].origin.node_has_prefix() {
+            cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+        }
+        nodes.skip_node(index)
+    }
+}
+
+impl DataBindings {
+    pub fn bindings(&self) -> &[Rc<LiveBinding>] {
+        &self.bindings
+    }
+    
+    /// Writes the widget changes in `actions` to the store, then the store to the widgets.
+    pub fn sync(&self, cx: &mut Cx, store: &mut DataBindingStore, actions: &WidgetActions, ui: &WidgetRef) {
+        store.widgets_to_data(cx, actions, ui).bind_all(self);
+        store.data_to_widgets(cx, actions, ui).bind_all(self);
+    }
+}
+
 enum Direction {
     DataToWidgets,
     WidgetsToData
@@ -105,6 +150,57 @@ impl<'a> DataBindingMap<'a> {
         }
     }
     
+    pub fn bind_all(&mut self, bindings: &DataBindings) {
+        for binding in bindings.bindings() {
+            if binding.two_way {
+                self.bind(&binding.from.0, &[&binding.to.0]);
+            }
+            else if self.is_data_to_widgets() {
+                self.bind_one_way(binding);
+            }
+        }
+    }
+    
+    fn bind_one_way(&mut self, binding: &LiveBinding) {
+        let value = match self.store.nodes.read_field_value(&binding.from.0) {
+            Some(value) => value.clone(),
+            None => return
+        };
+        let value = match &binding.map {
+            Some(map) => match eval_binding_map(self.cx, map, value) {
+                Some(value) => value,
+                None => return
+            }
+            None => value
+        };
+        // the widget path is the longest prefix that finds widgets, the rest is a property path
+        let to = &binding.to.0;
+        for split in (1..=to.len()).rev() {
+            let mut widgets = self.ui.widgets(&[&to[..split]]);
+            if widgets.is_empty() {
+                continue;
+            }
+            let nodes = one_way_nodes(&to[split..], value);
+            if split == to.len() {
+                for widget in widgets.iter() {
+                    if !self.store.mutated_by.contains(&widget.widget_uid()) {
+                        widget.data_to_widget(self.cx, &nodes, &[live_id!(value)]);
+                    }
+                }
+            }
+            else {
+                for widget in widgets.iter() {
+                    widget.apply_over(self.cx, &nodes);
+                    widget.redraw(self.cx);
+                }
+            }
+            return
+        }
+        if self.debug_missing {
+            log!("No widgets found for databinding {:?}", to);
+        }
+    }
+    
     pub fn apply<F>(&mut self, data: &[LiveId], widget_val: &[&[LiveId]; 2], map: F)
     where F: FnOnce(LiveValue) -> LiveValue {
         if self.is_data_to_widgets() {
@@ -123,3 +219,73 @@ impl<'a> DataBindingMap<'a> {
     }
 }
 
+// the nodes a one way binding hands to its widgets, the value of the widget itself or of the
+// property path that follows it
+fn one_way_nodes(property: &[LiveId], value: LiveValue) -> LiveNodeVec {
+    let mut nodes = LiveNodeVec::new();
+    if property.is_empty() {
+        nodes.write_field_value(&[live_id!(value)], value);
+    }
+    else {
+        nodes.write_field_value(property, value);
+    }
+    nodes
+}
+
+// evaluates a binding mapping expression with `value` replaced by the bound value
+fn eval_binding_map(cx: &mut Cx, map: &[LiveNode], value: LiveValue) -> Option<LiveValue> {
+    if !value.is_value_type() {
+        return None
+    }
+    let nodes: Vec<LiveNode> = map.iter().map( | node | match node.value {
+        LiveValue::Id(live_id!(value)) => LiveNode {value: value.clone(), ..node.clone()},
+        _ => node.clone()
+    }).collect();
+    let ret = live_eval(&cx.live_registry.clone().borrow(), 0, &mut 1, &nodes);
+    match ret {
+        Ok(LiveEval::Float64(v)) => Some(LiveValue::Float64(v)),
+        Ok(LiveEval::Int64(v)) => Some(LiveValue::Int64(v)),
+        Ok(LiveEval::Bool(v)) => Some(LiveValue::Bool(v)),
+        Ok(LiveEval::Vec2(v)) => Some(LiveValue::Vec2(v)),
+        Ok(LiveEval::Vec3(v)) => Some(LiveValue::Vec3(v)),
+        Ok(LiveEval::Vec4(v)) => Some(LiveValue::Vec4(v)),
+        Ok(LiveEval::String(v)) => Some(LiveValue::String(v)),
+        Err(err) => {
+            cx.apply_error_eval(err);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    
+    #[test]
+    fn one_way_property_nodes() {
+        let mut store = DataBindingStore::new();
+        store.nodes.write_field_value(&[live_id!(user), live_id!(age)], LiveValue::Int64(42));
+        store.nodes.write_field_value(&[live_id!(user), live_id!(name)], LiveValue::String(Rc::new("ann".to_string())));
+        let binding = LiveBinding {
+            from: LiveIdPath(vec![live_id!(user), live_id!(age)]),
+            to: LiveIdPath(vec![live_id!(meter), live_id!(draw_bg), live_id!(fill)]),
+            two_way: false,
+            map: None
+        };
+        let value = store.nodes.read_field_value(&binding.from.0).unwrap().clone();
+        
+        // the widget is found at meter, the rest of the path is the property to write
+        let nodes = one_way_nodes(&binding.to.0[1..], value.clone());
+        assert_eq!(nodes.read_field_value(&[live_id!(draw_bg), live_id!(fill)]), Some(&LiveValue::Int64(42)));
+        assert!(nodes[0].is_open() && nodes.last().unwrap().is_close());
+        assert_eq!(nodes.skip_node(0), nodes.len());
+        
+        // a path that ends at the widget sets its value, which data_to_widget reads back
+        let nodes = one_way_nodes(&[], value);
+        assert_eq!(nodes.read_field_value(&[live_id!(value)]), Some(&LiveValue::Int64(42)));
+        
+        // the store is left as it was, and paths it does not have bind nothing
+        assert_eq!(store.nodes.read_field_value(&[live_id!(user), live_id!(age)]), Some(&LiveValue::Int64(42)));
+        assert!(store.nodes.read_field_value(&[live_id!(user), live_id!(email)]).is_none());
+    }
+}
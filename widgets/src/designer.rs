@@ -8,20 +8,21 @@ use crate::{
 live_design!{
     import makepad_widgets::base::*
     import makepad_widgets::theme_desktop_dark::*
+    import makepad_widgets::theme::*
     import makepad_draw::shader::std::*
     
     Designer = {{Designer}} {
         has_view: true,
         flow: Right
         container: <RoundedView> {
-            draw_bg: {color: #3}
+            draw_bg: {color: (THEME_COLOR_BG_HEADER)}
             width: Fill, height: 400
             flow: Down, spacing: 10, padding:10
             <RoundedView>{
                 width: Fill, height: Fit
                 padding:5
-                draw_bg:{color:#5}
-                label = <Label> {text: "HI", draw_text:{color:#f}}
+                draw_bg:{color:(THEME_COLOR_BUTTON)}
+                label = <Label> {text: "HI", draw_text:{color:(THEME_COLOR_TEXT_HOVER)}}
             }
             inner = <HookWidget> {}
         }
@@ -33,7 +34,7 @@ live_design!{
             },
             b: <CachedScrollXY> {
                 dpi_factor: 1.5
-                draw_bg: {color: #4}
+                draw_bg: {color: (THEME_COLOR_BG_APP)}
                 width: Fill, height: Fill
                 flow: Down
                 design = <HookWidget> {}
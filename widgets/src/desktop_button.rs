@@ -35,6 +35,8 @@ impl Widget for DesktopButton{
 
     fn walk(&mut self, _cx:&mut Cx)->Walk{self.walk}
     
+    fn area(&self) -> Area {self.draw_bg.area()}
+    
     fn redraw(&mut self, cx:&mut Cx){
         self.draw_bg.redraw(cx)
     }
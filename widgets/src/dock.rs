@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::Chars;
 use crate::{
     makepad_micro_serde::*,
@@ -441,8 +441,12 @@ impl Dock {
     }
     
     pub fn to_store_items(&self)->Vec<DockItemStore>{
+        Self::store_items(&self.dock_items)
+    }
+    
+    fn store_items(dock_items: &HashMap<LiveId, DockItem>)->Vec<DockItemStore>{
         let mut out = Vec::new();
-        for (id, dock_item) in &self.dock_items{
+        for (id, dock_item) in dock_items{
             match dock_item{
                 DockItem::Splitter {
                     axis,
@@ -487,8 +491,114 @@ impl Dock {
         out
     }
     
-    pub fn from_store_item(&mut self, _store:&[DockItemStore]){
+    pub fn from_store_item(&mut self, cx: &mut Cx, store:&[DockItemStore])->bool{
+        let Some(dock_items) = Self::items_from_store(store, |kind| self.templates.contains_key(&kind)) else{
+            log!("Dock restore: stored layout has no valid root, keeping current layout");
+            return false
+        };
+        
+        // keep the widgets of tabs that survive with the same kind, drop the rest
+        self.items.retain(|item_id, (kind, _)|{
+            matches!(dock_items.get(item_id), Some(DockItem::Tab{kind:new_kind, ..}) if *new_kind == *kind)
+        });
+        self.dock_items = dock_items;
+        let mut tabs = Vec::new();
+        for (item_id, item) in self.dock_items.iter() {
+            if let DockItem::Tab {kind, ..} = item {
+                tabs.push((*item_id, *kind));
+            }
+        }
+        for (item_id, kind) in tabs {
+            self.item_or_create(cx, item_id, kind);
+        }
+        
+        self.drop_state = None;
+        self.needs_save = false;
+        for tab_bar in self.tab_bars.values_mut(){
+            tab_bar.contents_draw_list.redraw(cx);
+        }
+        self.area.redraw(cx);
+        true
+    }
+    
+    // the dock items of a stored layout, without tabs of kinds that have no template
+    fn items_from_store(store:&[DockItemStore], has_template: impl Fn(LiveId)->bool)->Option<HashMap<LiveId, DockItem>>{
+        let mut dock_items = HashMap::new();
+        for item in store{
+            let (id, dock_item) = match item{
+                DockItemStore::Splitter{id, axis, align, a, b}=>{
+                    (id.0, DockItem::Splitter{axis:*axis, align:*align, a:a.0, b:b.0})
+                }
+                DockItemStore::Tabs{id, tabs, selected, closable}=>{
+                    (id.0, DockItem::Tabs{tabs:tabs.iter().map(|v| v.0).collect(), selected:*selected, closable:*closable})
+                }
+                DockItemStore::Tab{id, name, closable, kind}=>{
+                    // a tab we can't instantiate anymore is a dangling tab
+                    if !has_template(kind.0){
+                        log!("Dock restore: dropping tab {} with unknown kind {}", id.0, kind.0);
+                        continue;
+                    }
+                    (id.0, DockItem::Tab{name:name.clone(), closable:*closable, kind:kind.0})
+                }
+            };
+            dock_items.insert(id, dock_item);
+        }
         
+        let root = live_id!(root);
+        if !Self::validate_store_items(&mut dock_items, root){
+            return None
+        }
+        Some(dock_items)
+    }
+    
+    // removes dangling ids, unreachable items and cycles from a restored layout.
+    // splitters missing a side collapse into the side that remains.
+    fn validate_store_items(dock_items: &mut HashMap<LiveId, DockItem>, root: LiveId)->bool{
+        // returns the id that should take the place of id in its parent
+        fn walk(dock_items: &mut HashMap<LiveId, DockItem>, id: LiveId, visited: &mut HashSet<LiveId>)->Option<LiveId>{
+            if !visited.insert(id){
+                return None
+            }
+            match dock_items.get(&id).cloned(){
+                Some(DockItem::Splitter{axis, align, a, b})=>{
+                    match (walk(dock_items, a, visited), walk(dock_items, b, visited)){
+                        (Some(a), Some(b)) => {
+                            dock_items.insert(id, DockItem::Splitter{axis, align, a, b});
+                            Some(id)
+                        }
+                        (Some(keep), None) | (None, Some(keep)) => {
+                            visited.remove(&id);
+                            Some(keep)
+                        }
+                        (None, None) => None
+                    }
+                }
+                Some(DockItem::Tabs{mut tabs, mut selected, closable})=>{
+                    tabs.retain(|tab_id|{
+                        matches!(dock_items.get(tab_id), Some(DockItem::Tab{..})) && visited.insert(*tab_id)
+                    });
+                    if selected >= tabs.len(){
+                        selected = tabs.len().max(1) - 1;
+                    }
+                    dock_items.insert(id, DockItem::Tabs{tabs, selected, closable});
+                    Some(id)
+                }
+                Some(DockItem::Tab{..}) | None => None
+            }
+        }
+        let mut visited = HashSet::new();
+        match walk(dock_items, root, &mut visited){
+            None => return false,
+            Some(new_root) => if new_root != root{
+                // the root collapsed, move what remains into the root slot
+                let item = dock_items.remove(&new_root).unwrap();
+                dock_items.insert(root, item);
+                visited.remove(&new_root);
+                visited.insert(root);
+            }
+        }
+        dock_items.retain(|id, _| visited.contains(id));
+        true
     }
     
     pub fn item(&mut self, entry_id: LiveId) -> Option<WidgetRef> {
@@ -952,8 +1062,16 @@ impl Widget for Dock {
         }
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        for (_, widget) in self.items.values() {
+            visit(widget);
+        }
+    }
+    
     fn walk(&mut self, _cx: &mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.area}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin_with(cx, &self.dock_items, | _, dock_items | {
             let id = live_id!(root);
@@ -1225,6 +1343,13 @@ impl DockRef {
         LiveId(0)
     }
         
+    pub fn from_store_item(&self, cx: &mut Cx, store: &[DockItemStore])->bool{
+        if let Some(mut dock) = self.borrow_mut() {
+            return dock.from_store_item(cx, store);
+        }
+        false
+    }
+    
     pub fn needs_save(&self)->Option<Vec<DockItemStore>>{
         if let Some(mut dock) = self.borrow_mut() {
             if dock.needs_save{
@@ -1241,4 +1366,49 @@ impl DockRef {
 }
 
 #[derive(Clone, WidgetSet)]
-pub struct DockSet(WidgetSet);
\ No newline at end of file
+pub struct DockSet(WidgetSet);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    
+    fn sorted(mut items: Vec<DockItemStore>)->Vec<String>{
+        let mut out: Vec<String> = items.drain(..).map(|item| format!("{:?}", item)).collect();
+        out.sort();
+        out
+    }
+    
+    #[test]
+    fn store_items_round_trip(){
+        let file = LiveId::from_str("src/app.rs");
+        // a second tab on the same file, as made by unique_tab_id
+        let duplicate = file.bytes_append(&0u32.to_be_bytes());
+        let mut dock_items = HashMap::new();
+        dock_items.insert(live_id!(root), DockItem::Splitter{
+            axis: SplitterAxis::Horizontal,
+            align: SplitterAlign::FromA(250.0),
+            a: live_id!(file_tree),
+            b: live_id!(edit_tabs)
+        });
+        dock_items.insert(live_id!(file_tree), DockItem::Tabs{tabs: vec![live_id!(file_tree_tab)], selected: 0, closable: false});
+        dock_items.insert(live_id!(file_tree_tab), DockItem::Tab{name: "Files".to_string(), closable: false, kind: live_id!(FileTree)});
+        dock_items.insert(live_id!(edit_tabs), DockItem::Tabs{tabs: vec![file, duplicate], selected: 1, closable: true});
+        dock_items.insert(file, DockItem::Tab{name: "app.rs".to_string(), closable: true, kind: live_id!(CodeEditor)});
+        dock_items.insert(duplicate, DockItem::Tab{name: "app.rs".to_string(), closable: true, kind: live_id!(CodeEditor)});
+        
+        let store = Dock::store_items(&dock_items);
+        let store = Vec::<DockItemStore>::deserialize_ron(&store.serialize_ron()).unwrap();
+        let restored = Dock::items_from_store(&store, |_| true).unwrap();
+        assert_eq!(sorted(Dock::store_items(&restored)), sorted(Dock::store_items(&dock_items)));
+        
+        // tabs of an unknown kind are dropped along with their place in the tab bar
+        let restored = Dock::items_from_store(&store, |kind| kind != live_id!(FileTree)).unwrap();
+        assert!(restored.get(&live_id!(file_tree_tab)).is_none());
+        assert!(matches!(restored.get(&live_id!(file_tree)), Some(DockItem::Tabs{tabs, ..}) if tabs.is_empty()));
+        assert!(matches!(restored.get(&duplicate), Some(DockItem::Tab{..})));
+        
+        // without a root there is nothing to restore
+        let store: Vec<DockItemStore> = store.into_iter().filter(|item| !matches!(item, DockItemStore::Splitter{..})).collect();
+        assert!(Dock::items_from_store(&store, |_| true).is_none());
+    }
+}
@@ -98,6 +98,7 @@ pub struct FileTree {
     
     #[rust] dragging_node_id: Option<FileNodeId>,
     #[rust] selected_node_id: Option<FileNodeId>,
+    #[rust] hovered_node_id: Option<FileNodeId>,
     #[rust] open_nodes: HashSet<FileNodeId>,
     
     #[rust] tree_nodes: ComponentMap<FileNodeId, (FileTreeNode, LiveId)>,
@@ -127,6 +128,8 @@ pub enum FileTreeAction {
     FileClicked(FileNodeId),
     FolderClicked(FileNodeId),
     ShouldFileStartDrag(FileNodeId),
+    NodeHoverIn(FileNodeId),
+    NodeHoverOut(FileNodeId),
 }
 
 pub enum FileTreeNodeAction {
@@ -134,6 +137,8 @@ pub enum FileTreeNodeAction {
     WasClicked,
     Opening,
     Closing,
+    HoverIn,
+    HoverOut,
     ShouldStartDrag
 }
 
@@ -210,9 +215,11 @@ impl FileTreeNode {
         match event.hits(cx, self.draw_bg.area()) {
             Hit::FingerHoverIn(_) => {
                 self.animator_play(cx, id!(hover.on));
+                dispatch_action(cx, FileTreeNodeAction::HoverIn);
             }
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
+                dispatch_action(cx, FileTreeNodeAction::HoverOut);
             }
             Hit::FingerMove(f) => {
                 if f.abs.distance(&f.abs_start) >= self.min_drag_distance {
@@ -415,8 +422,19 @@ impl FileTree {
             node.handle_event_with(cx, event, &mut | _, e | actions.push((*node_id, e)));
         }
         
+        // hover out and in can arrive in the same event, only report where the hover ended up
+        let hovered_node_id = self.hovered_node_id;
+        
         for (node_id, action) in actions {
             match action {
+                FileTreeNodeAction::HoverIn => {
+                    self.hovered_node_id = Some(node_id);
+                }
+                FileTreeNodeAction::HoverOut => {
+                    if self.hovered_node_id == Some(node_id) {
+                        self.hovered_node_id = None;
+                    }
+                }
                 FileTreeNodeAction::Opening => {
                     self.open_nodes.insert(node_id);
                 }
@@ -447,6 +465,15 @@ impl FileTree {
             }
         }
         
+        if self.hovered_node_id != hovered_node_id {
+            if let Some(node_id) = self.hovered_node_id {
+                dispatch_action(cx, FileTreeAction::NodeHoverIn(node_id));
+            }
+            else if let Some(node_id) = hovered_node_id {
+                dispatch_action(cx, FileTreeAction::NodeHoverOut(node_id));
+            }
+        }
+        
         match event.hits(cx, self.scroll_bars.area()) {
             Hit::KeyFocus(_) => {
                 if let Some(node_id) = self.selected_node_id {
@@ -480,6 +507,8 @@ impl Widget for FileTree {
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.scroll_bars.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, ()) {
             self.begin(cx, walk);
@@ -516,6 +545,33 @@ impl FileTreeRef{
     }
     
     
+    pub fn folder_clicked(&self, actions: &WidgetActions) -> Option<FileNodeId> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let FileTreeAction::FolderClicked(file_id) = item.action() {
+                return Some(file_id)
+            }
+        }
+        None
+    }
+    
+    pub fn node_hover_in(&self, actions: &WidgetActions) -> Option<FileNodeId> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let FileTreeAction::NodeHoverIn(node_id) = item.action() {
+                return Some(node_id)
+            }
+        }
+        None
+    }
+    
+    pub fn node_hover_out(&self, actions: &WidgetActions) -> Option<FileNodeId> {
+        if let Some(item) = actions.find_single_action(self.widget_uid()) {
+            if let FileTreeAction::NodeHoverOut(node_id) = item.action() {
+                return Some(node_id)
+            }
+        }
+        None
+    }
+    
     pub fn file_start_drag(&self, cx: &mut Cx, _file_id: FileNodeId, item: DragItem) {
         cx.start_dragging(vec![item]);
     }
@@ -289,6 +289,8 @@ impl Widget for FlatList {
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.scroll_bars.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, ()) {
             self.begin(cx, walk);
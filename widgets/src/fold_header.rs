@@ -73,12 +73,19 @@ impl Widget for FoldHeader {
     }
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {self.walk}
+    
+    fn area(&self) -> Area {self.area}
 
     fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet) {
         self.header.find_widgets(path, cached, results);
         self.body.find_widgets(path, cached, results);
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        visit(&self.header);
+        visit(&self.body);
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, DrawState::DrawHeader) {
             cx.begin_turtle(walk, self.layout);
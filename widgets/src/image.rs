@@ -54,6 +54,8 @@ impl Widget for Image {
         self.walk
     }
     
+    fn area(&self) -> Area {self.draw_bg.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.draw_walk(cx, walk)
     }
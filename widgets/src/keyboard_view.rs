@@ -138,6 +138,8 @@ impl Widget for KeyboardView {
         self.outer_walk
     }
     
+    fn area(&self) -> Area {self.area}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin_with(cx, &(), |cx,_|{
             self.view.walk(cx)
@@ -154,5 +156,9 @@ impl Widget for KeyboardView {
     fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet) {
         self.view.find_widgets(path, cached, results);
     }
+    
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        self.view.visit_children(visit);
+    }
 }
 
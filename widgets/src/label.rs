@@ -35,6 +35,8 @@ impl Widget for Label {
         self.walk
     }
     
+    fn area(&self) -> Area {self.draw_text.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk:Walk)->WidgetDraw{
         self.draw_text.draw_walk(cx, walk.with_add_padding(self.padding), self.align, self.text.as_ref());
         WidgetDraw::done()
@@ -47,6 +49,15 @@ impl Widget for Label {
     fn set_text(&mut self, v:&str){
         self.text.as_mut_empty().push_str(v);
     }
+    
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes:&[LiveNode], path: &[LiveId]){
+        if let Some(value) = nodes.read_field_value(path) {
+            if let Some(value) = value.as_str() {
+                self.set_text(value);
+                self.redraw(cx);
+            }
+        }
+    }
 }
 
 
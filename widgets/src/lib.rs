@@ -55,11 +55,15 @@ pub mod widget;
 pub mod data_binding;
 
 mod base;
+pub mod theme;
+mod theme_desktop_light;
+mod theme_desktop_high_contrast;
 mod theme_desktop_dark;
 pub mod image_cache;
 
 pub use crate::{
-    data_binding::{DataBindingStore, DataBindingMap},
+    data_binding::{DataBindingStore, DataBindingMap, DataBindings},
+    theme::{Theme, set_theme},
     button::*,
     view::*,
     image::*,
@@ -105,11 +109,15 @@ pub use crate::{
 
 pub fn live_design(cx: &mut Cx) {
     makepad_draw::live_design(cx);
+    crate::data_binding::live_design(cx);
     crate::page_flip::live_design(cx);
     crate::debug_view::live_design(cx);
     crate::fold_header::live_design(cx);
     crate::splitter::live_design(cx);
     crate::base::live_design(cx);
+    crate::theme::live_design(cx);
+    crate::theme_desktop_light::live_design(cx);
+    crate::theme_desktop_high_contrast::live_design(cx);
     crate::theme_desktop_dark::live_design(cx);
     crate::slider::live_design(cx);
     crate::label::live_design(cx);
@@ -40,6 +40,8 @@ impl Widget for LinkLabel {
         self.button.walk(cx)
     }
     
+    fn area(&self) -> Area {self.button.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.button.draw_walk_widget(cx, walk)
     }
@@ -62,6 +62,12 @@ impl Widget for MultiWindow {
         }
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        for window in self.windows.values_mut() {
+            window.visit_children(visit);
+        }
+    }
+    
     fn handle_widget_event_with(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, WidgetActionItem)) {
         for window in self.windows.values_mut() {
             window.handle_widget_event_with(cx, event, dispatch_action);
@@ -108,6 +108,12 @@ impl Widget for PageFlip {
         }
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        if let Some(page) = self.pages.get(&self.active_page) {
+            visit(page);
+        }
+    }
+    
     fn redraw(&mut self, cx: &mut Cx) {
         self.area.redraw(cx);
     }
@@ -127,6 +133,8 @@ impl Widget for PageFlip {
         self.walk
     }
     
+    fn area(&self) -> Area {self.area}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if let Some(page) = self.page(cx, self.active_page) {
             if self.draw_state.begin_with(cx, &(), | cx, _ | {
@@ -715,6 +715,8 @@ impl Widget for PortalList {
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.area}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, ListDrawState::Begin) {
             self.begin(cx, walk);
@@ -47,6 +47,8 @@ impl Widget for RotatedImage {
     fn walk(&mut self, _cx:&mut Cx) -> Walk {
         self.walk
     }
+    
+    fn area(&self) -> Area {self.draw_bg.area()}
 
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.draw_walk(cx, walk)
@@ -48,6 +48,8 @@ impl Widget for SlidePanel {
         self.frame.walk(cx)
     }
     
+    fn area(&self) -> Area {self.frame.area()}
+    
     fn redraw(&mut self, cx: &mut Cx) {
         self.frame.redraw(cx)
     }
@@ -56,6 +58,10 @@ impl Widget for SlidePanel {
         self.frame.find_widgets(path, cached, results);
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        self.frame.visit_children(visit);
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, mut walk: Walk) -> WidgetDraw {
         // ok lets set abs pos
         let rect = cx.peek_walk_turtle(walk);
@@ -109,6 +109,8 @@ impl Widget for SlidesView {
         self.walk
     }
     
+    fn area(&self) -> Area {self.area}
+    
     fn redraw(&mut self, cx: &mut Cx) {
         self.area.redraw(cx)
     }
@@ -119,6 +121,12 @@ impl Widget for SlidesView {
         }
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        for child in self.children.values() {
+            visit(child);
+        }
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         // alright lets draw the child slide
         // we always maximally show 2 slides
@@ -130,6 +130,11 @@ impl Widget for Splitter {
         self.b.find_widgets(path, cached, results);
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        visit(&self.a);
+        visit(&self.b);
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, DrawState::DrawA) {
             self.begin(cx, walk);
@@ -1,7 +1,9 @@
 use {
+    std::rc::Rc,
     crate::{
         makepad_derive_widget::*,
         makepad_draw::*,
+        scroll_bars::ScrollBars,
         widget::*,
     }
 };
@@ -29,6 +31,118 @@ pub enum UndoGroup {
 }
 
 
+// a line of the multi-line layout, in char indices into the text
+struct TextInputLine {
+    start: usize,
+    end: usize,
+    byte_start: usize,
+    byte_end: usize,
+    offsets: Vec<f64>,
+    // the line continues on the next one without a newline in between
+    soft_wrap: bool,
+}
+
+// the lines of a multi-line input and the line/column math on them, x positions are relative to the text
+#[derive(Default)]
+struct TextInputLayout {
+    lines: Vec<TextInputLine>,
+}
+
+impl TextInputLayout {
+    fn new(text: &str, layout_lines: Vec<TextLayoutLine>) -> Self {
+        let mut lines = Vec::new();
+        let mut char_index = 0;
+        let mut byte_index = 0;
+        for (i, line) in layout_lines.iter().enumerate() {
+            char_index += text[byte_index..line.start].chars().count();
+            let start = char_index;
+            char_index += line.offsets.len() - 1;
+            byte_index = line.end;
+            lines.push(TextInputLine {
+                start,
+                end: char_index,
+                byte_start: line.start,
+                byte_end: line.end,
+                offsets: line.offsets.clone(),
+                soft_wrap: layout_lines.get(i + 1).map_or(false, | next | next.start == line.end),
+            });
+        }
+        Self {lines}
+    }
+    
+    // the line a cursor is drawn on, a cursor at a soft wrap goes at the start of the next line
+    fn line_of(&self, index: usize) -> usize {
+        self.lines.iter().rposition( | line | line.start <= index).unwrap_or(0)
+    }
+    
+    fn cursor_x(&self, line: usize, index: usize) -> f64 {
+        if let Some(line) = self.lines.get(line) {
+            let offset = index.saturating_sub(line.start).min(line.offsets.len() - 1);
+            line.offsets[offset]
+        }
+        else {
+            0.0
+        }
+    }
+    
+    // the last position on a line, on a soft wrapped line that is before the char that wraps
+    fn line_end(&self, line: usize) -> usize {
+        let line = &self.lines[line];
+        if line.soft_wrap && line.end > line.start {line.end - 1} else {line.end}
+    }
+    
+    fn offset_at_x(&self, line: usize, x: f64) -> usize {
+        let text_line = &self.lines[line];
+        for (i, pair) in text_line.offsets.windows(2).enumerate() {
+            if x < (pair[0] + pair[1]) * 0.5 {
+                return (text_line.start + i).min(self.line_end(line))
+            }
+        }
+        self.line_end(line)
+    }
+    
+    fn closest_offset(&self, pos: DVec2, line_spacing: f64) -> usize {
+        if self.lines.is_empty() {
+            return 0
+        }
+        let line = (pos.y / line_spacing).floor().max(0.0) as usize;
+        self.offset_at_x(line.min(self.lines.len() - 1), pos.x)
+    }
+    
+    // the offset delta lines away from head, at column x or else at the column of head.
+    // returns it with the column to keep to on the next move
+    fn move_lines(&self, head: usize, x: Option<f64>, delta: isize, char_count: usize) -> Option<(usize, f64)> {
+        if self.lines.is_empty() {
+            return None
+        }
+        let line = self.line_of(head);
+        let x = x.unwrap_or_else( || self.cursor_x(line, head));
+        let target = line as isize + delta;
+        let head = if target < 0 {
+            0
+        }
+        else if target as usize >= self.lines.len() {
+            char_count
+        }
+        else {
+            self.offset_at_x(target as usize, x).min(char_count)
+        };
+        Some((head, x))
+    }
+    
+    // with a Fit height the input shows all lines up to max_lines, 0 means no limit
+    fn visible_height(&self, height: Size, max_lines: usize, line_spacing: f64) -> Size {
+        if height.is_fit() {
+            let line_count = self.lines.len().max(1);
+            let visible_lines = if max_lines > 0 {line_count.min(max_lines)} else {line_count};
+            Size::Fixed(visible_lines as f64 * line_spacing)
+        }
+        else {
+            Size::Fill
+        }
+    }
+}
+
 #[derive(Live, LiveHook)]
 #[repr(C)]
 pub struct DrawLabel {
@@ -61,6 +175,12 @@ pub struct TextInput {
     #[live] on_focus_select_all: bool,
     #[live] pub read_only: bool,
     
+    #[live] multi_line: bool,
+    // with a Fit height a multi-line input grows up to this many lines and scrolls after that,
+    // 0 means it grows without limit
+    #[live] max_lines: usize,
+    #[live] scroll_bars: ScrollBars,
+    
     //#[live] label_walk: Walk,
     
     #[live] pub text: String,
@@ -72,7 +192,14 @@ pub struct TextInput {
     #[rust] undo_stack: Vec<UndoItem>,
     #[rust] redo_stack: Vec<UndoItem>,
     #[rust] cursor_tail: usize,
-    #[rust] cursor_head: usize
+    #[rust] cursor_head: usize,
+    
+    #[rust] line_layout: TextInputLayout,
+    // where the text starts relative to draw_bg, draw positions move with alignment afterwards
+    #[rust] text_offset: DVec2,
+    // the x position up/down keep to, and the cursor it belongs to
+    #[rust] cursor_column: Option<(usize, f64)>,
+    #[rust] drawn_cursor_head: Option<usize>,
 }
 
 impl LiveHook for TextInput {
@@ -103,6 +230,8 @@ impl Widget for TextInput {
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {self.walk}
     
+    fn area(&self) -> Area {self.draw_bg.area()}
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.draw_walk(cx, walk);
         WidgetDraw::done()
@@ -116,6 +245,27 @@ impl Widget for TextInput {
     fn set_text(&mut self, v: &str) {
         self.filter_input(&v, None);
     }
+    
+    fn widget_to_data(&self, _cx: &mut Cx, actions: &WidgetActions, nodes: &mut LiveNodeVec, path: &[LiveId]) -> bool {
+        match actions.single_action(self.widget_uid()) {
+            TextInputAction::Change(v) => {
+                nodes.write_field_value(path, LiveValue::String(Rc::new(v)));
+                true
+            }
+            _ => false
+        }
+    }
+    
+    fn data_to_widget(&mut self, cx: &mut Cx, nodes: &[LiveNode], path: &[LiveId]) {
+        if let Some(value) = nodes.read_field_value(path) {
+            if let Some(value) = value.as_str() {
+                if value != self.text {
+                    self.set_text(value);
+                    self.redraw(cx);
+                }
+            }
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, WidgetAction)]
@@ -293,7 +443,7 @@ impl TextInput {
         output.clear();
         if self.ascii_only {
             for c in input.as_bytes() {
-                if *c>31 && *c<127 {
+                if (*c>31 && *c<127) || (*c == b'\n' && self.multi_line) {
                     output.push(*c as char);
                 }
             }
@@ -315,6 +465,28 @@ impl TextInput {
         }
     }
     
+    fn closest_offset(&self, cx: &Cx, abs: DVec2) -> Option<usize> {
+        if self.multi_line {
+            let origin = self.draw_bg.area().get_rect(cx).pos + self.text_offset;
+            Some(self.line_layout.closest_offset(abs - origin, self.draw_text.get_line_spacing()))
+        }
+        else {
+            self.draw_text.closest_offset(cx, abs)
+        }
+    }
+    
+    // moves the cursor head up or down by lines, keeping to the column it started from
+    fn move_cursor_lines(&mut self, delta: isize) {
+        let x = match self.cursor_column {
+            Some((head, x)) if head == self.cursor_head => Some(x),
+            _ => None
+        };
+        if let Some((head, x)) = self.line_layout.move_lines(self.cursor_head, x, delta, self.text.chars().count()) {
+            self.cursor_head = head;
+            self.cursor_column = Some((head, x));
+        }
+    }
+    
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) -> Vec<TextInputAction> {
         let mut actions = Vec::new();
         self.handle_event_with(cx, event, &mut | _, a | actions.push(a));
@@ -323,6 +495,9 @@ impl TextInput {
     
     pub fn handle_event_with(&mut self, cx: &mut Cx, event: &Event, dispatch_action: &mut dyn FnMut(&mut Cx, TextInputAction)) {
         self.animator_handle_event(cx, event);
+        if self.multi_line {
+            self.scroll_bars.handle_event_with(cx, event, &mut | _, _ | {});
+        }
         match event.hits(cx, self.draw_bg.area()) {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
@@ -380,6 +555,11 @@ impl TextInput {
                 KeyCode::Tab => {
                     // dispatch_action(cx, self, TextInputAction::Tab(key.mod_shift));
                 }
+                KeyCode::ReturnKey if self.multi_line && !ke.modifiers.logo && !ke.modifiers.control => {
+                    self.undo_id += 1;
+                    self.create_undo(UndoGroup::TextInput(self.undo_id));
+                    self.change(cx, "\n", dispatch_action);
+                }
                 KeyCode::ReturnKey => {
                     cx.hide_text_ime();
                     dispatch_action(cx, TextInputAction::Return(self.text.clone()));
@@ -428,6 +608,28 @@ impl TextInput {
                     }
                     self.draw_bg.redraw(cx);
                 }
+                KeyCode::ArrowUp | KeyCode::ArrowDown if self.multi_line && !ke.modifiers.logo => {
+                    self.undo_id += 1;
+                    self.move_cursor_lines(if let KeyCode::ArrowUp = ke.key_code {-1} else {1});
+                    if !ke.modifiers.shift {
+                        self.cursor_tail = self.cursor_head;
+                    }
+                    self.draw_bg.redraw(cx);
+                }
+                KeyCode::Home | KeyCode::End if self.multi_line && !ke.modifiers.logo && !self.line_layout.lines.is_empty() => {
+                    self.undo_id += 1;
+                    let line = self.line_layout.line_of(self.cursor_head);
+                    self.cursor_head = if let KeyCode::Home = ke.key_code {
+                        self.line_layout.lines[line].start
+                    }
+                    else {
+                        self.line_layout.line_end(line)
+                    }.min(self.text.chars().count());
+                    if !ke.modifiers.shift {
+                        self.cursor_tail = self.cursor_head;
+                    }
+                    self.draw_bg.redraw(cx);
+                }
                 KeyCode::ArrowDown => if !ke.modifiers.logo {
                     self.undo_id += 1;
                     // we need to figure out what is below our current cursor
@@ -503,7 +705,7 @@ impl TextInput {
                 self.set_key_focus(cx);
                 // ok so we need to calculate where we put the cursor down.
                 //elf.
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
+                if let Some(pos) = self.closest_offset(cx, fe.abs) {
                     //log!("{} {}", pos, fe.abs);
                     let pos = pos.min(self.text.chars().count());
                     if fe.tap_count == 1 {
@@ -528,7 +730,7 @@ impl TextInput {
             },
             Hit::FingerUp(fe) => {
                 self.double_tap_start = None;
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
+                if let Some(pos) = self.closest_offset(cx, fe.abs) {
                     let pos = pos.min(self.text.chars().count());
                     if !fe.modifiers.shift && fe.tap_count == 1 && fe.was_tap() {
                         self.cursor_head = pos;
@@ -547,7 +749,7 @@ impl TextInput {
                 }
             }
             Hit::FingerMove(fe) => {
-                if let Some(pos) = self.draw_text.closest_offset(cx, fe.abs) {
+                if let Some(pos) = self.closest_offset(cx, fe.abs) {
                     let pos = pos.min(self.text.chars().count());
                     if fe.tap_count == 2 {
                         let (head, tail) = self.double_tap_start.unwrap();
@@ -562,7 +764,7 @@ impl TextInput {
                         self.draw_bg.redraw(cx);
                     }
                     else if fe.tap_count == 1 {
-                        if let Some(pos_start) = self.draw_text.closest_offset(cx, fe.abs_start) {
+                        if let Some(pos_start) = self.closest_offset(cx, fe.abs_start) {
                             let pos_start = pos_start.min(self.text.chars().count());
                             
                             self.cursor_head = pos_start;
@@ -580,6 +782,9 @@ impl TextInput {
     }
     
     pub fn draw_walk(&mut self, cx: &mut Cx2d, walk: Walk) {
+        if self.multi_line {
+            return self.draw_walk_multi_line(cx, walk)
+        }
         
         self.draw_bg.begin(cx, walk, self.layout);
         let turtle_rect = cx.turtle().rect();
@@ -653,6 +858,109 @@ impl TextInput {
         
         cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
     }
+    
+    fn draw_walk_multi_line(&mut self, cx: &mut Cx2d, walk: Walk) {
+        self.draw_bg.begin(cx, walk, self.layout);
+        let bg_rect = cx.turtle().rect();
+        let width = cx.turtle().padded_rect().size.x;
+        
+        // the layout is done on the text as shown, which has a char for every char of the text
+        let text = if self.secret {
+            "*".repeat(self.text.chars().count())
+        }
+        else {
+            self.text.clone()
+        };
+        let layout_lines = self.draw_text.layout_lines(cx, width, &text);
+        self.line_layout = TextInputLayout::new(&text, layout_lines);
+        
+        let line_spacing = self.draw_text.get_line_spacing();
+        let line_count = self.line_layout.lines.len().max(1);
+        let visible_height = self.line_layout.visible_height(walk.height, self.max_lines, line_spacing);
+        let scroll_width = if walk.width.is_fit() {Size::Fit} else {Size::Fill};
+        self.scroll_bars.begin(cx, Walk::size(scroll_width, visible_height), Layout::default());
+        
+        // this makes sure selection goes behind the text
+        self.draw_select.append_to_draw_call(cx);
+        
+        let content_width = self.line_layout.lines.iter().map( | line | *line.offsets.last().unwrap()).fold(0.0, f64::max);
+        let rect = cx.walk_turtle(Walk::size(
+            Size::Fixed(content_width + self.cursor_size),
+            Size::Fixed(line_count as f64 * line_spacing)
+        ));
+        self.text_offset = rect.pos - bg_rect.pos;
+        
+        if self.text.len() == 0 {
+            self.draw_text.is_empty = 1.0;
+            self.draw_text.draw_abs(cx, rect.pos, &self.empty_message);
+        }
+        else {
+            self.draw_text.is_empty = 0.0;
+            for (i, line) in self.line_layout.lines.iter().enumerate() {
+                if line.byte_end > line.byte_start {
+                    let pos = rect.pos + dvec2(0.0, i as f64 * line_spacing);
+                    self.draw_text.draw_abs(cx, pos, &text[line.byte_start..line.byte_end]);
+                }
+            }
+        }
+        
+        let head_line = self.line_layout.line_of(self.cursor_head);
+        let head = dvec2(self.line_layout.cursor_x(head_line, self.cursor_head), head_line as f64 * line_spacing);
+        if !self.read_only && self.cursor_head == self.cursor_tail {
+            let top_drop = self.draw_text.get_font_size() * 0.2;
+            self.draw_cursor.draw_abs(cx, Rect {
+                pos: rect.pos + dvec2(head.x - 0.5 * self.cursor_size, head.y - top_drop),
+                size: dvec2(self.cursor_size, line_spacing)
+            });
+        }
+        
+        // draw a selection rect per line, selected newlines get a bit of extra width
+        if self.cursor_head != self.cursor_tail {
+            let top_drop = self.draw_text.get_font_size() * 0.3;
+            let bottom_drop = self.draw_text.get_font_size() * 0.1;
+            let newline_width = self.draw_text.get_font_size() * 0.5;
+            let (start, end) = self.sorted_cursor();
+            for (i, line) in self.line_layout.lines.iter().enumerate() {
+                if end <= line.start || start > line.end || line.soft_wrap && start >= line.end {
+                    continue
+                }
+                let left = start.max(line.start) - line.start;
+                let right = end.min(line.end) - line.start;
+                let mut width = line.offsets[right] - line.offsets[left];
+                if end > line.end && !line.soft_wrap {
+                    width += newline_width;
+                }
+                self.draw_select.draw_abs(cx, Rect {
+                    pos: rect.pos + dvec2(line.offsets[left], i as f64 * line_spacing - top_drop),
+                    size: dvec2(width, line_spacing + bottom_drop)
+                });
+            }
+        }
+        
+        self.scroll_bars.end(cx);
+        self.draw_bg.end(cx);
+        
+        // keep the cursor in view when it moved
+        if self.drawn_cursor_head != Some(self.cursor_head) {
+            self.drawn_cursor_head = Some(self.cursor_head);
+            self.scroll_bars.scroll_into_view(cx, Rect {
+                pos: head,
+                size: dvec2(self.cursor_size, line_spacing)
+            });
+        }
+        
+        if cx.has_key_focus(self.draw_bg.area()) {
+            if self.numeric_only {
+                cx.hide_text_ime();
+            }
+            else {
+                let ime_abs = rect.pos + head;
+                cx.show_text_ime(self.draw_bg.area(), ime_abs - bg_rect.pos);
+            }
+        }
+        
+        cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+    }
 }
 
 #[derive(Clone, PartialEq, WidgetRef)]
@@ -668,4 +976,85 @@ impl TextInputRef {
         None
     }
     
+    pub fn set_key_focus(&self, cx: &mut Cx) {
+        if let Some(inner) = self.borrow() {
+            inner.set_key_focus(cx);
+        }
+    }
+    
+    pub fn has_key_focus(&self, cx: &Cx) -> bool {
+        if let Some(inner) = self.borrow() {
+            cx.has_key_focus(inner.draw_bg.area())
+        }
+        else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    
+    // lays out text with a line per newline and every char 10 wide
+    fn layout(text: &str) -> TextInputLayout {
+        let mut layout_lines = Vec::new();
+        let mut start = 0;
+        for line in text.split('\n') {
+            layout_lines.push(TextLayoutLine {
+                start,
+                end: start + line.len(),
+                offsets: (0..=line.chars().count()).map( | i | i as f64 * 10.0).collect(),
+            });
+            start += line.len() + 1;
+        }
+        TextInputLayout::new(text, layout_lines)
+    }
+    
+    #[test]
+    fn move_lines_keeps_the_column() {
+        let text = "abcdef\nab\nabcdef";
+        let layout = layout(text);
+        let len = text.chars().count();
+        // from column 5 down onto the short line the cursor goes to its end
+        let (head, x) = layout.move_lines(5, None, 1, len).unwrap();
+        assert_eq!((head, x), (9, 50.0));
+        // and the next line gets column 5 back, going down or up
+        assert_eq!(layout.move_lines(head, Some(x), 1, len), Some((15, 50.0)));
+        assert_eq!(layout.move_lines(head, Some(x), -1, len), Some((5, 50.0)));
+        // without the kept column it starts from where the cursor is
+        assert_eq!(layout.move_lines(head, None, 1, len), Some((12, 20.0)));
+        // past the first and last line it goes to the start and end of the text
+        assert_eq!(layout.move_lines(3, None, -1, len), Some((0, 30.0)));
+        assert_eq!(layout.move_lines(13, None, 1, len), Some((len, 30.0)));
+        assert_eq!(TextInputLayout::default().move_lines(0, None, 1, 0), None);
+    }
+    
+    #[test]
+    fn soft_wrapped_lines() {
+        // "abcd ef" wrapped after the space, the cursor at the wrap is drawn on the second line
+        let text = "abcd ef";
+        let layout = TextInputLayout::new(text, vec![
+            TextLayoutLine {start: 0, end: 5, offsets: vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0]},
+            TextLayoutLine {start: 5, end: 7, offsets: vec![0.0, 10.0, 20.0]},
+        ]);
+        assert!(layout.lines[0].soft_wrap && !layout.lines[1].soft_wrap);
+        assert_eq!((layout.line_of(4), layout.line_of(5)), (0, 1));
+        // the end of a wrapped line is before the space it wraps at
+        assert_eq!(layout.line_end(0), 4);
+        assert_eq!(layout.move_lines(7, None, -1, 7), Some((2, 20.0)));
+        assert_eq!(layout.closest_offset(dvec2(100.0, 5.0), 10.0), 4);
+        assert_eq!(layout.closest_offset(dvec2(100.0, 15.0), 10.0), 7);
+    }
+    
+    #[test]
+    fn max_lines_limits_the_height() {
+        let layout = layout("a\nb\nc\nd\ne");
+        assert_eq!(layout.visible_height(Size::Fit, 3, 10.0).fixed_or_nan(), 30.0);
+        assert_eq!(layout.visible_height(Size::Fit, 0, 10.0).fixed_or_nan(), 50.0);
+        assert_eq!(layout.visible_height(Size::Fit, 8, 10.0).fixed_or_nan(), 50.0);
+        // an empty input is a line high, and a fixed height is left to the scroll bars
+        assert_eq!(TextInputLayout::default().visible_height(Size::Fit, 3, 10.0).fixed_or_nan(), 10.0);
+        assert!(layout.visible_height(Size::Fixed(25.0), 3, 10.0).is_fill());
+    }
 }
@@ -0,0 +1,261 @@
+use crate::makepad_platform::*;
+
+live_design!{
+    // the design tokens the widget styles import. this is the dark set, set_theme swaps
+    // the whole module for one of the other theme_desktop_* token sets at runtime
+    
+    THEME_FONT_LABEL = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_DATA = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_META = { 
+        font_size: 9.4,
+        top_drop: 1.2,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_CODE = {
+        font: {
+            path: dep("crate://self/resources/LiberationMono-Regular.ttf")
+        }
+        brightness: 1.1
+        font_size: 9.0
+        line_spacing: 2.0
+        top_drop: 1.3
+    }
+    
+    const THEME_DATA_ITEM_HEIGHT = 23.0
+    const THEME_DATA_ICON_WIDTH = 16.0
+    const THEME_DATA_ICON_HEIGHT = 24.0
+    // ABSOLUTE DEFS
+    
+    const THEME_BRIGHTNESS = #x40
+    const THEME_COLOR_HIGHLIGHT = #42
+    const THEME_COLOR_HIGH = #C00
+    const THEME_COLOR_MID = #FA0
+    const THEME_COLOR_LOW = #8A0
+    
+    // RELATIVE =DEFS
+    //    42, =78, 117
+    const THEME_COLOR_WHITE = #FFF
+    const THEME_COLOR_UP_80 = #FFFFFFCC
+    const THEME_COLOR_UP_50 = #FFFFFF80
+    const THEME_COLOR_UP_25 = #FFFFFF40
+    const THEME_COLOR_UP_15 = #FFFFFF26
+    const THEME_COLOR_UP_10 = #FFFFFF1A
+    const THEME_COLOR_UP_4 = #FFFFFF0A
+    const THEME_COLOR_DOWN_7 = #00000013
+    const THEME_COLOR_DOWN_10 = #00000030
+    const THEME_COLOR_DOWN_20 = #00000040
+    const THEME_COLOR_DOWN_50 = #00000080
+    const THEME_COLOR_BLACK = #000
+    
+    // CORE BACKGROUND COLORS
+    
+    const THEME_COLOR_BG_APP = (THEME_BRIGHTNESS)
+    
+    const THEME_COLOR_BG_HEADER = (blend(
+        THEME_COLOR_BG_APP,
+        THEME_COLOR_DOWN_10
+    ))
+    
+    const THEME_COLOR_CLEAR = (THEME_COLOR_BG_APP)
+    
+    const THEME_COLOR_BG_EDITOR = (blend(
+        THEME_COLOR_BG_HEADER,
+        THEME_COLOR_DOWN_10
+    ))
+    
+    const THEME_COLOR_BG_ODD = (blend(
+        THEME_COLOR_BG_EDITOR,
+        THEME_COLOR_DOWN_7
+    ))
+    
+    const THEME_COLOR_BG_SELECTED = (THEME_COLOR_HIGHLIGHT)
+    
+    const THEME_COLOR_BG_UNFOCUSSED = (blend(
+        THEME_COLOR_BG_EDITOR,
+        THEME_COLOR_UP_10
+    ))
+    
+    const THEME_COLOR_EDITOR_SELECTED = (THEME_COLOR_BG_SELECTED)
+    const THEME_COLOR_EDITOR_SELECTED_UNFOCUSSED = (THEME_COLOR_BG_SELECTED_UNFOCUSSED)
+    
+    const THEME_COLOR_BG_CURSOR = (blend(
+        THEME_COLOR_BG_EDITOR,
+        THEME_COLOR_UP_4
+    ))
+    
+    const THEME_COLOR_FG_CURSOR = (blend(
+        THEME_COLOR_BG_EDITOR,
+        THEME_COLOR_UP_50
+    ))
+    
+    // TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_DEFAULT = (THEME_COLOR_UP_50)
+    const THEME_COLOR_TEXT_HOVER = (THEME_COLOR_UP_80)
+    const THEME_COLOR_TEXT_META = (THEME_COLOR_UP_25)
+    const THEME_COLOR_TEXT_SELECTED = (THEME_COLOR_UP_80)
+    
+    // SPLITTER AND SCROLLBAR
+    
+    const THEME_COLOR_SCROLL_BAR_DEFAULT = (THEME_COLOR_UP_10)
+    
+    const THEME_COLOR_CONTROL_HOVER = (blend(
+        THEME_COLOR_BG_HEADER,
+        THEME_COLOR_UP_50
+    ))
+    
+    const THEME_COLOR_CONTROL_PRESSED = (blend(
+        THEME_COLOR_BG_HEADER,
+        THEME_COLOR_UP_25
+    ))
+    
+    // ICON COLORS
+    
+    const THEME_COLOR_ICON_WAIT = (THEME_COLOR_LOW),
+    const THEME_COLOR_ERROR = (THEME_COLOR_HIGH),
+    const THEME_COLOR_WARNING = (THEME_COLOR_MID),
+    const THEME_COLOR_ICON_PANIC = (THEME_COLOR_HIGH)
+    const THEME_COLOR_DRAG_QUAD = (THEME_COLOR_UP_50)
+    const THEME_COLOR_PANIC = #f0f
+    
+    const THEME_TAB_HEIGHT = 26.0,
+    const THEME_SPLITTER_HORIZONTAL = 16.0,
+    const THEME_SPLITTER_MIN_HORIZONTAL = (THEME_TAB_HEIGHT),
+    const THEME_SPLITTER_MAX_HORIZONTAL = (THEME_TAB_HEIGHT + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_MIN_VERTICAL = (THEME_SPLITTER_HORIZONTAL),
+    const THEME_SPLITTER_MAX_VERTICAL = (THEME_SPLITTER_HORIZONTAL + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_SIZE = 5.0
+    
+    // CONTROL TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_LABEL = #8
+    const THEME_COLOR_TEXT_CONTROL = #9
+    const THEME_COLOR_TEXT_CONTROL_FOCUS = #b
+    const THEME_COLOR_TEXT_CONTROL_HOVER = #c
+    const THEME_COLOR_TEXT_CONTROL_SELECTED = #f
+    const THEME_COLOR_TEXT_CHECK_BOX = #fff6
+    const THEME_COLOR_TEXT_NAV_CONTROL = #a
+    const THEME_COLOR_TEXT_RADIO = #x00000088
+    const THEME_COLOR_TEXT_RADIO_HOVER = #x000000CC
+    const THEME_COLOR_TEXT_RADIO_SELECTED = #xFFFFFF66
+    const THEME_COLOR_ICON = #8
+    const THEME_COLOR_ICON_HOVER = #c
+    
+    // BUTTON
+    
+    const THEME_COLOR_BUTTON = #53
+    const THEME_COLOR_BUTTON_HOVER = #5c
+    const THEME_COLOR_BUTTON_PRESSED = #33
+    const THEME_COLOR_BUTTON_BEVEL = #6d
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED = #1f
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED_BOTTOM = #5c
+    const THEME_COLOR_BUTTON_SHADOW = #2f
+    const THEME_COLOR_BUTTON_SHADOW_HOVER = #1f
+    
+    const THEME_COLOR_DESKTOP_BUTTON = #3
+    const THEME_COLOR_DESKTOP_BUTTON_HOVER = #6
+    const THEME_COLOR_DESKTOP_BUTTON_PRESSED = #9
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_HOVER = #e00
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_PRESSED = #c00
+    const THEME_COLOR_DESKTOP_BUTTON_XR_HOVER = #0aa
+    const THEME_COLOR_DESKTOP_BUTTON_XR_PRESSED = #077
+    const THEME_COLOR_DESKTOP_BUTTON_ICON = #f
+    
+    // CHECKBOX AND RADIO
+    
+    const THEME_COLOR_CHECK_BOX_TOP = #x00000077
+    const THEME_COLOR_CHECK_BOX_BOTTOM = #x00000044
+    const THEME_COLOR_CHECK_BOX_HOVER_TOP = #x000000AA
+    const THEME_COLOR_CHECK_BOX_HOVER_BOTTOM = #x00000066
+    const THEME_COLOR_CHECK_BOX_BORDER = #888
+    const THEME_COLOR_CHECK_TRACK = #2
+    const THEME_COLOR_CHECK_MARK = #f
+    const THEME_COLOR_RADIO_TAB = #x99EEFF
+    const THEME_COLOR_RADIO_TAB_SELECTED = #00000000
+    
+    // MENUS AND DROPDOWN
+    
+    const THEME_COLOR_BG_MENU = #0
+    const THEME_COLOR_MENU_ITEM_HOVER = #4
+    const THEME_COLOR_DROP_DOWN = #2
+    const THEME_COLOR_DROP_DOWN_HOVER = #3
+    const THEME_COLOR_FOLD_BUTTON = #a
+    const THEME_COLOR_FOLD_BUTTON_HOVER = #f
+    const THEME_COLOR_TAB_DRAG = #c
+    
+    // TEXT INPUT AND SLIDER
+    
+    const THEME_COLOR_TEXT_INPUT = #xFFFFFF55
+    const THEME_COLOR_TEXT_INPUT_HOVER = #xFFFFFF88
+    const THEME_COLOR_TEXT_INPUT_FOCUS = #xFFFFFFCC
+    const THEME_COLOR_TEXT_INPUT_EMPTY = #3
+    const THEME_COLOR_TEXT_INPUT_CURSOR = #f
+    const THEME_COLOR_TEXT_INPUT_SELECTION = #xFFFFFF40
+    const THEME_COLOR_TEXT_INPUT_BG = #0000
+    const THEME_COLOR_TEXT_INPUT_BORDER = #3
+    
+    const THEME_COLOR_SLIDER_TRACK = #38
+    const THEME_COLOR_SLIDER_TRACK_FOCUS = #30
+    const THEME_COLOR_SLIDER_FILL = #5
+    const THEME_COLOR_SLIDER_FILL_HOVER = #68
+    const THEME_COLOR_SLIDER_NUB = #8
+    const THEME_COLOR_SLIDER_NUB_HOVER = #f
+    const THEME_COLOR_SLIDER_NUB_FOCUS = #c
+    const THEME_COLOR_SLIDER_NUB_BG = #8
+    const THEME_COLOR_SLIDER_INPUT = #5
+    
+    // SLIDES
+    
+    const THEME_COLOR_SLIDE_BG = #x1A
+    const THEME_COLOR_SLIDE_TEXT = #D
+    const THEME_COLOR_SLIDE_TITLE = #f
+    const THEME_COLOR_SLIDE_CHAPTER_BG = #xFF5C39
+    const THEME_COLOR_SLIDE_CHAPTER_TITLE = #x181818
+    
+    // WINDOW
+    
+    const THEME_COLOR_NAV_FOCUS = #000f
+    const THEME_COLOR_MOUSE_CURSOR = #000
+    const THEME_COLOR_MOUSE_CURSOR_BORDER = #fff
+    const THEME_COLOR_SHADOW = #000
+}
+
+/// The token sets the desktop widget styles can be switched between with `set_theme`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    DesktopDark,
+    DesktopLight,
+    DesktopHighContrast,
+}
+
+impl Theme {
+    fn module_id(&self) -> LiveModuleId {
+        match self {
+            Self::DesktopDark => LiveModuleId(live_id!(makepad_widgets), live_id!(theme)),
+            Self::DesktopLight => LiveModuleId(live_id!(makepad_widgets), live_id!(theme_desktop_light)),
+            Self::DesktopHighContrast => LiveModuleId(live_id!(makepad_widgets), live_id!(theme_desktop_high_contrast)),
+        }
+    }
+}
+
+/// Switches all widgets over to the tokens of `theme`. The switch happens on the next event loop
+/// iteration, where the application gets re-applied the same way a live edit does.
+pub fn set_theme(cx: &mut Cx, theme: Theme) {
+    cx.replace_live_design(Theme::DesktopDark.module_id(), theme.module_id());
+}
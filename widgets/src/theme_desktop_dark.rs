@@ -3,145 +3,8 @@ use crate::makepad_platform::*;
 live_design!{
     import makepad_draw::shader::std::*;
     import crate::base::*;
-    
-    THEME_FONT_LABEL = {
-        font_size: 9.4,
-        font: {
-            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
-        }
-    }
-    
-    THEME_FONT_DATA = {
-        font_size: 9.4,
-        font: {
-            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
-        }
-    }
-    
-    THEME_FONT_META = { 
-        font_size: 9.4,
-        top_drop: 1.2,
-        font: {
-            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
-        }
-    }
-    
-    THEME_FONT_CODE = {
-        font: {
-            path: dep("crate://self/resources/LiberationMono-Regular.ttf")
-        }
-        brightness: 1.1
-        font_size: 9.0
-        line_spacing: 2.0
-        top_drop: 1.3
-    }
-    
-    const THEME_DATA_ITEM_HEIGHT = 23.0
-    const THEME_DATA_ICON_WIDTH = 16.0
-    const THEME_DATA_ICON_HEIGHT = 24.0
-    // ABSOLUTE DEFS
-    
-    const THEME_BRIGHTNESS = #x40
-    const THEME_COLOR_HIGHLIGHT = #42
-    const THEME_COLOR_HIGH = #C00
-    const THEME_COLOR_MID = #FA0
-    const THEME_COLOR_LOW = #8A0
-    
-    // RELATIVE =DEFS
-    //    42, =78, 117
-    const THEME_COLOR_WHITE = #FFF
-    const THEME_COLOR_UP_80 = #FFFFFFCC
-    const THEME_COLOR_UP_50 = #FFFFFF80
-    const THEME_COLOR_UP_25 = #FFFFFF40
-    const THEME_COLOR_UP_15 = #FFFFFF26
-    const THEME_COLOR_UP_10 = #FFFFFF1A
-    const THEME_COLOR_UP_4 = #FFFFFF0A
-    const THEME_COLOR_DOWN_7 = #00000013
-    const THEME_COLOR_DOWN_10 = #00000030
-    const THEME_COLOR_DOWN_20 = #00000040
-    const THEME_COLOR_DOWN_50 = #00000080
-    const THEME_COLOR_BLACK = #000
-    
-    // CORE BACKGROUND COLORS
-    
-    const THEME_COLOR_BG_APP = (THEME_BRIGHTNESS)
-    
-    const THEME_COLOR_BG_HEADER = (blend(
-        THEME_COLOR_BG_APP,
-        THEME_COLOR_DOWN_10
-    ))
-    
-    const THEME_COLOR_CLEAR = (THEME_COLOR_BG_APP)
-    
-    const THEME_COLOR_BG_EDITOR = (blend(
-        THEME_COLOR_BG_HEADER,
-        THEME_COLOR_DOWN_10
-    ))
-    
-    const THEME_COLOR_BG_ODD = (blend(
-        THEME_COLOR_BG_EDITOR,
-        THEME_COLOR_DOWN_7
-    ))
-    
-    const THEME_COLOR_BG_SELECTED = (THEME_COLOR_HIGHLIGHT)
-    
-    const THEME_COLOR_BG_UNFOCUSSED = (blend(
-        THEME_COLOR_BG_EDITOR,
-        THEME_COLOR_UP_10
-    ))
-    
-    const THEME_COLOR_EDITOR_SELECTED = (THEME_COLOR_BG_SELECTED)
-    const THEME_COLOR_EDITOR_SELECTED_UNFOCUSSED = (THEME_COLOR_BG_SELECTED_UNFOCUSSED)
-    
-    const THEME_COLOR_BG_CURSOR = (blend(
-        THEME_COLOR_BG_EDITOR,
-        THEME_COLOR_UP_4
-    ))
-    
-    const THEME_COLOR_FG_CURSOR = (blend(
-        THEME_COLOR_BG_EDITOR,
-        THEME_COLOR_UP_50
-    ))
-    
-    // TEXT / ICON COLORS
-    
-    const THEME_COLOR_TEXT_DEFAULT = (THEME_COLOR_UP_50)
-    const THEME_COLOR_TEXT_HOVER = (THEME_COLOR_UP_80)
-    const THEME_COLOR_TEXT_META = (THEME_COLOR_UP_25)
-    const THEME_COLOR_TEXT_SELECTED = (THEME_COLOR_UP_80)
-    
-    // SPLITTER AND SCROLLBAR
-    
-    const THEME_COLOR_SCROLL_BAR_DEFAULT = (THEME_COLOR_UP_10)
-    
-    const THEME_COLOR_CONTROL_HOVER = (blend(
-        THEME_COLOR_BG_HEADER,
-        THEME_COLOR_UP_50
-    ))
-    
-    const THEME_COLOR_CONTROL_PRESSED = (blend(
-        THEME_COLOR_BG_HEADER,
-        THEME_COLOR_UP_25
-    ))
-    
-    // ICON COLORS
-    
-    const THEME_COLOR_ICON_WAIT = (THEME_COLOR_LOW),
-    const THEME_COLOR_ERROR = (THEME_COLOR_HIGH),
-    const THEME_COLOR_WARNING = (THEME_COLOR_MID),
-    const THEME_COLOR_ICON_PANIC = (THEME_COLOR_HIGH)
-    const THEME_COLOR_DRAG_QUAD = (THEME_COLOR_UP_50)
-    const THEME_COLOR_PANIC = #f0f
-    
-    const THEME_TAB_HEIGHT = 26.0,
-    const THEME_SPLITTER_HORIZONTAL = 16.0,
-    const THEME_SPLITTER_MIN_HORIZONTAL = (THEME_TAB_HEIGHT),
-    const THEME_SPLITTER_MAX_HORIZONTAL = (THEME_TAB_HEIGHT + THEME_SPLITTER_SIZE),
-    const THEME_SPLITTER_MIN_VERTICAL = (THEME_SPLITTER_HORIZONTAL),
-    const THEME_SPLITTER_MAX_VERTICAL = (THEME_SPLITTER_HORIZONTAL + THEME_SPLITTER_SIZE),
-    const THEME_SPLITTER_SIZE = 5.0
-    
-    
+    // the THEME_* tokens used to live here, so importing this module still brings them in
+    pub import crate::theme::*;
     
     ScrollBar = <ScrollBarBase> {
         bar_size: 10.0,
@@ -235,7 +98,7 @@ live_design!{
         width: Fit
         height: Fit
         draw_text: {
-            color: #8,
+            color: (THEME_COLOR_TEXT_LABEL),
             text_style: <THEME_FONT_LABEL>{}
             wrap: Word
         }
@@ -266,11 +129,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #9,
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #9,
+                    THEME_COLOR_TEXT_CONTROL,
                     self.pressed
                 )
             }
@@ -282,11 +145,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #9,
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #9,
+                    THEME_COLOR_TEXT_CONTROL,
                     self.pressed
                 )
             }
@@ -301,11 +164,11 @@ live_design!{
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size);
                 let grad_top = 5.0;
                 let grad_bot = 1.0;
-                let body = mix(mix(#53, #5c, self.hover), #33, self.pressed);
+                let body = mix(mix(THEME_COLOR_BUTTON, THEME_COLOR_BUTTON_HOVER, self.hover), THEME_COLOR_BUTTON_PRESSED, self.pressed);
                 let body_transp = vec4(body.xyz, 0.0);
-                let top_gradient = mix(body_transp, mix(#6d, #1f, self.pressed), max(0.0, grad_top - sdf.pos.y) / grad_top);
+                let top_gradient = mix(body_transp, mix(THEME_COLOR_BUTTON_BEVEL, THEME_COLOR_BUTTON_BEVEL_PRESSED, self.pressed), max(0.0, grad_top - sdf.pos.y) / grad_top);
                 let bot_gradient = mix(
-                    mix(body_transp, #5c, self.pressed),
+                    mix(body_transp, THEME_COLOR_BUTTON_BEVEL_PRESSED_BOTTOM, self.pressed),
                     top_gradient,
                     clamp((self.rect_size.y - grad_bot - sdf.pos.y - 1.0) / grad_bot, 0.0, 1.0)
                 );
@@ -315,7 +178,7 @@ live_design!{
                 sdf.move_to(shift_inward, self.rect_size.y - self.border_radius);
                 sdf.line_to(self.rect_size.x - shift_inward, self.rect_size.y - self.border_radius);
                 sdf.stroke(
-                    mix(mix(#2f, #1f, self.hover), #0000, self.pressed),
+                    mix(mix(THEME_COLOR_BUTTON_SHADOW, THEME_COLOR_BUTTON_SHADOW_HOVER, self.hover), #0000, self.pressed),
                     self.border_radius
                 )
                 
@@ -403,38 +266,38 @@ live_design!{
                         let sz = self.size;
                         let c = vec2(left + sz, self.rect_size.y * 0.5);
                         sdf.box(left, c.y - sz, sz * 2.0, sz * 2.0, 3.0); // rounding = 3rd value
-                        sdf.fill_keep(mix(mix(#x00000077, #x00000044, pow(self.pos.y, 1.)), mix(#x000000AA, #x00000066, pow(self.pos.y, 1.0)), self.hover))
-                        sdf.stroke(#x888, 1.0) // outline
+                        sdf.fill_keep(mix(mix(THEME_COLOR_CHECK_BOX_TOP, THEME_COLOR_CHECK_BOX_BOTTOM, pow(self.pos.y, 1.)), mix(THEME_COLOR_CHECK_BOX_HOVER_TOP, THEME_COLOR_CHECK_BOX_HOVER_BOTTOM, pow(self.pos.y, 1.0)), self.hover))
+                        sdf.stroke(THEME_COLOR_CHECK_BOX_BORDER, 1.0) // outline
                         let szs = sz * 0.5;
                         let dx = 1.0;
                         sdf.move_to(left + 4.0, c.y);
                         sdf.line_to(c.x, c.y + szs);
                         sdf.line_to(c.x + szs, c.y - szs);
-                        sdf.stroke(mix(#fff0, #f, self.selected), 1.25);
+                        sdf.stroke(mix(vec4(THEME_COLOR_CHECK_MARK.xyz, 0.0), THEME_COLOR_CHECK_MARK, self.selected), 1.25);
                     }
                     CheckType::Radio => {
                         let sz = self.size;
                         let left = sz + 1.;
                         let c = vec2(left + sz, self.rect_size.y * 0.5);
                         sdf.circle(left, c.y, sz);
-                        sdf.fill(#2);
+                        sdf.fill(THEME_COLOR_CHECK_TRACK);
                         let isz = sz * 0.5;
                         sdf.circle(left, c.y, isz);
-                        sdf.fill(mix(#fff0, #f, self.selected));
+                        sdf.fill(mix(vec4(THEME_COLOR_CHECK_MARK.xyz, 0.0), THEME_COLOR_CHECK_MARK, self.selected));
                     }
                     CheckType::Toggle => {
                         let sz = self.size;
                         let left = sz + 1.;
                         let c = vec2(left + sz, self.rect_size.y * 0.5);
                         sdf.box(left, c.y - sz, sz * 3.0, sz * 2.0, 0.5 * sz);
-                        sdf.fill(#2);
+                        sdf.fill(THEME_COLOR_CHECK_TRACK);
                         let isz = sz * 0.5;
                         sdf.circle(left + sz + self.selected * sz, c.y, isz);
                         sdf.circle(left + sz + self.selected * sz, c.y, 0.5 * isz);
                         sdf.subtract();
                         sdf.circle(left + sz + self.selected * sz, c.y, isz);
                         sdf.blend(self.selected)
-                        sdf.fill(#f);
+                        sdf.fill(THEME_COLOR_CHECK_MARK);
                     }
                     CheckType::None => {
                         return #0000
@@ -444,7 +307,7 @@ live_design!{
             }
         }
         draw_text: {
-            color: #9,
+            color: (THEME_COLOR_TEXT_CONTROL),
             instance focus: 0.0
             instance selected: 0.0
             instance hover: 0.0
@@ -457,11 +320,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #fff6,
-                        #fff6,
+                        THEME_COLOR_TEXT_CHECK_BOX,
+                        THEME_COLOR_TEXT_CHECK_BOX,
                         self.hover
                     ),
-                    #fff6,
+                    THEME_COLOR_TEXT_CHECK_BOX,
                     self.selected
                 )
             }
@@ -474,11 +337,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #9,
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #f,
+                    THEME_COLOR_TEXT_CONTROL_SELECTED,
                     self.selected
                 )
             }
@@ -557,40 +420,40 @@ live_design!{
                 // WindowsMin
                 match self.button_type {
                     DesktopButtonType::WindowsMin => {
-                        sdf.clear(mix(#3, mix(#6, #9, self.pressed), self.hover));
+                        sdf.clear(mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_HOVER, THEME_COLOR_DESKTOP_BUTTON_PRESSED, self.pressed), self.hover));
                         sdf.move_to(c.x - sz, c.y);
                         sdf.line_to(c.x + sz, c.y);
-                        sdf.stroke(#f, 0.5 + 0.5 * self.dpi_dilate);
+                        sdf.stroke(THEME_COLOR_DESKTOP_BUTTON_ICON, 0.5 + 0.5 * self.dpi_dilate);
                         return sdf.result;
                     }
                     DesktopButtonType::WindowsMax => {
-                        sdf.clear(mix(#3, mix(#6, #9, self.pressed), self.hover));
+                        sdf.clear(mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_HOVER, THEME_COLOR_DESKTOP_BUTTON_PRESSED, self.pressed), self.hover));
                         sdf.rect(c.x - sz, c.y - sz, 2. * sz, 2. * sz);
-                        sdf.stroke(#f, 0.5 + 0.5 * self.dpi_dilate);
+                        sdf.stroke(THEME_COLOR_DESKTOP_BUTTON_ICON, 0.5 + 0.5 * self.dpi_dilate);
                         return sdf.result;
                     }
                     DesktopButtonType::WindowsMaxToggled => {
-                        let clear = mix(#3, mix(#6, #9, self.pressed), self.hover);
+                        let clear = mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_HOVER, THEME_COLOR_DESKTOP_BUTTON_PRESSED, self.pressed), self.hover);
                         sdf.clear(clear);
                         let sz = 3.5;
                         sdf.rect(c.x - sz + 1., c.y - sz - 1., 2. * sz, 2. * sz);
-                        sdf.stroke(#f, 0.5 + 0.5 * self.dpi_dilate);
+                        sdf.stroke(THEME_COLOR_DESKTOP_BUTTON_ICON, 0.5 + 0.5 * self.dpi_dilate);
                         sdf.rect(c.x - sz - 1., c.y - sz + 1., 2. * sz, 2. * sz);
                         sdf.fill_keep(clear);
-                        sdf.stroke(#f, 0.5 + 0.5 * self.dpi_dilate);
+                        sdf.stroke(THEME_COLOR_DESKTOP_BUTTON_ICON, 0.5 + 0.5 * self.dpi_dilate);
                         return sdf.result;
                     }
                     DesktopButtonType::WindowsClose => {
-                        sdf.clear(mix(#3, mix(#e00, #c00, self.pressed), self.hover));
+                        sdf.clear(mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_CLOSE_HOVER, THEME_COLOR_DESKTOP_BUTTON_CLOSE_PRESSED, self.pressed), self.hover));
                         sdf.move_to(c.x - sz, c.y - sz);
                         sdf.line_to(c.x + sz, c.y + sz);
                         sdf.move_to(c.x - sz, c.y + sz);
                         sdf.line_to(c.x + sz, c.y - sz);
-                        sdf.stroke(#f, 0.5 + 0.5 * self.dpi_dilate);
+                        sdf.stroke(THEME_COLOR_DESKTOP_BUTTON_ICON, 0.5 + 0.5 * self.dpi_dilate);
                         return sdf.result;
                     }
                     DesktopButtonType::XRMode => {
-                        sdf.clear(mix(#3, mix(#0aa, #077, self.pressed), self.hover));
+                        sdf.clear(mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_XR_HOVER, THEME_COLOR_DESKTOP_BUTTON_XR_PRESSED, self.pressed), self.hover));
                         let w = 12.;
                         let h = 8.;
                         sdf.box(c.x - w, c.y - h, 2. * w, 2. * h, 2.);
@@ -601,13 +464,13 @@ live_design!{
                         sdf.subtract();
                         sdf.circle(c.x, c.y + h - 0.75, 2.5);
                         sdf.subtract();
-                        sdf.fill(#8);
+                        sdf.fill(THEME_COLOR_ICON);
                         
                         return sdf.result;
                     }
                     DesktopButtonType::Fullscreen => {
                         sz = 8.;
-                        sdf.clear(mix(#3, mix(#6, #9, self.pressed), self.hover));
+                        sdf.clear(mix(THEME_COLOR_DESKTOP_BUTTON, mix(THEME_COLOR_DESKTOP_BUTTON_HOVER, THEME_COLOR_DESKTOP_BUTTON_PRESSED, self.pressed), self.hover));
                         sdf.rect(c.x - sz, c.y - sz, 2. * sz, 2. * sz);
                         sdf.rect(c.x - sz + 1.5, c.y - sz + 1.5, 2. * (sz - 1.5), 2. * (sz - 1.5));
                         sdf.subtract();
@@ -615,7 +478,7 @@ live_design!{
                         sdf.subtract();
                         sdf.rect(c.x - sz - 2., c.y - sz + 4., 2. * (sz + 2.), 2. * (sz - 4.));
                         sdf.subtract();
-                        sdf.fill(#f); //, 0.5 + 0.5 * dpi_dilate);
+                        sdf.fill(THEME_COLOR_DESKTOP_BUTTON_ICON); //, 0.5 + 0.5 * dpi_dilate);
                         
                         return sdf.result;
                     }
@@ -662,14 +525,14 @@ live_design!{
     NavControl = <NavControlBase> {
         draw_focus: {
             fn pixel(self) -> vec4 {
-                return #000f
+                return THEME_COLOR_NAV_FOCUS
             }
         }
         draw_text: {
             text_style: {
                 font_size: 6
             },
-            color: #a
+            color: (THEME_COLOR_TEXT_NAV_CONTROL)
         }
     }
     
@@ -734,8 +597,8 @@ live_design!{
         mouse_cursor_size: vec2(20, 20),
         draw_cursor: {
             instance border_width: 1.5
-            instance color: #000
-            instance border_color: #fff
+            instance color: (THEME_COLOR_MOUSE_CURSOR)
+            instance border_color: (THEME_COLOR_MOUSE_CURSOR_BORDER)
             
             fn get_color(self) -> vec4 {
                 return self.color
@@ -1002,7 +865,7 @@ live_design!{
         tab: <Tab> {}
         draw_drag: {
             draw_depth: 10
-            color: #c
+            color: (THEME_COLOR_TAB_DRAG)
         }
         draw_fill: {
             color: (THEME_COLOR_BG_HEADER)
@@ -1092,8 +955,8 @@ live_design!{
         draw_bg: {
             instance selected: 0.0
             instance hover: 0.0
-            instance color: #0
-            instance color_selected: #4
+            instance color: (THEME_COLOR_BG_MENU)
+            instance color_selected: (THEME_COLOR_MENU_ITEM_HOVER)
             
             fn pixel(self) -> vec4 {
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size);
@@ -1114,7 +977,7 @@ live_design!{
                 sdf.move_to(c.x - sz + dx * 0.5, c.y - sz + dx);
                 sdf.line_to(c.x, c.y + sz);
                 sdf.line_to(c.x + sz, c.y - sz);
-                sdf.stroke(mix(#fff0, #f, self.selected), 1.0);
+                sdf.stroke(mix(vec4(THEME_COLOR_CHECK_MARK.xyz, 0.0), THEME_COLOR_CHECK_MARK, self.selected), 1.0);
                 
                 return sdf.result;
             }
@@ -1172,7 +1035,7 @@ live_design!{
         height: Fit
         
         draw_bg: {
-            instance color: #0
+            instance color: (THEME_COLOR_BG_MENU)
             instance border_width: 0.0,
             instance border_color: #0000,
             instance inset: vec4(0.0, 0.0, 0.0, 0.0),
@@ -1211,14 +1074,14 @@ live_design!{
                 return mix(
                     mix(
                         mix(
-                            #9,
-                            #b,
+                            THEME_COLOR_TEXT_CONTROL,
+                            THEME_COLOR_TEXT_CONTROL_FOCUS,
                             self.focus
                         ),
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #9,
+                    THEME_COLOR_TEXT_CONTROL,
                     self.pressed
                 )
             }
@@ -1238,7 +1101,7 @@ live_design!{
                     self.rect_size.y,
                     self.border_radius
                 )
-                sdf.fill(mix(#2, #3, self.hover));
+                sdf.fill(mix(THEME_COLOR_DROP_DOWN, THEME_COLOR_DROP_DOWN_HOVER, self.hover));
             }
             
             fn pixel(self) -> vec4 {
@@ -1253,7 +1116,7 @@ live_design!{
                 sdf.line_to(c.x, c.y + sz * 0.75);
                 sdf.close_path();
                 
-                sdf.fill(mix(#8, #c, self.hover));
+                sdf.fill(mix(THEME_COLOR_ICON, THEME_COLOR_ICON_HOVER, self.hover));
                 
                 return sdf.result
             }
@@ -1548,7 +1411,7 @@ live_design!{
                 sdf.line_to(c.x, c.y - sz);
                 sdf.line_to(c.x + sz, c.y + sz);
                 sdf.close_path();
-                sdf.fill(mix(#a, #f, self.hover));
+                sdf.fill(mix(THEME_COLOR_FOLD_BUTTON, THEME_COLOR_FOLD_BUTTON_HOVER, self.hover));
                 return sdf.result * self.fade;
             }
         }
@@ -1647,11 +1510,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #9,
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #9,
+                    THEME_COLOR_TEXT_CONTROL,
                     self.pressed
                 )
             }
@@ -1735,8 +1598,8 @@ live_design!{
         draw_radio: {
             
             uniform size: 7.0;
-            uniform color_active: #00000000
-            uniform color_inactive: #x99EEFF
+            uniform color_active: (THEME_COLOR_RADIO_TAB_SELECTED)
+            uniform color_inactive: (THEME_COLOR_RADIO_TAB)
             
             fn pixel(self) -> vec4 {
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size)
@@ -1746,10 +1609,10 @@ live_design!{
                         let left = sz + 1.;
                         let c = vec2(left + sz, self.rect_size.y * 0.5);
                         sdf.circle(left, c.y, sz);
-                        sdf.fill(#2);
+                        sdf.fill(THEME_COLOR_CHECK_TRACK);
                         let isz = sz * 0.5;
                         sdf.circle(left, c.y, isz);
-                        sdf.fill(mix(#fff0, #f, self.selected));
+                        sdf.fill(mix(vec4(THEME_COLOR_CHECK_MARK.xyz, 0.0), THEME_COLOR_CHECK_MARK, self.selected));
                     }
                     RadioType::Tab => {
                         let sz = self.size;
@@ -1772,11 +1635,11 @@ live_design!{
             instance focus: 0.0
             instance selected: 0.0
             
-            uniform color_unselected: #x00000088
-            uniform color_unselected_hover: #x000000CC
-            uniform color_selected: #xFFFFFF66
+            uniform color_unselected: (THEME_COLOR_TEXT_RADIO)
+            uniform color_unselected_hover: (THEME_COLOR_TEXT_RADIO_HOVER)
+            uniform color_selected: (THEME_COLOR_TEXT_RADIO_SELECTED)
             
-            color: #9
+            color: (THEME_COLOR_TEXT_CONTROL)
             text_style: {
                 font: {
                     //path: d"resources/ibmplexsans-semibold.ttf"
@@ -1803,11 +1666,11 @@ live_design!{
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        #9,
-                        #c,
+                        THEME_COLOR_TEXT_CONTROL,
+                        THEME_COLOR_TEXT_CONTROL_HOVER,
                         self.hover
                     ),
-                    #9,
+                    THEME_COLOR_TEXT_CONTROL,
                     self.selected
                 )
             }
@@ -1933,14 +1796,14 @@ live_design!{
                 mix(
                     mix(
                         mix(
-                            #xFFFFFF55,
-                            #xFFFFFF88,
+                            THEME_COLOR_TEXT_INPUT,
+                            THEME_COLOR_TEXT_INPUT_HOVER,
                             self.hover
                         ),
-                        #xFFFFFFCC,
+                        THEME_COLOR_TEXT_INPUT_FOCUS,
                         self.focus
                     ),
-                    #3,
+                    THEME_COLOR_TEXT_INPUT_EMPTY,
                     self.is_empty
                 )
             }
@@ -1957,7 +1820,7 @@ live_design!{
                     self.rect_size.y,
                     self.border_radius
                 )
-                sdf.fill(mix(#ccc0, #f, self.focus));
+                sdf.fill(mix(vec4(THEME_COLOR_TEXT_INPUT_CURSOR.xyz, 0.0), THEME_COLOR_TEXT_INPUT_CURSOR, self.focus));
                 return sdf.result
             }
         }
@@ -1977,7 +1840,7 @@ live_design!{
                     self.rect_size.y,
                     self.border_radius
                 )
-                sdf.fill(mix(#5550, #xFFFFFF40, self.focus)); // Pad color
+                sdf.fill(mix(vec4(THEME_COLOR_TEXT_INPUT_SELECTION.xyz, 0.0), THEME_COLOR_TEXT_INPUT_SELECTION, self.focus)); // Pad color
                 return sdf.result
             }
         }
@@ -1990,9 +1853,10 @@ live_design!{
         on_focus_select_all: false,
         empty_message: "0",
         draw_bg: {
+            color: (THEME_COLOR_TEXT_INPUT_BG)
             instance radius: 2.0
             instance border_width: 0.0
-            instance border_color: #3
+            instance border_color: (THEME_COLOR_TEXT_INPUT_BORDER)
             instance inset: vec4(0.0, 0.0, 0.0, 0.0)
             
             fn get_color(self) -> vec4 {
@@ -2073,6 +1937,13 @@ live_design!{
         }
     }
     
+    TextArea = <TextInput> {
+        multi_line: true,
+        max_lines: 8,
+        width: Fill,
+        empty_message: "",
+        scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}
+    }
     
     Slider = <SliderBase> {
         min: 0.0,
@@ -2091,10 +1962,10 @@ live_design!{
                 
                 let sdf = Sdf2d::viewport(self.pos * self.rect_size)
                 
-                let slider_bg_color = mix(#38, #30, self.focus);
-                let slider_color = mix(mix(#5, #68, self.hover), #68, self.focus);
-                let nub_color = mix(mix(#8, #f, self.hover), mix(#c, #f, self.drag), self.focus);
-                let nubbg_color = mix(#eee0, #8, self.drag);
+                let slider_bg_color = mix(THEME_COLOR_SLIDER_TRACK, THEME_COLOR_SLIDER_TRACK_FOCUS, self.focus);
+                let slider_color = mix(mix(THEME_COLOR_SLIDER_FILL, THEME_COLOR_SLIDER_FILL_HOVER, self.hover), THEME_COLOR_SLIDER_FILL_HOVER, self.focus);
+                let nub_color = mix(mix(THEME_COLOR_SLIDER_NUB, THEME_COLOR_SLIDER_NUB_HOVER, self.hover), mix(THEME_COLOR_SLIDER_NUB_FOCUS, THEME_COLOR_SLIDER_NUB_HOVER, self.drag), self.focus);
+                let nubbg_color = mix(vec4(THEME_COLOR_SLIDER_NUB_BG.xyz, 0.0), THEME_COLOR_SLIDER_NUB_BG, self.drag);
                 
                 match self.slider_type {
                     SliderType::Horizontal => {
@@ -2125,7 +1996,7 @@ live_design!{
         }
         
         draw_text: {
-            color: #9
+            color: (THEME_COLOR_TEXT_CONTROL)
         }
         
         label_walk: {
@@ -2149,7 +2020,7 @@ live_design!{
             numeric_only: true,
             draw_bg: {
                 shape: None
-                color: #5
+                color: (THEME_COLOR_SLIDER_INPUT)
                 radius: 2.0
             },
             
@@ -2211,7 +2082,7 @@ live_design!{
     SlideBody = <Label> {
         margin:{top:20}
         draw_text: {
-            color: #D
+            color: (THEME_COLOR_SLIDE_TEXT)
             text_style: {
                 line_spacing:1.5
                 font:{path: dep("crate://makepad-widgets/resources/IBMPlexSans-Text.ttf")}
@@ -2222,13 +2093,13 @@ live_design!{
     }
     
     Slide = <RoundedView> {
-        draw_bg: {color: #x1A, radius: 5.0}
+        draw_bg: {color: (THEME_COLOR_SLIDE_BG), radius: 5.0}
         width: Fill,
         height: Fill
         align: {x: 0.0, y: 0.5} flow: Down, spacing: 10, padding: 50
         title = <Label> {
             draw_text: {
-                color: #f
+                color: (THEME_COLOR_SLIDE_TITLE)
                 text_style: {
                     line_spacing:1.0
                     font:{path: dep("crate://makepad-widgets/resources/IBMPlexSans-Text.ttf")}
@@ -2240,13 +2111,13 @@ live_design!{
     }
     
     SlideChapter = <Slide> {
-        draw_bg: {color: #xFF5C39, radius: 5.0}
+        draw_bg: {color: (THEME_COLOR_SLIDE_CHAPTER_BG), radius: 5.0}
         width: Fill,
         height: Fill
         align: {x: 0.0, y: 0.5} flow: Down, spacing: 10, padding: 50
         title = <Label> {
             draw_text: {
-                color: #x181818
+                color: (THEME_COLOR_SLIDE_CHAPTER_TITLE)
                 text_style: {
                     line_spacing:1.0
                     font:{path: dep("crate://makepad-widgets/resources/IBMPlexSans-Text.ttf")}
@@ -2277,7 +2148,7 @@ live_design!{
                 alpha = pow(pos.x, 0.5);
             }
             //turn vec4(base,is_viz);
-            return Pal::premul(mix(vec4(#000.xyz, is_viz), vec4(base, 0.), alpha));
+            return Pal::premul(mix(vec4(THEME_COLOR_SHADOW.xyz, is_viz), vec4(base, 0.), alpha));
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,211 @@
+use crate::makepad_platform::*;
+
+live_design!{
+    // high contrast desktop tokens, see crate::theme for how they get swapped in
+    
+    THEME_FONT_LABEL = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_DATA = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_META = { 
+        font_size: 9.4,
+        top_drop: 1.2,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_CODE = {
+        font: {
+            path: dep("crate://self/resources/LiberationMono-Regular.ttf")
+        }
+        brightness: 1.1
+        font_size: 9.0
+        line_spacing: 2.0
+        top_drop: 1.3
+    }
+    
+    const THEME_DATA_ITEM_HEIGHT = 23.0
+    const THEME_DATA_ICON_WIDTH = 16.0
+    const THEME_DATA_ICON_HEIGHT = 24.0
+    // ABSOLUTE DEFS
+    
+    const THEME_BRIGHTNESS = #000
+    const THEME_COLOR_HIGHLIGHT = #x0000C0
+    const THEME_COLOR_HIGH = #xFF4040
+    const THEME_COLOR_MID = #xFFD000
+    const THEME_COLOR_LOW = #x40FF40
+    
+    // RELATIVE =DEFS
+    //    42, =78, 117
+    const THEME_COLOR_WHITE = #FFF
+    const THEME_COLOR_UP_80 = #FFFFFFCC
+    const THEME_COLOR_UP_50 = #FFFFFF80
+    const THEME_COLOR_UP_25 = #FFFFFF40
+    const THEME_COLOR_UP_15 = #FFFFFF26
+    const THEME_COLOR_UP_10 = #FFFFFF1A
+    const THEME_COLOR_UP_4 = #FFFFFF0A
+    const THEME_COLOR_DOWN_7 = #00000013
+    const THEME_COLOR_DOWN_10 = #00000030
+    const THEME_COLOR_DOWN_20 = #00000040
+    const THEME_COLOR_DOWN_50 = #00000080
+    const THEME_COLOR_BLACK = #000
+    
+    // CORE BACKGROUND COLORS
+    
+    const THEME_COLOR_BG_APP = (THEME_BRIGHTNESS)
+    
+    const THEME_COLOR_BG_HEADER = #x1C1C1C
+    
+    const THEME_COLOR_CLEAR = (THEME_COLOR_BG_APP)
+    
+    const THEME_COLOR_BG_EDITOR = #000
+    
+    const THEME_COLOR_BG_ODD = #x0A0A0A
+    
+    const THEME_COLOR_BG_SELECTED = (THEME_COLOR_HIGHLIGHT)
+    
+    const THEME_COLOR_BG_UNFOCUSSED = #x404040
+    
+    const THEME_COLOR_EDITOR_SELECTED = (THEME_COLOR_BG_SELECTED)
+    const THEME_COLOR_EDITOR_SELECTED_UNFOCUSSED = (THEME_COLOR_BG_SELECTED_UNFOCUSSED)
+    
+    const THEME_COLOR_BG_CURSOR = #x1A1A1A
+    
+    const THEME_COLOR_FG_CURSOR = #xFFFF00
+    
+    // TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_DEFAULT = #FFF
+    const THEME_COLOR_TEXT_HOVER = #xFFFF00
+    const THEME_COLOR_TEXT_META = #xC0C0C0
+    const THEME_COLOR_TEXT_SELECTED = #FFF
+    
+    // SPLITTER AND SCROLLBAR
+    
+    const THEME_COLOR_SCROLL_BAR_DEFAULT = #xC0C0C0
+    
+    const THEME_COLOR_CONTROL_HOVER = #xFFFF00
+    
+    const THEME_COLOR_CONTROL_PRESSED = #x00FFFF
+    
+    // ICON COLORS
+    
+    const THEME_COLOR_ICON_WAIT = (THEME_COLOR_LOW),
+    const THEME_COLOR_ERROR = (THEME_COLOR_HIGH),
+    const THEME_COLOR_WARNING = (THEME_COLOR_MID),
+    const THEME_COLOR_ICON_PANIC = (THEME_COLOR_HIGH)
+    const THEME_COLOR_DRAG_QUAD = #xFFFF0080
+    const THEME_COLOR_PANIC = #f0f
+    
+    const THEME_TAB_HEIGHT = 26.0,
+    const THEME_SPLITTER_HORIZONTAL = 16.0,
+    const THEME_SPLITTER_MIN_HORIZONTAL = (THEME_TAB_HEIGHT),
+    const THEME_SPLITTER_MAX_HORIZONTAL = (THEME_TAB_HEIGHT + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_MIN_VERTICAL = (THEME_SPLITTER_HORIZONTAL),
+    const THEME_SPLITTER_MAX_VERTICAL = (THEME_SPLITTER_HORIZONTAL + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_SIZE = 5.0
+    
+    // CONTROL TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_LABEL = #FFF
+    const THEME_COLOR_TEXT_CONTROL = #FFF
+    const THEME_COLOR_TEXT_CONTROL_FOCUS = #xFFFF00
+    const THEME_COLOR_TEXT_CONTROL_HOVER = #xFFFF00
+    const THEME_COLOR_TEXT_CONTROL_SELECTED = #x00FFFF
+    const THEME_COLOR_TEXT_CHECK_BOX = #FFF
+    const THEME_COLOR_TEXT_NAV_CONTROL = #FFF
+    const THEME_COLOR_TEXT_RADIO = #FFF
+    const THEME_COLOR_TEXT_RADIO_HOVER = #xFFFF00
+    const THEME_COLOR_TEXT_RADIO_SELECTED = #FFF
+    const THEME_COLOR_ICON = #FFF
+    const THEME_COLOR_ICON_HOVER = #xFFFF00
+    
+    // BUTTON
+    
+    const THEME_COLOR_BUTTON = #x1A1A1A
+    const THEME_COLOR_BUTTON_HOVER = #x333300
+    const THEME_COLOR_BUTTON_PRESSED = #x004040
+    const THEME_COLOR_BUTTON_BEVEL = #FFF
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED = #x00FFFF
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED_BOTTOM = #x00FFFF
+    const THEME_COLOR_BUTTON_SHADOW = #FFF
+    const THEME_COLOR_BUTTON_SHADOW_HOVER = #xFFFF00
+    
+    const THEME_COLOR_DESKTOP_BUTTON = #000
+    const THEME_COLOR_DESKTOP_BUTTON_HOVER = #x404040
+    const THEME_COLOR_DESKTOP_BUTTON_PRESSED = #x606060
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_HOVER = #F00
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_PRESSED = #C00
+    const THEME_COLOR_DESKTOP_BUTTON_XR_HOVER = #0CC
+    const THEME_COLOR_DESKTOP_BUTTON_XR_PRESSED = #088
+    const THEME_COLOR_DESKTOP_BUTTON_ICON = #FFF
+    
+    // CHECKBOX AND RADIO
+    
+    const THEME_COLOR_CHECK_BOX_TOP = #000
+    const THEME_COLOR_CHECK_BOX_BOTTOM = #000
+    const THEME_COLOR_CHECK_BOX_HOVER_TOP = #x333300
+    const THEME_COLOR_CHECK_BOX_HOVER_BOTTOM = #x333300
+    const THEME_COLOR_CHECK_BOX_BORDER = #FFF
+    const THEME_COLOR_CHECK_TRACK = #x404040
+    const THEME_COLOR_CHECK_MARK = #xFFFF00
+    const THEME_COLOR_RADIO_TAB = #x303030
+    const THEME_COLOR_RADIO_TAB_SELECTED = (THEME_COLOR_HIGHLIGHT)
+    
+    // MENUS AND DROPDOWN
+    
+    const THEME_COLOR_BG_MENU = #000
+    const THEME_COLOR_MENU_ITEM_HOVER = (THEME_COLOR_HIGHLIGHT)
+    const THEME_COLOR_DROP_DOWN = #000
+    const THEME_COLOR_DROP_DOWN_HOVER = #x333300
+    const THEME_COLOR_FOLD_BUTTON = #FFF
+    const THEME_COLOR_FOLD_BUTTON_HOVER = #xFFFF00
+    const THEME_COLOR_TAB_DRAG = #xFFFF00
+    
+    // TEXT INPUT AND SLIDER
+    
+    const THEME_COLOR_TEXT_INPUT = #FFF
+    const THEME_COLOR_TEXT_INPUT_HOVER = #FFF
+    const THEME_COLOR_TEXT_INPUT_FOCUS = #FFF
+    const THEME_COLOR_TEXT_INPUT_EMPTY = #xA0A0A0
+    const THEME_COLOR_TEXT_INPUT_CURSOR = #xFFFF00
+    const THEME_COLOR_TEXT_INPUT_SELECTION = #x3A3AFFC0
+    const THEME_COLOR_TEXT_INPUT_BG = #000
+    const THEME_COLOR_TEXT_INPUT_BORDER = #FFF
+    
+    const THEME_COLOR_SLIDER_TRACK = #x606060
+    const THEME_COLOR_SLIDER_TRACK_FOCUS = #x808080
+    const THEME_COLOR_SLIDER_FILL = #FFF
+    const THEME_COLOR_SLIDER_FILL_HOVER = #xFFFF00
+    const THEME_COLOR_SLIDER_NUB = #FFF
+    const THEME_COLOR_SLIDER_NUB_HOVER = #xFFFF00
+    const THEME_COLOR_SLIDER_NUB_FOCUS = #x00FFFF
+    const THEME_COLOR_SLIDER_NUB_BG = #000
+    const THEME_COLOR_SLIDER_INPUT = #x1A1A1A
+    
+    // SLIDES
+    
+    const THEME_COLOR_SLIDE_BG = #000
+    const THEME_COLOR_SLIDE_TEXT = #FFF
+    const THEME_COLOR_SLIDE_TITLE = #xFFFF00
+    const THEME_COLOR_SLIDE_CHAPTER_BG = (THEME_COLOR_HIGHLIGHT)
+    const THEME_COLOR_SLIDE_CHAPTER_TITLE = #FFF
+    
+    // WINDOW
+    
+    const THEME_COLOR_NAV_FOCUS = #000f
+    const THEME_COLOR_MOUSE_CURSOR = #xFFFF00
+    const THEME_COLOR_MOUSE_CURSOR_BORDER = #000
+    const THEME_COLOR_SHADOW = #000
+}
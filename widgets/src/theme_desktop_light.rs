@@ -0,0 +1,211 @@
+use crate::makepad_platform::*;
+
+live_design!{
+    // light desktop tokens, see crate::theme for how they get swapped in
+    
+    THEME_FONT_LABEL = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_DATA = {
+        font_size: 9.4,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_META = { 
+        font_size: 9.4,
+        top_drop: 1.2,
+        font: {
+            path: dep("crate://self/resources/GoNotoKurrent-Regular.ttf")
+        }
+    }
+    
+    THEME_FONT_CODE = {
+        font: {
+            path: dep("crate://self/resources/LiberationMono-Regular.ttf")
+        }
+        brightness: 1.1
+        font_size: 9.0
+        line_spacing: 2.0
+        top_drop: 1.3
+    }
+    
+    const THEME_DATA_ITEM_HEIGHT = 23.0
+    const THEME_DATA_ICON_WIDTH = 16.0
+    const THEME_DATA_ICON_HEIGHT = 24.0
+    // ABSOLUTE DEFS
+    
+    const THEME_BRIGHTNESS = #xE4
+    const THEME_COLOR_HIGHLIGHT = #xC4D7EE
+    const THEME_COLOR_HIGH = #C00
+    const THEME_COLOR_MID = #C70
+    const THEME_COLOR_LOW = #580
+    
+    // RELATIVE =DEFS
+    //    42, =78, 117
+    const THEME_COLOR_WHITE = #FFF
+    const THEME_COLOR_UP_80 = #FFFFFFCC
+    const THEME_COLOR_UP_50 = #FFFFFF80
+    const THEME_COLOR_UP_25 = #FFFFFF40
+    const THEME_COLOR_UP_15 = #FFFFFF26
+    const THEME_COLOR_UP_10 = #FFFFFF1A
+    const THEME_COLOR_UP_4 = #FFFFFF0A
+    const THEME_COLOR_DOWN_7 = #00000013
+    const THEME_COLOR_DOWN_10 = #00000030
+    const THEME_COLOR_DOWN_20 = #00000040
+    const THEME_COLOR_DOWN_50 = #00000080
+    const THEME_COLOR_BLACK = #000
+    
+    // CORE BACKGROUND COLORS
+    
+    const THEME_COLOR_BG_APP = (THEME_BRIGHTNESS)
+    
+    const THEME_COLOR_BG_HEADER = #xD6D6D6
+    
+    const THEME_COLOR_CLEAR = (THEME_COLOR_BG_APP)
+    
+    const THEME_COLOR_BG_EDITOR = #xF6F6F6
+    
+    const THEME_COLOR_BG_ODD = #xEEEEEE
+    
+    const THEME_COLOR_BG_SELECTED = (THEME_COLOR_HIGHLIGHT)
+    
+    const THEME_COLOR_BG_UNFOCUSSED = #xDCDCDC
+    
+    const THEME_COLOR_EDITOR_SELECTED = (THEME_COLOR_BG_SELECTED)
+    const THEME_COLOR_EDITOR_SELECTED_UNFOCUSSED = (THEME_COLOR_BG_SELECTED_UNFOCUSSED)
+    
+    const THEME_COLOR_BG_CURSOR = #xECECEC
+    
+    const THEME_COLOR_FG_CURSOR = #x333333
+    
+    // TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_DEFAULT = #x000000A0
+    const THEME_COLOR_TEXT_HOVER = #x000000E0
+    const THEME_COLOR_TEXT_META = #x00000070
+    const THEME_COLOR_TEXT_SELECTED = #x000000E0
+    
+    // SPLITTER AND SCROLLBAR
+    
+    const THEME_COLOR_SCROLL_BAR_DEFAULT = (THEME_COLOR_DOWN_20)
+    
+    const THEME_COLOR_CONTROL_HOVER = #x8C8C8C
+    
+    const THEME_COLOR_CONTROL_PRESSED = #xA8A8A8
+    
+    // ICON COLORS
+    
+    const THEME_COLOR_ICON_WAIT = (THEME_COLOR_LOW),
+    const THEME_COLOR_ERROR = (THEME_COLOR_HIGH),
+    const THEME_COLOR_WARNING = (THEME_COLOR_MID),
+    const THEME_COLOR_ICON_PANIC = (THEME_COLOR_HIGH)
+    const THEME_COLOR_DRAG_QUAD = (THEME_COLOR_DOWN_20)
+    const THEME_COLOR_PANIC = #f0f
+    
+    const THEME_TAB_HEIGHT = 26.0,
+    const THEME_SPLITTER_HORIZONTAL = 16.0,
+    const THEME_SPLITTER_MIN_HORIZONTAL = (THEME_TAB_HEIGHT),
+    const THEME_SPLITTER_MAX_HORIZONTAL = (THEME_TAB_HEIGHT + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_MIN_VERTICAL = (THEME_SPLITTER_HORIZONTAL),
+    const THEME_SPLITTER_MAX_VERTICAL = (THEME_SPLITTER_HORIZONTAL + THEME_SPLITTER_SIZE),
+    const THEME_SPLITTER_SIZE = 5.0
+    
+    // CONTROL TEXT / ICON COLORS
+    
+    const THEME_COLOR_TEXT_LABEL = #x555555
+    const THEME_COLOR_TEXT_CONTROL = #x444444
+    const THEME_COLOR_TEXT_CONTROL_FOCUS = #x333333
+    const THEME_COLOR_TEXT_CONTROL_HOVER = #x111111
+    const THEME_COLOR_TEXT_CONTROL_SELECTED = #x000000
+    const THEME_COLOR_TEXT_CHECK_BOX = #x000000AA
+    const THEME_COLOR_TEXT_NAV_CONTROL = #x555555
+    const THEME_COLOR_TEXT_RADIO = #x00000088
+    const THEME_COLOR_TEXT_RADIO_HOVER = #x000000CC
+    const THEME_COLOR_TEXT_RADIO_SELECTED = #x000000EE
+    const THEME_COLOR_ICON = #x777777
+    const THEME_COLOR_ICON_HOVER = #x333333
+    
+    // BUTTON
+    
+    const THEME_COLOR_BUTTON = #xF2F2F2
+    const THEME_COLOR_BUTTON_HOVER = #xFBFBFB
+    const THEME_COLOR_BUTTON_PRESSED = #xD2D2D2
+    const THEME_COLOR_BUTTON_BEVEL = #xFFFFFF
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED = #xB4B4B4
+    const THEME_COLOR_BUTTON_BEVEL_PRESSED_BOTTOM = #xE6E6E6
+    const THEME_COLOR_BUTTON_SHADOW = #xB4B4B4
+    const THEME_COLOR_BUTTON_SHADOW_HOVER = #xA0A0A0
+    
+    const THEME_COLOR_DESKTOP_BUTTON = #xE4E4E4
+    const THEME_COLOR_DESKTOP_BUTTON_HOVER = #xCFCFCF
+    const THEME_COLOR_DESKTOP_BUTTON_PRESSED = #xB8B8B8
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_HOVER = #xE81123
+    const THEME_COLOR_DESKTOP_BUTTON_CLOSE_PRESSED = #xC50F1F
+    const THEME_COLOR_DESKTOP_BUTTON_XR_HOVER = #x3BB
+    const THEME_COLOR_DESKTOP_BUTTON_XR_PRESSED = #x299
+    const THEME_COLOR_DESKTOP_BUTTON_ICON = #x222222
+    
+    // CHECKBOX AND RADIO
+    
+    const THEME_COLOR_CHECK_BOX_TOP = #xFFFFFF
+    const THEME_COLOR_CHECK_BOX_BOTTOM = #xF0F0F0
+    const THEME_COLOR_CHECK_BOX_HOVER_TOP = #xF4F8FF
+    const THEME_COLOR_CHECK_BOX_HOVER_BOTTOM = #xE2EAF6
+    const THEME_COLOR_CHECK_BOX_BORDER = #x888888
+    const THEME_COLOR_CHECK_TRACK = #xC6C6C6
+    const THEME_COLOR_CHECK_MARK = #x222222
+    const THEME_COLOR_RADIO_TAB = #xA8DEEC
+    const THEME_COLOR_RADIO_TAB_SELECTED = #00000000
+    
+    // MENUS AND DROPDOWN
+    
+    const THEME_COLOR_BG_MENU = #xFAFAFA
+    const THEME_COLOR_MENU_ITEM_HOVER = (THEME_COLOR_HIGHLIGHT)
+    const THEME_COLOR_DROP_DOWN = #xF2F2F2
+    const THEME_COLOR_DROP_DOWN_HOVER = #xFBFBFB
+    const THEME_COLOR_FOLD_BUTTON = #x777777
+    const THEME_COLOR_FOLD_BUTTON_HOVER = #x222222
+    const THEME_COLOR_TAB_DRAG = #x555555
+    
+    // TEXT INPUT AND SLIDER
+    
+    const THEME_COLOR_TEXT_INPUT = #x00000099
+    const THEME_COLOR_TEXT_INPUT_HOVER = #x000000BB
+    const THEME_COLOR_TEXT_INPUT_FOCUS = #x000000EE
+    const THEME_COLOR_TEXT_INPUT_EMPTY = #x00000055
+    const THEME_COLOR_TEXT_INPUT_CURSOR = #x222222
+    const THEME_COLOR_TEXT_INPUT_SELECTION = #x3070D040
+    const THEME_COLOR_TEXT_INPUT_BG = #xFFFFFF
+    const THEME_COLOR_TEXT_INPUT_BORDER = #xBBBBBB
+    
+    const THEME_COLOR_SLIDER_TRACK = #xC6C6C6
+    const THEME_COLOR_SLIDER_TRACK_FOCUS = #xBBBBBB
+    const THEME_COLOR_SLIDER_FILL = #x8A8A8A
+    const THEME_COLOR_SLIDER_FILL_HOVER = #x5A82C0
+    const THEME_COLOR_SLIDER_NUB = #x555555
+    const THEME_COLOR_SLIDER_NUB_HOVER = #x111111
+    const THEME_COLOR_SLIDER_NUB_FOCUS = #x333333
+    const THEME_COLOR_SLIDER_NUB_BG = (THEME_COLOR_BG_APP)
+    const THEME_COLOR_SLIDER_INPUT = #xF8F8F8
+    
+    // SLIDES
+    
+    const THEME_COLOR_SLIDE_BG = #xF2F2F2
+    const THEME_COLOR_SLIDE_TEXT = #x333333
+    const THEME_COLOR_SLIDE_TITLE = #x111111
+    const THEME_COLOR_SLIDE_CHAPTER_BG = #xFF5C39
+    const THEME_COLOR_SLIDE_CHAPTER_TITLE = #x181818
+    
+    // WINDOW
+    
+    const THEME_COLOR_NAV_FOCUS = #xFFFFFFFF
+    const THEME_COLOR_MOUSE_CURSOR = #000
+    const THEME_COLOR_MOUSE_CURSOR_BORDER = #fff
+    const THEME_COLOR_SHADOW = #x888888
+}
@@ -510,6 +510,10 @@ impl Widget for View {
         self.walk
     }
     
+    fn area(&self) -> Area {
+        self.area
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         self.draw_walk(cx, walk)
     }
@@ -567,6 +571,14 @@ impl Widget for View {
             }
         }
     }
+    
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        for id in &self.draw_order {
+            if let Some(child) = self.children.get(id) {
+                visit(child);
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
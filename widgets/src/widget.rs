@@ -40,6 +40,10 @@ pub trait Widget: LiveApply {
     fn find_widgets(&mut self, _path: &[LiveId], _cached: WidgetCache, _results: &mut WidgetSet) {
     }
     
+    // containers pass their direct children in draw order, used by the widget inspector
+    fn visit_children(&mut self, _visit: &mut dyn FnMut(&WidgetRef)) {
+    }
+    
     fn widget(&mut self, path: &[LiveId]) -> WidgetRef {
         let mut results = WidgetSet::default();
         self.find_widgets(path, WidgetCache::Yes, &mut results);
@@ -62,6 +66,7 @@ pub trait Widget: LiveApply {
     
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw;
     fn walk(&mut self, _cx:&mut Cx) -> Walk {Walk::default()}
+    fn area(&self) -> Area {Area::Empty}
     fn redraw(&mut self, _cx: &mut Cx);
     
     fn is_visible(&self) -> bool {
@@ -201,7 +206,8 @@ impl Clone for Box<dyn WidgetAction> {
 
 pub struct WidgetRefInner{
     pub widget: Box<dyn Widget >,
-    pub id: LiveId
+    pub id: LiveId,
+    pub origin: LiveNodeOrigin,
 }
 #[derive(Clone, Default)]
 pub struct WidgetRef(Rc<RefCell<Option<WidgetRefInner>>>);
@@ -423,7 +429,8 @@ impl WidgetRef {
     pub fn new_with_inner(widget: Box<dyn Widget>, id:LiveId) -> Self {
         Self (Rc::new(RefCell::new(Some(WidgetRefInner{
             widget,
-            id
+            id,
+            origin: LiveNodeOrigin::empty()
         }))))
     }
     
@@ -558,6 +565,37 @@ impl WidgetRef {
         }
     }
     
+    pub fn widget_tree(&self, cx: &Cx, depth: usize, nodes: &mut Vec<WidgetTreeNode>) {
+        if let Some(inner) = self.0.borrow_mut().as_mut() {
+            let rect = inner.widget.area().get_clipped_rect(cx);
+            let mut node = WidgetTreeNode {
+                depth,
+                id: inner.id.to_string(),
+                x: rect.pos.x,
+                y: rect.pos.y,
+                width: rect.size.x,
+                height: rect.size.y,
+                visible: inner.widget.is_visible(),
+                text: inner.widget.text(),
+                ..Default::default()
+            };
+            {
+                let live_registry = cx.live_registry.borrow();
+                if let Some(info) = live_registry.live_type_infos.get(&inner.widget.type_id()) {
+                    node.type_name = info.type_name.to_string();
+                }
+                if let Some(token_id) = inner.origin.token_id().filter( | token_id | token_id.file_id().is_some()) {
+                    let span = live_registry.token_id_to_span(token_id);
+                    node.file_name = live_registry.file_id_to_file_name(span.file_id).to_string();
+                    node.line = span.start.line;
+                    node.column = span.start.column;
+                }
+            }
+            nodes.push(node);
+            inner.widget.visit_children(&mut | child | child.widget_tree(cx, depth + 1, nodes));
+        }
+    }
+    
     pub fn borrow_mut<T: 'static + Widget>(&self) -> Option<std::cell::RefMut<'_, T >> {
         if let Ok(ret) = std::cell::RefMut::filter_map(self.0.borrow_mut(), | inner | {
             if let Some(inner) = inner.as_mut() {
@@ -608,6 +646,9 @@ impl WidgetRef {
                     log!("TYPECHANGE");
                 }
                 else {
+                    if from.is_from_doc() {
+                        component.origin = nodes[index].origin;
+                    }
                     return component.widget.apply(cx, from, index, nodes);
                 }
             }
@@ -618,7 +659,8 @@ impl WidgetRef {
                     }
                 *inner = Some(WidgetRefInner{
                     widget: component,
-                    id: nodes[index].id
+                    id: nodes[index].id,
+                    origin: nodes[index].origin
                 });
                 if let Some(component) = &mut *inner {
                     return component.widget.apply(cx, from, index, nodes);
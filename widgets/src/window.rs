@@ -176,6 +176,11 @@ impl Window {
                 }
                 true
             }
+            Event::WidgetTree(e) => {
+                let mut nodes = e.response.borrow_mut();
+                self.view.visit_children(&mut | child | child.widget_tree(cx, 0, &mut nodes));
+                return
+            }
             Event::TouchUpdate(ev) => ev.window_id != self.window.window_id(),
             Event::MouseDown(ev) => ev.window_id != self.window.window_id(),
             Event::MouseMove(ev) => ev.window_id != self.window.window_id(),
@@ -307,6 +312,8 @@ impl Widget for Window {
     
     fn walk(&mut self, _cx:&mut Cx) -> Walk {Walk::default()}
     
+    fn area(&self) -> Area {self.view.area()}
+    
     fn redraw(&mut self, cx: &mut Cx) {
         self.view.redraw(cx)
     }
@@ -315,6 +322,10 @@ impl Widget for Window {
         self.view.find_widgets(path, cached, results);
     }
     
+    fn visit_children(&mut self, visit: &mut dyn FnMut(&WidgetRef)) {
+        self.view.visit_children(visit);
+    }
+    
     fn draw_walk_widget(&mut self, cx: &mut Cx2d, walk: Walk) -> WidgetDraw {
         if self.draw_state.begin(cx, DrawState::Drawing) {
             if self.begin(cx).is_not_redrawing() {
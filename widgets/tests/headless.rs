@@ -0,0 +1,180 @@
+// Renders a small widget tree with the headless software renderer and checks the pixels,
+// the same way screenshot tests in CI run an app with --headless.
+
+use {
+    std::{cell::RefCell, rc::Rc},
+    makepad_widgets::*,
+};
+
+live_design!{
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    App = {{App}} {
+        ui: <Window> {
+            pass: {clear_color: #000}
+            body = <View> {
+                flow: Down,
+                padding: 10,
+                spacing: 10,
+                <SolidView> {
+                    width: 40,
+                    height: 20,
+                    draw_bg: {color: #f00}
+                }
+                <Label> {
+                    text: "Hello"
+                    draw_text: {
+                        color: #fff
+                        text_style: {
+                            font: {path: dep("crate://makepad-widgets/resources/IBMPlexSans-Text.ttf")}
+                            font_size: 12
+                        }
+                    }
+                }
+                <RoundedView> {
+                    width: 40,
+                    height: 40,
+                    draw_bg: {color: #0f0, radius: 10.0}
+                }
+            }
+        }
+    }
+    
+    ThemeApp = {{ThemeApp}} {
+        ui: <Window> {
+            pass: {clear_color: #000}
+            body = <SolidView> {
+                draw_bg: {color: (THEME_COLOR_BG_APP)}
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook)]
+pub struct App {
+    #[live] ui: WidgetRef,
+}
+
+impl AppMain for App {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::Draw(event) = event {
+            return self.ui.draw_widget_all(&mut Cx2d::new(cx, event));
+        }
+        self.ui.handle_widget_event(cx, event);
+    }
+}
+
+#[derive(Live, LiveHook)]
+pub struct ThemeApp {
+    #[live] ui: WidgetRef,
+}
+
+impl AppMain for ThemeApp {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        if let Event::Draw(event) = event {
+            return self.ui.draw_widget_all(&mut Cx2d::new(cx, event));
+        }
+        self.ui.handle_widget_event(cx, event);
+    }
+}
+
+// sets up a Cx for the app the way app_main! does and runs its first frames
+fn run<A: LiveNew + AppMain + 'static>(width: f64, height: f64) -> HeadlessRunner {
+    let app = Rc::new(RefCell::new(None));
+    let cx = Rc::new(RefCell::new(Cx::new(Box::new(move | cx, event | {
+        if let Event::Construct = event {
+            *app.borrow_mut() = Some(A::new_main(cx));
+        }
+        if let Event::LiveEdit = event {
+            app.borrow_mut().update_main(cx);
+        }
+        <dyn AppMain>::handle_event(app.borrow_mut().as_mut().unwrap(), cx, event);
+    }))));
+    makepad_widgets::live_design(&mut *cx.borrow_mut());
+    live_design(&mut *cx.borrow_mut());
+    cx.borrow_mut().init_cx_os();
+
+    let mut runner = HeadlessRunner::new(cx, HeadlessOptions {
+        size: Some(dvec2(width, height)),
+        ..Default::default()
+    });
+    runner.run_frames(3);
+    runner
+}
+
+fn render(width: f64, height: f64) -> HeadlessFrame {
+    run::<App>(width, height).main_window_frame().expect("the window was not rendered")
+}
+
+// the smallest rect holding all pixels that match
+fn bounds(frame: &HeadlessFrame, f: impl Fn([u8; 4]) -> bool) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            if f(frame.pixel(x, y)) {
+                bounds = Some(match bounds {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1)),
+                    None => (x, y, x + 1, y + 1)
+                });
+            }
+        }
+    }
+    bounds
+}
+
+#[test]
+fn render_widgets() {
+    let frame = render(100.0, 140.0);
+    assert_eq!((frame.width, frame.height), (100, 140));
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+    // the filled quad covers its rect exactly
+    assert_eq!(bounds(&frame, | pixel | pixel == RED), Some((10, 10, 50, 30)));
+    assert_eq!(frame.pixel(9, 10), BLACK);
+    assert_eq!(frame.pixel(50, 29), BLACK);
+
+    // the rounded rect is 40x40 below the text, with its corners cut off. its edge is
+    // antialiased by the sdf, so the mostly green pixels can stop a pixel short of the rect
+    let (x0, y0, x1, y1) = bounds(&frame, | pixel | pixel[1] > 128 && pixel[0] < 128).expect("the rounded rect was not drawn");
+    assert!(x0.abs_diff(10) <= 1 && x1.abs_diff(50) <= 1, "x range {}..{}", x0, x1);
+    assert!((y1 - y0).abs_diff(40) <= 2, "height {}", y1 - y0);
+    assert_eq!(frame.pixel(x0 + 20, y0 + 20), GREEN);
+    for (x, y) in [(x0, y0), (x1 - 1, y0), (x0, y1 - 1), (x1 - 1, y1 - 1)] {
+        assert_eq!(frame.pixel(x, y), BLACK);
+    }
+
+    // the text sits between them, drawn in light pixels on the black background
+    let text_pixels = (40..y0).flat_map( | y | (10..frame.width).map(move | x | (x, y)))
+        .filter( | &(x, y) | frame.pixel(x, y)[1] > 128)
+        .count();
+    assert!(text_pixels > 20, "only {} text pixels", text_pixels);
+
+    // and the whole frame matches the golden image, set MAKEPAD_UPDATE_GOLDEN=1 to rewrite it
+    // after an intended change. the tolerance covers float differences between compilers
+    let golden_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/render_widgets.png");
+    if std::env::var("MAKEPAD_UPDATE_GOLDEN").is_ok() {
+        std::fs::write(golden_path, frame.to_png()).unwrap();
+    }
+    let golden = HeadlessFrame::from_png(&std::fs::read(golden_path).unwrap()).unwrap();
+    let diff = frame.diff(&golden, 2);
+    assert!(diff.is_match(), "frame differs from {}: {:?}", golden_path, diff);
+}
+
+#[test]
+fn switch_theme() {
+    let mut runner = run::<ThemeApp>(20.0, 20.0);
+    let color = | runner: &mut HeadlessRunner, theme: Option<Theme> | {
+        if let Some(theme) = theme {
+            set_theme(&mut runner.cx.borrow_mut(), theme);
+            runner.run_frames(2);
+        }
+        runner.main_window_frame().unwrap().pixel(10, 10)
+    };
+    // THEME_COLOR_BG_APP is THEME_BRIGHTNESS, #40 in the dark tokens and #E4 in the light ones
+    assert_eq!(color(&mut runner, None), [0x40, 0x40, 0x40, 255]);
+    assert_eq!(color(&mut runner, Some(Theme::DesktopLight)), [0xe4, 0xe4, 0xe4, 255]);
+    assert_eq!(color(&mut runner, Some(Theme::DesktopDark)), [0x40, 0x40, 0x40, 255]);
+}